@@ -0,0 +1,56 @@
+use bevy::app::{ScheduleRunnerPlugin, TerminalCtrlCHandlerPlugin};
+use bevy::log::LogPlugin;
+use bevy::prelude::*;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use voxelcraft::net::{NetServer, ServerNetPlugin};
+use voxelcraft::server::ServerConfig;
+use voxelcraft::world::{WorldDirectory, TICKS_PER_SECOND};
+use voxelcraft::SimulationPlugin;
+
+/// Config file read when no path is given on the command line
+const DEFAULT_CONFIG_PATH: &str = "server.toml";
+
+fn main() {
+    let config_path = std::env::args().nth(1).map_or_else(|| PathBuf::from(DEFAULT_CONFIG_PATH), PathBuf::from);
+    let (settings, import, config) = match ServerConfig::load_or_create(&config_path)
+        .and_then(|config| Ok((config.open_world()?, config.anvil_import()?, config)))
+    {
+        Ok(loaded) => loaded,
+        Err(error) => {
+            eprintln!("{}", error);
+            std::process::exit(1);
+        }
+    };
+    let server = match NetServer::bind(config.port) {
+        Ok(server) => server.with_operators(config.operators.iter().cloned()),
+        Err(error) => {
+            eprintln!("Couldn't listen on port {}: {}", config.port, error);
+            std::process::exit(1);
+        }
+    };
+
+    let mut app = App::new();
+    if let Some(import) = import {
+        app.insert_resource(import);
+    }
+    app
+        // No window or GPU: the main loop just runs at the world tick rate
+        .add_plugins(MinimalPlugins.set(ScheduleRunnerPlugin::run_loop(Duration::from_secs_f64(1.0 / TICKS_PER_SECOND))))
+        .add_plugins(LogPlugin::default())
+        // Ctrl+C exits through `AppExit`, so the world is saved on the way out
+        .add_plugins(TerminalCtrlCHandlerPlugin)
+        .insert_resource(settings)
+        .insert_resource(WorldDirectory(config.world_path.clone()))
+        .add_plugins(SimulationPlugin)
+        .insert_resource(server)
+        .add_plugins(ServerNetPlugin)
+        .add_systems(Startup, move || {
+            info!(
+                "Server started: world {} (seed {}), view distance {}, port {}",
+                config.world_path.display(), settings.seed, settings.view_distance, config.port,
+            );
+        })
+        .run();
+}
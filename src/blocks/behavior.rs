@@ -0,0 +1,53 @@
+use bevy::prelude::IVec3;
+use super::registry::BlockState;
+use crate::world::BlockWorld;
+
+/// Hooks that give a block type simulation behavior
+/// Implemented by mods and attached to a block with `BlockTypeBuilder::behavior`
+/// All methods default to doing nothing, so behaviors only implement what they need
+pub trait BlockBehavior: Send + Sync + std::fmt::Debug {
+    /// Called when this block is picked by the random tick system
+    /// Only blocks registered with `random_ticks(true)` are ever picked
+    fn random_tick(&self, _world: &mut BlockWorld, _pos: IVec3) {}
+
+    /// Called when a tick scheduled with `BlockWorld::schedule_tick` comes due
+    fn scheduled_tick(&self, _world: &mut BlockWorld, _pos: IVec3) {}
+
+    /// Called after this block is placed at `pos`, replacing `old_state`
+    fn on_placed(&self, _world: &mut BlockWorld, _pos: IVec3, _old_state: BlockState) {}
+
+    /// Called after this block (`old_state`) is removed or replaced by a different block
+    fn on_broken(&self, _world: &mut BlockWorld, _pos: IVec3, _old_state: BlockState) {}
+
+    /// Called when the block at `neighbor_pos` (one of the 6 adjacent blocks) changes
+    fn on_neighbor_changed(&self, _world: &mut BlockWorld, _pos: IVec3, _neighbor_pos: IVec3) {}
+
+    /// Called when the player uses (right-clicks) this block
+    /// Return true if the block handled it, so nothing gets placed against it
+    fn on_use(&self, _world: &mut BlockWorld, _pos: IVec3, _state: BlockState) -> bool {
+        false
+    }
+
+    /// Called when an entity moves into this block's cell (e.g., pressure plates)
+    fn on_entity_enter(&self, _world: &mut BlockWorld, _pos: IVec3) {}
+
+    /// Called when the last entity leaves this block's cell
+    fn on_entity_leave(&self, _world: &mut BlockWorld, _pos: IVec3) {}
+
+    /// Signal strength (0-15) this block sends into its neighbor in `direction`
+    fn power_output(&self, _state: BlockState, _direction: IVec3) -> u8 {
+        0
+    }
+
+    /// Adjust the state of this block as it is placed against a face with outward normal `face_normal`
+    /// (e.g., to store which way it faces)
+    fn placement_state(&self, state: BlockState, _face_normal: IVec3) -> BlockState {
+        state
+    }
+
+    /// Adjust the state of this block as the structure it's in is rotated or mirrored, with
+    /// `transform` taking each direction to where it points afterwards (e.g., to turn which way it faces)
+    fn transform_state(&self, state: BlockState, _transform: &dyn Fn(IVec3) -> IVec3) -> BlockState {
+        state
+    }
+}
@@ -1,3 +1,7 @@
+use std::sync::Arc;
+use super::behavior::BlockBehavior;
+use crate::items::{ToolKind, ToolTier};
+
 /// Represents which face of a block
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum BlockFace {
@@ -28,6 +32,29 @@ pub struct BlockProperties {
     /// Per-face tint colors for biome-specific coloring (e.g., grass, leaves)
     /// None = no tint (white), Some = tint color
     pub tint_colors: FaceTints,
+    /// Whether the random tick system should pick this block (e.g., grass spreading)
+    pub random_ticks: bool,
+    /// Whether the block falls when unsupported (e.g., sand, gravel)
+    pub affected_by_gravity: bool,
+    /// Height of the collision box for solid blocks (0.5 for half blocks)
+    pub collision_height: f32,
+    /// Whether the block is a liquid the player can drown in (e.g., water)
+    pub is_liquid: bool,
+    /// How long the block takes to mine (stone is 1.5), negative for unbreakable blocks
+    pub hardness: f32,
+    /// Tool kind that mines this block faster
+    pub preferred_tool: Option<ToolKind>,
+    /// Lowest tier of the preferred tool that makes the block drop anything
+    /// None means it drops even when broken by hand
+    pub min_tool_tier: Option<ToolTier>,
+    /// Item or block string ID dropped instead of the block itself (e.g., ore dropping a gem)
+    pub drops: Option<String>,
+    /// World ticks the block burns for as furnace fuel, None if it doesn't burn
+    pub fuel_ticks: Option<u32>,
+    /// Whether the block is a door (mobs that can open doors path through it when closed)
+    pub is_door: bool,
+    /// Simulation hooks (random/scheduled ticks), None for inert blocks
+    pub behavior: Option<Arc<dyn BlockBehavior>>,
 }
 
 /// Per-face tint colors
@@ -209,6 +236,17 @@ impl BlockType {
             light_emission: 0,
             textures: None,
             tint_colors: FaceTints::none(),
+            random_ticks: false,
+            affected_by_gravity: false,
+            collision_height: 1.0,
+            is_liquid: false,
+            hardness: 1.0,
+            preferred_tool: None,
+            min_tool_tier: None,
+            drops: None,
+            fuel_ticks: None,
+            is_door: false,
+            behavior: None,
         }
     }
 }
@@ -221,6 +259,17 @@ pub struct BlockTypeBuilder {
     light_emission: u8,
     textures: Option<BlockTextures>,
     tint_colors: FaceTints,
+    random_ticks: bool,
+    affected_by_gravity: bool,
+    collision_height: f32,
+    is_liquid: bool,
+    hardness: f32,
+    preferred_tool: Option<ToolKind>,
+    min_tool_tier: Option<ToolTier>,
+    drops: Option<String>,
+    fuel_ticks: Option<u32>,
+    is_door: bool,
+    behavior: Option<Arc<dyn BlockBehavior>>,
 }
 
 #[allow(dead_code)]
@@ -250,6 +299,72 @@ impl BlockTypeBuilder {
         self
     }
 
+    pub fn random_ticks(mut self, random_ticks: bool) -> Self {
+        self.random_ticks = random_ticks;
+        self
+    }
+
+    pub fn gravity(mut self, affected_by_gravity: bool) -> Self {
+        self.affected_by_gravity = affected_by_gravity;
+        self
+    }
+
+    pub fn collision_height(mut self, height: f32) -> Self {
+        self.collision_height = height.clamp(0.0, 1.0);
+        self
+    }
+
+    pub fn liquid(mut self, is_liquid: bool) -> Self {
+        self.is_liquid = is_liquid;
+        self
+    }
+
+    pub fn hardness(mut self, hardness: f32) -> Self {
+        self.hardness = hardness;
+        self
+    }
+
+    /// Can't be mined outside creative mode (e.g., bedrock)
+    pub fn unbreakable(mut self) -> Self {
+        self.hardness = -1.0;
+        self
+    }
+
+    /// Tool kind that mines this block faster, any tool or a bare hand still drops it
+    pub fn tool(mut self, kind: ToolKind) -> Self {
+        self.preferred_tool = Some(kind);
+        self
+    }
+
+    /// Only drops when mined with this tool kind of at least this tier
+    pub fn requires_tool(mut self, kind: ToolKind, min_tier: ToolTier) -> Self {
+        self.preferred_tool = Some(kind);
+        self.min_tool_tier = Some(min_tier);
+        self
+    }
+
+    pub fn drops(mut self, item: &str) -> Self {
+        self.drops = Some(item.to_string());
+        self
+    }
+
+    /// Burns in a furnace for this many world ticks
+    pub fn fuel(mut self, ticks: u32) -> Self {
+        self.fuel_ticks = Some(ticks);
+        self
+    }
+
+    /// Mark the block as a door for pathfinding
+    pub fn door(mut self) -> Self {
+        self.is_door = true;
+        self
+    }
+
+    pub fn behavior(mut self, behavior: impl BlockBehavior + 'static) -> Self {
+        self.behavior = Some(Arc::new(behavior));
+        self
+    }
+
     pub fn build(self) -> BlockType {
         BlockType {
             properties: BlockProperties {
@@ -260,6 +375,17 @@ impl BlockTypeBuilder {
                 light_emission: self.light_emission,
                 textures: self.textures.unwrap_or_else(|| BlockTextures::uniform(AtlasCoord::new(0, 0))),
                 tint_colors: self.tint_colors,
+                random_ticks: self.random_ticks,
+                affected_by_gravity: self.affected_by_gravity,
+                collision_height: self.collision_height,
+                is_liquid: self.is_liquid,
+                hardness: self.hardness,
+                preferred_tool: self.preferred_tool,
+                min_tool_tier: self.min_tool_tier,
+                drops: self.drops,
+                fuel_ticks: self.fuel_ticks,
+                is_door: self.is_door,
+                behavior: self.behavior,
             },
         }
     }
@@ -1,5 +1,7 @@
 mod registry;
 mod block_type;
+mod behavior;
 
-pub use registry::{BlockRegistry, BlockId};
+pub use registry::{BlockRegistry, BlockId, BlockState};
 pub use block_type::{BlockType, BlockFace, BlockTextures, AtlasCoord, FaceTints};
+pub use behavior::BlockBehavior;
@@ -15,6 +15,39 @@ impl BlockId {
     }
 }
 
+/// A block together with its 4-bit data value
+/// The data value stores per-block state such as power level, orientation or on/off
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BlockState {
+    pub id: BlockId,
+    pub data: u8,
+}
+
+impl BlockState {
+    pub const AIR: BlockState = BlockState { id: BlockId::AIR, data: 0 };
+
+    /// Maximum data value (data is a nibble)
+    pub const MAX_DATA: u8 = 15;
+
+    pub fn new(id: BlockId) -> Self {
+        Self { id, data: 0 }
+    }
+
+    pub fn with_data(id: BlockId, data: u8) -> Self {
+        Self { id, data: data.min(Self::MAX_DATA) }
+    }
+
+    pub fn is_air(self) -> bool {
+        self.id.is_air()
+    }
+}
+
+impl From<BlockId> for BlockState {
+    fn from(id: BlockId) -> Self {
+        Self::new(id)
+    }
+}
+
 /// Global registry for all block types
 /// This is a Bevy resource that mods can access to register new blocks
 #[derive(Resource, Clone)]
@@ -85,6 +118,12 @@ impl BlockRegistry {
         self.blocks.get(id.0 as usize)?.as_ref()
     }
 
+    /// Whether a block stops light: anything but air, transparent blocks and liquids
+    /// Unregistered IDs count as opaque
+    pub fn is_opaque(&self, id: BlockId) -> bool {
+        !id.is_air() && self.get_block(id).is_none_or(|block| !block.properties.is_transparent && !block.properties.is_liquid)
+    }
+
     /// Get a block type by its string ID
     pub fn get_block_by_string_id(&self, id: &str) -> Option<&BlockType> {
         let block_id = self.id_map.get(id)?;
@@ -0,0 +1,251 @@
+use bevy::math::{DVec3, I64Vec3};
+use bevy::prelude::*;
+use crate::blocks::{BlockRegistry, BlockState};
+use crate::items::{resolve_item, ItemRegistry};
+use crate::resources::HotbarItem;
+use crate::world::WORLD_LIMIT;
+use super::dispatcher::CommandError;
+
+/// What an argument node reads from the command line
+#[derive(Debug, Clone, PartialEq)]
+pub enum ArgumentType {
+    /// A whole number within `min..=max`
+    Integer { min: i32, max: i32 },
+    /// One word without spaces
+    Word,
+    /// Everything left on the line
+    GreedyString,
+    /// Three whole-number coordinates, each absolute or `~`-relative to the block the player is in
+    BlockPos,
+    /// Three coordinates, each absolute or `~`-relative to the player's feet
+    /// Whole absolute x and z land on the middle of the block
+    Position,
+    /// A block's string ID ("core:stone", or just "stone" for a core block),
+    /// optionally with a data value in brackets ("core:wire[15]")
+    Block,
+    /// An item's or block's string ID ("core:iron_pickaxe", or just "iron_pickaxe")
+    Item,
+}
+
+/// An argument as read from the command line
+#[derive(Debug, Clone, PartialEq)]
+pub enum ArgumentValue {
+    Integer(i32),
+    String(String),
+    Coordinates(Coordinates),
+    Block(BlockState),
+    Item(HotbarItem),
+}
+
+/// One coordinate as typed: a number, or `~` with an optional offset
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Coordinate {
+    /// A whole-number block coordinate, kept exact
+    Block(i32),
+    /// A position along the axis
+    Absolute(f64),
+    /// `~`: this far from where the command was run
+    Relative(f64),
+}
+
+impl Default for Coordinate {
+    fn default() -> Self {
+        Self::Relative(0.0)
+    }
+}
+
+impl Coordinate {
+    fn resolve(self, origin: f32) -> f64 {
+        match self {
+            Self::Block(value) => value as f64,
+            Self::Absolute(value) => value,
+            Self::Relative(offset) => origin as f64 + offset,
+        }
+    }
+
+    /// Only relative coordinates go through floating point, from the block `origin` is in
+    fn resolve_block(self, origin: f32) -> i64 {
+        match self {
+            Self::Block(value) => value as i64,
+            Self::Absolute(value) => value.floor() as i64,
+            Self::Relative(offset) => (origin.floor() as f64 + offset).floor() as i64,
+        }
+    }
+}
+
+/// Three coordinates as typed, resolved against where the command was run from
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Coordinates {
+    pub x: Coordinate,
+    pub y: Coordinate,
+    pub z: Coordinate,
+}
+
+impl Coordinates {
+    /// The position, with `~` taken from `origin`, if it's within `WORLD_LIMIT` of the origin
+    pub fn resolve(&self, origin: Vec3) -> Option<Vec3> {
+        let pos = DVec3::new(self.x.resolve(origin.x), self.y.resolve(origin.y), self.z.resolve(origin.z));
+        (pos.abs().max_element() <= WORLD_LIMIT as f64).then(|| pos.as_vec3())
+    }
+
+    /// The block position, with `~` taken from the block `origin` is in, if it's within
+    /// `WORLD_LIMIT` of the origin
+    pub fn resolve_block(&self, origin: Vec3) -> Option<IVec3> {
+        let pos = I64Vec3::new(self.x.resolve_block(origin.x), self.y.resolve_block(origin.y), self.z.resolve_block(origin.z));
+        (pos.abs().max_element() <= WORLD_LIMIT as i64).then(|| pos.as_ivec3())
+    }
+}
+
+impl ArgumentType {
+    /// Any whole number
+    pub fn integer() -> Self {
+        Self::Integer { min: i32::MIN, max: i32::MAX }
+    }
+
+    /// A whole number within `min..=max`
+    pub fn integer_between(min: i32, max: i32) -> Self {
+        Self::Integer { min, max }
+    }
+
+    /// Read this argument from `input` at `start`, returning it and where it ends
+    pub(crate) fn parse(&self, input: &str, start: usize, world: &World) -> Result<(ArgumentValue, usize), CommandError> {
+        let end = word_end(input, start);
+        let word = &input[start..end];
+        let error = |message: String| CommandError::syntax(message, input, start);
+        match *self {
+            Self::Integer { min, max } => {
+                let value: i32 = word.parse().map_err(|_| error(format!("Expected a whole number, found '{}'", word)))?;
+                if value < min {
+                    return Err(error(format!("The number must not be less than {}, found {}", min, value)));
+                }
+                if value > max {
+                    return Err(error(format!("The number must not be more than {}, found {}", max, value)));
+                }
+                Ok((ArgumentValue::Integer(value), end))
+            }
+            Self::Word if word.is_empty() => Err(error("Expected a word".to_string())),
+            Self::Word => Ok((ArgumentValue::String(word.to_string()), end)),
+            Self::GreedyString if start >= input.len() => Err(error("Expected text".to_string())),
+            Self::GreedyString => Ok((ArgumentValue::String(input[start..].to_string()), input.len())),
+            Self::BlockPos | Self::Position => {
+                let whole = *self == Self::BlockPos;
+                let mut axes = [Coordinate::default(); 3];
+                let mut cursor = start;
+                for (index, axis) in axes.iter_mut().enumerate() {
+                    if index > 0 {
+                        if !input[cursor..].starts_with(' ') {
+                            return Err(CommandError::syntax("Expected three coordinates".to_string(), input, cursor));
+                        }
+                        cursor += 1;
+                    }
+                    let end = word_end(input, cursor);
+                    // Whole absolute x and z are block corners; a position means the middle of that block
+                    let center = !whole && index != 1;
+                    *axis = parse_coordinate(&input[cursor..end], whole, center)
+                        .map_err(|message| CommandError::syntax(message, input, cursor))?;
+                    cursor = end;
+                }
+                let [x, y, z] = axes;
+                Ok((ArgumentValue::Coordinates(Coordinates { x, y, z }), cursor))
+            }
+            Self::Block => {
+                let state = parse_block(word, world.resource::<BlockRegistry>()).map_err(error)?;
+                Ok((ArgumentValue::Block(state), end))
+            }
+            Self::Item => {
+                let item = resolve_item(&full_id(word), world.resource::<ItemRegistry>(), world.resource::<BlockRegistry>())
+                    .map_err(|_| error(format!("Unknown item '{}'", word)))?;
+                Ok((ArgumentValue::Item(item), end))
+            }
+        }
+    }
+
+    /// Ways to finish `partial`, the text typed for this argument so far
+    pub(crate) fn suggest(&self, partial: &str, world: &World) -> Vec<String> {
+        match self {
+            Self::BlockPos | Self::Position => {
+                let whole = *self == Self::BlockPos;
+                let mut parts: Vec<&str> = partial.split(' ').collect();
+                if parts.len() > 3 || parts.iter().any(|part| !part.is_empty() && parse_coordinate(part, whole, false).is_err()) {
+                    return Vec::new();
+                }
+                if parts.last() == Some(&"") {
+                    parts.pop();
+                }
+                parts.resize(3, "~");
+                vec![parts.join(" ")]
+            }
+            Self::Block | Self::Item if !partial.contains(' ') => {
+                let blocks = world.resource::<BlockRegistry>();
+                let mut ids: Vec<&str> = blocks.get_all_ids().into_iter()
+                    .filter(|&id| !id.is_air())
+                    .filter_map(|id| blocks.get_string_id(id))
+                    .collect();
+                if *self == Self::Item {
+                    ids.extend(world.resource::<ItemRegistry>().string_ids());
+                }
+                let mut ids: Vec<String> = ids.into_iter()
+                    .filter(|id| id.starts_with(partial) || id.split_once(':').is_some_and(|(_, name)| name.starts_with(partial)))
+                    .map(str::to_string)
+                    .collect();
+                ids.sort();
+                ids
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// How the argument is shown in usage lines
+    pub(crate) fn usage(&self, name: &str) -> String {
+        match self {
+            Self::BlockPos | Self::Position => format!("<{}: x y z>", name),
+            _ => format!("<{}>", name),
+        }
+    }
+}
+
+/// Where the word starting at `start` ends
+pub(crate) fn word_end(input: &str, start: usize) -> usize {
+    input[start..].find(' ').map_or(input.len(), |offset| start + offset)
+}
+
+fn parse_coordinate(word: &str, whole: bool, center: bool) -> Result<Coordinate, String> {
+    let (relative, number) = match word.strip_prefix('~') {
+        Some(offset) => (true, offset),
+        None => (false, word),
+    };
+    if relative && number.is_empty() {
+        return Ok(Coordinate::Relative(0.0));
+    }
+    if whole {
+        let value: i32 = number.parse().map_err(|_| format!("Expected a whole-number coordinate, found '{}'", word))?;
+        return Ok(if relative { Coordinate::Relative(value as f64) } else { Coordinate::Block(value) });
+    }
+    let value: f64 = number.parse().map_err(|_| format!("Expected a coordinate, found '{}'", word))?;
+    if !value.is_finite() {
+        return Err(format!("Expected a coordinate, found '{}'", word));
+    }
+    Ok(match relative {
+        true => Coordinate::Relative(value),
+        false if center && !number.contains('.') => Coordinate::Absolute(value + 0.5),
+        false => Coordinate::Absolute(value),
+    })
+}
+
+fn parse_block(word: &str, blocks: &BlockRegistry) -> Result<BlockState, String> {
+    let (id, data) = match word.strip_suffix(']').and_then(|rest| rest.split_once('[')) {
+        Some((id, data)) => {
+            let data: u8 = data.parse().ok().filter(|&data| data <= BlockState::MAX_DATA)
+                .ok_or_else(|| format!("Block data must be 0 to {}, found '{}'", BlockState::MAX_DATA, data))?;
+            (id, data)
+        }
+        None => (word, 0),
+    };
+    let block = blocks.get_id(&full_id(id)).ok_or_else(|| format!("Unknown block '{}'", id))?;
+    Ok(BlockState::with_data(block, data))
+}
+
+/// A string ID with the core namespace filled in if it has none
+fn full_id(id: &str) -> String {
+    if id.contains(':') { id.to_string() } else { format!("core:{}", id) }
+}
@@ -0,0 +1,35 @@
+use super::arguments::ArgumentType;
+use super::dispatcher::{CommandDispatcher, CommandError, CommandNode};
+use super::player::register_player_commands;
+use super::world::register_world_commands;
+use super::history::register_history_commands;
+use super::selection::register_selection_commands;
+
+/// Register the commands every game has, before any mod's
+pub fn register_builtin_commands(dispatcher: &mut CommandDispatcher) {
+    let help = CommandNode::literal("help")
+        .describe("Lists the commands, or shows how to use one")
+        .executes(|context| {
+            let dispatcher = context.world.resource::<CommandDispatcher>();
+            let lines: Vec<String> = dispatcher.commands().iter()
+                .filter(|command| command.allows(&context.source))
+                .map(|command| match command.description() {
+                    "" => format!("/{}", command.name()),
+                    description => format!("/{} - {}", command.name(), description),
+                })
+                .collect();
+            Ok(lines.join("\n"))
+        })
+        .then(CommandNode::argument("command", ArgumentType::Word).executes(|context| {
+            let name = context.string("command").trim_start_matches('/');
+            let dispatcher = context.world.resource::<CommandDispatcher>();
+            let command = dispatcher.get(name).ok_or_else(|| CommandError::Failed(format!("Unknown command '{}'", name)))?;
+            let lines: Vec<String> = command.usage().into_iter().map(|usage| format!("/{}", usage)).collect();
+            Ok(lines.join("\n"))
+        }));
+    dispatcher.register(help).expect("built-in commands are registered first");
+    register_player_commands(dispatcher);
+    register_world_commands(dispatcher);
+    register_history_commands(dispatcher);
+    register_selection_commands(dispatcher);
+}
@@ -0,0 +1,494 @@
+use bevy::prelude::*;
+use bevy::ecs::system::SystemState;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+use super::arguments::{word_end, ArgumentType, ArgumentValue, Coordinates};
+use crate::blocks::BlockState;
+use crate::resources::HotbarItem;
+use crate::systems::WorldBlocks;
+use crate::world::BlockWorld;
+
+/// What a command says back: a message for the player (may be empty) or why it failed
+pub type CommandResult = Result<String, CommandError>;
+
+type Executor = Arc<dyn Fn(&mut CommandContext) -> CommandResult + Send + Sync>;
+
+/// Characters of the input shown before the point a syntax error is reported at
+const ERROR_CONTEXT: usize = 10;
+
+/// Why a command didn't run
+#[derive(Debug, Clone, PartialEq)]
+pub enum CommandError {
+    /// The input doesn't fit the command tree; `cursor` is where in `input` it went wrong
+    Syntax { message: String, input: String, cursor: usize },
+    /// The command was understood but couldn't be carried out
+    Failed(String),
+}
+
+impl CommandError {
+    pub fn syntax(message: String, input: &str, cursor: usize) -> Self {
+        Self::Syntax { message, input: input.to_string(), cursor }
+    }
+
+    fn cursor(&self) -> usize {
+        match self {
+            Self::Syntax { cursor, .. } => *cursor,
+            Self::Failed(_) => 0,
+        }
+    }
+}
+
+impl fmt::Display for CommandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Syntax { message, input, cursor } => {
+                let before: String = input[..*cursor].chars().rev().take(ERROR_CONTEXT).collect::<Vec<_>>().into_iter().rev().collect();
+                let ellipsis = if before.len() < *cursor { "..." } else { "" };
+                write!(f, "{}: {}{}<--[HERE]", message, ellipsis, before)
+            }
+            Self::Failed(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+/// How far a command source is trusted: commands that change the world or other players
+/// need `Operator`, which local players have and a server's clients only when opped
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum PermissionLevel {
+    #[default]
+    Player,
+    Operator,
+}
+
+pub(super) fn outside_world() -> CommandError {
+    CommandError::Failed("That position is outside the world".to_string())
+}
+
+/// Who ran a command and from where
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CommandSource {
+    pub player: Entity,
+    /// The player's feet, what `~` coordinates are relative to
+    pub position: Vec3,
+    pub permission: PermissionLevel,
+}
+
+/// What an executing command gets: the world, who ran it and its arguments by name
+pub struct CommandContext<'w> {
+    pub world: &'w mut World,
+    pub source: CommandSource,
+    arguments: HashMap<String, ArgumentValue>,
+}
+
+impl CommandContext<'_> {
+    /// Whether the branch that ran has an argument called `name`
+    pub fn has(&self, name: &str) -> bool {
+        self.arguments.contains_key(name)
+    }
+
+    fn argument(&self, name: &str) -> &ArgumentValue {
+        self.arguments.get(name).unwrap_or_else(|| panic!("No argument '{}' on this branch of the command", name))
+    }
+
+    /// An `Integer` argument
+    /// Like the other getters, panics if the branch that ran has no such argument
+    pub fn integer(&self, name: &str) -> i32 {
+        match self.argument(name) {
+            ArgumentValue::Integer(value) => *value,
+            other => panic!("Argument '{}' is not an integer: {:?}", name, other),
+        }
+    }
+
+    /// A `Word` or `GreedyString` argument
+    pub fn string(&self, name: &str) -> &str {
+        match self.argument(name) {
+            ArgumentValue::String(value) => value,
+            other => panic!("Argument '{}' is not a string: {:?}", name, other),
+        }
+    }
+
+    fn coordinates(&self, name: &str) -> Coordinates {
+        match self.argument(name) {
+            ArgumentValue::Coordinates(coordinates) => *coordinates,
+            other => panic!("Argument '{}' is not coordinates: {:?}", name, other),
+        }
+    }
+
+    /// A `BlockPos` argument, resolved against the source; positions past `WORLD_LIMIT` fail
+    pub fn block_pos(&self, name: &str) -> Result<IVec3, CommandError> {
+        self.coordinates(name).resolve_block(self.source.position).ok_or_else(outside_world)
+    }
+
+    /// A `Position` argument, resolved against the source; positions past `WORLD_LIMIT` fail
+    pub fn position(&self, name: &str) -> Result<Vec3, CommandError> {
+        self.coordinates(name).resolve(self.source.position).ok_or_else(outside_world)
+    }
+
+    /// A `Block` argument
+    pub fn block(&self, name: &str) -> BlockState {
+        match self.argument(name) {
+            ArgumentValue::Block(state) => *state,
+            other => panic!("Argument '{}' is not a block: {:?}", name, other),
+        }
+    }
+
+    /// An `Item` argument
+    pub fn item(&self, name: &str) -> HotbarItem {
+        match self.argument(name) {
+            ArgumentValue::Item(item) => *item,
+            other => panic!("Argument '{}' is not an item: {:?}", name, other),
+        }
+    }
+
+    /// Change blocks through `WorldBlocks::edit_as`, so the edit gets block callbacks, neighbor
+    /// updates and remesh marking like any other, all in one batch the player can undo
+    pub fn edit_blocks<R>(&mut self, f: impl FnOnce(&mut BlockWorld) -> R) -> R {
+        let mut state = SystemState::<WorldBlocks>::new(self.world);
+        let result = state.get_mut(self.world).edit_as(self.source.player, f);
+        state.apply(self.world);
+        result
+    }
+}
+
+/// A node of the command tree: a literal word or a typed argument, the nodes that may follow
+/// it, and what runs when the input ends here
+/// Built up like `CommandNode::literal("time").then(CommandNode::literal("set").then(...))`
+#[derive(Clone)]
+pub struct CommandNode {
+    name: String,
+    /// `None` for a literal, which must be typed as `name`
+    argument: Option<ArgumentType>,
+    children: Vec<CommandNode>,
+    executor: Option<Executor>,
+    description: String,
+    permission: PermissionLevel,
+}
+
+impl CommandNode {
+    /// A word that must be typed exactly
+    pub fn literal(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            argument: None,
+            children: Vec::new(),
+            executor: None,
+            description: String::new(),
+            permission: PermissionLevel::Player,
+        }
+    }
+
+    /// A value read by `argument_type`, available to executors as `name`
+    pub fn argument(name: &str, argument_type: ArgumentType) -> Self {
+        Self { argument: Some(argument_type), ..Self::literal(name) }
+    }
+
+    /// Allow `child` to follow this node
+    pub fn then(mut self, child: CommandNode) -> Self {
+        self.children.push(child);
+        self
+    }
+
+    /// Run `executor` when the input ends at this node
+    pub fn executes(mut self, executor: impl Fn(&mut CommandContext) -> CommandResult + Send + Sync + 'static) -> Self {
+        self.executor = Some(Arc::new(executor));
+        self
+    }
+
+    /// What the command does, shown by `/help` (only used on a command's root)
+    pub fn describe(mut self, description: &str) -> Self {
+        self.description = description.to_string();
+        self
+    }
+
+    /// Only let sources with at least `permission` run the command (only used on a command's
+    /// root; commands are open to everyone unless they say otherwise)
+    pub fn requires(mut self, permission: PermissionLevel) -> Self {
+        self.permission = permission;
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Whether `source` may run the command
+    pub fn allows(&self, source: &CommandSource) -> bool {
+        source.permission >= self.permission
+    }
+
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+
+    /// Every complete way to type the command from this node, e.g. "time set <ticks>"
+    pub fn usage(&self) -> Vec<String> {
+        let word = match &self.argument {
+            Some(argument) => argument.usage(&self.name),
+            None => self.name.clone(),
+        };
+        let mut lines = Vec::new();
+        if self.executor.is_some() {
+            lines.push(word.clone());
+        }
+        for child in &self.children {
+            lines.extend(child.usage().into_iter().map(|rest| format!("{} {}", word, rest)));
+        }
+        lines
+    }
+
+    /// Read this node at `start`, returning where it ends and the argument value if it has one
+    fn parse(&self, input: &str, start: usize, world: &World) -> Result<(usize, Option<ArgumentValue>), CommandError> {
+        match &self.argument {
+            Some(argument) => argument.parse(input, start, world).map(|(value, end)| (end, Some(value))),
+            None => {
+                let end = word_end(input, start);
+                if input[start..end] == self.name {
+                    Ok((end, None))
+                } else {
+                    Err(CommandError::syntax(format!("Expected '{}'", self.name), input, start))
+                }
+            }
+        }
+    }
+
+    /// Ways to finish `partial`, the text typed for this node so far
+    fn suggest(&self, partial: &str, world: &World) -> Vec<String> {
+        match &self.argument {
+            Some(argument) => argument.suggest(partial, world),
+            None if self.name.starts_with(partial) => vec![self.name.clone()],
+            None => Vec::new(),
+        }
+    }
+}
+
+/// A way to finish the input: replace everything from `start` with `text`
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Suggestion {
+    pub start: usize,
+    pub text: String,
+}
+
+/// Every command, by the literal it starts with
+/// Filled at startup with the built-in commands and each mod's `GameMod::register_commands`
+#[derive(Resource, Default, Clone)]
+pub struct CommandDispatcher {
+    commands: Vec<CommandNode>,
+}
+
+/// A command line matched against the tree, ready to run
+struct ParsedCommand {
+    executor: Executor,
+    arguments: HashMap<String, ArgumentValue>,
+}
+
+impl CommandDispatcher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a command; its root must be a literal no other command starts with
+    pub fn register(&mut self, command: CommandNode) -> Result<(), String> {
+        if command.argument.is_some() {
+            return Err(format!("Command '{}' must start with a literal", command.name));
+        }
+        if self.get(&command.name).is_some() {
+            return Err(format!("Command '{}' is already registered", command.name));
+        }
+        self.commands.push(command);
+        Ok(())
+    }
+
+    /// The command starting with `name`
+    pub fn get(&self, name: &str) -> Option<&CommandNode> {
+        self.commands.iter().find(|command| command.name == name)
+    }
+
+    /// Every command, in the order registered
+    pub fn commands(&self) -> &[CommandNode] {
+        &self.commands
+    }
+
+    /// Run `input` (without the leading '/') as `source`
+    pub fn execute(world: &mut World, source: CommandSource, input: &str) -> CommandResult {
+        let input = input.trim_end();
+        let dispatcher = world.resource::<CommandDispatcher>();
+        if dispatcher.get(&input[..word_end(input, 0)]).is_some_and(|command| !command.allows(&source)) {
+            return Err(CommandError::Failed("You don't have permission to use this command".to_string()));
+        }
+        let parsed = dispatcher.parse(input, world)?;
+        let mut context = CommandContext { world, source, arguments: parsed.arguments };
+        (parsed.executor)(&mut context)
+    }
+
+    fn parse(&self, input: &str, world: &World) -> Result<ParsedCommand, CommandError> {
+        let name = &input[..word_end(input, 0)];
+        let Some(command) = self.get(name) else {
+            return Err(CommandError::syntax(format!("Unknown command '{}'", name), input, 0));
+        };
+        let mut parser = Parser { input, world, error: None };
+        parser.node(command, 0).ok_or_else(|| parser.error.unwrap())
+    }
+
+    /// Ways to finish the last word (or coordinates) of `input`, best first
+    pub fn complete(&self, input: &str, world: &World) -> Vec<Suggestion> {
+        let mut suggestions = Vec::new();
+        suggest(&self.commands, input, 0, world, &mut suggestions);
+        suggestions.sort();
+        suggestions.dedup();
+        suggestions
+    }
+}
+
+/// Walks the tree depth-first, keeping the error that got furthest into the input
+struct Parser<'a> {
+    input: &'a str,
+    world: &'a World,
+    error: Option<CommandError>,
+}
+
+impl Parser<'_> {
+    fn fail(&mut self, error: CommandError) {
+        if self.error.as_ref().is_none_or(|current| error.cursor() > current.cursor()) {
+            self.error = Some(error);
+        }
+    }
+
+    fn node(&mut self, node: &CommandNode, start: usize) -> Option<ParsedCommand> {
+        let (end, value) = match node.parse(self.input, start, self.world) {
+            Ok(parsed) => parsed,
+            Err(error) => {
+                self.fail(error);
+                return None;
+            }
+        };
+        let mut parsed = if end == self.input.len() {
+            let Some(executor) = node.executor.clone() else {
+                self.fail(CommandError::syntax("Incomplete command".to_string(), self.input, end));
+                return None;
+            };
+            ParsedCommand { executor, arguments: HashMap::new() }
+        } else if node.children.is_empty() {
+            self.fail(CommandError::syntax("Unexpected text after the command".to_string(), self.input, end));
+            return None;
+        } else {
+            // Arguments end at a space, so what follows one is always the next node
+            node.children.iter().find_map(|child| self.node(child, end + 1))?
+        };
+        if let Some(value) = value {
+            parsed.arguments.insert(node.name.clone(), value);
+        }
+        Some(parsed)
+    }
+}
+
+fn suggest(nodes: &[CommandNode], input: &str, start: usize, world: &World, suggestions: &mut Vec<Suggestion>) {
+    for node in nodes {
+        match node.parse(input, start, world) {
+            Ok((end, _)) if end < input.len() => suggest(&node.children, input, end + 1, world, suggestions),
+            _ => suggestions.extend(node.suggest(&input[start..], world).into_iter().map(|text| Suggestion { start, text })),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blocks::BlockRegistry;
+    use crate::mods::{GameMod, VanillaMod};
+
+    #[derive(Resource, Default)]
+    struct Placed(Vec<(IVec3, BlockState)>);
+
+    fn world_with_commands() -> World {
+        let mut world = World::new();
+        let mut blocks = BlockRegistry::new();
+        VanillaMod.register_blocks(&mut blocks);
+        world.insert_resource(blocks);
+        world.init_resource::<Placed>();
+
+        let mut dispatcher = CommandDispatcher::new();
+        let place = CommandNode::literal("place")
+            .then(CommandNode::argument("pos", ArgumentType::BlockPos)
+                .then(CommandNode::argument("block", ArgumentType::Block).executes(|context| {
+                    let placed = (context.block_pos("pos")?, context.block("block"));
+                    context.world.resource_mut::<Placed>().0.push(placed);
+                    Ok(format!("Placed at {}", placed.0))
+                })));
+        let secret = CommandNode::literal("secret").requires(PermissionLevel::Operator).executes(|_| Ok("Opped".to_string()));
+        let count = CommandNode::literal("count")
+            .then(CommandNode::literal("up").then(CommandNode::argument("to", ArgumentType::integer_between(1, 10))
+                .executes(|context| Ok((1..=context.integer("to")).map(|n| n.to_string()).collect::<Vec<_>>().join(" ")))))
+            .then(CommandNode::literal("down").executes(|_| Err(CommandError::Failed("Can't count down".to_string()))));
+        dispatcher.register(place).unwrap();
+        dispatcher.register(count).unwrap();
+        dispatcher.register(secret).unwrap();
+        assert!(dispatcher.register(CommandNode::literal("count")).is_err());
+        world.insert_resource(dispatcher);
+        world
+    }
+
+    fn run_as(world: &mut World, permission: PermissionLevel, input: &str) -> CommandResult {
+        let source = CommandSource { player: Entity::PLACEHOLDER, position: Vec3::new(10.5, 64.0, -3.2), permission };
+        CommandDispatcher::execute(world, source, input)
+    }
+
+    fn run(world: &mut World, input: &str) -> CommandResult {
+        run_as(world, PermissionLevel::Player, input)
+    }
+
+    #[test]
+    fn commands_run_with_their_arguments_resolved() {
+        let mut world = world_with_commands();
+        let stone = BlockState::new(world.resource::<BlockRegistry>().get_id("core:stone").unwrap());
+        let wire = world.resource::<BlockRegistry>().get_id("core:wire").unwrap();
+
+        assert_eq!(run(&mut world, "place ~ ~-1 ~2 stone"), Ok("Placed at [10, 63, -2]".to_string()));
+        run(&mut world, "place 1 2 3 core:wire[7]").unwrap();
+        // Past 2^24, where a float can't tell neighboring blocks apart
+        run(&mut world, "place 16777217 -16777217 29999999 stone").unwrap();
+        assert_eq!(world.resource::<Placed>().0, vec![
+            (IVec3::new(10, 63, -2), stone),
+            (IVec3::new(1, 2, 3), BlockState::with_data(wire, 7)),
+            (IVec3::new(16777217, -16777217, 29999999), stone),
+        ]);
+        assert_eq!(run(&mut world, "count up 3"), Ok("1 2 3".to_string()));
+        assert_eq!(run(&mut world, "count down"), Err(CommandError::Failed("Can't count down".to_string())));
+
+        // Checked before the rest of the line is even parsed
+        let refused = Err(CommandError::Failed("You don't have permission to use this command".to_string()));
+        assert_eq!(run(&mut world, "secret"), refused);
+        assert_eq!(run(&mut world, "secret with junk"), refused);
+        assert_eq!(run_as(&mut world, PermissionLevel::Operator, "secret"), Ok("Opped".to_string()));
+    }
+
+    #[test]
+    fn bad_input_is_reported_where_it_goes_wrong() {
+        let mut world = world_with_commands();
+        let error = |world: &mut World, input: &str| run(world, input).unwrap_err().to_string();
+
+        assert_eq!(error(&mut world, "jump"), "Unknown command 'jump': <--[HERE]");
+        assert_eq!(error(&mut world, "count up 11"), "The number must not be more than 10, found 11: count up <--[HERE]");
+        assert_eq!(error(&mut world, "count sideways"), "Expected 'up': count <--[HERE]");
+        assert_eq!(error(&mut world, "count up"), "Incomplete command: count up<--[HERE]");
+        assert_eq!(error(&mut world, "place ~ ~ ~ core:nothing"), "Unknown block 'core:nothing': ...ace ~ ~ ~ <--[HERE]");
+        assert_eq!(error(&mut world, "place 1.5 2 3 stone"), "Expected a whole-number coordinate, found '1.5': place <--[HERE]");
+        assert_eq!(error(&mut world, "count down now"), "Unexpected text after the command: count down<--[HERE]");
+    }
+
+    #[test]
+    fn the_last_word_completes_from_the_tree() {
+        let world = world_with_commands();
+        let dispatcher = world.resource::<CommandDispatcher>();
+        let texts = |input: &str| -> Vec<String> {
+            dispatcher.complete(input, &world).into_iter().map(|suggestion| input[..suggestion.start].to_string() + &suggestion.text).collect()
+        };
+
+        assert_eq!(texts("c"), ["count"]);
+        assert_eq!(texts("count "), ["count down", "count up"]);
+        assert_eq!(texts("place ~1 "), ["place ~1 ~ ~"]);
+        assert!(texts("place ~ ~ ~ sto").contains(&"place ~ ~ ~ core:stone".to_string()));
+        assert!(texts("count up 5").is_empty());
+        assert_eq!(dispatcher.get("count").unwrap().usage(), ["count up <to>", "count down"]);
+    }
+}
@@ -0,0 +1,149 @@
+use bevy::prelude::*;
+use bevy::ecs::system::SystemState;
+use crate::components::GameMode;
+use crate::systems::WorldBlocks;
+use crate::world::{world_to_chunk_local, BlockDelta, BlockEntityDelta, EditHistory, EditTransaction, SavedChunks, SetBlockFlags};
+use super::dispatcher::{CommandContext, CommandDispatcher, CommandError, CommandNode, CommandResult};
+
+/// `/undo` and `/redo`, which step through the block edits of the player running them
+/// Anyone in creative mode may run them, as Ctrl+Z and Ctrl+Y do; they only ever change
+/// blocks the player changed themselves
+pub(super) fn register_history_commands(dispatcher: &mut CommandDispatcher) {
+    let undo = CommandNode::literal("undo")
+        .describe("Undoes your last block edit (creative mode)")
+        .executes(undo);
+    let redo = CommandNode::literal("redo")
+        .describe("Redoes the block edit you last undid (creative mode)")
+        .executes(redo);
+
+    for command in [undo, redo] {
+        dispatcher.register(command).expect("built-in commands are registered first");
+    }
+}
+
+fn undo(context: &mut CommandContext) -> CommandResult {
+    let transaction = history(context, "Undo")?.take_undo()
+        .ok_or_else(|| CommandError::Failed("Nothing to undo".to_string()))?;
+    let result = apply_transaction(context.world, &transaction.inverse());
+    let changes = transaction.deltas.len();
+    let mut history = history(context, "Undo")?;
+    match result {
+        Ok(()) => {
+            history.undone(transaction);
+            Ok(format!("Undid {} block change(s)", changes))
+        }
+        Err(error @ NotApplied::Loading) => {
+            history.push_undo(transaction);
+            Err(error.into_error("undone"))
+        }
+        // It never will apply, so it's dropped rather than blocking the edits before it
+        Err(error) => Err(error.into_error("undone")),
+    }
+}
+
+fn redo(context: &mut CommandContext) -> CommandResult {
+    let transaction = history(context, "Redo")?.take_redo()
+        .ok_or_else(|| CommandError::Failed("Nothing to redo".to_string()))?;
+    let result = apply_transaction(context.world, &transaction);
+    let changes = transaction.deltas.len();
+    let mut history = history(context, "Redo")?;
+    match result {
+        Ok(()) => {
+            history.push_undo(transaction);
+            Ok(format!("Redid {} block change(s)", changes))
+        }
+        Err(error @ NotApplied::Loading) => {
+            history.undone(transaction);
+            Err(error.into_error("redone"))
+        }
+        Err(error) => Err(error.into_error("redone")),
+    }
+}
+
+/// Why a transaction couldn't be applied
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum NotApplied {
+    /// Some of its blocks are in a chunk that's still loading
+    Loading,
+    /// This many of its blocks were changed since by something else (another player,
+    /// a falling block, a piston), and would be overwritten
+    Changed(usize),
+}
+
+impl NotApplied {
+    fn into_error(self, done: &str) -> CommandError {
+        CommandError::Failed(match self {
+            Self::Loading => "Some of those blocks are still loading".to_string(),
+            Self::Changed(count) => format!("{} of those blocks changed since, so the edit can't be {}", count, done),
+        })
+    }
+}
+
+/// The edit history of a creative player running `/undo` or `/redo`
+fn history<'a>(context: &'a mut CommandContext, action: &str) -> Result<Mut<'a, EditHistory>, CommandError> {
+    let player = context.source.player;
+    if context.world.get::<GameMode>(player) != Some(&GameMode::Creative) {
+        return Err(CommandError::Failed(format!("{} only works in creative mode", action)));
+    }
+    context.world.get_mut::<EditHistory>(player)
+        .ok_or_else(|| CommandError::Failed("You have no edit history".to_string()))
+}
+
+/// Set every block of `transaction` to its new state and block entity, without recording it
+/// as an edit
+/// Loaded blocks change through `WorldBlocks::edit` with their callbacks; blocks of unloaded
+/// chunks are written as they are into `SavedChunks`, to show when the chunk loads again.
+/// Nothing changes if any block is in a chunk that's neither (one still loading), or isn't
+/// in the old state the transaction expects
+fn apply_transaction(world: &mut World, transaction: &EditTransaction) -> Result<(), NotApplied> {
+    let mut state = SystemState::<(WorldBlocks, ResMut<SavedChunks>)>::new(world);
+    let (mut world_blocks, mut saved) = state.get_mut(world);
+
+    let is_loaded = |pos: IVec3| world_blocks.chunk_manager().loaded_chunks.contains_key(&world_to_chunk_local(pos).0);
+    let (loaded, unloaded): (Vec<BlockDelta>, Vec<BlockDelta>) = transaction.deltas.iter()
+        .partition(|delta| is_loaded(delta.pos));
+    let (loaded_entities, unloaded_entities): (Vec<&BlockEntityDelta>, Vec<&BlockEntityDelta>) = transaction.block_entities.iter()
+        .partition(|delta| is_loaded(delta.pos));
+    let mut changed = 0;
+    for delta in &unloaded {
+        let (coord, local) = world_to_chunk_local(delta.pos);
+        let chunk = saved.chunk_mut(coord).ok_or(NotApplied::Loading)?;
+        if chunk.get_state(local.x as usize, local.y as usize, local.z as usize) != delta.old {
+            changed += 1;
+        }
+    }
+
+    let result = world_blocks.edit(|world| {
+        changed += loaded.iter().filter(|delta| world.get_state(delta.pos) != Some(delta.old)).count();
+        if changed > 0 {
+            return Err(NotApplied::Changed(changed));
+        }
+        for delta in &loaded {
+            world.set_block(delta.pos, delta.new, SetBlockFlags::DEFAULT);
+        }
+        // After the blocks, so these replace whatever their placement callbacks made
+        for delta in loaded_entities {
+            match &delta.new {
+                Some(entity) => world.set_block_entity(delta.pos, entity.clone()),
+                None => drop(world.take_block_entity(delta.pos)),
+            }
+        }
+        Ok(())
+    });
+    if result.is_ok() {
+        for delta in unloaded {
+            let (coord, local) = world_to_chunk_local(delta.pos);
+            if let Some(chunk) = saved.chunk_mut(coord) {
+                chunk.set_state(local.x as usize, local.y as usize, local.z as usize, delta.new);
+            }
+        }
+        for delta in unloaded_entities {
+            let (coord, local) = world_to_chunk_local(delta.pos);
+            if let Some(chunk) = saved.chunk_mut(coord) {
+                chunk.set_block_entity(local.x as usize, local.y as usize, local.z as usize, delta.new.clone());
+            }
+        }
+    }
+    state.apply(world);
+    result
+}
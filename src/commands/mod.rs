@@ -0,0 +1,58 @@
+mod arguments;
+mod dispatcher;
+mod builtin;
+mod player;
+mod world;
+mod history;
+mod selection;
+
+pub use arguments::{ArgumentType, ArgumentValue, Coordinate, Coordinates};
+pub use dispatcher::{CommandContext, CommandDispatcher, CommandError, CommandNode, CommandResult, CommandSource, PermissionLevel, Suggestion};
+pub use builtin::register_builtin_commands;
+
+use bevy::prelude::*;
+use crate::net::RemotePlayer;
+use crate::physics::PhysicsBody;
+
+/// Run a command line (without the leading '/') as `player`
+#[derive(Event, Debug, Clone)]
+pub struct RunCommand {
+    pub player: Entity,
+    pub command: String,
+}
+
+/// What a command said back to the player who ran it
+#[derive(Event, Debug, Clone)]
+pub struct CommandFeedback {
+    pub player: Entity,
+    pub message: String,
+    /// The command failed, `message` says why
+    pub error: bool,
+}
+
+/// Run the commands sent since last frame, answering each player that said something back
+/// The local player is trusted with every command, a server's clients only when opped
+pub fn run_commands(world: &mut World) {
+    let requests: Vec<RunCommand> = world.resource_mut::<Events<RunCommand>>().drain().collect();
+    for request in requests {
+        let position = match world.get::<PhysicsBody>(request.player) {
+            Some(body) => body.position,
+            None => match world.get::<Transform>(request.player) {
+                Some(transform) => transform.translation,
+                None => continue,
+            },
+        };
+        let permission = match world.get::<RemotePlayer>(request.player) {
+            Some(remote) if !remote.operator => PermissionLevel::Player,
+            _ => PermissionLevel::Operator,
+        };
+        let source = CommandSource { player: request.player, position, permission };
+        let (message, error) = match CommandDispatcher::execute(world, source, &request.command) {
+            Ok(message) => (message, false),
+            Err(error) => (error.to_string(), true),
+        };
+        if !message.is_empty() {
+            world.send_event(CommandFeedback { player: request.player, message, error });
+        }
+    }
+}
@@ -0,0 +1,106 @@
+use bevy::prelude::*;
+use crate::blocks::BlockRegistry;
+use crate::components::GameMode;
+use crate::items::{new_stack, ItemRegistry};
+use crate::physics::PhysicsBody;
+use crate::resources::{HotbarItem, ItemStack, PlayerInventory};
+use crate::systems::DropItem;
+use super::arguments::ArgumentType;
+use super::dispatcher::{CommandContext, CommandDispatcher, CommandError, CommandNode, CommandResult, PermissionLevel};
+
+/// Most items one `/give` hands out (a hundred stacks)
+const MAX_GIVE_COUNT: i32 = 6400;
+
+/// `/gamemode`, `/tp` and `/give`, which act on the player running them (operators only)
+pub(super) fn register_player_commands(dispatcher: &mut CommandDispatcher) {
+    let mut gamemode = CommandNode::literal("gamemode").describe("Sets your game mode");
+    for mode in GameMode::ALL {
+        gamemode = gamemode.then(CommandNode::literal(mode.id()).executes(move |context| set_game_mode(context, mode)));
+    }
+
+    let tp = CommandNode::literal("tp")
+        .describe("Teleports you")
+        .then(CommandNode::argument("position", ArgumentType::Position).executes(|context| {
+            let position = context.position("position")?;
+            teleport(context, position)
+        }));
+
+    let give = CommandNode::literal("give")
+        .describe("Gives you items")
+        .then(CommandNode::argument("item", ArgumentType::Item)
+            .executes(|context| give_items(context, 1))
+            .then(CommandNode::argument("count", ArgumentType::integer_between(1, MAX_GIVE_COUNT)).executes(|context| {
+                let count = context.integer("count");
+                give_items(context, count)
+            })));
+
+    for command in [gamemode, tp, give] {
+        dispatcher.register(command.requires(PermissionLevel::Operator)).expect("built-in commands are registered first");
+    }
+}
+
+fn set_game_mode(context: &mut CommandContext, mode: GameMode) -> CommandResult {
+    let mut game_mode = context.world.get_mut::<GameMode>(context.source.player)
+        .ok_or_else(|| CommandError::Failed("You have no game mode".to_string()))?;
+    *game_mode = mode;
+    Ok(format!("Set own game mode to {} Mode", mode.name()))
+}
+
+fn teleport(context: &mut CommandContext, position: Vec3) -> CommandResult {
+    let mut player = context.world.entity_mut(context.source.player);
+    let offset = match player.get_mut::<PhysicsBody>() {
+        Some(mut body) => {
+            body.teleport(position);
+            body.velocity = Vec3::ZERO;
+            body.render_offset
+        }
+        None => Vec3::ZERO,
+    };
+    if let Some(mut transform) = player.get_mut::<Transform>() {
+        transform.translation = position + offset;
+    }
+    Ok(format!("Teleported to {:.2}, {:.2}, {:.2}", position.x, position.y, position.z))
+}
+
+/// Put `count` of the item into the player's hotbar, dropping what doesn't fit at their feet
+/// Players with their own `PlayerInventory` component (a server's clients) get it there,
+/// everyone else in the `PlayerInventory` resource
+fn give_items(context: &mut CommandContext, count: i32) -> CommandResult {
+    let item = context.item("item");
+    let name = item_name(item, context.world);
+    let template = new_stack(item, 1, context.world.resource::<ItemRegistry>());
+    let mut stacks = Vec::new();
+    let mut remaining = count;
+    while remaining > 0 {
+        let stack_count = remaining.min(template.max_count() as i32);
+        stacks.push(ItemStack { count: stack_count as u8, ..template });
+        remaining -= stack_count;
+    }
+
+    let mut leftovers = Vec::new();
+    let player = context.source.player;
+    let mut add = |inventory: &mut PlayerInventory| {
+        for stack in &stacks {
+            let left = inventory.add_stack(*stack);
+            if left > 0 {
+                leftovers.push(ItemStack { count: left, ..*stack });
+            }
+        }
+    };
+    match context.world.get_mut::<PlayerInventory>(player) {
+        Some(mut inventory) => add(&mut inventory),
+        None => add(&mut context.world.resource_mut::<PlayerInventory>()),
+    }
+    for stack in leftovers {
+        context.world.send_event(DropItem { pos: context.source.position + Vec3::Y * 0.5, stack });
+    }
+    Ok(format!("Gave {} {}", count, name))
+}
+
+fn item_name(item: HotbarItem, world: &World) -> String {
+    match item {
+        HotbarItem::Item(id) => world.resource::<ItemRegistry>().name(id).to_string(),
+        HotbarItem::Block(id) => world.resource::<BlockRegistry>().get_block(id)
+            .map_or_else(|| "?".to_string(), |block| block.properties.name.clone()),
+    }
+}
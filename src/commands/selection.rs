@@ -0,0 +1,213 @@
+use bevy::prelude::*;
+use crate::blocks::{BlockRegistry, BlockState};
+use crate::world::{Clipboard, Schematic, SchematicDirectory, Selection, SetBlockFlags};
+use super::arguments::ArgumentType;
+use super::dispatcher::{CommandContext, CommandDispatcher, CommandError, CommandNode, CommandResult, PermissionLevel};
+use super::world::{not_loaded, Region};
+
+/// `/pos1`, `/pos2`, `/deselect`, `/copy`, `/cut`, `/paste`, `/rotate`, `/flip` and `/schematic`:
+/// marking a box of blocks (also done with the selection wand) and moving it around through
+/// the player's clipboard (operators only)
+pub(super) fn register_selection_commands(dispatcher: &mut CommandDispatcher) {
+    let corner = |name: &'static str, description: &'static str, second: bool| CommandNode::literal(name)
+        .describe(description)
+        .executes(move |context| {
+            let pos = feet_block(context);
+            set_corner(context, pos, second)
+        })
+        .then(CommandNode::argument("pos", ArgumentType::BlockPos).executes(move |context| {
+            let pos = context.block_pos("pos")?;
+            set_corner(context, pos, second)
+        }));
+    let pos1 = corner("pos1", "Sets the first corner of your selection", false);
+    let pos2 = corner("pos2", "Sets the second corner of your selection", true);
+
+    let deselect = CommandNode::literal("deselect")
+        .describe("Clears your selection")
+        .executes(|context| {
+            *selection(context)? = Selection::default();
+            Ok("Selection cleared".to_string())
+        });
+
+    let copy = CommandNode::literal("copy")
+        .describe("Copies your selection to your clipboard")
+        .executes(|context| copy_selection(context, false));
+    let cut = CommandNode::literal("cut")
+        .describe("Copies your selection to your clipboard and empties it")
+        .executes(|context| copy_selection(context, true));
+    let paste = CommandNode::literal("paste")
+        .describe("Pastes your clipboard where it was copied from relative to you")
+        .executes(|context| paste_clipboard(context, false))
+        .then(CommandNode::literal("masked").executes(|context| paste_clipboard(context, true)));
+
+    let mut rotate = CommandNode::literal("rotate").describe("Turns your clipboard clockwise around you");
+    for (degrees, quarter_turns) in [("90", 1), ("180", 2), ("270", 3)] {
+        rotate = rotate.then(CommandNode::literal(degrees).executes(move |context| {
+            transform_clipboard(context, |schematic, registry| schematic.rotated(quarter_turns, registry))?;
+            Ok(format!("Rotated the clipboard by {} degrees", degrees))
+        }));
+    }
+    let mut flip = CommandNode::literal("flip").describe("Mirrors your clipboard along an axis through you");
+    for (name, axis) in [("x", 0), ("y", 1), ("z", 2)] {
+        flip = flip.then(CommandNode::literal(name).executes(move |context| {
+            transform_clipboard(context, |schematic, registry| schematic.flipped(axis, registry))?;
+            Ok(format!("Flipped the clipboard along {}", name))
+        }));
+    }
+
+    let schematic = CommandNode::literal("schematic")
+        .describe("Saves your clipboard to a schematic file, or loads one into it")
+        .then(CommandNode::literal("save").then(CommandNode::argument("name", ArgumentType::Word).executes(save_schematic)))
+        .then(CommandNode::literal("load").then(CommandNode::argument("name", ArgumentType::Word).executes(load_schematic)))
+        .then(CommandNode::literal("list").executes(|context| {
+            let names = context.world.resource::<SchematicDirectory>().names();
+            match names.is_empty() {
+                true => Ok("No schematics saved".to_string()),
+                false => Ok(format!("Schematics: {}", names.join(", "))),
+            }
+        }));
+
+    for command in [pos1, pos2, deselect, copy, cut, paste, rotate, flip, schematic] {
+        dispatcher.register(command.requires(PermissionLevel::Operator)).expect("built-in commands are registered first");
+    }
+}
+
+/// The block the player is standing in, what copies and pastes are relative to
+fn feet_block(context: &CommandContext) -> IVec3 {
+    context.source.position.floor().as_ivec3()
+}
+
+fn selection<'a>(context: &'a mut CommandContext) -> Result<Mut<'a, Selection>, CommandError> {
+    context.world.get_mut::<Selection>(context.source.player)
+        .ok_or_else(|| CommandError::Failed("You can't select blocks".to_string()))
+}
+
+fn clipboard<'a>(context: &'a mut CommandContext) -> Result<Mut<'a, Clipboard>, CommandError> {
+    context.world.get_mut::<Clipboard>(context.source.player)
+        .ok_or_else(|| CommandError::Failed("You have no clipboard".to_string()))
+}
+
+fn empty_clipboard() -> CommandError {
+    CommandError::Failed("Your clipboard is empty".to_string())
+}
+
+fn set_corner(context: &mut CommandContext, pos: IVec3, second: bool) -> CommandResult {
+    let mut selection = selection(context)?;
+    let (corner, name) = match second {
+        false => (&mut selection.first, "First"),
+        true => (&mut selection.second, "Second"),
+    };
+    *corner = Some(pos);
+    Ok(match selection.volume() {
+        0 => format!("{} corner set to {}, {}, {}", name, pos.x, pos.y, pos.z),
+        volume => format!("{} corner set to {}, {}, {} ({} blocks)", name, pos.x, pos.y, pos.z, volume),
+    })
+}
+
+/// Copy the selection, block entities included, into the clipboard; a cut then empties it
+/// as one edit the player can undo
+fn copy_selection(context: &mut CommandContext, cut: bool) -> CommandResult {
+    let (min, max) = selection(context)?.bounds()
+        .ok_or_else(|| CommandError::Failed("Set both corners of your selection first".to_string()))?;
+    let region = Region::new(min, max);
+    region.check_volume()?;
+    let offset = min - feet_block(context);
+
+    let schematic = context.edit_blocks(|world| {
+        if region.positions().any(|pos| world.get_state(pos).is_none()) {
+            return Err(not_loaded());
+        }
+        let mut schematic = Schematic::new(region.size(), offset);
+        for pos in region.positions() {
+            schematic.set(pos - min, world.get_state(pos).unwrap_or(BlockState::AIR));
+            // Taken out before the cut, so a furnace doesn't drop what it holds as well
+            let block_entity = match cut {
+                true => world.take_block_entity(pos),
+                false => world.block_entity(pos).cloned(),
+            };
+            if let Some(block_entity) = block_entity {
+                schematic.add_block_entity(pos - min, block_entity);
+            }
+        }
+        if cut {
+            for pos in region.positions() {
+                world.set_block(pos, BlockState::AIR, SetBlockFlags::DEFAULT);
+            }
+        }
+        Ok(schematic)
+    })?;
+
+    let volume = schematic.volume();
+    clipboard(context)?.0 = Some(schematic);
+    Ok(format!("{} {} block(s)", if cut { "Cut" } else { "Copied" }, volume))
+}
+
+/// Place the clipboard at its offset from the player as one edit they can undo
+/// Masked pastes leave the world alone where the clipboard holds air
+fn paste_clipboard(context: &mut CommandContext, masked: bool) -> CommandResult {
+    let schematic = clipboard(context)?.0.clone().ok_or_else(empty_clipboard)?;
+    let region = Region::with_size(feet_block(context).as_i64vec3() + schematic.offset.as_i64vec3(), schematic.size())?;
+    region.check_volume()?;
+    let min = region.min;
+
+    let pasted = context.edit_blocks(|world| {
+        if region.positions().any(|pos| world.get_state(pos).is_none()) {
+            return Err(not_loaded());
+        }
+        let mut pasted = 0;
+        for (local, state) in schematic.blocks() {
+            if !(masked && state.is_air()) && world.set_block(min + local, state, SetBlockFlags::DEFAULT) {
+                pasted += 1;
+            }
+        }
+        for (local, block_entity) in schematic.block_entities() {
+            world.set_block_entity(min + *local, block_entity.clone());
+        }
+        Ok(pasted)
+    })?;
+    Ok(format!("Pasted {} block(s)", pasted))
+}
+
+fn transform_clipboard(context: &mut CommandContext, transform: impl FnOnce(&Schematic, &BlockRegistry) -> Schematic) -> Result<(), CommandError> {
+    let schematic = clipboard(context)?.0.take().ok_or_else(empty_clipboard)?;
+    let transformed = transform(&schematic, context.world.resource::<BlockRegistry>());
+    clipboard(context)?.0 = Some(transformed);
+    Ok(())
+}
+
+/// A schematic name, which becomes a file name
+fn schematic_name(context: &CommandContext) -> Result<String, CommandError> {
+    let name = context.string("name");
+    match name.chars().all(|character| character.is_ascii_alphanumeric() || character == '_' || character == '-') {
+        true => Ok(name.to_string()),
+        false => Err(CommandError::Failed(format!("Invalid schematic name '{}' (use letters, digits, _ and -)", name))),
+    }
+}
+
+fn save_schematic(context: &mut CommandContext) -> CommandResult {
+    let name = schematic_name(context)?;
+    let schematic = clipboard(context)?.0.clone().ok_or_else(empty_clipboard)?;
+    let path = context.world.resource::<SchematicDirectory>().path(&name);
+    schematic.save(&path, context.world.resource::<BlockRegistry>()).map_err(CommandError::Failed)?;
+    Ok(format!("Saved the clipboard as '{}'", name))
+}
+
+fn load_schematic(context: &mut CommandContext) -> CommandResult {
+    let name = schematic_name(context)?;
+    let path = context.world.resource::<SchematicDirectory>().path(&name);
+    if !path.exists() {
+        return Err(CommandError::Failed(format!("No schematic named '{}'", name)));
+    }
+    let (schematic, mut missing) = Schematic::load(&path, context.world.resource::<BlockRegistry>())
+        .map_err(CommandError::Failed)?;
+    let volume = schematic.volume();
+    clipboard(context)?.0 = Some(schematic);
+
+    let mut message = format!("Loaded '{}' into the clipboard ({} block(s))", name, volume);
+    missing.sort();
+    missing.dedup();
+    if !missing.is_empty() {
+        message += &format!("\nUnknown blocks were replaced with air: {}", missing.join(", "));
+    }
+    Ok(message)
+}
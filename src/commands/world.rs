@@ -0,0 +1,245 @@
+use bevy::math::I64Vec3;
+use bevy::prelude::*;
+use crate::blocks::{BlockId, BlockState};
+use crate::systems::TimeOfDay;
+use crate::world::{BlockEntity, ChunkManager, SetBlockFlags, WorldTick, WORLD_LIMIT};
+use super::arguments::ArgumentType;
+use super::dispatcher::{outside_world, CommandContext, CommandDispatcher, CommandError, CommandNode, CommandResult, PermissionLevel};
+
+/// Most blocks `/fill` and `/clone` change at once
+const MAX_REGION_VOLUME: i64 = 32768;
+
+/// `/time`, `/seed`, `/setblock`, `/fill` and `/clone`, which act on the world (operators only)
+pub(super) fn register_world_commands(dispatcher: &mut CommandDispatcher) {
+    let mut set = CommandNode::literal("set")
+        .then(CommandNode::argument("time", ArgumentType::integer_between(0, i32::MAX)).executes(|context| {
+            let ticks = context.integer("time") as i64;
+            set_time(context, ticks)
+        }));
+    for (name, ticks) in [("day", 1000), ("noon", 6000), ("night", 13000), ("midnight", 18000)] {
+        set = set.then(CommandNode::literal(name).executes(move |context| set_time(context, ticks)));
+    }
+    let time = CommandNode::literal("time")
+        .describe("Changes or queries the time of day")
+        .then(set)
+        .then(CommandNode::literal("add").then(CommandNode::argument("time", ArgumentType::integer()).executes(|context| {
+            let ticks = context.world.resource::<TimeOfDay>().ticks() + context.integer("time") as i64;
+            set_time(context, ticks)
+        })))
+        .then(CommandNode::literal("query")
+            .then(CommandNode::literal("daytime").executes(|context| {
+                Ok(format!("The time is {}", context.world.resource::<TimeOfDay>().ticks()))
+            }))
+            .then(CommandNode::literal("gametime").executes(|context| {
+                Ok(format!("The time is {}", context.world.resource::<WorldTick>().tick))
+            })))
+        .then(CommandNode::literal("pause").executes(|context| set_time_paused(context, true)))
+        .then(CommandNode::literal("resume").executes(|context| set_time_paused(context, false)));
+
+    let seed = CommandNode::literal("seed")
+        .describe("Shows the world seed")
+        .executes(|context| Ok(format!("Seed: [{}]", context.world.resource::<ChunkManager>().world_seed)));
+
+    let setblock = CommandNode::literal("setblock")
+        .describe("Changes a block")
+        .then(CommandNode::argument("pos", ArgumentType::BlockPos)
+            .then(CommandNode::argument("block", ArgumentType::Block)
+                .executes(|context| set_block(context, false))
+                .then(CommandNode::literal("replace").executes(|context| set_block(context, false)))
+                .then(CommandNode::literal("keep").executes(|context| set_block(context, true)))));
+
+    let fill = CommandNode::literal("fill")
+        .describe("Fills a region with a block")
+        .then(CommandNode::argument("from", ArgumentType::BlockPos)
+            .then(CommandNode::argument("to", ArgumentType::BlockPos)
+                .then(CommandNode::argument("block", ArgumentType::Block)
+                    .executes(|context| fill(context, FillMode::Replace(None)))
+                    .then(CommandNode::literal("replace")
+                        .executes(|context| fill(context, FillMode::Replace(None)))
+                        .then(CommandNode::argument("filter", ArgumentType::Block).executes(|context| {
+                            let filter = context.block("filter").id;
+                            fill(context, FillMode::Replace(Some(filter)))
+                        })))
+                    .then(CommandNode::literal("hollow").executes(|context| fill(context, FillMode::Hollow)))
+                    .then(CommandNode::literal("outline").executes(|context| fill(context, FillMode::Outline))))));
+
+    let clone = CommandNode::literal("clone")
+        .describe("Copies a region to another place")
+        .then(CommandNode::argument("begin", ArgumentType::BlockPos)
+            .then(CommandNode::argument("end", ArgumentType::BlockPos)
+                .then(CommandNode::argument("destination", ArgumentType::BlockPos)
+                    .executes(|context| clone(context, false))
+                    .then(CommandNode::literal("replace").executes(|context| clone(context, false)))
+                    .then(CommandNode::literal("masked").executes(|context| clone(context, true))))));
+
+    for command in [time, seed, setblock, fill, clone] {
+        dispatcher.register(command.requires(PermissionLevel::Operator)).expect("built-in commands are registered first");
+    }
+}
+
+fn set_time(context: &mut CommandContext, ticks: i64) -> CommandResult {
+    let mut time_of_day = context.world.resource_mut::<TimeOfDay>();
+    time_of_day.set_ticks(ticks);
+    Ok(format!("Set the time to {}", time_of_day.ticks()))
+}
+
+fn set_time_paused(context: &mut CommandContext, paused: bool) -> CommandResult {
+    context.world.resource_mut::<TimeOfDay>().paused = paused;
+    Ok(if paused { "Stopped the day/night cycle" } else { "Started the day/night cycle" }.to_string())
+}
+
+/// An axis-aligned box of blocks, both corners included
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) struct Region {
+    pub(super) min: IVec3,
+    pub(super) max: IVec3,
+}
+
+impl Region {
+    pub(super) fn new(a: IVec3, b: IVec3) -> Self {
+        Self { min: a.min(b), max: a.max(b) }
+    }
+
+    /// The box of `size` blocks with its lowest corner at `min`, if it's all within `WORLD_LIMIT`
+    pub(super) fn with_size(min: I64Vec3, size: IVec3) -> Result<Self, CommandError> {
+        let max = min + size.as_i64vec3() - I64Vec3::ONE;
+        let limit = WORLD_LIMIT as i64;
+        if min.min(max).min_element() < -limit || min.max(max).max_element() > limit || size.min_element() < 1 {
+            return Err(outside_world());
+        }
+        Ok(Self::new(min.as_ivec3(), max.as_ivec3()))
+    }
+
+    /// Blocks along each axis (corners within `WORLD_LIMIT` keep this well inside an i32)
+    pub(super) fn size(&self) -> IVec3 {
+        (self.max.as_i64vec3() - self.min.as_i64vec3() + I64Vec3::ONE).as_ivec3()
+    }
+
+    /// Blocks in the box, `None` past what an i64 holds
+    pub(super) fn volume(&self) -> Option<i64> {
+        let size = self.max.as_i64vec3() - self.min.as_i64vec3() + I64Vec3::ONE;
+        size.x.checked_mul(size.y)?.checked_mul(size.z)
+    }
+
+    /// Every position, x fastest then z then y
+    pub(super) fn positions(&self) -> impl Iterator<Item = IVec3> {
+        let Region { min, max } = *self;
+        (min.y..=max.y).flat_map(move |y| {
+            (min.z..=max.z).flat_map(move |z| (min.x..=max.x).map(move |x| IVec3::new(x, y, z)))
+        })
+    }
+
+    /// Whether `pos` is on one of the box's six faces
+    fn on_edge(&self, pos: IVec3) -> bool {
+        pos.cmpeq(self.min).any() || pos.cmpeq(self.max).any()
+    }
+
+    /// Fail with Minecraft's message if the region is too big to edit at once
+    pub(super) fn check_volume(&self) -> Result<(), CommandError> {
+        match self.volume() {
+            Some(volume) if volume <= MAX_REGION_VOLUME => Ok(()),
+            volume => Err(CommandError::Failed(format!(
+                "Too many blocks in the specified area (maximum {}, specified {})",
+                MAX_REGION_VOLUME, volume.map_or_else(|| "more than any count".to_string(), |volume| volume.to_string()),
+            ))),
+        }
+    }
+}
+
+pub(super) fn not_loaded() -> CommandError {
+    CommandError::Failed("That position is not loaded".to_string())
+}
+
+fn set_block(context: &mut CommandContext, keep: bool) -> CommandResult {
+    let pos = context.block_pos("pos")?;
+    let state = context.block("block");
+    context.edit_blocks(|world| {
+        let old = world.get_state(pos).ok_or_else(not_loaded)?;
+        if (keep && !old.is_air()) || !world.set_block(pos, state, SetBlockFlags::DEFAULT) {
+            return Err(CommandError::Failed("Could not set the block".to_string()));
+        }
+        Ok(format!("Changed the block at {}, {}, {}", pos.x, pos.y, pos.z))
+    })
+}
+
+/// What `/fill` does to each block of the region
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FillMode {
+    /// Every block, or only the blocks of the filter type
+    Replace(Option<BlockId>),
+    /// The outside, emptying the inside
+    Hollow,
+    /// The outside, leaving the inside alone
+    Outline,
+}
+
+fn fill(context: &mut CommandContext, mode: FillMode) -> CommandResult {
+    let region = Region::new(context.block_pos("from")?, context.block_pos("to")?);
+    let state = context.block("block");
+    region.check_volume()?;
+
+    // One edit for the whole region, so each chunk it touches is remeshed once
+    let filled = context.edit_blocks(|world| {
+        if region.positions().any(|pos| world.get_state(pos).is_none()) {
+            return Err(not_loaded());
+        }
+        let mut filled = 0;
+        for pos in region.positions() {
+            let target = match mode {
+                FillMode::Replace(Some(filter)) if world.get_block(pos) != Some(filter) => continue,
+                FillMode::Replace(_) => state,
+                _ if region.on_edge(pos) => state,
+                FillMode::Hollow => BlockState::AIR,
+                FillMode::Outline => continue,
+            };
+            if world.set_block(pos, target, SetBlockFlags::DEFAULT) {
+                filled += 1;
+            }
+        }
+        Ok(filled)
+    })?;
+    match filled {
+        0 => Err(CommandError::Failed("No blocks were filled".to_string())),
+        filled => Ok(format!("Successfully filled {} block(s)", filled)),
+    }
+}
+
+/// Copy the region between `begin` and `end` to `destination` (its lowest corner), block
+/// entities included. Masked clones leave the destination alone where the source is air
+fn clone(context: &mut CommandContext, masked: bool) -> CommandResult {
+    let source = Region::new(context.block_pos("begin")?, context.block_pos("end")?);
+    source.check_volume()?;
+    let destination = Region::with_size(context.block_pos("destination")?.as_i64vec3(), source.size())?;
+    // Both boxes are within the world, so the offset between them fits
+    let offset = destination.min - source.min;
+
+    let cloned = context.edit_blocks(|world| {
+        // Read everything first, so a destination overlapping the source copies the original blocks
+        let mut blocks: Vec<(IVec3, BlockState, Option<BlockEntity>)> = Vec::new();
+        for pos in source.positions() {
+            let state = world.get_state(pos).ok_or_else(not_loaded)?;
+            if !(masked && state.is_air()) {
+                blocks.push((pos + offset, state, world.block_entity(pos).cloned()));
+            }
+        }
+        if destination.positions().any(|pos| world.get_state(pos).is_none()) {
+            return Err(not_loaded());
+        }
+
+        let mut cloned = 0;
+        for (pos, state, block_entity) in blocks {
+            let changed = world.set_block(pos, state, SetBlockFlags::DEFAULT);
+            if let Some(block_entity) = block_entity {
+                world.set_block_entity(pos, block_entity);
+            }
+            if changed {
+                cloned += 1;
+            }
+        }
+        Ok(cloned)
+    })?;
+    match cloned {
+        0 => Err(CommandError::Failed("No blocks were cloned".to_string())),
+        cloned => Ok(format!("Successfully cloned {} block(s)", cloned)),
+    }
+}
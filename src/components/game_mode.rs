@@ -0,0 +1,119 @@
+use bevy::prelude::*;
+
+/// What a player is allowed to do, derived from their game mode
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Abilities {
+    /// Double-tapping jump toggles flight
+    pub can_fly: bool,
+    /// Blocks break on the first hit, including unbreakable ones like bedrock
+    pub instant_break: bool,
+    /// Ignores all damage and never gets hungry
+    pub invulnerable: bool,
+    /// Can place and break blocks
+    pub can_build: bool,
+    /// Flies through terrain without colliding
+    pub noclip: bool,
+}
+
+/// Per-player game mode, stored on the player entity
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GameMode {
+    Survival,
+    #[default]
+    Creative,
+    /// Survival rules, but blocks can't be placed or broken
+    Adventure,
+    /// Invisible observer: noclip flight and no interaction with the world
+    Spectator,
+}
+
+impl GameMode {
+    pub fn abilities(self) -> Abilities {
+        match self {
+            GameMode::Survival => Abilities {
+                can_fly: false,
+                instant_break: false,
+                invulnerable: false,
+                can_build: true,
+                noclip: false,
+            },
+            GameMode::Creative => Abilities {
+                can_fly: true,
+                instant_break: true,
+                invulnerable: true,
+                can_build: true,
+                noclip: false,
+            },
+            GameMode::Adventure => Abilities {
+                can_fly: false,
+                instant_break: false,
+                invulnerable: false,
+                can_build: false,
+                noclip: false,
+            },
+            GameMode::Spectator => Abilities {
+                can_fly: true,
+                instant_break: false,
+                invulnerable: true,
+                can_build: false,
+                noclip: true,
+            },
+        }
+    }
+
+    /// Whether the player can use blocks and items at all (everything but spectator)
+    pub fn can_interact(self) -> bool {
+        self != GameMode::Spectator
+    }
+
+    /// Every mode, in the order `/gamemode` lists them
+    pub const ALL: [GameMode; 4] = [GameMode::Survival, GameMode::Creative, GameMode::Adventure, GameMode::Spectator];
+
+    /// The name typed in `/gamemode` and sent over the network
+    pub fn id(self) -> &'static str {
+        match self {
+            GameMode::Survival => "survival",
+            GameMode::Creative => "creative",
+            GameMode::Adventure => "adventure",
+            GameMode::Spectator => "spectator",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|mode| mode.id() == id)
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            GameMode::Survival => "Survival",
+            GameMode::Creative => "Creative",
+            GameMode::Adventure => "Adventure",
+            GameMode::Spectator => "Spectator",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_mode_is_found_by_its_id() {
+        for mode in GameMode::ALL {
+            assert_eq!(GameMode::from_id(mode.id()), Some(mode));
+        }
+        assert_eq!(GameMode::from_id("Creative"), None);
+    }
+
+    #[test]
+    fn only_spectator_is_noclip_and_cannot_interact() {
+        for mode in [GameMode::Survival, GameMode::Creative, GameMode::Adventure] {
+            assert!(!mode.abilities().noclip);
+            assert!(mode.can_interact());
+        }
+        let spectator = GameMode::Spectator.abilities();
+        assert!(spectator.noclip && spectator.can_fly && !spectator.can_build);
+        assert!(!GameMode::Spectator.can_interact());
+        assert!(!GameMode::Adventure.abilities().can_build);
+    }
+}
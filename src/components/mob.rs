@@ -0,0 +1,68 @@
+use bevy::prelude::*;
+use crate::entities::{EntityRegistry, EntityTypeId, MobBrain, Navigation};
+
+/// A living mob; its `PhysicsBody` holds position and velocity,
+/// the `Transform` sits at the feet and turns with the yaw
+#[derive(Component, Debug, Clone)]
+pub struct Mob {
+    pub entity_type: EntityTypeId,
+    pub health: u8,
+    /// Facing, in radians around Y (0 faces -Z)
+    pub yaw: f32,
+    pub brain: MobBrain,
+    /// Path to where the brain wants to go
+    pub navigation: Navigation,
+    /// Ran into a wall last tick (the AI jumps)
+    pub blocked: bool,
+    /// World ticks left of invulnerability after a hit
+    pub hurt_ticks: u32,
+    /// Walk cycle position in radians, drives the leg swing
+    pub walk_phase: f32,
+}
+
+impl Mob {
+    pub fn new(data: MobData) -> Self {
+        Self {
+            entity_type: data.entity_type,
+            health: data.health,
+            yaw: data.yaw,
+            brain: MobBrain::default(),
+            navigation: Navigation::default(),
+            blocked: false,
+            hurt_ticks: 0,
+            walk_phase: 0.0,
+        }
+    }
+
+    /// The state that's kept when the mob's chunk unloads
+    pub fn data(&self, position: Vec3) -> MobData {
+        MobData { entity_type: self.entity_type, position, yaw: self.yaw, health: self.health }
+    }
+}
+
+/// A mob's persistent state: what's saved with its chunk and what spawning starts from
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MobData {
+    pub entity_type: EntityTypeId,
+    /// Feet position
+    pub position: Vec3,
+    pub yaw: f32,
+    pub health: u8,
+}
+
+impl MobData {
+    /// A fresh mob at full health
+    pub fn new(entity_type: EntityTypeId, position: Vec3, registry: &EntityRegistry) -> Self {
+        let health = registry.get_entity(entity_type).map_or(1, |entity| entity.properties.max_health);
+        Self { entity_type, position, yaw: 0.0, health }
+    }
+}
+
+/// One box of a mob's model (a child of the mob)
+#[derive(Component)]
+pub struct MobPart {
+    /// Where the box rotates around, relative to the mob's feet
+    pub pivot: Vec3,
+    /// Leg swing phase, None for parts that don't move
+    pub swing: Option<f32>,
+}
@@ -1,11 +1,17 @@
 mod camera;
+mod game_mode;
+mod player;
+mod mob;
 mod voxel;
 mod ui;
 mod world;
 mod crosshair;
 
-pub use camera::CameraController;
+pub use camera::{CameraController, CameraMode};
+pub use game_mode::GameMode;
+pub use player::{Player, PlayerModel, PLAYER_MODEL_PARTS, GRAVITY, PLAYER_HALF_WIDTH, PLAYER_HEIGHT, PLAYER_EYE_HEIGHT, PLAYER_STEP_HEIGHT};
+pub use mob::{Mob, MobData, MobPart};
 pub use voxel::Voxel;
-pub use ui::{ClickText, FpsCounter, DebugOverlay, PauseMenu, ResumeButton, ExitButton, ChunkBorder, BlockHighlight, Hotbar, HotbarSlot, HotbarSlotIcon, HotbarSlotText, SurvivalBars, HeartBackground, HeartIcon, HungerBackground, HungerIcon};
+pub use ui::{ClickText, FpsCounter, DebugOverlay, PauseMenu, ResumeButton, ExitButton, DeathScreen, RespawnButton, CraftingScreenRoot, CraftingSlot, CraftingOutput, CraftingSlotLabel, FurnaceScreenRoot, FurnaceSlotButton, FurnaceSlotLabel, FurnaceBurnFill, FurnaceProgressFill, ChatMessages, ChatSuggestions, ChatInput, ChunkBorder, SelectionOutline, BlockHighlight, Hotbar, HotbarSlot, HotbarSlotIcon, HotbarSlotText, HotbarSlotCount, HotbarSlotDurability, HotbarSlotDurabilityFill, SurvivalBars, HeartBackground, HeartIcon, HungerBackground, HungerIcon, AirIcon, HurtOverlay};
 pub use world::{Skybox, NightSkybox, Sun, Moon};
 pub use crosshair::Crosshair;
@@ -0,0 +1,96 @@
+use bevy::prelude::*;
+
+/// Downward acceleration in blocks/s² shared by the player and other falling entities
+pub const GRAVITY: f32 = 20.0;
+
+/// Player collision box: 0.6 wide, 1.8 tall, eyes 1.6 above the feet
+pub const PLAYER_HALF_WIDTH: f32 = 0.3;
+pub const PLAYER_HEIGHT: f32 = 1.8;
+pub const PLAYER_EYE_HEIGHT: f32 = 1.6;
+
+/// Ledges up to this height are walked up automatically (half blocks)
+pub const PLAYER_STEP_HEIGHT: f32 = 0.6;
+
+/// The player entity: its Transform sits at the feet and turns with the yaw,
+/// the PhysicsBody next to it owns position, velocity and the AABB
+#[derive(Component)]
+pub struct Player {
+    pub yaw: f32,   // Horizontal rotation (left/right)
+    pub pitch: f32, // Vertical rotation (up/down)
+    pub move_speed: f32,
+    pub sprint_multiplier: f32,
+    pub is_flying: bool,
+    pub gravity: f32,
+    pub jump_force: f32,
+    pub last_space_press: f32, // For double-tap detection
+    pub sneak_multiplier: f32,
+    pub is_sneaking: bool,
+    pub is_sprinting: bool,
+    /// Horizontal velocity requested by the movement keys (blocks/s)
+    pub move_input: Vec3,
+    /// Vertical velocity requested by Space/Shift while flying (blocks/s)
+    pub fly_input: f32,
+    /// Jump pressed since the last physics step
+    pub jump_requested: bool,
+    /// Bed the player last used; respawns happen there instead of the world spawn
+    pub bed_spawn: Option<IVec3>,
+}
+
+impl Default for Player {
+    fn default() -> Self {
+        Self {
+            yaw: 0.0,
+            pitch: 0.0,
+            move_speed: 5.0,
+            sprint_multiplier: 2.0,
+            is_flying: false,
+            gravity: GRAVITY,
+            jump_force: 8.0,
+            last_space_press: -1.0,
+            sneak_multiplier: 0.3,
+            is_sneaking: false,
+            is_sprinting: false,
+            move_input: Vec3::ZERO,
+            fly_input: 0.0,
+            jump_requested: false,
+            bed_spawn: None,
+        }
+    }
+}
+
+impl Player {
+    /// Head orientation from yaw and pitch
+    pub fn look_rotation(&self) -> Quat {
+        Quat::from_euler(EulerRot::YXZ, self.yaw, self.pitch, 0.0)
+    }
+
+    /// Unit vector the player is looking along
+    pub fn look_direction(&self) -> Vec3 {
+        self.look_rotation() * Vec3::NEG_Z
+    }
+
+    /// Eye position for a player whose feet are at `transform`
+    pub fn eye_position(transform: &Transform) -> Vec3 {
+        transform.translation + Vec3::Y * PLAYER_EYE_HEIGHT
+    }
+}
+
+/// Marker for the cuboid body parts drawn in third-person views
+#[derive(Component)]
+pub struct PlayerModel;
+
+/// The player's cuboid body parts as (size, center above the feet, color), for the local
+/// player's third-person model and the other players on a server
+pub const PLAYER_MODEL_PARTS: [(Vec3, Vec3, Color); 6] = {
+    const SKIN: Color = Color::srgb(0.85, 0.65, 0.5);
+    const SHIRT: Color = Color::srgb(0.2, 0.55, 0.7);
+    const TROUSERS: Color = Color::srgb(0.25, 0.25, 0.55);
+    [
+        (Vec3::new(0.5, 0.5, 0.5), Vec3::new(0.0, 1.55, 0.0), SKIN),
+        (Vec3::new(0.5, 0.6, 0.25), Vec3::new(0.0, 1.0, 0.0), SHIRT),
+        (Vec3::new(0.2, 0.6, 0.25), Vec3::new(-0.35, 1.0, 0.0), SKIN),
+        (Vec3::new(0.2, 0.6, 0.25), Vec3::new(0.35, 1.0, 0.0), SKIN),
+        (Vec3::new(0.24, 0.7, 0.25), Vec3::new(-0.12, 0.35, 0.0), TROUSERS),
+        (Vec3::new(0.24, 0.7, 0.25), Vec3::new(0.12, 0.35, 0.0), TROUSERS),
+    ]
+};
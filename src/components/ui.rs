@@ -1,4 +1,5 @@
 use bevy::prelude::*;
+use crate::crafting::FurnaceSlot;
 
 #[derive(Component)]
 pub struct ClickText {
@@ -24,9 +25,75 @@ pub struct ResumeButton;
 #[derive(Component)]
 pub struct ExitButton;
 
+#[derive(Component)]
+pub struct DeathScreen;
+
+#[derive(Component)]
+pub struct RespawnButton;
+
+/// Root of the crafting table screen
+#[derive(Component)]
+pub struct CraftingScreenRoot;
+
+/// One of the 9 crafting grid slots (a button)
+#[derive(Component)]
+pub struct CraftingSlot {
+    pub index: usize,
+}
+
+/// The crafting result slot (a button that crafts when clicked)
+#[derive(Component)]
+pub struct CraftingOutput;
+
+/// Text inside a crafting slot, `None` for the result slot
+#[derive(Component)]
+pub struct CraftingSlotLabel {
+    pub index: Option<usize>,
+}
+
+/// Root of the furnace screen
+#[derive(Component)]
+pub struct FurnaceScreenRoot;
+
+/// One of the furnace's input, fuel and output slots (a button)
+#[derive(Component)]
+pub struct FurnaceSlotButton {
+    pub slot: FurnaceSlot,
+}
+
+/// Text inside a furnace slot
+#[derive(Component)]
+pub struct FurnaceSlotLabel {
+    pub slot: FurnaceSlot,
+}
+
+/// Fill of the flame gauge, its width is the fuel left in the burning item
+#[derive(Component)]
+pub struct FurnaceBurnFill;
+
+/// Fill of the progress bar, its width is how far the current item has smelted
+#[derive(Component)]
+pub struct FurnaceProgressFill;
+
+/// Chat lines shown above the hotbar (all recent ones while the console is open)
+#[derive(Component)]
+pub struct ChatMessages;
+
+/// Ways to complete the command being typed
+#[derive(Component)]
+pub struct ChatSuggestions;
+
+/// The console's input line, only shown while it's open
+#[derive(Component)]
+pub struct ChatInput;
+
 #[derive(Component)]
 pub struct ChunkBorder;
 
+/// The wireframe box around the local player's selection
+#[derive(Component)]
+pub struct SelectionOutline;
+
 #[derive(Component)]
 pub struct BlockHighlight;
 
@@ -50,6 +117,24 @@ pub struct HotbarSlotText {
     pub slot_index: usize,
 }
 
+/// Stack size shown in the corner of a hotbar slot
+#[derive(Component)]
+pub struct HotbarSlotCount {
+    pub slot_index: usize,
+}
+
+/// Durability bar along the bottom of a hotbar slot (only shown for worn tools)
+#[derive(Component)]
+pub struct HotbarSlotDurability {
+    pub slot_index: usize,
+}
+
+/// The colored part of a durability bar, its width is the durability left
+#[derive(Component)]
+pub struct HotbarSlotDurabilityFill {
+    pub slot_index: usize,
+}
+
 /// Container for health and hunger bars (survival mode only)
 #[derive(Component)]
 pub struct SurvivalBars;
@@ -81,3 +166,14 @@ pub struct HungerIcon {
     /// Hunger icon index (0-9 for 10 drumsticks)
     pub index: usize,
 }
+
+/// Individual air bubble in the air meter (shown while the head is underwater)
+#[derive(Component)]
+pub struct AirIcon {
+    /// Bubble index (0-9 for 10 bubbles)
+    pub index: usize,
+}
+
+/// Full-screen red tint flashed when the player takes damage
+#[derive(Component)]
+pub struct HurtOverlay;
@@ -0,0 +1,202 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+use crate::blocks::BlockRegistry;
+use crate::items::{new_stack, resolve_item, ItemRegistry};
+use super::recipe::{CraftingRecipe, Ingredient, RecipeShape, SmeltingRecipe};
+
+/// Largest crafting grid (the crafting table)
+pub const MAX_GRID_SIZE: usize = 3;
+
+/// World ticks a furnace takes to smelt one item unless the recipe says otherwise (10 seconds)
+pub const DEFAULT_COOK_TICKS: u32 = 200;
+
+/// A recipe data file (`recipes.ron`): tags first, then the recipes that may use them
+/// Items, blocks and tags are named by their string IDs; tags are prefixed with `#`
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct RecipeFile {
+    pub tags: HashMap<String, Vec<String>>,
+    pub recipes: Vec<RecipeDef>,
+    pub smelting: Vec<SmeltingDef>,
+}
+
+impl RecipeFile {
+    pub fn parse(source: &str) -> Result<Self, String> {
+        ron::from_str(source).map_err(|error| format!("Invalid recipe file: {}", error))
+    }
+}
+
+/// What a recipe makes
+#[derive(Debug, Clone, Deserialize)]
+pub struct ResultDef {
+    pub item: String,
+    #[serde(default = "one")]
+    pub count: u8,
+}
+
+fn one() -> u8 {
+    1
+}
+
+fn default_cook_ticks() -> u32 {
+    DEFAULT_COOK_TICKS
+}
+
+/// A furnace recipe as written by a mod or data file: one input item (or `#tag`) per result
+/// `SmeltingDef::new("core:iron_ore", "core:iron_ingot", 1).cook_ticks(100)`
+#[derive(Debug, Clone, Deserialize)]
+pub struct SmeltingDef {
+    pub input: String,
+    pub result: ResultDef,
+    #[serde(default = "default_cook_ticks")]
+    pub cook_ticks: u32,
+}
+
+impl SmeltingDef {
+    pub fn new(input: &str, result: &str, count: u8) -> Self {
+        Self {
+            input: input.to_string(),
+            result: ResultDef { item: result.to_string(), count },
+            cook_ticks: DEFAULT_COOK_TICKS,
+        }
+    }
+
+    pub fn cook_ticks(mut self, ticks: u32) -> Self {
+        self.cook_ticks = ticks;
+        self
+    }
+
+    /// Turn string IDs into items; `ingredient` resolves single items and tags
+    pub(super) fn resolve(
+        self,
+        ingredient: impl Fn(&str) -> Result<Ingredient, String>,
+        items: &ItemRegistry,
+        blocks: &BlockRegistry,
+    ) -> Result<SmeltingRecipe, String> {
+        if self.result.count == 0 {
+            return Err(format!("Smelting recipe for '{}' makes nothing", self.result.item));
+        }
+        if self.cook_ticks == 0 {
+            return Err(format!("Smelting recipe for '{}' needs a cook time", self.result.item));
+        }
+        let input = ingredient(&self.input)?;
+        let item = resolve_item(&self.result.item, items, blocks)?;
+        Ok(SmeltingRecipe {
+            input,
+            result: new_stack(item, self.result.count, items),
+            cook_ticks: self.cook_ticks,
+        })
+    }
+}
+
+/// A recipe as written by a mod or data file, before its IDs are resolved
+#[derive(Debug, Clone, Deserialize)]
+pub enum RecipeDef {
+    /// Rows of characters, each mapped to an ingredient through `key`; spaces are empty slots
+    Shaped {
+        pattern: Vec<String>,
+        #[serde(default)]
+        key: HashMap<char, String>,
+        result: ResultDef,
+    },
+    Shapeless {
+        ingredients: Vec<String>,
+        result: ResultDef,
+    },
+}
+
+/// Builder for recipes registered from code
+/// `RecipeDef::shaped(&["##", "##"]).key('#', "#core:planks").result("core:crafting_table", 1)`
+pub struct RecipeBuilder {
+    shape: BuilderShape,
+}
+
+enum BuilderShape {
+    Shaped { pattern: Vec<String>, key: HashMap<char, String> },
+    Shapeless(Vec<String>),
+}
+
+impl RecipeDef {
+    pub fn shaped(pattern: &[&str]) -> RecipeBuilder {
+        RecipeBuilder {
+            shape: BuilderShape::Shaped {
+                pattern: pattern.iter().map(|row| row.to_string()).collect(),
+                key: HashMap::new(),
+            },
+        }
+    }
+
+    pub fn shapeless(ingredients: &[&str]) -> RecipeBuilder {
+        RecipeBuilder {
+            shape: BuilderShape::Shapeless(ingredients.iter().map(|id| id.to_string()).collect()),
+        }
+    }
+
+    /// Turn string IDs into items; `ingredient` resolves single items and tags
+    pub(super) fn resolve(
+        self,
+        ingredient: impl Fn(&str) -> Result<Ingredient, String>,
+        items: &ItemRegistry,
+        blocks: &BlockRegistry,
+    ) -> Result<CraftingRecipe, String> {
+        let (shape, result) = match self {
+            RecipeDef::Shaped { pattern, key, result } => {
+                let height = pattern.len();
+                let width = pattern.iter().map(|row| row.chars().count()).max().unwrap_or(0);
+                if width == 0 || width > MAX_GRID_SIZE || height > MAX_GRID_SIZE {
+                    return Err(format!("Recipe for '{}' has an invalid {}x{} pattern", result.item, width, height));
+                }
+
+                let mut cells = Vec::with_capacity(width * height);
+                for row in &pattern {
+                    let mut chars = row.chars();
+                    for _ in 0..width {
+                        let cell = match chars.next() {
+                            None | Some(' ') => None,
+                            Some(symbol) => {
+                                let id = key.get(&symbol)
+                                    .ok_or_else(|| format!("Recipe for '{}' has no key for '{}'", result.item, symbol))?;
+                                Some(ingredient(id)?)
+                            }
+                        };
+                        cells.push(cell);
+                    }
+                }
+                (RecipeShape::Shaped { width, height, cells }, result)
+            }
+            RecipeDef::Shapeless { ingredients, result } => {
+                if ingredients.is_empty() || ingredients.len() > MAX_GRID_SIZE * MAX_GRID_SIZE {
+                    return Err(format!("Recipe for '{}' needs 1-9 ingredients", result.item));
+                }
+                let resolved = ingredients.iter()
+                    .map(|id| ingredient(id))
+                    .collect::<Result<Vec<_>, _>>()?;
+                (RecipeShape::Shapeless(resolved), result)
+            }
+        };
+
+        if result.count == 0 {
+            return Err(format!("Recipe for '{}' makes nothing", result.item));
+        }
+        let item = resolve_item(&result.item, items, blocks)?;
+        Ok(CraftingRecipe { shape, result: new_stack(item, result.count, items) })
+    }
+}
+
+impl RecipeBuilder {
+    /// Map a pattern character to an item or `#tag` (shaped recipes only)
+    pub fn key(mut self, symbol: char, ingredient: &str) -> Self {
+        if let BuilderShape::Shaped { key, .. } = &mut self.shape {
+            key.insert(symbol, ingredient.to_string());
+        }
+        self
+    }
+
+    pub fn result(self, item: &str, count: u8) -> RecipeDef {
+        let result = ResultDef { item: item.to_string(), count };
+        match self.shape {
+            BuilderShape::Shaped { pattern, key } => RecipeDef::Shaped { pattern, key, result },
+            BuilderShape::Shapeless(ingredients) => RecipeDef::Shapeless { ingredients, result },
+        }
+    }
+}
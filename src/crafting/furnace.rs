@@ -0,0 +1,218 @@
+use crate::resources::{take_one, HotbarItem, ItemStack};
+use super::recipe::RecipeRegistry;
+
+/// The three slots of a furnace
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FurnaceSlot {
+    Input,
+    Fuel,
+    Output,
+}
+
+/// A furnace's contents and progress (its block entity)
+/// Ticked once per world tick whether or not anyone has its screen open
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Furnace {
+    pub input: Option<ItemStack>,
+    pub fuel: Option<ItemStack>,
+    pub output: Option<ItemStack>,
+    /// World ticks left on the fuel item currently burning (lit while above zero)
+    pub burn_ticks: u32,
+    /// Burn time of the fuel item currently burning, for the flame gauge
+    pub burn_ticks_total: u32,
+    /// World ticks spent smelting the current input item
+    pub cook_ticks: u32,
+    /// Cook time of the current input's recipe, for the progress arrow (0 when idle)
+    pub cook_ticks_total: u32,
+}
+
+impl Furnace {
+    pub fn is_lit(&self) -> bool {
+        self.burn_ticks > 0
+    }
+
+    pub fn slot(&self, slot: FurnaceSlot) -> Option<ItemStack> {
+        match slot {
+            FurnaceSlot::Input => self.input,
+            FurnaceSlot::Fuel => self.fuel,
+            FurnaceSlot::Output => self.output,
+        }
+    }
+
+    pub fn slot_mut(&mut self, slot: FurnaceSlot) -> &mut Option<ItemStack> {
+        match slot {
+            FurnaceSlot::Input => &mut self.input,
+            FurnaceSlot::Fuel => &mut self.fuel,
+            FurnaceSlot::Output => &mut self.output,
+        }
+    }
+
+    /// Fraction of the current fuel item left (0 when out)
+    pub fn burn_fraction(&self) -> f32 {
+        if self.burn_ticks_total == 0 {
+            return 0.0;
+        }
+        self.burn_ticks as f32 / self.burn_ticks_total as f32
+    }
+
+    /// Fraction of the current item smelted so far
+    pub fn cook_fraction(&self) -> f32 {
+        if self.cook_ticks_total == 0 {
+            return 0.0;
+        }
+        self.cook_ticks as f32 / self.cook_ticks_total as f32
+    }
+
+    /// Put one item from `held` in the input or fuel slot if it's empty or holds the same
+    /// item with room to spare (the output slot only takes items out)
+    pub fn place_one(&mut self, slot: FurnaceSlot, held: ItemStack) -> bool {
+        if slot == FurnaceSlot::Output {
+            return false;
+        }
+        let one = ItemStack { count: 1, ..held };
+        match self.slot_mut(slot) {
+            target @ None => {
+                *target = Some(one);
+                true
+            }
+            Some(stack) if stack.stacks_with(&one) && stack.space() > 0 => {
+                stack.count += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Every stack in the furnace, emptying it (for dropping when the furnace is broken)
+    pub fn take_contents(&mut self) -> Vec<ItemStack> {
+        [self.input.take(), self.fuel.take(), self.output.take()].into_iter().flatten().collect()
+    }
+
+    /// Advance one world tick
+    ///
+    /// Fuel burns down by one tick; a new fuel item is only lit when there's something to
+    /// smelt and room for the result. While lit, the input cooks and each finished item
+    /// moves to the output. Without heat, progress cools back down twice as fast
+    pub fn tick(&mut self, recipes: &RecipeRegistry, fuel_ticks: impl Fn(HotbarItem) -> Option<u32>) {
+        let recipe = self.input
+            .and_then(|input| recipes.find_smelting(input.item))
+            .filter(|recipe| self.output_accepts(&recipe.result));
+
+        self.burn_ticks = self.burn_ticks.saturating_sub(1);
+
+        let Some(recipe) = recipe else {
+            self.cook_ticks = 0;
+            self.cook_ticks_total = 0;
+            return;
+        };
+        self.cook_ticks_total = recipe.cook_ticks;
+
+        if !self.is_lit() {
+            if let Some(ticks) = self.fuel.and_then(|fuel| fuel_ticks(fuel.item)) {
+                take_one(&mut self.fuel);
+                self.burn_ticks = ticks;
+                self.burn_ticks_total = ticks;
+            }
+        }
+
+        if !self.is_lit() {
+            self.cook_ticks = self.cook_ticks.saturating_sub(2);
+            return;
+        }
+
+        self.cook_ticks += 1;
+        if self.cook_ticks >= recipe.cook_ticks {
+            self.cook_ticks = 0;
+            take_one(&mut self.input);
+            match &mut self.output {
+                Some(output) => output.count += recipe.result.count,
+                output @ None => *output = Some(recipe.result),
+            }
+        }
+    }
+
+    /// Whether the output slot has room for another `result`
+    fn output_accepts(&self, result: &ItemStack) -> bool {
+        match self.output {
+            None => true,
+            Some(output) => output.stacks_with(result) && output.space() >= result.count,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blocks::{BlockRegistry, BlockType};
+    use crate::crafting::SmeltingDef;
+    use crate::items::{ItemRegistry, ItemType};
+
+    struct Setup {
+        recipes: RecipeRegistry,
+        ore: HotbarItem,
+        ingot: HotbarItem,
+        coal: HotbarItem,
+    }
+
+    fn setup() -> Setup {
+        let mut blocks = BlockRegistry::new();
+        let ore = blocks.register_block(BlockType::builder("test:ore", "Ore").build()).unwrap();
+        let mut items = ItemRegistry::new();
+        let ingot = items.register_item(ItemType::builder("test:ingot", "Ingot").build()).unwrap();
+        let coal = items.register_item(ItemType::builder("test:coal", "Coal").fuel(10).build()).unwrap();
+
+        let mut recipes = RecipeRegistry::new();
+        recipes.register_smelting(SmeltingDef::new("test:ore", "test:ingot", 1).cook_ticks(4), &items, &blocks).unwrap();
+        Setup { recipes, ore: HotbarItem::Block(ore), ingot: HotbarItem::Item(ingot), coal: HotbarItem::Item(coal) }
+    }
+
+    fn run(furnace: &mut Furnace, setup: &Setup, ticks: u32) {
+        for _ in 0..ticks {
+            furnace.tick(&setup.recipes, |item| (item == setup.coal).then_some(10));
+        }
+    }
+
+    #[test]
+    fn smelts_while_fuel_lasts() {
+        let setup = setup();
+        let mut furnace = Furnace {
+            input: Some(ItemStack::new(setup.ore, 5)),
+            fuel: Some(ItemStack::new(setup.coal, 1)),
+            ..Default::default()
+        };
+
+        run(&mut furnace, &setup, 1);
+        assert!(furnace.is_lit());
+        assert_eq!(furnace.fuel, None, "lighting uses up a fuel item");
+
+        // 10 ticks of fuel at 4 ticks per item smelts two items
+        run(&mut furnace, &setup, 9);
+        assert_eq!(furnace.output, Some(ItemStack::new(setup.ingot, 2)));
+        assert_eq!(furnace.input.unwrap().count, 3);
+        assert_eq!(furnace.cook_ticks, 2);
+
+        // Out of fuel: goes out and progress cools off
+        run(&mut furnace, &setup, 1);
+        assert!(!furnace.is_lit());
+        assert_eq!(furnace.cook_ticks, 0);
+        run(&mut furnace, &setup, 20);
+        assert_eq!(furnace.output.unwrap().count, 2);
+    }
+
+    #[test]
+    fn keeps_fuel_when_there_is_nothing_to_smelt() {
+        let setup = setup();
+        let mut furnace = Furnace { fuel: Some(ItemStack::new(setup.coal, 2)), ..Default::default() };
+        run(&mut furnace, &setup, 5);
+        assert!(!furnace.is_lit());
+        assert_eq!(furnace.fuel.unwrap().count, 2);
+
+        // A full output slot blocks smelting the same way
+        furnace.input = Some(ItemStack::new(setup.ore, 1));
+        furnace.output = Some(ItemStack::new(setup.ingot, 64));
+        run(&mut furnace, &setup, 5);
+        assert!(!furnace.is_lit());
+        assert_eq!(furnace.fuel.unwrap().count, 2);
+        assert_eq!(furnace.input.unwrap().count, 1);
+    }
+}
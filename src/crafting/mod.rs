@@ -0,0 +1,67 @@
+mod data;
+mod recipe;
+mod furnace;
+
+// RecipeDef and SmeltingDef are the builders mods use in `GameMod::register_recipes`
+pub use data::{RecipeDef, SmeltingDef, MAX_GRID_SIZE};
+pub use recipe::RecipeRegistry;
+pub use furnace::{Furnace, FurnaceSlot};
+
+use bevy::prelude::*;
+use data::RecipeFile;
+use std::path::PathBuf;
+use crate::blocks::BlockRegistry;
+use crate::items::ItemRegistry;
+
+/// Load a mod's recipe data file into the registry
+///
+/// Search order:
+/// 1. assets/mods/{mod_id}/recipes.ron
+/// 2. Embedded mod data (if provided)
+///
+/// A broken file is logged and skipped rather than stopping the game
+pub fn load_mod_recipes(
+    mod_id: &str,
+    embedded: Option<&str>,
+    recipes: &mut RecipeRegistry,
+    items: &ItemRegistry,
+    blocks: &BlockRegistry,
+) {
+    let path = PathBuf::from("assets").join("mods").join(mod_id).join("recipes.ron");
+    let source = match std::fs::read_to_string(&path) {
+        Ok(source) => {
+            info!("  -> Loading recipes from {:?}", path);
+            source
+        }
+        Err(_) => match embedded {
+            Some(source) => source.to_string(),
+            None => return,
+        },
+    };
+
+    match RecipeFile::parse(&source).and_then(|file| recipes.load_file(file, items, blocks)) {
+        Ok(count) => info!("  -> Loaded {} recipes for mod '{}'", count, mod_id),
+        Err(error) => warn!("Failed to load recipes for mod '{}': {}", mod_id, error),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mods::{GameMod, VanillaMod};
+
+    #[test]
+    fn core_recipes_resolve_against_core_content() {
+        let mut blocks = BlockRegistry::new();
+        VanillaMod.register_blocks(&mut blocks);
+        let mut items = ItemRegistry::new();
+        VanillaMod.register_items(&mut items);
+
+        let file = RecipeFile::parse(VanillaMod.get_embedded_recipes().unwrap()).unwrap();
+        let mut recipes = RecipeRegistry::new();
+        let count = recipes.load_file(file, &items, &blocks).unwrap();
+        assert_eq!(recipes.crafting_recipes().len() + recipes.smelting_recipes().len(), count);
+        assert!(!recipes.smelting_recipes().is_empty());
+        assert!(recipes.tag("core:planks").is_some());
+    }
+}
@@ -0,0 +1,407 @@
+use bevy::prelude::*;
+use std::collections::HashMap;
+use crate::blocks::BlockRegistry;
+use crate::items::{resolve_item, ItemRegistry};
+use crate::resources::{HotbarItem, ItemStack};
+use super::data::{RecipeDef, RecipeFile, SmeltingDef};
+
+/// Prefix that marks an ingredient as a tag (`#core:planks`) instead of a single item
+pub const TAG_PREFIX: char = '#';
+
+/// One recipe slot: the set of items that are accepted there
+/// Tags are expanded when the recipe is registered and kept in step as the tag grows,
+/// so matching is a plain lookup
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ingredient {
+    items: Vec<HotbarItem>,
+    /// The tag the items came from, if any
+    tag: Option<String>,
+}
+
+impl Ingredient {
+    pub fn of(items: Vec<HotbarItem>) -> Self {
+        Self { items, tag: None }
+    }
+
+    fn tagged(tag: &str, items: Vec<HotbarItem>) -> Self {
+        Self { items, tag: Some(tag.to_string()) }
+    }
+
+    pub fn matches(&self, item: HotbarItem) -> bool {
+        self.items.contains(&item)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RecipeShape {
+    /// Fixed layout, row-major; None cells must stay empty
+    /// Matches anywhere in the grid, and also mirrored left-to-right
+    Shaped { width: usize, height: usize, cells: Vec<Option<Ingredient>> },
+    /// Every ingredient exactly once, in any slot
+    Shapeless(Vec<Ingredient>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CraftingRecipe {
+    pub shape: RecipeShape,
+    pub result: ItemStack,
+}
+
+impl CraftingRecipe {
+    /// Whether this recipe matches a square crafting grid of side `size` (row-major slots)
+    pub fn matches(&self, size: usize, grid: &[Option<HotbarItem>]) -> bool {
+        match &self.shape {
+            RecipeShape::Shaped { width, height, cells } => matches_shaped(*width, *height, cells, size, grid),
+            RecipeShape::Shapeless(ingredients) => {
+                let items: Vec<HotbarItem> = grid.iter().flatten().copied().collect();
+                items.len() == ingredients.len() && assign_shapeless(ingredients, &items, &mut vec![false; items.len()])
+            }
+        }
+    }
+}
+
+/// A furnace recipe: one input item smelts into the result over `cook_ticks` world ticks
+#[derive(Debug, Clone, PartialEq)]
+pub struct SmeltingRecipe {
+    pub input: Ingredient,
+    pub result: ItemStack,
+    pub cook_ticks: u32,
+}
+
+/// Compare the occupied bounding box of the grid against the pattern, as is and mirrored
+fn matches_shaped(width: usize, height: usize, cells: &[Option<Ingredient>], size: usize, grid: &[Option<HotbarItem>]) -> bool {
+    let occupied = (0..size * size).filter(|&i| grid[i].is_some());
+    let (mut min_x, mut min_y, mut max_x, mut max_y) = (size, size, 0, 0);
+    let mut any = false;
+    for i in occupied {
+        let (x, y) = (i % size, i / size);
+        min_x = min_x.min(x);
+        min_y = min_y.min(y);
+        max_x = max_x.max(x);
+        max_y = max_y.max(y);
+        any = true;
+    }
+    if !any || max_x - min_x + 1 != width || max_y - min_y + 1 != height {
+        return false;
+    }
+
+    let fits = |mirrored: bool| {
+        (0..height).all(|y| {
+            (0..width).all(|x| {
+                let pattern_x = if mirrored { width - 1 - x } else { x };
+                let item = grid[(min_y + y) * size + min_x + x];
+                match (&cells[y * width + pattern_x], item) {
+                    (None, None) => true,
+                    (Some(ingredient), Some(item)) => ingredient.matches(item),
+                    _ => false,
+                }
+            })
+        })
+    };
+    fits(false) || fits(true)
+}
+
+/// Backtracking match of items to ingredients (ingredients can overlap through tags)
+fn assign_shapeless(ingredients: &[Ingredient], items: &[HotbarItem], used: &mut [bool]) -> bool {
+    let Some((ingredient, rest)) = ingredients.split_first() else {
+        return true;
+    };
+    for i in 0..items.len() {
+        if !used[i] && ingredient.matches(items[i]) {
+            used[i] = true;
+            if assign_shapeless(rest, items, used) {
+                return true;
+            }
+            used[i] = false;
+        }
+    }
+    false
+}
+
+/// Every crafting and smelting recipe and item tag, filled in by mods at startup
+#[derive(Resource, Default)]
+pub struct RecipeRegistry {
+    crafting: Vec<CraftingRecipe>,
+    smelting: Vec<SmeltingRecipe>,
+    /// Tag name (without the `#`) to the items it contains
+    tags: HashMap<String, Vec<HotbarItem>>,
+}
+
+impl RecipeRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add items to a tag, creating it if needed (several mods can extend one tag)
+    /// Recipes already registered with the tag accept the new items too
+    pub fn add_to_tag(&mut self, tag: &str, ids: &[&str], items: &ItemRegistry, blocks: &BlockRegistry) -> Result<(), String> {
+        let resolved = ids.iter()
+            .map(|id| resolve_item(id, items, blocks))
+            .collect::<Result<Vec<_>, _>>()?;
+        let entry = self.tags.entry(tag.to_string()).or_default();
+        for item in resolved {
+            if !entry.contains(&item) {
+                entry.push(item);
+            }
+        }
+
+        let members = entry.clone();
+        for ingredient in self.ingredients_mut().filter(|ingredient| ingredient.tag.as_deref() == Some(tag)) {
+            ingredient.items.clone_from(&members);
+        }
+        Ok(())
+    }
+
+    /// Resolve a recipe's string IDs and add it
+    /// Fails if an item, block or tag it names doesn't exist
+    pub fn register(&mut self, def: RecipeDef, items: &ItemRegistry, blocks: &BlockRegistry) -> Result<(), String> {
+        let recipe = def.resolve(|id| self.resolve_ingredient(id, items, blocks), items, blocks)?;
+        self.crafting.push(recipe);
+        Ok(())
+    }
+
+    /// Resolve a smelting recipe's string IDs and add it
+    pub fn register_smelting(&mut self, def: SmeltingDef, items: &ItemRegistry, blocks: &BlockRegistry) -> Result<(), String> {
+        let recipe = def.resolve(|id| self.resolve_ingredient(id, items, blocks), items, blocks)?;
+        self.smelting.push(recipe);
+        Ok(())
+    }
+
+    /// Register every tag and recipe in a data file, returning how many recipes were added
+    /// Tags come first so the file's recipes can use them
+    pub fn load_file(&mut self, file: RecipeFile, items: &ItemRegistry, blocks: &BlockRegistry) -> Result<usize, String> {
+        let mut tags: Vec<_> = file.tags.into_iter().collect();
+        tags.sort_by(|a, b| a.0.cmp(&b.0));
+        for (tag, ids) in tags {
+            let ids: Vec<&str> = ids.iter().map(String::as_str).collect();
+            self.add_to_tag(&tag, &ids, items, blocks)?;
+        }
+
+        let count = file.recipes.len() + file.smelting.len();
+        for recipe in file.recipes {
+            self.register(recipe, items, blocks)?;
+        }
+        for recipe in file.smelting {
+            self.register_smelting(recipe, items, blocks)?;
+        }
+        Ok(count)
+    }
+
+    /// First recipe matching a square grid of side `size`
+    pub fn find_crafting(&self, size: usize, grid: &[Option<HotbarItem>]) -> Option<&CraftingRecipe> {
+        self.crafting.iter().find(|recipe| recipe.matches(size, grid))
+    }
+
+    pub fn crafting_recipes(&self) -> &[CraftingRecipe] {
+        &self.crafting
+    }
+
+    /// First smelting recipe that accepts `item` as its input
+    pub fn find_smelting(&self, item: HotbarItem) -> Option<&SmeltingRecipe> {
+        self.smelting.iter().find(|recipe| recipe.input.matches(item))
+    }
+
+    pub fn smelting_recipes(&self) -> &[SmeltingRecipe] {
+        &self.smelting
+    }
+
+    pub fn tag(&self, tag: &str) -> Option<&[HotbarItem]> {
+        self.tags.get(tag).map(Vec::as_slice)
+    }
+
+    /// Every ingredient of every crafting and smelting recipe
+    fn ingredients_mut(&mut self) -> impl Iterator<Item = &mut Ingredient> {
+        let crafting = self.crafting.iter_mut().flat_map(|recipe| -> Vec<&mut Ingredient> {
+            match &mut recipe.shape {
+                RecipeShape::Shaped { cells, .. } => cells.iter_mut().flatten().collect(),
+                RecipeShape::Shapeless(ingredients) => ingredients.iter_mut().collect(),
+            }
+        });
+        crafting.chain(self.smelting.iter_mut().map(|recipe| &mut recipe.input))
+    }
+
+    fn resolve_ingredient(&self, id: &str, items: &ItemRegistry, blocks: &BlockRegistry) -> Result<Ingredient, String> {
+        match id.strip_prefix(TAG_PREFIX) {
+            Some(tag) => self.tags.get(tag)
+                .map(|items| Ingredient::tagged(tag, items.clone()))
+                .ok_or_else(|| format!("Unknown tag '{}'", tag)),
+            None => resolve_item(id, items, blocks).map(|item| Ingredient::of(vec![item])),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blocks::BlockType;
+    use crate::items::{ItemType, ToolKind, ToolTier};
+    use crate::crafting::data::DEFAULT_COOK_TICKS;
+
+    fn registry() -> (RecipeRegistry, ItemRegistry, BlockRegistry) {
+        let mut blocks = BlockRegistry::new();
+        for id in ["test:oak_planks", "test:birch_planks", "test:log", "test:table", "test:stone"] {
+            blocks.register_block(BlockType::builder(id, id).build()).unwrap();
+        }
+        let mut items = ItemRegistry::new();
+        items.register_item(ItemType::builder("test:stick", "Stick").build()).unwrap();
+        items.register_item(ItemType::builder("test:pickaxe", "Pickaxe").tool(ToolKind::Pickaxe, ToolTier::Wood).build()).unwrap();
+
+        let mut recipes = RecipeRegistry::new();
+        recipes.add_to_tag("test:planks", &["test:oak_planks", "test:birch_planks"], &items, &blocks).unwrap();
+        (recipes, items, blocks)
+    }
+
+    fn block(blocks: &BlockRegistry, id: &str) -> Option<HotbarItem> {
+        Some(HotbarItem::Block(blocks.get_id(id).unwrap()))
+    }
+
+    #[test]
+    fn shaped_recipe_matches_anywhere_in_the_grid() {
+        let (mut recipes, items, blocks) = registry();
+        recipes.register(
+            RecipeDef::shaped(&["#", "#"]).key('#', "#test:planks").result("test:stick", 4),
+            &items,
+            &blocks,
+        ).unwrap();
+
+        let oak = block(&blocks, "test:oak_planks");
+        let birch = block(&blocks, "test:birch_planks");
+
+        // Bottom-right column of a 3x3 grid, mixing planks through the tag
+        let grid = [None, None, None, None, None, oak, None, None, birch];
+        let recipe = recipes.find_crafting(3, &grid).expect("sticks should match");
+        let stick = items.get_id("test:stick").unwrap();
+        assert_eq!(recipe.result, ItemStack::new(HotbarItem::Item(stick), 4));
+
+        // Same pattern in a 2x2 grid
+        assert!(recipes.find_crafting(2, &[oak, None, oak, None]).is_some());
+
+        // Side by side is a different shape, and a stray extra block breaks the match
+        assert!(recipes.find_crafting(2, &[oak, oak, None, None]).is_none());
+        assert!(recipes.find_crafting(2, &[oak, oak, oak, None]).is_none());
+    }
+
+    #[test]
+    fn shaped_recipe_matches_mirrored() {
+        let (mut recipes, items, blocks) = registry();
+        // An L shape: the mirrored version should craft the same thing
+        recipes.register(
+            RecipeDef::shaped(&["##", "# ", "# "]).key('#', "test:stone").result("test:table", 1),
+            &items,
+            &blocks,
+        ).unwrap();
+
+        let s = block(&blocks, "test:stone");
+        let normal = [s, s, None, s, None, None, s, None, None];
+        let mirrored = [None, s, s, None, None, s, None, None, s];
+        let flipped = [s, None, None, s, None, None, s, s, None];
+        assert!(recipes.find_crafting(3, &normal).is_some());
+        assert!(recipes.find_crafting(3, &mirrored).is_some());
+        assert!(recipes.find_crafting(3, &flipped).is_none(), "upside down is not a mirror");
+
+        // Too tall for the 2x2 grid
+        assert!(recipes.find_crafting(2, &[s, s, s, None]).is_none());
+    }
+
+    #[test]
+    fn shapeless_recipe_ignores_positions_but_counts_items() {
+        let (mut recipes, items, blocks) = registry();
+        recipes.register(
+            RecipeDef::shapeless(&["test:log", "#test:planks"]).result("test:table", 1),
+            &items,
+            &blocks,
+        ).unwrap();
+
+        let log = block(&blocks, "test:log");
+        let birch = block(&blocks, "test:birch_planks");
+        assert!(recipes.find_crafting(3, &[None, None, birch, None, None, None, log, None, None]).is_some());
+        assert!(recipes.find_crafting(2, &[birch, log, None, None]).is_some());
+        assert!(recipes.find_crafting(2, &[birch, log, log, None]).is_none());
+        assert!(recipes.find_crafting(2, &[log, None, None, None]).is_none());
+    }
+
+    #[test]
+    fn tags_extended_later_reach_registered_recipes() {
+        let (mut recipes, items, blocks) = registry();
+        recipes.register(
+            RecipeDef::shaped(&["#", "#"]).key('#', "#test:planks").result("test:stick", 4),
+            &items,
+            &blocks,
+        ).unwrap();
+        recipes.register_smelting(SmeltingDef::new("#test:planks", "test:stone", 1), &items, &blocks).unwrap();
+
+        let table = block(&blocks, "test:table");
+        assert!(recipes.find_crafting(2, &[table, None, table, None]).is_none());
+
+        // Another mod adds to the tag after the recipes were registered
+        recipes.add_to_tag("test:planks", &["test:table"], &items, &blocks).unwrap();
+        assert!(recipes.find_crafting(2, &[table, None, table, None]).is_some());
+        assert!(recipes.find_smelting(table.unwrap()).is_some());
+        let oak = block(&blocks, "test:oak_planks");
+        assert!(recipes.find_crafting(2, &[oak, None, table, None]).is_some());
+        assert_eq!(recipes.tag("test:planks").unwrap().len(), 3);
+    }
+
+    #[test]
+    fn unknown_ids_are_rejected() {
+        let (mut recipes, items, blocks) = registry();
+        assert!(recipes.register(RecipeDef::shapeless(&["test:nope"]).result("test:table", 1), &items, &blocks).is_err());
+        assert!(recipes.register(RecipeDef::shapeless(&["#test:nope"]).result("test:table", 1), &items, &blocks).is_err());
+        assert!(recipes.register(RecipeDef::shapeless(&["test:log"]).result("core:air", 1), &items, &blocks).is_err());
+        assert!(recipes.register(RecipeDef::shaped(&["#"]).result("test:table", 1), &items, &blocks).is_err());
+    }
+
+    #[test]
+    fn crafted_tools_start_at_full_durability() {
+        let (mut recipes, items, blocks) = registry();
+        recipes.register(
+            RecipeDef::shaped(&["###", " | ", " | "]).key('#', "#test:planks").key('|', "test:stick").result("test:pickaxe", 1),
+            &items,
+            &blocks,
+        ).unwrap();
+
+        let result = recipes.crafting_recipes()[0].result;
+        assert_eq!(result.durability, Some(ToolTier::Wood.durability()));
+        assert_eq!(result.max_count(), 1);
+    }
+
+    #[test]
+    fn data_file_loads_tags_before_recipes() {
+        let (mut recipes, items, blocks) = registry();
+        let file = RecipeFile::parse(r###"(
+            tags: { "test:logs": ["test:log"] },
+            recipes: [
+                Shapeless(ingredients: ["#test:logs"], result: (item: "test:oak_planks", count: 4)),
+                Shaped(pattern: ["##", "##"], key: { '#': "#test:planks" }, result: (item: "test:table")),
+            ],
+        )"###).unwrap();
+        assert_eq!(recipes.load_file(file, &items, &blocks), Ok(2));
+
+        let log = block(&blocks, "test:log");
+        let recipe = recipes.find_crafting(2, &[None, None, None, log]).unwrap();
+        assert_eq!(recipe.result.count, 4);
+        let oak = block(&blocks, "test:oak_planks");
+        assert_eq!(recipes.find_crafting(2, &[oak; 4]).unwrap().result.count, 1);
+    }
+
+    #[test]
+    fn smelting_recipes_load_from_data_files() {
+        let (mut recipes, items, blocks) = registry();
+        let file = RecipeFile::parse(r###"(
+            smelting: [
+                (input: "#test:planks", result: (item: "test:stick", count: 2)),
+                (input: "test:log", result: (item: "test:stone"), cook_ticks: 100),
+            ],
+        )"###).unwrap();
+        assert_eq!(recipes.load_file(file, &items, &blocks), Ok(2));
+
+        let birch = block(&blocks, "test:birch_planks").unwrap();
+        let recipe = recipes.find_smelting(birch).expect("tagged input should smelt");
+        assert_eq!(recipe.result.count, 2);
+        assert_eq!(recipe.cook_ticks, DEFAULT_COOK_TICKS);
+
+        let log = block(&blocks, "test:log").unwrap();
+        assert_eq!(recipes.find_smelting(log).unwrap().cook_ticks, 100);
+        assert!(recipes.find_smelting(block(&blocks, "test:stone").unwrap()).is_none());
+    }
+}
@@ -0,0 +1,246 @@
+use bevy::prelude::*;
+use rand::Rng;
+use crate::components::PLAYER_EYE_HEIGHT;
+
+/// Ticks a mob keeps walking toward a target it can't reach before picking another
+const GIVE_UP_TICKS: u32 = 200;
+
+/// Horizontal distance at which a walk target counts as reached
+const ARRIVE_DISTANCE: f32 = 0.5;
+
+/// How far a panicking mob runs before picking a new spot to run to
+const FLEE_DISTANCE: f32 = 6.0;
+
+/// How close to the player's eyes a melee attacker has to get to hit, and the ticks between hits
+const MELEE_REACH: f32 = 2.0;
+const MELEE_COOLDOWN_TICKS: u32 = 20;
+
+/// A piece of mob AI; an entity type lists them in priority order
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Behavior {
+    /// Swim up while in a liquid, so the mob keeps its head above water
+    Swim,
+    /// Run from whatever hurt it for `ticks` world ticks, at `speed` times the walk speed
+    Panic { ticks: u32, speed: f32 },
+    /// While idle, walk to a random spot up to `range` blocks away, on average once every `chance` ticks
+    Wander { chance: u32, range: f32 },
+    /// Chase a player within `range` blocks and hit them for `damage` when in reach
+    MeleeAttack { range: f32, damage: u8 },
+    /// Turn to watch a player within `range` blocks while standing still
+    LookAtPlayer { range: f32 },
+}
+
+/// What a mob knows about its surroundings this tick
+#[derive(Debug, Clone, Copy)]
+pub struct Senses {
+    /// Feet position
+    pub position: Vec3,
+    pub in_liquid: bool,
+    /// Walking into a wall last tick
+    pub blocked: bool,
+    /// Eye position of the nearest player
+    pub player: Option<Vec3>,
+}
+
+/// What the AI wants the body to do this tick
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Steering {
+    /// Where to walk to (the path there is up to the navigation), None to stand still
+    pub goal: Option<Vec3>,
+    /// Multiple of the entity's walk speed
+    pub speed: f32,
+    pub jump: bool,
+    /// Point to face instead of the walking direction
+    pub look_at: Option<Vec3>,
+    /// Damage to deal to the player this tick
+    pub attack: Option<u8>,
+}
+
+/// Per-mob AI state, ticked by `think`
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct MobBrain {
+    /// Where the mob is walking to
+    pub target: Option<Vec3>,
+    /// Ticks spent walking to the current target
+    pub walk_ticks: u32,
+    /// World ticks of panic left
+    pub panic_ticks: u32,
+    /// Where the hit that caused the panic came from
+    pub threat: Vec3,
+    /// World ticks until the next melee hit
+    pub attack_cooldown: u32,
+}
+
+impl MobBrain {
+    /// React to being hit from `from`: panic, if the mob has a panic behavior
+    pub fn hurt(&mut self, from: Vec3, behaviors: &[Behavior]) {
+        let panic = behaviors.iter().find_map(|behavior| match *behavior {
+            Behavior::Panic { ticks, .. } => Some(ticks),
+            _ => None,
+        });
+        if let Some(ticks) = panic {
+            self.panic_ticks = ticks;
+            self.threat = from;
+            self.target = None;
+        }
+    }
+
+    fn walk_to(&mut self, target: Vec3) {
+        self.target = Some(target);
+        self.walk_ticks = 0;
+    }
+
+    /// The mob got where it was going (or found it can't get any closer)
+    pub fn arrive(&mut self) {
+        self.target = None;
+    }
+
+    /// Run one world tick of AI: the first behavior that wants to walk gets the legs,
+    /// swimming and looking combine with whatever else is going on
+    pub fn think(&mut self, behaviors: &[Behavior], senses: &Senses, rng: &mut impl Rng) -> Steering {
+        if let Some(target) = self.target {
+            self.walk_ticks += 1;
+            if (target - senses.position).xz().length() < ARRIVE_DISTANCE || self.walk_ticks > GIVE_UP_TICKS {
+                self.target = None;
+            }
+        }
+
+        self.attack_cooldown = self.attack_cooldown.saturating_sub(1);
+        let mut steering = Steering::default();
+        let mut walking = false;
+        for behavior in behaviors {
+            match *behavior {
+                Behavior::Swim => steering.jump |= senses.in_liquid,
+                Behavior::Panic { speed, .. } if !walking && self.panic_ticks > 0 => {
+                    self.panic_ticks -= 1;
+                    if self.target.is_none() {
+                        let away = (senses.position - self.threat).with_y(0.0).normalize_or(Vec3::X);
+                        let direction = Quat::from_rotation_y(rng.gen_range(-0.8..0.8)) * away;
+                        self.walk_to(senses.position + direction * FLEE_DISTANCE);
+                    }
+                    if self.panic_ticks == 0 {
+                        self.target = None;
+                    }
+                    steering.speed = speed;
+                    walking = true;
+                }
+                Behavior::Wander { chance, range } if !walking => {
+                    if self.target.is_none() && rng.gen_range(0..chance.max(1)) == 0 {
+                        let offset = Vec3::new(rng.gen_range(-range..=range), 0.0, rng.gen_range(-range..=range));
+                        self.walk_to(senses.position + offset);
+                    }
+                    if self.target.is_some() {
+                        steering.speed = 1.0;
+                        walking = true;
+                    }
+                }
+                Behavior::MeleeAttack { range, damage } if !walking => {
+                    let Some(player) = senses.player.filter(|player| player.distance(senses.position) <= range) else {
+                        continue;
+                    };
+                    // Chasing replaces any wander target; the path is replanned as the player moves
+                    self.walk_to(player - Vec3::Y * PLAYER_EYE_HEIGHT);
+                    if self.attack_cooldown == 0 && player.distance(senses.position + Vec3::Y) <= MELEE_REACH {
+                        self.attack_cooldown = MELEE_COOLDOWN_TICKS;
+                        steering.attack = Some(damage);
+                    }
+                    steering.speed = 1.0;
+                    steering.look_at = Some(player);
+                    walking = true;
+                }
+                Behavior::LookAtPlayer { range } if !walking => {
+                    steering.look_at = senses.player.filter(|player| player.distance(senses.position) <= range);
+                }
+                _ => {}
+            }
+        }
+
+        steering.goal = self.target.filter(|_| walking);
+        if steering.goal.is_some() {
+            steering.jump |= senses.blocked;
+        } else {
+            steering.speed = 0.0;
+        }
+        steering
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    const PIG: [Behavior; 3] = [
+        Behavior::Panic { ticks: 40, speed: 2.0 },
+        Behavior::Wander { chance: 1, range: 8.0 },
+        Behavior::LookAtPlayer { range: 6.0 },
+    ];
+
+    fn senses(position: Vec3) -> Senses {
+        Senses { position, in_liquid: false, blocked: false, player: None }
+    }
+
+    #[test]
+    fn hurt_mobs_run_away_from_the_threat() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let mut brain = MobBrain::default();
+        brain.hurt(Vec3::new(-3.0, 0.0, 0.0), &PIG);
+
+        let steering = brain.think(&PIG, &senses(Vec3::ZERO), &mut rng);
+        assert_eq!(steering.speed, 2.0);
+        let goal = steering.goal.unwrap();
+        assert!(goal.x > 3.0, "should flee along +X, got {:?}", goal);
+
+        // Panic wears off and the mob goes back to wandering at normal speed
+        for _ in 0..40 {
+            brain.think(&PIG, &senses(Vec3::ZERO), &mut rng);
+        }
+        assert_eq!(brain.panic_ticks, 0);
+        assert_eq!(brain.think(&PIG, &senses(Vec3::ZERO), &mut rng).speed, 1.0);
+    }
+
+    #[test]
+    fn stuck_wanderers_give_up_and_idle_mobs_watch_players() {
+        let mut rng = StdRng::seed_from_u64(2);
+        let mut brain = MobBrain::default();
+        brain.walk_to(Vec3::new(100.0, 0.0, 0.0));
+        let mut blocked = senses(Vec3::ZERO);
+        blocked.blocked = true;
+
+        assert!(brain.think(&PIG, &blocked, &mut rng).jump);
+        for _ in 0..GIVE_UP_TICKS {
+            brain.think(&PIG, &blocked, &mut rng);
+        }
+        assert_ne!(brain.target, Some(Vec3::new(100.0, 0.0, 0.0)));
+
+        let idle = [Behavior::Swim, Behavior::LookAtPlayer { range: 6.0 }];
+        let mut watching = senses(Vec3::ZERO);
+        watching.player = Some(Vec3::new(2.0, 1.6, 0.0));
+        watching.in_liquid = true;
+        let steering = MobBrain::default().think(&idle, &watching, &mut rng);
+        assert_eq!(steering.look_at, watching.player);
+        assert!(steering.jump);
+        assert_eq!(steering.goal, None);
+    }
+
+    #[test]
+    fn melee_attackers_chase_and_hit_with_a_cooldown() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let zombie = [
+            Behavior::MeleeAttack { range: 16.0, damage: 3 },
+            Behavior::Wander { chance: 1, range: 8.0 },
+        ];
+        let mut brain = MobBrain::default();
+        let mut chasing = senses(Vec3::ZERO);
+        chasing.player = Some(Vec3::new(10.0, PLAYER_EYE_HEIGHT, 0.0));
+        let steering = brain.think(&zombie, &chasing, &mut rng);
+        assert_eq!(steering.goal, Some(Vec3::new(10.0, 0.0, 0.0)));
+        assert_eq!(steering.attack, None);
+
+        chasing.player = Some(Vec3::new(1.0, PLAYER_EYE_HEIGHT, 0.0));
+        assert_eq!(brain.think(&zombie, &chasing, &mut rng).attack, Some(3));
+        let hits = (0..MELEE_COOLDOWN_TICKS).filter(|_| brain.think(&zombie, &chasing, &mut rng).attack.is_some()).count();
+        assert_eq!(hits, 1, "one hit per cooldown");
+    }
+}
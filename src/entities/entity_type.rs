@@ -0,0 +1,223 @@
+use crate::components::GRAVITY;
+use super::ai::Behavior;
+use super::model::{EntityModel, ModelPart};
+
+/// How an entity's physics body moves
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EntityPhysics {
+    /// Horizontal speed while walking (blocks/s)
+    pub walk_speed: f32,
+    /// Upward velocity of a jump (blocks/s)
+    pub jump_velocity: f32,
+    /// Ledges up to this height are walked up without jumping
+    pub step_height: f32,
+    /// Downward acceleration (blocks/s²)
+    pub gravity: f32,
+}
+
+impl Default for EntityPhysics {
+    fn default() -> Self {
+        Self {
+            walk_speed: 1.5,
+            jump_velocity: 7.0,
+            step_height: 0.0,
+            gravity: GRAVITY,
+        }
+    }
+}
+
+/// An item dropped when the entity dies
+#[derive(Debug, Clone, PartialEq)]
+pub struct EntityDrop {
+    /// Item or block string ID
+    pub item: String,
+    pub min: u8,
+    pub max: u8,
+}
+
+/// Natural spawning pool an entity type belongs to, each with its own cap
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MobCategory {
+    /// Spawns in the dark around the player and despawns when far from them
+    Hostile,
+    /// Spawns with newly generated chunks and stays
+    Passive,
+}
+
+impl MobCategory {
+    /// Most loaded mobs of the category before natural spawning stops adding more
+    pub fn cap(self) -> usize {
+        match self {
+            MobCategory::Hostile => 40,
+            MobCategory::Passive => 30,
+        }
+    }
+}
+
+/// How natural spawning places an entity type
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpawnRule {
+    pub category: MobCategory,
+    /// Chance relative to the other entity types of the category
+    pub weight: u32,
+    /// How many spawn together
+    pub group_min: u8,
+    pub group_max: u8,
+    /// Block string IDs the mob spawns on (there are no biomes, so the ground stands in for them),
+    /// empty for any solid floor
+    pub floors: Vec<String>,
+}
+
+/// Properties of an entity type
+#[derive(Debug, Clone)]
+pub struct EntityProperties {
+    /// Unique identifier for this entity type (e.g., "core:pig", "mymod:custom_mob")
+    pub id: String,
+    /// Display name for the entity
+    pub name: String,
+    /// Collision box: half its width and its height, from the feet
+    pub half_width: f32,
+    pub height: f32,
+    /// Health when spawned (2 per heart, like the player)
+    pub max_health: u8,
+    pub model: EntityModel,
+    pub physics: EntityPhysics,
+    /// AI behaviors in priority order: the first one that wants to move the entity does
+    pub behaviors: Vec<Behavior>,
+    /// Items dropped on death
+    pub drops: Vec<EntityDrop>,
+    /// Whether paths may lead through closed doors (the mob opens them on the way)
+    pub opens_doors: bool,
+    /// Natural spawning, None for mobs that only come from spawn eggs
+    pub spawn: Option<SpawnRule>,
+}
+
+/// Represents a specific type of entity (mob)
+#[derive(Debug, Clone)]
+pub struct EntityType {
+    pub properties: EntityProperties,
+}
+
+impl EntityType {
+    /// Builder pattern for creating entity types
+    pub fn builder(id: &str, name: &str) -> EntityTypeBuilder {
+        EntityTypeBuilder {
+            id: id.to_string(),
+            name: name.to_string(),
+            half_width: 0.3,
+            height: 1.8,
+            max_health: 20,
+            parts: Vec::new(),
+            physics: EntityPhysics::default(),
+            behaviors: Vec::new(),
+            drops: Vec::new(),
+            opens_doors: false,
+            spawn: None,
+            spawn_floors: Vec::new(),
+        }
+    }
+}
+
+pub struct EntityTypeBuilder {
+    id: String,
+    name: String,
+    half_width: f32,
+    height: f32,
+    max_health: u8,
+    parts: Vec<ModelPart>,
+    physics: EntityPhysics,
+    behaviors: Vec<Behavior>,
+    drops: Vec<EntityDrop>,
+    opens_doors: bool,
+    spawn: Option<SpawnRule>,
+    spawn_floors: Vec<String>,
+}
+
+impl EntityTypeBuilder {
+    /// Collision box size: half the width and the full height
+    pub fn size(mut self, half_width: f32, height: f32) -> Self {
+        self.half_width = half_width;
+        self.height = height;
+        self
+    }
+
+    pub fn health(mut self, max_health: u8) -> Self {
+        self.max_health = max_health;
+        self
+    }
+
+    /// Add a box to the model
+    pub fn part(mut self, part: ModelPart) -> Self {
+        self.parts.push(part);
+        self
+    }
+
+    pub fn walk_speed(mut self, speed: f32) -> Self {
+        self.physics.walk_speed = speed;
+        self
+    }
+
+    pub fn jump_velocity(mut self, velocity: f32) -> Self {
+        self.physics.jump_velocity = velocity;
+        self
+    }
+
+    pub fn step_height(mut self, height: f32) -> Self {
+        self.physics.step_height = height;
+        self
+    }
+
+    pub fn gravity(mut self, gravity: f32) -> Self {
+        self.physics.gravity = gravity;
+        self
+    }
+
+    /// Add an AI behavior, lower priority than the ones added before it
+    pub fn behavior(mut self, behavior: Behavior) -> Self {
+        self.behaviors.push(behavior);
+        self
+    }
+
+    /// Drop between `min` and `max` of an item or block on death
+    pub fn drops(mut self, item: &str, min: u8, max: u8) -> Self {
+        self.drops.push(EntityDrop { item: item.to_string(), min, max });
+        self
+    }
+
+    /// Let the mob open closed doors on its way
+    pub fn opens_doors(mut self) -> Self {
+        self.opens_doors = true;
+        self
+    }
+
+    /// Spawn naturally in packs of `min` to `max`, picked by `weight` among the category's types
+    pub fn natural_spawn(mut self, category: MobCategory, weight: u32, min: u8, max: u8) -> Self {
+        self.spawn = Some(SpawnRule { category, weight, group_min: min, group_max: max, floors: Vec::new() });
+        self
+    }
+
+    /// Only spawn naturally on this block (may be given several times)
+    pub fn spawns_on(mut self, block: &str) -> Self {
+        self.spawn_floors.push(block.to_string());
+        self
+    }
+
+    pub fn build(self) -> EntityType {
+        let spawn = self.spawn.map(|rule| SpawnRule { floors: self.spawn_floors, ..rule });
+        EntityType {
+            properties: EntityProperties {
+                id: self.id,
+                name: self.name,
+                half_width: self.half_width,
+                height: self.height,
+                max_health: self.max_health,
+                model: EntityModel { parts: self.parts },
+                physics: self.physics,
+                behaviors: self.behaviors,
+                drops: self.drops,
+                opens_doors: self.opens_doors,
+                spawn,
+            },
+        }
+    }
+}
@@ -0,0 +1,11 @@
+mod registry;
+mod entity_type;
+mod model;
+mod ai;
+mod pathfinding;
+
+pub use registry::{EntityRegistry, EntityTypeId};
+pub use entity_type::{EntityType, MobCategory};
+pub use model::ModelPart;
+pub use ai::{Behavior, MobBrain, Senses};
+pub use pathfinding::{Navigation, NavigationStatus, PathCell, PathGrid, PathOptions};
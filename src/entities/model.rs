@@ -0,0 +1,61 @@
+use bevy::prelude::*;
+use bevy::render::mesh::VertexAttributeValues;
+use crate::blocks::AtlasCoord;
+
+/// One textured box of an entity model
+/// Positions are relative to the entity's feet with the model facing -Z (Bevy's forward)
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModelPart {
+    pub size: Vec3,
+    pub center: Vec3,
+    /// Atlas cell drawn on every face
+    pub texture: AtlasCoord,
+    /// Multiplied with the texture (e.g., pink-tinted wool)
+    pub tint: Color,
+    /// Legs swing around their top while walking; the value is the phase (1.0 or -1.0)
+    pub swing: Option<f32>,
+}
+
+impl ModelPart {
+    pub fn new(size: Vec3, center: Vec3, texture: AtlasCoord) -> Self {
+        Self { size, center, texture, tint: Color::WHITE, swing: None }
+    }
+
+    pub fn tint(mut self, tint: Color) -> Self {
+        self.tint = tint;
+        self
+    }
+
+    /// Swing back and forth while walking, in step with other parts of the same phase
+    pub fn swing(mut self, phase: f32) -> Self {
+        self.swing = Some(phase);
+        self
+    }
+
+    /// Point the part rotates around: the top center for swinging parts, the center otherwise
+    pub fn pivot(&self) -> Vec3 {
+        match self.swing {
+            Some(_) => self.center + Vec3::Y * (self.size.y / 2.0),
+            None => self.center,
+        }
+    }
+
+    /// Box mesh around the pivot with every face showing the part's atlas cell
+    pub fn mesh(&self) -> Mesh {
+        let mut mesh = Mesh::from(Cuboid::from_size(self.size)).translated_by(self.center - self.pivot());
+        let (u_min, v_min, u_max, v_max) = self.texture.uv_coords();
+        if let Some(VertexAttributeValues::Float32x2(uvs)) = mesh.attribute_mut(Mesh::ATTRIBUTE_UV_0) {
+            for [u, v] in uvs.iter_mut() {
+                *u = u_min + *u * (u_max - u_min);
+                *v = v_min + *v * (v_max - v_min);
+            }
+        }
+        mesh
+    }
+}
+
+/// An entity's model: boxes built from atlas cells
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EntityModel {
+    pub parts: Vec<ModelPart>,
+}
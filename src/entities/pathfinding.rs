@@ -0,0 +1,544 @@
+use bevy::prelude::*;
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+use crate::physics::ChunkColliders;
+use crate::world::world_to_chunk_local;
+use super::ai::Behavior;
+use super::entity_type::EntityProperties;
+
+/// Nodes a single search may expand before settling for the closest spot it found
+const MAX_SEARCH_NODES: usize = 2000;
+
+/// Extra cost of a node whose feet are in a liquid (swimming is slow)
+const LIQUID_COST: f32 = 2.0;
+
+/// Extra cost of a jump up a ledge
+const JUMP_COST: f32 = 1.0;
+
+/// Extra cost per block dropped
+const FALL_COST: f32 = 0.5;
+
+/// A node counts as the goal if it's in the goal's column within this many blocks up or down
+/// (wander targets are picked at the mob's height, the ground there can be higher or lower)
+const GOAL_HEIGHT_TOLERANCE: i32 = 4;
+
+/// Horizontal distance from a waypoint's center at which it counts as reached
+const WAYPOINT_REACHED: f32 = 0.35;
+
+/// What the pathfinder needs to know about one block cell
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PathCell {
+    /// Not loaded - never walked into
+    Unloaded,
+    /// Nothing to collide with (air, flowers, open doors)
+    Open,
+    Solid,
+    Liquid,
+    /// A door that blocks the way until it's opened
+    ClosedDoor,
+}
+
+/// Block grid the pathfinder searches
+pub trait PathGrid {
+    fn cell(&self, pos: IVec3) -> PathCell;
+}
+
+impl PathGrid for ChunkColliders<'_, '_, '_> {
+    fn cell(&self, pos: IVec3) -> PathCell {
+        let (coord, local) = world_to_chunk_local(pos);
+        let Some(chunk) = self.chunk_manager.loaded_chunks.get(&coord).and_then(|&entity| self.chunks.get(entity).ok()) else {
+            return PathCell::Unloaded;
+        };
+        let state = chunk.get_state(local.x as usize, local.y as usize, local.z as usize);
+        if state.is_air() {
+            return PathCell::Open;
+        }
+        match self.registry.get_block(state.id).map(|block| &block.properties) {
+            Some(properties) if properties.is_liquid => PathCell::Liquid,
+            Some(properties) if properties.is_solid && properties.is_door => PathCell::ClosedDoor,
+            Some(properties) if properties.is_solid => PathCell::Solid,
+            Some(_) => PathCell::Open,
+            None => PathCell::Solid,
+        }
+    }
+}
+
+/// The shape and movement limits of the entity a path is for, in whole blocks
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PathOptions {
+    /// Footprint edge length (1 for anything up to a block wide)
+    pub width: i32,
+    /// Headroom needed
+    pub height: i32,
+    /// Highest ledge climbed by jumping
+    pub jump_height: i32,
+    /// Longest drop taken without looking for a way around
+    pub max_fall: i32,
+    pub can_swim: bool,
+    pub opens_doors: bool,
+}
+
+impl PathOptions {
+    /// Limits for an entity type: its collision box rounded up, how high it can jump
+    /// and a 3 block safe fall
+    pub fn for_entity(properties: &EntityProperties) -> Self {
+        let physics = &properties.physics;
+        let jump = physics.jump_velocity * physics.jump_velocity / (2.0 * physics.gravity) + physics.step_height;
+        Self {
+            width: ((properties.half_width * 2.0).ceil() as i32).max(1),
+            height: (properties.height.ceil() as i32).max(1),
+            jump_height: jump.floor() as i32,
+            max_fall: 3,
+            can_swim: properties.behaviors.contains(&Behavior::Swim),
+            opens_doors: properties.opens_doors,
+        }
+    }
+
+    fn passable(&self, cell: PathCell) -> bool {
+        match cell {
+            PathCell::Open | PathCell::Liquid => true,
+            PathCell::ClosedDoor => self.opens_doors,
+            PathCell::Solid | PathCell::Unloaded => false,
+        }
+    }
+
+    /// Every cell the body covers with its feet in `pos` is passable
+    fn fits(&self, grid: &impl PathGrid, pos: IVec3) -> bool {
+        (0..self.height).all(|y| {
+            (0..self.width).all(|x| (0..self.width).all(|z| self.passable(grid.cell(pos + IVec3::new(x, y, z)))))
+        })
+    }
+
+    fn in_liquid(&self, grid: &impl PathGrid, pos: IVec3) -> bool {
+        grid.cell(pos) == PathCell::Liquid
+    }
+
+    /// Something solid under the footprint, or water to swim in
+    fn supported(&self, grid: &impl PathGrid, pos: IVec3) -> bool {
+        let on_floor = (0..self.width).any(|x| (0..self.width).any(|z| grid.cell(pos + IVec3::new(x, -1, z)) == PathCell::Solid));
+        on_floor || (self.can_swim && self.in_liquid(grid, pos))
+    }
+
+    fn standable(&self, grid: &impl PathGrid, pos: IVec3) -> bool {
+        self.fits(grid, pos) && self.supported(grid, pos)
+    }
+
+    /// Feet block of a body whose feet are at `position`
+    pub fn node_at(&self, position: Vec3) -> IVec3 {
+        let offset = (self.width - 1) as f32 / 2.0;
+        IVec3::new(
+            (position.x - offset).floor() as i32,
+            // Feet resting on a half block sit inside its cell
+            (position.y + 0.01).floor() as i32,
+            (position.z - offset).floor() as i32,
+        )
+    }
+
+    /// Feet position at the center of a node's footprint
+    pub fn node_center(&self, node: IVec3) -> Vec3 {
+        node.as_vec3() + Vec3::new(self.width as f32 / 2.0, 0.0, self.width as f32 / 2.0)
+    }
+
+    /// Nodes reachable from `node` in one move, with their cost
+    fn neighbors(&self, grid: &impl PathGrid, node: IVec3) -> Vec<(IVec3, f32)> {
+        let mut neighbors = Vec::new();
+        let liquid_cost = |pos: IVec3| if self.in_liquid(grid, pos) { LIQUID_COST } else { 0.0 };
+
+        for (dx, dz) in [(1, 0), (-1, 0), (0, 1), (0, -1)] {
+            let side = node + IVec3::new(dx, 0, dz);
+            if self.fits(grid, side) {
+                // Walk across, or drop down to the first floor below
+                for drop in 0..=self.max_fall {
+                    let below = side - IVec3::Y * drop;
+                    if drop > 0 && !self.fits(grid, below) {
+                        break;
+                    }
+                    if self.supported(grid, below) {
+                        neighbors.push((below, 1.0 + drop as f32 * FALL_COST + liquid_cost(below)));
+                        break;
+                    }
+                }
+            } else {
+                // Jump onto the ledge, if there's headroom above us
+                for up in 1..=self.jump_height {
+                    if !self.fits(grid, node + IVec3::Y * up) {
+                        break;
+                    }
+                    let ledge = side + IVec3::Y * up;
+                    if self.standable(grid, ledge) {
+                        neighbors.push((ledge, 1.0 + JUMP_COST * up as f32 + liquid_cost(ledge)));
+                        break;
+                    }
+                }
+            }
+        }
+
+        // Diagonals on level ground, without cutting corners
+        for (dx, dz) in [(1, 1), (1, -1), (-1, 1), (-1, -1)] {
+            let corner = node + IVec3::new(dx, 0, dz);
+            let sides_clear = self.fits(grid, node + IVec3::new(dx, 0, 0)) && self.fits(grid, node + IVec3::new(0, 0, dz));
+            if sides_clear && self.standable(grid, corner) {
+                neighbors.push((corner, std::f32::consts::SQRT_2 + liquid_cost(corner)));
+            }
+        }
+
+        // Swim straight up to the surface
+        if self.in_liquid(grid, node) && self.standable(grid, node + IVec3::Y) {
+            neighbors.push((node + IVec3::Y, 1.0 + LIQUID_COST));
+        }
+        neighbors
+    }
+}
+
+/// Estimated cost from `from` to `to`: diagonal distance across, plus height difference
+/// Going down is weighted like a drop so the estimate never exceeds the real cost
+fn heuristic(from: IVec3, to: IVec3) -> f32 {
+    let delta = to - from;
+    let (dx, dz) = (delta.x.abs(), delta.z.abs());
+    let (long, short) = (dx.max(dz) as f32, dx.min(dz) as f32);
+    let climb = if delta.y > 0 { delta.y as f32 } else { -delta.y as f32 * FALL_COST };
+    long + short * (std::f32::consts::SQRT_2 - 1.0) + climb
+}
+
+/// A node waiting in the open set, ordered so the heap pops the lowest estimated total first
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct OpenNode {
+    pos: IVec3,
+    estimate: f32,
+}
+
+impl Eq for OpenNode {}
+
+impl Ord for OpenNode {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.estimate.total_cmp(&self.estimate)
+    }
+}
+
+impl PartialOrd for OpenNode {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// A path found by `PathSearch`: the nodes to walk through after the start
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Path {
+    pub nodes: Vec<IVec3>,
+    /// Whether it reaches the goal, rather than the closest spot to it that could be reached
+    pub complete: bool,
+}
+
+/// An A* search over the block grid that can be spread over several ticks
+#[derive(Debug, Clone)]
+pub struct PathSearch {
+    goal: IVec3,
+    options: PathOptions,
+    open: BinaryHeap<OpenNode>,
+    /// Best known cost to each node seen and the node it was reached from
+    visited: HashMap<IVec3, (f32, Option<IVec3>)>,
+    /// Node closest to the goal so far, the fallback when the goal can't be reached
+    closest: (IVec3, f32),
+    expanded: usize,
+}
+
+impl PathSearch {
+    pub fn new(start: IVec3, goal: IVec3, options: PathOptions) -> Self {
+        let estimate = heuristic(start, goal);
+        Self {
+            goal,
+            options,
+            open: BinaryHeap::from([OpenNode { pos: start, estimate }]),
+            visited: HashMap::from([(start, (0.0, None))]),
+            closest: (start, estimate),
+            expanded: 0,
+        }
+    }
+
+    fn is_goal(&self, pos: IVec3) -> bool {
+        pos.x == self.goal.x && pos.z == self.goal.z && (pos.y - self.goal.y).abs() <= GOAL_HEIGHT_TOLERANCE
+    }
+
+    fn path_to(&self, end: IVec3, complete: bool) -> Path {
+        let mut nodes = Vec::new();
+        let mut current = Some(end);
+        while let Some(pos) = current {
+            nodes.push(pos);
+            current = self.visited.get(&pos).and_then(|&(_, parent)| parent);
+        }
+        nodes.pop(); // The start
+        nodes.reverse();
+        Path { nodes, complete }
+    }
+
+    /// Expand up to `budget` nodes (taking what's used out of it)
+    /// Returns the path once the search is over, None if it needs more ticks
+    pub fn run(&mut self, grid: &impl PathGrid, budget: &mut usize) -> Option<Path> {
+        while *budget > 0 {
+            let Some(OpenNode { pos, estimate }) = self.open.pop() else {
+                // Everything reachable has been seen
+                return Some(self.path_to(self.closest.0, false));
+            };
+            let cost = self.visited[&pos].0;
+            if estimate > cost + heuristic(pos, self.goal) + 1e-3 {
+                continue; // Stale entry, a cheaper way here was found later
+            }
+            if self.is_goal(pos) {
+                return Some(self.path_to(pos, true));
+            }
+            if self.expanded >= MAX_SEARCH_NODES {
+                return Some(self.path_to(self.closest.0, false));
+            }
+            *budget -= 1;
+            self.expanded += 1;
+
+            for (next, step) in self.options.neighbors(grid, pos) {
+                let next_cost = cost + step;
+                if self.visited.get(&next).is_some_and(|&(known, _)| known <= next_cost) {
+                    continue;
+                }
+                self.visited.insert(next, (next_cost, Some(pos)));
+                let remaining = heuristic(next, self.goal);
+                if remaining < self.closest.1 {
+                    self.closest = (next, remaining);
+                }
+                self.open.push(OpenNode { pos: next, estimate: next_cost + remaining });
+            }
+        }
+        None
+    }
+}
+
+/// What path following wants the body to do this tick
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NavigationStatus {
+    /// No goal, or the path is still being searched for
+    Idle,
+    /// Walk toward this feet position
+    Walking(Vec3),
+    /// The path has been walked (or no path exists)
+    Arrived,
+}
+
+/// A mob's path to its current goal, searched for a bit each tick and then followed
+#[derive(Debug, Clone, Default)]
+pub struct Navigation {
+    goal: Option<IVec3>,
+    search: Option<PathSearch>,
+    path: Path,
+    next: usize,
+}
+
+impl Navigation {
+    /// Search for and follow a path to `goal`, restarting whenever the goal moves to another block
+    /// Searches spend from `budget`, shared by every mob this tick
+    pub fn follow(
+        &mut self,
+        goal: Option<Vec3>,
+        position: Vec3,
+        options: &PathOptions,
+        grid: &impl PathGrid,
+        budget: &mut usize,
+    ) -> NavigationStatus {
+        let Some(goal) = goal.map(|goal| options.node_at(goal)) else {
+            *self = Self::default();
+            return NavigationStatus::Idle;
+        };
+        if self.goal != Some(goal) {
+            let mut start = options.node_at(position);
+            // Standing on a half block puts the feet in its cell
+            if !options.fits(grid, start) {
+                start += IVec3::Y;
+            }
+            *self = Self { goal: Some(goal), search: Some(PathSearch::new(start, goal, *options)), ..default() };
+        }
+
+        if let Some(search) = &mut self.search {
+            match search.run(grid, budget) {
+                Some(path) => {
+                    self.path = path;
+                    self.search = None;
+                }
+                None => return NavigationStatus::Idle,
+            }
+        }
+
+        while let Some(&node) = self.path.nodes.get(self.next) {
+            let center = options.node_center(node);
+            let reached = (center - position).xz().length() < WAYPOINT_REACHED && (position.y - center.y).abs() < 1.0;
+            if !reached {
+                return NavigationStatus::Walking(center);
+            }
+            self.next += 1;
+        }
+        NavigationStatus::Arrived
+    }
+
+    /// The node being walked to
+    pub fn next_node(&self) -> Option<IVec3> {
+        self.path.nodes.get(self.next).copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Hand-built grid: listed cells, open air everywhere else
+    #[derive(Default)]
+    struct Grid(HashMap<IVec3, PathCell>);
+
+    impl Grid {
+        /// A 16x16 stone floor at y=0
+        fn floor() -> Self {
+            let mut grid = Grid::default();
+            for x in 0..16 {
+                for z in 0..16 {
+                    grid.0.insert(IVec3::new(x, 0, z), PathCell::Solid);
+                }
+            }
+            grid
+        }
+
+        fn set(&mut self, pos: IVec3, cell: PathCell) {
+            self.0.insert(pos, cell);
+        }
+
+        /// A wall along x = `x`, `height` blocks tall, across the whole floor
+        fn wall(&mut self, x: i32, height: i32) {
+            for z in 0..16 {
+                for y in 1..=height {
+                    self.set(IVec3::new(x, y, z), PathCell::Solid);
+                }
+            }
+        }
+    }
+
+    impl PathGrid for Grid {
+        fn cell(&self, pos: IVec3) -> PathCell {
+            self.0.get(&pos).copied().unwrap_or(PathCell::Open)
+        }
+    }
+
+    const PIG: PathOptions = PathOptions { width: 1, height: 1, jump_height: 1, max_fall: 3, can_swim: true, opens_doors: false };
+
+    fn find(grid: &Grid, start: IVec3, goal: IVec3, options: PathOptions) -> Path {
+        let mut budget = usize::MAX;
+        PathSearch::new(start, goal, options).run(grid, &mut budget).unwrap()
+    }
+
+    #[test]
+    fn walks_around_walls_and_jumps_low_ledges() {
+        let mut grid = Grid::floor();
+        grid.wall(5, 2);
+        // A gap in the tall wall at z = 12
+        grid.set(IVec3::new(5, 1, 12), PathCell::Open);
+        grid.set(IVec3::new(5, 2, 12), PathCell::Open);
+
+        let path = find(&grid, IVec3::new(2, 1, 2), IVec3::new(8, 1, 2), PIG);
+        assert!(path.complete);
+        assert!(path.nodes.contains(&IVec3::new(5, 1, 12)), "should go through the gap: {:?}", path.nodes);
+        assert_eq!(path.nodes.last(), Some(&IVec3::new(8, 1, 2)));
+
+        // A one block wall is jumped instead
+        let mut grid = Grid::floor();
+        grid.wall(5, 1);
+        let path = find(&grid, IVec3::new(2, 1, 2), IVec3::new(8, 1, 2), PIG);
+        assert!(path.complete);
+        assert!(path.nodes.contains(&IVec3::new(5, 2, 2)));
+        assert!(path.nodes.len() < 10);
+    }
+
+    #[test]
+    fn avoids_long_drops_and_stays_in_loaded_chunks() {
+        // A raised platform at y=5 with the floor below; the goal is on the floor
+        let mut grid = Grid::floor();
+        for x in 0..6 {
+            for z in 0..16 {
+                for y in 1..=4 {
+                    grid.set(IVec3::new(x, y, z), PathCell::Solid);
+                }
+            }
+        }
+        let unreachable = find(&grid, IVec3::new(2, 5, 2), IVec3::new(10, 1, 2), PIG);
+        assert!(!unreachable.complete);
+        assert!(unreachable.nodes.iter().all(|node| node.y == 5));
+
+        // Stairs down make it reachable
+        for (x, height) in [(6, 3), (7, 2), (8, 1)] {
+            for y in 1..=height {
+                grid.set(IVec3::new(x, y, 8), PathCell::Solid);
+            }
+        }
+        assert!(find(&grid, IVec3::new(2, 5, 2), IVec3::new(10, 1, 2), PIG).complete);
+
+        // Unloaded cells are walls
+        let mut grid = Grid::floor();
+        for z in 0..16 {
+            grid.set(IVec3::new(5, 1, z), PathCell::Unloaded);
+        }
+        assert!(!find(&grid, IVec3::new(2, 1, 2), IVec3::new(8, 1, 2), PIG).complete);
+    }
+
+    #[test]
+    fn heuristic_never_overestimates_a_drop() {
+        for drop in 1..=PIG.max_fall {
+            let step_cost = 1.0 + drop as f32 * FALL_COST;
+            assert!(heuristic(IVec3::ZERO, IVec3::new(1, -drop, 0)) <= step_cost);
+        }
+        assert_eq!(heuristic(IVec3::ZERO, IVec3::new(1, 1, 0)), 1.0 + JUMP_COST);
+    }
+
+    #[test]
+    fn doors_fluids_and_body_size() {
+        let mut grid = Grid::floor();
+        grid.wall(5, 3);
+        grid.set(IVec3::new(5, 1, 8), PathCell::ClosedDoor);
+        grid.set(IVec3::new(5, 2, 8), PathCell::ClosedDoor);
+        let goal = IVec3::new(8, 1, 8);
+        assert!(!find(&grid, IVec3::new(2, 1, 8), goal, PathOptions { height: 2, ..PIG }).complete);
+        let villager = PathOptions { height: 2, opens_doors: true, ..PIG };
+        assert!(find(&grid, IVec3::new(2, 1, 8), goal, villager).nodes.contains(&IVec3::new(5, 1, 8)));
+
+        // A pool across the way: swimmers cross it, others can't stand in it
+        let mut grid = Grid::floor();
+        for z in 0..16 {
+            grid.set(IVec3::new(5, 0, z), PathCell::Liquid);
+            grid.set(IVec3::new(5, -1, z), PathCell::Solid);
+        }
+        assert!(find(&grid, IVec3::new(2, 1, 2), IVec3::new(8, 1, 2), PIG).complete);
+        assert!(!find(&grid, IVec3::new(2, 1, 2), IVec3::new(8, 1, 2), PathOptions { can_swim: false, max_fall: 0, ..PIG }).complete);
+
+        // A one block gap under a low ceiling fits a pig but not a 2 block tall mob
+        let mut grid = Grid::floor();
+        grid.wall(5, 3);
+        grid.set(IVec3::new(5, 1, 8), PathCell::Open);
+        assert!(find(&grid, IVec3::new(2, 1, 8), goal, PIG).complete);
+        assert!(!find(&grid, IVec3::new(2, 1, 8), goal, PathOptions { height: 2, ..PIG }).complete);
+    }
+
+    #[test]
+    fn searches_spread_over_ticks_and_get_followed() {
+        let grid = Grid::floor();
+        let mut navigation = Navigation::default();
+        let goal = Some(Vec3::new(12.5, 1.0, 3.5));
+        let mut position = Vec3::new(2.5, 1.0, 3.5);
+
+        let mut budget = 3;
+        assert_eq!(navigation.follow(goal, position, &PIG, &grid, &mut budget), NavigationStatus::Idle);
+        assert_eq!(budget, 0);
+
+        let mut budget = 1000;
+        for _ in 0..20 {
+            match navigation.follow(goal, position, &PIG, &grid, &mut budget) {
+                NavigationStatus::Walking(target) => position = target,
+                status => {
+                    assert_eq!(status, NavigationStatus::Arrived);
+                    break;
+                }
+            }
+        }
+        assert_eq!(PIG.node_at(position), IVec3::new(12, 1, 3));
+        assert_eq!(navigation.follow(goal, position, &PIG, &grid, &mut budget), NavigationStatus::Arrived);
+    }
+}
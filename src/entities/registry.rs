@@ -0,0 +1,96 @@
+use bevy::prelude::*;
+use std::collections::HashMap;
+use super::entity_type::EntityType;
+
+/// Numeric ID for an entity type
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EntityTypeId(pub u16);
+
+/// Global registry for all mob types
+/// This is a Bevy resource that mods can access to register new entities
+#[derive(Resource, Clone, Default)]
+pub struct EntityRegistry {
+    entities: Vec<EntityType>,
+    /// Map from string ID to numeric ID for lookups
+    id_map: HashMap<String, EntityTypeId>,
+}
+
+impl EntityRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a new entity type and return its ID
+    /// Returns an error if an entity with this string ID already exists
+    pub fn register_entity(&mut self, entity_type: EntityType) -> Result<EntityTypeId, String> {
+        let string_id = entity_type.properties.id.clone();
+
+        if self.id_map.contains_key(&string_id) {
+            return Err(format!("Entity '{}' is already registered", string_id));
+        }
+        if self.entities.len() >= u16::MAX as usize {
+            return Err("Entity registry is full (65535 entity types)".to_string());
+        }
+
+        let entity_id = EntityTypeId(self.entities.len() as u16);
+        self.entities.push(entity_type);
+        self.id_map.insert(string_id.clone(), entity_id);
+
+        info!("Registered entity '{}' with ID {}", string_id, entity_id.0);
+
+        Ok(entity_id)
+    }
+
+    /// Get an entity type by its numeric ID
+    pub fn get_entity(&self, id: EntityTypeId) -> Option<&EntityType> {
+        self.entities.get(id.0 as usize)
+    }
+
+    /// Get the numeric ID for an entity by its string ID
+    pub fn get_id(&self, string_id: &str) -> Option<EntityTypeId> {
+        self.id_map.get(string_id).copied()
+    }
+
+    /// Display name, or "?" for an unknown ID
+    pub fn name(&self, id: EntityTypeId) -> &str {
+        self.get_entity(id).map_or("?", |entity| entity.properties.name.as_str())
+    }
+
+    /// Every registered type with its ID
+    pub fn iter(&self) -> impl Iterator<Item = (EntityTypeId, &EntityType)> {
+        self.entities.iter().enumerate().map(|(index, entity)| (EntityTypeId(index as u16), entity))
+    }
+
+    /// Get the number of registered entity types
+    pub fn entity_count(&self) -> usize {
+        self.entities.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blocks::AtlasCoord;
+    use crate::entities::{Behavior, ModelPart};
+
+    #[test]
+    fn test_register_and_get_entity() {
+        let mut registry = EntityRegistry::new();
+        let pig = registry.register_entity(
+            EntityType::builder("core:pig", "Pig")
+                .size(0.45, 0.9)
+                .health(10)
+                .part(ModelPart::new(Vec3::new(0.6, 0.5, 0.9), Vec3::new(0.0, 0.55, 0.0), AtlasCoord::new(0, 4)))
+                .behavior(Behavior::Wander { chance: 120, range: 8.0 })
+                .build()
+        ).unwrap();
+
+        assert_eq!(registry.entity_count(), 1);
+        assert_eq!(registry.get_id("core:pig"), Some(pig));
+        assert_eq!(registry.name(pig), "Pig");
+        let properties = &registry.get_entity(pig).unwrap().properties;
+        assert_eq!(properties.max_health, 10);
+        assert_eq!(properties.model.parts.len(), 1);
+        assert!(registry.register_entity(EntityType::builder("core:pig", "Pig").build()).is_err());
+    }
+}
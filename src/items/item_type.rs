@@ -0,0 +1,101 @@
+use super::tool::{ToolKind, ToolProperties, ToolTier};
+
+/// How much an item restores when eaten
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FoodProperties {
+    /// Hunger points restored (2 per drumstick)
+    pub nutrition: u8,
+    /// Saturation gained per point of nutrition
+    pub saturation_modifier: f32,
+}
+
+/// Properties of an item type
+#[derive(Debug, Clone)]
+pub struct ItemProperties {
+    /// Unique identifier for this item type (e.g., "core:stick", "mymod:custom_item")
+    pub id: String,
+    /// Display name for the item
+    pub name: String,
+    /// Food values, None for items that can't be eaten
+    pub food: Option<FoodProperties>,
+    /// Tool type and tier, None for items that don't speed up mining
+    pub tool: Option<ToolProperties>,
+    /// World ticks the item burns for as furnace fuel, None if it doesn't burn
+    pub fuel_ticks: Option<u32>,
+    /// Entity string ID spawned by using the item on a block (spawn eggs)
+    pub spawns: Option<String>,
+}
+
+impl ItemProperties {
+    /// Uses before the item breaks, None for items that don't wear out
+    pub fn max_durability(&self) -> Option<u16> {
+        self.tool.map(|tool| tool.tier.durability())
+    }
+}
+
+/// Represents a specific type of item
+#[derive(Debug, Clone)]
+pub struct ItemType {
+    pub properties: ItemProperties,
+}
+
+impl ItemType {
+    /// Builder pattern for creating item types
+    pub fn builder(id: &str, name: &str) -> ItemTypeBuilder {
+        ItemTypeBuilder {
+            id: id.to_string(),
+            name: name.to_string(),
+            food: None,
+            tool: None,
+            fuel_ticks: None,
+            spawns: None,
+        }
+    }
+}
+
+pub struct ItemTypeBuilder {
+    id: String,
+    name: String,
+    food: Option<FoodProperties>,
+    tool: Option<ToolProperties>,
+    fuel_ticks: Option<u32>,
+    spawns: Option<String>,
+}
+
+impl ItemTypeBuilder {
+    pub fn food(mut self, nutrition: u8, saturation_modifier: f32) -> Self {
+        self.food = Some(FoodProperties { nutrition, saturation_modifier });
+        self
+    }
+
+    /// Make this item a tool (tools don't stack and wear out with use)
+    pub fn tool(mut self, kind: ToolKind, tier: ToolTier) -> Self {
+        self.tool = Some(ToolProperties { kind, tier });
+        self
+    }
+
+    /// Burns in a furnace for this many world ticks
+    pub fn fuel(mut self, ticks: u32) -> Self {
+        self.fuel_ticks = Some(ticks);
+        self
+    }
+
+    /// Right-clicking a block with this item spawns the entity on top of it
+    pub fn spawns(mut self, entity: &str) -> Self {
+        self.spawns = Some(entity.to_string());
+        self
+    }
+
+    pub fn build(self) -> ItemType {
+        ItemType {
+            properties: ItemProperties {
+                id: self.id,
+                name: self.name,
+                food: self.food,
+                tool: self.tool,
+                fuel_ticks: self.fuel_ticks,
+                spawns: self.spawns,
+            },
+        }
+    }
+}
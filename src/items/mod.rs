@@ -0,0 +1,49 @@
+mod registry;
+mod item_type;
+mod tool;
+
+pub use registry::{ItemRegistry, ItemId};
+pub use item_type::{ItemType, FoodProperties};
+pub use tool::{ToolKind, ToolTier, ToolProperties, attack_damage, can_harvest, break_seconds};
+
+use crate::blocks::BlockRegistry;
+use crate::resources::{HotbarItem, ItemStack};
+
+/// Look up an item or block by its namespaced string ID (items first)
+pub fn resolve_item(id: &str, items: &ItemRegistry, blocks: &BlockRegistry) -> Result<HotbarItem, String> {
+    if let Some(item) = items.get_id(id) {
+        return Ok(HotbarItem::Item(item));
+    }
+    match blocks.get_id(id) {
+        Some(block) if !block.is_air() => Ok(HotbarItem::Block(block)),
+        _ => Err(format!("Unknown item '{}'", id)),
+    }
+}
+
+/// A fresh stack of an item; tools start at full durability and never stack
+pub fn new_stack(item: HotbarItem, count: u8, items: &ItemRegistry) -> ItemStack {
+    let durability = match item {
+        HotbarItem::Item(id) => items.get_item(id).and_then(|item| item.properties.max_durability()),
+        HotbarItem::Block(_) => None,
+    };
+    match durability {
+        Some(durability) => ItemStack::tool(item, durability),
+        None => ItemStack::new(item, count),
+    }
+}
+
+/// The tool properties of whatever is in a stack, if it's a tool
+pub fn stack_tool(stack: Option<ItemStack>, items: &ItemRegistry) -> Option<ToolProperties> {
+    match stack?.item {
+        HotbarItem::Item(id) => items.get_item(id)?.properties.tool,
+        HotbarItem::Block(_) => None,
+    }
+}
+
+/// How many world ticks an item burns for in a furnace, None if it isn't fuel
+pub fn fuel_ticks(item: HotbarItem, items: &ItemRegistry, blocks: &BlockRegistry) -> Option<u32> {
+    match item {
+        HotbarItem::Item(id) => items.get_item(id)?.properties.fuel_ticks,
+        HotbarItem::Block(id) => blocks.get_block(id)?.properties.fuel_ticks,
+    }
+}
@@ -0,0 +1,94 @@
+use bevy::prelude::*;
+use std::collections::HashMap;
+use super::item_type::ItemType;
+
+/// Numeric ID for a non-block item type
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ItemId(pub u16);
+
+/// Global registry for all non-block item types (blocks are items through the BlockRegistry)
+/// This is a Bevy resource that mods can access to register new items
+#[derive(Resource, Clone, Default)]
+pub struct ItemRegistry {
+    items: Vec<ItemType>,
+    /// Map from string ID to numeric ID for lookups
+    id_map: HashMap<String, ItemId>,
+}
+
+impl ItemRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a new item type and return its ID
+    /// Returns an error if an item with this string ID already exists
+    pub fn register_item(&mut self, item_type: ItemType) -> Result<ItemId, String> {
+        let string_id = item_type.properties.id.clone();
+
+        if self.id_map.contains_key(&string_id) {
+            return Err(format!("Item '{}' is already registered", string_id));
+        }
+        if self.items.len() >= u16::MAX as usize {
+            return Err("Item registry is full (65535 item types)".to_string());
+        }
+
+        let item_id = ItemId(self.items.len() as u16);
+        self.items.push(item_type);
+        self.id_map.insert(string_id.clone(), item_id);
+
+        info!("Registered item '{}' with ID {}", string_id, item_id.0);
+
+        Ok(item_id)
+    }
+
+    /// Get an item type by its numeric ID
+    pub fn get_item(&self, id: ItemId) -> Option<&ItemType> {
+        self.items.get(id.0 as usize)
+    }
+
+    /// Get the numeric ID for an item by its string ID
+    pub fn get_id(&self, string_id: &str) -> Option<ItemId> {
+        self.id_map.get(string_id).copied()
+    }
+
+    /// Get the string ID for an item by its numeric ID
+    pub fn get_string_id(&self, id: ItemId) -> Option<&str> {
+        self.get_item(id).map(|item| item.properties.id.as_str())
+    }
+
+    /// Display name, or "?" for an unknown ID
+    pub fn name(&self, id: ItemId) -> &str {
+        self.get_item(id).map_or("?", |item| item.properties.name.as_str())
+    }
+
+    /// Every item's string ID, in registration order
+    pub fn string_ids(&self) -> impl Iterator<Item = &str> {
+        self.items.iter().map(|item| item.properties.id.as_str())
+    }
+
+    /// Get the number of registered items
+    pub fn item_count(&self) -> usize {
+        self.items.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::items::{ToolKind, ToolTier};
+
+    #[test]
+    fn test_register_and_get_item() {
+        let mut registry = ItemRegistry::new();
+        let stick = registry.register_item(ItemType::builder("core:stick", "Stick").build()).unwrap();
+        let pickaxe = registry.register_item(
+            ItemType::builder("core:stone_pickaxe", "Stone Pickaxe").tool(ToolKind::Pickaxe, ToolTier::Stone).build()
+        ).unwrap();
+
+        assert_eq!(registry.item_count(), 2);
+        assert_eq!(registry.get_id("core:stick"), Some(stick));
+        assert_eq!(registry.name(pickaxe), "Stone Pickaxe");
+        assert_eq!(registry.get_item(pickaxe).unwrap().properties.max_durability(), Some(131));
+        assert!(registry.register_item(ItemType::builder("core:stick", "Stick").build()).is_err());
+    }
+}
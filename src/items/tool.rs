@@ -0,0 +1,138 @@
+use crate::blocks::BlockType;
+
+/// Which kind of block a tool is good at
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ToolKind {
+    Pickaxe,
+    Shovel,
+    Axe,
+}
+
+/// Tool material, ordered from weakest to strongest
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ToolTier {
+    Wood,
+    Stone,
+    Iron,
+    Diamond,
+}
+
+impl ToolTier {
+    /// Mining speed multiplier on blocks that prefer this tool kind
+    pub fn efficiency(self) -> f32 {
+        match self {
+            ToolTier::Wood => 2.0,
+            ToolTier::Stone => 4.0,
+            ToolTier::Iron => 6.0,
+            ToolTier::Diamond => 8.0,
+        }
+    }
+
+    /// Number of blocks a tool of this tier can break before it breaks
+    pub fn durability(self) -> u16 {
+        match self {
+            ToolTier::Wood => 59,
+            ToolTier::Stone => 131,
+            ToolTier::Iron => 250,
+            ToolTier::Diamond => 1561,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ToolProperties {
+    pub kind: ToolKind,
+    pub tier: ToolTier,
+}
+
+/// Half-hearts of damage a hit deals: 1 by hand, tools hit harder by kind and tier
+/// (axes hardest, shovels least)
+pub fn attack_damage(tool: Option<ToolProperties>) -> u8 {
+    let Some(tool) = tool else {
+        return 1;
+    };
+    let kind = match tool.kind {
+        ToolKind::Shovel => 2,
+        ToolKind::Pickaxe => 3,
+        ToolKind::Axe => 4,
+    };
+    kind + tool.tier as u8
+}
+
+/// Whether breaking the block with this tool (or by hand) drops anything
+pub fn can_harvest(block: &BlockType, tool: Option<ToolProperties>) -> bool {
+    let properties = &block.properties;
+    match properties.min_tool_tier {
+        None => true,
+        Some(min_tier) => tool.is_some_and(|tool| {
+            Some(tool.kind) == properties.preferred_tool && tool.tier >= min_tier
+        }),
+    }
+}
+
+/// Seconds of holding the button to break a block, None for unbreakable blocks
+///
+/// Hardness is scaled by 1.5 when the block can be harvested and by 5 when it can't
+/// (so mining stone by hand is slow as well as fruitless), then divided by the tool's
+/// efficiency if it's the kind the block prefers
+pub fn break_seconds(block: &BlockType, tool: Option<ToolProperties>) -> Option<f32> {
+    let hardness = block.properties.hardness;
+    if hardness < 0.0 {
+        return None;
+    }
+
+    let speed = match tool {
+        Some(tool) if Some(tool.kind) == block.properties.preferred_tool => tool.tier.efficiency(),
+        _ => 1.0,
+    };
+    let penalty = if can_harvest(block, tool) { 1.5 } else { 5.0 };
+    Some(hardness * penalty / speed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stone() -> BlockType {
+        BlockType::builder("test:stone", "Stone")
+            .hardness(1.5)
+            .requires_tool(ToolKind::Pickaxe, ToolTier::Wood)
+            .build()
+    }
+
+    fn tool(kind: ToolKind, tier: ToolTier) -> Option<ToolProperties> {
+        Some(ToolProperties { kind, tier })
+    }
+
+    #[test]
+    fn efficiency_applies_only_to_the_preferred_tool() {
+        let stone = stone();
+        assert_eq!(break_seconds(&stone, None), Some(7.5));
+        assert_eq!(break_seconds(&stone, tool(ToolKind::Shovel, ToolTier::Diamond)), Some(7.5));
+        assert_eq!(break_seconds(&stone, tool(ToolKind::Pickaxe, ToolTier::Wood)), Some(1.125));
+        assert_eq!(break_seconds(&stone, tool(ToolKind::Pickaxe, ToolTier::Diamond)), Some(0.28125));
+
+        let dirt = BlockType::builder("test:dirt", "Dirt").hardness(0.5).tool(ToolKind::Shovel).build();
+        assert_eq!(break_seconds(&dirt, None), Some(0.75));
+        assert_eq!(break_seconds(&dirt, tool(ToolKind::Shovel, ToolTier::Stone)), Some(0.1875));
+
+        let bedrock = BlockType::builder("test:bedrock", "Bedrock").unbreakable().build();
+        assert_eq!(break_seconds(&bedrock, tool(ToolKind::Pickaxe, ToolTier::Diamond)), None);
+    }
+
+    #[test]
+    fn harvesting_needs_the_right_kind_and_tier() {
+        let ore = BlockType::builder("test:ore", "Ore")
+            .hardness(3.0)
+            .requires_tool(ToolKind::Pickaxe, ToolTier::Iron)
+            .build();
+        assert!(!can_harvest(&ore, None));
+        assert!(!can_harvest(&ore, tool(ToolKind::Pickaxe, ToolTier::Stone)));
+        assert!(!can_harvest(&ore, tool(ToolKind::Axe, ToolTier::Diamond)));
+        assert!(can_harvest(&ore, tool(ToolKind::Pickaxe, ToolTier::Iron)));
+        assert!(can_harvest(&ore, tool(ToolKind::Pickaxe, ToolTier::Diamond)));
+
+        assert!(can_harvest(&stone(), tool(ToolKind::Pickaxe, ToolTier::Wood)));
+        assert!(can_harvest(&BlockType::builder("test:dirt", "Dirt").build(), None));
+    }
+}
@@ -0,0 +1,19 @@
+pub mod assets;
+pub mod blocks;
+pub mod commands;
+pub mod components;
+pub mod crafting;
+pub mod entities;
+pub mod items;
+pub mod mods;
+pub mod net;
+pub mod physics;
+pub mod rendering;
+pub mod resources;
+pub mod server;
+pub mod systems;
+pub mod ui;
+pub mod world;
+mod simulation;
+
+pub use simulation::SimulationPlugin;
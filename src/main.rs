@@ -1,13 +1,3 @@
-mod assets;
-mod blocks;
-mod components;
-mod mods;
-mod rendering;
-mod resources;
-mod systems;
-mod ui;
-mod world;
-
 use bevy::prelude::*;
 use bevy::render::settings::{Backends, RenderCreation, WgpuSettings};
 use bevy::render::RenderPlugin;
@@ -17,21 +7,37 @@ use bevy::diagnostic::FrameTimeDiagnosticsPlugin;
 use bevy::pbr::{ExtendedMaterial, MaterialPlugin, StandardMaterial};
 use bevy::text::{TextColor, TextFont};
 
-use components::*;
-use mods::ModPlugin;
-use rendering::*;
-use resources::{GameState, ChunkBorderState, PlayerInventory, PlayerStats, FpsStats};
-use assets::IconsTextureHandle;
-use systems::*;
-use ui::{setup_pause_menu, setup_hotbar, setup_survival_bars};
-use blocks::BlockRegistry;
-use world::{setup_terrain, spawn_chunks_around_player, process_chunk_tasks, get_spawn_height};
+use voxelcraft::{physics, rendering, systems, SimulationPlugin};
+use voxelcraft::components::*;
+use voxelcraft::physics::PhysicsBody;
+use voxelcraft::rendering::*;
+use voxelcraft::commands::run_commands;
+use voxelcraft::resources::{GameState, ChatConsole, ChatLog, ChunkBorderState, PlayerInventory, HotbarItem, FpsStats, CraftingScreen, FurnaceScreen, MiningProgress, MAX_STACK_SIZE};
+use voxelcraft::assets::IconsTextureHandle;
+use voxelcraft::systems::*;
+use voxelcraft::ui::{setup_pause_menu, setup_death_screen, setup_crafting_screen, setup_furnace_screen, setup_hotbar, setup_survival_bars, setup_chat};
+use voxelcraft::blocks::BlockRegistry;
+use voxelcraft::items::{new_stack, ItemRegistry};
+use voxelcraft::world::{setup_terrain, process_chunk_tasks, attach_chunk_meshes, unload_distant_chunks, AnvilImport, ChunkMeshing, Clipboard, EditHistory, Selection, SpawnPoint};
+use voxelcraft::mods::ModPlugin;
+use voxelcraft::net::{ClientNetPlugin, NetClient};
+use voxelcraft::server::DEFAULT_PORT;
+use std::net::ToSocketAddrs;
 
 // Import Crosshair component
-use components::Crosshair;
+use voxelcraft::components::Crosshair;
 
 fn main() {
-    App::new()
+    let mut app = App::new();
+    // `--import <directory>` explores a Minecraft world instead of generated terrain
+    if let Some(world) = std::env::args().skip_while(|arg| arg != "--import").nth(1) {
+        app.insert_resource(AnvilImport::new(world));
+    }
+    // `--connect <address> <name>` plays on a multiplayer server instead of a local world
+    let args: Vec<String> = std::env::args().collect();
+    let client = args.iter().position(|arg| arg == "--connect")
+        .map(|index| connect_to_server(args.get(index + 1), args.get(index + 2)));
+    app
         .add_plugins(
             DefaultPlugins
                 .set(WindowPlugin {
@@ -56,44 +62,51 @@ fn main() {
         .add_plugins(FrameTimeDiagnosticsPlugin)
         .add_plugins(MaterialPlugin::<VoxelExtendedMaterial>::default())
         .add_plugins(MaterialPlugin::<rendering::terrain_material::TerrainMaterial>::default())
-        .add_plugins(ModPlugin)
-        .init_resource::<GameState>()
         .init_resource::<ChunkBorderState>()
         .init_resource::<rendering::IsometricIconCache>()
-        .init_resource::<TimeOfDay>()
-        .init_resource::<SkyLightLevel>()
         .init_resource::<systems::LightingOverlayState>()
-        .init_resource::<PlayerStats>()
+        .init_resource::<CraftingScreen>()
+        .init_resource::<FurnaceScreen>()
+        .init_resource::<MiningProgress>()
         .init_resource::<FpsStats>()
         .init_resource::<IconsTextureHandle>()
-        .add_systems(Startup, (setup, setup_cursor_grab, setup_pause_menu, setup_hotbar, setup_survival_bars, setup_terrain, init_inventory).chain())
-        // Input systems
+        .init_resource::<ChatConsole>()
+        .init_resource::<ChatLog>()
+        .add_event::<SendChat>()
+        // The client's scene and UI go in after the simulation has found the spawn point
+        .add_systems(Startup, (setup, setup_cursor_grab, setup_pause_menu, setup_death_screen, setup_crafting_screen, setup_furnace_screen, setup_hotbar, setup_survival_bars, setup_chat).chain().after(setup_terrain))
+        // Input systems (key bindings are off while typing into the chat console)
+        .add_systems(Update, (handle_window_focus, toggle_pause_menu))
         .add_systems(Update, (
-            handle_window_focus,
-            toggle_pause_menu,
             toggle_chunk_borders,
             toggle_debug_overlay,
             toggle_ui_visibility,
-            toggle_creative_mode,
+            cycle_camera_mode,
             take_screenshot,
-            handle_time_controls,
             systems::toggle_lighting_overlay,
+            undo_redo_keys.before(handle_local_chat).run_if(|state: Res<GameState>| state.accepts_gameplay_input()),
+        ).run_if(chat_closed))
+        // Chat console; Escape closes it rather than pausing
+        .add_systems(Update, (
+            chat_console_input.after(toggle_pause_menu),
+            update_chat_suggestions.after(chat_console_input),
+            update_chat_display.after(update_chat_suggestions).after(show_command_feedback),
         ))
         // Gameplay systems
         .add_systems(Update, (
-            spawn_chunks_around_player.run_if(|state: Res<GameState>| !state.paused),
-            process_chunk_tasks,
             camera_movement_controls.run_if(|state: Res<GameState>| !state.paused),
-            camera_look_controls.run_if(|state: Res<GameState>| !state.paused),
+            camera_look_controls.run_if(|state: Res<GameState>| state.accepts_gameplay_input()),
             hotbar_mouse_wheel_selection.run_if(|state: Res<GameState>| !state.paused),
-            hotbar_number_key_selection.run_if(|state: Res<GameState>| !state.paused),
-            block_interaction.run_if(|state: Res<GameState>| !state.paused),
-            voxel_click_detection.run_if(|state: Res<GameState>| !state.paused),
+            hotbar_number_key_selection.run_if(|state: Res<GameState>| !state.paused).run_if(chat_closed),
+            block_interaction.before(apply_block_edits).run_if(|state: Res<GameState>| state.accepts_gameplay_input()),
+            voxel_click_detection.run_if(|state: Res<GameState>| state.accepts_gameplay_input()),
             update_torch_light,
             follow_player_with_torch_light,
         ))
-        // Remeshing must run after block interaction to see the updated chunk data
-        .add_systems(PostUpdate, remesh_modified_chunks)
+        .add_systems(Update, update_camera_rig.after(physics::interpolate_physics_bodies).after(camera_look_controls))
+        // Remeshing must run after relighting to see the settled light,
+        // and before unloading so no queued command points at a despawned chunk
+        .add_systems(PostUpdate, remesh_modified_chunks.after(relight_chunks).before(unload_distant_chunks))
         // UI update systems
         .add_systems(Update, (
             update_hotbar_selection,
@@ -105,15 +118,18 @@ fn main() {
             update_survival_bars_visibility,
             update_health_display,
             update_hunger_display,
+            update_air_display,
+            update_hurt_flash,
             update_debug_overlay,
             update_click_text_timer,
             update_pause_menu_visibility,
             handle_pause_menu_buttons,
+            update_death_screen_visibility,
+            update_hotbar_counts,
+            update_hotbar_durability,
         ))
         // Day/night cycle systems
         .add_systems(Update, (
-            update_time_of_day,
-            update_sky_light_level,
             systems::update_stale_chunk_lighting,
             update_sun_transform,
             systems::update_night_skybox_alpha,
@@ -123,11 +139,87 @@ fn main() {
             update_sky_position,
             update_voxel_material,
             update_chunk_borders,
+            update_selection_outline,
             update_block_highlight,
             systems::detect_chunk_changes,
             systems::update_lighting_overlay,
-        ))
-        .run();
+        ));
+
+    match client {
+        // The server runs the world: chunks, entities, the hotbar and survival come from it,
+        // and block edits and chat go to it
+        Some(client) => {
+            app.add_plugins(ModPlugin)
+                .add_plugins(ClientNetPlugin)
+                .insert_resource(client)
+                .init_resource::<GameState>()
+                .init_resource::<TimeOfDay>()
+                .init_resource::<SkyLightLevel>()
+                // Models for the other players, mobs, drops and falling blocks it tells us about
+                .add_systems(Update, attach_replicated_models);
+        }
+        None => {
+            app.add_plugins(SimulationPlugin)
+                .init_resource::<ChunkMeshing>()
+                .add_systems(Startup, init_inventory.after(setup_terrain))
+                // Chat and commands run locally
+                .add_systems(Update, (
+                    handle_local_chat.after(chat_console_input).before(run_commands),
+                    show_command_feedback.after(run_commands),
+                ))
+                // Survival and combat the server would handle when connected
+                .add_systems(Update, (
+                    attack_mobs.before(block_interaction).run_if(|state: Res<GameState>| state.accepts_gameplay_input()),
+                    eat_held_food,
+                    handle_player_death,
+                    handle_respawn_button,
+                ))
+                // Models for the chunks and entities the simulation spawned
+                .add_systems(Update, (
+                    attach_chunk_meshes.after(process_chunk_tasks),
+                    attach_falling_block_meshes.after(spawn_falling_blocks),
+                    attach_item_drop_models.after(spawn_item_drops),
+                    attach_mob_models.after(spawn_mobs),
+                    animate_mobs,
+                ))
+                // Crafting and furnace screens
+                .add_systems(Update, (
+                    open_crafting_screen.after(apply_block_edits),
+                    close_crafting_screen.after(toggle_pause_menu),
+                    handle_crafting_clicks,
+                    update_crafting_screen,
+                    open_furnace_screen.after(apply_block_edits),
+                    close_furnace_screen.after(toggle_pause_menu),
+                    handle_furnace_clicks,
+                    update_furnace_screen,
+                ));
+        }
+    }
+    app.run();
+}
+
+/// Connect to the server given after `--connect`, or exit saying why not
+/// The address may leave out the port to use the server's default
+fn connect_to_server(address: Option<&String>, name: Option<&String>) -> NetClient {
+    let (Some(address), Some(name)) = (address, name) else {
+        eprintln!("Usage: voxelcraft --connect <address> <name>");
+        std::process::exit(1);
+    };
+    let resolved = address.to_socket_addrs()
+        .or_else(|_| (address.as_str(), DEFAULT_PORT).to_socket_addrs())
+        .ok()
+        .and_then(|mut addrs| addrs.next());
+    let Some(addr) = resolved else {
+        eprintln!("Couldn't find the server {}", address);
+        std::process::exit(1);
+    };
+    match NetClient::connect(addr, name) {
+        Ok(client) => client,
+        Err(error) => {
+            eprintln!("Couldn't connect to {}: {}", address, error);
+            std::process::exit(1);
+        }
+    }
 }
 
 fn setup(
@@ -136,6 +228,7 @@ fn setup(
     mut materials: ResMut<Assets<VoxelExtendedMaterial>>,
     mut standard_materials: ResMut<Assets<StandardMaterial>>,
     mut images: ResMut<Assets<Image>>,
+    spawn_point: Option<Res<SpawnPoint>>,
 ) {
     // Create daytime skybox (blue gradient)
     let skybox_texture = create_skybox_texture(&mut images);
@@ -243,12 +336,37 @@ fn setup(
     // Set clear color for the window background
     commands.insert_resource(ClearColor(Color::srgb(0.5, 0.7, 0.95)));
 
-    // Spawn camera with first-person controller and bloom effect
-    // Calculate spawn height based on actual terrain at spawn position
-    let spawn_x = 0;
-    let spawn_z = 0;
-    let spawn_y = get_spawn_height(spawn_x, spawn_z, 42); // seed 42 matches ChunkManager default
+    // Spawn the player at the world spawn point (a server moves them where it wants on login)
+    let spawn_feet = spawn_point.map_or(Vec3::ZERO, |spawn_point| spawn_point.feet_position());
 
+    commands.spawn((
+        Transform::from_translation(spawn_feet),
+        Visibility::default(),
+        Player::default(),
+        GameMode::default(),
+        EditHistory::default(),
+        Selection::default(),
+        Clipboard::default(),
+        PhysicsBody::new(spawn_feet, PLAYER_HALF_WIDTH, PLAYER_HEIGHT)
+            .with_step_height(PLAYER_STEP_HEIGHT),
+    )).with_children(|parent| {
+        // Cuboid body parts, only drawn in third person
+        for (size, center, color) in PLAYER_MODEL_PARTS {
+            parent.spawn((
+                Mesh3d(meshes.add(Cuboid::from_size(size))),
+                MeshMaterial3d(standard_materials.add(StandardMaterial {
+                    base_color: color,
+                    perceptual_roughness: 1.0,
+                    ..default()
+                })),
+                Transform::from_translation(center),
+                Visibility::Hidden,
+                PlayerModel,
+            ));
+        }
+    });
+
+    // Spawn the camera rig (placed at the player's eyes by update_camera_rig)
     commands.spawn((
         Camera3d::default(),
         Camera {
@@ -263,7 +381,7 @@ fn setup(
             far: 2000.0,
             ..default()
         }),
-        Transform::from_xyz(spawn_x as f32, spawn_y, spawn_z as f32),
+        Transform::from_translation(spawn_feet + Vec3::Y * PLAYER_EYE_HEIGHT),
         CameraController::default(),
         Tonemapping::TonyMcMapface, // Good tonemapping for outdoor scenes
         // Bloom disabled - causes hazy/foggy appearance
@@ -357,6 +475,7 @@ fn setup(
 fn init_inventory(
     mut commands: Commands,
     block_registry: Res<BlockRegistry>,
+    item_registry: Res<ItemRegistry>,
 ) {
     // Get block IDs for the hotbar
     // Position 1 (index 0): Bedrock
@@ -372,6 +491,18 @@ fn init_inventory(
     let grass = block_registry.get_id("core:grass")
         .expect("core:grass not found in registry");
 
-    let inventory = PlayerInventory::new_with_blocks(bedrock, stone, dirt, grass);
+    let mut inventory = PlayerInventory::new_with_blocks(bedrock, stone, dirt, grass);
+
+    // Position 5 (index 4): Pig spawn eggs, Position 6 (index 5): Bread
+    let stack = |item, count| Some(new_stack(item, count, &item_registry));
+    let block_stack = |id: &str| block_registry.get_id(id).and_then(|block| stack(HotbarItem::Block(block), MAX_STACK_SIZE));
+    let item_stack = |id: &str, count| item_registry.get_id(id).and_then(|item| stack(HotbarItem::Item(item), count));
+    inventory.hotbar[4] = item_stack("core:pig_spawn_egg", 16);
+    inventory.hotbar[5] = item_stack("core:bread", 16);
+
+    // Positions 7-9 (indices 6-8): Logs (for crafting), Wire, Stone Pickaxe
+    inventory.hotbar[6] = block_stack("core:log");
+    inventory.hotbar[7] = block_stack("core:wire");
+    inventory.hotbar[8] = item_stack("core:stone_pickaxe", 1);
     commands.insert_resource(inventory);
 }
@@ -0,0 +1,143 @@
+use bevy::prelude::{IVec3, Vec3};
+use rand::Rng;
+use crate::blocks::{BlockBehavior, BlockState};
+use crate::crafting::Furnace;
+use crate::world::{BlockEntity, BlockWorld, SetBlockFlags, WorldSpawn};
+
+/// Light level needed above a grass block for it to spread
+const GRASS_SPREAD_MIN_LIGHT: u8 = 9;
+
+/// Light level needed above a dirt block for grass to spread onto it
+const GRASS_TARGET_MIN_LIGHT: u8 = 4;
+
+/// Grass spreads onto nearby lit dirt and dies back to dirt when covered
+/// Follows Minecraft's rules: each random tick tries 4 targets in a 3x5x3 box (y -3..+1)
+#[derive(Debug)]
+pub struct GrassBehavior;
+
+impl BlockBehavior for GrassBehavior {
+    fn random_tick(&self, world: &mut BlockWorld, pos: IVec3) {
+        let (Some(grass), Some(dirt)) = (world.registry().get_id("core:grass"), world.registry().get_id("core:dirt")) else {
+            return;
+        };
+
+        let above = pos + IVec3::Y;
+
+        // Covered by an opaque block - grass dies back to dirt
+        if !world.is_transparent(above) {
+            world.set_block(pos, dirt, SetBlockFlags::DEFAULT);
+            return;
+        }
+
+        if world.get_light(above) < GRASS_SPREAD_MIN_LIGHT {
+            return;
+        }
+
+        for _ in 0..4 {
+            let offset = IVec3::new(
+                world.rng().gen_range(-1..=1),
+                world.rng().gen_range(-3..=1),
+                world.rng().gen_range(-1..=1),
+            );
+            let target = pos + offset;
+            let target_above = target + IVec3::Y;
+
+            if world.get_block(target) == Some(dirt)
+                && world.is_transparent(target_above)
+                && world.get_light(target_above) >= GRASS_TARGET_MIN_LIGHT
+            {
+                world.set_block(target, grass, SetBlockFlags::DEFAULT);
+            }
+        }
+    }
+}
+
+/// Using a bed makes it the player's respawn point
+#[derive(Debug)]
+pub struct BedBehavior;
+
+impl BlockBehavior for BedBehavior {
+    fn on_use(&self, world: &mut BlockWorld, pos: IVec3, _state: BlockState) -> bool {
+        world.spawn(WorldSpawn::SpawnPointSet { pos });
+        true
+    }
+}
+
+/// Using a crafting table opens the crafting screen
+#[derive(Debug)]
+pub struct CraftingTableBehavior;
+
+impl BlockBehavior for CraftingTableBehavior {
+    fn on_use(&self, world: &mut BlockWorld, pos: IVec3, _state: BlockState) -> bool {
+        world.spawn(WorldSpawn::OpenCrafting { pos });
+        true
+    }
+}
+
+/// A furnace (lit or unlit) owns a `Furnace` block entity holding its slots
+/// Using it opens the furnace screen; breaking it drops whatever was inside.
+/// Smelting itself runs in the furnace tick system, which also swaps the lit and unlit
+/// blocks without callbacks so the block entity stays put
+#[derive(Debug)]
+pub struct FurnaceBehavior;
+
+impl BlockBehavior for FurnaceBehavior {
+    fn on_placed(&self, world: &mut BlockWorld, pos: IVec3, _old_state: BlockState) {
+        if world.block_entity(pos).is_none() {
+            world.set_block_entity(pos, BlockEntity::Furnace(Furnace::default()));
+        }
+    }
+
+    fn on_broken(&self, world: &mut BlockWorld, pos: IVec3, _old_state: BlockState) {
+        let Some(BlockEntity::Furnace(mut furnace)) = world.take_block_entity(pos) else {
+            return;
+        };
+        for stack in furnace.take_contents() {
+            world.spawn(WorldSpawn::ItemDrop { pos: pos.as_vec3() + Vec3::splat(0.5), stack });
+        }
+    }
+
+    fn on_use(&self, world: &mut BlockWorld, pos: IVec3, _state: BlockState) -> bool {
+        world.spawn(WorldSpawn::OpenFurnace { pos });
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::{TestWorld, WorldRng};
+
+    fn random_tick(world: &mut TestWorld, pos: IVec3) {
+        world.edit(|world| GrassBehavior.random_tick(world, pos));
+    }
+
+    #[test]
+    fn grass_spreads_to_lit_dirt_only() {
+        let mut world = TestWorld::new(IVec3::new(-2, 0, -2), IVec3::new(2, 4, 2));
+        world.rng = WorldRng::from_seed(42).0;
+        let (grass, lit, covered) = (IVec3::new(0, 1, 0), IVec3::new(1, 1, 0), IVec3::new(-1, 1, 0));
+        world.place(grass, "core:grass");
+        world.place(lit, "core:dirt");
+        world.place(covered, "core:dirt");
+        world.place(covered + IVec3::Y, "core:stone");
+
+        for _ in 0..200 {
+            random_tick(&mut world, grass);
+        }
+        assert_eq!(world.block(lit), world.id("core:grass"));
+        assert_eq!(world.block(covered), world.id("core:dirt"));
+        assert_eq!(world.block(grass), world.id("core:grass"));
+    }
+
+    #[test]
+    fn covered_grass_dies_back_to_dirt() {
+        let mut world = TestWorld::new(IVec3::new(-2, 0, -2), IVec3::new(2, 4, 2));
+        let grass = IVec3::new(0, 1, 0);
+        world.place(grass, "core:grass");
+        world.place(grass + IVec3::Y, "core:stone");
+
+        random_tick(&mut world, grass);
+        assert_eq!(world.block(grass), world.id("core:dirt"));
+    }
+}
@@ -1,5 +1,7 @@
 mod mod_trait;
 mod vanilla;
+mod behaviors;
+mod power;
 
 pub use mod_trait::GameMod;
 pub use vanilla::VanillaMod;
@@ -7,6 +9,10 @@ pub use vanilla::VanillaMod;
 use bevy::prelude::*;
 use crate::assets::AssetManager;
 use crate::blocks::BlockRegistry;
+use crate::commands::{register_builtin_commands, CommandDispatcher};
+use crate::crafting::{load_mod_recipes, RecipeRegistry};
+use crate::entities::EntityRegistry;
+use crate::items::ItemRegistry;
 
 /// Resource that holds all registered mods
 #[derive(Resource, Default)]
@@ -32,29 +38,53 @@ impl ModRegistry {
 }
 
 /// System to initialize all mods during startup
+#[allow(clippy::too_many_arguments)]
 pub fn initialize_mods(
     mod_registry: Res<ModRegistry>,
     mut block_registry: ResMut<BlockRegistry>,
+    mut item_registry: ResMut<ItemRegistry>,
+    mut recipe_registry: ResMut<RecipeRegistry>,
+    mut entity_registry: ResMut<EntityRegistry>,
+    mut command_dispatcher: ResMut<CommandDispatcher>,
     mut asset_manager: ResMut<AssetManager>,
-    mut images: ResMut<Assets<Image>>,
+    mut images: Option<ResMut<Assets<Image>>>,
 ) {
     info!("Initializing {} mods", mod_registry.mods.len());
+    register_builtin_commands(&mut command_dispatcher);
 
     for game_mod in mod_registry.mods() {
         info!("Initializing mod: {} v{}", game_mod.id(), game_mod.version());
 
-        // Load mod's texture atlas
-        asset_manager.load_mod_texture_atlas(
-            game_mod.id(),
-            &mut images,
-            game_mod.get_embedded_texture_atlas(),
-        );
-
-        // Register mod's blocks
+        // Load mod's texture atlas (headless apps have no images to load it into)
+        if let Some(images) = images.as_mut() {
+            asset_manager.load_mod_texture_atlas(
+                game_mod.id(),
+                images,
+                game_mod.get_embedded_texture_atlas(),
+            );
+        }
+
+        // Register mod's items, blocks and entities
+        game_mod.register_items(&mut item_registry);
         game_mod.register_blocks(&mut block_registry);
+        game_mod.register_entities(&mut entity_registry);
+        game_mod.register_commands(&mut command_dispatcher);
+    }
+
+    // Recipes can name items and blocks from any mod, so they're loaded once everything exists
+    for game_mod in mod_registry.mods() {
+        load_mod_recipes(game_mod.id(), game_mod.get_embedded_recipes(), &mut recipe_registry, &item_registry, &block_registry);
+        game_mod.register_recipes(&mut recipe_registry, &item_registry, &block_registry);
     }
 
-    info!("All mods initialized. Total blocks: {}", block_registry.block_count());
+    info!(
+        "All mods initialized. Total blocks: {}, items: {}, entities: {}, recipes: {}, commands: {}",
+        block_registry.block_count(),
+        item_registry.item_count(),
+        entity_registry.entity_count(),
+        recipe_registry.crafting_recipes().len(),
+        command_dispatcher.commands().len(),
+    );
 }
 
 /// Plugin for the mod system
@@ -64,6 +94,10 @@ impl Plugin for ModPlugin {
     fn build(&self, app: &mut App) {
         // Initialize registries
         app.init_resource::<BlockRegistry>();
+        app.init_resource::<ItemRegistry>();
+        app.init_resource::<RecipeRegistry>();
+        app.init_resource::<EntityRegistry>();
+        app.init_resource::<CommandDispatcher>();
         app.init_resource::<AssetManager>();
 
         // Create mod registry and register core mods
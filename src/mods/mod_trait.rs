@@ -1,4 +1,8 @@
 use crate::blocks::BlockRegistry;
+use crate::commands::CommandDispatcher;
+use crate::crafting::RecipeRegistry;
+use crate::entities::EntityRegistry;
+use crate::items::ItemRegistry;
 
 /// Trait that all mods must implement
 #[allow(dead_code)]
@@ -21,13 +25,29 @@ pub trait GameMod: Send + Sync {
         None
     }
 
-    /// Called during startup to register items (future implementation)
-    fn register_items(&self, _registry: &mut ()) {
+    /// Get embedded recipe data (the contents of a `recipes.ron`, optional)
+    /// If assets/mods/{id}/recipes.ron exists on disk it is used instead
+    fn get_embedded_recipes(&self) -> Option<&'static str> {
+        None
+    }
+
+    /// Called during startup to register non-block items (tools, food, materials)
+    fn register_items(&self, _registry: &mut ItemRegistry) {
+        // Default implementation does nothing
+    }
+
+    /// Called during startup, after every mod's items and blocks are registered, to add recipes from code
+    fn register_recipes(&self, _recipes: &mut RecipeRegistry, _items: &ItemRegistry, _blocks: &BlockRegistry) {
+        // Default implementation does nothing
+    }
+
+    /// Called during startup to register entity (mob) types
+    fn register_entities(&self, _registry: &mut EntityRegistry) {
         // Default implementation does nothing
     }
 
-    /// Called during startup to register entities (future implementation)
-    fn register_entities(&self, _registry: &mut ()) {
+    /// Called during startup, after the built-in commands, to add chat commands
+    fn register_commands(&self, _dispatcher: &mut CommandDispatcher) {
         // Default implementation does nothing
     }
 }
@@ -0,0 +1,625 @@
+use bevy::prelude::IVec3;
+use std::collections::{HashMap, HashSet, VecDeque};
+use crate::blocks::{BlockBehavior, BlockId, BlockState};
+use crate::world::{BlockWorld, SetBlockFlags, NEIGHBOR_OFFSETS};
+
+/// Strongest signal a power source emits
+pub const MAX_POWER: u8 = 15;
+
+/// How long a pressed button stays on (20 ticks = 1 second)
+const BUTTON_PRESS_TICKS: u64 = 20;
+
+/// Delay before consumers (lamps, doors, pistons) react to a power change
+const CONSUMER_DELAY_TICKS: u64 = 1;
+
+/// Delay before a wire network recomputes after one of its inputs changed
+const WIRE_DELAY_TICKS: u64 = 1;
+
+/// Largest wire network recomputed in one go
+const MAX_WIRE_NETWORK: usize = 4096;
+
+/// Most blocks a piston can push
+const PISTON_PUSH_LIMIT: usize = 12;
+
+/// Data bit for "on" (levers, buttons, pressure plates)
+const POWERED_BIT: u8 = 0b0001;
+
+/// Data bit remembering whether a door was powered at its last update
+const DOOR_POWERED_BIT: u8 = 0b0001;
+
+/// Data bits holding a piston's facing (index into NEIGHBOR_OFFSETS)
+const PISTON_FACING_MASK: u8 = 0b0111;
+
+/// Data bit set while a piston is extended
+const PISTON_EXTENDED_BIT: u8 = 0b1000;
+
+/// Strongest signal the neighbors of `pos` send into it, ignoring the neighbor at `pos + skip`
+fn received_power_except(world: &BlockWorld, pos: IVec3, skip: IVec3) -> u8 {
+    NEIGHBOR_OFFSETS.iter()
+        .filter(|&&offset| offset != skip)
+        .map(|&offset| world.power_output(pos + offset, -offset))
+        .max()
+        .unwrap_or(0)
+}
+
+fn set_powered(world: &mut BlockWorld, pos: IVec3, state: BlockState, powered: bool) {
+    let data = if powered { state.data | POWERED_BIT } else { state.data & !POWERED_BIT };
+    world.set_block(pos, BlockState::with_data(state.id, data), SetBlockFlags::DEFAULT);
+}
+
+/// A switch that toggles between on and off when used
+#[derive(Debug)]
+pub struct LeverBehavior;
+
+impl BlockBehavior for LeverBehavior {
+    fn on_use(&self, world: &mut BlockWorld, pos: IVec3, state: BlockState) -> bool {
+        set_powered(world, pos, state, state.data & POWERED_BIT == 0);
+        true
+    }
+
+    fn power_output(&self, state: BlockState, _direction: IVec3) -> u8 {
+        if state.data & POWERED_BIT != 0 { MAX_POWER } else { 0 }
+    }
+}
+
+/// Emits a short pulse when used
+#[derive(Debug)]
+pub struct ButtonBehavior;
+
+impl BlockBehavior for ButtonBehavior {
+    fn on_use(&self, world: &mut BlockWorld, pos: IVec3, state: BlockState) -> bool {
+        if state.data & POWERED_BIT == 0 {
+            set_powered(world, pos, state, true);
+            world.schedule_tick(pos, BUTTON_PRESS_TICKS);
+        }
+        true
+    }
+
+    fn scheduled_tick(&self, world: &mut BlockWorld, pos: IVec3) {
+        if let Some(state) = world.get_state(pos) {
+            set_powered(world, pos, state, false);
+        }
+    }
+
+    fn power_output(&self, state: BlockState, _direction: IVec3) -> u8 {
+        if state.data & POWERED_BIT != 0 { MAX_POWER } else { 0 }
+    }
+}
+
+/// Powered while an entity stands on it
+#[derive(Debug)]
+pub struct PressurePlateBehavior;
+
+impl BlockBehavior for PressurePlateBehavior {
+    fn on_entity_enter(&self, world: &mut BlockWorld, pos: IVec3) {
+        if let Some(state) = world.get_state(pos) {
+            set_powered(world, pos, state, true);
+        }
+    }
+
+    fn on_entity_leave(&self, world: &mut BlockWorld, pos: IVec3) {
+        if let Some(state) = world.get_state(pos) {
+            set_powered(world, pos, state, false);
+        }
+    }
+
+    fn power_output(&self, state: BlockState, _direction: IVec3) -> u8 {
+        if state.data & POWERED_BIT != 0 { MAX_POWER } else { 0 }
+    }
+}
+
+/// Carries a signal between face-adjacent wires, losing one level per block
+/// The data value is the current signal strength (0-15). When a non-wire neighbor
+/// changes, the whole connected network is recomputed on the next world tick, so
+/// the result never depends on the order updates arrived in
+#[derive(Debug)]
+pub struct WireBehavior;
+
+impl WireBehavior {
+    /// Recompute signal strengths for the wire network containing `start`
+    fn update_network(world: &mut BlockWorld, start: IVec3, wire: BlockId) {
+        // Flood-fill the connected wires
+        let mut network = vec![start];
+        let mut members = HashSet::from([start]);
+        let mut frontier = VecDeque::from([start]);
+        while let Some(pos) = frontier.pop_front() {
+            for offset in NEIGHBOR_OFFSETS {
+                let neighbor = pos + offset;
+                if network.len() < MAX_WIRE_NETWORK
+                    && world.get_block(neighbor) == Some(wire)
+                    && members.insert(neighbor)
+                {
+                    network.push(neighbor);
+                    frontier.push_back(neighbor);
+                }
+            }
+        }
+
+        // Signal entering the network from sources and other non-wire blocks
+        let mut levels: HashMap<IVec3, u8> = HashMap::new();
+        let mut buckets: Vec<Vec<IVec3>> = vec![Vec::new(); MAX_POWER as usize + 1];
+        for &pos in &network {
+            let input = NEIGHBOR_OFFSETS.iter()
+                .filter(|&&offset| !members.contains(&(pos + offset)))
+                .map(|&offset| world.power_output(pos + offset, -offset))
+                .max()
+                .unwrap_or(0);
+            levels.insert(pos, input);
+            buckets[input as usize].push(pos);
+        }
+
+        // Spread strongest-first, losing one level per wire
+        for level in (1..=MAX_POWER).rev() {
+            for pos in std::mem::take(&mut buckets[level as usize]) {
+                if levels[&pos] != level {
+                    continue;
+                }
+                for offset in NEIGHBOR_OFFSETS {
+                    let neighbor = pos + offset;
+                    if let Some(neighbor_level) = levels.get_mut(&neighbor) {
+                        if *neighbor_level < level - 1 {
+                            *neighbor_level = level - 1;
+                            buckets[level as usize - 1].push(neighbor);
+                        }
+                    }
+                }
+            }
+        }
+
+        // Apply in a fixed order so consumer callbacks are deterministic
+        network.sort_by_key(|pos| (pos.x, pos.y, pos.z));
+        for pos in network {
+            let level = levels[&pos];
+            if world.get_state(pos).is_some_and(|state| state.data != level) {
+                world.set_block(pos, BlockState::with_data(wire, level), SetBlockFlags::DEFAULT);
+            }
+        }
+    }
+}
+
+impl BlockBehavior for WireBehavior {
+    fn scheduled_tick(&self, world: &mut BlockWorld, pos: IVec3) {
+        if let Some(wire) = world.get_block(pos) {
+            Self::update_network(world, pos, wire);
+        }
+    }
+
+    fn on_placed(&self, world: &mut BlockWorld, pos: IVec3, _old_state: BlockState) {
+        world.schedule_tick(pos, WIRE_DELAY_TICKS);
+    }
+
+    fn on_neighbor_changed(&self, world: &mut BlockWorld, pos: IVec3, neighbor_pos: IVec3) {
+        // Level changes inside the network are already handled by the network update
+        if world.get_block(neighbor_pos) != world.get_block(pos) {
+            world.schedule_tick(pos, WIRE_DELAY_TICKS);
+        }
+    }
+
+    fn power_output(&self, state: BlockState, _direction: IVec3) -> u8 {
+        state.data
+    }
+}
+
+/// A lamp that swaps between its unlit and lit block while powered
+#[derive(Debug)]
+pub struct LampBehavior {
+    pub unlit: &'static str,
+    pub lit: &'static str,
+}
+
+impl BlockBehavior for LampBehavior {
+    fn scheduled_tick(&self, world: &mut BlockWorld, pos: IVec3) {
+        let (Some(unlit), Some(lit)) = (world.registry().get_id(self.unlit), world.registry().get_id(self.lit)) else {
+            return;
+        };
+        let target = if world.received_power(pos) > 0 { lit } else { unlit };
+        if world.get_block(pos) != Some(target) {
+            world.set_block(pos, target, SetBlockFlags::DEFAULT);
+        }
+    }
+
+    fn on_placed(&self, world: &mut BlockWorld, pos: IVec3, _old_state: BlockState) {
+        world.schedule_tick(pos, CONSUMER_DELAY_TICKS);
+    }
+
+    fn on_neighbor_changed(&self, world: &mut BlockWorld, pos: IVec3, _neighbor_pos: IVec3) {
+        world.schedule_tick(pos, CONSUMER_DELAY_TICKS);
+    }
+}
+
+/// A door that swaps between its closed (solid) and open block
+/// Opens when power rises and closes when it falls; can also be toggled by hand
+#[derive(Debug)]
+pub struct DoorBehavior {
+    pub closed: &'static str,
+    pub open: &'static str,
+}
+
+impl DoorBehavior {
+    fn ids(&self, world: &BlockWorld) -> Option<(BlockId, BlockId)> {
+        Some((world.registry().get_id(self.closed)?, world.registry().get_id(self.open)?))
+    }
+}
+
+impl BlockBehavior for DoorBehavior {
+    fn on_use(&self, world: &mut BlockWorld, pos: IVec3, state: BlockState) -> bool {
+        let Some((closed, open)) = self.ids(world) else {
+            return false;
+        };
+        let toggled = if state.id == open { closed } else { open };
+        world.set_block(pos, BlockState::with_data(toggled, state.data), SetBlockFlags::DEFAULT);
+        true
+    }
+
+    fn scheduled_tick(&self, world: &mut BlockWorld, pos: IVec3) {
+        let (Some((closed, open)), Some(state)) = (self.ids(world), world.get_state(pos)) else {
+            return;
+        };
+        let powered = world.received_power(pos) > 0;
+        let was_powered = state.data & DOOR_POWERED_BIT != 0;
+        if powered == was_powered {
+            return;
+        }
+
+        let id = if powered { open } else { closed };
+        let data = if powered { state.data | DOOR_POWERED_BIT } else { state.data & !DOOR_POWERED_BIT };
+        world.set_block(pos, BlockState::with_data(id, data), SetBlockFlags::DEFAULT);
+    }
+
+    fn on_placed(&self, world: &mut BlockWorld, pos: IVec3, _old_state: BlockState) {
+        world.schedule_tick(pos, CONSUMER_DELAY_TICKS);
+    }
+
+    fn on_neighbor_changed(&self, world: &mut BlockWorld, pos: IVec3, _neighbor_pos: IVec3) {
+        world.schedule_tick(pos, CONSUMER_DELAY_TICKS);
+    }
+}
+
+/// Direction a piston (or piston head) faces
+fn piston_facing(state: BlockState) -> IVec3 {
+    NEIGHBOR_OFFSETS[(state.data & PISTON_FACING_MASK) as usize % NEIGHBOR_OFFSETS.len()]
+}
+
+/// A piston (or piston head) turned to face where `transform` takes its facing, still extended or not
+fn transform_piston(state: BlockState, transform: &dyn Fn(IVec3) -> IVec3) -> BlockState {
+    let facing = transform(piston_facing(state));
+    let index = NEIGHBOR_OFFSETS.iter().position(|&offset| offset == facing).unwrap_or(3);
+    BlockState::with_data(state.id, (state.data & !PISTON_FACING_MASK) | index as u8)
+}
+
+/// Pushes the blocks in front of it one block forward while powered
+/// Powered from any side except its face. Data holds the facing and an extended bit
+#[derive(Debug)]
+pub struct PistonBehavior {
+    pub head: &'static str,
+}
+
+impl PistonBehavior {
+    fn extend(&self, world: &mut BlockWorld, pos: IVec3, state: BlockState, facing: IVec3) {
+        let (Some(head), Some(bedrock)) = (world.registry().get_id(self.head), world.registry().get_id("core:bedrock")) else {
+            return;
+        };
+
+        // Collect the line of blocks to push, stopping at the first empty cell
+        let mut pushed = Vec::new();
+        let mut cursor = pos + facing;
+        loop {
+            let Some(block) = world.get_state(cursor) else {
+                return;
+            };
+            if block.is_air() {
+                break;
+            }
+            // Blocks with a block entity (furnaces) stay put, as in Minecraft, rather than
+            // being broken and placed again without their contents
+            let immovable = block.id == bedrock || block.id == head
+                || (block.id == state.id && block.data & PISTON_EXTENDED_BIT != 0)
+                || world.block_entity(cursor).is_some();
+            if immovable || pushed.len() == PISTON_PUSH_LIMIT {
+                return;
+            }
+            pushed.push((cursor, block));
+            cursor += facing;
+        }
+
+        world.set_block(pos, BlockState::with_data(state.id, state.data | PISTON_EXTENDED_BIT), SetBlockFlags::DEFAULT);
+        for &(block_pos, block) in pushed.iter().rev() {
+            world.set_block(block_pos + facing, block, SetBlockFlags::DEFAULT);
+        }
+        world.set_block(pos + facing, BlockState::with_data(head, state.data & PISTON_FACING_MASK), SetBlockFlags::DEFAULT);
+    }
+
+    fn retract(&self, world: &mut BlockWorld, pos: IVec3, state: BlockState, facing: IVec3) {
+        world.set_block(pos, BlockState::with_data(state.id, state.data & !PISTON_EXTENDED_BIT), SetBlockFlags::DEFAULT);
+        if world.registry().get_id(self.head) == world.get_block(pos + facing) {
+            world.set_block(pos + facing, BlockState::AIR, SetBlockFlags::DEFAULT);
+        }
+    }
+}
+
+impl BlockBehavior for PistonBehavior {
+    fn scheduled_tick(&self, world: &mut BlockWorld, pos: IVec3) {
+        let Some(state) = world.get_state(pos) else {
+            return;
+        };
+        let facing = piston_facing(state);
+        let powered = received_power_except(world, pos, facing) > 0;
+        let extended = state.data & PISTON_EXTENDED_BIT != 0;
+
+        if powered && !extended {
+            self.extend(world, pos, state, facing);
+        } else if !powered && extended {
+            self.retract(world, pos, state, facing);
+        }
+    }
+
+    fn placement_state(&self, state: BlockState, face_normal: IVec3) -> BlockState {
+        let facing = NEIGHBOR_OFFSETS.iter().position(|&offset| offset == face_normal).unwrap_or(3);
+        BlockState::with_data(state.id, facing as u8)
+    }
+
+    fn transform_state(&self, state: BlockState, transform: &dyn Fn(IVec3) -> IVec3) -> BlockState {
+        transform_piston(state, transform)
+    }
+
+    fn on_placed(&self, world: &mut BlockWorld, pos: IVec3, _old_state: BlockState) {
+        world.schedule_tick(pos, CONSUMER_DELAY_TICKS);
+    }
+
+    fn on_broken(&self, world: &mut BlockWorld, pos: IVec3, old_state: BlockState) {
+        // Take the head with us
+        let front = pos + piston_facing(old_state);
+        if old_state.data & PISTON_EXTENDED_BIT != 0 && world.registry().get_id(self.head) == world.get_block(front) {
+            world.set_block(front, BlockState::AIR, SetBlockFlags::DEFAULT);
+        }
+    }
+
+    fn on_neighbor_changed(&self, world: &mut BlockWorld, pos: IVec3, _neighbor_pos: IVec3) {
+        world.schedule_tick(pos, CONSUMER_DELAY_TICKS);
+    }
+}
+
+/// The extended arm of a piston; breaking it retracts the piston
+#[derive(Debug)]
+pub struct PistonHeadBehavior {
+    pub piston: &'static str,
+}
+
+impl BlockBehavior for PistonHeadBehavior {
+    fn transform_state(&self, state: BlockState, transform: &dyn Fn(IVec3) -> IVec3) -> BlockState {
+        transform_piston(state, transform)
+    }
+
+    fn on_broken(&self, world: &mut BlockWorld, pos: IVec3, old_state: BlockState) {
+        let base = pos - piston_facing(old_state);
+        let Some(base_state) = world.get_state(base) else {
+            return;
+        };
+        if Some(base_state.id) == world.registry().get_id(self.piston) && base_state.data & PISTON_EXTENDED_BIT != 0 {
+            world.set_block(base, BlockState::with_data(base_state.id, base_state.data & !PISTON_EXTENDED_BIT), SetBlockFlags::DEFAULT);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::{BlockEntity, TestWorld};
+
+    /// A stone floor at y=0 with room above it for circuits along +X
+    fn floor() -> TestWorld {
+        TestWorld::new(IVec3::new(-8, 0, -8), IVec3::new(24, 8, 8))
+    }
+
+    fn pos(x: i32) -> IVec3 {
+        IVec3::new(x, 1, 0)
+    }
+
+    #[test]
+    fn lever_powers_wire_and_lamp() {
+        let mut world = floor();
+        world.place(pos(0), "core:lever");
+        for x in 1..=3 {
+            world.place(pos(x), "core:wire");
+        }
+        world.place(pos(4), "core:lamp");
+        world.run_ticks(5);
+        assert_eq!(world.block(pos(4)), world.id("core:lamp"));
+
+        assert!(world.use_block(pos(0)));
+        world.run_ticks(5);
+        assert_eq!(world.state(pos(1)).data, 15);
+        assert_eq!(world.state(pos(2)).data, 14);
+        assert_eq!(world.state(pos(3)).data, 13);
+        assert_eq!(world.block(pos(4)), world.id("core:lamp_lit"));
+
+        world.use_block(pos(0));
+        world.run_ticks(5);
+        for x in 1..=3 {
+            assert_eq!(world.state(pos(x)).data, 0);
+        }
+        assert_eq!(world.block(pos(4)), world.id("core:lamp"));
+    }
+
+    #[test]
+    fn signal_decays_over_long_wire() {
+        let mut world = floor();
+        world.place(pos(0), "core:lever");
+        for x in 1..=16 {
+            world.place(pos(x), "core:wire");
+        }
+        world.place(pos(17), "core:lamp");
+        world.use_block(pos(0));
+        world.run_ticks(5);
+
+        assert_eq!(world.state(pos(15)).data, 1);
+        assert_eq!(world.state(pos(16)).data, 0);
+        assert_eq!(world.block(pos(17)), world.id("core:lamp"));
+    }
+
+    #[test]
+    fn breaking_wire_cuts_signal() {
+        let mut world = floor();
+        world.place(pos(0), "core:lever");
+        for x in 1..=3 {
+            world.place(pos(x), "core:wire");
+        }
+        world.place(pos(4), "core:lamp");
+        world.use_block(pos(0));
+        world.run_ticks(5);
+        assert_eq!(world.block(pos(4)), world.id("core:lamp_lit"));
+
+        world.edit(|w| w.set_block(pos(2), BlockState::AIR, SetBlockFlags::DEFAULT));
+        world.run_ticks(5);
+        assert_eq!(world.state(pos(1)).data, 15);
+        assert_eq!(world.state(pos(3)).data, 0);
+        assert_eq!(world.block(pos(4)), world.id("core:lamp"));
+    }
+
+    #[test]
+    fn wire_loop_settles_when_unpowered() {
+        let mut world = floor();
+        world.place(pos(0), "core:lever");
+        // A 3x3 ring of wire next to the lever
+        let ring: Vec<IVec3> = (1..=3).flat_map(|x| (-1..=1).map(move |z| IVec3::new(x, 1, z)))
+            .filter(|p| *p != IVec3::new(2, 1, 0))
+            .collect();
+        for &p in &ring {
+            let wire = world.id("core:wire");
+            world.edit(|w| w.set_block(p, wire, SetBlockFlags::DEFAULT));
+        }
+
+        world.use_block(pos(0));
+        world.run_ticks(5);
+        assert_eq!(world.state(pos(1)).data, 15);
+        assert_eq!(world.state(IVec3::new(3, 1, 0)).data, 11);
+
+        world.use_block(pos(0));
+        world.run_ticks(5);
+        assert!(ring.iter().all(|&p| world.state(p).data == 0));
+    }
+
+    #[test]
+    fn button_pulse_ends() {
+        let mut world = floor();
+        world.place(pos(0), "core:button");
+        world.place(pos(1), "core:lamp");
+        world.use_block(pos(0));
+        world.run_ticks(2);
+        assert_eq!(world.block(pos(1)), world.id("core:lamp_lit"));
+
+        world.run_ticks(BUTTON_PRESS_TICKS + 2);
+        assert_eq!(world.state(pos(0)).data, 0);
+        assert_eq!(world.block(pos(1)), world.id("core:lamp"));
+    }
+
+    #[test]
+    fn pressure_plate_opens_door() {
+        let mut world = floor();
+        world.place(pos(0), "core:pressure_plate");
+        world.place(pos(1), "core:door");
+
+        world.edit(|w| w.entity_entered(pos(0)));
+        world.run_ticks(2);
+        assert_eq!(world.block(pos(1)), world.id("core:door_open"));
+
+        world.edit(|w| w.entity_left(pos(0)));
+        world.run_ticks(2);
+        assert_eq!(world.block(pos(1)), world.id("core:door"));
+    }
+
+    #[test]
+    fn piston_pushes_and_retracts() {
+        let mut world = floor();
+        world.place(pos(0), "core:lever");
+        // Placed against the west face of a block, so it faces +X
+        world.place_facing(pos(1), "core:piston", IVec3::X);
+        world.place(pos(2), "core:dirt");
+        world.place(pos(3), "core:stone");
+
+        world.use_block(pos(0));
+        world.run_ticks(3);
+        assert_eq!(world.block(pos(2)), world.id("core:piston_head"));
+        assert_eq!(world.block(pos(3)), world.id("core:dirt"));
+        assert_eq!(world.block(pos(4)), world.id("core:stone"));
+
+        world.use_block(pos(0));
+        world.run_ticks(3);
+        assert!(world.state(pos(2)).is_air());
+        assert_eq!(world.block(pos(3)), world.id("core:dirt"));
+        assert_eq!(world.state(pos(1)).data & PISTON_EXTENDED_BIT, 0);
+    }
+
+    #[test]
+    fn piston_blocked_by_bedrock() {
+        let mut world = floor();
+        world.place(pos(0), "core:lever");
+        world.place_facing(pos(1), "core:piston", IVec3::X);
+        world.place(pos(2), "core:dirt");
+        world.place(pos(3), "core:bedrock");
+
+        world.use_block(pos(0));
+        world.run_ticks(3);
+        assert_eq!(world.block(pos(2)), world.id("core:dirt"));
+        assert_eq!(world.state(pos(1)).data & PISTON_EXTENDED_BIT, 0);
+    }
+
+    #[test]
+    fn piston_blocked_by_furnace() {
+        let mut world = floor();
+        world.place(pos(0), "core:lever");
+        world.place_facing(pos(1), "core:piston", IVec3::X);
+        world.place(pos(2), "core:dirt");
+        world.place(pos(3), "core:furnace");
+        let Some(BlockEntity::Furnace(furnace)) = world.storage.block_entities.get_mut(&pos(3)) else {
+            panic!("furnace without its block entity");
+        };
+        furnace.burn_ticks = 100;
+        let furnace = BlockEntity::Furnace(furnace.clone());
+
+        world.use_block(pos(0));
+        world.run_ticks(3);
+        assert_eq!(world.block(pos(2)), world.id("core:dirt"));
+        assert_eq!(world.block(pos(3)), world.id("core:furnace"));
+        assert_eq!(world.storage.block_entities.get(&pos(3)), Some(&furnace));
+        assert!(!world.storage.block_entities.contains_key(&pos(4)));
+        assert_eq!(world.state(pos(1)).data & PISTON_EXTENDED_BIT, 0);
+    }
+
+    #[test]
+    fn pistons_turn_with_the_structure_they_are_in() {
+        let world = floor();
+        let piston = world.id("core:piston");
+        let behavior = PistonBehavior { head: "core:piston_head" };
+        let east = behavior.placement_state(BlockState::new(piston), IVec3::X);
+        let extended = BlockState::with_data(piston, east.data | PISTON_EXTENDED_BIT);
+
+        // A quarter turn clockwise seen from above takes east to south
+        let turned = behavior.transform_state(extended, &|d: IVec3| IVec3::new(-d.z, d.y, d.x));
+        assert_eq!(piston_facing(turned), IVec3::Z);
+        assert_ne!(turned.data & PISTON_EXTENDED_BIT, 0);
+        let mirrored = behavior.transform_state(east, &|d: IVec3| d * IVec3::new(-1, 1, 1));
+        assert_eq!(piston_facing(mirrored), IVec3::NEG_X);
+    }
+
+    #[test]
+    fn propagation_is_deterministic() {
+        let build = || {
+            let mut world = floor();
+            world.place(pos(0), "core:lever");
+            world.place(IVec3::new(6, 1, 2), "core:lever");
+            for x in 1..=6 {
+                world.place(pos(x), "core:wire");
+                world.place(IVec3::new(x, 1, 1), "core:wire");
+            }
+            world.place(pos(7), "core:lamp");
+            world.use_block(pos(0));
+            world.use_block(IVec3::new(6, 1, 2));
+            world.run_ticks(10);
+            let mut states: Vec<_> = world.storage.blocks.into_iter().collect();
+            states.sort_by_key(|(p, _)| (p.x, p.y, p.z));
+            states
+        };
+        assert_eq!(build(), build());
+    }
+}
@@ -1,5 +1,13 @@
+use bevy::prelude::{Color, Vec3};
 use super::mod_trait::GameMod;
+use super::behaviors::{BedBehavior, CraftingTableBehavior, FurnaceBehavior, GrassBehavior};
+use super::power::{
+    LeverBehavior, ButtonBehavior, PressurePlateBehavior, WireBehavior,
+    LampBehavior, DoorBehavior, PistonBehavior, PistonHeadBehavior,
+};
 use crate::blocks::{BlockRegistry, BlockType, BlockTextures, AtlasCoord, FaceTints};
+use crate::entities::{Behavior, EntityRegistry, EntityType, MobCategory, ModelPart};
+use crate::items::{ItemRegistry, ItemType, ToolKind, ToolTier};
 
 /// The core/vanilla mod that provides basic Minecraft-like blocks
 pub struct VanillaMod;
@@ -22,6 +30,77 @@ impl GameMod for VanillaMod {
         Some(include_bytes!("../../assets/mods/core/textures/atlas.png"))
     }
 
+    fn get_embedded_recipes(&self) -> Option<&'static str> {
+        Some(include_str!("../../assets/mods/core/recipes.ron"))
+    }
+
+    fn register_items(&self, registry: &mut ItemRegistry) {
+        let simple = [
+            ("core:torch", "Torch"),
+            ("core:iron_ingot", "Iron Ingot"),
+            ("core:diamond", "Diamond"),
+        ];
+        for (id, name) in simple {
+            registry.register_item(ItemType::builder(id, name).build())
+                .expect("Failed to register item");
+        }
+
+        // Fuel - burn times in world ticks (a furnace smelts one item per 200)
+        let fuels = [
+            ("core:stick", "Stick", 100),
+            ("core:coal", "Coal", 1600),
+            ("core:charcoal", "Charcoal", 1600),
+        ];
+        for (id, name, ticks) in fuels {
+            registry.register_item(ItemType::builder(id, name).fuel(ticks).build())
+                .expect("Failed to register item");
+        }
+
+        registry.register_item(ItemType::builder("core:apple", "Apple").food(4, 0.3).build())
+            .expect("Failed to register apple");
+        registry.register_item(ItemType::builder("core:bread", "Bread").food(5, 0.6).build())
+            .expect("Failed to register bread");
+        registry.register_item(ItemType::builder("core:raw_porkchop", "Raw Porkchop").food(3, 0.3).build())
+            .expect("Failed to register raw porkchop");
+        registry.register_item(ItemType::builder("core:cooked_porkchop", "Cooked Porkchop").food(8, 0.8).build())
+            .expect("Failed to register cooked porkchop");
+
+        registry.register_item(ItemType::builder("core:rotten_flesh", "Rotten Flesh").food(4, 0.1).build())
+            .expect("Failed to register rotten flesh");
+
+        registry.register_item(ItemType::builder("core:pig_spawn_egg", "Pig Spawn Egg").spawns("core:pig").build())
+            .expect("Failed to register pig spawn egg");
+        registry.register_item(ItemType::builder("core:zombie_spawn_egg", "Zombie Spawn Egg").spawns("core:zombie").build())
+            .expect("Failed to register zombie spawn egg");
+
+        // Tools: core:{tier}_{kind}, e.g. core:stone_pickaxe
+        let tiers = [
+            (ToolTier::Wood, "wooden", "Wooden"),
+            (ToolTier::Stone, "stone", "Stone"),
+            (ToolTier::Iron, "iron", "Iron"),
+            (ToolTier::Diamond, "diamond", "Diamond"),
+        ];
+        let kinds = [
+            (ToolKind::Pickaxe, "pickaxe", "Pickaxe"),
+            (ToolKind::Shovel, "shovel", "Shovel"),
+            (ToolKind::Axe, "axe", "Axe"),
+        ];
+        for (tier, tier_id, tier_name) in tiers {
+            for (kind, kind_id, kind_name) in kinds {
+                let mut tool = ItemType::builder(
+                    &format!("core:{}_{}", tier_id, kind_id),
+                    &format!("{} {}", tier_name, kind_name),
+                )
+                    .tool(kind, tier);
+                if tier == ToolTier::Wood {
+                    tool = tool.fuel(200);
+                }
+                registry.register_item(tool.build())
+                    .expect("Failed to register tool");
+            }
+        }
+    }
+
     fn register_blocks(&self, registry: &mut BlockRegistry) {
         // Note: Air is already registered in the BlockRegistry default constructor
 
@@ -31,12 +110,28 @@ impl GameMod for VanillaMod {
         // (2, 0) = Dirt
         // (3, 0) = Grass side
         // (1, 1) = Bedrock
+        // (2, 1) = Sand
+        // (3, 1) = Gravel
+        // (4, 0) = Oak planks
+        // (4, 1) / (5, 1) = Oak log side / top
+        // (11, 2) / (11, 3) = Crafting table top / side
+        // (2, 2) / (1, 2) / (2, 3) = Coal / iron / diamond ore
+        // (6, 8) / (6, 9) = Bed top / side
+        // (13, 12) = Water
+        // (0, 6) = Lever
+        // (1, 6) = Wooden door (lower half)
+        // (11, 6) / (12, 6) / (13, 6) = Piston face / side / back
+        // (4, 10) = Redstone dust (grayscale, gets tinted)
+        // (3, 13) / (4, 13) = Redstone lamp off / on
+        // (0, 4) = White wool
 
         // Stone - uniform gray texture at (1, 0)
         let stone = BlockType::builder("core:stone", "Stone")
             .solid(true)
             .transparent(false)
             .textures(BlockTextures::uniform(AtlasCoord::new(1, 0)))
+            .hardness(1.5)
+            .requires_tool(ToolKind::Pickaxe, ToolTier::Wood)
             .build();
         registry.register_block(stone)
             .expect("Failed to register stone block");
@@ -46,6 +141,8 @@ impl GameMod for VanillaMod {
             .solid(true)
             .transparent(false)
             .textures(BlockTextures::uniform(AtlasCoord::new(2, 0)))
+            .hardness(0.5)
+            .tool(ToolKind::Shovel)
             .build();
         registry.register_block(dirt)
             .expect("Failed to register dirt block");
@@ -53,9 +150,12 @@ impl GameMod for VanillaMod {
         // Grass - grass top (0,0), dirt bottom (2,0), dirt sides (2,0) with grass overlay (6,2)
         // Top is grayscale and gets tinted with Minecraft's default grass color #7cbd6b
         // Sides use dirt texture with grayscale grass overlay that also gets tinted
+        // Random ticks spread grass onto lit dirt and turn covered grass back to dirt
         let grass = BlockType::builder("core:grass", "Grass Block")
             .solid(true)
             .transparent(false)
+            .random_ticks(true)
+            .behavior(GrassBehavior)
             .textures(BlockTextures::with_side_overlay(
                 AtlasCoord::new(0, 0),  // Grass top (grayscale, gets tinted)
                 AtlasCoord::new(2, 0),  // Dirt bottom (no tint)
@@ -70,6 +170,9 @@ impl GameMod for VanillaMod {
                 east: None,
                 west: None,
             })
+            .hardness(0.6)
+            .tool(ToolKind::Shovel)
+            .drops("core:dirt")
             .build();
         registry.register_block(grass)
             .expect("Failed to register grass block");
@@ -79,8 +182,315 @@ impl GameMod for VanillaMod {
             .solid(true)
             .transparent(false)
             .textures(BlockTextures::uniform(AtlasCoord::new(1, 1)))
+            .unbreakable()
             .build();
         registry.register_block(bedrock)
             .expect("Failed to register bedrock block");
+
+        // Sand - falls when unsupported, uniform texture at (2, 1)
+        let sand = BlockType::builder("core:sand", "Sand")
+            .solid(true)
+            .transparent(false)
+            .gravity(true)
+            .textures(BlockTextures::uniform(AtlasCoord::new(2, 1)))
+            .hardness(0.5)
+            .tool(ToolKind::Shovel)
+            .build();
+        registry.register_block(sand)
+            .expect("Failed to register sand block");
+
+        // Gravel - falls when unsupported, uniform texture at (3, 1)
+        let gravel = BlockType::builder("core:gravel", "Gravel")
+            .solid(true)
+            .transparent(false)
+            .gravity(true)
+            .textures(BlockTextures::uniform(AtlasCoord::new(3, 1)))
+            .hardness(0.6)
+            .tool(ToolKind::Shovel)
+            .build();
+        registry.register_block(gravel)
+            .expect("Failed to register gravel block");
+
+        // Water - walk-through liquid the player can drown in, texture at (13, 12)
+        let water = BlockType::builder("core:water", "Water")
+            .solid(false)
+            .transparent(true)
+            .liquid(true)
+            .textures(BlockTextures::uniform(AtlasCoord::new(13, 12)))
+            .tint_colors(FaceTints::uniform((0.25, 0.45, 0.9)))
+            .unbreakable()
+            .build();
+        registry.register_block(water)
+            .expect("Failed to register water block");
+
+        // Bed - a low block that sets the player's respawn point when used
+        let bed = BlockType::builder("core:bed", "Bed")
+            .solid(true)
+            .transparent(true)
+            .collision_height(0.5625)
+            .textures(BlockTextures::top_bottom_sides(AtlasCoord::new(6, 8), AtlasCoord::new(4, 0), AtlasCoord::new(6, 9)))
+            .behavior(BedBehavior)
+            .hardness(0.2)
+            .build();
+        registry.register_block(bed)
+            .expect("Failed to register bed block");
+
+        // Log and planks - the raw material for crafting
+        let log = BlockType::builder("core:log", "Oak Log")
+            .solid(true)
+            .transparent(false)
+            .textures(BlockTextures::top_bottom_sides(AtlasCoord::new(5, 1), AtlasCoord::new(5, 1), AtlasCoord::new(4, 1)))
+            .hardness(2.0)
+            .tool(ToolKind::Axe)
+            .fuel(300)
+            .build();
+        registry.register_block(log)
+            .expect("Failed to register log block");
+
+        let planks = BlockType::builder("core:planks", "Oak Planks")
+            .solid(true)
+            .transparent(false)
+            .textures(BlockTextures::uniform(AtlasCoord::new(4, 0)))
+            .hardness(2.0)
+            .tool(ToolKind::Axe)
+            .fuel(300)
+            .build();
+        registry.register_block(planks)
+            .expect("Failed to register planks block");
+
+        // Crafting table - using it opens the 3x3 crafting grid
+        let crafting_table = BlockType::builder("core:crafting_table", "Crafting Table")
+            .solid(true)
+            .transparent(false)
+            .behavior(CraftingTableBehavior)
+            .textures(BlockTextures::top_bottom_sides(AtlasCoord::new(11, 2), AtlasCoord::new(4, 0), AtlasCoord::new(11, 3)))
+            .hardness(2.5)
+            .tool(ToolKind::Axe)
+            .fuel(300)
+            .build();
+        registry.register_block(crafting_table)
+            .expect("Failed to register crafting table block");
+
+        // Furnace - smelts with fuel; the lit furnace is a separate block that gives off light
+        for (id, name, front, light) in [
+            ("core:furnace", "Furnace", AtlasCoord::new(12, 2), 0),
+            ("core:furnace_lit", "Lit Furnace", AtlasCoord::new(13, 3), 13),
+        ] {
+            let mut textures = BlockTextures::top_bottom_sides(AtlasCoord::new(14, 3), AtlasCoord::new(14, 3), AtlasCoord::new(13, 2));
+            textures.north = front;
+            let furnace = BlockType::builder(id, name)
+                .solid(true)
+                .transparent(false)
+                .light_emission(light)
+                .hardness(3.5)
+                .requires_tool(ToolKind::Pickaxe, ToolTier::Wood)
+                .drops("core:furnace")
+                .behavior(FurnaceBehavior)
+                .textures(textures)
+                .build();
+            registry.register_block(furnace)
+                .expect("Failed to register furnace block");
+        }
+
+        // Ores - each needs a pickaxe of at least the given tier to drop anything
+        for (id, name, texture, min_tier, drops) in [
+            ("core:coal_ore", "Coal Ore", AtlasCoord::new(2, 2), ToolTier::Wood, Some("core:coal")),
+            ("core:iron_ore", "Iron Ore", AtlasCoord::new(1, 2), ToolTier::Stone, None),
+            ("core:diamond_ore", "Diamond Ore", AtlasCoord::new(2, 3), ToolTier::Iron, Some("core:diamond")),
+        ] {
+            let mut ore = BlockType::builder(id, name)
+                .solid(true)
+                .transparent(false)
+                .hardness(3.0)
+                .requires_tool(ToolKind::Pickaxe, min_tier)
+                .textures(BlockTextures::uniform(texture));
+            if let Some(item) = drops {
+                ore = ore.drops(item);
+            }
+            registry.register_block(ore.build())
+                .expect("Failed to register ore block");
+        }
+
+        // Unknown block - what Minecraft blocks without a mapping are imported as,
+        // tinted magenta so they stand out
+        let unknown = BlockType::builder("core:unknown", "Unknown Block")
+            .solid(true)
+            .transparent(false)
+            .textures(BlockTextures::uniform(AtlasCoord::new(0, 4)))
+            .tint_colors(FaceTints::uniform((0.9, 0.2, 0.9)))
+            .hardness(0.5)
+            .build();
+        registry.register_block(unknown)
+            .expect("Failed to register unknown block");
+
+        self.register_power_blocks(registry);
+    }
+
+    fn register_entities(&self, registry: &mut EntityRegistry) {
+        // Pig: pink-tinted wool boxes, facing -Z, legs swinging in diagonal pairs
+        let wool = AtlasCoord::new(0, 4);
+        let pink = Color::srgb(0.95, 0.68, 0.68);
+        let leg = |x: f32, z: f32, phase: f32| {
+            ModelPart::new(Vec3::new(0.2, 0.3, 0.2), Vec3::new(x, 0.15, z), wool).tint(pink).swing(phase)
+        };
+        let pig = EntityType::builder("core:pig", "Pig")
+            .size(0.45, 0.9)
+            .health(10)
+            .part(ModelPart::new(Vec3::new(0.6, 0.5, 0.95), Vec3::new(0.0, 0.55, 0.05), wool).tint(pink))
+            .part(ModelPart::new(Vec3::new(0.5, 0.5, 0.5), Vec3::new(0.0, 0.75, -0.6), wool).tint(pink))
+            .part(ModelPart::new(Vec3::new(0.25, 0.18, 0.06), Vec3::new(0.0, 0.68, -0.88), wool).tint(Color::srgb(0.9, 0.55, 0.6)))
+            .part(leg(-0.17, -0.3, 1.0))
+            .part(leg(0.17, -0.3, -1.0))
+            .part(leg(-0.17, 0.35, -1.0))
+            .part(leg(0.17, 0.35, 1.0))
+            .walk_speed(1.5)
+            .step_height(0.6)
+            .behavior(Behavior::Swim)
+            .behavior(Behavior::Panic { ticks: 100, speed: 2.0 })
+            .behavior(Behavior::Wander { chance: 120, range: 8.0 })
+            .behavior(Behavior::LookAtPlayer { range: 6.0 })
+            .drops("core:raw_porkchop", 1, 3)
+            .natural_spawn(MobCategory::Passive, 10, 2, 4)
+            .spawns_on("core:grass")
+            .build();
+        registry.register_entity(pig).expect("Failed to register pig");
+
+        // Zombie: green skin, blue clothes, arms held out in front
+        let skin = Color::srgb(0.35, 0.55, 0.3);
+        let shirt = Color::srgb(0.2, 0.5, 0.6);
+        let trousers = Color::srgb(0.25, 0.25, 0.55);
+        let zombie_leg = |x: f32, phase: f32| {
+            ModelPart::new(Vec3::new(0.25, 0.75, 0.25), Vec3::new(x, 0.375, 0.0), wool).tint(trousers).swing(phase)
+        };
+        let zombie = EntityType::builder("core:zombie", "Zombie")
+            .size(0.3, 1.95)
+            .health(20)
+            .part(ModelPart::new(Vec3::new(0.5, 0.75, 0.25), Vec3::new(0.0, 1.125, 0.0), wool).tint(shirt))
+            .part(ModelPart::new(Vec3::new(0.5, 0.5, 0.5), Vec3::new(0.0, 1.75, 0.0), wool).tint(skin))
+            .part(ModelPart::new(Vec3::new(0.25, 0.25, 0.75), Vec3::new(-0.375, 1.375, -0.25), wool).tint(skin))
+            .part(ModelPart::new(Vec3::new(0.25, 0.25, 0.75), Vec3::new(0.375, 1.375, -0.25), wool).tint(skin))
+            .part(zombie_leg(-0.125, 1.0))
+            .part(zombie_leg(0.125, -1.0))
+            .walk_speed(2.0)
+            .step_height(0.6)
+            .behavior(Behavior::Swim)
+            .behavior(Behavior::MeleeAttack { range: 16.0, damage: 3 })
+            .behavior(Behavior::Wander { chance: 120, range: 8.0 })
+            .behavior(Behavior::LookAtPlayer { range: 8.0 })
+            .drops("core:rotten_flesh", 0, 2)
+            .natural_spawn(MobCategory::Hostile, 100, 1, 3)
+            .build();
+        registry.register_entity(zombie).expect("Failed to register zombie");
+    }
+}
+
+impl VanillaMod {
+    /// Signal sources, wire and the blocks that react to power
+    fn register_power_blocks(&self, registry: &mut BlockRegistry) {
+        // Sources are non-solid so they can sit next to a walkway
+        let lever = BlockType::builder("core:lever", "Lever")
+            .solid(false)
+            .transparent(true)
+            .behavior(LeverBehavior)
+            .textures(BlockTextures::uniform(AtlasCoord::new(0, 6)))
+            .hardness(0.5)
+            .build();
+        registry.register_block(lever)
+            .expect("Failed to register lever block");
+
+        let button = BlockType::builder("core:button", "Stone Button")
+            .solid(false)
+            .transparent(true)
+            .behavior(ButtonBehavior)
+            .textures(BlockTextures::uniform(AtlasCoord::new(1, 0)))
+            .hardness(0.5)
+            .tool(ToolKind::Pickaxe)
+            .build();
+        registry.register_block(button)
+            .expect("Failed to register button block");
+
+        let pressure_plate = BlockType::builder("core:pressure_plate", "Pressure Plate")
+            .solid(false)
+            .transparent(true)
+            .behavior(PressurePlateBehavior)
+            .textures(BlockTextures::uniform(AtlasCoord::new(1, 0)))
+            .hardness(0.5)
+            .requires_tool(ToolKind::Pickaxe, ToolTier::Wood)
+            .build();
+        registry.register_block(pressure_plate)
+            .expect("Failed to register pressure plate block");
+
+        // Wire - data value holds the signal strength, tinted red like redstone dust
+        let wire = BlockType::builder("core:wire", "Redstone Wire")
+            .solid(false)
+            .transparent(true)
+            .behavior(WireBehavior)
+            .textures(BlockTextures::uniform(AtlasCoord::new(4, 10)))
+            .tint_colors(FaceTints::uniform((0.8, 0.05, 0.05)))
+            .hardness(0.0)
+            .build();
+        registry.register_block(wire)
+            .expect("Failed to register wire block");
+
+        // Lamp - unlit and lit are separate blocks so each gets its own texture
+        for (id, name, texture, light) in [
+            ("core:lamp", "Redstone Lamp", AtlasCoord::new(3, 13), 0),
+            ("core:lamp_lit", "Lit Redstone Lamp", AtlasCoord::new(4, 13), 15),
+        ] {
+            let lamp = BlockType::builder(id, name)
+                .solid(true)
+                .transparent(false)
+                .light_emission(light)
+                .hardness(0.3)
+                .drops("core:lamp")
+                .behavior(LampBehavior { unlit: "core:lamp", lit: "core:lamp_lit" })
+                .textures(BlockTextures::uniform(texture))
+                .build();
+            registry.register_block(lamp)
+                .expect("Failed to register lamp block");
+        }
+
+        // Door - the open door is a separate non-solid block
+        for (id, name, solid) in [("core:door", "Door", true), ("core:door_open", "Open Door", false)] {
+            let door = BlockType::builder(id, name)
+                .solid(solid)
+                .transparent(!solid)
+                .hardness(3.0)
+                .tool(ToolKind::Axe)
+                .drops("core:door")
+                .door()
+                .behavior(DoorBehavior { closed: "core:door", open: "core:door_open" })
+                .textures(BlockTextures::uniform(AtlasCoord::new(1, 6)))
+                .build();
+            registry.register_block(door)
+                .expect("Failed to register door block");
+        }
+
+        // Piston - data holds the facing (from the face it was placed against) and an extended bit
+        let piston = BlockType::builder("core:piston", "Piston")
+            .solid(true)
+            .transparent(false)
+            .behavior(PistonBehavior { head: "core:piston_head" })
+            .textures(BlockTextures::top_bottom_sides(
+                AtlasCoord::new(11, 6),
+                AtlasCoord::new(13, 6),
+                AtlasCoord::new(12, 6),
+            ))
+            .hardness(0.5)
+            .tool(ToolKind::Pickaxe)
+            .build();
+        registry.register_block(piston)
+            .expect("Failed to register piston block");
+
+        let piston_head = BlockType::builder("core:piston_head", "Piston Head")
+            .solid(true)
+            .transparent(false)
+            .behavior(PistonHeadBehavior { piston: "core:piston" })
+            .textures(BlockTextures::uniform(AtlasCoord::new(11, 6)))
+            .hardness(0.5)
+            .tool(ToolKind::Pickaxe)
+            .build();
+        registry.register_block(piston_head)
+            .expect("Failed to register piston head block");
     }
 }
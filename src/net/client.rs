@@ -0,0 +1,613 @@
+use bevy::prelude::*;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::f32::consts::{PI, TAU};
+use std::io::{self, ErrorKind};
+use std::net::{Ipv4Addr, SocketAddr, UdpSocket};
+use std::time::{Duration, Instant};
+use crate::blocks::BlockRegistry;
+use crate::components::{GameMode, Player};
+use crate::physics::{begin_physics_step, interpolate_physics_bodies, step_player, ChunkColliders, MoveCommand, PhysicsBody};
+use crate::resources::{ChatLog, PlayerInventory, PlayerStats};
+use crate::systems::{BlockEditRequest, NeedsRemesh, SendChat};
+use crate::world::{world_to_chunk_local, Chunk, ChunkCoord, ChunkManager, Selection, NEIGHBOR_OFFSETS, TICKS_PER_SECOND};
+use super::connection::{Connection, NetError};
+use super::protocol::{
+    decode_datagram, encode_datagram, Channel, ClientPacket, NetEntityKind, Packet, ServerPacket, PROTOCOL_VERSION,
+};
+
+/// Seconds between UDP hellos until the server confirms one
+const UDP_HELLO_SECONDS: f32 = 1.0;
+
+/// Largest datagram read; the packets sent over UDP are far smaller
+const MAX_DATAGRAM: usize = 1500;
+
+/// Move commands sent in each packet: the newest and resends of the ones before it,
+/// so a lost datagram doesn't lose a step
+const REDUNDANT_MOVES: usize = 4;
+
+/// Unacknowledged move commands kept for replay before the oldest are given up on
+const MAX_PENDING_MOVES: usize = 200;
+
+/// How far behind the server's latest tick other entities are shown, so there is
+/// usually a snapshot on either side to interpolate between
+const INTERPOLATION_DELAY_TICKS: f64 = 2.0;
+
+/// A connection to a multiplayer server
+/// Insert one (from `NetClient::connect`) to let `ClientNetPlugin` play on that server
+#[derive(Resource)]
+pub struct NetClient {
+    connection: Connection,
+    /// Connected to the server's port, which serves UDP next to TCP
+    udp: UdpSocket,
+    /// Our player's entity id on the server, once logged in
+    player_id: Option<u64>,
+    udp_token: u64,
+    udp_ready: bool,
+    next_udp_hello: f32,
+    /// Hotbar slot last sent
+    last_slot: Option<usize>,
+    disconnect_reason: Option<String>,
+    /// Extra delay on every packet each way (see `with_latency`)
+    latency: Duration,
+    /// Packets received but held back until the simulated latency has passed
+    inbound: VecDeque<(Instant, ServerPacket)>,
+    /// Packets to send once the simulated latency has passed
+    outbound: VecDeque<(Instant, ClientPacket)>,
+}
+
+impl NetClient {
+    /// Connect to the server at `addr` and ask to log in as `name`
+    pub fn connect(addr: SocketAddr, name: &str) -> io::Result<Self> {
+        let mut connection = Connection::connect(addr)?;
+        let udp = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0))?;
+        udp.connect(addr)?;
+        udp.set_nonblocking(true)?;
+        connection.send(&ClientPacket::Login { protocol_version: PROTOCOL_VERSION, name: name.to_string() });
+        Ok(Self {
+            connection,
+            udp,
+            player_id: None,
+            udp_token: 0,
+            udp_ready: false,
+            next_udp_hello: 0.0,
+            last_slot: None,
+            disconnect_reason: None,
+            latency: Duration::ZERO,
+            inbound: VecDeque::new(),
+            outbound: VecDeque::new(),
+        })
+    }
+
+    /// Delay every packet after login by `latency` each way, to try out prediction on a
+    /// fast network (loopback tests)
+    pub fn with_latency(mut self, latency: Duration) -> Self {
+        self.latency = latency;
+        self
+    }
+
+    /// Our player's entity id on the server, once logged in
+    pub fn player_id(&self) -> Option<u64> {
+        self.player_id
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.disconnect_reason.is_none()
+    }
+
+    /// Why the connection ended (empty when the server just went away)
+    pub fn disconnect_reason(&self) -> Option<&str> {
+        self.disconnect_reason.as_deref()
+    }
+
+    /// Whether the server has heard our UDP hello, so fast updates come by datagram
+    pub fn udp_ready(&self) -> bool {
+        self.udp_ready
+    }
+
+    /// Send a packet, after the simulated latency if there is one
+    fn send(&mut self, packet: ClientPacket) {
+        if self.latency.is_zero() {
+            self.send_now(&packet);
+        } else {
+            self.outbound.push_back((Instant::now() + self.latency, packet));
+        }
+    }
+
+    /// Send over UDP when the packet is unreliable and the server knows our address, else over TCP
+    fn send_now(&mut self, packet: &ClientPacket) {
+        if self.udp_ready && packet.channel() == Channel::Unreliable {
+            // A lost datagram is just a missed update
+            let _ = self.udp.send(&encode_datagram(self.udp_token, packet));
+        } else {
+            self.connection.send(packet);
+        }
+    }
+}
+
+/// Where a replicated entity was at one world tick
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Snapshot {
+    pub tick: u64,
+    pub position: Vec3,
+    pub yaw: f32,
+    pub pitch: f32,
+}
+
+/// An entity the server tells us about (other players, mobs, item drops, falling blocks)
+/// Its `Transform` is interpolated between the snapshots as they come in
+#[derive(Component, Debug, Clone)]
+pub struct Replicated {
+    /// The entity's id on the server
+    pub id: u64,
+    pub kind: NetEntityKind,
+    /// Received snapshots not yet passed, oldest first
+    pub snapshots: VecDeque<Snapshot>,
+}
+
+impl Replicated {
+    /// Add a snapshot from the server; ones older than the newest (late datagrams) are dropped
+    fn push(&mut self, snapshot: Snapshot) {
+        let Some(&last) = self.snapshots.back() else {
+            self.snapshots.push_back(snapshot);
+            return;
+        };
+        if snapshot.tick <= last.tick {
+            return;
+        }
+        // Entities are only sent when they move, so one that sat still until now
+        // starts moving from where it sat a tick ago rather than crawling there from long before
+        if snapshot.tick > last.tick + 1 {
+            self.snapshots.push_back(Snapshot { tick: snapshot.tick - 1, ..last });
+        }
+        self.snapshots.push_back(snapshot);
+    }
+
+    /// The interpolated snapshot at `tick`, dropping the snapshots passed
+    fn sample(&mut self, tick: f64) -> Option<Snapshot> {
+        while self.snapshots.len() > 1 && self.snapshots[1].tick as f64 <= tick {
+            self.snapshots.pop_front();
+        }
+        let from = *self.snapshots.front()?;
+        let Some(&to) = self.snapshots.get(1) else {
+            return Some(from);
+        };
+        let t = ((tick - from.tick as f64) / (to.tick - from.tick) as f64).clamp(0.0, 1.0) as f32;
+        let turn = |from: f32, to: f32| from + ((to - from + PI).rem_euclid(TAU) - PI) * t;
+        Some(Snapshot {
+            tick: from.tick,
+            position: from.position.lerp(to.position, t),
+            yaw: turn(from.yaw, to.yaw),
+            pitch: from.pitch + (to.pitch - from.pitch) * t,
+        })
+    }
+}
+
+/// Local entities of the replicated entities, by server id
+#[derive(Resource, Debug, Default)]
+pub struct ReplicatedEntities {
+    pub entities: HashMap<u64, Entity>,
+}
+
+/// The server's world tick as last heard, to tell where between snapshots to show things
+#[derive(Resource, Debug, Default)]
+pub struct ServerClock {
+    pub latest_tick: u64,
+    /// Elapsed seconds when `latest_tick` arrived
+    pub received_at: f32,
+}
+
+impl ServerClock {
+    fn observe(&mut self, tick: u64, now: f32) {
+        if tick > self.latest_tick {
+            self.latest_tick = tick;
+            self.received_at = now;
+        }
+    }
+
+    /// The server's tick now, going by how long ago the latest one arrived
+    pub fn estimated_tick(&self, now: f32) -> f64 {
+        self.latest_tick as f64 + (now - self.received_at) as f64 * TICKS_PER_SECOND
+    }
+}
+
+/// The server's answer to our move commands, waiting to be reconciled with
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct AuthoritativeState {
+    tick: u64,
+    sequence: u32,
+    position: Vec3,
+    velocity: Vec3,
+    on_ground: bool,
+}
+
+/// Client-side prediction of the local player: move commands are applied locally as soon
+/// as they're sent, and kept until the server acknowledges them so they can be replayed
+/// on top of each authoritative state it sends back
+#[derive(Resource, Debug, Default)]
+pub struct Prediction {
+    /// Commands sent but not yet acknowledged, oldest first
+    pub pending: VecDeque<MoveCommand>,
+    next_sequence: u32,
+    /// Latest state from the server not yet reconciled with
+    correction: Option<AuthoritativeState>,
+    /// Tick of the latest state from the server, older ones arriving late are dropped
+    state_tick: u64,
+    /// How far the last reconciliation moved the player from where it was predicted to be
+    pub last_correction: f32,
+}
+
+/// Plays on a multiplayer server instead of simulating the world locally: chunks, block and
+/// light changes, entities, the hotbar, health and hunger, game mode and chat come from the
+/// server, and the local `Player`'s move commands, block edit requests, slot changes and chat go to it
+/// Add it in place of `SimulationPlugin`, next to `ModPlugin` for the registries; it runs
+/// whenever a `NetClient` resource is present
+pub struct ClientNetPlugin;
+
+impl Plugin for ClientNetPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ChunkManager>()
+            .init_resource::<PlayerInventory>()
+            .init_resource::<PlayerStats>()
+            .init_resource::<ChatLog>()
+            .init_resource::<ReplicatedEntities>()
+            .init_resource::<ServerClock>()
+            .init_resource::<Prediction>()
+            .add_event::<SendChat>()
+            .add_event::<BlockEditRequest>()
+            // Prediction steps at the server's tick rate so both sides take the same steps
+            .insert_resource(Time::<Fixed>::from_hz(TICKS_PER_SECOND))
+            .add_systems(PreUpdate, receive_server_packets.run_if(resource_exists::<NetClient>))
+            .add_systems(FixedPreUpdate, begin_physics_step)
+            .add_systems(FixedUpdate, predict_local_player.run_if(resource_exists::<NetClient>))
+            .add_systems(Update, (interpolate_physics_bodies, interpolate_replicated_entities))
+            .add_systems(PostUpdate, send_client_packets.run_if(resource_exists::<NetClient>));
+    }
+}
+
+/// Read the server's packets (TCP, then UDP) and apply them to the local world
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn receive_server_packets(
+    mut commands: Commands,
+    mut client: ResMut<NetClient>,
+    time: Res<Time>,
+    mut chunk_manager: ResMut<ChunkManager>,
+    mut chunks: Query<&mut Chunk>,
+    mut player: Query<(&mut Transform, Option<&mut PhysicsBody>, Option<&mut GameMode>, Option<&mut Selection>), (With<Player>, Without<Replicated>)>,
+    mut replicated: Query<&mut Replicated>,
+    mut replicated_entities: ResMut<ReplicatedEntities>,
+    mut clock: ResMut<ServerClock>,
+    mut prediction: ResMut<Prediction>,
+    mut inventory: ResMut<PlayerInventory>,
+    mut stats: ResMut<PlayerStats>,
+    mut chat: ResMut<ChatLog>,
+) {
+    if !client.is_connected() {
+        return;
+    }
+    let received_at = Instant::now();
+    // Until login the latency isn't simulated, so login answers come straight away
+    let due = received_at + if client.player_id.is_some() { client.latency } else { Duration::ZERO };
+    loop {
+        match client.connection.receive::<ServerPacket>() {
+            Ok(Some(packet)) => client.inbound.push_back((due, packet)),
+            Ok(None) => break,
+            Err(error) => {
+                warn!("Lost connection to the server: {}", error);
+                client.disconnect_reason = Some(match error {
+                    NetError::Protocol(error) => format!("Bad packet: {}", error),
+                    _ => String::new(),
+                });
+                break;
+            }
+        }
+    }
+    let mut buffer = [0; MAX_DATAGRAM];
+    loop {
+        match client.udp.recv(&mut buffer) {
+            Ok(len) => {
+                if let Ok((token, packet)) = decode_datagram::<ServerPacket>(&buffer[..len]) {
+                    if token == client.udp_token {
+                        client.inbound.push_back((due, packet));
+                    }
+                }
+            }
+            Err(error) if error.kind() == ErrorKind::WouldBlock => break,
+            // Refusals of earlier datagrams show up here; nothing to do about them
+            Err(_) => continue,
+        }
+    }
+
+    let now = time.elapsed_secs();
+    // Whatever the server said before closing is read now rather than after the simulated latency
+    let closed = !client.is_connected();
+    // Chunks whose blocks changed need meshing again, with their neighbors for the faces between them
+    let mut remesh: HashSet<ChunkCoord> = HashSet::new();
+
+    while client.inbound.front().is_some_and(|(due, _)| closed || *due <= received_at) {
+        let Some((_, packet)) = client.inbound.pop_front() else {
+            break;
+        };
+        match packet {
+            ServerPacket::LoginSuccess { player: id, position, udp_token } => {
+                client.player_id = Some(id);
+                client.udp_token = udp_token;
+                if let Ok((mut transform, body, _, _)) = player.get_single_mut() {
+                    transform.translation = position;
+                    if let Some(mut body) = body {
+                        body.teleport(position);
+                        transform.translation += body.render_offset;
+                    }
+                }
+                info!("Logged in");
+            }
+            ServerPacket::Disconnect { reason } => {
+                info!("Disconnected: {}", reason);
+                client.disconnect_reason = Some(reason);
+            }
+            ServerPacket::KeepAlive { id } => client.send(ClientPacket::KeepAlive { id }),
+            ServerPacket::UdpReady => client.udp_ready = true,
+            ServerPacket::ChunkData(data) => {
+                let chunk = match data.to_chunk() {
+                    Ok(chunk) => chunk,
+                    Err(error) => {
+                        warn!("Bad chunk {:?} from the server: {}", data.coord, error);
+                        continue;
+                    }
+                };
+                let coord = chunk.coord;
+                match chunk_manager.loaded_chunks.get(&coord).and_then(|&entity| chunks.get_mut(entity).ok()) {
+                    Some(mut existing) => *existing = chunk,
+                    None => {
+                        // Placed like the chunks the simulation loads, so their meshes land in the right spot
+                        let entity = commands.spawn((Transform::from_translation(coord.to_world_pos()), chunk)).id();
+                        chunk_manager.loaded_chunks.insert(coord, entity);
+                    }
+                }
+                remesh.extend(NEIGHBOR_OFFSETS.iter().map(|offset| ChunkCoord::new(coord.x + offset.x, coord.y + offset.y, coord.z + offset.z)));
+                remesh.insert(coord);
+            }
+            ServerPacket::UnloadChunk { coord } => {
+                if let Some(entity) = chunk_manager.loaded_chunks.remove(&coord) {
+                    commands.entity(entity).despawn_recursive();
+                }
+            }
+            ServerPacket::BlockChange { pos, state } => {
+                let (coord, local) = world_to_chunk_local(pos);
+                let Some(mut chunk) = chunk_manager.loaded_chunks.get(&coord).and_then(|&entity| chunks.get_mut(entity).ok()) else {
+                    continue;
+                };
+                chunk.set_state(local.x as usize, local.y as usize, local.z as usize, state);
+                remesh.extend(NEIGHBOR_OFFSETS.iter().map(|&offset| world_to_chunk_local(pos + offset).0));
+                remesh.insert(coord);
+            }
+            ServerPacket::LightUpdate { coord, light } => {
+                if let Some(mut chunk) = chunk_manager.loaded_chunks.get(&coord).and_then(|&entity| chunks.get_mut(entity).ok()) {
+                    light.apply_to(&mut chunk);
+                    remesh.insert(coord);
+                }
+            }
+            ServerPacket::EntitySpawn { id, tick, kind, position, yaw } => {
+                clock.observe(tick, now);
+                if let Some(old) = replicated_entities.entities.remove(&id) {
+                    commands.entity(old).despawn_recursive();
+                }
+                let snapshot = Snapshot { tick, position, yaw, pitch: 0.0 };
+                let entity = commands.spawn((
+                    Transform::from_translation(position).with_rotation(Quat::from_rotation_y(yaw)),
+                    Replicated { id, kind, snapshots: VecDeque::from([snapshot]) },
+                )).id();
+                replicated_entities.entities.insert(id, entity);
+            }
+            ServerPacket::EntityMove { id, tick, position, yaw, pitch } => {
+                clock.observe(tick, now);
+                if let Some(mut state) = replicated_entities.entities.get(&id).and_then(|&entity| replicated.get_mut(entity).ok()) {
+                    state.push(Snapshot { tick, position, yaw, pitch });
+                }
+            }
+            ServerPacket::EntityDespawn { id } => {
+                if let Some(entity) = replicated_entities.entities.remove(&id) {
+                    commands.entity(entity).despawn_recursive();
+                }
+            }
+            ServerPacket::Inventory { hotbar } => inventory.hotbar = hotbar,
+            ServerPacket::Chat { sender, message } => {
+                match &sender {
+                    Some(name) => info!("<{}> {}", name, message),
+                    None => info!("{}", message),
+                }
+                chat.push(sender, message);
+            }
+            ServerPacket::PlayerState { tick, sequence, position, velocity, on_ground } => {
+                clock.observe(tick, now);
+                // States can arrive out of order over UDP
+                if tick < prediction.state_tick {
+                    continue;
+                }
+                prediction.state_tick = tick;
+                prediction.correction = Some(AuthoritativeState { tick, sequence, position, velocity, on_ground });
+            }
+            ServerPacket::GameMode { mode } => {
+                if let Ok((_, _, Some(mut game_mode), _)) = player.get_single_mut() {
+                    *game_mode = mode;
+                }
+            }
+            ServerPacket::Selection(selection) => {
+                if let Ok((_, _, _, Some(mut local))) = player.get_single_mut() {
+                    *local = selection;
+                }
+            }
+            ServerPacket::Stats { health, hunger, air, invulnerable_ticks } => {
+                stats.health = health;
+                stats.hunger = hunger;
+                stats.air = air;
+                stats.invulnerable_ticks = invulnerable_ticks;
+            }
+        }
+    }
+
+    for coord in remesh {
+        if let Some(&entity) = chunk_manager.loaded_chunks.get(&coord) {
+            commands.entity(entity).insert(NeedsRemesh);
+        }
+    }
+}
+
+/// Turn the input held on the local player into this tick's move command: reconcile with
+/// the server's latest state (replaying the commands it hasn't applied yet), apply the new
+/// command locally with the shared `step_player` and send it off
+fn predict_local_player(
+    mut client: ResMut<NetClient>,
+    mut prediction: ResMut<Prediction>,
+    time: Res<Time>,
+    chunk_manager: Res<ChunkManager>,
+    chunks: Query<&'static Chunk>,
+    block_registry: Res<BlockRegistry>,
+    mut player: Query<(&mut Player, &mut PhysicsBody, Option<&GameMode>), Without<Replicated>>,
+) {
+    if client.player_id.is_none() || !client.is_connected() {
+        return;
+    }
+    let Ok((mut player, mut body, game_mode)) = player.get_single_mut() else {
+        return;
+    };
+    let dt = time.delta_secs();
+    let noclip = game_mode.is_some_and(|mode| mode.abilities().noclip);
+    let colliders = ChunkColliders { chunk_manager: &chunk_manager, chunks: &chunks, registry: &block_registry };
+    let prediction = prediction.as_mut();
+
+    // Taken before replaying overwrites the held input
+    let mut command = MoveCommand::from_player(0, &player);
+
+    if let Some(state) = prediction.correction.take() {
+        let predicted = body.position;
+        prediction.pending.retain(|pending| pending.sequence > state.sequence);
+        body.position = state.position;
+        body.velocity = state.velocity;
+        body.on_ground = state.on_ground;
+        for pending in &prediction.pending {
+            pending.apply_to(&mut player);
+            step_player(&mut player, &mut body, noclip, &colliders, dt);
+        }
+        prediction.last_correction = predicted.distance(body.position);
+        if prediction.last_correction > 0.01 {
+            debug!("Corrected prediction by {:.3} at tick {}", prediction.last_correction, state.tick);
+        }
+    }
+
+    // Hold still until the terrain around the player has arrived
+    command.apply_to(&mut player);
+    if !chunk_manager.loaded_chunks.contains_key(&ChunkCoord::from_world_pos(body.position)) {
+        return;
+    }
+
+    prediction.next_sequence += 1;
+    command.sequence = prediction.next_sequence;
+    step_player(&mut player, &mut body, noclip, &colliders, dt);
+    prediction.pending.push_back(command);
+    let excess = prediction.pending.len().saturating_sub(MAX_PENDING_MOVES);
+    prediction.pending.drain(..excess);
+
+    let resend = prediction.pending.len().saturating_sub(REDUNDANT_MOVES);
+    client.send(ClientPacket::MoveCommands { commands: prediction.pending.range(resend..).copied().collect() });
+}
+
+/// Place replicated entities between the snapshots around the server's tick, a little in the past
+fn interpolate_replicated_entities(
+    time: Res<Time>,
+    clock: Res<ServerClock>,
+    mut entities: Query<(&mut Transform, &mut Replicated)>,
+) {
+    let tick = clock.estimated_tick(time.elapsed_secs()) - INTERPOLATION_DELAY_TICKS;
+    for (mut transform, mut replicated) in entities.iter_mut() {
+        if let Some(snapshot) = replicated.sample(tick) {
+            transform.translation = snapshot.position;
+            transform.rotation = Quat::from_rotation_y(snapshot.yaw);
+        }
+    }
+}
+
+/// Send the local player's block edit requests, slot changes and chat, then write out the queue
+fn send_client_packets(
+    mut client: ResMut<NetClient>,
+    time: Res<Time>,
+    player: Query<Entity, (With<Player>, Without<Replicated>)>,
+    inventory: Res<PlayerInventory>,
+    mut edits: EventReader<BlockEditRequest>,
+    mut chat: EventReader<SendChat>,
+) {
+    if !client.is_connected() {
+        return;
+    }
+    let now = time.elapsed_secs();
+    let local_player = player.get_single().ok();
+
+    if client.player_id.is_some() {
+        if !client.udp_ready && now >= client.next_udp_hello {
+            client.next_udp_hello = now + UDP_HELLO_SECONDS;
+            let _ = client.udp.send(&encode_datagram(client.udp_token, &ClientPacket::UdpHello));
+        }
+
+        for request in edits.read() {
+            if local_player == Some(request.player) {
+                client.send(ClientPacket::BlockEdit(request.action));
+            }
+        }
+
+        if client.last_slot != Some(inventory.selected_slot) {
+            client.last_slot = Some(inventory.selected_slot);
+            client.send(ClientPacket::SelectSlot { slot: inventory.selected_slot as u8 });
+        }
+    } else {
+        edits.clear();
+    }
+
+    for SendChat(message) in chat.read() {
+        client.send(ClientPacket::Chat { message: message.clone() });
+    }
+
+    let sent_at = Instant::now();
+    while client.outbound.front().is_some_and(|(due, _)| *due <= sent_at) {
+        if let Some((_, packet)) = client.outbound.pop_front() {
+            client.send_now(&packet);
+        }
+    }
+
+    if let Err(error) = client.connection.flush() {
+        warn!("Lost connection to the server: {}", error);
+        client.disconnect_reason = Some(String::new());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(tick: u64, x: f32, yaw: f32) -> Snapshot {
+        Snapshot { tick, position: Vec3::new(x, 0.0, 0.0), yaw, pitch: 0.0 }
+    }
+
+    #[test]
+    fn replicated_entities_interpolate_between_snapshots() {
+        let mut replicated = Replicated {
+            id: 1,
+            kind: NetEntityKind::Player { name: "alice".to_string() },
+            snapshots: VecDeque::new(),
+        };
+        replicated.push(snapshot(10, 0.0, 3.0));
+        replicated.push(snapshot(11, 1.0, -3.0));
+        // A late snapshot is dropped
+        replicated.push(snapshot(9, 5.0, 0.0));
+
+        let halfway = replicated.sample(10.5).unwrap();
+        assert!((halfway.position.x - 0.5).abs() < 1e-5);
+        // Yaw turns the short way round, through PI
+        assert!((halfway.yaw.rem_euclid(TAU) - PI).abs() < 0.01);
+
+        // After standing still, motion starts a tick before the next snapshot
+        replicated.push(snapshot(20, 3.0, 0.0));
+        assert_eq!(replicated.sample(15.0).unwrap().position.x, 1.0);
+        assert!((replicated.sample(19.5).unwrap().position.x - 2.0).abs() < 1e-5);
+        assert_eq!(replicated.sample(25.0).unwrap().position.x, 3.0);
+        assert_eq!(replicated.snapshots.len(), 1);
+    }
+}
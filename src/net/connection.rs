@@ -0,0 +1,125 @@
+use std::fmt;
+use std::io::{self, ErrorKind, Read, Write};
+use std::net::{SocketAddr, TcpStream};
+use super::protocol::{decode_frame, encode_frame, Packet, ProtocolError};
+
+/// Bytes read from the socket at a time
+const READ_CHUNK: usize = 16 * 1024;
+
+/// Why a connection stopped working
+#[derive(Debug)]
+pub enum NetError {
+    Io(io::Error),
+    Protocol(ProtocolError),
+    /// The other side closed the connection
+    Closed,
+}
+
+impl fmt::Display for NetError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            NetError::Io(error) => write!(f, "{}", error),
+            NetError::Protocol(error) => write!(f, "protocol error: {}", error),
+            NetError::Closed => write!(f, "connection closed"),
+        }
+    }
+}
+
+impl From<io::Error> for NetError {
+    fn from(error: io::Error) -> Self {
+        NetError::Io(error)
+    }
+}
+
+impl From<ProtocolError> for NetError {
+    fn from(error: ProtocolError) -> Self {
+        NetError::Protocol(error)
+    }
+}
+
+/// A stream of length-prefixed frames over a non-blocking TCP socket
+/// Sends are queued and written out by `flush`, which the owner calls once per update;
+/// `receive` hands back one complete packet at a time as its bytes arrive
+pub struct Connection {
+    stream: TcpStream,
+    incoming: Vec<u8>,
+    outgoing: Vec<u8>,
+    /// The other side has closed; what it sent before that is still handed out
+    closed: bool,
+}
+
+impl Connection {
+    pub fn new(stream: TcpStream) -> io::Result<Self> {
+        stream.set_nonblocking(true)?;
+        stream.set_nodelay(true)?;
+        Ok(Self { stream, incoming: Vec::new(), outgoing: Vec::new(), closed: false })
+    }
+
+    /// Connect to `addr`, waiting for the connection to be established
+    pub fn connect(addr: SocketAddr) -> io::Result<Self> {
+        Self::new(TcpStream::connect(addr)?)
+    }
+
+    pub fn peer_addr(&self) -> io::Result<SocketAddr> {
+        self.stream.peer_addr()
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.stream.local_addr()
+    }
+
+    /// Queue a packet to go out with the next `flush`
+    pub fn send<P: Packet>(&mut self, packet: &P) {
+        self.outgoing.extend_from_slice(&encode_frame(packet));
+    }
+
+    /// Bytes queued that the socket hasn't taken yet
+    pub fn queued(&self) -> usize {
+        self.outgoing.len()
+    }
+
+    /// Write as much of the queue as the socket takes without blocking
+    pub fn flush(&mut self) -> Result<(), NetError> {
+        while !self.outgoing.is_empty() {
+            match self.stream.write(&self.outgoing) {
+                Ok(0) => return Err(NetError::Closed),
+                Ok(written) => {
+                    self.outgoing.drain(..written);
+                }
+                Err(error) if error.kind() == ErrorKind::WouldBlock => break,
+                Err(error) if error.kind() == ErrorKind::Interrupted => continue,
+                Err(error) => return Err(error.into()),
+            }
+        }
+        Ok(())
+    }
+
+    /// The next complete packet, or None until more bytes arrive
+    pub fn receive<P: Packet>(&mut self) -> Result<Option<P>, NetError> {
+        if let Some(packet) = self.take_frame()? {
+            return Ok(Some(packet));
+        }
+        let mut buffer = [0; READ_CHUNK];
+        while !self.closed {
+            match self.stream.read(&mut buffer) {
+                Ok(0) => self.closed = true,
+                Ok(read) => self.incoming.extend_from_slice(&buffer[..read]),
+                Err(error) if error.kind() == ErrorKind::WouldBlock => break,
+                Err(error) if error.kind() == ErrorKind::Interrupted => continue,
+                Err(error) => return Err(error.into()),
+            }
+        }
+        match self.take_frame()? {
+            None if self.closed => Err(NetError::Closed),
+            packet => Ok(packet),
+        }
+    }
+
+    fn take_frame<P: Packet>(&mut self) -> Result<Option<P>, NetError> {
+        let Some((packet, used)) = decode_frame(&self.incoming)? else {
+            return Ok(None);
+        };
+        self.incoming.drain(..used);
+        Ok(Some(packet))
+    }
+}
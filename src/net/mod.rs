@@ -0,0 +1,10 @@
+pub mod protocol;
+mod palette;
+mod connection;
+mod server;
+mod client;
+
+pub use palette::{ChunkData, ChunkLight, PalettedBlocks};
+pub use connection::{Connection, NetError};
+pub use server::{NetServer, RemotePlayer, ServerNetPlugin};
+pub use client::{ClientNetPlugin, NetClient, Prediction, Replicated, ReplicatedEntities, ServerClock, Snapshot};
@@ -0,0 +1,175 @@
+use crate::blocks::BlockState;
+use crate::world::{Chunk, ChunkCoord, NibbleArray, PalettedContainer, CHUNK_SIZE};
+use super::protocol::{PacketReader, PacketWriter, ProtocolError};
+
+const CHUNK_VOLUME: usize = CHUNK_SIZE * CHUNK_SIZE * CHUNK_SIZE;
+
+/// A chunk's blocks as its `PalettedContainer` stores them: the palette plus one small index
+/// per block packed into words (see `PalettedContainer::to_palette`)
+/// A chunk of a single state (air, solid stone) is just its palette
+#[derive(Debug, Clone, PartialEq)]
+pub struct PalettedBlocks {
+    pub palette: Vec<BlockState>,
+    /// Bits per packed index
+    pub bits: u8,
+    pub words: Vec<u64>,
+}
+
+impl PalettedBlocks {
+    pub fn from_chunk(chunk: &Chunk) -> Self {
+        let (palette, bits, words) = chunk.blocks.to_palette();
+        Self { palette, bits, words }
+    }
+
+    /// The chunk's blocks in the storage they were sent from
+    pub fn to_container(&self) -> Result<PalettedContainer, ProtocolError> {
+        PalettedContainer::from_palette(self.palette.clone(), self.bits, &self.words)
+            .ok_or(ProtocolError::Invalid("chunk blocks"))
+    }
+
+    pub fn encode(&self, writer: &mut PacketWriter) {
+        writer.u16(self.palette.len() as u16);
+        for &state in &self.palette {
+            writer.block_state(state);
+        }
+        writer.u8(self.bits);
+        writer.u16(self.words.len() as u16);
+        for &word in &self.words {
+            writer.u64(word);
+        }
+    }
+
+    pub fn decode(reader: &mut PacketReader) -> Result<Self, ProtocolError> {
+        let palette_len = reader.u16()? as usize;
+        if palette_len > CHUNK_VOLUME {
+            return Err(ProtocolError::Invalid("chunk palette"));
+        }
+        let palette = (0..palette_len).map(|_| reader.block_state()).collect::<Result<_, _>>()?;
+        let bits = reader.u8()?;
+        let word_count = reader.u16()? as usize;
+        if word_count > CHUNK_VOLUME {
+            return Err(ProtocolError::Invalid("chunk block data"));
+        }
+        let words = (0..word_count).map(|_| reader.u64()).collect::<Result<_, _>>()?;
+        Ok(Self { palette, bits, words })
+    }
+}
+
+/// A chunk's sky and block light, two levels per byte
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChunkLight {
+    pub sky: Vec<u8>,
+    pub block: Vec<u8>,
+}
+
+impl ChunkLight {
+    pub fn from_chunk(chunk: &Chunk) -> Self {
+        Self { sky: chunk.light_levels.to_bytes(), block: chunk.block_light.to_bytes() }
+    }
+
+    /// Overwrite the chunk's light with these levels
+    pub fn apply_to(&self, chunk: &mut Chunk) {
+        chunk.light_levels = NibbleArray::from_bytes(&self.sky);
+        chunk.block_light = NibbleArray::from_bytes(&self.block);
+    }
+
+    pub fn encode(&self, writer: &mut PacketWriter) {
+        writer.bytes(&self.sky);
+        writer.bytes(&self.block);
+    }
+
+    pub fn decode(reader: &mut PacketReader) -> Result<Self, ProtocolError> {
+        let sky = reader.bytes()?.to_vec();
+        let block = reader.bytes()?.to_vec();
+        if sky.len() != CHUNK_VOLUME / 2 || block.len() != CHUNK_VOLUME / 2 {
+            return Err(ProtocolError::Invalid("chunk light"));
+        }
+        Ok(Self { sky, block })
+    }
+}
+
+/// Everything a client needs to show a chunk (block entities such as furnace contents stay on the server)
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChunkData {
+    pub coord: ChunkCoord,
+    pub blocks: PalettedBlocks,
+    pub light: ChunkLight,
+}
+
+impl ChunkData {
+    pub fn from_chunk(chunk: &Chunk) -> Self {
+        Self {
+            coord: chunk.coord,
+            blocks: PalettedBlocks::from_chunk(chunk),
+            light: ChunkLight::from_chunk(chunk),
+        }
+    }
+
+    pub fn to_chunk(&self) -> Result<Chunk, ProtocolError> {
+        let mut chunk = Chunk::new(self.coord);
+        chunk.blocks = self.blocks.to_container()?;
+        self.light.apply_to(&mut chunk);
+        Ok(chunk)
+    }
+
+    pub fn encode(&self, writer: &mut PacketWriter) {
+        writer.chunk_coord(self.coord);
+        self.blocks.encode(writer);
+        self.light.encode(writer);
+    }
+
+    pub fn decode(reader: &mut PacketReader) -> Result<Self, ProtocolError> {
+        Ok(Self {
+            coord: reader.chunk_coord()?,
+            blocks: PalettedBlocks::decode(reader)?,
+            light: ChunkLight::decode(reader)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blocks::BlockId;
+    use crate::net::protocol::{decode_frame, encode_frame, ServerPacket};
+
+    #[test]
+    fn chunks_round_trip_through_their_palette() {
+        let mut chunk = Chunk::new(ChunkCoord::new(1, -2, 3));
+        for y in 0..4 {
+            for z in 0..CHUNK_SIZE {
+                for x in 0..CHUNK_SIZE {
+                    chunk.set_state(x, y, z, BlockState::with_data(BlockId(1 + ((x + z) % 5) as u16), (y % 2) as u8));
+                    chunk.set_light(x, y, z, 0);
+                }
+            }
+        }
+        chunk.set_block_light(5, 4, 5, 14);
+
+        let data = ChunkData::from_chunk(&chunk);
+        // Air plus 5 blocks with 2 data values each
+        assert_eq!(data.blocks.palette.len(), 11);
+        assert_eq!(data.blocks.bits, 4);
+
+        let frame = encode_frame(&ServerPacket::ChunkData(data));
+        let Some((ServerPacket::ChunkData(decoded), _)) = decode_frame(&frame).unwrap() else {
+            panic!("not a chunk");
+        };
+        let restored = decoded.to_chunk().unwrap();
+        assert_eq!(restored.coord, chunk.coord);
+        assert_eq!(restored.blocks, chunk.blocks);
+        assert_eq!(restored.light_levels, chunk.light_levels);
+        assert_eq!(restored.block_light, chunk.block_light);
+    }
+
+    #[test]
+    fn uniform_chunks_need_no_indices() {
+        let air = ChunkData::from_chunk(&Chunk::new(ChunkCoord::new(0, 8, 0)));
+        assert_eq!(air.blocks.palette, vec![BlockState::AIR]);
+        assert!(air.blocks.words.is_empty());
+
+        let mut broken = air.blocks.clone();
+        broken.bits = 2;
+        assert!(broken.to_container().is_err());
+    }
+}
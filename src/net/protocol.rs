@@ -0,0 +1,751 @@
+use bevy::prelude::*;
+use std::fmt;
+use crate::blocks::{BlockId, BlockState};
+use crate::components::GameMode;
+use crate::entities::EntityTypeId;
+use crate::items::ItemId;
+use crate::physics::MoveCommand;
+use crate::resources::{HotbarItem, ItemStack};
+use crate::systems::BlockAction;
+use crate::world::{ChunkCoord, Selection};
+use super::palette::{ChunkData, ChunkLight};
+
+/// Version of the wire format, bumped whenever a packet changes
+/// Clients and servers on different versions refuse each other at login
+pub const PROTOCOL_VERSION: u16 = 7;
+
+/// Largest frame either side accepts; a bigger length prefix means the stream is broken
+pub const MAX_FRAME_SIZE: usize = 1 << 20;
+
+/// Bytes of the length prefix in front of every frame
+const LENGTH_PREFIX: usize = 4;
+
+/// Longest player name accepted at login
+pub const MAX_NAME_LENGTH: usize = 16;
+
+/// Longest chat message accepted
+pub const MAX_CHAT_LENGTH: usize = 256;
+
+/// Most move commands in one packet (the newest plus resends of earlier unacknowledged ones)
+pub const MAX_MOVE_COMMANDS: usize = 16;
+
+/// Why bytes couldn't be read as a packet
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProtocolError {
+    /// The packet ended before all its fields were read
+    Truncated,
+    /// Bytes were left over after the last field
+    TrailingBytes,
+    UnknownPacket(u8),
+    FrameTooLarge(usize),
+    /// A field held a value no packet can have
+    Invalid(&'static str),
+}
+
+impl fmt::Display for ProtocolError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ProtocolError::Truncated => write!(f, "packet ended early"),
+            ProtocolError::TrailingBytes => write!(f, "unexpected bytes after packet"),
+            ProtocolError::UnknownPacket(id) => write!(f, "unknown packet id {}", id),
+            ProtocolError::FrameTooLarge(size) => write!(f, "frame of {} bytes is over the {} byte limit", size, MAX_FRAME_SIZE),
+            ProtocolError::Invalid(what) => write!(f, "invalid {}", what),
+        }
+    }
+}
+
+/// How a packet travels
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Channel {
+    /// In order and never lost (TCP)
+    Reliable,
+    /// Over UDP once the client's address is known, where newer packets make lost ones moot
+    /// (positions); falls back to TCP until then
+    Unreliable,
+}
+
+/// Appends fields in wire order: integers big-endian, strings and byte runs length-prefixed
+#[derive(Default)]
+pub struct PacketWriter {
+    bytes: Vec<u8>,
+}
+
+impl PacketWriter {
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+
+    pub fn u8(&mut self, value: u8) {
+        self.bytes.push(value);
+    }
+
+    pub fn bool(&mut self, value: bool) {
+        self.u8(value as u8);
+    }
+
+    pub fn u16(&mut self, value: u16) {
+        self.bytes.extend_from_slice(&value.to_be_bytes());
+    }
+
+    pub fn u32(&mut self, value: u32) {
+        self.bytes.extend_from_slice(&value.to_be_bytes());
+    }
+
+    pub fn u64(&mut self, value: u64) {
+        self.bytes.extend_from_slice(&value.to_be_bytes());
+    }
+
+    pub fn i32(&mut self, value: i32) {
+        self.bytes.extend_from_slice(&value.to_be_bytes());
+    }
+
+    pub fn f32(&mut self, value: f32) {
+        self.bytes.extend_from_slice(&value.to_be_bytes());
+    }
+
+    pub fn string(&mut self, value: &str) {
+        self.u16(value.len() as u16);
+        self.bytes.extend_from_slice(value.as_bytes());
+    }
+
+    pub fn bytes(&mut self, value: &[u8]) {
+        self.u32(value.len() as u32);
+        self.bytes.extend_from_slice(value);
+    }
+
+    pub fn vec3(&mut self, value: Vec3) {
+        self.f32(value.x);
+        self.f32(value.y);
+        self.f32(value.z);
+    }
+
+    pub fn ivec3(&mut self, value: IVec3) {
+        self.i32(value.x);
+        self.i32(value.y);
+        self.i32(value.z);
+    }
+
+    pub fn chunk_coord(&mut self, coord: ChunkCoord) {
+        self.ivec3(IVec3::new(coord.x, coord.y, coord.z));
+    }
+
+    pub fn block_state(&mut self, state: BlockState) {
+        self.u16(state.id.0);
+        self.u8(state.data);
+    }
+
+    pub fn move_command(&mut self, command: &MoveCommand) {
+        self.u32(command.sequence);
+        self.vec3(command.move_input);
+        self.f32(command.fly_input);
+        let flags = [command.jump, command.sneaking, command.sprinting, command.flying];
+        self.u8(flags.iter().enumerate().fold(0, |bits, (bit, &set)| bits | (set as u8) << bit));
+        self.f32(command.yaw);
+        self.f32(command.pitch);
+    }
+
+    pub fn item_stack(&mut self, stack: Option<ItemStack>) {
+        let Some(stack) = stack else {
+            self.u8(0);
+            return;
+        };
+        match stack.item {
+            HotbarItem::Block(id) => {
+                self.u8(1);
+                self.u16(id.0);
+            }
+            HotbarItem::Item(id) => {
+                self.u8(2);
+                self.u16(id.0);
+            }
+        }
+        self.u8(stack.count);
+        self.bool(stack.durability.is_some());
+        self.u16(stack.durability.unwrap_or(0));
+    }
+}
+
+/// Reads fields written by `PacketWriter`, failing on anything short or out of range
+pub struct PacketReader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> PacketReader<'a> {
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes }
+    }
+
+    /// Fail if anything is left over once the packet is read
+    pub fn finish(&self) -> Result<(), ProtocolError> {
+        if self.bytes.is_empty() { Ok(()) } else { Err(ProtocolError::TrailingBytes) }
+    }
+
+    fn take<const N: usize>(&mut self) -> Result<[u8; N], ProtocolError> {
+        let (head, rest) = self.bytes.split_first_chunk::<N>().ok_or(ProtocolError::Truncated)?;
+        self.bytes = rest;
+        Ok(*head)
+    }
+
+    fn take_slice(&mut self, len: usize) -> Result<&'a [u8], ProtocolError> {
+        if self.bytes.len() < len {
+            return Err(ProtocolError::Truncated);
+        }
+        let (head, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(head)
+    }
+
+    pub fn u8(&mut self) -> Result<u8, ProtocolError> {
+        Ok(self.take::<1>()?[0])
+    }
+
+    pub fn bool(&mut self) -> Result<bool, ProtocolError> {
+        match self.u8()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(ProtocolError::Invalid("bool")),
+        }
+    }
+
+    pub fn u16(&mut self) -> Result<u16, ProtocolError> {
+        Ok(u16::from_be_bytes(self.take()?))
+    }
+
+    pub fn u32(&mut self) -> Result<u32, ProtocolError> {
+        Ok(u32::from_be_bytes(self.take()?))
+    }
+
+    pub fn u64(&mut self) -> Result<u64, ProtocolError> {
+        Ok(u64::from_be_bytes(self.take()?))
+    }
+
+    pub fn i32(&mut self) -> Result<i32, ProtocolError> {
+        Ok(i32::from_be_bytes(self.take()?))
+    }
+
+    /// A float, which must be finite - positions and angles never hold NaN or infinity
+    pub fn f32(&mut self) -> Result<f32, ProtocolError> {
+        let value = f32::from_be_bytes(self.take()?);
+        if value.is_finite() { Ok(value) } else { Err(ProtocolError::Invalid("float")) }
+    }
+
+    pub fn string(&mut self) -> Result<String, ProtocolError> {
+        let len = self.u16()? as usize;
+        let bytes = self.take_slice(len)?;
+        String::from_utf8(bytes.to_vec()).map_err(|_| ProtocolError::Invalid("string"))
+    }
+
+    pub fn bytes(&mut self) -> Result<&'a [u8], ProtocolError> {
+        let len = self.u32()? as usize;
+        self.take_slice(len)
+    }
+
+    pub fn vec3(&mut self) -> Result<Vec3, ProtocolError> {
+        Ok(Vec3::new(self.f32()?, self.f32()?, self.f32()?))
+    }
+
+    pub fn ivec3(&mut self) -> Result<IVec3, ProtocolError> {
+        Ok(IVec3::new(self.i32()?, self.i32()?, self.i32()?))
+    }
+
+    pub fn chunk_coord(&mut self) -> Result<ChunkCoord, ProtocolError> {
+        let pos = self.ivec3()?;
+        Ok(ChunkCoord::new(pos.x, pos.y, pos.z))
+    }
+
+    pub fn block_state(&mut self) -> Result<BlockState, ProtocolError> {
+        let id = BlockId(self.u16()?);
+        let data = self.u8()?;
+        if data > BlockState::MAX_DATA {
+            return Err(ProtocolError::Invalid("block data"));
+        }
+        Ok(BlockState { id, data })
+    }
+
+    pub fn move_command(&mut self) -> Result<MoveCommand, ProtocolError> {
+        let sequence = self.u32()?;
+        let move_input = self.vec3()?;
+        let fly_input = self.f32()?;
+        let flags = self.u8()?;
+        if flags >> 4 != 0 {
+            return Err(ProtocolError::Invalid("move flags"));
+        }
+        let flag = |bit: u8| flags & (1 << bit) != 0;
+        Ok(MoveCommand {
+            sequence,
+            move_input,
+            fly_input,
+            jump: flag(0),
+            sneaking: flag(1),
+            sprinting: flag(2),
+            flying: flag(3),
+            yaw: self.f32()?,
+            pitch: self.f32()?,
+        })
+    }
+
+    pub fn item_stack(&mut self) -> Result<Option<ItemStack>, ProtocolError> {
+        let item = match self.u8()? {
+            0 => return Ok(None),
+            1 => HotbarItem::Block(BlockId(self.u16()?)),
+            2 => HotbarItem::Item(ItemId(self.u16()?)),
+            _ => return Err(ProtocolError::Invalid("item kind")),
+        };
+        let count = self.u8()?;
+        let has_durability = self.bool()?;
+        let durability = self.u16()?;
+        Ok(Some(ItemStack { item, count, durability: has_durability.then_some(durability) }))
+    }
+}
+
+/// A message either side can put on the wire: a one-byte packet id, then its fields
+pub trait Packet: Sized {
+    fn encode(&self, writer: &mut PacketWriter);
+
+    fn decode(reader: &mut PacketReader) -> Result<Self, ProtocolError>;
+
+    fn channel(&self) -> Channel {
+        Channel::Reliable
+    }
+}
+
+/// A packet as one frame: its length as a 4-byte prefix, then the packet
+pub fn encode_frame<P: Packet>(packet: &P) -> Vec<u8> {
+    let mut writer = PacketWriter::default();
+    writer.u32(0);
+    packet.encode(&mut writer);
+    let mut bytes = writer.into_bytes();
+    let len = (bytes.len() - LENGTH_PREFIX) as u32;
+    bytes[..LENGTH_PREFIX].copy_from_slice(&len.to_be_bytes());
+    bytes
+}
+
+/// Split the first complete frame off the front of `buffer`
+/// Returns the packet and how many bytes it used, or None if the frame isn't all there yet
+pub fn decode_frame<P: Packet>(buffer: &[u8]) -> Result<Option<(P, usize)>, ProtocolError> {
+    let Some(prefix) = buffer.first_chunk::<LENGTH_PREFIX>() else {
+        return Ok(None);
+    };
+    let len = u32::from_be_bytes(*prefix) as usize;
+    if len > MAX_FRAME_SIZE {
+        return Err(ProtocolError::FrameTooLarge(len));
+    }
+    let Some(body) = buffer.get(LENGTH_PREFIX..LENGTH_PREFIX + len) else {
+        return Ok(None);
+    };
+    let mut reader = PacketReader::new(body);
+    let packet = P::decode(&mut reader)?;
+    reader.finish()?;
+    Ok(Some((packet, LENGTH_PREFIX + len)))
+}
+
+/// A packet as a UDP datagram: the sender's login token, then the packet
+/// (datagrams carry their own length, and the token tells the server which client sent it)
+pub fn encode_datagram<P: Packet>(token: u64, packet: &P) -> Vec<u8> {
+    let mut writer = PacketWriter::default();
+    writer.u64(token);
+    packet.encode(&mut writer);
+    writer.into_bytes()
+}
+
+pub fn decode_datagram<P: Packet>(datagram: &[u8]) -> Result<(u64, P), ProtocolError> {
+    let mut reader = PacketReader::new(datagram);
+    let token = reader.u64()?;
+    let packet = P::decode(&mut reader)?;
+    reader.finish()?;
+    Ok((token, packet))
+}
+
+/// What kind of thing a replicated entity is, with what a client needs to draw it
+#[derive(Debug, Clone, PartialEq)]
+pub enum NetEntityKind {
+    Player { name: String },
+    Mob { entity_type: EntityTypeId },
+    Item { stack: ItemStack },
+    FallingBlock { state: BlockState },
+}
+
+/// Packets sent by clients
+#[derive(Debug, Clone, PartialEq)]
+pub enum ClientPacket {
+    /// First packet on a connection; names aren't authenticated (offline mode)
+    Login { protocol_version: u16, name: String },
+    /// Answer to the server's keep-alive with the same id
+    KeepAlive { id: u64 },
+    /// Sent over UDP after login until the server answers `UdpReady`, so it learns our address
+    UdpHello,
+    /// The newest move commands, oldest first; earlier ones the server hasn't acknowledged
+    /// are sent again in case their datagram was lost
+    MoveCommands { commands: Vec<MoveCommand> },
+    /// A block edit for the server to check and apply
+    BlockEdit(BlockAction),
+    SelectSlot { slot: u8 },
+    Chat { message: String },
+}
+
+impl Packet for ClientPacket {
+    fn encode(&self, writer: &mut PacketWriter) {
+        match self {
+            ClientPacket::Login { protocol_version, name } => {
+                writer.u8(0);
+                writer.u16(*protocol_version);
+                writer.string(name);
+            }
+            ClientPacket::KeepAlive { id } => {
+                writer.u8(1);
+                writer.u64(*id);
+            }
+            ClientPacket::UdpHello => writer.u8(2),
+            ClientPacket::MoveCommands { commands } => {
+                writer.u8(3);
+                writer.u8(commands.len() as u8);
+                for command in commands {
+                    writer.move_command(command);
+                }
+            }
+            ClientPacket::BlockEdit(action) => {
+                writer.u8(4);
+                match *action {
+                    BlockAction::StartBreak { pos } => {
+                        writer.u8(2);
+                        writer.ivec3(pos);
+                    }
+                    BlockAction::Break { pos } => {
+                        writer.u8(0);
+                        writer.ivec3(pos);
+                    }
+                    BlockAction::UseOn { pos, face } => {
+                        writer.u8(1);
+                        writer.ivec3(pos);
+                        writer.ivec3(face);
+                    }
+                }
+            }
+            ClientPacket::SelectSlot { slot } => {
+                writer.u8(5);
+                writer.u8(*slot);
+            }
+            ClientPacket::Chat { message } => {
+                writer.u8(6);
+                writer.string(message);
+            }
+        }
+    }
+
+    fn decode(reader: &mut PacketReader) -> Result<Self, ProtocolError> {
+        Ok(match reader.u8()? {
+            0 => ClientPacket::Login { protocol_version: reader.u16()?, name: reader.string()? },
+            1 => ClientPacket::KeepAlive { id: reader.u64()? },
+            2 => ClientPacket::UdpHello,
+            3 => {
+                let count = reader.u8()? as usize;
+                if count > MAX_MOVE_COMMANDS {
+                    return Err(ProtocolError::Invalid("move command count"));
+                }
+                let commands = (0..count).map(|_| reader.move_command()).collect::<Result<_, _>>()?;
+                ClientPacket::MoveCommands { commands }
+            }
+            4 => ClientPacket::BlockEdit(match reader.u8()? {
+                0 => BlockAction::Break { pos: reader.ivec3()? },
+                1 => BlockAction::UseOn { pos: reader.ivec3()?, face: reader.ivec3()? },
+                2 => BlockAction::StartBreak { pos: reader.ivec3()? },
+                _ => return Err(ProtocolError::Invalid("block action")),
+            }),
+            5 => ClientPacket::SelectSlot { slot: reader.u8()? },
+            6 => ClientPacket::Chat { message: reader.string()? },
+            id => return Err(ProtocolError::UnknownPacket(id)),
+        })
+    }
+
+    fn channel(&self) -> Channel {
+        match self {
+            ClientPacket::MoveCommands { .. } | ClientPacket::UdpHello => Channel::Unreliable,
+            _ => Channel::Reliable,
+        }
+    }
+}
+
+/// Packets sent by the server
+#[derive(Debug, Clone, PartialEq)]
+pub enum ServerPacket {
+    /// Login accepted: the id of our own player entity, where it spawned, and the token
+    /// that marks our UDP datagrams
+    LoginSuccess { player: u64, position: Vec3, udp_token: u64 },
+    /// The server is closing the connection
+    Disconnect { reason: String },
+    /// Must be answered with a `ClientPacket::KeepAlive` with the same id
+    KeepAlive { id: u64 },
+    /// The server has our UDP address; unreliable packets go over UDP from now on
+    UdpReady,
+    /// A chunk came into view (or is sent again whole)
+    ChunkData(ChunkData),
+    /// A chunk left view; clients drop it
+    UnloadChunk { coord: ChunkCoord },
+    BlockChange { pos: IVec3, state: BlockState },
+    /// A chunk's sky and block light after a relight
+    LightUpdate { coord: ChunkCoord, light: ChunkLight },
+    /// An entity came into view, where it is as of world tick `tick`
+    EntitySpawn { id: u64, tick: u64, kind: NetEntityKind, position: Vec3, yaw: f32 },
+    /// Where an entity is as of world tick `tick`; older moves arriving late are dropped
+    EntityMove { id: u64, tick: u64, position: Vec3, yaw: f32, pitch: f32 },
+    EntityDespawn { id: u64 },
+    /// Our hotbar contents
+    Inventory { hotbar: [Option<ItemStack>; 9] },
+    /// A chat line; server messages (joins, leaves) have no sender
+    Chat { sender: Option<String>, message: String },
+    /// Where our own player's body is as of world tick `tick`, after the server applied
+    /// our move commands up to `sequence`
+    PlayerState { tick: u64, sequence: u32, position: Vec3, velocity: Vec3, on_ground: bool },
+    /// Our game mode changed (or is being told at login); prediction needs it to move like the server
+    GameMode { mode: GameMode },
+    /// Our selection corners changed (by the wand or `/pos1`, `/pos2`), for the outline
+    Selection(Selection),
+    /// Our health, hunger and air changed, for the survival bars; `invulnerable_ticks`
+    /// fades the hurt flash
+    Stats { health: u8, hunger: u8, air: u16, invulnerable_ticks: u16 },
+}
+
+impl Packet for ServerPacket {
+    fn encode(&self, writer: &mut PacketWriter) {
+        match self {
+            ServerPacket::LoginSuccess { player, position, udp_token } => {
+                writer.u8(0);
+                writer.u64(*player);
+                writer.vec3(*position);
+                writer.u64(*udp_token);
+            }
+            ServerPacket::Disconnect { reason } => {
+                writer.u8(1);
+                writer.string(reason);
+            }
+            ServerPacket::KeepAlive { id } => {
+                writer.u8(2);
+                writer.u64(*id);
+            }
+            ServerPacket::UdpReady => writer.u8(3),
+            ServerPacket::ChunkData(data) => {
+                writer.u8(4);
+                data.encode(writer);
+            }
+            ServerPacket::UnloadChunk { coord } => {
+                writer.u8(5);
+                writer.chunk_coord(*coord);
+            }
+            ServerPacket::BlockChange { pos, state } => {
+                writer.u8(6);
+                writer.ivec3(*pos);
+                writer.block_state(*state);
+            }
+            ServerPacket::LightUpdate { coord, light } => {
+                writer.u8(7);
+                writer.chunk_coord(*coord);
+                light.encode(writer);
+            }
+            ServerPacket::EntitySpawn { id, tick, kind, position, yaw } => {
+                writer.u8(8);
+                writer.u64(*id);
+                writer.u64(*tick);
+                match kind {
+                    NetEntityKind::Player { name } => {
+                        writer.u8(0);
+                        writer.string(name);
+                    }
+                    NetEntityKind::Mob { entity_type } => {
+                        writer.u8(1);
+                        writer.u16(entity_type.0);
+                    }
+                    NetEntityKind::Item { stack } => {
+                        writer.u8(2);
+                        writer.item_stack(Some(*stack));
+                    }
+                    NetEntityKind::FallingBlock { state } => {
+                        writer.u8(3);
+                        writer.block_state(*state);
+                    }
+                }
+                writer.vec3(*position);
+                writer.f32(*yaw);
+            }
+            ServerPacket::EntityMove { id, tick, position, yaw, pitch } => {
+                writer.u8(9);
+                writer.u64(*id);
+                writer.u64(*tick);
+                writer.vec3(*position);
+                writer.f32(*yaw);
+                writer.f32(*pitch);
+            }
+            ServerPacket::EntityDespawn { id } => {
+                writer.u8(10);
+                writer.u64(*id);
+            }
+            ServerPacket::Inventory { hotbar } => {
+                writer.u8(11);
+                for stack in hotbar {
+                    writer.item_stack(*stack);
+                }
+            }
+            ServerPacket::Chat { sender, message } => {
+                writer.u8(12);
+                writer.bool(sender.is_some());
+                if let Some(sender) = sender {
+                    writer.string(sender);
+                }
+                writer.string(message);
+            }
+            ServerPacket::PlayerState { tick, sequence, position, velocity, on_ground } => {
+                writer.u8(13);
+                writer.u64(*tick);
+                writer.u32(*sequence);
+                writer.vec3(*position);
+                writer.vec3(*velocity);
+                writer.bool(*on_ground);
+            }
+            ServerPacket::GameMode { mode } => {
+                writer.u8(14);
+                writer.u8(GameMode::ALL.iter().position(|other| other == mode).unwrap_or_default() as u8);
+            }
+            ServerPacket::Selection(selection) => {
+                writer.u8(15);
+                for corner in [selection.first, selection.second] {
+                    writer.bool(corner.is_some());
+                    if let Some(corner) = corner {
+                        writer.ivec3(corner);
+                    }
+                }
+            }
+            ServerPacket::Stats { health, hunger, air, invulnerable_ticks } => {
+                writer.u8(16);
+                writer.u8(*health);
+                writer.u8(*hunger);
+                writer.u16(*air);
+                writer.u16(*invulnerable_ticks);
+            }
+        }
+    }
+
+    fn decode(reader: &mut PacketReader) -> Result<Self, ProtocolError> {
+        Ok(match reader.u8()? {
+            0 => ServerPacket::LoginSuccess { player: reader.u64()?, position: reader.vec3()?, udp_token: reader.u64()? },
+            1 => ServerPacket::Disconnect { reason: reader.string()? },
+            2 => ServerPacket::KeepAlive { id: reader.u64()? },
+            3 => ServerPacket::UdpReady,
+            4 => ServerPacket::ChunkData(ChunkData::decode(reader)?),
+            5 => ServerPacket::UnloadChunk { coord: reader.chunk_coord()? },
+            6 => ServerPacket::BlockChange { pos: reader.ivec3()?, state: reader.block_state()? },
+            7 => ServerPacket::LightUpdate { coord: reader.chunk_coord()?, light: ChunkLight::decode(reader)? },
+            8 => {
+                let id = reader.u64()?;
+                let tick = reader.u64()?;
+                let kind = match reader.u8()? {
+                    0 => NetEntityKind::Player { name: reader.string()? },
+                    1 => NetEntityKind::Mob { entity_type: EntityTypeId(reader.u16()?) },
+                    2 => NetEntityKind::Item { stack: reader.item_stack()?.ok_or(ProtocolError::Invalid("item stack"))? },
+                    3 => NetEntityKind::FallingBlock { state: reader.block_state()? },
+                    _ => return Err(ProtocolError::Invalid("entity kind")),
+                };
+                ServerPacket::EntitySpawn { id, tick, kind, position: reader.vec3()?, yaw: reader.f32()? }
+            }
+            9 => ServerPacket::EntityMove {
+                id: reader.u64()?,
+                tick: reader.u64()?,
+                position: reader.vec3()?,
+                yaw: reader.f32()?,
+                pitch: reader.f32()?,
+            },
+            10 => ServerPacket::EntityDespawn { id: reader.u64()? },
+            11 => {
+                let mut hotbar = [None; 9];
+                for slot in hotbar.iter_mut() {
+                    *slot = reader.item_stack()?;
+                }
+                ServerPacket::Inventory { hotbar }
+            }
+            12 => {
+                let sender = if reader.bool()? { Some(reader.string()?) } else { None };
+                ServerPacket::Chat { sender, message: reader.string()? }
+            }
+            13 => ServerPacket::PlayerState {
+                tick: reader.u64()?,
+                sequence: reader.u32()?,
+                position: reader.vec3()?,
+                velocity: reader.vec3()?,
+                on_ground: reader.bool()?,
+            },
+            14 => ServerPacket::GameMode {
+                mode: *GameMode::ALL.get(reader.u8()? as usize).ok_or(ProtocolError::Invalid("game mode"))?,
+            },
+            15 => {
+                let mut corner = || -> Result<Option<IVec3>, ProtocolError> {
+                    Ok(if reader.bool()? { Some(reader.ivec3()?) } else { None })
+                };
+                ServerPacket::Selection(Selection { first: corner()?, second: corner()? })
+            }
+            16 => ServerPacket::Stats { health: reader.u8()?, hunger: reader.u8()?, air: reader.u16()?, invulnerable_ticks: reader.u16()? },
+            id => return Err(ProtocolError::UnknownPacket(id)),
+        })
+    }
+
+    fn channel(&self) -> Channel {
+        match self {
+            ServerPacket::EntityMove { .. } | ServerPacket::PlayerState { .. } => Channel::Unreliable,
+            _ => Channel::Reliable,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip<P: Packet + fmt::Debug + PartialEq>(packet: P) {
+        let frame = encode_frame(&packet);
+        let (decoded, used) = decode_frame::<P>(&frame).unwrap().unwrap();
+        assert_eq!(decoded, packet);
+        assert_eq!(used, frame.len());
+        // Every shorter prefix is an incomplete frame, not an error
+        for len in 0..frame.len() {
+            assert_eq!(decode_frame::<P>(&frame[..len]).unwrap(), None);
+        }
+    }
+
+    #[test]
+    fn packets_survive_the_wire() {
+        round_trip(ClientPacket::Login { protocol_version: PROTOCOL_VERSION, name: "Steve".to_string() });
+        round_trip(ClientPacket::MoveCommands { commands: vec![
+            MoveCommand { sequence: 9, move_input: Vec3::new(1.5, 0.0, -3.25), jump: true, flying: true, yaw: 1.0, pitch: -0.5, ..default() },
+            MoveCommand { sequence: 10, sneaking: true, ..default() },
+        ] });
+        round_trip(ClientPacket::BlockEdit(BlockAction::UseOn { pos: IVec3::new(-1, 2, 3), face: IVec3::NEG_Y }));
+        round_trip(ClientPacket::BlockEdit(BlockAction::StartBreak { pos: IVec3::new(4, -5, 6) }));
+        round_trip(ServerPacket::BlockChange { pos: IVec3::new(7, -8, 9), state: BlockState::with_data(BlockId(4), 3) });
+        round_trip(ServerPacket::EntitySpawn {
+            id: 42,
+            tick: 1200,
+            kind: NetEntityKind::Item { stack: ItemStack::tool(HotbarItem::Item(ItemId(2)), 59) },
+            position: Vec3::ONE,
+            yaw: 0.25,
+        });
+        round_trip(ServerPacket::Inventory { hotbar: [Some(ItemStack::new(HotbarItem::Block(BlockId(1)), 64)); 9] });
+        round_trip(ServerPacket::Chat { sender: None, message: "Steve joined the game".to_string() });
+        round_trip(ServerPacket::PlayerState { tick: 3, sequence: 10, position: Vec3::Y, velocity: Vec3::NEG_Y, on_ground: false });
+        round_trip(ServerPacket::GameMode { mode: GameMode::Spectator });
+        round_trip(ServerPacket::Selection(Selection { first: None, second: Some(IVec3::new(-30_000_000, 5, 2)) }));
+        round_trip(ServerPacket::Stats { health: 7, hunger: 20, air: 300, invulnerable_ticks: 10 });
+
+        let (token, packet) = decode_datagram::<ClientPacket>(&encode_datagram(7, &ClientPacket::UdpHello)).unwrap();
+        assert_eq!((token, packet), (7, ClientPacket::UdpHello));
+    }
+
+    #[test]
+    fn malformed_frames_are_rejected() {
+        let mut frame = encode_frame(&ServerPacket::KeepAlive { id: 1 });
+        frame.push(0);
+        let len = frame.len() as u32 - 4;
+        frame[..4].copy_from_slice(&len.to_be_bytes());
+        assert_eq!(decode_frame::<ServerPacket>(&frame), Err(ProtocolError::TrailingBytes));
+
+        assert_eq!(decode_frame::<ServerPacket>(&[0, 0, 0, 1, 200]), Err(ProtocolError::UnknownPacket(200)));
+        assert_eq!(decode_frame::<ServerPacket>(&[0xff, 0xff, 0xff, 0xff]), Err(ProtocolError::FrameTooLarge(u32::MAX as usize)));
+        assert_eq!(decode_frame::<ServerPacket>(&[0, 0, 0, 1, 2]), Err(ProtocolError::Truncated));
+    }
+}
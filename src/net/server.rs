@@ -0,0 +1,770 @@
+use bevy::prelude::*;
+use rand::Rng;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::io::{self, ErrorKind};
+use std::net::{Ipv4Addr, SocketAddr, TcpListener, UdpSocket};
+use crate::blocks::BlockRegistry;
+use crate::commands::{run_commands, CommandFeedback, RunCommand};
+use crate::components::{GameMode, Mob, Player, PLAYER_HALF_WIDTH, PLAYER_HEIGHT, PLAYER_STEP_HEIGHT};
+use crate::physics::{step_player, ChunkColliders, MoveCommand, PhysicsBody};
+use crate::resources::{PlayerInventory, PlayerStats};
+use crate::systems::{
+    apply_step_effects, relight_chunks, update_environment_damage, BlockChanged, BlockEditRequest, ChunkLightChanged, FallingBlock,
+    ItemDrop, NeedsPostProcess, NeedsRelight, PlayerDamage,
+};
+use crate::world::{world_to_chunk_local, Chunk, ChunkCoord, Clipboard, ChunkManager, EditHistory, Selection, SpawnPoint, WorldSettings, WorldTick, CHUNK_SIZE, UNLOAD_MARGIN};
+use super::connection::{Connection, NetError};
+use super::palette::{ChunkData, ChunkLight};
+use super::protocol::{
+    decode_datagram, encode_datagram, Channel, ClientPacket, NetEntityKind, Packet, ServerPacket,
+    MAX_CHAT_LENGTH, MAX_NAME_LENGTH, PROTOCOL_VERSION,
+};
+
+/// Seconds between keep-alives
+const KEEP_ALIVE_SECONDS: f32 = 5.0;
+
+/// Seconds a client may stay silent before it's dropped
+const TIMEOUT_SECONDS: f32 = 30.0;
+
+/// Chunks sent to each client per update, nearest first
+const CHUNKS_PER_UPDATE: usize = 16;
+
+/// Bytes a client may have waiting to go out before no more chunks are queued for it
+const CHUNK_BACKLOG: usize = 256 * 1024;
+
+/// Bytes a client may have waiting to go out before it's dropped for not keeping up
+const MAX_BACKLOG: usize = 16 * 1024 * 1024;
+
+/// Block changes in one chunk at once beyond which the whole chunk is sent instead
+const BLOCK_CHANGES_PER_CHUNK: usize = 64;
+
+/// Largest datagram read; the packets sent over UDP are far smaller
+const MAX_DATAGRAM: usize = 1500;
+
+/// Smallest change in position or facing worth sending
+const MOVE_EPSILON: f32 = 0.001;
+
+/// Move commands a player can bank while none arrive, to catch up after a late packet
+/// Players get one step per tick on average, so they can't move faster by sending more
+const MAX_MOVE_CREDIT: u32 = 10;
+
+/// Move commands held for a player before the oldest are dropped
+const MAX_QUEUED_MOVES: usize = 40;
+
+/// The player entity of a connected client, which joins in survival
+/// Carries its own `PlayerInventory` and `PlayerStats` components, so the simulation keeps
+/// each client's hotbar, health and hunger apart
+#[derive(Component, Debug, Clone)]
+pub struct RemotePlayer {
+    pub name: String,
+    /// Named in the server's operators, so trusted with every command
+    pub operator: bool,
+}
+
+/// One connection and what it has been sent
+struct Client {
+    connection: Connection,
+    /// Set once the client has logged in
+    player: Option<Entity>,
+    name: String,
+    udp_token: u64,
+    /// Where the client's datagrams come from, once it has said hello over UDP
+    udp_addr: Option<SocketAddr>,
+    sent_chunks: HashSet<ChunkCoord>,
+    /// Entities the client has been told about, with the position, yaw and pitch last sent
+    known_entities: HashMap<Entity, (Vec3, f32, f32)>,
+    /// Elapsed seconds when the last packet arrived
+    last_heard: f32,
+    /// Set when the client is to be dropped, with the reason it's given
+    disconnect: Option<String>,
+    /// Move commands waiting for a tick, oldest first
+    moves: VecDeque<MoveCommand>,
+    /// Sequence of the latest move command queued
+    last_sequence: u32,
+    /// Sequence of the latest move command applied, acknowledged in `PlayerState`
+    applied_sequence: u32,
+    /// Move commands that may be applied before the next tick's
+    move_credit: u32,
+    /// Acknowledged sequence and body position last sent in a `PlayerState`
+    state_sent: Option<(u32, Vec3)>,
+    /// The `Stats` packet last sent, as its health, hunger, air and invulnerability ticks
+    stats_sent: Option<(u8, u8, u16, u16)>,
+}
+
+impl Client {
+    fn logged_in(&self) -> Option<Entity> {
+        self.player.filter(|_| self.disconnect.is_none())
+    }
+
+    /// Queue the commands not seen yet, resends of earlier ones are skipped
+    fn queue_moves(&mut self, commands: Vec<MoveCommand>) {
+        for command in commands {
+            if command.sequence > self.last_sequence {
+                self.last_sequence = command.sequence;
+                self.moves.push_back(command);
+            }
+        }
+        let excess = self.moves.len().saturating_sub(MAX_QUEUED_MOVES);
+        self.moves.drain(..excess);
+    }
+
+    /// Queue a packet, over UDP when it's unreliable and the client's address is known
+    fn send(&mut self, udp: &UdpSocket, packet: &ServerPacket) {
+        match (packet.channel(), self.udp_addr) {
+            (Channel::Unreliable, Some(addr)) => {
+                // A lost or refused datagram is just a missed update
+                let _ = udp.send_to(&encode_datagram(self.udp_token, packet), addr);
+            }
+            _ => self.connection.send(packet),
+        }
+    }
+}
+
+/// The listening sockets of a multiplayer server and its connected clients
+/// Insert one (from `NetServer::bind`) to let `ServerNetPlugin` accept players
+#[derive(Resource)]
+pub struct NetServer {
+    listener: TcpListener,
+    /// Bound to the same port as the listener
+    udp: UdpSocket,
+    clients: Vec<Client>,
+    /// Names of the players who may run commands that change the world
+    operators: HashSet<String>,
+    next_keep_alive: f32,
+    keep_alive_id: u64,
+}
+
+impl NetServer {
+    /// Listen for TCP and UDP on `port` on every interface (0 picks a free port)
+    pub fn bind(port: u16) -> io::Result<Self> {
+        let listener = TcpListener::bind((Ipv4Addr::UNSPECIFIED, port))?;
+        listener.set_nonblocking(true)?;
+        let udp = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, listener.local_addr()?.port()))?;
+        udp.set_nonblocking(true)?;
+        Ok(Self { listener, udp, clients: Vec::new(), operators: HashSet::new(), next_keep_alive: 0.0, keep_alive_id: 0 })
+    }
+
+    /// Trust the players called `names` with every command
+    /// (names aren't authenticated, so only op players on servers you can reach privately)
+    pub fn with_operators(mut self, names: impl IntoIterator<Item = String>) -> Self {
+        self.operators = names.into_iter().collect();
+        self
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Names of the logged-in players
+    pub fn player_names(&self) -> impl Iterator<Item = &str> {
+        self.clients.iter().filter(|client| client.logged_in().is_some()).map(|client| client.name.as_str())
+    }
+
+    /// Send a chat line to every logged-in player
+    fn broadcast_chat(&mut self, sender: Option<&str>, message: &str) {
+        let packet = ServerPacket::Chat { sender: sender.map(str::to_string), message: message.to_string() };
+        for client in self.clients.iter_mut().filter(|client| client.logged_in().is_some()) {
+            client.connection.send(&packet);
+        }
+    }
+}
+
+/// Serves the simulation to remote players over the network protocol: logs players in,
+/// streams chunks, block, light and entity changes to them and turns their packets into
+/// moves, block edit requests and commands
+/// Runs alongside `SimulationPlugin` whenever a `NetServer` resource is present
+pub struct ServerNetPlugin;
+
+impl Plugin for ServerNetPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, (
+            accept_clients,
+            receive_client_packets,
+        ).chain().before(run_commands).run_if(resource_exists::<NetServer>))
+            // Remote players step through their move commands on the world tick, before
+            // damage is dealt so their landings hurt the same tick
+            .add_systems(FixedUpdate, (
+                apply_move_commands,
+                send_player_states,
+            ).chain().before(update_environment_damage).run_if(resource_exists::<NetServer>))
+            .add_systems(PostUpdate, (
+                stream_chunks,
+                send_block_changes,
+                send_light_updates,
+                replicate_entities,
+                sync_inventories,
+                sync_game_modes,
+                sync_selections,
+                sync_stats,
+                send_command_feedback,
+                keep_clients_alive,
+                flush_clients,
+            ).chain().after(relight_chunks).run_if(resource_exists::<NetServer>));
+    }
+}
+
+/// Take new connections; they have until the timeout to log in
+fn accept_clients(mut server: ResMut<NetServer>, time: Res<Time>) {
+    loop {
+        match server.listener.accept() {
+            Ok((stream, addr)) => match Connection::new(stream) {
+                Ok(connection) => {
+                    info!("Connection from {}", addr);
+                    server.clients.push(Client {
+                        connection,
+                        player: None,
+                        name: String::new(),
+                        udp_token: rand::thread_rng().gen(),
+                        udp_addr: None,
+                        sent_chunks: HashSet::new(),
+                        known_entities: HashMap::new(),
+                        last_heard: time.elapsed_secs(),
+                        disconnect: None,
+                        moves: VecDeque::new(),
+                        last_sequence: 0,
+                        applied_sequence: 0,
+                        move_credit: 0,
+                        state_sent: None,
+                        stats_sent: None,
+                    });
+                }
+                Err(error) => warn!("Couldn't set up connection from {}: {}", addr, error),
+            },
+            Err(error) if error.kind() == ErrorKind::WouldBlock => break,
+            Err(error) => {
+                warn!("Accepting a connection failed: {}", error);
+                break;
+            }
+        }
+    }
+}
+
+/// Read every client's packets (TCP, then UDP) and act on them
+fn receive_client_packets(
+    mut commands: Commands,
+    mut server: ResMut<NetServer>,
+    time: Res<Time>,
+    spawn_point: Res<SpawnPoint>,
+    mut inventories: Query<&mut PlayerInventory, With<RemotePlayer>>,
+    mut edits: EventWriter<BlockEditRequest>,
+    mut run_command: EventWriter<RunCommand>,
+) {
+    let now = time.elapsed_secs();
+    let server = server.as_mut();
+    let mut chat = Vec::new();
+
+    for index in 0..server.clients.len() {
+        loop {
+            let client = &mut server.clients[index];
+            if client.disconnect.is_some() {
+                break;
+            }
+            let packet = match client.connection.receive::<ClientPacket>() {
+                Ok(Some(packet)) => packet,
+                Ok(None) => break,
+                Err(NetError::Protocol(error)) => {
+                    client.disconnect = Some(format!("Bad packet: {}", error));
+                    break;
+                }
+                Err(_) => {
+                    client.disconnect = Some(String::new());
+                    break;
+                }
+            };
+            client.last_heard = now;
+
+            let Some(player) = client.player else {
+                let ClientPacket::Login { protocol_version, name } = packet else {
+                    client.disconnect = Some("Not logged in".to_string());
+                    break;
+                };
+                let taken = server.clients.iter().any(|other| other.logged_in().is_some() && other.name == name);
+                let client = &mut server.clients[index];
+                if let Err(reason) = check_login(protocol_version, &name, taken) {
+                    client.disconnect = Some(reason);
+                    break;
+                }
+
+                let position = spawn_point.feet_position();
+                let player = commands.spawn((
+                    Transform::from_translation(position),
+                    PhysicsBody::new(position, PLAYER_HALF_WIDTH, PLAYER_HEIGHT).with_step_height(PLAYER_STEP_HEIGHT),
+                    Player::default(),
+                    GameMode::Survival,
+                    EditHistory::default(),
+                    Selection::default(),
+                    Clipboard::default(),
+                    PlayerInventory::default(),
+                    PlayerStats::default(),
+                    RemotePlayer { name: name.clone(), operator: server.operators.contains(&name) },
+                )).id();
+                info!("{} logged in from {:?}", name, client.connection.peer_addr().ok());
+                client.connection.send(&ServerPacket::LoginSuccess { player: player.to_bits(), position, udp_token: client.udp_token });
+                client.player = Some(player);
+                client.name = name.clone();
+                chat.push((None, format!("{} joined the game", name)));
+                continue;
+            };
+
+            match packet {
+                ClientPacket::Chat { message } => {
+                    let message: String = message.trim().chars().take(MAX_CHAT_LENGTH).collect();
+                    if let Some(command) = message.strip_prefix('/') {
+                        info!("{} ran /{}", client.name, command);
+                        run_command.send(RunCommand { player, command: command.to_string() });
+                    } else if !message.is_empty() {
+                        chat.push((Some(client.name.clone()), message));
+                    }
+                }
+                packet => handle_player_packet(packet, client, player, &mut inventories, &mut edits),
+            }
+        }
+    }
+
+    // Datagrams find their client by login token; only hellos may come from a new address
+    let mut buffer = [0; MAX_DATAGRAM];
+    loop {
+        let (len, addr) = match server.udp.recv_from(&mut buffer) {
+            Ok(received) => received,
+            Err(error) if error.kind() == ErrorKind::WouldBlock => break,
+            // A client that went away can bounce an earlier datagram back as an error
+            Err(_) => continue,
+        };
+        let Ok((token, packet)) = decode_datagram::<ClientPacket>(&buffer[..len]) else {
+            continue;
+        };
+        let Some(client) = server.clients.iter_mut().find(|client| client.udp_token == token) else {
+            continue;
+        };
+        let Some(player) = client.logged_in() else {
+            continue;
+        };
+        client.last_heard = now;
+        match packet {
+            ClientPacket::UdpHello => {
+                client.udp_addr = Some(addr);
+                client.connection.send(&ServerPacket::UdpReady);
+            }
+            packet if client.udp_addr == Some(addr) && packet.channel() == Channel::Unreliable => {
+                handle_player_packet(packet, client, player, &mut inventories, &mut edits);
+            }
+            _ => {}
+        }
+    }
+
+    for (sender, message) in chat {
+        match &sender {
+            Some(name) => info!("<{}> {}", name, message),
+            None => info!("{}", message),
+        }
+        server.broadcast_chat(sender.as_deref(), &message);
+    }
+}
+
+/// Whether a login may go ahead, or why not
+fn check_login(protocol_version: u16, name: &str, taken: bool) -> Result<(), String> {
+    if protocol_version < PROTOCOL_VERSION {
+        return Err(format!("Outdated client, the server is on protocol {}", PROTOCOL_VERSION));
+    }
+    if protocol_version > PROTOCOL_VERSION {
+        return Err(format!("Outdated server, it is on protocol {}", PROTOCOL_VERSION));
+    }
+    let valid = (1..=MAX_NAME_LENGTH).contains(&name.len())
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if !valid {
+        return Err(format!("Names are 1 to {} letters, digits or underscores", MAX_NAME_LENGTH));
+    }
+    if taken {
+        return Err(format!("{} is already playing", name));
+    }
+    Ok(())
+}
+
+/// Act on a packet from a logged-in player
+fn handle_player_packet(
+    packet: ClientPacket,
+    client: &mut Client,
+    player: Entity,
+    inventories: &mut Query<&mut PlayerInventory, With<RemotePlayer>>,
+    edits: &mut EventWriter<BlockEditRequest>,
+) {
+    let Ok(mut inventory) = inventories.get_mut(player) else {
+        return;
+    };
+    match packet {
+        ClientPacket::MoveCommands { commands } => client.queue_moves(commands),
+        ClientPacket::BlockEdit(action) => {
+            edits.send(BlockEditRequest { player, action });
+        }
+        ClientPacket::SelectSlot { slot } if (slot as usize) < inventory.hotbar.len() => {
+            inventory.selected_slot = slot as usize;
+        }
+        _ => {}
+    }
+}
+
+/// Step each remote player's body through the move commands their client sent, with the
+/// same `step_player` the client predicts with, within the player's move credit
+/// Each step tires the player and hurts them on landing like the local player's physics does
+fn apply_move_commands(
+    mut server: ResMut<NetServer>,
+    time: Res<Time>,
+    chunk_manager: Res<ChunkManager>,
+    chunks: Query<&'static Chunk>,
+    block_registry: Res<BlockRegistry>,
+    mut players: Query<(&mut Player, &mut PhysicsBody, &GameMode, &mut PlayerStats), With<RemotePlayer>>,
+    mut damage: EventWriter<PlayerDamage>,
+) {
+    let dt = time.delta_secs();
+    let colliders = ChunkColliders { chunk_manager: &chunk_manager, chunks: &chunks, registry: &block_registry };
+    for client in server.clients.iter_mut() {
+        let Some(entity) = client.logged_in() else {
+            continue;
+        };
+        let Ok((mut player, mut body, game_mode, mut stats)) = players.get_mut(entity) else {
+            continue;
+        };
+        client.move_credit = (client.move_credit + 1).min(MAX_MOVE_CREDIT);
+        // Commands wait while the terrain around the player loads
+        if !chunk_manager.loaded_chunks.contains_key(&ChunkCoord::from_world_pos(body.position)) {
+            continue;
+        }
+
+        let abilities = game_mode.abilities();
+        while client.move_credit > 0 {
+            let Some(mut command) = client.moves.pop_front() else {
+                break;
+            };
+            client.move_credit -= 1;
+            client.applied_sequence = command.sequence;
+
+            // Nothing faster than sprinting, and flight only where the game mode allows it
+            let max_speed = player.move_speed * player.sprint_multiplier;
+            command.move_input = Vec3::new(command.move_input.x, 0.0, command.move_input.z).clamp_length_max(max_speed);
+            command.fly_input = command.fly_input.clamp(-max_speed, max_speed);
+            command.flying = abilities.noclip || (command.flying && abilities.can_fly);
+
+            command.apply_to(&mut player);
+            let step = step_player(&mut player, &mut body, abilities.noclip, &colliders, dt);
+            apply_step_effects(entity, &player, &step, &mut stats, &mut damage);
+        }
+    }
+}
+
+/// Tell each client where the server put their player and which of their moves it has applied
+fn send_player_states(
+    mut server: ResMut<NetServer>,
+    tick: Res<WorldTick>,
+    bodies: Query<&PhysicsBody, With<RemotePlayer>>,
+) {
+    let server = server.as_mut();
+    for client in server.clients.iter_mut() {
+        let Some(body) = client.logged_in().and_then(|player| bodies.get(player).ok()) else {
+            continue;
+        };
+        let sequence = client.applied_sequence;
+        if client.state_sent == Some((sequence, body.position)) {
+            continue;
+        }
+        client.state_sent = Some((sequence, body.position));
+        client.send(&server.udp, &ServerPacket::PlayerState {
+            tick: tick.tick,
+            sequence,
+            position: body.position,
+            velocity: body.velocity,
+            on_ground: body.on_ground,
+        });
+    }
+}
+
+/// Send each player the settled chunks in their view (nearest first), and tell them to
+/// drop chunks that unloaded or fell out of view
+/// New chunks wait while a client still has `CHUNK_BACKLOG` bytes to take, so a slow one
+/// gets them at its own pace
+fn stream_chunks(
+    mut server: ResMut<NetServer>,
+    settings: Res<WorldSettings>,
+    chunk_manager: Res<ChunkManager>,
+    chunks: Query<&Chunk, (Without<NeedsRelight>, Without<NeedsPostProcess>)>,
+    players: Query<&Transform, With<RemotePlayer>>,
+) {
+    for client in server.clients.iter_mut() {
+        let Some(transform) = client.logged_in().and_then(|player| players.get(player).ok()) else {
+            continue;
+        };
+        let center = ChunkCoord::from_world_pos(transform.translation);
+
+        let stale: Vec<ChunkCoord> = client.sent_chunks.iter()
+            .filter(|&&coord| !chunk_manager.loaded_chunks.contains_key(&coord) || !settings.in_view(center, coord, UNLOAD_MARGIN))
+            .copied()
+            .collect();
+        for coord in stale {
+            client.sent_chunks.remove(&coord);
+            client.connection.send(&ServerPacket::UnloadChunk { coord });
+        }
+        if client.connection.queued() > CHUNK_BACKLOG {
+            continue;
+        }
+
+        let mut unsent: Vec<(ChunkCoord, &Chunk)> = chunk_manager.loaded_chunks.iter()
+            .filter(|(coord, _)| settings.in_view(center, **coord, 0) && !client.sent_chunks.contains(coord))
+            .filter_map(|(coord, &entity)| Some((*coord, chunks.get(entity).ok()?)))
+            .collect();
+        unsent.sort_by_key(|(coord, _)| coord.distance_squared(&center));
+        for (coord, chunk) in unsent.into_iter().take(CHUNKS_PER_UPDATE) {
+            client.sent_chunks.insert(coord);
+            client.connection.send(&ServerPacket::ChunkData(ChunkData::from_chunk(chunk)));
+        }
+    }
+}
+
+/// Forward changed blocks to the players who have their chunk
+/// A chunk with many changes at once (a `/fill`) is sent again whole instead
+fn send_block_changes(
+    mut server: ResMut<NetServer>,
+    mut changes: EventReader<BlockChanged>,
+    chunk_manager: Res<ChunkManager>,
+    chunks: Query<&Chunk>,
+) {
+    let mut by_chunk: HashMap<ChunkCoord, HashSet<IVec3>> = HashMap::new();
+    for change in changes.read() {
+        by_chunk.entry(world_to_chunk_local(change.0).0).or_default().insert(change.0);
+    }
+    for (coord, positions) in by_chunk {
+        let Some(chunk) = chunk_manager.loaded_chunks.get(&coord).and_then(|&entity| chunks.get(entity).ok()) else {
+            continue;
+        };
+        let packets: Vec<ServerPacket> = if positions.len() > BLOCK_CHANGES_PER_CHUNK {
+            vec![ServerPacket::ChunkData(ChunkData::from_chunk(chunk))]
+        } else {
+            positions.into_iter()
+                .map(|pos| {
+                    let local = world_to_chunk_local(pos).1;
+                    ServerPacket::BlockChange { pos, state: chunk.get_state(local.x as usize, local.y as usize, local.z as usize) }
+                })
+                .collect()
+        };
+        for client in server.clients.iter_mut().filter(|client| client.sent_chunks.contains(&coord)) {
+            for packet in &packets {
+                client.connection.send(packet);
+            }
+        }
+    }
+}
+
+/// Forward relit chunks' light to the players who have them
+fn send_light_updates(
+    mut server: ResMut<NetServer>,
+    mut relit: EventReader<ChunkLightChanged>,
+    chunk_manager: Res<ChunkManager>,
+    chunks: Query<&Chunk>,
+) {
+    let coords: HashSet<ChunkCoord> = relit.read().map(|relit| relit.0).collect();
+    for coord in coords {
+        let Some(chunk) = chunk_manager.loaded_chunks.get(&coord).and_then(|&entity| chunks.get(entity).ok()) else {
+            continue;
+        };
+        let light = ChunkLight::from_chunk(chunk);
+        for client in server.clients.iter_mut().filter(|client| client.sent_chunks.contains(&coord)) {
+            client.connection.send(&ServerPacket::LightUpdate { coord, light: light.clone() });
+        }
+    }
+}
+
+/// Tell each player about the mobs, item drops, falling blocks and other players within their
+/// view distance as they come and go, and where the ones they know about moved
+#[allow(clippy::type_complexity)]
+fn replicate_entities(
+    mut server: ResMut<NetServer>,
+    settings: Res<WorldSettings>,
+    tick: Res<WorldTick>,
+    entities: Query<
+        (Entity, &Transform, Option<&PhysicsBody>, Option<&Mob>, Option<&ItemDrop>, Option<&FallingBlock>, Option<&RemotePlayer>, Option<&Player>),
+        Or<(With<Mob>, With<ItemDrop>, With<FallingBlock>, With<RemotePlayer>)>,
+    >,
+) {
+    let range = (settings.view_distance * CHUNK_SIZE as i32) as f32;
+    let server = server.as_mut();
+    for client in server.clients.iter_mut() {
+        let Some(own_player) = client.logged_in() else {
+            continue;
+        };
+        let Ok((_, own_transform, ..)) = entities.get(own_player) else {
+            continue;
+        };
+        let center = own_transform.translation;
+        // Bodies are sent as of the latest physics step rather than interpolated between steps,
+        // so the position matches the tick it's stamped with
+
+        let mut visible = HashSet::new();
+        for (entity, transform, body, mob, drop, falling, remote, player) in entities.iter() {
+            let position = body.map_or(transform.translation, |body| body.position + body.render_offset);
+            if entity == own_player || position.distance(center) > range {
+                continue;
+            }
+            let kind = match (mob, drop, falling, remote) {
+                (Some(mob), ..) => NetEntityKind::Mob { entity_type: mob.entity_type },
+                (_, Some(drop), ..) => NetEntityKind::Item { stack: drop.stack },
+                (_, _, Some(falling), _) => NetEntityKind::FallingBlock { state: falling.state },
+                (.., Some(remote)) => NetEntityKind::Player { name: remote.name.clone() },
+                _ => continue,
+            };
+            let (yaw, pitch) = match (player, mob) {
+                (Some(player), _) => (player.yaw, player.pitch),
+                (_, Some(mob)) => (mob.yaw, 0.0),
+                _ => (0.0, 0.0),
+            };
+            visible.insert(entity);
+
+            let id = entity.to_bits();
+            match client.known_entities.get(&entity) {
+                None => {
+                    client.send(&server.udp, &ServerPacket::EntitySpawn { id, tick: tick.tick, kind, position, yaw });
+                }
+                Some(&(last_position, last_yaw, last_pitch)) => {
+                    let moved = last_position.distance(position) > MOVE_EPSILON
+                        || (last_yaw - yaw).abs() > MOVE_EPSILON
+                        || (last_pitch - pitch).abs() > MOVE_EPSILON;
+                    if !moved {
+                        continue;
+                    }
+                    client.send(&server.udp, &ServerPacket::EntityMove { id, tick: tick.tick, position, yaw, pitch });
+                }
+            }
+            client.known_entities.insert(entity, (position, yaw, pitch));
+        }
+
+        let gone: Vec<Entity> = client.known_entities.keys().filter(|entity| !visible.contains(entity)).copied().collect();
+        for entity in gone {
+            client.known_entities.remove(&entity);
+            client.send(&server.udp, &ServerPacket::EntityDespawn { id: entity.to_bits() });
+        }
+    }
+}
+
+/// Send players their hotbar whenever it changes
+#[allow(clippy::type_complexity)]
+fn sync_inventories(
+    mut server: ResMut<NetServer>,
+    inventories: Query<(Entity, &PlayerInventory), (With<RemotePlayer>, Changed<PlayerInventory>)>,
+) {
+    for (player, inventory) in inventories.iter() {
+        if let Some(client) = server.clients.iter_mut().find(|client| client.logged_in() == Some(player)) {
+            client.connection.send(&ServerPacket::Inventory { hotbar: inventory.hotbar });
+        }
+    }
+}
+
+/// Tell players their game mode when they log in and whenever it changes (by `/gamemode`)
+#[allow(clippy::type_complexity)]
+fn sync_game_modes(
+    mut server: ResMut<NetServer>,
+    game_modes: Query<(Entity, &GameMode), (With<RemotePlayer>, Changed<GameMode>)>,
+) {
+    for (player, &mode) in game_modes.iter() {
+        if let Some(client) = server.clients.iter_mut().find(|client| client.logged_in() == Some(player)) {
+            client.connection.send(&ServerPacket::GameMode { mode });
+        }
+    }
+}
+
+/// Send players their selection corners whenever they change, so they see its outline
+#[allow(clippy::type_complexity)]
+fn sync_selections(
+    mut server: ResMut<NetServer>,
+    selections: Query<(Entity, &Selection), (With<RemotePlayer>, Changed<Selection>)>,
+) {
+    for (player, &selection) in selections.iter() {
+        if let Some(client) = server.clients.iter_mut().find(|client| client.logged_in() == Some(player)) {
+            client.connection.send(&ServerPacket::Selection(selection));
+        }
+    }
+}
+
+/// Send players their health, hunger and air when they log in and whenever they change
+/// Hunger's hidden counters change nearly every tick, so what was last sent is compared instead
+/// of waiting for `Changed`
+fn sync_stats(mut server: ResMut<NetServer>, stats: Query<&PlayerStats, With<RemotePlayer>>) {
+    for client in server.clients.iter_mut() {
+        let Some(stats) = client.logged_in().and_then(|player| stats.get(player).ok()) else {
+            continue;
+        };
+        let shown = (stats.health, stats.hunger, stats.air, stats.invulnerable_ticks);
+        if client.stats_sent == Some(shown) {
+            continue;
+        }
+        client.stats_sent = Some(shown);
+        client.connection.send(&ServerPacket::Stats {
+            health: stats.health,
+            hunger: stats.hunger,
+            air: stats.air,
+            invulnerable_ticks: stats.invulnerable_ticks,
+        });
+    }
+}
+
+/// Answer commands in chat, only to the player who ran them
+fn send_command_feedback(mut server: ResMut<NetServer>, mut feedback: EventReader<CommandFeedback>) {
+    for CommandFeedback { player, message, .. } in feedback.read() {
+        let Some(client) = server.clients.iter_mut().find(|client| client.player == Some(*player)) else {
+            continue;
+        };
+        for line in message.lines() {
+            client.connection.send(&ServerPacket::Chat { sender: None, message: line.to_string() });
+        }
+    }
+}
+
+/// Ping clients now and then, and drop the ones that have gone quiet
+fn keep_clients_alive(mut server: ResMut<NetServer>, time: Res<Time>) {
+    let now = time.elapsed_secs();
+    let ping = now >= server.next_keep_alive;
+    if ping {
+        server.next_keep_alive = now + KEEP_ALIVE_SECONDS;
+        server.keep_alive_id += 1;
+    }
+    let id = server.keep_alive_id;
+
+    for client in server.clients.iter_mut().filter(|client| client.disconnect.is_none()) {
+        if now - client.last_heard > TIMEOUT_SECONDS {
+            client.disconnect = Some("Timed out".to_string());
+        } else if ping && client.player.is_some() {
+            client.connection.send(&ServerPacket::KeepAlive { id });
+        }
+    }
+}
+
+/// Write out everything queued for each client, and drop the clients that are leaving:
+/// they get the reason, their player despawns and everyone else hears they left
+/// A client that has stopped reading is dropped once `MAX_BACKLOG` bytes wait for it
+fn flush_clients(mut commands: Commands, mut server: ResMut<NetServer>) {
+    for client in server.clients.iter_mut() {
+        if client.disconnect.is_none() {
+            if let Err(error) = client.connection.flush() {
+                client.disconnect = Some(String::new());
+                info!("Lost connection to {}: {}", client.name, error);
+            } else if client.connection.queued() > MAX_BACKLOG {
+                // Nothing more is queued for it, not even the reason
+                client.disconnect = Some(String::new());
+                info!("Dropped {}: {} bytes waiting to be sent", client.name, client.connection.queued());
+            }
+        }
+    }
+
+    let (leaving, staying): (Vec<Client>, Vec<Client>) = server.clients.drain(..).partition(|client| client.disconnect.is_some());
+    server.clients = staying;
+    for mut client in leaving {
+        let reason = client.disconnect.take().unwrap_or_default();
+        if !reason.is_empty() {
+            // Best effort - the client may already be gone
+            client.connection.send(&ServerPacket::Disconnect { reason: reason.clone() });
+            let _ = client.connection.flush();
+        }
+        let Some(player) = client.player else {
+            continue;
+        };
+        commands.entity(player).despawn_recursive();
+        info!("{} left the game {}", client.name, if reason.is_empty() { String::new() } else { format!("({})", reason) });
+        server.broadcast_chat(None, &format!("{} left the game", client.name));
+    }
+}
@@ -0,0 +1,90 @@
+use bevy::prelude::*;
+use super::collision::{CollisionWorld, MoveOptions, MoveResult, move_and_collide};
+use super::collision_box::CollisionBox;
+
+/// An entity moved by the fixed-timestep physics
+/// Physics works on the feet position; the entity's `Transform` is interpolated
+/// between the last two physics steps every frame so motion stays smooth
+#[derive(Component, Debug, Clone)]
+pub struct PhysicsBody {
+    /// Feet position (bottom center of the box) after the latest physics step
+    pub position: Vec3,
+    /// Feet position after the step before that
+    pub previous_position: Vec3,
+    pub velocity: Vec3,
+    pub half_width: f32,
+    pub height: f32,
+    /// Highest ledge walked up automatically
+    pub step_height: f32,
+    pub on_ground: bool,
+    /// Offset from the feet to the entity's `Transform` (e.g., eye height for a camera)
+    pub render_offset: Vec3,
+}
+
+impl PhysicsBody {
+    pub fn new(position: Vec3, half_width: f32, height: f32) -> Self {
+        Self {
+            position,
+            previous_position: position,
+            velocity: Vec3::ZERO,
+            half_width,
+            height,
+            step_height: 0.0,
+            on_ground: false,
+            render_offset: Vec3::ZERO,
+        }
+    }
+
+    pub fn with_step_height(mut self, step_height: f32) -> Self {
+        self.step_height = step_height;
+        self
+    }
+
+    pub fn collision_box(&self) -> CollisionBox {
+        CollisionBox::from_feet(self.position, self.half_width, self.height)
+    }
+
+    /// Move by `delta` with collision, updating position and `on_ground`
+    /// Velocity along blocked axes is zeroed
+    pub fn move_by(&mut self, world: &impl CollisionWorld, delta: Vec3, edge_guard: bool) -> MoveResult {
+        let options = MoveOptions { step_height: self.step_height, edge_guard, on_ground: self.on_ground };
+        let result = move_and_collide(world, self.collision_box(), delta, options);
+
+        self.position += result.offset;
+        self.on_ground = result.on_ground;
+        if result.blocked.x {
+            self.velocity.x = 0.0;
+        }
+        if result.blocked.y {
+            self.velocity.y = 0.0;
+        }
+        if result.blocked.z {
+            self.velocity.z = 0.0;
+        }
+        result
+    }
+
+    /// Jump straight to `position` without interpolating from the old one
+    pub fn teleport(&mut self, position: Vec3) {
+        self.position = position;
+        self.previous_position = position;
+    }
+}
+
+/// Remember where every body was before this physics step (runs before FixedUpdate)
+pub fn begin_physics_step(mut bodies: Query<&mut PhysicsBody>) {
+    for mut body in bodies.iter_mut() {
+        body.previous_position = body.position;
+    }
+}
+
+/// Place each body's transform between its last two physics positions
+pub fn interpolate_physics_bodies(
+    fixed_time: Res<Time<Fixed>>,
+    mut bodies: Query<(&PhysicsBody, &mut Transform)>,
+) {
+    let alpha = fixed_time.overstep_fraction();
+    for (body, mut transform) in bodies.iter_mut() {
+        transform.translation = body.previous_position.lerp(body.position, alpha) + body.render_offset;
+    }
+}
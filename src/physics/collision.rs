@@ -0,0 +1,340 @@
+use bevy::prelude::*;
+use super::collision_box::CollisionBox;
+use crate::blocks::{BlockRegistry, BlockState};
+use crate::systems::WorldBlocks;
+use crate::world::{Chunk, ChunkManager, world_to_chunk_local};
+
+/// Faces closer than this count as touching rather than overlapping
+/// Absorbs float drift so bodies resting on a surface don't sink into it
+const CONTACT_EPSILON: f32 = 1e-4;
+
+/// Step size used when shrinking a move to keep a sneaking body on its ledge
+const EDGE_GUARD_STEP: f32 = 0.05;
+
+/// Block collision geometry the resolver queries
+pub trait CollisionWorld {
+    /// World-space collision box of the block at `pos`
+    /// None if the block can be walked through or isn't loaded
+    fn block_box(&self, pos: IVec3) -> Option<CollisionBox>;
+
+    /// Every block collision box touching `region`
+    fn collision_boxes(&self, region: CollisionBox) -> Vec<CollisionBox> {
+        let (min, max) = region.block_range();
+        let mut boxes = Vec::new();
+        for y in min.y..=max.y {
+            for z in min.z..=max.z {
+                for x in min.x..=max.x {
+                    if let Some(block_box) = self.block_box(IVec3::new(x, y, z)) {
+                        boxes.push(block_box);
+                    }
+                }
+            }
+        }
+        boxes
+    }
+
+    /// Whether any block collision box overlaps `region`
+    fn is_obstructed(&self, region: CollisionBox) -> bool {
+        self.collision_boxes(region).iter().any(|block_box| block_box.intersects(&region))
+    }
+
+    /// Whether the block at `pos` is a liquid (false if it isn't loaded)
+    fn is_liquid(&self, pos: IVec3) -> bool;
+
+    /// Whether any liquid block overlaps `region`
+    fn in_liquid(&self, region: CollisionBox) -> bool {
+        let (min, max) = region.block_range();
+        (min.y..=max.y).any(|y| (min.z..=max.z).any(|z| (min.x..=max.x).any(|x| {
+            let pos = IVec3::new(x, y, z);
+            self.is_liquid(pos) && CollisionBox::block(pos).intersects(&region)
+        })))
+    }
+}
+
+/// Collision box of `state` placed at `pos`: solid blocks collide, with their collision height
+pub fn block_collision_box(registry: &BlockRegistry, pos: IVec3, state: BlockState) -> Option<CollisionBox> {
+    if state.is_air() {
+        return None;
+    }
+    let properties = &registry.get_block(state.id)?.properties;
+    if !properties.is_solid {
+        return None;
+    }
+    let block_box = CollisionBox::block(pos);
+    Some(CollisionBox::new(block_box.min, block_box.max - Vec3::Y * (1.0 - properties.collision_height)))
+}
+
+/// Whether `state` is a liquid block
+fn is_liquid_state(registry: &BlockRegistry, state: BlockState) -> bool {
+    !state.is_air() && registry.get_block(state.id).is_some_and(|block| block.properties.is_liquid)
+}
+
+/// Read-only collision view of the loaded chunk entities
+pub struct ChunkColliders<'a, 'w, 's> {
+    pub chunk_manager: &'a ChunkManager,
+    pub chunks: &'a Query<'w, 's, &'static Chunk>,
+    pub registry: &'a BlockRegistry,
+}
+
+impl ChunkColliders<'_, '_, '_> {
+    fn get_state(&self, pos: IVec3) -> Option<BlockState> {
+        let (coord, local) = world_to_chunk_local(pos);
+        let entity = self.chunk_manager.loaded_chunks.get(&coord)?;
+        let chunk = self.chunks.get(*entity).ok()?;
+        Some(chunk.get_state(local.x as usize, local.y as usize, local.z as usize))
+    }
+}
+
+impl CollisionWorld for ChunkColliders<'_, '_, '_> {
+    fn block_box(&self, pos: IVec3) -> Option<CollisionBox> {
+        block_collision_box(self.registry, pos, self.get_state(pos)?)
+    }
+
+    fn is_liquid(&self, pos: IVec3) -> bool {
+        self.get_state(pos).is_some_and(|state| is_liquid_state(self.registry, state))
+    }
+}
+
+impl CollisionWorld for WorldBlocks<'_, '_> {
+    fn block_box(&self, pos: IVec3) -> Option<CollisionBox> {
+        block_collision_box(self.registry(), pos, self.get_state(pos)?)
+    }
+
+    fn is_liquid(&self, pos: IVec3) -> bool {
+        self.get_state(pos).is_some_and(|state| is_liquid_state(self.registry(), state))
+    }
+}
+
+/// How a body is allowed to move
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MoveOptions {
+    /// Highest ledge the body climbs automatically when walking into it (0 disables step-up)
+    pub step_height: f32,
+    /// Refuse horizontal movement that would walk off a ledge (sneaking)
+    pub edge_guard: bool,
+    /// Whether the body was standing on something before this move
+    pub on_ground: bool,
+}
+
+/// Outcome of `move_and_collide`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MoveResult {
+    /// How far the body actually moved
+    pub offset: Vec3,
+    /// Whether the body ended the move standing on something
+    pub on_ground: bool,
+    /// Whether movement was blocked along each axis
+    pub blocked: BVec3,
+}
+
+/// Largest `delta` along `axis` that keeps `moving` out of `obstacle`
+fn clip_axis(moving: &CollisionBox, obstacle: &CollisionBox, axis: usize, delta: f32) -> f32 {
+    // Only obstacles overlapping on the other two axes can be hit
+    for other in [(axis + 1) % 3, (axis + 2) % 3] {
+        if moving.max[other] <= obstacle.min[other] + CONTACT_EPSILON
+            || moving.min[other] >= obstacle.max[other] - CONTACT_EPSILON
+        {
+            return delta;
+        }
+    }
+
+    if delta > 0.0 && moving.max[axis] <= obstacle.min[axis] + CONTACT_EPSILON {
+        delta.min(obstacle.min[axis] - moving.max[axis])
+    } else if delta < 0.0 && moving.min[axis] >= obstacle.max[axis] - CONTACT_EPSILON {
+        delta.max(obstacle.max[axis] - moving.min[axis])
+    } else {
+        delta
+    }
+}
+
+/// Move `body` along one axis, stopping at the first obstacle
+fn sweep_axis(obstacles: &[CollisionBox], body: &mut CollisionBox, axis: usize, delta: f32) -> f32 {
+    if delta == 0.0 {
+        return 0.0;
+    }
+    let clipped = obstacles.iter().fold(delta, |delta, obstacle| clip_axis(body, obstacle, axis, delta));
+    let mut offset = Vec3::ZERO;
+    offset[axis] = clipped;
+    *body = body.translated(offset);
+    clipped
+}
+
+/// Per-axis sweep (Y, then X, then Z) - each axis moves as far as it can before the next
+fn sweep(obstacles: &[CollisionBox], body: CollisionBox, delta: Vec3) -> Vec3 {
+    let mut body = body;
+    let y = sweep_axis(obstacles, &mut body, 1, delta.y);
+    let x = sweep_axis(obstacles, &mut body, 0, delta.x);
+    let z = sweep_axis(obstacles, &mut body, 2, delta.z);
+    Vec3::new(x, y, z)
+}
+
+/// Shrink horizontal movement until the body would still have ground within `drop` below it
+fn guard_edges(world: &impl CollisionWorld, body: CollisionBox, delta: Vec3, drop: f32) -> Vec3 {
+    let has_ground = |offset: Vec3| world.is_obstructed(body.translated(offset - Vec3::Y * drop));
+    let shrink = |value: f32| {
+        if value.abs() < EDGE_GUARD_STEP { 0.0 } else { value - EDGE_GUARD_STEP * value.signum() }
+    };
+
+    let mut delta = delta;
+    while delta.x != 0.0 && !has_ground(Vec3::new(delta.x, 0.0, 0.0)) {
+        delta.x = shrink(delta.x);
+    }
+    while delta.z != 0.0 && !has_ground(Vec3::new(0.0, 0.0, delta.z)) {
+        delta.z = shrink(delta.z);
+    }
+    while delta.x != 0.0 && delta.z != 0.0 && !has_ground(Vec3::new(delta.x, 0.0, delta.z)) {
+        delta.x = shrink(delta.x);
+        delta.z = shrink(delta.z);
+    }
+    delta
+}
+
+/// Move a box through the world by `delta` without passing through solid blocks
+/// The whole path is swept, so nothing is skipped however large `delta` is.
+/// Blocked horizontal movement retries one step up (half blocks, stairs) when
+/// `step_height` allows it, keeping whichever attempt got further
+pub fn move_and_collide(world: &impl CollisionWorld, body: CollisionBox, delta: Vec3, options: MoveOptions) -> MoveResult {
+    let mut delta = delta;
+    if options.edge_guard && options.on_ground && delta.y <= 0.0 {
+        delta = guard_edges(world, body, delta, options.step_height.max(EDGE_GUARD_STEP));
+    }
+
+    let obstacles = world.collision_boxes(body.expanded_towards(delta));
+    let mut offset = sweep(&obstacles, body, delta);
+    let mut landed = delta.y < 0.0 && offset.y != delta.y;
+
+    let blocked_horizontally = offset.x != delta.x || offset.z != delta.z;
+    if options.step_height > 0.0 && (options.on_ground || landed) && blocked_horizontally {
+        // Retry from the start: up by the step height, across, then back down onto the step
+        let step = Vec3::new(delta.x, options.step_height, delta.z);
+        let step_obstacles = world.collision_boxes(body.expanded_towards(step));
+        let mut stepped_body = body;
+        let up = sweep_axis(&step_obstacles, &mut stepped_body, 1, step.y);
+        let x = sweep_axis(&step_obstacles, &mut stepped_body, 0, step.x);
+        let z = sweep_axis(&step_obstacles, &mut stepped_body, 2, step.z);
+        let down = sweep_axis(&step_obstacles, &mut stepped_body, 1, -up);
+
+        if x * x + z * z > offset.x * offset.x + offset.z * offset.z {
+            offset = Vec3::new(x, up + down, z);
+            landed = down != -up;
+        }
+    }
+
+    MoveResult {
+        offset,
+        on_ground: landed,
+        blocked: BVec3::new(offset.x != delta.x, offset.y != delta.y, offset.z != delta.z),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    /// Hand-built collision grid: block position -> collision height
+    #[derive(Default)]
+    struct Grid(HashMap<IVec3, f32>);
+
+    impl Grid {
+        fn floor() -> Self {
+            let mut grid = Grid::default();
+            for x in -8..8 {
+                for z in -8..8 {
+                    grid.0.insert(IVec3::new(x, 0, z), 1.0);
+                }
+            }
+            grid
+        }
+    }
+
+    impl CollisionWorld for Grid {
+        fn block_box(&self, pos: IVec3) -> Option<CollisionBox> {
+            let height = self.0.get(&pos)?;
+            let block_box = CollisionBox::block(pos);
+            Some(CollisionBox::new(block_box.min, block_box.max - Vec3::Y * (1.0 - height)))
+        }
+
+        fn is_liquid(&self, _pos: IVec3) -> bool {
+            false
+        }
+    }
+
+    fn player_at(feet: Vec3) -> CollisionBox {
+        CollisionBox::from_feet(feet, 0.3, 1.8)
+    }
+
+    const WALKING: MoveOptions = MoveOptions { step_height: 0.6, edge_guard: false, on_ground: true };
+
+    #[test]
+    fn lands_on_floor() {
+        let grid = Grid::floor();
+        let result = move_and_collide(&grid, player_at(Vec3::new(0.5, 3.0, 0.5)), Vec3::new(0.0, -5.0, 0.0), MoveOptions::default());
+        assert!((result.offset.y + 2.0).abs() < 1e-4);
+        assert!(result.on_ground);
+    }
+
+    #[test]
+    fn huge_step_does_not_tunnel_through_thin_wall() {
+        let mut grid = Grid::floor();
+        for y in 1..3 {
+            grid.0.insert(IVec3::new(3, y, 0), 1.0);
+        }
+        let result = move_and_collide(&grid, player_at(Vec3::new(0.5, 1.0, 0.5)), Vec3::new(50.0, 0.0, 0.0), WALKING);
+        // Stops flush against the wall at x = 3
+        assert!((0.5 + result.offset.x + 0.3 - 3.0).abs() < 1e-4);
+        assert!(result.blocked.x);
+    }
+
+    #[test]
+    fn moves_flush_to_wall_instead_of_stopping_short() {
+        let mut grid = Grid::floor();
+        grid.0.insert(IVec3::new(1, 1, 0), 1.0);
+        let result = move_and_collide(&grid, player_at(Vec3::new(0.5, 1.0, 0.5)), Vec3::new(0.15, 0.0, 0.0), WALKING);
+        assert!((result.offset.x - 0.15).abs() < 1e-4);
+        let result = move_and_collide(&grid, player_at(Vec3::new(0.5, 1.0, 0.5)), Vec3::new(0.5, 0.0, 0.0), WALKING);
+        assert!((result.offset.x - 0.2).abs() < 1e-4);
+    }
+
+    #[test]
+    fn steps_up_half_block() {
+        let mut grid = Grid::floor();
+        grid.0.insert(IVec3::new(1, 1, 0), 0.5);
+        let result = move_and_collide(&grid, player_at(Vec3::new(0.5, 1.0, 0.5)), Vec3::new(0.5, 0.0, 0.0), WALKING);
+        assert!((result.offset.x - 0.5).abs() < 1e-4);
+        assert!((result.offset.y - 0.5).abs() < 1e-4);
+        assert!(result.on_ground);
+    }
+
+    #[test]
+    fn does_not_step_up_full_block() {
+        let mut grid = Grid::floor();
+        grid.0.insert(IVec3::new(1, 1, 0), 1.0);
+        let result = move_and_collide(&grid, player_at(Vec3::new(0.5, 1.0, 0.5)), Vec3::new(0.5, 0.0, 0.0), WALKING);
+        assert!(result.offset.y.abs() < 1e-4);
+        assert!(result.blocked.x);
+    }
+
+    #[test]
+    fn sneaking_stops_at_ledge() {
+        let mut grid = Grid::default();
+        grid.0.insert(IVec3::new(0, 0, 0), 1.0);
+        let options = MoveOptions { edge_guard: true, ..WALKING };
+        let result = move_and_collide(&grid, player_at(Vec3::new(0.5, 1.0, 0.5)), Vec3::new(2.0, 0.0, 0.0), options);
+        // Can lean out until the box edge is just over the ledge, but not fall
+        assert!(result.offset.x < 0.8 + 1e-4);
+        assert!(result.offset.x > 0.0);
+
+        let unguarded = move_and_collide(&grid, player_at(Vec3::new(0.5, 1.0, 0.5)), Vec3::new(2.0, 0.0, 0.0), WALKING);
+        assert!((unguarded.offset.x - 2.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn rays_enter_boxes_they_point_at() {
+        let target = player_at(Vec3::new(3.5, 0.0, 0.5));
+        let distance = target.ray_distance(Vec3::new(0.5, 1.0, 0.5), Vec3::X).unwrap();
+        assert!((distance - 2.7).abs() < 1e-4);
+        assert_eq!(target.ray_distance(Vec3::new(0.5, 1.0, 0.5), Vec3::NEG_X), None);
+        assert_eq!(target.ray_distance(Vec3::new(3.5, 1.0, 0.5), Vec3::Y), Some(0.0));
+    }
+}
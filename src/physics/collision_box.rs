@@ -0,0 +1,60 @@
+use bevy::prelude::*;
+
+/// Axis-aligned box used for entity and block collision
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CollisionBox {
+    pub min: Vec3,
+    pub max: Vec3,
+}
+
+impl CollisionBox {
+    pub fn new(min: Vec3, max: Vec3) -> Self {
+        Self { min, max }
+    }
+
+    /// Box of an entity whose feet are centered at `feet`
+    pub fn from_feet(feet: Vec3, half_width: f32, height: f32) -> Self {
+        Self {
+            min: Vec3::new(feet.x - half_width, feet.y, feet.z - half_width),
+            max: Vec3::new(feet.x + half_width, feet.y + height, feet.z + half_width),
+        }
+    }
+
+    /// The unit box of the block at `pos`
+    pub fn block(pos: IVec3) -> Self {
+        let min = pos.as_vec3();
+        Self { min, max: min + Vec3::ONE }
+    }
+
+    pub fn translated(self, offset: Vec3) -> Self {
+        Self { min: self.min + offset, max: self.max + offset }
+    }
+
+    /// Stretch the box along `delta`, covering everything it passes through while moving
+    pub fn expanded_towards(self, delta: Vec3) -> Self {
+        Self { min: self.min + delta.min(Vec3::ZERO), max: self.max + delta.max(Vec3::ZERO) }
+    }
+
+    /// Whether the boxes overlap (touching faces don't count)
+    pub fn intersects(&self, other: &CollisionBox) -> bool {
+        self.min.x < other.max.x && self.max.x > other.min.x
+            && self.min.y < other.max.y && self.max.y > other.min.y
+            && self.min.z < other.max.z && self.max.z > other.min.z
+    }
+
+    /// Inclusive range of block positions the box touches
+    pub fn block_range(&self) -> (IVec3, IVec3) {
+        (self.min.floor().as_ivec3(), self.max.floor().as_ivec3())
+    }
+
+    /// Distance along the ray from `origin` (a unit `direction`) to where it enters the box,
+    /// 0 if it starts inside, None if it misses
+    pub fn ray_distance(&self, origin: Vec3, direction: Vec3) -> Option<f32> {
+        let inverse = direction.recip();
+        let near = (self.min - origin) * inverse;
+        let far = (self.max - origin) * inverse;
+        let enter = near.min(far).max_element();
+        let exit = near.max(far).min_element();
+        (exit >= enter.max(0.0)).then_some(enter.max(0.0))
+    }
+}
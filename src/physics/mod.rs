@@ -0,0 +1,9 @@
+mod collision_box;
+mod collision;
+mod body;
+mod movement;
+
+pub use collision_box::CollisionBox;
+pub use collision::{CollisionWorld, ChunkColliders, MoveOptions, move_and_collide};
+pub use body::{PhysicsBody, begin_physics_step, interpolate_physics_bodies};
+pub use movement::{MoveCommand, PlayerStep, step_player, TERMINAL_VELOCITY};
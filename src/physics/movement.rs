@@ -0,0 +1,188 @@
+use bevy::prelude::*;
+use crate::components::Player;
+use super::body::PhysicsBody;
+use super::collision::CollisionWorld;
+
+/// Fastest anything falls through the air (blocks/s)
+pub const TERMINAL_VELOCITY: f32 = 40.0;
+
+/// Upward speed of a player swimming with jump held, and the fastest one sinks (blocks/s)
+const SWIM_SPEED: f32 = 4.0;
+const SINK_SPEED: f32 = 2.0;
+
+/// Walking speed in a liquid, relative to on land
+const LIQUID_MOVE_FACTOR: f32 = 0.5;
+
+/// One fixed step's worth of player input, numbered so a client can tell which of its
+/// commands the server has applied (client-side prediction replays the rest)
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct MoveCommand {
+    pub sequence: u32,
+    /// Horizontal velocity requested by the movement keys (blocks/s)
+    pub move_input: Vec3,
+    /// Vertical velocity requested while flying (blocks/s)
+    pub fly_input: f32,
+    pub jump: bool,
+    pub sneaking: bool,
+    pub sprinting: bool,
+    pub flying: bool,
+    pub yaw: f32,
+    pub pitch: f32,
+}
+
+impl MoveCommand {
+    /// The input the player holds right now
+    pub fn from_player(sequence: u32, player: &Player) -> Self {
+        Self {
+            sequence,
+            move_input: player.move_input,
+            fly_input: player.fly_input,
+            jump: player.jump_requested,
+            sneaking: player.is_sneaking,
+            sprinting: player.is_sprinting,
+            flying: player.is_flying,
+            yaw: player.yaw,
+            pitch: player.pitch,
+        }
+    }
+
+    /// Hold this input on the player for the next `step_player`
+    pub fn apply_to(&self, player: &mut Player) {
+        player.move_input = self.move_input;
+        player.fly_input = self.fly_input;
+        player.jump_requested = self.jump;
+        player.is_sneaking = self.sneaking;
+        player.is_sprinting = self.sprinting;
+        player.is_flying = self.flying;
+        player.yaw = self.yaw;
+        player.pitch = self.pitch;
+    }
+}
+
+/// What happened during one `step_player`, for the caller to turn into hunger and damage
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct PlayerStep {
+    /// Jumped off the ground this step
+    pub jumped: bool,
+    /// How far the body moved
+    pub moved: Vec3,
+    /// Falling speed when the body landed this step
+    pub landing_speed: Option<f32>,
+}
+
+/// Move a player's body one fixed step by the input held on `player`
+/// Walking applies gravity, auto step-up and (while sneaking) the ledge guard;
+/// in a liquid the body moves slowly, sinks gently and swims up while jump is held.
+/// Flying moves freely but still collides, `noclip` passes through terrain.
+/// The same function runs the local player, a server's remote players and a client's
+/// prediction, so all three agree on where the same inputs lead
+pub fn step_player(player: &mut Player, body: &mut PhysicsBody, noclip: bool, world: &impl CollisionWorld, dt: f32) -> PlayerStep {
+    let mut step = PlayerStep::default();
+    let start = body.position;
+
+    if noclip {
+        body.velocity = player.move_input + Vec3::Y * player.fly_input;
+        body.position += body.velocity * dt;
+        body.on_ground = false;
+    } else if player.is_flying {
+        body.velocity = player.move_input + Vec3::Y * player.fly_input;
+        let delta = body.velocity * dt;
+        body.move_by(world, delta, false);
+        body.on_ground = false;
+    } else {
+        let in_liquid = world.in_liquid(body.collision_box());
+        let move_factor = if in_liquid { LIQUID_MOVE_FACTOR } else { 1.0 };
+        body.velocity.x = player.move_input.x * move_factor;
+        body.velocity.z = player.move_input.z * move_factor;
+        if player.jump_requested && in_liquid {
+            body.velocity.y = SWIM_SPEED;
+        } else if player.jump_requested && body.on_ground {
+            body.velocity.y = player.jump_force;
+            step.jumped = true;
+        }
+        body.velocity.y -= player.gravity * dt;
+        // A liquid drags a fall down to a slow sink
+        body.velocity.y = body.velocity.y.max(if in_liquid { -SINK_SPEED } else { -TERMINAL_VELOCITY });
+
+        let delta = body.velocity * dt;
+        let was_on_ground = body.on_ground;
+        let impact_speed = -body.velocity.y;
+        body.move_by(world, delta, player.is_sneaking);
+        // Falling into a liquid breaks the fall, however shallow it is
+        if body.on_ground && !was_on_ground && !world.in_liquid(body.collision_box()) {
+            step.landing_speed = Some(impact_speed);
+        }
+    }
+
+    player.jump_requested = false;
+    step.moved = body.position - start;
+    step
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::PLAYER_HEIGHT;
+    use crate::physics::CollisionBox;
+
+    const DT: f32 = 1.0 / 20.0;
+
+    /// A floor at y=0 under a pool of liquid `depth` blocks deep
+    struct Pool {
+        depth: i32,
+    }
+
+    impl CollisionWorld for Pool {
+        fn block_box(&self, pos: IVec3) -> Option<CollisionBox> {
+            (pos.y <= 0).then(|| CollisionBox::block(pos))
+        }
+
+        fn is_liquid(&self, pos: IVec3) -> bool {
+            (1..=self.depth).contains(&pos.y)
+        }
+    }
+
+    /// Drop a player from `height` above the floor and return the speed they land at, if any
+    fn fall_into(pool: &Pool, height: f32) -> Option<f32> {
+        let mut player = Player::default();
+        let mut body = PhysicsBody::new(Vec3::new(0.5, 1.0 + height, 0.5), 0.3, PLAYER_HEIGHT);
+        for _ in 0..200 {
+            let step = step_player(&mut player, &mut body, false, pool, DT);
+            if body.on_ground {
+                return step.landing_speed;
+            }
+        }
+        panic!("never landed, stuck at {:?}", body.position);
+    }
+
+    #[test]
+    fn falling_into_a_liquid_breaks_the_fall() {
+        let dry = fall_into(&Pool { depth: 0 }, 20.0).expect("landing on dry ground");
+        assert!(dry > 15.0, "landed at {}", dry);
+        assert_eq!(fall_into(&Pool { depth: 1 }, 20.0), None);
+        assert_eq!(fall_into(&Pool { depth: 3 }, 20.0), None);
+    }
+
+    #[test]
+    fn held_jump_swims_to_the_surface() {
+        let pool = Pool { depth: 3 };
+        let mut player = Player::default();
+        let mut body = PhysicsBody::new(Vec3::new(0.5, 1.0, 0.5), 0.3, PLAYER_HEIGHT);
+        let mut highest: f32 = 0.0;
+        for _ in 0..60 {
+            player.jump_requested = true;
+            let step = step_player(&mut player, &mut body, false, &pool, DT);
+            assert!(!step.jumped, "swimming isn't jumping off the ground");
+            highest = highest.max(body.position.y);
+        }
+        // Up until the feet reach the surface at y=4
+        assert!(highest > 3.9, "only got up to {}", highest);
+
+        // Letting go sinks back to the floor without a hard landing
+        for _ in 0..200 {
+            let step = step_player(&mut player, &mut body, false, &pool, DT);
+            assert_eq!(step.landing_speed, None);
+        }
+        assert!(body.on_ground && body.position.y < 1.01, "floating at {:?}", body.position);
+    }
+}
@@ -0,0 +1,182 @@
+use bevy::prelude::*;
+
+/// Lines kept in the chat log
+const CHAT_HISTORY: usize = 100;
+
+/// Lines typed into the console kept for Up/Down
+const INPUT_HISTORY: usize = 50;
+
+/// One line of chat; server messages (joins, leaves, command feedback) have no sender
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChatLine {
+    pub sender: Option<String>,
+    pub message: String,
+}
+
+/// Chat and command feedback, oldest first
+#[derive(Resource, Debug, Default)]
+pub struct ChatLog {
+    pub lines: Vec<ChatLine>,
+}
+
+impl ChatLog {
+    /// Add a line, forgetting the oldest past the history limit
+    pub fn push(&mut self, sender: Option<String>, message: String) {
+        self.lines.push(ChatLine { sender, message });
+        let excess = self.lines.len().saturating_sub(CHAT_HISTORY);
+        self.lines.drain(..excess);
+    }
+}
+
+/// The chat console: the line being typed, lines typed before, and ways to complete a command
+#[derive(Resource, Debug, Default)]
+pub struct ChatConsole {
+    pub open: bool,
+    pub input: String,
+    /// Lines sent, oldest first
+    pub history: Vec<String>,
+    /// Position in `history` while going back through it with Up
+    browsing: Option<usize>,
+    /// What was typed before going back through the history
+    draft: String,
+    /// Whole lines the input could be completed to, best first
+    pub suggestions: Vec<String>,
+    /// The suggestion Tab last put in the input
+    pub selected: Option<usize>,
+    /// The input `suggestions` were worked out for
+    pub suggested_for: Option<String>,
+}
+
+impl ChatConsole {
+    /// Open with `input` already typed ("/" when opened to type a command)
+    pub fn open(&mut self, input: &str) {
+        self.open = true;
+        self.input = input.to_string();
+        self.browsing = None;
+        self.edited();
+    }
+
+    pub fn close(&mut self) {
+        self.open = false;
+        self.input.clear();
+        self.edited();
+    }
+
+    pub fn type_char(&mut self, character: char) {
+        if !character.is_control() {
+            self.input.push(character);
+            self.edited();
+        }
+    }
+
+    pub fn backspace(&mut self) {
+        self.input.pop();
+        self.edited();
+    }
+
+    /// Close, returning the line typed if there is one and remembering it for Up
+    pub fn submit(&mut self) -> Option<String> {
+        let line = self.input.trim().to_string();
+        self.close();
+        if line.is_empty() {
+            return None;
+        }
+        if self.history.last() != Some(&line) {
+            self.history.push(line.clone());
+            let excess = self.history.len().saturating_sub(INPUT_HISTORY);
+            self.history.drain(..excess);
+        }
+        Some(line)
+    }
+
+    /// Go back to the line sent before the one shown
+    pub fn history_back(&mut self) {
+        let index = match self.browsing {
+            None if self.history.is_empty() => return,
+            None => {
+                self.draft = self.input.clone();
+                self.history.len() - 1
+            }
+            Some(index) => index.saturating_sub(1),
+        };
+        self.browsing = Some(index);
+        self.input = self.history[index].clone();
+        self.edited();
+    }
+
+    /// Go forward again, ending at what was being typed
+    pub fn history_forward(&mut self) {
+        let Some(index) = self.browsing else {
+            return;
+        };
+        if index + 1 < self.history.len() {
+            self.browsing = Some(index + 1);
+            self.input = self.history[index + 1].clone();
+        } else {
+            self.browsing = None;
+            self.input = std::mem::take(&mut self.draft);
+        }
+        self.edited();
+    }
+
+    /// Put the next suggestion in the input (Tab), going round to the first after the last
+    pub fn complete(&mut self) {
+        if self.suggestions.is_empty() {
+            return;
+        }
+        let index = self.selected.map_or(0, |index| (index + 1) % self.suggestions.len());
+        self.selected = Some(index);
+        self.input = self.suggestions[index].clone();
+    }
+
+    /// Whether the suggestions are out of date: the input was typed into since they were worked out
+    pub fn needs_suggestions(&self) -> bool {
+        self.selected.is_none() && self.suggested_for.as_ref() != Some(&self.input)
+    }
+
+    fn edited(&mut self) {
+        self.selected = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn console_history_and_completion_cycle() {
+        let mut console = ChatConsole::default();
+        for line in ["/help", "hello", "hello", "/seed"] {
+            console.open("");
+            line.chars().for_each(|character| console.type_char(character));
+            assert_eq!(console.submit().as_deref(), Some(line));
+        }
+        assert_eq!(console.history, ["/help", "hello", "/seed"]);
+
+        // Up walks back through what was sent, Down returns to the line being typed
+        console.open("/ti");
+        console.history_back();
+        console.history_back();
+        assert_eq!(console.input, "hello");
+        console.history_forward();
+        console.history_forward();
+        assert_eq!(console.input, "/ti");
+
+        // Tab goes round the suggestions without them being worked out again
+        console.suggestions = vec!["/time".to_string(), "/tp".to_string()];
+        console.suggested_for = Some(console.input.clone());
+        console.complete();
+        console.complete();
+        assert_eq!(console.input, "/tp");
+        assert!(!console.needs_suggestions());
+        console.complete();
+        assert_eq!(console.input, "/time");
+        console.type_char(' ');
+        assert!(console.needs_suggestions());
+
+        // Blank lines aren't sent
+        console.open("  ");
+        assert_eq!(console.submit(), None);
+        assert!(!console.open);
+    }
+}
@@ -0,0 +1,58 @@
+use bevy::prelude::*;
+use crate::crafting::{RecipeRegistry, MAX_GRID_SIZE};
+use super::inventory::{take_one, HotbarItem, ItemStack};
+
+/// The crafting table screen: which table is open and what's on its 3x3 grid
+/// Items on the grid belong to the player and go back to the hotbar when the screen closes
+#[derive(Resource, Default)]
+pub struct CraftingScreen {
+    pub table: Option<IVec3>,
+    pub grid: [Option<ItemStack>; MAX_GRID_SIZE * MAX_GRID_SIZE],
+}
+
+impl CraftingScreen {
+    pub fn is_open(&self) -> bool {
+        self.table.is_some()
+    }
+
+    /// The grid as recipes see it (one of each item per slot)
+    pub fn items(&self) -> [Option<HotbarItem>; MAX_GRID_SIZE * MAX_GRID_SIZE] {
+        self.grid.map(|slot| slot.map(|stack| stack.item))
+    }
+
+    /// What the grid currently crafts into
+    pub fn output(&self, recipes: &RecipeRegistry) -> Option<ItemStack> {
+        recipes.find_crafting(MAX_GRID_SIZE, &self.items()).map(|recipe| recipe.result)
+    }
+
+    /// Put one item from `held` on a slot if it's empty or holds the same item with room to spare
+    pub fn place_one(&mut self, index: usize, held: ItemStack) -> bool {
+        let one = ItemStack { count: 1, ..held };
+        match &mut self.grid[index] {
+            slot @ None => {
+                *slot = Some(one);
+                true
+            }
+            Some(stack) if stack.stacks_with(&one) && stack.space() > 0 => {
+                stack.count += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Craft once: use up one item from every occupied slot and return the result
+    pub fn craft(&mut self, recipes: &RecipeRegistry) -> Option<ItemStack> {
+        let result = self.output(recipes)?;
+        for slot in self.grid.iter_mut() {
+            take_one(slot);
+        }
+        Some(result)
+    }
+
+    /// Close the screen, handing back everything left on the grid
+    pub fn close(&mut self) -> Vec<ItemStack> {
+        self.table = None;
+        self.grid.iter_mut().filter_map(Option::take).collect()
+    }
+}
@@ -0,0 +1,14 @@
+use bevy::prelude::*;
+
+/// The furnace screen: which furnace is open
+/// The items stay in the furnace's block entity, so closing the screen moves nothing
+#[derive(Resource, Default)]
+pub struct FurnaceScreen {
+    pub furnace: Option<IVec3>,
+}
+
+impl FurnaceScreen {
+    pub fn is_open(&self) -> bool {
+        self.furnace.is_some()
+    }
+}
@@ -4,7 +4,18 @@ use bevy::prelude::*;
 pub struct GameState {
     pub paused: bool,
     pub ui_visible: bool,
-    pub creative_mode: bool,
+    /// The player died and the death screen is up (the game is paused until respawn)
+    pub dead: bool,
+    /// A block's screen (e.g. a crafting table) is up: the world keeps running,
+    /// but the mouse drives the UI instead of the player
+    pub screen_open: bool,
+}
+
+impl GameState {
+    /// Whether mouse look and block interaction should respond
+    pub fn accepts_gameplay_input(&self) -> bool {
+        !self.paused && !self.screen_open
+    }
 }
 
 impl Default for GameState {
@@ -12,14 +23,16 @@ impl Default for GameState {
         Self {
             paused: false,
             ui_visible: true,
-            creative_mode: true, // Start in creative mode for now
+            dead: false,
+            screen_open: false,
         }
     }
 }
 
 /// Player health and hunger stats for survival mode
+/// The resource is the local player's; a server's remote players carry their own as a component
 #[allow(dead_code)]
-#[derive(Resource)]
+#[derive(Resource, Component, Debug, Clone, PartialEq)]
 pub struct PlayerStats {
     /// Current health (0-20, like Minecraft's 10 hearts * 2 half-hearts)
     pub health: u8,
@@ -29,6 +42,18 @@ pub struct PlayerStats {
     pub hunger: u8,
     /// Maximum hunger
     pub max_hunger: u8,
+    /// Remaining air in ticks, drains while the head is in a liquid
+    pub air: u16,
+    /// Maximum air (15 seconds)
+    pub max_air: u16,
+    /// Ticks left during which further damage is ignored
+    pub invulnerable_ticks: u16,
+    /// Hidden buffer drained before hunger (never above the hunger level)
+    pub saturation: f32,
+    /// Accumulated effort; every 4.0 costs one point of saturation or hunger
+    pub exhaustion: f32,
+    /// Ticks counted towards the next regeneration or starvation step
+    pub food_timer: u16,
 }
 
 impl Default for PlayerStats {
@@ -38,10 +63,23 @@ impl Default for PlayerStats {
             max_health: 20,
             hunger: 20,
             max_hunger: 20,
+            air: 300,
+            max_air: 300,
+            invulnerable_ticks: 0,
+            saturation: 5.0,
+            exhaustion: 0.0,
+            food_timer: 0,
         }
     }
 }
 
+impl PlayerStats {
+    /// Record effort (sprinting, jumping, mining, getting hurt) for the hunger system
+    pub fn add_exhaustion(&mut self, amount: f32) {
+        self.exhaustion = (self.exhaustion + amount).min(40.0);
+    }
+}
+
 /// FPS tracking for debug overlay
 #[derive(Resource)]
 pub struct FpsStats {
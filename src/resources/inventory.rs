@@ -1,21 +1,6 @@
 use bevy::prelude::*;
 use crate::blocks::BlockId;
-
-/// Unique identifier for non-block items
-#[allow(dead_code)]
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub enum ItemId {
-    Torch,
-}
-
-impl ItemId {
-    /// Get the display name for this item
-    pub fn name(&self) -> &'static str {
-        match self {
-            ItemId::Torch => "Torch",
-        }
-    }
-}
+use crate::items::{ItemId, ItemRegistry};
 
 /// Represents something that can be held in the inventory
 #[allow(dead_code)]
@@ -25,9 +10,65 @@ pub enum HotbarItem {
     Item(ItemId),
 }
 
-#[derive(Resource)]
+/// Largest number of items a single slot can hold
+pub const MAX_STACK_SIZE: u8 = 64;
+
+/// A number of identical items in one slot
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ItemStack {
+    pub item: HotbarItem,
+    pub count: u8,
+    /// Uses left for tools, None for items that don't wear out
+    /// A stack with durability always holds exactly one item
+    pub durability: Option<u16>,
+}
+
+impl ItemStack {
+    pub fn new(item: HotbarItem, count: u8) -> Self {
+        Self { item, count: count.min(MAX_STACK_SIZE), durability: None }
+    }
+
+    pub fn one(item: HotbarItem) -> Self {
+        Self::new(item, 1)
+    }
+
+    /// A single tool with this many uses left
+    pub fn tool(item: HotbarItem, durability: u16) -> Self {
+        Self { item, count: 1, durability: Some(durability) }
+    }
+
+    /// Most items this stack can hold (tools don't stack)
+    pub fn max_count(&self) -> u8 {
+        if self.durability.is_some() { 1 } else { MAX_STACK_SIZE }
+    }
+
+    /// Room left before the stack is full
+    pub fn space(&self) -> u8 {
+        self.max_count().saturating_sub(self.count)
+    }
+
+    /// Whether items from `other` can be merged into this stack
+    pub fn stacks_with(&self, other: &ItemStack) -> bool {
+        self.item == other.item && self.durability.is_none() && other.durability.is_none()
+    }
+}
+
+/// Take one item off a slot, emptying it when the last one is used
+pub fn take_one(slot: &mut Option<ItemStack>) -> Option<HotbarItem> {
+    let stack = slot.as_mut()?;
+    let item = stack.item;
+    stack.count -= 1;
+    if stack.count == 0 {
+        *slot = None;
+    }
+    Some(item)
+}
+
+/// The local player's hotbar is a resource; on a server each client's player entity
+/// carries its own as a component
+#[derive(Resource, Component, Debug, Clone, PartialEq)]
 pub struct PlayerInventory {
-    pub hotbar: [Option<HotbarItem>; 9],
+    pub hotbar: [Option<ItemStack>; 9],
     pub selected_slot: usize, // 0-8
 }
 
@@ -43,11 +84,12 @@ impl Default for PlayerInventory {
 #[allow(dead_code)]
 impl PlayerInventory {
     pub fn new_with_blocks(bedrock: BlockId, stone: BlockId, dirt: BlockId, grass: BlockId) -> Self {
-        let mut hotbar: [Option<HotbarItem>; 9] = [None; 9];
-        hotbar[0] = Some(HotbarItem::Block(bedrock));  // Slot 1 (index 0)
-        hotbar[1] = Some(HotbarItem::Block(stone));    // Slot 2 (index 1)
-        hotbar[2] = Some(HotbarItem::Block(dirt));     // Slot 3 (index 2)
-        hotbar[3] = Some(HotbarItem::Block(grass));    // Slot 4 (index 3)
+        let full = |block| Some(ItemStack::new(HotbarItem::Block(block), MAX_STACK_SIZE));
+        let mut hotbar: [Option<ItemStack>; 9] = [None; 9];
+        hotbar[0] = full(bedrock);  // Slot 1 (index 0)
+        hotbar[1] = full(stone);    // Slot 2 (index 1)
+        hotbar[2] = full(dirt);     // Slot 3 (index 2)
+        hotbar[3] = full(grass);    // Slot 4 (index 3)
         // Torch implementation exists but is not added to hotbar until it's fully working
 
         Self {
@@ -56,9 +98,61 @@ impl PlayerInventory {
         }
     }
 
+    /// Put items into the hotbar, topping up matching stacks before using empty slots
+    /// Returns how many items didn't fit
+    pub fn add_stack(&mut self, stack: ItemStack) -> u8 {
+        let mut remaining = stack.count;
+
+        for existing in self.hotbar.iter_mut().flatten() {
+            if remaining == 0 {
+                break;
+            }
+            if existing.stacks_with(&stack) {
+                let moved = remaining.min(existing.space());
+                existing.count += moved;
+                remaining -= moved;
+            }
+        }
+
+        for slot in self.hotbar.iter_mut() {
+            if remaining == 0 {
+                break;
+            }
+            if slot.is_none() {
+                let moved = remaining.min(stack.max_count());
+                *slot = Some(ItemStack { count: moved, ..stack });
+                remaining -= moved;
+            }
+        }
+
+        remaining
+    }
+
+    /// Whether the whole stack would fit into the hotbar
+    pub fn can_fit(&self, stack: ItemStack) -> bool {
+        let room: u32 = self.hotbar.iter()
+            .map(|slot| match slot {
+                None => stack.max_count() as u32,
+                Some(existing) if existing.stacks_with(&stack) => existing.space() as u32,
+                Some(_) => 0,
+            })
+            .sum();
+        room >= stack.count as u32
+    }
+
+    /// Put a single picked-up item into the hotbar, returns false if there was no room
+    pub fn add_item(&mut self, item: HotbarItem) -> bool {
+        self.add_stack(ItemStack::one(item)) == 0
+    }
+
+    /// The item in the selected slot, if any
+    pub fn selected_item(&self) -> Option<HotbarItem> {
+        self.hotbar[self.selected_slot].map(|stack| stack.item)
+    }
+
     /// Get the selected item if it's a block
     pub fn get_selected_block(&self) -> Option<BlockId> {
-        match self.hotbar[self.selected_slot] {
+        match self.selected_item() {
             Some(HotbarItem::Block(id)) => Some(id),
             _ => None,
         }
@@ -66,15 +160,35 @@ impl PlayerInventory {
 
     /// Get the selected item if it's a non-block item
     pub fn get_selected_item(&self) -> Option<ItemId> {
-        match self.hotbar[self.selected_slot] {
+        match self.selected_item() {
             Some(HotbarItem::Item(id)) => Some(id),
             _ => None,
         }
     }
 
+    /// Use up one of the selected item (e.g., after eating or placing it)
+    pub fn consume_selected(&mut self) {
+        take_one(&mut self.hotbar[self.selected_slot]);
+    }
+
+    /// Wear down the selected tool by one use, removing it when it runs out
+    /// Returns true if the tool broke
+    pub fn wear_selected(&mut self) -> bool {
+        let slot = &mut self.hotbar[self.selected_slot];
+        let Some(durability) = slot.as_mut().and_then(|stack| stack.durability.as_mut()) else {
+            return false;
+        };
+        *durability = durability.saturating_sub(1);
+        if *durability == 0 {
+            *slot = None;
+            return true;
+        }
+        false
+    }
+
     /// Check if the torch is currently selected
-    pub fn is_torch_selected(&self) -> bool {
-        matches!(self.hotbar[self.selected_slot], Some(HotbarItem::Item(ItemId::Torch)))
+    pub fn is_torch_selected(&self, items: &ItemRegistry) -> bool {
+        items.get_id("core:torch").is_some_and(|torch| self.get_selected_item() == Some(torch))
     }
 
     pub fn select_slot(&mut self, slot: usize) {
@@ -88,3 +202,45 @@ impl PlayerInventory {
         self.selected_slot = new_slot as usize;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn add_stack_tops_up_before_using_empty_slots() {
+        let stone = HotbarItem::Block(BlockId(1));
+        let mut inventory = PlayerInventory::default();
+        inventory.hotbar[2] = Some(ItemStack::new(stone, 60));
+
+        assert_eq!(inventory.add_stack(ItemStack::new(stone, 10)), 0);
+        assert_eq!(inventory.hotbar[2], Some(ItemStack::new(stone, MAX_STACK_SIZE)));
+        assert_eq!(inventory.hotbar[0], Some(ItemStack::new(stone, 6)));
+    }
+
+    #[test]
+    fn full_hotbar_returns_the_leftover() {
+        let stone = HotbarItem::Block(BlockId(1));
+        let mut inventory = PlayerInventory {
+            hotbar: [Some(ItemStack::new(stone, MAX_STACK_SIZE - 1)); 9],
+            ..Default::default()
+        };
+
+        assert_eq!(inventory.add_stack(ItemStack::new(stone, 12)), 3);
+        assert!(!inventory.add_item(HotbarItem::Item(ItemId(0))));
+    }
+
+    #[test]
+    fn tools_take_a_slot_each_and_break_when_worn_out() {
+        let pickaxe = HotbarItem::Item(ItemId(3));
+        let mut inventory = PlayerInventory::default();
+        assert_eq!(inventory.add_stack(ItemStack::tool(pickaxe, 2)), 0);
+        assert_eq!(inventory.add_stack(ItemStack::tool(pickaxe, 2)), 0);
+        assert!(inventory.hotbar[1].is_some(), "tools never merge");
+
+        assert!(!inventory.wear_selected());
+        assert_eq!(inventory.hotbar[0].unwrap().durability, Some(1));
+        assert!(inventory.wear_selected());
+        assert!(inventory.hotbar[0].is_none());
+    }
+}
@@ -0,0 +1,27 @@
+use bevy::prelude::*;
+
+/// The block the player is holding the mouse on and how close it is to breaking
+#[derive(Resource, Default)]
+pub struct MiningProgress {
+    pub target: Option<IVec3>,
+    /// Fraction of the break time done, the block breaks at 1.0
+    pub progress: f32,
+}
+
+impl MiningProgress {
+    /// Add progress on a block, starting over if it's a different block than before
+    /// Returns true once the block is done
+    pub fn advance(&mut self, pos: IVec3, amount: f32) -> bool {
+        if self.target != Some(pos) {
+            self.target = Some(pos);
+            self.progress = 0.0;
+        }
+        self.progress += amount;
+        self.progress >= 1.0
+    }
+
+    pub fn reset(&mut self) {
+        self.target = None;
+        self.progress = 0.0;
+    }
+}
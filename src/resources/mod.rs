@@ -1,6 +1,14 @@
 mod game_state;
 mod inventory;
+mod crafting;
+mod furnace;
+mod mining;
+mod chat;
 
 pub use game_state::{GameState, ChunkBorderState, ChunkBorderMode, PlayerStats, FpsStats};
-pub use inventory::{PlayerInventory, HotbarItem};
+pub use inventory::{PlayerInventory, HotbarItem, ItemStack, MAX_STACK_SIZE, take_one};
+pub use crafting::CraftingScreen;
+pub use furnace::FurnaceScreen;
+pub use mining::MiningProgress;
+pub use chat::{ChatConsole, ChatLine, ChatLog};
 pub use crate::world::ChunkManager;
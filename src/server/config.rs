@@ -0,0 +1,144 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use crate::world::{AnvilImport, BlockMapping, WorldSettings};
+
+/// Port the server listens on when server.toml doesn't say
+pub const DEFAULT_PORT: u16 = 25565;
+
+/// Name of the file in the world directory that remembers the world's seed
+const LEVEL_FILE: &str = "level.toml";
+
+/// Dedicated server settings from server.toml; missing keys keep their defaults
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerConfig {
+    /// Seed for a new world (an existing world keeps the seed it was created with)
+    pub seed: u32,
+    /// Radius of the sphere of chunks loaded around each player
+    pub view_distance: i32,
+    pub port: u16,
+    /// Directory the world is kept in: its seed (level.toml), the spawn point and the chunks
+    /// that were edited or hold mobs. Untouched terrain is generated again from the seed
+    pub world_path: PathBuf,
+    /// Minecraft world directory to load chunks from instead of generating them
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub import_world: Option<PathBuf>,
+    /// RON file of extra Minecraft-to-block mappings for `import_world`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub block_mapping: Option<PathBuf>,
+    /// Names of the players allowed to run commands that change the world or game modes
+    pub operators: Vec<String>,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            seed: WorldSettings::default().seed,
+            view_distance: WorldSettings::default().view_distance,
+            port: DEFAULT_PORT,
+            world_path: PathBuf::from("world"),
+            import_world: None,
+            block_mapping: None,
+            operators: Vec::new(),
+        }
+    }
+}
+
+/// What the world directory remembers about the world itself
+#[derive(Debug, Serialize, Deserialize)]
+struct LevelInfo {
+    seed: u32,
+}
+
+impl ServerConfig {
+    pub fn parse(source: &str) -> Result<Self, String> {
+        let config: Self = toml::from_str(source).map_err(|error| format!("Invalid server config: {}", error))?;
+        if config.view_distance < 1 {
+            return Err(format!("Invalid server config: view_distance must be at least 1, got {}", config.view_distance));
+        }
+        Ok(config)
+    }
+
+    /// Read the config at `path`, writing one with the defaults first if there is none
+    pub fn load_or_create(path: &Path) -> Result<Self, String> {
+        if !path.exists() {
+            let config = Self::default();
+            let source = toml::to_string(&config).map_err(|error| error.to_string())?;
+            fs::write(path, source).map_err(|error| format!("Can't write {}: {}", path.display(), error))?;
+            return Ok(config);
+        }
+        let source = fs::read_to_string(path).map_err(|error| format!("Can't read {}: {}", path.display(), error))?;
+        Self::parse(&source)
+    }
+
+    /// The Minecraft world to import, if one is configured, with its block mapping loaded
+    pub fn anvil_import(&self) -> Result<Option<AnvilImport>, String> {
+        let Some(world) = &self.import_world else {
+            return Ok(None);
+        };
+        let mapping = match &self.block_mapping {
+            Some(path) => BlockMapping::load(path)?,
+            None => BlockMapping::default(),
+        };
+        Ok(Some(AnvilImport::new(world).with_mapping(mapping)))
+    }
+
+    /// Open the world directory, creating it (and recording the configured seed) for a new world,
+    /// and return the settings to run it with
+    /// Chunks and the spawn point are saved there by the simulation (see `WorldDirectory`)
+    pub fn open_world(&self) -> Result<WorldSettings, String> {
+        let level_path = self.world_path.join(LEVEL_FILE);
+        let level = if level_path.exists() {
+            let source = fs::read_to_string(&level_path).map_err(|error| format!("Can't read {}: {}", level_path.display(), error))?;
+            toml::from_str(&source).map_err(|error| format!("Invalid {}: {}", level_path.display(), error))?
+        } else {
+            let level = LevelInfo { seed: self.seed };
+            fs::create_dir_all(&self.world_path)
+                .map_err(|error| format!("Can't create {}: {}", self.world_path.display(), error))?;
+            let source = toml::to_string(&level).map_err(|error| error.to_string())?;
+            fs::write(&level_path, source).map_err(|error| format!("Can't write {}: {}", level_path.display(), error))?;
+            level
+        };
+
+        let defaults = WorldSettings::default();
+        Ok(WorldSettings {
+            seed: level.seed,
+            view_distance: self.view_distance,
+            view_distance_vertical: defaults.view_distance_vertical.min(self.view_distance),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_keys_keep_defaults_and_worlds_keep_their_seed() {
+        let config = ServerConfig::parse("seed = 7\nport = 4000\n").unwrap();
+        assert_eq!(config.seed, 7);
+        assert_eq!(config.port, 4000);
+        assert_eq!(config.view_distance, ServerConfig::default().view_distance);
+        assert!(ServerConfig::parse("view_distance = 0").is_err());
+        assert!(ServerConfig::parse("sed = 7").is_err(), "typos are reported");
+        assert!(config.operators.is_empty(), "nobody is opped by default");
+        assert_eq!(ServerConfig::parse("operators = [\"alice\"]").unwrap().operators, ["alice"]);
+        assert!(config.anvil_import().unwrap().is_none());
+        let import = ServerConfig::parse("import_world = \"minecraft\"").unwrap().anvil_import().unwrap().unwrap();
+        assert_eq!(import.world_path, PathBuf::from("minecraft"));
+
+        let world_path = std::env::temp_dir().join(format!("voxelcraft-config-test-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&world_path);
+        let config = ServerConfig { world_path: world_path.clone(), view_distance: 3, ..config };
+        let settings = config.open_world().unwrap();
+        assert_eq!(settings.seed, 7);
+        assert_eq!(settings.view_distance, 3);
+        assert!(settings.view_distance_vertical <= 3);
+
+        // Changing the configured seed doesn't regenerate an existing world differently
+        let reopened = ServerConfig { seed: 99, ..config }.open_world().unwrap();
+        assert_eq!(reopened.seed, 7);
+        fs::remove_dir_all(&world_path).unwrap();
+    }
+}
@@ -0,0 +1,101 @@
+use bevy::prelude::*;
+use crate::commands::{run_commands, CommandFeedback, RunCommand};
+use crate::mods::ModPlugin;
+use crate::resources::{GameState, PlayerInventory, PlayerStats};
+use crate::systems::*;
+use crate::world::{
+    setup_terrain, spawn_chunks_around_player, process_chunk_tasks, unload_distant_chunks,
+    load_world, write_saved_chunks, save_world_on_exit,
+    SavedChunks, SchematicDirectory, WorldSettings, WorldSpawn, WorldTick, ScheduledTicks, TICKS_PER_SECOND,
+};
+
+/// The game world without a window: registries, world generation, chunk loading and unloading,
+/// world ticks, mobs and player physics and survival
+/// The client adds rendering, input and UI on top; the dedicated server runs it under `MinimalPlugins`.
+/// Insert a `WorldSettings` before adding the plugin to change the seed or view distance
+pub struct SimulationPlugin;
+
+impl Plugin for SimulationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(ModPlugin)
+            .init_resource::<WorldSettings>()
+            .init_resource::<GameState>()
+            .init_resource::<PlayerStats>()
+            .init_resource::<PlayerInventory>()
+            .init_resource::<TimeOfDay>()
+            .init_resource::<SkyLightLevel>()
+            .init_resource::<WorldTick>()
+            .init_resource::<ScheduledTicks>()
+            .init_resource::<SavedChunks>()
+            .init_resource::<SchematicDirectory>()
+            .add_event::<WorldSpawn>()
+            .add_event::<PlayerDamage>()
+            .add_event::<DropItem>()
+            .add_event::<SpawnMob>()
+            .add_event::<OpenDoor>()
+            .add_event::<ChunkLightChanged>()
+            .add_event::<BlockEditRequest>()
+            .add_event::<BlockChanged>()
+            .add_event::<RunCommand>()
+            .add_event::<CommandFeedback>()
+            // World simulation runs at a fixed 20 TPS, independent of frame rate
+            .insert_resource(Time::<Fixed>::from_hz(TICKS_PER_SECOND))
+            // Terrain setup runs first so the spawn point exists when players are spawned
+            .add_systems(Startup, setup_terrain)
+            // With a `WorldDirectory`, the saved world is read back once the mods have registered
+            // everything it names
+            .add_systems(PostStartup, load_world)
+            // Chunk loading and the entities that come and go with the world
+            .add_systems(Update, (
+                spawn_chunks_around_player.run_if(|state: Res<GameState>| !state.paused),
+                process_chunk_tasks,
+                post_process_generated_chunks.after(process_chunk_tasks),
+                spawn_passive_mobs_in_new_chunks.after(process_chunk_tasks).before(post_process_generated_chunks),
+                spawn_falling_blocks,
+                spawn_item_drops,
+                spawn_mobs,
+                apply_block_edits.run_if(|state: Res<GameState>| !state.paused),
+                update_falling_blocks.run_if(|state: Res<GameState>| !state.paused),
+                update_item_drops.run_if(|state: Res<GameState>| !state.paused),
+                update_bed_spawn,
+                update_time_of_day,
+                update_sky_light_level,
+            ))
+            // Chat commands get the whole world to work on
+            .add_systems(Update, run_commands)
+            // Physics (fixed timestep; transforms follow the bodies, interpolated between steps)
+            .add_systems(FixedPreUpdate, crate::physics::begin_physics_step)
+            .add_systems(Update, crate::physics::interpolate_physics_bodies)
+            .add_systems(FixedUpdate, player_physics.run_if(|state: Res<GameState>| !state.paused))
+            .add_systems(FixedUpdate, (
+                update_mobs,
+                open_doors_for_mobs,
+            ).chain().after(player_physics).run_if(|state: Res<GameState>| !state.paused))
+            // Player damage and hunger (fixed timestep, after movement so landings are seen the same tick)
+            .add_systems(FixedUpdate, (
+                update_environment_damage,
+                update_hunger,
+                apply_player_damage,
+                respawn_dead_players,
+            ).chain().after(player_physics).run_if(|state: Res<GameState>| !state.paused))
+            // World tick systems (fixed timestep)
+            .add_systems(FixedUpdate, (
+                advance_world_tick,
+                update_block_contacts,
+                process_scheduled_ticks,
+                random_tick_chunks,
+                tick_furnaces,
+                spawn_hostile_mobs,
+                despawn_distant_mobs,
+            ).chain().run_if(|state: Res<GameState>| !state.paused))
+            // Edited and newly loaded chunks are relit, then chunks far from every player
+            // unload, taking their mobs along, and are written to the world directory
+            .add_systems(PostUpdate, (
+                relight_chunks,
+                unload_distant_chunks,
+                save_mobs_in_unloaded_chunks,
+                write_saved_chunks,
+            ).chain())
+            .add_systems(Last, save_world_on_exit);
+    }
+}
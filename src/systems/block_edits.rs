@@ -0,0 +1,228 @@
+use bevy::prelude::*;
+use std::collections::HashMap;
+use crate::components::{GameMode, MobData, Player, PLAYER_HALF_WIDTH, PLAYER_HEIGHT};
+use crate::entities::EntityRegistry;
+use crate::items::{break_seconds, can_harvest, new_stack, resolve_item, stack_tool, ItemRegistry};
+use crate::physics::{CollisionBox, PhysicsBody};
+use crate::resources::{HotbarItem, PlayerInventory, PlayerStats};
+use crate::commands::CommandFeedback;
+use crate::world::{Selection, SetBlockFlags, SELECTION_WAND};
+use crate::blocks::BlockState;
+use super::hunger::MINING_EXHAUSTION;
+use super::{DropItem, SpawnMob, WorldBlocks};
+
+/// How far a player can reach to break, use or place blocks
+pub const BLOCK_REACH: f32 = 8.0;
+
+/// Slack on top of the reach when checking requests, for a player that moved since aiming
+const REACH_TOLERANCE: f32 = 2.0;
+
+/// Slack on a block's break time when checking a break, for packets bunching up on the way
+const BREAK_TIME_TOLERANCE: f32 = 0.2;
+
+/// What a player asks to do to a block
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockAction {
+    /// Start mining the block at `pos`; the break that follows has to wait out its break time
+    StartBreak { pos: IVec3 },
+    /// Break the block at `pos` (mining finished, or an instant-break click)
+    Break { pos: IVec3 },
+    /// Right-click the `face` of the block at `pos` with the selected hotbar item:
+    /// use the block, or else spawn the held egg's mob or place the held block against that face
+    UseOn { pos: IVec3, face: IVec3 },
+}
+
+/// A player's request to change the world
+/// `block_interaction` sends these for the local player and servers for their clients;
+/// `apply_block_edits` is the only place they reach the world, so the same rules hold for both
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockEditRequest {
+    pub player: Entity,
+    pub action: BlockAction,
+}
+
+/// Apply players' block edit requests after checking their game mode, reach and held items
+/// Players with their own `PlayerInventory` and `PlayerStats` components (a server's clients)
+/// use and wear the items in it and tire themselves, everyone else the resources. Break times are timed
+/// here from each player's `StartBreak`, so a break that comes too soon is ignored
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub fn apply_block_edits(
+    mut requests: EventReader<BlockEditRequest>,
+    mut world_blocks: WorldBlocks,
+    mut players: Query<(&Transform, &GameMode, Option<&PhysicsBody>, Option<&mut PlayerInventory>, Option<&mut Selection>, Option<&mut PlayerStats>), With<Player>>,
+    mut local_inventory: ResMut<PlayerInventory>,
+    mut local_stats: ResMut<PlayerStats>,
+    item_registry: Res<ItemRegistry>,
+    entity_registry: Res<EntityRegistry>,
+    mut drops: EventWriter<DropItem>,
+    mut mobs: EventWriter<SpawnMob>,
+    mut feedback: EventWriter<CommandFeedback>,
+    time: Res<Time>,
+    mut mining_since: Local<HashMap<Entity, (IVec3, f32)>>,
+) {
+    mining_since.retain(|&player, _| players.contains(player));
+    let now = time.elapsed_secs();
+    for request in requests.read() {
+        let Ok((transform, game_mode, body, own_inventory, selection, own_stats)) = players.get_mut(request.player) else {
+            continue;
+        };
+        // Spectators can't touch the world at all
+        if !game_mode.can_interact() {
+            continue;
+        }
+        let abilities = game_mode.abilities();
+        let mut inventory = match own_inventory {
+            Some(inventory) => inventory,
+            None => local_inventory.reborrow(),
+        };
+        let mut stats = match own_stats {
+            Some(stats) => stats,
+            None => local_stats.reborrow(),
+        };
+
+        let target = match request.action {
+            BlockAction::StartBreak { pos } | BlockAction::Break { pos } | BlockAction::UseOn { pos, .. } => pos,
+        };
+        let eye = Player::eye_position(transform);
+        if eye.distance(target.as_vec3() + Vec3::splat(0.5)) > BLOCK_REACH + REACH_TOLERANCE {
+            continue;
+        }
+
+        // In creative the selection wand marks corners instead of breaking or placing
+        let holds_wand = inventory.get_selected_item().is_some_and(|item| item_registry.get_id(SELECTION_WAND) == Some(item));
+        if let Some(mut selection) = selection.filter(|_| *game_mode == GameMode::Creative && holds_wand) {
+            let (corner, name) = match request.action {
+                BlockAction::StartBreak { .. } => continue,
+                BlockAction::Break { .. } => (&mut selection.first, "First"),
+                BlockAction::UseOn { .. } => (&mut selection.second, "Second"),
+            };
+            *corner = Some(target);
+            let volume = selection.volume();
+            let message = match volume {
+                0 => format!("{} corner set to {}, {}, {}", name, target.x, target.y, target.z),
+                _ => format!("{} corner set to {}, {}, {} ({} blocks)", name, target.x, target.y, target.z, volume),
+            };
+            feedback.send(CommandFeedback { player: request.player, message, error: false });
+            continue;
+        }
+
+        match request.action {
+            BlockAction::StartBreak { pos } => {
+                mining_since.insert(request.player, (pos, now));
+            }
+            BlockAction::Break { pos } => {
+                if !abilities.can_build {
+                    continue;
+                }
+                let Some(state) = world_blocks.get_state(pos).filter(|state| !state.is_air()) else {
+                    continue;
+                };
+                let Some(block_type) = world_blocks.registry().get_block(state.id) else {
+                    continue;
+                };
+
+                // Instant-break abilities skip drops and tool wear
+                if abilities.instant_break {
+                    break_block(&mut world_blocks, request.player, pos, state);
+                    continue;
+                }
+
+                let tool = stack_tool(inventory.hotbar[inventory.selected_slot], &item_registry);
+                let Some(seconds) = break_seconds(block_type, tool) else {
+                    continue;
+                };
+                // Only as long after starting on this block as it takes to mine
+                let mined = mining_since.get(&request.player)
+                    .filter(|(start, _)| *start == pos)
+                    .map_or(0.0, |(_, since)| now - since);
+                if mined + BREAK_TIME_TOLERANCE < seconds {
+                    continue;
+                }
+                mining_since.remove(&request.player);
+                // Work out the drop before the block is gone
+                let wears_tool = tool.is_some() && block_type.properties.hardness > 0.0;
+                let drop = match &block_type.properties.drops {
+                    _ if !can_harvest(block_type, tool) => None,
+                    Some(id) => resolve_item(id, &item_registry, world_blocks.registry()).ok(),
+                    None => Some(HotbarItem::Block(state.id)),
+                };
+
+                if break_block(&mut world_blocks, request.player, pos, state) {
+                    stats.add_exhaustion(MINING_EXHAUSTION);
+                    if let Some(item) = drop {
+                        let pos = pos.as_vec3() + Vec3::splat(0.5);
+                        drops.send(DropItem { pos, stack: new_stack(item, 1, &item_registry) });
+                    }
+                    if wears_tool && inventory.wear_selected() {
+                        info!("Tool broke!");
+                    }
+                }
+            }
+            BlockAction::UseOn { pos, face } => {
+                // Usable blocks (levers, buttons, doors) take the click instead of having a block placed on them
+                if world_blocks.edit_as(request.player, |world| world.use_block(pos)) {
+                    continue;
+                }
+                if !abilities.can_build || face.abs().element_sum() != 1 {
+                    continue;
+                }
+                let placement_pos = pos + face;
+
+                // Spawn eggs put their mob on the clicked face instead of placing anything
+                let spawn_egg = inventory.get_selected_item()
+                    .and_then(|item| item_registry.get_item(item)?.properties.spawns.as_deref())
+                    .and_then(|id| entity_registry.get_id(id));
+                if let Some(entity_type) = spawn_egg {
+                    let position = placement_pos.as_vec3() + Vec3::new(0.5, 0.0, 0.5);
+                    mobs.send(SpawnMob(MobData::new(entity_type, position, &entity_registry)));
+                    if !abilities.instant_break {
+                        inventory.consume_selected();
+                    }
+                    continue;
+                }
+
+                let Some(selected_block) = inventory.get_selected_block() else {
+                    continue;
+                };
+
+                // Check if player would be placing block inside themselves
+                let player_box = body.map_or_else(
+                    || CollisionBox::from_feet(transform.translation, PLAYER_HALF_WIDTH, PLAYER_HEIGHT),
+                    |body| body.collision_box(),
+                );
+                if player_box.intersects(&CollisionBox::block(placement_pos)) {
+                    info!("Cannot place block inside player!");
+                    continue;
+                }
+
+                // Only place if the target position is loaded and air
+                if world_blocks.get_state(placement_pos).is_some_and(|state| state.is_air()) {
+                    let placed = world_blocks.edit_as(request.player, |world| {
+                        let state = world.placement_state(selected_block, face);
+                        world.set_block(placement_pos, state, SetBlockFlags::DEFAULT)
+                    });
+
+                    if placed {
+                        // Creative building doesn't use up the stack
+                        if !abilities.instant_break {
+                            inventory.consume_selected();
+                        }
+                        info!("Placed block {:?} at {:?}", selected_block, placement_pos);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Remove a block through the world API as `player`, returns whether it was removed
+fn break_block(world_blocks: &mut WorldBlocks, player: Entity, pos: IVec3, state: BlockState) -> bool {
+    let destroyed = world_blocks.edit_as(player, |world| world.set_block(pos, BlockState::AIR, SetBlockFlags::DEFAULT));
+
+    if destroyed {
+        if let Some(block_type) = world_blocks.registry().get_block(state.id) {
+            info!("Destroyed {} at {:?}", block_type.properties.id, pos);
+        }
+    }
+    destroyed
+}
@@ -1,11 +1,14 @@
 use bevy::prelude::*;
-use crate::components::{CameraController, BlockHighlight};
-use crate::resources::ChunkManager;
+use crate::components::{Player, BlockHighlight};
+use crate::resources::{ChunkManager, MiningProgress};
 use crate::world::{ChunkCoord, Chunk, CHUNK_SIZE};
 
+/// The outline darkens as the block under it is mined
+#[allow(clippy::too_many_arguments)]
 pub fn update_block_highlight(
     mut commands: Commands,
-    camera_query: Query<(&Transform, &CameraController), With<CameraController>>,
+    mining: Res<MiningProgress>,
+    player_query: Query<(&Transform, &Player)>,
     existing_highlights: Query<Entity, With<BlockHighlight>>,
     chunk_manager: Res<ChunkManager>,
     chunks: Query<&Chunk>,
@@ -17,22 +20,23 @@ pub fn update_block_highlight(
         commands.entity(entity).despawn();
     }
 
-    let Ok((camera_transform, _controller)) = camera_query.get_single() else {
+    let Ok((player_transform, player)) = player_query.get_single() else {
         return;
     };
 
-    // Raycast from camera to find which block we're looking at
-    let ray_origin = camera_transform.translation;
-    let ray_direction = camera_transform.forward();
+    // Raycast from the player's eyes to find which block they're looking at
+    let ray_origin = Player::eye_position(player_transform);
+    let ray_direction = player.look_direction();
     let max_distance = 10.0;
 
-    if let Some(hit_pos) = raycast_terrain(ray_origin, *ray_direction, max_distance, &chunk_manager, &chunks) {
+    if let Some(hit_pos) = raycast_terrain(ray_origin, ray_direction, max_distance, &chunk_manager, &chunks) {
         // Create wireframe box around the block
         let mesh = create_block_highlight_mesh();
         let mesh_handle = meshes.add(mesh);
 
+        let progress = if mining.target == Some(hit_pos.as_ivec3()) { mining.progress.clamp(0.0, 1.0) } else { 0.0 };
         let material = materials.add(StandardMaterial {
-            base_color: Color::srgba(0.0, 0.0, 0.0, 0.4), // Black with transparency
+            base_color: Color::srgba(0.0, 0.0, 0.0, 0.4 + 0.55 * progress), // Black with transparency
             unlit: true,
             alpha_mode: AlphaMode::Blend,
             ..default()
@@ -0,0 +1,253 @@
+use bevy::prelude::*;
+use bevy::ecs::system::SystemParam;
+use std::collections::HashSet;
+use crate::blocks::{BlockRegistry, BlockState};
+use crate::world::{
+    BlockEntity, Chunk, ChunkCoord, ChunkManager, ChunkModified, ChunkStorage, BlockWorld, EditHistory, EditTransaction, WorldTick, ScheduledTicks, WorldRng,
+    CHUNK_SIZE, world_to_chunk_local,
+};
+use crate::systems::{NeedsRelight, NeedsRemesh};
+
+/// Marker for freshly generated chunks whose blocks haven't had their placement callbacks run
+#[derive(Component)]
+pub struct NeedsPostProcess;
+
+/// A block in a loaded chunk changed through `WorldBlocks::edit` (sent so servers can forward it)
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockChanged(pub IVec3);
+
+/// System parameter bundling everything needed to edit the live world through `BlockWorld`
+/// Systems that change blocks take this instead of writing `Chunk`s directly, so that
+/// block callbacks, neighbor updates and remesh marking all happen in one place
+#[derive(SystemParam)]
+pub struct WorldBlocks<'w, 's> {
+    commands: Commands<'w, 's>,
+    chunk_manager: Res<'w, ChunkManager>,
+    chunks: Query<'w, 's, &'static mut Chunk>,
+    registry: Res<'w, BlockRegistry>,
+    scheduled_ticks: ResMut<'w, ScheduledTicks>,
+    rng: ResMut<'w, WorldRng>,
+    world_tick: Res<'w, WorldTick>,
+    histories: Query<'w, 's, &'static mut EditHistory>,
+}
+
+impl<'w, 's> WorldBlocks<'w, 's> {
+    /// Run `f` against the live world, then mark every chunk it changed for remeshing,
+    /// send a `BlockChanged` event per changed block and any entity spawns it requested
+    /// as `WorldSpawn` events
+    pub fn edit<R>(&mut self, f: impl FnOnce(&mut BlockWorld) -> R) -> R {
+        self.edit_recorded(f).0
+    }
+
+    /// Like `edit`, recording what changed as one transaction in `player`'s `EditHistory`
+    /// (if they have one) so they can undo it
+    pub fn edit_as<R>(&mut self, player: Entity, f: impl FnOnce(&mut BlockWorld) -> R) -> R {
+        let (result, transaction) = self.edit_recorded(f);
+        if let Ok(mut history) = self.histories.get_mut(player) {
+            history.record(transaction);
+        }
+        result
+    }
+
+    fn edit_recorded<R>(&mut self, f: impl FnOnce(&mut BlockWorld) -> R) -> (R, EditTransaction) {
+        let mut storage = ChunkStorage { chunk_manager: &self.chunk_manager, chunks: &mut self.chunks };
+        let mut world = BlockWorld::new(
+            &mut storage,
+            &self.registry,
+            &mut self.scheduled_ticks,
+            &mut self.rng.0,
+            self.world_tick.tick,
+        );
+
+        let result = f(&mut world);
+
+        let changed = world.take_changed();
+        let spawns = world.take_spawns();
+        let transaction = world.take_deltas();
+        mark_positions_for_remesh(&mut self.commands, &self.chunk_manager, &changed);
+        mark_chunks_modified(&mut self.commands, &self.chunk_manager, &changed);
+        for &pos in &changed {
+            self.commands.send_event(BlockChanged(pos));
+        }
+        for spawn in spawns {
+            self.commands.send_event(spawn);
+        }
+        (result, transaction)
+    }
+
+    /// Get the block state at a world position (None if not loaded)
+    pub fn get_state(&self, pos: IVec3) -> Option<BlockState> {
+        let (coord, local) = world_to_chunk_local(pos);
+        let entity = self.chunk_manager.loaded_chunks.get(&coord)?;
+        let chunk = self.chunks.get(*entity).ok()?;
+        Some(chunk.get_state(local.x as usize, local.y as usize, local.z as usize))
+    }
+
+    /// Get the block entity at a world position (None if there isn't one or it's not loaded)
+    pub fn block_entity(&self, pos: IVec3) -> Option<&BlockEntity> {
+        let (coord, local) = world_to_chunk_local(pos);
+        let entity = self.chunk_manager.loaded_chunks.get(&coord)?;
+        self.chunks.get(*entity).ok()?.block_entity(local.x as usize, local.y as usize, local.z as usize)
+    }
+
+    /// Mutable block entity access for systems that only change block entity state
+    /// (UI slot clicks) - changes that touch blocks go through `edit`
+    pub fn block_entity_mut(&mut self, pos: IVec3) -> Option<&mut BlockEntity> {
+        let (coord, local) = world_to_chunk_local(pos);
+        let entity = self.chunk_manager.loaded_chunks.get(&coord)?;
+        self.chunks.get_mut(*entity).ok()?.into_inner()
+            .block_entity_mut(local.x as usize, local.y as usize, local.z as usize)
+    }
+
+    /// World positions of every block entity in the loaded chunks
+    pub fn block_entity_positions(&self) -> Vec<IVec3> {
+        self.chunks.iter()
+            .flat_map(|chunk| {
+                let origin = IVec3::new(chunk.coord.x, chunk.coord.y, chunk.coord.z) * CHUNK_SIZE as i32;
+                chunk.block_entity_positions().map(move |local| origin + local.as_ivec3())
+            })
+            .collect()
+    }
+
+    /// Read-only access to the chunk entities (e.g., for raycasts)
+    pub fn chunks(&self) -> &Query<'w, 's, &'static mut Chunk> {
+        &self.chunks
+    }
+
+    pub fn chunk_manager(&self) -> &ChunkManager {
+        &self.chunk_manager
+    }
+
+    pub fn registry(&self) -> &BlockRegistry {
+        &self.registry
+    }
+
+    pub fn commands(&mut self) -> &mut Commands<'w, 's> {
+        &mut self.commands
+    }
+}
+
+/// Worldgen post-processing: run placement callbacks for the blocks of newly generated chunks
+/// Generation writes chunks directly on a background task, so blocks with behaviors get
+/// their `on_placed` hook here, through the same mutation API as player edits. Chunks whose
+/// palette has no block with a behavior are skipped without looking at their blocks; gravity
+/// blocks are left alone, so generated sand over a cave only falls once something disturbs it
+pub fn post_process_generated_chunks(
+    mut world_blocks: WorldBlocks,
+    new_chunks: Query<Entity, With<NeedsPostProcess>>,
+) {
+    let mut placed: Vec<IVec3> = Vec::new();
+
+    for entity in new_chunks.iter() {
+        world_blocks.commands().entity(entity).remove::<NeedsPostProcess>();
+
+        let Ok(chunk) = world_blocks.chunks().get(entity) else {
+            continue;
+        };
+        let registry = world_blocks.registry();
+        let has_behavior = |state: BlockState| !state.id.is_air()
+            && registry.get_block(state.id).is_some_and(|block| block.properties.behavior.is_some());
+        if !chunk.blocks.any_state(has_behavior) {
+            continue;
+        }
+
+        let origin = IVec3::new(chunk.coord.x, chunk.coord.y, chunk.coord.z) * CHUNK_SIZE as i32;
+        for y in 0..CHUNK_SIZE {
+            for z in 0..CHUNK_SIZE {
+                for x in 0..CHUNK_SIZE {
+                    if has_behavior(chunk.get_state(x, y, z)) {
+                        placed.push(origin + IVec3::new(x as i32, y as i32, z as i32));
+                    }
+                }
+            }
+        }
+    }
+
+    if placed.is_empty() {
+        return;
+    }
+
+    world_blocks.edit(|world| {
+        for pos in placed {
+            world.fire_placed(pos);
+        }
+    });
+}
+
+/// Flag the chunks holding `changed` so they're saved rather than regenerated when they unload
+fn mark_chunks_modified(commands: &mut Commands, chunk_manager: &ChunkManager, changed: &HashSet<IVec3>) {
+    let coords: HashSet<_> = changed.iter().map(|&pos| world_to_chunk_local(pos).0).collect();
+    for coord in coords {
+        if let Some(&entity) = chunk_manager.loaded_chunks.get(&coord) {
+            commands.entity(entity).insert(ChunkModified);
+        }
+    }
+}
+
+/// Mark the chunks containing changed blocks for remeshing
+/// Chunks bordering a changed block are marked too, so their faces and light stay correct.
+/// Each chunk is marked once no matter how many of its blocks changed, and only blocks on a
+/// chunk's edge look past it, so region edits of many thousands of blocks stay cheap
+pub(crate) fn mark_positions_for_remesh(
+    commands: &mut Commands,
+    chunk_manager: &ChunkManager,
+    changed: &HashSet<IVec3>,
+) {
+    let last = CHUNK_SIZE as u32 - 1;
+    let mut dirty = HashSet::new();
+    for &pos in changed {
+        let (coord, local) = world_to_chunk_local(pos);
+        dirty.insert(coord);
+        // The chunk across each face this block touches
+        for axis in 0..3 {
+            let step = match local[axis] {
+                0 => -1,
+                l if l == last => 1,
+                _ => continue,
+            };
+            let mut neighbor = IVec3::new(coord.x, coord.y, coord.z);
+            neighbor[axis] += step;
+            dirty.insert(ChunkCoord::new(neighbor.x, neighbor.y, neighbor.z));
+        }
+    }
+
+    for coord in dirty {
+        if let Some(&entity) = chunk_manager.loaded_chunks.get(&coord) {
+            commands.entity(entity).insert((NeedsRelight, NeedsRemesh));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::ecs::system::RunSystemOnce;
+
+    #[test]
+    fn region_edits_mark_each_touched_chunk_once() {
+        let mut world = World::new();
+        let mut chunk_manager = ChunkManager::default();
+        for x in -1..=2 {
+            for y in -1..=1 {
+                for z in -1..=1 {
+                    let coord = ChunkCoord::new(x, y, z);
+                    chunk_manager.loaded_chunks.insert(coord, world.spawn_empty().id());
+                }
+            }
+        }
+
+        // A slab across the border between two chunks, touching the top of the chunks below it
+        let changed: HashSet<IVec3> = (10..20)
+            .flat_map(|x| (0..3).flat_map(move |y| (2..6).map(move |z| IVec3::new(x, y, z))))
+            .collect();
+        let loaded = chunk_manager.loaded_chunks.clone();
+        world.run_system_once(move |mut commands: Commands| mark_positions_for_remesh(&mut commands, &chunk_manager, &changed)).unwrap();
+
+        let mut marked: Vec<ChunkCoord> = loaded.iter()
+            .filter(|(_, &entity)| world.get::<NeedsRemesh>(entity).is_some() && world.get::<NeedsRelight>(entity).is_some())
+            .map(|(&coord, _)| coord)
+            .collect();
+        marked.sort_by_key(|coord| (coord.x, coord.y, coord.z));
+        assert_eq!(marked, [ChunkCoord::new(0, -1, 0), ChunkCoord::new(0, 0, 0), ChunkCoord::new(1, -1, 0), ChunkCoord::new(1, 0, 0)]);
+    }
+}
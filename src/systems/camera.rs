@@ -1,8 +1,13 @@
 use bevy::prelude::*;
 use bevy::input::mouse::MouseMotion;
 use bevy::window::{PrimaryWindow, CursorGrabMode, WindowFocused};
-use crate::components::CameraController;
-use crate::world::{Chunk, ChunkCoord, CHUNK_SIZE};
+use crate::components::{CameraController, CameraMode, GameMode, Player, PlayerModel};
+use crate::net::RemotePlayer;
+use crate::physics::{step_player, ChunkColliders, CollisionBox, CollisionWorld, PhysicsBody, PlayerStep};
+use crate::resources::PlayerStats;
+use crate::systems::{fall_damage, DamageSource, PlayerDamage};
+use super::hunger::{JUMP_EXHAUSTION, SPRINT_EXHAUSTION_PER_BLOCK, SPRINT_HUNGER, SPRINT_JUMP_EXHAUSTION};
+use crate::world::{Chunk, ChunkCoord};
 use crate::resources::ChunkManager;
 use crate::blocks::BlockRegistry;
 use crate::resources::GameState;
@@ -24,8 +29,8 @@ pub fn handle_window_focus(
     // Handle focus events
     for event in focus_events.read() {
         if event.focused {
-            // Window gained focus - re-grab cursor if game is not paused
-            if !game_state.paused {
+            // Window gained focus - re-grab cursor if no menu or screen is up
+            if game_state.accepts_gameplay_input() {
                 if let Ok(mut window) = windows.get_single_mut() {
                     window.cursor_options.grab_mode = CursorGrabMode::Locked;
                     window.cursor_options.visible = false;
@@ -35,7 +40,7 @@ pub fn handle_window_focus(
     }
 
     // Also re-grab on any mouse click when not paused (fallback for alt-tab issues)
-    if !game_state.paused && (mouse_button.just_pressed(MouseButton::Left) || mouse_button.just_pressed(MouseButton::Right)) {
+    if game_state.accepts_gameplay_input() && (mouse_button.just_pressed(MouseButton::Left) || mouse_button.just_pressed(MouseButton::Right)) {
         if let Ok(mut window) = windows.get_single_mut() {
             if window.cursor_options.grab_mode != CursorGrabMode::Locked {
                 window.cursor_options.grab_mode = CursorGrabMode::Locked;
@@ -45,42 +50,71 @@ pub fn handle_window_focus(
     }
 }
 
+/// Read the movement keys into the player
+/// The actual movement happens in `player_physics` on the fixed timestep
 pub fn camera_movement_controls(
-    mut query: Query<(&mut Transform, &mut CameraController)>,
+    mut query: Query<(&mut Player, &mut PhysicsBody, &GameMode)>,
     keyboard_input: Res<ButtonInput<KeyCode>>,
     time: Res<Time>,
-    chunk_manager: Res<ChunkManager>,
-    chunks: Query<&Chunk>,
-    block_registry: Res<BlockRegistry>,
+    stats: Res<PlayerStats>,
+    game_state: Res<GameState>,
 ) {
-    let dt = time.delta_secs();
     let current_time = time.elapsed_secs();
 
-    for (mut transform, mut controller) in query.iter_mut() {
+    for (mut player, mut body, game_mode) in query.iter_mut() {
+        // Stand still while a screen has the input
+        if game_state.screen_open {
+            player.move_input = Vec3::ZERO;
+            player.fly_input = 0.0;
+            player.jump_requested = false;
+            continue;
+        }
+
+        let abilities = game_mode.abilities();
+        // Noclip always flies, and switching to a mode without flight drops the player
+        if abilities.noclip {
+            player.is_flying = true;
+        } else if !abilities.can_fly && player.is_flying {
+            player.is_flying = false;
+        }
+
+        // Holding space jumps on the next physics step (only if grounded then), or swims up in a liquid
+        if keyboard_input.pressed(KeyCode::Space) && !player.is_flying {
+            player.jump_requested = true;
+        }
+
         // Check for double-tap space to toggle fly mode
         if keyboard_input.just_pressed(KeyCode::Space) {
-            let time_since_last_press = current_time - controller.last_space_press;
-            if time_since_last_press < 0.3 {
+            let time_since_last_press = current_time - player.last_space_press;
+            if time_since_last_press < 0.3 && abilities.can_fly && !abilities.noclip {
                 // Double-tap detected - toggle fly mode
-                controller.is_flying = !controller.is_flying;
-                controller.velocity_y = 0.0;
-            } else if !controller.is_flying && controller.is_grounded {
-                // Single tap - jump (only if grounded and not flying)
-                controller.velocity_y = controller.jump_force;
+                player.is_flying = !player.is_flying;
+                body.velocity.y = 0.0;
+                player.jump_requested = false;
             }
-            controller.last_space_press = current_time;
+            player.last_space_press = current_time;
         }
 
-        // Calculate movement speed (with sprint)
-        let mut speed = controller.move_speed;
-        if keyboard_input.pressed(KeyCode::ControlLeft) {
-            speed *= controller.sprint_multiplier;
+        let shift = keyboard_input.pressed(KeyCode::ShiftLeft) || keyboard_input.pressed(KeyCode::ShiftRight);
+        player.is_sneaking = shift && !player.is_flying;
+
+        // Too hungry to sprint in survival
+        let can_sprint = abilities.invulnerable || player.is_flying || stats.hunger > SPRINT_HUNGER;
+        player.is_sprinting = keyboard_input.pressed(KeyCode::ControlLeft) && can_sprint && !player.is_sneaking;
+
+        // Calculate movement speed (with sprint, or slowed while sneaking)
+        let mut speed = player.move_speed;
+        if player.is_sprinting {
+            speed *= player.sprint_multiplier;
+        } else if player.is_sneaking {
+            speed *= player.sneak_multiplier;
         }
 
-        // Calculate horizontal velocity
+        // Calculate horizontal velocity relative to where the player faces
         let mut velocity = Vec3::ZERO;
-        let forward = Vec3::new(transform.forward().x, 0.0, transform.forward().z).normalize_or_zero();
-        let right = Vec3::new(transform.right().x, 0.0, transform.right().z).normalize_or_zero();
+        let facing = Quat::from_rotation_y(player.yaw);
+        let forward = facing * Vec3::NEG_Z;
+        let right = facing * Vec3::X;
 
         // WASD movement
         if keyboard_input.pressed(KeyCode::KeyW) {
@@ -96,268 +130,168 @@ pub fn camera_movement_controls(
             velocity += right;
         }
 
-        // Normalize horizontal movement
-        if velocity.length() > 0.0 {
-            velocity = velocity.normalize();
-        }
-
-        // Apply horizontal movement
-        let horizontal_delta = velocity * speed * dt;
-
-        // Player AABB dimensions
-        const PLAYER_WIDTH: f32 = 0.6;
-        const PLAYER_HALF_WIDTH: f32 = PLAYER_WIDTH / 2.0;
-        const PLAYER_HEIGHT: f32 = 1.8;
-        const PLAYER_EYE_HEIGHT: f32 = 1.6; // Camera is 1.6 blocks above feet
-
-        if controller.is_flying {
-            // Flying mode - free vertical movement with collision detection
-            const EPSILON: f32 = 0.01; // Small value to prevent collision with block we're standing on
+        player.move_input = velocity.normalize_or_zero() * speed;
 
-            // Try vertical movement with collision check
-            let mut new_y = transform.translation.y;
-            if keyboard_input.pressed(KeyCode::Space) {
-                new_y += speed * dt;
-            }
-            if keyboard_input.pressed(KeyCode::ShiftLeft) || keyboard_input.pressed(KeyCode::ShiftRight) {
-                new_y -= speed * dt;
-            }
-
-            // Check vertical collision using AABB (add epsilon to feet)
-            let feet_y = new_y - PLAYER_EYE_HEIGHT;
-            let head_y = feet_y + PLAYER_HEIGHT;
-            let min = Vec3::new(
-                transform.translation.x - PLAYER_HALF_WIDTH,
-                feet_y + EPSILON,
-                transform.translation.z - PLAYER_HALF_WIDTH
-            );
-            let max = Vec3::new(
-                transform.translation.x + PLAYER_HALF_WIDTH,
-                head_y,
-                transform.translation.z + PLAYER_HALF_WIDTH
-            );
-
-            if !check_aabb_collision(min, max, &chunk_manager, &chunks, &block_registry) {
-                transform.translation.y = new_y;
-            }
-
-            // Try horizontal movement with collision check (add epsilon to feet)
-            let new_x = transform.translation.x + horizontal_delta.x;
-            let new_z = transform.translation.z + horizontal_delta.z;
-            let feet_y = transform.translation.y - PLAYER_EYE_HEIGHT;
-            let head_y = feet_y + PLAYER_HEIGHT;
-            let min = Vec3::new(
-                new_x - PLAYER_HALF_WIDTH,
-                feet_y + EPSILON,
-                new_z - PLAYER_HALF_WIDTH
-            );
-            let max = Vec3::new(
-                new_x + PLAYER_HALF_WIDTH,
-                head_y,
-                new_z + PLAYER_HALF_WIDTH
-            );
-
-            if !check_aabb_collision(min, max, &chunk_manager, &chunks, &block_registry) {
-                transform.translation.x = new_x;
-                transform.translation.z = new_z;
-            }
-
-            controller.is_grounded = false;
-        } else {
-            // Walking mode - apply gravity and collision
-
-            // First, check if we're currently stuck inside a block and push up if so
-            let current_feet_y = transform.translation.y - PLAYER_EYE_HEIGHT;
-            let current_head_y = current_feet_y + PLAYER_HEIGHT;
-            let current_min = Vec3::new(
-                transform.translation.x - PLAYER_HALF_WIDTH,
-                current_feet_y + 0.01, // Small offset to ignore ground
-                transform.translation.z - PLAYER_HALF_WIDTH
-            );
-            let current_max = Vec3::new(
-                transform.translation.x + PLAYER_HALF_WIDTH,
-                current_head_y,
-                transform.translation.z + PLAYER_HALF_WIDTH
-            );
-
-            if check_aabb_collision(current_min, current_max, &chunk_manager, &chunks, &block_registry) {
-                // We're stuck inside a block - push up until we're free
-                transform.translation.y += 0.5;
-                controller.velocity_y = 0.0;
-                return; // Skip normal movement this frame
-            }
-
-            // Apply gravity
-            controller.velocity_y -= controller.gravity * dt;
-
-            // Calculate new Y position
-            let new_y = transform.translation.y + controller.velocity_y * dt;
-            let new_feet_y = new_y - PLAYER_EYE_HEIGHT;
-            let new_head_y = new_feet_y + PLAYER_HEIGHT;
-
-            // Check vertical collision with AABB
-            let min = Vec3::new(
-                transform.translation.x - PLAYER_HALF_WIDTH,
-                new_feet_y,
-                transform.translation.z - PLAYER_HALF_WIDTH
-            );
-            let max = Vec3::new(
-                transform.translation.x + PLAYER_HALF_WIDTH,
-                new_head_y,
-                transform.translation.z + PLAYER_HALF_WIDTH
-            );
-
-            if check_aabb_collision(min, max, &chunk_manager, &chunks, &block_registry) {
-                if controller.velocity_y > 0.0 {
-                    // Hit ceiling while rising - stop vertical movement
-                    controller.velocity_y = 0.0;
-                } else {
-                    // Hit ground while falling - stop and mark as grounded
-                    controller.is_grounded = true;
-                    controller.velocity_y = 0.0;
-                    // Don't change Y position - we stay where we are
-                }
-            } else {
-                // No collision - apply vertical movement
-                controller.is_grounded = false;
-                transform.translation.y = new_y;
-            }
-
-            // Try horizontal movement with AABB collision check
-            if horizontal_delta.length() > 0.0001 {
-                let new_x = transform.translation.x + horizontal_delta.x;
-                let new_z = transform.translation.z + horizontal_delta.z;
-                let feet_y = transform.translation.y - PLAYER_EYE_HEIGHT;
-                let head_y = feet_y + PLAYER_HEIGHT;
-
-                // Check collision at the new horizontal position
-                // Use small offset above feet to not collide with ground we're standing on
-                let min = Vec3::new(
-                    new_x - PLAYER_HALF_WIDTH,
-                    feet_y + 0.01,
-                    new_z - PLAYER_HALF_WIDTH
-                );
-                let max = Vec3::new(
-                    new_x + PLAYER_HALF_WIDTH,
-                    head_y,
-                    new_z + PLAYER_HALF_WIDTH
-                );
-
-                if !check_aabb_collision(min, max, &chunk_manager, &chunks, &block_registry) {
-                    // Move horizontally
-                    transform.translation.x = new_x;
-                    transform.translation.z = new_z;
-                }
-            }
+        // Flying - Space rises, Shift descends
+        player.fly_input = 0.0;
+        if keyboard_input.pressed(KeyCode::Space) {
+            player.fly_input += speed;
+        }
+        if shift {
+            player.fly_input -= speed;
         }
     }
 }
 
-/// Check if an AABB (axis-aligned bounding box) collides with any solid blocks
-/// Returns true if there is a collision
-fn check_aabb_collision(
-    min: Vec3,
-    max: Vec3,
-    chunk_manager: &ChunkManager,
-    chunks: &Query<&Chunk>,
-    block_registry: &BlockRegistry,
-) -> bool {
-    // Get the range of blocks the AABB overlaps
-    let min_block = IVec3::new(min.x.floor() as i32, min.y.floor() as i32, min.z.floor() as i32);
-    let max_block = IVec3::new(max.x.floor() as i32, max.y.floor() as i32, max.z.floor() as i32);
-
-    // Check all blocks that the AABB overlaps
-    for by in min_block.y..=max_block.y {
-        for bz in min_block.z..=max_block.z {
-            for bx in min_block.x..=max_block.x {
-                let block_pos = Vec3::new(bx as f32 + 0.5, by as f32 + 0.5, bz as f32 + 0.5);
-                let chunk_coord = ChunkCoord::from_world_pos(block_pos);
-
-                if let Some(&chunk_entity) = chunk_manager.loaded_chunks.get(&chunk_coord) {
-                    if let Ok(chunk) = chunks.get(chunk_entity) {
-                        // Convert world position to local chunk position
-                        let local_x = (bx - chunk_coord.x * CHUNK_SIZE as i32) as usize;
-                        let local_y = (by - chunk_coord.y * CHUNK_SIZE as i32) as usize;
-                        let local_z = (bz - chunk_coord.z * CHUNK_SIZE as i32) as usize;
-
-                        if local_x < CHUNK_SIZE && local_y < CHUNK_SIZE && local_z < CHUNK_SIZE {
-                            let block_id = chunk.get_block(local_x, local_y, local_z);
-                            if !block_id.is_air() {
-                                if let Some(block_type) = block_registry.get_block(block_id) {
-                                    if block_type.properties.is_solid {
-                                        return true; // Collision detected
-                                    }
-                                }
-                            }
-                        }
-                    }
-                }
-            }
+/// Move the player on the fixed timestep with swept collision (see `step_player`)
+/// Being stuck inside a block is left to suffocation damage. A server's remote players
+/// move by the commands their clients send instead
+#[allow(clippy::type_complexity)]
+pub fn player_physics(
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut Player, &mut PhysicsBody, &GameMode, Option<&mut PlayerStats>), Without<RemotePlayer>>,
+    chunk_manager: Res<ChunkManager>,
+    chunks: Query<&'static Chunk>,
+    block_registry: Res<BlockRegistry>,
+    mut local_stats: ResMut<PlayerStats>,
+    mut damage: EventWriter<PlayerDamage>,
+) {
+    let dt = time.delta_secs();
+    let colliders = ChunkColliders { chunk_manager: &chunk_manager, chunks: &chunks, registry: &block_registry };
+
+    for (entity, mut player, mut body, game_mode, own_stats) in query.iter_mut() {
+        // Hold still until the terrain around the player has loaded
+        if !chunk_manager.loaded_chunks.contains_key(&ChunkCoord::from_world_pos(body.position)) {
+            continue;
         }
-    }
 
-    false // No collision
+        let step = step_player(&mut player, &mut body, game_mode.abilities().noclip, &colliders, dt);
+        let stats = match own_stats {
+            Some(stats) => stats.into_inner(),
+            None => &mut *local_stats,
+        };
+        apply_step_effects(entity, &player, &step, stats, &mut damage);
+    }
 }
 
-#[allow(dead_code)]
-fn get_terrain_height_at(
-    pos: Vec3,
-    chunk_manager: &ChunkManager,
-    chunks: &Query<&Chunk>,
-    block_registry: &BlockRegistry,
-) -> f32 {
-    // Get the chunk X and Z coordinates
-    let chunk_x = (pos.x / CHUNK_SIZE as f32).floor() as i32;
-    let chunk_z = (pos.z / CHUNK_SIZE as f32).floor() as i32;
-
-    // Calculate local position within chunk
-    let local_x = ((pos.x - chunk_x as f32 * CHUNK_SIZE as f32).floor() as i32).max(0).min(CHUNK_SIZE as i32 - 1) as usize;
-    let local_z = ((pos.z - chunk_z as f32 * CHUNK_SIZE as f32).floor() as i32).max(0).min(CHUNK_SIZE as i32 - 1) as usize;
-
-    // Search from top to bottom across ALL vertically stacked chunks
-    // Start from a reasonable max height and work down
-    for world_y in (0..256).rev() {
-        let chunk_y = world_y / CHUNK_SIZE;
-        let local_y = world_y % CHUNK_SIZE;
-
-        let chunk_coord = ChunkCoord::new(chunk_x, chunk_y as i32, chunk_z);
-
-        if let Some(&chunk_entity) = chunk_manager.loaded_chunks.get(&chunk_coord) {
-            if let Ok(chunk) = chunks.get(chunk_entity) {
-                let block_id = chunk.get_block(local_x, local_y, local_z);
-                if !block_id.is_air() {
-                    if let Some(block_type) = block_registry.get_block(block_id) {
-                        if block_type.properties.is_solid {
-                            // Return the top of this block (world_y + 1)
-                            return world_y as f32 + 1.0;
-                        }
-                    }
-                }
-            }
-        }
+/// Exhaustion and fall damage from one movement step of `player`
+/// Shared by the local player's physics and a server's remote players
+pub fn apply_step_effects(entity: Entity, player: &Player, step: &PlayerStep, stats: &mut PlayerStats, damage: &mut EventWriter<PlayerDamage>) {
+    if step.jumped {
+        stats.add_exhaustion(if player.is_sprinting { SPRINT_JUMP_EXHAUSTION } else { JUMP_EXHAUSTION });
+    }
+    if player.is_sprinting && !player.is_flying {
+        stats.add_exhaustion(step.moved.xz().length() * SPRINT_EXHAUSTION_PER_BLOCK);
     }
 
-    1.0 // Default to Y=1 if no terrain found
+    // Landing turns the impact speed into fall damage
+    if let Some(impact_speed) = step.landing_speed {
+        let amount = fall_damage(impact_speed, player.gravity);
+        if amount > 0 {
+            damage.send(PlayerDamage { player: entity, source: DamageSource::Fall, amount });
+        }
+    }
 }
 
+/// Turn the local player with the mouse
 pub fn camera_look_controls(
-    mut query: Query<(&mut Transform, &mut CameraController)>,
+    mut player_query: Query<(&mut Transform, &mut Player), Without<RemotePlayer>>,
+    camera_query: Query<&CameraController>,
     mut mouse_motion_events: EventReader<MouseMotion>,
 ) {
-    for (mut transform, mut controller) in query.iter_mut() {
-        // FPS-style camera: always rotate with mouse movement (cursor is locked)
-        for event in mouse_motion_events.read() {
-            // Update yaw (horizontal rotation)
-            controller.yaw -= event.delta.x * controller.look_sensitivity;
+    let Ok(controller) = camera_query.get_single() else {
+        return;
+    };
 
-            // Update pitch (vertical rotation)
-            controller.pitch -= event.delta.y * controller.look_sensitivity;
+    // FPS-style look: always rotate with mouse movement (cursor is locked)
+    let delta: Vec2 = mouse_motion_events.read().map(|event| event.delta).sum();
 
-            // Clamp pitch to prevent camera flipping
-            controller.pitch = controller.pitch.clamp(-std::f32::consts::FRAC_PI_2 + 0.01, std::f32::consts::FRAC_PI_2 - 0.01);
+    for (mut transform, mut player) in player_query.iter_mut() {
+        // Update yaw (horizontal rotation)
+        player.yaw -= delta.x * controller.look_sensitivity;
+
+        // Update pitch (vertical rotation)
+        player.pitch -= delta.y * controller.look_sensitivity;
+
+        // Clamp pitch to prevent camera flipping
+        player.pitch = player.pitch.clamp(-std::f32::consts::FRAC_PI_2 + 0.01, std::f32::consts::FRAC_PI_2 - 0.01);
+
+        // The body only turns around the vertical axis; the head pitch lives on the camera
+        transform.rotation = Quat::from_rotation_y(player.yaw);
+    }
+}
+
+/// Gap kept between a pulled-in third-person camera and the block it hit
+const CAMERA_CLEARANCE: f32 = 0.2;
+
+/// Place the camera rig relative to the player's eyes
+/// Third-person views pull in along the view ray so the camera never ends up inside terrain
+#[allow(clippy::type_complexity)]
+pub fn update_camera_rig(
+    player_query: Query<(&Transform, &Player, &GameMode), (Without<CameraController>, Without<RemotePlayer>)>,
+    mut camera_query: Query<(&mut Transform, &CameraController)>,
+    mut model_query: Query<&mut Visibility, With<PlayerModel>>,
+    chunk_manager: Res<ChunkManager>,
+    chunks: Query<&'static Chunk>,
+    block_registry: Res<BlockRegistry>,
+) {
+    let Ok((player_transform, player, game_mode)) = player_query.get_single() else {
+        return;
+    };
+    let Ok((mut camera_transform, controller)) = camera_query.get_single_mut() else {
+        return;
+    };
+
+    let eye = Player::eye_position(player_transform);
+    let look = player.look_direction();
+
+    // Spectators have no body to show
+    let model_visibility = if controller.mode == CameraMode::FirstPerson || *game_mode == GameMode::Spectator {
+        Visibility::Hidden
+    } else {
+        Visibility::Inherited
+    };
+    for mut visibility in model_query.iter_mut() {
+        visibility.set_if_neq(model_visibility);
+    }
+
+    let offset_direction = match controller.mode {
+        CameraMode::FirstPerson => {
+            camera_transform.translation = eye;
+            camera_transform.rotation = player.look_rotation();
+            return;
         }
+        CameraMode::ThirdPersonBack => -look,
+        CameraMode::ThirdPersonFront => look,
+    };
+
+    let colliders = ChunkColliders { chunk_manager: &chunk_manager, chunks: &chunks, registry: &block_registry };
+    let distance = if game_mode.abilities().noclip {
+        controller.distance
+    } else {
+        pull_in_distance(&colliders, eye, offset_direction, controller.distance)
+    };
+
+    camera_transform.translation = eye + offset_direction * distance;
+    match controller.mode {
+        CameraMode::ThirdPersonFront => camera_transform.look_at(eye, Vec3::Y),
+        _ => camera_transform.rotation = player.look_rotation(),
+    }
+}
 
-        // Apply rotation to transform
-        transform.rotation = Quat::from_euler(EulerRot::YXZ, controller.yaw, controller.pitch, 0.0);
+/// How far the camera can back away from `origin` along `direction` before touching terrain
+fn pull_in_distance(world: &impl CollisionWorld, origin: Vec3, direction: Vec3, max_distance: f32) -> f32 {
+    const STEP: f32 = 0.05;
+    let probe = Vec3::splat(CAMERA_CLEARANCE * 0.5);
+
+    let mut distance = 0.0;
+    while distance < max_distance {
+        let next = (distance + STEP).min(max_distance);
+        let point = origin + direction * next;
+        if world.is_obstructed(CollisionBox::new(point - probe, point + probe)) {
+            break;
+        }
+        distance = next;
     }
+    distance
 }
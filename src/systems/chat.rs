@@ -0,0 +1,189 @@
+use bevy::prelude::*;
+use bevy::input::ButtonState;
+use bevy::input::keyboard::{Key, KeyboardInput};
+use bevy::window::{PrimaryWindow, CursorGrabMode};
+use crate::commands::{CommandDispatcher, CommandFeedback, RunCommand};
+use crate::components::{ChatInput, ChatMessages, ChatSuggestions, Player};
+use crate::resources::{ChatConsole, ChatLog, GameState};
+
+/// Chat lines shown at once
+const SHOWN_LINES: usize = 10;
+
+/// Seconds new chat stays up with the console closed
+const CHAT_FADE_SECONDS: f32 = 10.0;
+
+/// Suggestions listed above the input line
+const SHOWN_SUGGESTIONS: usize = 8;
+
+/// Say something in chat; lines starting with '/' are commands
+#[derive(Event, Debug, Clone)]
+pub struct SendChat(pub String);
+
+/// Run condition for key bindings: they'd be typed into the console while it's open
+pub fn chat_closed(console: Res<ChatConsole>) -> bool {
+    !console.open
+}
+
+/// Open the console with T (or '/' to start a command) and type into it:
+/// Enter sends the line, Escape closes it, Up/Down go through the lines sent before
+/// and Tab completes the command being typed
+pub fn chat_console_input(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut key_events: EventReader<KeyboardInput>,
+    mut console: ResMut<ChatConsole>,
+    mut game_state: ResMut<GameState>,
+    mut sent: EventWriter<SendChat>,
+    mut windows: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    if !console.open {
+        // The key that opens the console isn't typed into it
+        let command = key_events.read()
+            .any(|event| event.state == ButtonState::Pressed && event.logical_key == Key::Character("/".into()));
+        if !(command || keyboard_input.just_pressed(KeyCode::KeyT)) || !game_state.accepts_gameplay_input() || game_state.dead {
+            return;
+        }
+        console.open(if command { "/" } else { "" });
+        // The player stands still and the mouse is free while typing
+        game_state.screen_open = true;
+        if let Ok(mut window) = windows.get_single_mut() {
+            window.cursor_options.grab_mode = CursorGrabMode::None;
+            window.cursor_options.visible = true;
+        }
+        return;
+    }
+
+    for event in key_events.read() {
+        if event.state != ButtonState::Pressed {
+            continue;
+        }
+        match &event.logical_key {
+            Key::Enter => {
+                if let Some(line) = console.submit() {
+                    sent.send(SendChat(line));
+                }
+            }
+            Key::Escape => console.close(),
+            Key::Backspace => console.backspace(),
+            Key::ArrowUp => console.history_back(),
+            Key::ArrowDown => console.history_forward(),
+            Key::Tab => console.complete(),
+            Key::Space => console.type_char(' '),
+            Key::Character(text) => text.chars().for_each(|character| console.type_char(character)),
+            _ => {}
+        }
+        if !console.open {
+            break;
+        }
+    }
+
+    if !console.open {
+        game_state.screen_open = false;
+        // The death screen keeps the cursor free
+        if !game_state.dead {
+            if let Ok(mut window) = windows.get_single_mut() {
+                window.cursor_options.grab_mode = CursorGrabMode::Locked;
+                window.cursor_options.visible = false;
+            }
+        }
+    }
+}
+
+/// Work out how the command being typed could go on, for Tab and the list above the input
+pub fn update_chat_suggestions(world: &mut World) {
+    let console = world.resource::<ChatConsole>();
+    if !console.open || !console.needs_suggestions() {
+        return;
+    }
+    let input = console.input.clone();
+    let suggestions = match input.strip_prefix('/') {
+        Some(command) => world.resource::<CommandDispatcher>().complete(command, world).into_iter()
+            .map(|suggestion| format!("/{}{}", &command[..suggestion.start], suggestion.text))
+            .collect(),
+        None => Vec::new(),
+    };
+    let mut console = world.resource_mut::<ChatConsole>();
+    console.suggestions = suggestions;
+    console.suggested_for = Some(input);
+}
+
+/// Without a server, run commands here and put chat straight into the log
+pub fn handle_local_chat(
+    mut chat: EventReader<SendChat>,
+    mut chat_log: ResMut<ChatLog>,
+    player_query: Query<Entity, With<Player>>,
+    mut run_command: EventWriter<RunCommand>,
+) {
+    for SendChat(line) in chat.read() {
+        match line.strip_prefix('/') {
+            Some(command) => {
+                if let Ok(player) = player_query.get_single() {
+                    run_command.send(RunCommand { player, command: command.to_string() });
+                }
+            }
+            None => chat_log.push(Some("Player".to_string()), line.clone()),
+        }
+    }
+}
+
+/// Put what commands said back into the chat log
+pub fn show_command_feedback(
+    mut feedback: EventReader<CommandFeedback>,
+    mut chat_log: ResMut<ChatLog>,
+) {
+    for CommandFeedback { message, .. } in feedback.read() {
+        for line in message.lines() {
+            chat_log.push(None, line.to_string());
+        }
+    }
+}
+
+/// Show the latest chat (for a while after it arrives, or while the console is open),
+/// the suggestions and the input line
+#[allow(clippy::type_complexity)]
+pub fn update_chat_display(
+    time: Res<Time>,
+    console: Res<ChatConsole>,
+    chat_log: Res<ChatLog>,
+    mut shown_until: Local<f32>,
+    mut messages: Query<(&mut Text, &mut Visibility), With<ChatMessages>>,
+    mut suggestions: Query<(&mut Text, &mut Visibility), (With<ChatSuggestions>, Without<ChatMessages>)>,
+    mut input: Query<(&mut Text, &mut Visibility), (With<ChatInput>, Without<ChatMessages>, Without<ChatSuggestions>)>,
+) {
+    let now = time.elapsed_secs();
+    if chat_log.is_changed() && !chat_log.lines.is_empty() {
+        *shown_until = now + CHAT_FADE_SECONDS;
+    }
+    let show = |visible: bool| if visible { Visibility::Visible } else { Visibility::Hidden };
+
+    for (mut text, mut visibility) in messages.iter_mut() {
+        visibility.set_if_neq(show(!chat_log.lines.is_empty() && (console.open || now < *shown_until)));
+        if chat_log.is_changed() {
+            let start = chat_log.lines.len().saturating_sub(SHOWN_LINES);
+            let lines: Vec<String> = chat_log.lines[start..].iter()
+                .map(|line| match &line.sender {
+                    Some(sender) => format!("<{}> {}", sender, line.message),
+                    None => line.message.clone(),
+                })
+                .collect();
+            **text = lines.join("\n");
+        }
+    }
+
+    if !console.is_changed() {
+        return;
+    }
+    for (mut text, mut visibility) in suggestions.iter_mut() {
+        *visibility = show(console.open && !console.suggestions.is_empty());
+        let lines: Vec<String> = console.suggestions.iter().enumerate().take(SHOWN_SUGGESTIONS)
+            .map(|(index, suggestion)| match console.selected == Some(index) {
+                true => format!("> {}", suggestion),
+                false => format!("  {}", suggestion),
+            })
+            .collect();
+        **text = lines.join("\n");
+    }
+    for (mut text, mut visibility) in input.iter_mut() {
+        *visibility = show(console.open);
+        **text = format!("{}_", console.input);
+    }
+}
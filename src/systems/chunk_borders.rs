@@ -1,5 +1,5 @@
 use bevy::prelude::*;
-use crate::components::{CameraController, ChunkBorder};
+use crate::components::{Player, ChunkBorder};
 use crate::resources::{ChunkBorderState, ChunkBorderMode};
 use crate::world::{ChunkCoord, CHUNK_SIZE};
 
@@ -8,7 +8,7 @@ const CORNER_RENDER_DISTANCE: i32 = 2;  // Show red corners within 2 chunks of p
 pub fn update_chunk_borders(
     mut commands: Commands,
     border_state: Res<ChunkBorderState>,
-    camera_query: Query<&Transform, With<CameraController>>,
+    player_query: Query<&Transform, With<Player>>,
     existing_borders: Query<Entity, With<ChunkBorder>>,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
@@ -23,12 +23,12 @@ pub fn update_chunk_borders(
         return;
     }
 
-    let Ok(camera_transform) = camera_query.get_single() else {
+    let Ok(player_transform) = player_query.get_single() else {
         return;
     };
 
-    let player_chunk = ChunkCoord::from_world_pos(camera_transform.translation);
-    let player_y = camera_transform.translation.y;
+    let player_chunk = ChunkCoord::from_world_pos(player_transform.translation);
+    let player_y = player_transform.translation.y;
 
     // Create materials once
     let red_material = materials.add(StandardMaterial {
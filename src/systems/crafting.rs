@@ -0,0 +1,169 @@
+use bevy::prelude::*;
+use bevy::window::{PrimaryWindow, CursorGrabMode};
+use crate::blocks::BlockRegistry;
+use crate::components::{CraftingOutput, CraftingScreenRoot, CraftingSlot, CraftingSlotLabel, Player, PLAYER_HEIGHT};
+use crate::crafting::RecipeRegistry;
+use crate::items::ItemRegistry;
+use crate::resources::{CraftingScreen, GameState, HotbarItem, ItemStack, PlayerInventory};
+use crate::systems::DropItem;
+use crate::world::WorldSpawn;
+
+/// Open the crafting screen when a crafting table is used
+pub fn open_crafting_screen(
+    mut spawn_events: EventReader<WorldSpawn>,
+    mut screen: ResMut<CraftingScreen>,
+    mut game_state: ResMut<GameState>,
+    mut windows: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    for event in spawn_events.read() {
+        let WorldSpawn::OpenCrafting { pos } = *event else {
+            continue;
+        };
+        screen.table = Some(pos);
+        game_state.screen_open = true;
+        if let Ok(mut window) = windows.get_single_mut() {
+            window.cursor_options.grab_mode = CursorGrabMode::None;
+            window.cursor_options.visible = true;
+        }
+    }
+}
+
+/// Close the crafting screen on E or Escape, returning the grid to the hotbar
+/// Whatever doesn't fit is dropped at the player, and dying drops the whole grid
+pub fn close_crafting_screen(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut screen: ResMut<CraftingScreen>,
+    mut game_state: ResMut<GameState>,
+    mut inventory: ResMut<PlayerInventory>,
+    player_query: Query<&Transform, With<Player>>,
+    mut drops: EventWriter<DropItem>,
+    mut windows: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    if !screen.is_open() {
+        return;
+    }
+    let close_pressed = keyboard_input.just_pressed(KeyCode::KeyE) || keyboard_input.just_pressed(KeyCode::Escape);
+    if !close_pressed && !game_state.dead {
+        return;
+    }
+
+    let drop_pos = player_query.get_single().map_or(Vec3::ZERO, |transform| transform.translation + Vec3::Y * (PLAYER_HEIGHT * 0.5));
+    for stack in screen.close() {
+        let left = if game_state.dead { stack.count } else { inventory.add_stack(stack) };
+        if left > 0 {
+            drops.send(DropItem { pos: drop_pos, stack: ItemStack { count: left, ..stack } });
+        }
+    }
+
+    game_state.screen_open = false;
+    // The death screen keeps the cursor free
+    if !game_state.dead {
+        if let Ok(mut window) = windows.get_single_mut() {
+            window.cursor_options.grab_mode = CursorGrabMode::Locked;
+            window.cursor_options.visible = false;
+        }
+    }
+}
+
+/// Clicks on the crafting screen
+/// Left click on a grid slot moves one of the held item onto it, right click takes the slot back,
+/// and clicking the result crafts once if the hotbar has room for it
+#[allow(clippy::type_complexity)]
+pub fn handle_crafting_clicks(
+    mouse_button: Res<ButtonInput<MouseButton>>,
+    slot_query: Query<(&Interaction, &CraftingSlot)>,
+    output_query: Query<&Interaction, (Changed<Interaction>, With<CraftingOutput>)>,
+    mut screen: ResMut<CraftingScreen>,
+    mut inventory: ResMut<PlayerInventory>,
+    recipes: Res<RecipeRegistry>,
+) {
+    if !screen.is_open() {
+        return;
+    }
+
+    for (interaction, slot) in slot_query.iter() {
+        if *interaction == Interaction::None {
+            continue;
+        }
+        if mouse_button.just_pressed(MouseButton::Left) {
+            let held = inventory.hotbar[inventory.selected_slot];
+            if let Some(stack) = held.filter(|&stack| screen.place_one(slot.index, stack)) {
+                inventory.consume_selected();
+                debug!("Placed {:?} on crafting slot {}", stack.item, slot.index);
+            }
+        } else if mouse_button.just_pressed(MouseButton::Right) {
+            if let Some(stack) = screen.grid[slot.index].take() {
+                let left = inventory.add_stack(stack);
+                if left > 0 {
+                    screen.grid[slot.index] = Some(ItemStack { count: left, ..stack });
+                }
+            }
+        }
+    }
+
+    for interaction in output_query.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        let Some(result) = screen.output(&recipes) else {
+            continue;
+        };
+        if !inventory.can_fit(result) {
+            info!("No room in the hotbar for the crafted item");
+            continue;
+        }
+        if let Some(crafted) = screen.craft(&recipes) {
+            inventory.add_stack(crafted);
+        }
+    }
+}
+
+/// Show or hide the crafting screen and refresh its slot labels and hover colors
+#[allow(clippy::type_complexity)]
+pub fn update_crafting_screen(
+    screen: Res<CraftingScreen>,
+    recipes: Res<RecipeRegistry>,
+    item_registry: Res<ItemRegistry>,
+    block_registry: Res<BlockRegistry>,
+    mut root_query: Query<&mut Visibility, With<CraftingScreenRoot>>,
+    mut label_query: Query<(&CraftingSlotLabel, &mut Text)>,
+    mut button_query: Query<(&Interaction, &mut BackgroundColor), (Or<(With<CraftingSlot>, With<CraftingOutput>)>, Changed<Interaction>)>,
+) {
+    for (interaction, mut color) in button_query.iter_mut() {
+        *color = BackgroundColor(match *interaction {
+            Interaction::None => Color::srgb(0.15, 0.15, 0.15),
+            _ => Color::srgb(0.25, 0.25, 0.25),
+        });
+    }
+
+    if !screen.is_changed() {
+        return;
+    }
+
+    for mut visibility in root_query.iter_mut() {
+        *visibility = if screen.is_open() { Visibility::Visible } else { Visibility::Hidden };
+    }
+
+    let output = screen.output(&recipes);
+    for (label, mut text) in label_query.iter_mut() {
+        let stack = match label.index {
+            Some(index) => screen.grid[index],
+            None => output,
+        };
+        **text = stack.map_or_else(String::new, |stack| stack_label(stack, &item_registry, &block_registry));
+    }
+}
+
+/// Item name with the count underneath when there's more than one
+pub(super) fn stack_label(stack: ItemStack, item_registry: &ItemRegistry, block_registry: &BlockRegistry) -> String {
+    let name = match stack.item {
+        HotbarItem::Block(block) => block_registry.get_block(block)
+            .map_or("?", |block_type| block_type.properties.name.as_str()),
+        HotbarItem::Item(item) => item_registry.name(item),
+    };
+    if stack.count > 1 {
+        format!("{}\nx{}", name, stack.count)
+    } else {
+        name.to_string()
+    }
+}
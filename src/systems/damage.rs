@@ -0,0 +1,150 @@
+use bevy::prelude::*;
+use crate::components::{GameMode, Player};
+use crate::physics::{CollisionWorld, PhysicsBody};
+use crate::resources::PlayerStats;
+use crate::systems::WorldBlocks;
+use super::falling_blocks::VOID_Y;
+use super::hunger::DAMAGE_EXHAUSTION;
+
+/// Ticks after a hit during which further damage is ignored (half a second)
+pub const INVULNERABILITY_TICKS: u16 = 10;
+
+/// Falls shorter than this many blocks are harmless
+pub const SAFE_FALL_DISTANCE: f32 = 3.0;
+
+/// Damage per hit for each environmental source
+const SUFFOCATION_DAMAGE: u8 = 1;
+const VOID_DAMAGE: u8 = 4;
+const DROWNING_DAMAGE: u8 = 2;
+
+/// Ticks between drowning hits once the air meter is empty
+const DROWNING_INTERVAL: u16 = 20;
+
+/// Air regained per tick with the head out of liquid
+const AIR_REFILL_PER_TICK: u16 = 5;
+
+/// What hurt the player
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DamageSource {
+    Fall,
+    Suffocation,
+    Void,
+    Drowning,
+    Starvation,
+    /// Hit by a hostile mob
+    Mob,
+}
+
+/// Request to hurt a player, resolved by `apply_player_damage`
+#[derive(Event, Debug, Clone, Copy)]
+pub struct PlayerDamage {
+    pub player: Entity,
+    pub source: DamageSource,
+    pub amount: u8,
+}
+
+/// Fall damage for landing at `speed` blocks/s: one point per block fallen past the safe distance
+/// The fall height is recovered from the impact speed (v² = 2gh)
+pub fn fall_damage(speed: f32, gravity: f32) -> u8 {
+    if speed <= 0.0 || gravity <= 0.0 {
+        return 0;
+    }
+    let height = speed * speed / (2.0 * gravity);
+    (height - SAFE_FALL_DISTANCE).ceil().clamp(0.0, u8::MAX as f32) as u8
+}
+
+/// Check the world around each player every tick for void, suffocation and drowning
+/// Also counts down the invulnerability frames. Players with their own `PlayerStats`
+/// component (a server's clients) use it, everyone else the `PlayerStats` resource
+pub fn update_environment_damage(
+    world_blocks: WorldBlocks,
+    mut player_query: Query<(Entity, &Transform, &PhysicsBody, Option<&mut PlayerStats>), With<Player>>,
+    mut local_stats: ResMut<PlayerStats>,
+    mut damage: EventWriter<PlayerDamage>,
+) {
+    for (player, transform, body, own_stats) in player_query.iter_mut() {
+        let mut stats = match own_stats {
+            Some(stats) => stats,
+            None => local_stats.reborrow(),
+        };
+        if stats.invulnerable_ticks > 0 {
+            stats.invulnerable_ticks -= 1;
+        }
+
+        if body.position.y < VOID_Y {
+            damage.send(PlayerDamage { player, source: DamageSource::Void, amount: VOID_DAMAGE });
+        }
+
+        let eye = Player::eye_position(transform);
+        let eye_cell = eye.floor().as_ivec3();
+
+        // Suffocating when the eyes are inside a block's collision box
+        if world_blocks.block_box(eye_cell).is_some_and(|block_box| eye.y < block_box.max.y) {
+            damage.send(PlayerDamage { player, source: DamageSource::Suffocation, amount: SUFFOCATION_DAMAGE });
+        }
+
+        let head_in_liquid = world_blocks.get_state(eye_cell)
+            .and_then(|state| world_blocks.registry().get_block(state.id))
+            .is_some_and(|block| block.properties.is_liquid);
+
+        if head_in_liquid {
+            if stats.air > 0 {
+                stats.air -= 1;
+            } else {
+                // Out of air: hurt, then give back a sliver so the next hit lands DROWNING_INTERVAL ticks later
+                damage.send(PlayerDamage { player, source: DamageSource::Drowning, amount: DROWNING_DAMAGE });
+                stats.air = DROWNING_INTERVAL;
+            }
+        } else if stats.air < stats.max_air {
+            stats.air = (stats.air + AIR_REFILL_PER_TICK).min(stats.max_air);
+        }
+    }
+}
+
+/// Apply queued damage to the hurt player's health
+/// Invulnerable game modes are immune and a fresh hit is ignored while invulnerability frames last
+pub fn apply_player_damage(
+    mut events: EventReader<PlayerDamage>,
+    mut local_stats: ResMut<PlayerStats>,
+    mut player_query: Query<(&GameMode, Option<&mut PlayerStats>), With<Player>>,
+) {
+    for event in events.read() {
+        let Ok((mode, own_stats)) = player_query.get_mut(event.player) else {
+            continue;
+        };
+        let mut stats = match own_stats {
+            Some(stats) => stats,
+            None => local_stats.reborrow(),
+        };
+        if mode.abilities().invulnerable || event.amount == 0 || stats.invulnerable_ticks > 0 {
+            continue;
+        }
+
+        stats.health = stats.health.saturating_sub(event.amount);
+        stats.invulnerable_ticks = INVULNERABILITY_TICKS;
+        stats.add_exhaustion(DAMAGE_EXHAUSTION);
+        info!("Took {} damage from {:?} ({} health left)", event.amount, event.source, stats.health);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::GRAVITY;
+
+    #[test]
+    fn short_falls_are_harmless() {
+        let jump_landing_speed = 8.0;
+        assert_eq!(fall_damage(jump_landing_speed, GRAVITY), 0);
+        assert_eq!(fall_damage(0.0, GRAVITY), 0);
+    }
+
+    #[test]
+    fn fall_damage_grows_with_height() {
+        let speed_for = |height: f32| (2.0 * GRAVITY * height).sqrt();
+        assert_eq!(fall_damage(speed_for(2.9), GRAVITY), 0);
+        assert_eq!(fall_damage(speed_for(3.5), GRAVITY), 1);
+        assert_eq!(fall_damage(speed_for(10.5), GRAVITY), 8);
+        assert_eq!(fall_damage(speed_for(22.5), GRAVITY), 20);
+    }
+}
@@ -0,0 +1,172 @@
+use bevy::prelude::*;
+use bevy::window::{PrimaryWindow, CursorGrabMode};
+use crate::components::{DeathScreen, Player, RespawnButton, PLAYER_HEIGHT};
+use crate::physics::{CollisionBox, CollisionWorld, PhysicsBody};
+use crate::resources::{GameState, PlayerInventory, PlayerStats};
+use crate::world::{SpawnPoint, WorldSpawn};
+use crate::systems::{DropItem, WorldBlocks};
+
+/// How far apart the dropped inventory is scattered around the death location
+const DROP_SCATTER: f32 = 0.4;
+
+/// Blocks searched upwards from a bed for room to stand
+const BED_CLEARANCE_SEARCH: i32 = 3;
+
+/// Enter the death state when the local player's health runs out
+/// Drops the whole hotbar where the player died, pauses the game and frees the cursor
+pub fn handle_player_death(
+    mut game_state: ResMut<GameState>,
+    stats: Res<PlayerStats>,
+    mut inventory: ResMut<PlayerInventory>,
+    player_query: Query<&PhysicsBody, (With<Player>, Without<PlayerStats>)>,
+    mut drops: EventWriter<DropItem>,
+    mut windows: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    if stats.health > 0 || game_state.dead {
+        return;
+    }
+
+    if let Ok(body) = player_query.get_single() {
+        drop_hotbar(&mut inventory, body, &mut drops);
+    }
+
+    game_state.dead = true;
+    game_state.paused = true;
+    if let Ok(mut window) = windows.get_single_mut() {
+        window.cursor_options.grab_mode = CursorGrabMode::None;
+        window.cursor_options.visible = true;
+    }
+    info!("Player died");
+}
+
+/// Players with their own `PlayerStats` (a server's clients) have no death screen to wait on:
+/// they drop their hotbar where they died and come straight back at their bed or the world spawn
+pub fn respawn_dead_players(
+    mut players: Query<(&mut Player, &mut PhysicsBody, &mut PlayerStats, Option<&mut PlayerInventory>)>,
+    world_blocks: WorldBlocks,
+    spawn_point: Res<SpawnPoint>,
+    mut drops: EventWriter<DropItem>,
+) {
+    for (mut player, mut body, mut stats, inventory) in players.iter_mut() {
+        if stats.health > 0 {
+            continue;
+        }
+        if let Some(mut inventory) = inventory {
+            drop_hotbar(&mut inventory, &body, &mut drops);
+        }
+        respawn(&mut player, &mut body, &world_blocks, &spawn_point);
+        *stats = PlayerStats::default();
+        info!("Player died and respawned at {:?}", body.position);
+    }
+}
+
+/// Scatter every stack on the hotbar around the middle of the player's body
+fn drop_hotbar(inventory: &mut PlayerInventory, body: &PhysicsBody, drops: &mut EventWriter<DropItem>) {
+    let center = body.position + Vec3::Y * (PLAYER_HEIGHT * 0.5);
+    let stacks: Vec<_> = inventory.hotbar.iter_mut().filter_map(|slot| slot.take()).collect();
+    let count = stacks.len().max(1) as f32;
+    for (i, stack) in stacks.into_iter().enumerate() {
+        let angle = i as f32 / count * std::f32::consts::TAU;
+        let offset = Vec3::new(angle.cos(), 0.0, angle.sin()) * DROP_SCATTER;
+        drops.send(DropItem { pos: center + offset, stack });
+    }
+}
+
+/// Put the player back at their bed (if it still stands) or the world spawn
+fn respawn(player: &mut Player, body: &mut PhysicsBody, world_blocks: &WorldBlocks, spawn_point: &SpawnPoint) {
+    let bed_position = player.bed_spawn.and_then(|bed| bed_respawn_position(world_blocks, bed, body));
+    if player.bed_spawn.is_some() && bed_position.is_none() {
+        info!("Your bed was missing or obstructed");
+        player.bed_spawn = None;
+    }
+
+    body.teleport(bed_position.unwrap_or_else(|| spawn_point.feet_position()));
+    body.velocity = Vec3::ZERO;
+    player.is_flying = false;
+}
+
+/// Remember the last bed the player used as their respawn point
+pub fn update_bed_spawn(
+    mut spawn_events: EventReader<WorldSpawn>,
+    mut player_query: Query<&mut Player>,
+) {
+    for event in spawn_events.read() {
+        let WorldSpawn::SpawnPointSet { pos } = *event else {
+            continue;
+        };
+        for mut player in player_query.iter_mut() {
+            player.bed_spawn = Some(pos);
+        }
+        info!("Respawn point set to bed at {:?}", pos);
+    }
+}
+
+pub fn update_death_screen_visibility(
+    game_state: Res<GameState>,
+    mut screen_query: Query<&mut Visibility, With<DeathScreen>>,
+) {
+    if !game_state.is_changed() {
+        return;
+    }
+
+    for mut visibility in screen_query.iter_mut() {
+        *visibility = if game_state.dead {
+            Visibility::Visible
+        } else {
+            Visibility::Hidden
+        };
+    }
+}
+
+/// Respawn button: restore the player at their bed (if it still stands) or the world spawn
+#[allow(clippy::type_complexity)]
+pub fn handle_respawn_button(
+    mut interaction_query: Query<(&Interaction, &mut BackgroundColor), (Changed<Interaction>, With<RespawnButton>)>,
+    mut game_state: ResMut<GameState>,
+    mut stats: ResMut<PlayerStats>,
+    mut player_query: Query<(&mut Player, &mut PhysicsBody), Without<PlayerStats>>,
+    world_blocks: WorldBlocks,
+    spawn_point: Res<SpawnPoint>,
+    mut windows: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    for (interaction, mut color) in interaction_query.iter_mut() {
+        match *interaction {
+            Interaction::Pressed => {
+                if let Ok((mut player, mut body)) = player_query.get_single_mut() {
+                    respawn(&mut player, &mut body, &world_blocks, &spawn_point);
+                }
+
+                *stats = PlayerStats::default();
+                game_state.dead = false;
+                game_state.paused = false;
+                if let Ok(mut window) = windows.get_single_mut() {
+                    window.cursor_options.grab_mode = CursorGrabMode::Locked;
+                    window.cursor_options.visible = false;
+                }
+            }
+            Interaction::Hovered => {
+                *color = BackgroundColor(Color::srgb(0.25, 0.25, 0.25));
+            }
+            Interaction::None => {
+                *color = BackgroundColor(Color::srgb(0.15, 0.15, 0.15));
+            }
+        }
+    }
+}
+
+/// Feet position on top of a bed with room to stand, None if the bed is gone or buried
+/// Unloaded beds are trusted and respawned on directly
+fn bed_respawn_position(world: &WorldBlocks, bed: IVec3, body: &PhysicsBody) -> Option<Vec3> {
+    let bed_id = world.registry().get_id("core:bed")?;
+    let Some(state) = world.get_state(bed) else {
+        return Some(bed.as_vec3() + Vec3::new(0.5, 1.0, 0.5));
+    };
+    if state.id != bed_id {
+        return None;
+    }
+
+    let top = world.block_box(bed).map_or(bed.y as f32 + 1.0, |block_box| block_box.max.y);
+    (0..BED_CLEARANCE_SEARCH)
+        .map(|dy| Vec3::new(bed.x as f32 + 0.5, top + dy as f32, bed.z as f32 + 0.5))
+        .find(|&feet| !world.is_obstructed(CollisionBox::from_feet(feet, body.half_width, body.height)))
+}
@@ -1,6 +1,6 @@
 use bevy::prelude::*;
 use bevy::diagnostic::{DiagnosticsStore, FrameTimeDiagnosticsPlugin};
-use crate::components::{CameraController, DebugOverlay};
+use crate::components::{GameMode, Player, DebugOverlay};
 use crate::blocks::{BlockRegistry, BlockId};
 use crate::world::{Chunk, ChunkCoord, CHUNK_SIZE};
 use crate::systems::{TimeOfDay, SkyLightLevel};
@@ -9,15 +9,15 @@ use crate::resources::FpsStats;
 /// System to update debug overlay with FPS, position, and block info
 pub fn update_debug_overlay(
     diagnostics: Res<DiagnosticsStore>,
-    camera_query: Query<(&Transform, &CameraController), With<CameraController>>,
-    chunk_query: Query<(&Chunk, &Transform), Without<CameraController>>,
+    player_query: Query<(&Transform, &Player, &GameMode)>,
+    chunk_query: Query<(&Chunk, &Transform), Without<Player>>,
     mut debug_text_query: Query<(&mut Text, &DebugOverlay)>,
     block_registry: Res<BlockRegistry>,
     time_of_day: Res<TimeOfDay>,
     sky_light: Res<SkyLightLevel>,
     mut fps_stats: ResMut<FpsStats>,
 ) {
-    let Ok((camera_transform, controller)) = camera_query.get_single() else {
+    let Ok((player_transform, player, game_mode)) = player_query.get_single() else {
         return;
     };
 
@@ -30,7 +30,8 @@ pub fn update_debug_overlay(
         return;
     }
 
-    let camera_pos = camera_transform.translation;
+    let feet_pos = player_transform.translation;
+    let eye_pos = Player::eye_position(player_transform);
     let mut debug_text = String::new();
 
     // Add FPS with min/avg/max tracking
@@ -61,17 +62,17 @@ pub fn update_debug_overlay(
     debug_text.push_str(&format!("Sky Light: {}\n", sky_light.level));
 
     // Add position
-    debug_text.push_str(&format!("X: {:.1}\nY: {:.1}\nZ: {:.1}\n", camera_pos.x, camera_pos.y, camera_pos.z));
+    debug_text.push_str(&format!("X: {:.1}\nY: {:.1}\nZ: {:.1}\n", feet_pos.x, feet_pos.y, feet_pos.z));
 
     // Add cardinal direction
-    let direction = get_cardinal_direction(controller.yaw);
+    let direction = get_cardinal_direction(player.yaw);
     debug_text.push_str(&format!("Facing: {}\n", direction));
+    debug_text.push_str(&format!("Mode: {}\n", game_mode.name()));
 
     // Get block player is standing on
-    // Player's eyes are at camera_pos, feet are 1.6 blocks below
     // Check just below the feet (0.1 blocks down) to get the block they're standing ON
-    let standing_on_pos = camera_pos - Vec3::Y * 1.7; // Slightly below feet level
-    let player_block_pos = camera_pos - Vec3::Y * 0.5; // Air block at player's body
+    let standing_on_pos = feet_pos - Vec3::Y * 0.1;
+    let player_block_pos = eye_pos - Vec3::Y * 0.5; // Air block at player's body
 
     let standing_on_block = get_block_at_world_pos(standing_on_pos, &chunk_query);
     let standing_on_name = get_block_name(standing_on_block, &block_registry);
@@ -83,7 +84,7 @@ pub fn update_debug_overlay(
     debug_text.push_str(&format!("Block Light: {}\n", light_at_player));
 
     // Raycast to find block player is looking at
-    let raycast_result = raycast_block(camera_transform, &chunk_query, 8.0);
+    let raycast_result = raycast_block(eye_pos, player.look_direction(), &chunk_query, 8.0);
     let looking_at_name = get_block_name(raycast_result.block_id, &block_registry);
 
     // Get light at the face being looked at (the air block in front of the solid block)
@@ -183,12 +184,11 @@ struct RaycastResult {
 
 /// Simple raycast to find block player is looking at
 fn raycast_block<F: bevy::ecs::query::QueryFilter>(
-    camera_transform: &Transform,
+    start: Vec3,
+    direction: Vec3,
     chunk_query: &Query<(&Chunk, &Transform), F>,
     max_distance: f32,
 ) -> RaycastResult {
-    let start = camera_transform.translation;
-    let direction = camera_transform.forward();
 
     // Step along ray
     let step_size = 0.1;
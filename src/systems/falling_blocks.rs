@@ -0,0 +1,219 @@
+use bevy::prelude::*;
+use crate::assets::AssetManager;
+use crate::blocks::{BlockRegistry, BlockState};
+use crate::components::GRAVITY;
+use crate::physics::TERMINAL_VELOCITY;
+use crate::rendering::terrain_material::TerrainMaterial;
+use crate::world::mesh_gen::create_single_block_mesh;
+use crate::world::{BlockWorld, SetBlockFlags, WorldSpawn};
+use crate::systems::WorldBlocks;
+
+/// Entities that fall below this height are lost in the void
+pub const VOID_Y: f32 = -64.0;
+
+/// A gravity block (sand, gravel) that lost its support and is falling
+/// The transform holds the block's minimum corner, matching the single block mesh
+#[derive(Component)]
+pub struct FallingBlock {
+    pub state: BlockState,
+    pub velocity_y: f32,
+}
+
+/// Material shared by block-shaped entities (falling blocks, dropped blocks)
+pub(crate) fn block_entity_material(
+    cached: &mut Option<Handle<TerrainMaterial>>,
+    asset_manager: &AssetManager,
+    materials: &mut Assets<TerrainMaterial>,
+) -> Handle<TerrainMaterial> {
+    cached.get_or_insert_with(|| {
+        let atlas = asset_manager.get_mod_texture_atlas("core").unwrap_or_default();
+        materials.add(TerrainMaterial::new(atlas))
+    }).clone()
+}
+
+/// Spawn an entity for every block that started falling this frame
+pub fn spawn_falling_blocks(
+    mut commands: Commands,
+    mut spawn_events: EventReader<WorldSpawn>,
+) {
+    for event in spawn_events.read() {
+        let WorldSpawn::FallingBlock { pos, state } = *event else {
+            continue;
+        };
+        commands.spawn((
+            Transform::from_translation(pos.as_vec3()),
+            FallingBlock { state, velocity_y: 0.0 },
+        ));
+    }
+}
+
+/// Give newly spawned falling blocks their block mesh
+pub fn attach_falling_block_meshes(
+    mut commands: Commands,
+    falling_blocks: Query<(Entity, &FallingBlock), Added<FallingBlock>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<TerrainMaterial>>,
+    asset_manager: Res<AssetManager>,
+    block_registry: Res<BlockRegistry>,
+    mut material: Local<Option<Handle<TerrainMaterial>>>,
+) {
+    for (entity, falling) in falling_blocks.iter() {
+        let Some(mesh) = create_single_block_mesh(falling.state.id, &block_registry) else {
+            continue;
+        };
+        commands.entity(entity).insert((
+            Mesh3d(meshes.add(mesh)),
+            MeshMaterial3d(block_entity_material(&mut material, &asset_manager, &mut materials)),
+        ));
+    }
+}
+
+/// Apply gravity to falling blocks and land them
+/// A block landing on a solid block is placed back into the world; one landing on
+/// a non-full block (or in an occupied cell) breaks into a dropped item instead
+pub fn update_falling_blocks(
+    time: Res<Time>,
+    mut world_blocks: WorldBlocks,
+    mut falling_blocks: Query<(Entity, &mut Transform, &mut FallingBlock)>,
+) {
+    let dt = time.delta_secs();
+
+    for (entity, mut transform, mut falling) in falling_blocks.iter_mut() {
+        let mut position = transform.translation;
+        let fall = world_blocks.edit(|world| step_falling_block(world, &mut falling, &mut position, dt));
+        transform.translation = position;
+        if matches!(fall, Fall::Landed | Fall::Lost) {
+            world_blocks.commands().entity(entity).despawn_recursive();
+        }
+    }
+}
+
+/// What became of a falling block after one step
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Fall {
+    Falling,
+    /// Held still until the chunk below loads
+    Waiting,
+    /// Placed into the world, or broken into a dropped item
+    Landed,
+    /// Fell into the void
+    Lost,
+}
+
+/// Move a falling block whose minimum corner is at `position` through `dt` seconds of gravity
+/// Every cell the bottom face sweeps through is checked, so fast blocks can't tunnel
+pub(crate) fn step_falling_block(world: &mut BlockWorld, falling: &mut FallingBlock, position: &mut Vec3, dt: f32) -> Fall {
+    falling.velocity_y = (falling.velocity_y - GRAVITY * dt).max(-TERMINAL_VELOCITY);
+
+    let old_y = position.y;
+    let new_y = old_y + falling.velocity_y * dt;
+    if new_y < VOID_Y {
+        return Fall::Lost;
+    }
+
+    let x = position.x.floor() as i32;
+    let z = position.z.floor() as i32;
+
+    let mut landed_on = None;
+    for cell_y in (new_y.floor() as i32..old_y.ceil() as i32).rev() {
+        match world.get_state(IVec3::new(x, cell_y, z)) {
+            Some(state) if state.is_air() => {}
+            Some(_) => {
+                landed_on = Some(cell_y);
+                break;
+            }
+            None => {
+                falling.velocity_y = 0.0;
+                return Fall::Waiting;
+            }
+        }
+    }
+
+    let Some(support_y) = landed_on else {
+        position.y = new_y;
+        return Fall::Falling;
+    };
+
+    let support = IVec3::new(x, support_y, z);
+    let target = support + IVec3::Y;
+    if world.is_solid(support) && world.can_fall_into(target) {
+        world.set_block(target, falling.state, SetBlockFlags::DEFAULT);
+    } else {
+        world.spawn(WorldSpawn::BlockDrop { pos: target.as_vec3() + Vec3::splat(0.5), block: falling.state.id });
+    }
+    Fall::Landed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::{TestWorld, FALL_DELAY_TICKS};
+
+    /// A one-block stone floor at y=0 with air above and below it
+    fn floor() -> TestWorld {
+        TestWorld::new(IVec3::new(-2, -16, -2), IVec3::new(2, 16, 2))
+    }
+
+    /// Step a block falling from `from` at `dt` seconds a step until it lands, returning
+    /// what landing spawned
+    fn drop_block(world: &mut TestWorld, from: Vec3, name: &str, dt: f32) -> Vec<WorldSpawn> {
+        let mut falling = FallingBlock { state: BlockState::new(world.id(name)), velocity_y: 0.0 };
+        let mut position = from;
+        for _ in 0..1000 {
+            let (fall, spawns) = world.edit(|world| (step_falling_block(world, &mut falling, &mut position, dt), world.take_spawns()));
+            if fall == Fall::Landed {
+                return spawns;
+            }
+            assert_eq!(fall, Fall::Falling);
+        }
+        panic!("the block never landed");
+    }
+
+    #[test]
+    fn sand_losing_its_support_falls_after_the_delay() {
+        let mut world = floor();
+        world.place(IVec3::new(0, 1, 0), "core:stone");
+        world.place(IVec3::new(0, 2, 0), "core:sand");
+        let sand = world.id("core:sand");
+        assert!(world.scheduled_ticks.is_empty(), "supported sand stays put");
+
+        world.edit(|world| world.set_block(IVec3::new(0, 1, 0), BlockState::AIR, SetBlockFlags::DEFAULT));
+        for _ in 1..FALL_DELAY_TICKS {
+            assert!(world.run_tick().is_empty());
+            assert_eq!(world.block(IVec3::new(0, 2, 0)), sand);
+        }
+        assert_eq!(world.run_tick(), [WorldSpawn::FallingBlock { pos: IVec3::new(0, 2, 0), state: BlockState::new(sand) }]);
+        assert!(world.block(IVec3::new(0, 2, 0)).is_air());
+    }
+
+    #[test]
+    fn falling_blocks_land_on_the_first_solid_block_below() {
+        let mut world = floor();
+        world.place(IVec3::new(0, 3, 0), "core:stone");
+        assert!(drop_block(&mut world, Vec3::new(0.0, 10.0, 0.0), "core:gravel", 0.05).is_empty());
+        assert_eq!(world.block(IVec3::new(0, 4, 0)), world.id("core:gravel"));
+        assert!(world.block(IVec3::new(0, 1, 0)).is_air());
+    }
+
+    #[test]
+    fn falling_blocks_break_on_non_full_blocks() {
+        let mut world = floor();
+        world.place(IVec3::new(0, 1, 0), "core:wire");
+        let sand = world.id("core:sand");
+        assert_eq!(
+            drop_block(&mut world, Vec3::new(0.0, 6.0, 0.0), "core:sand", 0.05),
+            [WorldSpawn::BlockDrop { pos: Vec3::new(0.5, 2.5, 0.5), block: sand }],
+        );
+        assert_eq!(world.block(IVec3::new(0, 1, 0)), world.id("core:wire"));
+        assert!(world.block(IVec3::new(0, 2, 0)).is_air());
+    }
+
+    #[test]
+    fn fast_falls_cannot_tunnel_through_a_thin_floor() {
+        let mut world = floor();
+        // A whole second a step falls past the floor in one step, at terminal velocity
+        assert!(drop_block(&mut world, Vec3::new(1.0, 12.0, 1.0), "core:sand", 1.0).is_empty());
+        assert_eq!(world.block(IVec3::new(1, 1, 1)), world.id("core:sand"));
+        assert!((-16..0).all(|y| world.block(IVec3::new(1, y, 1)).is_air()));
+    }
+}
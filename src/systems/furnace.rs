@@ -0,0 +1,200 @@
+use bevy::prelude::*;
+use bevy::window::{PrimaryWindow, CursorGrabMode};
+use crate::blocks::{BlockRegistry, BlockState};
+use crate::components::{FurnaceBurnFill, FurnaceProgressFill, FurnaceScreenRoot, FurnaceSlotButton, FurnaceSlotLabel};
+use crate::crafting::{Furnace, FurnaceSlot, RecipeRegistry};
+use crate::items::{fuel_ticks, ItemRegistry};
+use crate::resources::{FurnaceScreen, GameState, ItemStack, PlayerInventory};
+use crate::world::{BlockEntity, Chunk, ChunkManager, SetBlockFlags, WorldSpawn, world_to_chunk_local};
+use super::block_updates::WorldBlocks;
+use super::crafting::stack_label;
+
+/// Smelt in every loaded furnace, once per world tick
+/// Runs whether or not anyone has the furnace open. Furnaces swap between their lit and
+/// unlit blocks as fuel starts and stops burning - without callbacks, so the block entity
+/// holding the furnace's contents stays in place
+pub fn tick_furnaces(
+    mut world_blocks: WorldBlocks,
+    recipes: Res<RecipeRegistry>,
+    item_registry: Res<ItemRegistry>,
+) {
+    let positions = world_blocks.block_entity_positions();
+    if positions.is_empty() {
+        return;
+    }
+    let registry = world_blocks.registry();
+    let (Some(unlit), Some(lit)) = (registry.get_id("core:furnace"), registry.get_id("core:furnace_lit")) else {
+        return;
+    };
+
+    world_blocks.edit(|world| {
+        let blocks = world.registry();
+        for pos in positions {
+            let Some(BlockEntity::Furnace(furnace)) = world.block_entity_mut(pos) else {
+                continue;
+            };
+            furnace.tick(&recipes, |item| fuel_ticks(item, &item_registry, blocks));
+
+            let target = if furnace.is_lit() { lit } else { unlit };
+            let Some(state) = world.get_state(pos) else {
+                continue;
+            };
+            if state.id != target && (state.id == lit || state.id == unlit) {
+                world.set_block(pos, BlockState { id: target, ..state }, SetBlockFlags::NOTIFY_NEIGHBORS);
+            }
+        }
+    });
+}
+
+/// Open the furnace screen when a furnace is used
+pub fn open_furnace_screen(
+    mut spawn_events: EventReader<WorldSpawn>,
+    mut screen: ResMut<FurnaceScreen>,
+    mut game_state: ResMut<GameState>,
+    mut windows: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    for event in spawn_events.read() {
+        let WorldSpawn::OpenFurnace { pos } = *event else {
+            continue;
+        };
+        screen.furnace = Some(pos);
+        game_state.screen_open = true;
+        if let Ok(mut window) = windows.get_single_mut() {
+            window.cursor_options.grab_mode = CursorGrabMode::None;
+            window.cursor_options.visible = true;
+        }
+    }
+}
+
+/// Close the furnace screen on E or Escape, on death, or when the furnace is gone
+/// (broken, or unloaded with its chunk)
+pub fn close_furnace_screen(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut screen: ResMut<FurnaceScreen>,
+    mut game_state: ResMut<GameState>,
+    chunk_manager: Res<ChunkManager>,
+    chunks: Query<&Chunk>,
+    mut windows: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    let Some(pos) = screen.furnace else {
+        return;
+    };
+    let close_pressed = keyboard_input.just_pressed(KeyCode::KeyE) || keyboard_input.just_pressed(KeyCode::Escape);
+    let furnace_gone = furnace_at(&chunk_manager, &chunks, pos).is_none();
+    if !close_pressed && !game_state.dead && !furnace_gone {
+        return;
+    }
+
+    screen.furnace = None;
+    game_state.screen_open = false;
+    // The death screen keeps the cursor free
+    if !game_state.dead {
+        if let Ok(mut window) = windows.get_single_mut() {
+            window.cursor_options.grab_mode = CursorGrabMode::Locked;
+            window.cursor_options.visible = false;
+        }
+    }
+}
+
+/// Clicks on the furnace screen
+/// Left click on the input or fuel slot moves one of the held item into it (the fuel slot
+/// only takes items that burn), right click takes the slot back, and clicking the output
+/// takes the smelted items
+pub fn handle_furnace_clicks(
+    mouse_button: Res<ButtonInput<MouseButton>>,
+    slot_query: Query<(&Interaction, &FurnaceSlotButton)>,
+    screen: Res<FurnaceScreen>,
+    mut inventory: ResMut<PlayerInventory>,
+    mut world_blocks: WorldBlocks,
+    item_registry: Res<ItemRegistry>,
+    block_registry: Res<BlockRegistry>,
+) {
+    let Some(pos) = screen.furnace else {
+        return;
+    };
+    let left = mouse_button.just_pressed(MouseButton::Left);
+    let right = mouse_button.just_pressed(MouseButton::Right);
+    if !left && !right {
+        return;
+    }
+    let Some(BlockEntity::Furnace(furnace)) = world_blocks.block_entity_mut(pos) else {
+        return;
+    };
+
+    for (interaction, button) in slot_query.iter() {
+        if *interaction == Interaction::None {
+            continue;
+        }
+        let slot = button.slot;
+        if left && slot != FurnaceSlot::Output {
+            let held = inventory.hotbar[inventory.selected_slot];
+            let accepted = held.filter(|stack| {
+                slot != FurnaceSlot::Fuel || fuel_ticks(stack.item, &item_registry, &block_registry).is_some()
+            });
+            if let Some(stack) = accepted.filter(|&stack| furnace.place_one(slot, stack)) {
+                inventory.consume_selected();
+                debug!("Placed {:?} in furnace slot {:?}", stack.item, slot);
+            }
+        } else if let Some(stack) = furnace.slot_mut(slot).take() {
+            let left_over = inventory.add_stack(stack);
+            if left_over > 0 {
+                *furnace.slot_mut(slot) = Some(ItemStack { count: left_over, ..stack });
+            }
+        }
+    }
+}
+
+/// Show or hide the furnace screen and, while it's open, refresh its slots and gauges
+/// from the furnace's block entity (it keeps smelting underneath the open screen)
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub fn update_furnace_screen(
+    screen: Res<FurnaceScreen>,
+    chunk_manager: Res<ChunkManager>,
+    chunks: Query<&Chunk>,
+    item_registry: Res<ItemRegistry>,
+    block_registry: Res<BlockRegistry>,
+    mut root_query: Query<&mut Visibility, With<FurnaceScreenRoot>>,
+    mut label_query: Query<(&FurnaceSlotLabel, &mut Text)>,
+    mut burn_query: Query<&mut Node, (With<FurnaceBurnFill>, Without<FurnaceProgressFill>)>,
+    mut progress_query: Query<&mut Node, (With<FurnaceProgressFill>, Without<FurnaceBurnFill>)>,
+    mut button_query: Query<(&Interaction, &mut BackgroundColor), (With<FurnaceSlotButton>, Changed<Interaction>)>,
+) {
+    for (interaction, mut color) in button_query.iter_mut() {
+        *color = BackgroundColor(match *interaction {
+            Interaction::None => Color::srgb(0.15, 0.15, 0.15),
+            _ => Color::srgb(0.25, 0.25, 0.25),
+        });
+    }
+
+    if screen.is_changed() {
+        for mut visibility in root_query.iter_mut() {
+            *visibility = if screen.is_open() { Visibility::Visible } else { Visibility::Hidden };
+        }
+    }
+
+    let Some(furnace) = screen.furnace.and_then(|pos| furnace_at(&chunk_manager, &chunks, pos)) else {
+        return;
+    };
+    for (label, mut text) in label_query.iter_mut() {
+        let new_text = furnace.slot(label.slot)
+            .map_or_else(String::new, |stack| stack_label(stack, &item_registry, &block_registry));
+        if **text != new_text {
+            **text = new_text;
+        }
+    }
+    for mut node in burn_query.iter_mut() {
+        node.width = Val::Percent(furnace.burn_fraction() * 100.0);
+    }
+    for mut node in progress_query.iter_mut() {
+        node.width = Val::Percent(furnace.cook_fraction() * 100.0);
+    }
+}
+
+/// The furnace block entity at a world position, if it's loaded
+fn furnace_at<'a>(chunk_manager: &ChunkManager, chunks: &'a Query<&Chunk>, pos: IVec3) -> Option<&'a Furnace> {
+    let (coord, local) = world_to_chunk_local(pos);
+    let chunk = chunks.get(*chunk_manager.loaded_chunks.get(&coord)?).ok()?;
+    match chunk.block_entity(local.x as usize, local.y as usize, local.z as usize)? {
+        BlockEntity::Furnace(furnace) => Some(furnace),
+    }
+}
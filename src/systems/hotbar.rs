@@ -1,8 +1,9 @@
 use bevy::prelude::*;
 use bevy::input::mouse::MouseWheel;
-use crate::components::{HotbarSlot, HotbarSlotIcon, HotbarSlotText};
+use crate::components::{HotbarSlot, HotbarSlotCount, HotbarSlotDurability, HotbarSlotDurabilityFill, HotbarSlotIcon, HotbarSlotText};
 use crate::resources::{PlayerInventory, HotbarItem};
 use crate::blocks::BlockRegistry;
+use crate::items::ItemRegistry;
 use crate::assets::AssetManager;
 use crate::rendering::{render_isometric_cube, IsometricIconCache};
 
@@ -70,9 +71,11 @@ pub fn hotbar_number_key_selection(
 }
 
 /// Update hotbar slot icons to show isometric block cubes
+#[allow(clippy::too_many_arguments)]
 pub fn update_hotbar_icons(
     inventory: Res<PlayerInventory>,
     block_registry: Res<BlockRegistry>,
+    item_registry: Res<ItemRegistry>,
     asset_manager: Res<AssetManager>,
     mut icon_cache: ResMut<IsometricIconCache>,
     mut images: ResMut<Assets<Image>>,
@@ -92,7 +95,7 @@ pub fn update_hotbar_icons(
     // First pass: collect block IDs that need isometric icons generated
     let mut blocks_to_render: Vec<crate::blocks::BlockId> = Vec::new();
     for (icon, _, _) in icon_query.iter() {
-        if let Some(HotbarItem::Block(block_id)) = inventory.hotbar[icon.slot_index] {
+        if let Some(HotbarItem::Block(block_id)) = inventory.hotbar[icon.slot_index].map(|stack| stack.item) {
             if icon_cache.get(block_id).is_none() {
                 if !blocks_to_render.contains(&block_id) {
                     blocks_to_render.push(block_id);
@@ -127,7 +130,7 @@ pub fn update_hotbar_icons(
 
     // Second pass: update the UI icons (for blocks)
     for (icon, mut image_node, mut visibility) in icon_query.iter_mut() {
-        match inventory.hotbar[icon.slot_index] {
+        match inventory.hotbar[icon.slot_index].map(|stack| stack.item) {
             Some(HotbarItem::Block(block_id)) => {
                 if let Some(cached_handle) = icon_cache.get(block_id) {
                     image_node.image = cached_handle.clone();
@@ -149,10 +152,10 @@ pub fn update_hotbar_icons(
 
     // Third pass: update the text labels (for items without textures)
     for (text_slot, mut text, mut visibility) in text_query.iter_mut() {
-        match inventory.hotbar[text_slot.slot_index] {
+        match inventory.hotbar[text_slot.slot_index].map(|stack| stack.item) {
             Some(HotbarItem::Item(item_id)) => {
                 // Show item name as placeholder text
-                **text = item_id.name().to_string();
+                **text = item_registry.name(item_id).to_string();
                 *visibility = Visibility::Inherited;
             }
             _ => {
@@ -162,3 +165,54 @@ pub fn update_hotbar_icons(
         }
     }
 }
+
+/// Show stack sizes in the slot corners (single items show no number)
+pub fn update_hotbar_counts(
+    inventory: Res<PlayerInventory>,
+    mut count_query: Query<(&HotbarSlotCount, &mut Text)>,
+) {
+    if !inventory.is_changed() {
+        return;
+    }
+
+    for (slot, mut text) in count_query.iter_mut() {
+        **text = match inventory.hotbar[slot.slot_index] {
+            Some(stack) if stack.count > 1 => stack.count.to_string(),
+            _ => String::new(),
+        };
+    }
+}
+
+/// Show a durability bar on worn tools, going from green to red as it wears down
+pub fn update_hotbar_durability(
+    inventory: Res<PlayerInventory>,
+    item_registry: Res<ItemRegistry>,
+    mut bar_query: Query<(&HotbarSlotDurability, &mut Visibility)>,
+    mut fill_query: Query<(&HotbarSlotDurabilityFill, &mut Node, &mut BackgroundColor)>,
+) {
+    if !inventory.is_changed() {
+        return;
+    }
+
+    // Fraction of durability left, None when there's no bar to show
+    let fraction_left = |slot_index: usize| {
+        let stack = inventory.hotbar[slot_index]?;
+        let HotbarItem::Item(item) = stack.item else {
+            return None;
+        };
+        let max = item_registry.get_item(item)?.properties.max_durability()?;
+        let left = stack.durability?;
+        (left < max).then(|| left as f32 / max as f32)
+    };
+
+    for (bar, mut visibility) in bar_query.iter_mut() {
+        *visibility = if fraction_left(bar.slot_index).is_some() { Visibility::Inherited } else { Visibility::Hidden };
+    }
+
+    for (fill, mut node, mut color) in fill_query.iter_mut() {
+        if let Some(fraction) = fraction_left(fill.slot_index) {
+            node.width = Val::Percent(fraction * 100.0);
+            *color = BackgroundColor(Color::srgb(1.0 - fraction, fraction, 0.0));
+        }
+    }
+}
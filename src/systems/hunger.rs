@@ -0,0 +1,186 @@
+use bevy::prelude::*;
+use crate::components::{GameMode, Player};
+use crate::items::{FoodProperties, ItemRegistry};
+use crate::resources::{GameState, PlayerInventory, PlayerStats};
+use super::damage::{DamageSource, PlayerDamage};
+
+/// Exhaustion that costs one point of saturation (or hunger once saturation is gone)
+pub const EXHAUSTION_PER_POINT: f32 = 4.0;
+
+/// Exhaustion added by player actions
+pub const SPRINT_EXHAUSTION_PER_BLOCK: f32 = 0.1;
+pub const JUMP_EXHAUSTION: f32 = 0.05;
+pub const SPRINT_JUMP_EXHAUSTION: f32 = 0.2;
+pub const MINING_EXHAUSTION: f32 = 0.005;
+pub const DAMAGE_EXHAUSTION: f32 = 0.1;
+const REGEN_EXHAUSTION: f32 = 6.0;
+
+/// Hunger needed for natural regeneration, and the level at or below which sprinting stops
+const REGEN_HUNGER: u8 = 18;
+pub const SPRINT_HUNGER: u8 = 6;
+
+/// Ticks between regeneration steps (fast when full and saturated) and starvation hits
+const FAST_REGEN_TICKS: u16 = 10;
+const FOOD_TICKS: u16 = 80;
+const STARVATION_DAMAGE: u8 = 1;
+
+/// Holding right-click this long eats the selected food
+const EAT_SECONDS: f32 = 1.6;
+
+/// Advance the food state by one tick
+/// Drains exhaustion into saturation and hunger, then regenerates health when fed
+/// Returns the starvation damage to deal this tick
+pub fn food_tick(stats: &mut PlayerStats) -> u8 {
+    while stats.exhaustion >= EXHAUSTION_PER_POINT {
+        stats.exhaustion -= EXHAUSTION_PER_POINT;
+        if stats.saturation > 0.0 {
+            stats.saturation = (stats.saturation - 1.0).max(0.0);
+        } else {
+            stats.hunger = stats.hunger.saturating_sub(1);
+        }
+    }
+
+    let hurt = stats.health < stats.max_health;
+    let saturated = stats.saturation > 0.0 && stats.hunger >= stats.max_hunger;
+    let interval = if saturated && hurt { FAST_REGEN_TICKS } else { FOOD_TICKS };
+
+    if !(hurt && stats.hunger >= REGEN_HUNGER) && stats.hunger > 0 {
+        stats.food_timer = 0;
+        return 0;
+    }
+
+    stats.food_timer += 1;
+    if stats.food_timer < interval {
+        return 0;
+    }
+    stats.food_timer = 0;
+
+    if stats.hunger == 0 {
+        return STARVATION_DAMAGE;
+    }
+
+    stats.health = (stats.health + 1).min(stats.max_health);
+    stats.add_exhaustion(REGEN_EXHAUSTION);
+    0
+}
+
+/// Restore hunger and saturation from a food item
+/// Saturation is capped by the hunger level, so it can never exceed what's been eaten
+pub fn eat(stats: &mut PlayerStats, food: FoodProperties) {
+    stats.hunger = (stats.hunger + food.nutrition).min(stats.max_hunger);
+    let gained = food.nutrition as f32 * food.saturation_modifier * 2.0;
+    stats.saturation = (stats.saturation + gained).min(stats.hunger as f32);
+}
+
+/// Run the hunger simulation on the fixed timestep for every player (see `update_environment_damage`
+/// for whose `PlayerStats` are used)
+/// Invulnerable game modes don't get hungry, so their exhaustion is simply discarded
+pub fn update_hunger(
+    mut player_query: Query<(Entity, &GameMode, Option<&mut PlayerStats>), With<Player>>,
+    mut local_stats: ResMut<PlayerStats>,
+    mut damage: EventWriter<PlayerDamage>,
+) {
+    for (player, mode, own_stats) in player_query.iter_mut() {
+        let mut stats = match own_stats {
+            Some(stats) => stats,
+            None => local_stats.reborrow(),
+        };
+        if mode.abilities().invulnerable {
+            if stats.exhaustion > 0.0 {
+                stats.exhaustion = 0.0;
+            }
+            continue;
+        }
+
+        let starvation = food_tick(&mut stats);
+        if starvation > 0 {
+            damage.send(PlayerDamage { player, source: DamageSource::Starvation, amount: starvation });
+        }
+    }
+}
+
+/// Eat the selected food by holding right-click
+/// Letting go, switching slots or being full cancels the bite
+#[allow(clippy::too_many_arguments)]
+pub fn eat_held_food(
+    time: Res<Time>,
+    mouse_button: Res<ButtonInput<MouseButton>>,
+    game_state: Res<GameState>,
+    player_query: Query<&GameMode, With<Player>>,
+    item_registry: Res<ItemRegistry>,
+    mut inventory: ResMut<PlayerInventory>,
+    mut stats: ResMut<PlayerStats>,
+    mut progress: Local<Option<(usize, f32)>>,
+) {
+    let food = inventory.get_selected_item()
+        .and_then(|item| item_registry.get_item(item))
+        .and_then(|item| item.properties.food);
+    let hungry = stats.hunger < stats.max_hunger
+        && player_query.get_single().is_ok_and(|mode| mode.can_interact());
+
+    let Some(food) = food.filter(|_| hungry && game_state.accepts_gameplay_input() && mouse_button.pressed(MouseButton::Right)) else {
+        *progress = None;
+        return;
+    };
+
+    let slot = inventory.selected_slot;
+    let elapsed = match *progress {
+        Some((eating_slot, elapsed)) if eating_slot == slot => elapsed + time.delta_secs(),
+        _ => 0.0,
+    };
+
+    if elapsed < EAT_SECONDS {
+        *progress = Some((slot, elapsed));
+        return;
+    }
+
+    eat(&mut stats, food);
+    inventory.consume_selected();
+    *progress = None;
+    info!("Ate food (hunger {}, saturation {:.1})", stats.hunger, stats.saturation);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fed(health: u8, hunger: u8, saturation: f32) -> PlayerStats {
+        PlayerStats { health, hunger, saturation, ..Default::default() }
+    }
+
+    #[test]
+    fn exhaustion_drains_saturation_before_hunger() {
+        let mut stats = fed(20, 20, 1.0);
+        stats.add_exhaustion(EXHAUSTION_PER_POINT * 2.0);
+        food_tick(&mut stats);
+        assert_eq!(stats.saturation, 0.0);
+        assert_eq!(stats.hunger, 19);
+    }
+
+    #[test]
+    fn regenerates_only_when_fed() {
+        let mut well_fed = fed(10, 18, 0.0);
+        let mut hungry = fed(10, 17, 0.0);
+        for _ in 0..FOOD_TICKS {
+            food_tick(&mut well_fed);
+            food_tick(&mut hungry);
+        }
+        assert_eq!(well_fed.health, 11);
+        assert_eq!(hungry.health, 10);
+    }
+
+    #[test]
+    fn starves_at_zero_hunger() {
+        let mut stats = fed(10, 0, 0.0);
+        let damage: u32 = (0..FOOD_TICKS * 2).map(|_| food_tick(&mut stats) as u32).sum();
+        assert_eq!(damage, 2 * STARVATION_DAMAGE as u32);
+    }
+
+    #[test]
+    fn saturation_is_capped_by_hunger() {
+        let mut stats = fed(20, 2, 0.0);
+        eat(&mut stats, FoodProperties { nutrition: 2, saturation_modifier: 10.0 });
+        assert_eq!(stats.hunger, 4);
+        assert_eq!(stats.saturation, 4.0);
+    }
+}
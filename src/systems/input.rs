@@ -1,14 +1,17 @@
 use bevy::prelude::*;
 use bevy::window::{PrimaryWindow, CursorGrabMode};
-use crate::components::DebugOverlay;
+use crate::components::{CameraController, DebugOverlay, GameMode, Player};
 use crate::resources::{GameState, ChunkBorderState, ChunkBorderMode};
+use super::SendChat;
 
 pub fn toggle_pause_menu(
     keyboard_input: Res<ButtonInput<KeyCode>>,
     mut game_state: ResMut<GameState>,
     mut windows: Query<&mut Window, With<PrimaryWindow>>,
 ) {
-    if keyboard_input.just_pressed(KeyCode::Escape) {
+    // The death screen can't be dismissed, only left through respawning,
+    // and Escape on a block screen closes that screen instead
+    if keyboard_input.just_pressed(KeyCode::Escape) && !game_state.dead && !game_state.screen_open {
         game_state.paused = !game_state.paused;
 
         // Toggle cursor grab mode
@@ -69,13 +72,35 @@ pub fn take_screenshot(
     }
 }
 
-pub fn toggle_creative_mode(
+pub fn cycle_camera_mode(
     keyboard_input: Res<ButtonInput<KeyCode>>,
-    mut game_state: ResMut<GameState>,
+    mut query: Query<&mut CameraController>,
+) {
+    if keyboard_input.just_pressed(KeyCode::F5) {
+        // Cycle: first person -> third person back -> third person front
+        for mut controller in query.iter_mut() {
+            controller.mode = controller.mode.next();
+            info!("Camera mode: {:?}", controller.mode);
+        }
+    }
+}
+
+/// Ctrl+Z undoes the player's last block edit and Ctrl+Y (or Ctrl+Shift+Z) redoes it, in creative mode
+/// Sent as `/undo` and `/redo` chat commands, so a server runs them against its own copy of the world
+pub fn undo_redo_keys(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    player_query: Query<&GameMode, With<Player>>,
+    mut chat: EventWriter<SendChat>,
 ) {
-    if keyboard_input.just_pressed(KeyCode::F4) {
-        game_state.creative_mode = !game_state.creative_mode;
-        let mode = if game_state.creative_mode { "Creative" } else { "Survival" };
-        info!("Game mode changed to: {}", mode);
+    if !keyboard_input.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight])
+        || player_query.get_single().ok() != Some(&GameMode::Creative)
+    {
+        return;
+    }
+    let shift = keyboard_input.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+    if keyboard_input.just_pressed(KeyCode::KeyY) || (shift && keyboard_input.just_pressed(KeyCode::KeyZ)) {
+        chat.send(SendChat("/redo".to_string()));
+    } else if keyboard_input.just_pressed(KeyCode::KeyZ) {
+        chat.send(SendChat("/undo".to_string()));
     }
 }
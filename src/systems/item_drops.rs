@@ -0,0 +1,189 @@
+use bevy::prelude::*;
+use crate::assets::AssetManager;
+use crate::blocks::BlockRegistry;
+use crate::components::{GameMode, Player, GRAVITY, PLAYER_HEIGHT};
+use crate::physics::{CollisionBox, MoveOptions, TERMINAL_VELOCITY, move_and_collide};
+use crate::rendering::terrain_material::TerrainMaterial;
+use crate::resources::{HotbarItem, ItemStack, PlayerInventory};
+use crate::world::mesh_gen::create_single_block_mesh;
+use crate::world::WorldSpawn;
+use crate::systems::WorldBlocks;
+use super::falling_blocks::{block_entity_material, VOID_Y};
+
+/// Edge length of a dropped block's model
+const DROP_SIZE: f32 = 0.25;
+
+/// Seconds before a fresh drop can be picked up
+const PICKUP_DELAY: f32 = 0.5;
+
+/// Distance from the player's body at which drops are picked up
+const PICKUP_RADIUS: f32 = 1.5;
+
+/// Drops that aren't picked up disappear after 5 minutes
+const DESPAWN_SECONDS: f32 = 300.0;
+
+/// Spin speed of dropped items in radians/s
+const SPIN_SPEED: f32 = 1.5;
+
+/// A dropped item lying in the world, waiting to be picked up
+/// The transform holds the center of the item's model
+#[derive(Component)]
+pub struct ItemDrop {
+    pub stack: ItemStack,
+    pub velocity_y: f32,
+    pub age: f32,
+}
+
+/// Request to drop an inventory item into the world (e.g., the inventory on death)
+#[derive(Event, Debug, Clone, Copy)]
+pub struct DropItem {
+    pub pos: Vec3,
+    pub stack: ItemStack,
+}
+
+/// Spawn an entity for every item dropped this frame; `attach_item_drop_models` adds its model
+pub fn spawn_item_drops(
+    mut commands: Commands,
+    mut spawn_events: EventReader<WorldSpawn>,
+    mut drop_events: EventReader<DropItem>,
+) {
+    let block_drops = spawn_events.read().filter_map(|event| match *event {
+        WorldSpawn::BlockDrop { pos, block } => Some(DropItem { pos, stack: ItemStack::one(HotbarItem::Block(block)) }),
+        WorldSpawn::ItemDrop { pos, stack } => Some(DropItem { pos, stack }),
+        _ => None,
+    });
+    let drops: Vec<DropItem> = block_drops.chain(drop_events.read().copied()).collect();
+
+    for DropItem { pos, stack } in drops {
+        commands.spawn((
+            Transform::from_translation(pos),
+            ItemDrop { stack, velocity_y: 0.0, age: 0.0 },
+        ));
+    }
+}
+
+/// Give newly dropped items a small spinning model
+#[allow(clippy::too_many_arguments)]
+pub fn attach_item_drop_models(
+    mut commands: Commands,
+    drops: Query<(Entity, &ItemDrop), Added<ItemDrop>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<TerrainMaterial>>,
+    mut standard_materials: ResMut<Assets<StandardMaterial>>,
+    asset_manager: Res<AssetManager>,
+    block_registry: Res<BlockRegistry>,
+    mut material: Local<Option<Handle<TerrainMaterial>>>,
+) {
+    for (entity, drop) in drops.iter() {
+        let mut parent = commands.entity(entity);
+        if !spawn_drop_model(&mut parent, drop.stack.item, &mut meshes, &mut materials, &mut standard_materials, &asset_manager, &block_registry, &mut material) {
+            parent.despawn();
+        }
+    }
+}
+
+/// Add the model of a dropped `item` as a child of `drop`: blocks get a scaled-down block mesh,
+/// other items a plain colored cube
+/// The parent sits at the drop's center; false when the block has no mesh to show
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn spawn_drop_model(
+    drop: &mut EntityCommands,
+    item: HotbarItem,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<TerrainMaterial>,
+    standard_materials: &mut Assets<StandardMaterial>,
+    asset_manager: &AssetManager,
+    block_registry: &BlockRegistry,
+    material: &mut Option<Handle<TerrainMaterial>>,
+) -> bool {
+    match item {
+        HotbarItem::Block(block) => {
+            let Some(mesh) = create_single_block_mesh(block, block_registry) else {
+                return false;
+            };
+            let material = block_entity_material(material, asset_manager, materials);
+            // Offset the 0..1 block mesh so it's centered on the parent
+            drop.insert(Visibility::default()).with_children(|parent| {
+                parent.spawn((
+                    Mesh3d(meshes.add(mesh)),
+                    MeshMaterial3d(material),
+                    Transform::from_translation(Vec3::splat(-DROP_SIZE / 2.0)).with_scale(Vec3::splat(DROP_SIZE)),
+                ));
+            });
+        }
+        HotbarItem::Item(_) => {
+            let mesh = meshes.add(Cuboid::from_length(DROP_SIZE));
+            let material = standard_materials.add(StandardMaterial {
+                base_color: Color::srgb(0.8, 0.6, 0.3),
+                ..default()
+            });
+            drop.insert(Visibility::default()).with_children(|parent| {
+                parent.spawn((Mesh3d(mesh), MeshMaterial3d(material)));
+            });
+        }
+    }
+    true
+}
+
+/// Drop physics, despawn timer and pickup into the hotbar of the nearest player in reach
+/// Players with their own `PlayerInventory` component (a server's clients) pick up into it,
+/// everyone else into the `PlayerInventory` resource
+#[allow(clippy::type_complexity)]
+pub fn update_item_drops(
+    time: Res<Time>,
+    mut world_blocks: WorldBlocks,
+    mut local_inventory: ResMut<PlayerInventory>,
+    mut player_query: Query<(Entity, &Transform, &GameMode, Option<&mut PlayerInventory>), (With<Player>, Without<ItemDrop>)>,
+    mut drops: Query<(Entity, &mut Transform, &mut ItemDrop)>,
+) {
+    let dt = time.delta_secs();
+    // Pick up around the middle of the body, spectators can't pick anything up
+    let player_bodies: Vec<(Entity, Vec3)> = player_query.iter()
+        .filter(|(_, _, game_mode, _)| game_mode.can_interact())
+        .map(|(entity, transform, _, _)| (entity, transform.translation + Vec3::Y * (PLAYER_HEIGHT * 0.5)))
+        .collect();
+
+    for (entity, mut transform, mut drop) in drops.iter_mut() {
+        drop.age += dt;
+        if drop.age > DESPAWN_SECONDS || transform.translation.y < VOID_Y {
+            world_blocks.commands().entity(entity).despawn_recursive();
+            continue;
+        }
+
+        let picker = player_bodies.iter()
+            .map(|&(player, body)| (player, transform.translation.distance(body)))
+            .filter(|&(_, distance)| drop.age > PICKUP_DELAY && distance < PICKUP_RADIUS)
+            .min_by(|a, b| a.1.total_cmp(&b.1));
+        if let Some((player, _)) = picker {
+            let mut inventory = match player_query.get_mut(player).ok().and_then(|(_, _, _, inventory)| inventory) {
+                Some(inventory) => inventory,
+                None => local_inventory.reborrow(),
+            };
+            // Only flag the inventory as changed when something actually fit
+            let left = inventory.bypass_change_detection().add_stack(drop.stack);
+            if left < drop.stack.count {
+                inventory.set_changed();
+            }
+            if left == 0 {
+                world_blocks.commands().entity(entity).despawn_recursive();
+                continue;
+            }
+            drop.stack.count = left;
+        }
+
+        transform.rotate_y(SPIN_SPEED * dt);
+
+        // Fall until the model rests on a solid block
+        if world_blocks.get_state(transform.translation.floor().as_ivec3()).is_none() {
+            // Chunk isn't loaded - hold still
+            continue;
+        }
+        drop.velocity_y = (drop.velocity_y - GRAVITY * dt).max(-TERMINAL_VELOCITY);
+        let body = CollisionBox::from_feet(transform.translation - Vec3::Y * (DROP_SIZE / 2.0), DROP_SIZE / 2.0, DROP_SIZE);
+        let result = move_and_collide(&world_blocks, body, Vec3::Y * drop.velocity_y * dt, MoveOptions::default());
+        transform.translation += result.offset;
+        if result.blocked.y {
+            drop.velocity_y = 0.0;
+        }
+    }
+}
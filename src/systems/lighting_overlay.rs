@@ -1,7 +1,8 @@
 use bevy::prelude::*;
 use bevy::render::mesh::{Indices, PrimitiveTopology};
 use bevy::render::render_asset::RenderAssetUsages;
-use crate::components::CameraController;
+use crate::blocks::BlockRegistry;
+use crate::components::Player;
 use crate::world::{Chunk, ChunkCoord, CHUNK_SIZE};
 use crate::systems::NeedsRemesh;
 
@@ -31,7 +32,26 @@ impl Default for LightingOverlayState {
 pub struct LightingOverlayMarker;
 
 /// Light level threshold - blocks with light < 7 can spawn hostile mobs in Minecraft
-const MOB_SPAWN_THRESHOLD: u8 = 7;
+pub const MOB_SPAWN_THRESHOLD: u8 = 7;
+
+/// How the light on a spawnable surface rates for hostile mobs
+/// The overlay draws these as markers and the mob spawner goes by the same rating
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpawnLight {
+    /// Light < 7 even in daylight: mobs can spawn now (red marker)
+    Dangerous,
+    /// Only lit by the sky: dangerous once the sky darkens (yellow marker)
+    DangerousAtNight,
+    /// Open to the sky or lit by a light source: never spawns (no marker)
+    Safe,
+}
+
+impl SpawnLight {
+    /// Whether a hostile mob can spawn here while the sky is at `sky_light_level`
+    pub fn spawns_at(self, sky_light: u8, block_light: u8, sky_light_level: u8) -> bool {
+        self != SpawnLight::Safe && sky_light.min(sky_light_level).max(block_light) < MOB_SPAWN_THRESHOLD
+    }
+}
 
 /// How far (in chunks) to render the overlay
 const OVERLAY_RENDER_DISTANCE: i32 = 2;
@@ -64,8 +84,9 @@ pub fn detect_chunk_changes(
 pub fn update_lighting_overlay(
     mut commands: Commands,
     mut overlay_state: ResMut<LightingOverlayState>,
-    camera_query: Query<&Transform, With<CameraController>>,
+    player_query: Query<&Transform, With<Player>>,
     chunk_query: Query<(&Chunk, &Transform)>,
+    registry: Res<BlockRegistry>,
     existing_markers: Query<Entity, With<LightingOverlayMarker>>,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
@@ -118,11 +139,11 @@ pub fn update_lighting_overlay(
     overlay_state.rebuild_delay = 0;
     overlay_state.needs_update = false;
 
-    let Ok(camera_transform) = camera_query.get_single() else {
+    let Ok(player_transform) = player_query.get_single() else {
         return;
     };
 
-    let player_chunk = ChunkCoord::from_world_pos(camera_transform.translation);
+    let player_chunk = ChunkCoord::from_world_pos(player_transform.translation);
 
     // Create materials for different light levels
     // Red: light level < 7 (mobs can spawn now)
@@ -165,6 +186,7 @@ pub fn update_lighting_overlay(
                         collect_low_light_positions(
                             chunk,
                             chunk_transform.translation,
+                            &registry,
                             &mut red_positions,
                             &mut yellow_positions,
                         );
@@ -201,34 +223,13 @@ pub fn update_lighting_overlay(
 fn collect_low_light_positions(
     chunk: &Chunk,
     chunk_world_pos: Vec3,
+    registry: &BlockRegistry,
     red_positions: &mut Vec<Vec3>,
     yellow_positions: &mut Vec<Vec3>,
 ) {
     for y in 0..CHUNK_SIZE {
         for z in 0..CHUNK_SIZE {
             for x in 0..CHUNK_SIZE {
-                let block = chunk.get_block(x, y, z);
-
-                // Skip non-solid blocks (we want to mark on top of solid blocks)
-                if block.is_air() {
-                    continue;
-                }
-
-                // Check if there's air above this block (spawnable surface)
-                let above_y = y + 1;
-                if above_y >= CHUNK_SIZE {
-                    // Would need to check neighbor chunk - skip for now
-                    continue;
-                }
-
-                let block_above = chunk.get_block(x, above_y, z);
-                if !block_above.is_air() {
-                    continue;
-                }
-
-                // Get the light level of the air block above
-                let light = chunk.get_light(x, above_y, z);
-
                 // World position of the marker (on top of the block)
                 let world_pos = chunk_world_pos + Vec3::new(
                     x as f32 + 0.5,
@@ -236,31 +237,65 @@ fn collect_low_light_positions(
                     z as f32 + 0.5,
                 );
 
-                if light < MOB_SPAWN_THRESHOLD {
-                    // Red: definitely dangerous (light < 7)
-                    red_positions.push(world_pos);
-                } else if light == 15 {
-                    // Light 15 with direct sky access = safe day AND night
-                    // Light 15 without direct sky access = yellow (could be from propagation)
-                    // Check if there's direct sky access by looking up in the same chunk
-                    let has_sky_access = has_direct_sky_access_in_chunk(chunk, x, above_y, z);
-                    if !has_sky_access {
-                        // No direct sky access but light is 15 - likely propagated from entrance
-                        // This could still be dangerous at night since the propagated light
-                        // from the entrance will drop
-                        yellow_positions.push(world_pos);
-                    }
-                    // If has_sky_access, it's truly safe - skip
-                } else {
-                    // Light 7-14: potentially dangerous at night
-                    // Yellow marker for all of these since they could drop below 7
-                    yellow_positions.push(world_pos);
+                match spawn_surface_light(chunk, x, y, z, registry) {
+                    Some(SpawnLight::Dangerous) => red_positions.push(world_pos),
+                    Some(SpawnLight::DangerousAtNight) => yellow_positions.push(world_pos),
+                    Some(SpawnLight::Safe) | None => {}
                 }
             }
         }
     }
 }
 
+/// Rate the surface on top of the block at (x, y, z) for hostile spawns,
+/// None if it isn't a surface a mob could stand on
+pub fn spawn_surface_light(chunk: &Chunk, x: usize, y: usize, z: usize, registry: &BlockRegistry) -> Option<SpawnLight> {
+    // Mobs stand on top of solid blocks (not on liquids, torches or plants)
+    let floor = registry.get_block(chunk.get_block(x, y, z))?;
+    if !floor.properties.is_solid || floor.properties.is_liquid {
+        return None;
+    }
+
+    // Check if there's air above this block (spawnable surface)
+    let above_y = y + 1;
+    if above_y >= CHUNK_SIZE {
+        // Would need to check neighbor chunk - skip for now
+        return None;
+    }
+
+    let block_above = chunk.get_block(x, above_y, z);
+    if !block_above.is_air() {
+        return None;
+    }
+
+    // Get the light levels of the air block above
+    let (light, block_light) = chunk.get_light_pair(x, above_y, z);
+
+    if block_light >= MOB_SPAWN_THRESHOLD {
+        // Lit by a torch or other light source, which doesn't change at night
+        Some(SpawnLight::Safe)
+    } else if light < MOB_SPAWN_THRESHOLD {
+        // Red: definitely dangerous (light < 7)
+        Some(SpawnLight::Dangerous)
+    } else if light == 15 {
+        // Light 15 with direct sky access = safe day AND night
+        // Light 15 without direct sky access = yellow (could be from propagation)
+        // Check if there's direct sky access by looking up in the same chunk
+        if has_direct_sky_access_in_chunk(chunk, x, above_y, z) {
+            Some(SpawnLight::Safe)
+        } else {
+            // No direct sky access but light is 15 - likely propagated from entrance
+            // This could still be dangerous at night since the propagated light
+            // from the entrance will drop
+            Some(SpawnLight::DangerousAtNight)
+        }
+    } else {
+        // Light 7-14: potentially dangerous at night
+        // Yellow marker for all of these since they could drop below 7
+        Some(SpawnLight::DangerousAtNight)
+    }
+}
+
 /// Check if a position has direct vertical sky access within the same chunk
 /// This is a simplified check - it only looks up within this chunk
 fn has_direct_sky_access_in_chunk(chunk: &Chunk, x: usize, y: usize, z: usize) -> bool {
@@ -309,3 +344,42 @@ fn create_x_markers_mesh(positions: &[Vec3]) -> Mesh {
         .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, normals)
         .with_inserted_indices(Indices::U32(indices))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blocks::BlockType;
+
+    #[test]
+    fn spawn_light_follows_the_overlay_colors() {
+        let mut registry = BlockRegistry::new();
+        let stone = registry.register_block(BlockType::builder("test:stone", "Stone").build()).unwrap();
+        let torch = registry.register_block(BlockType::builder("test:torch", "Torch").solid(false).build()).unwrap();
+        let mut chunk = Chunk::new(ChunkCoord::new(0, 0, 0));
+        chunk.set_block(1, 4, 1, stone);
+        chunk.set_block(2, 4, 1, torch);
+        chunk.set_block(3, 4, 1, stone);
+        chunk.set_block(3, 5, 1, stone);
+
+        // Open sky at full light is safe at any time of day
+        assert_eq!(spawn_surface_light(&chunk, 1, 4, 1, &registry), Some(SpawnLight::Safe));
+        // Mobs don't stand on torches or inside blocks
+        assert_eq!(spawn_surface_light(&chunk, 2, 4, 1, &registry), None);
+        assert_eq!(spawn_surface_light(&chunk, 3, 4, 1, &registry), None);
+
+        chunk.set_light(1, 5, 1, 10);
+        let light = spawn_surface_light(&chunk, 1, 4, 1, &registry).unwrap();
+        assert_eq!(light, SpawnLight::DangerousAtNight);
+        assert!(!light.spawns_at(10, 0, 15));
+        assert!(light.spawns_at(10, 0, 4));
+
+        chunk.set_light(1, 5, 1, 3);
+        let light = spawn_surface_light(&chunk, 1, 4, 1, &registry).unwrap();
+        assert_eq!(light, SpawnLight::Dangerous);
+        assert!(light.spawns_at(3, 0, 15));
+
+        // A torch nearby makes the spot safe day and night
+        chunk.set_block_light(1, 5, 1, 8);
+        assert_eq!(spawn_surface_light(&chunk, 1, 4, 1, &registry), Some(SpawnLight::Safe));
+    }
+}
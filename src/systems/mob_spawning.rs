@@ -0,0 +1,340 @@
+use bevy::prelude::*;
+use rand::Rng;
+use crate::blocks::BlockRegistry;
+use crate::components::{Mob, MobData, Player};
+use crate::entities::{EntityRegistry, EntityType, EntityTypeId, MobCategory};
+use crate::physics::PhysicsBody;
+use crate::world::{Chunk, ChunkCoord, ChunkManager, WorldRng, CHUNK_SIZE, world_to_chunk_local};
+use super::lighting_overlay::{spawn_surface_light, SpawnLight};
+use super::mobs::{block_at, SpawnMob};
+use super::{NeedsPostProcess, SkyLightLevel};
+
+/// Chunks around the player that hostile mobs spawn in: horizontally, and above or below
+const SPAWN_CHUNK_RADIUS: i32 = 4;
+const SPAWN_CHUNK_HEIGHT: i32 = 2;
+
+/// Hostile mobs never spawn closer to the player than this (blocks)
+const MIN_SPAWN_DISTANCE: f32 = 24.0;
+
+/// How far down from a random spot the spawner looks for a floor
+const FLOOR_SEARCH_DEPTH: i32 = 16;
+
+/// Hostile mobs farther than this from the player despawn at once
+const DESPAWN_DISTANCE: f32 = 128.0;
+
+/// Hostile mobs farther than this despawn at random, on average after `RANDOM_DESPAWN_CHANCE` ticks
+const RANDOM_DESPAWN_DISTANCE: f32 = 32.0;
+const RANDOM_DESPAWN_CHANCE: u32 = 800;
+
+/// How far from the first mob of a pack the others may spawn (blocks, each axis)
+const GROUP_SPREAD: i32 = 4;
+
+/// One in this many newly generated chunks gets a herd of passive animals
+const PASSIVE_CHUNK_CHANCE: u32 = 8;
+
+/// Natural spawning category of an entity type, None for types that don't spawn naturally
+fn category(registry: &EntityRegistry, id: EntityTypeId) -> Option<MobCategory> {
+    registry.get_entity(id)?.properties.spawn.as_ref().map(|rule| rule.category)
+}
+
+/// Loaded mobs of a category, for its cap
+fn count_mobs(registry: &EntityRegistry, mobs: &Query<&Mob>, wanted: MobCategory) -> usize {
+    mobs.iter().filter(|mob| category(registry, mob.entity_type) == Some(wanted)).count()
+}
+
+/// Pick an entity type of the category by spawn weight
+fn pick_entity_type<'a>(registry: &'a EntityRegistry, wanted: MobCategory, rng: &mut impl Rng) -> Option<(EntityTypeId, &'a EntityType)> {
+    let candidates: Vec<_> = registry.iter()
+        .filter_map(|(id, entity_type)| {
+            let rule = entity_type.properties.spawn.as_ref().filter(|rule| rule.category == wanted && rule.weight > 0)?;
+            Some((id, entity_type, rule.weight))
+        })
+        .collect();
+    let total: u32 = candidates.iter().map(|&(_, _, weight)| weight).sum();
+    if total == 0 {
+        return None;
+    }
+    let mut roll = rng.gen_range(0..total);
+    for (id, entity_type, weight) in candidates {
+        if roll < weight {
+            return Some((id, entity_type));
+        }
+        roll -= weight;
+    }
+    None
+}
+
+/// A place a mob can spawn: on top of a floor block, with room for its body
+struct SpawnSpot {
+    /// Feet position, centered on the block
+    feet: Vec3,
+    light: SpawnLight,
+    sky_light: u8,
+    block_light: u8,
+}
+
+/// Where a mob of `entity_type` would stand on the block at `floor`, None if that block isn't
+/// a spawnable surface (the same check as the lighting overlay), isn't one the type spawns on,
+/// or the body doesn't fit above it
+fn spawn_spot(
+    floor: IVec3,
+    entity_type: &EntityType,
+    chunk_manager: &ChunkManager,
+    chunks: &Query<&Chunk>,
+    registry: &BlockRegistry,
+) -> Option<SpawnSpot> {
+    let (coord, local) = world_to_chunk_local(floor);
+    let chunk = chunks.get(*chunk_manager.loaded_chunks.get(&coord)?).ok()?;
+    let (x, y, z) = (local.x as usize, local.y as usize, local.z as usize);
+    let light = spawn_surface_light(chunk, x, y, z, registry)?;
+
+    let rule = entity_type.properties.spawn.as_ref()?;
+    let floor_block = chunk.get_block(x, y, z);
+    if !rule.floors.is_empty() && !rule.floors.iter().any(|id| registry.get_id(id) == Some(floor_block)) {
+        return None;
+    }
+
+    // The surface check saw air right above the floor; taller bodies need the cells above that clear too
+    let cells = entity_type.properties.height.ceil() as i32;
+    for dy in 2..=cells {
+        let state = block_at(chunk_manager, chunks, floor + IVec3::Y * dy)?;
+        let blocked = registry.get_block(state.id)
+            .is_some_and(|block| block.properties.is_solid || block.properties.is_liquid);
+        if blocked {
+            return None;
+        }
+    }
+
+    let (sky_light, block_light) = chunk.get_light_pair(x, y + 1, z);
+    Some(SpawnSpot { feet: floor.as_vec3() + Vec3::new(0.5, 1.0, 0.5), light, sky_light, block_light })
+}
+
+/// Look down from `top` for the first floor the mob can spawn on
+fn find_spot(
+    top: IVec3,
+    depth: i32,
+    entity_type: &EntityType,
+    chunk_manager: &ChunkManager,
+    chunks: &Query<&Chunk>,
+    registry: &BlockRegistry,
+) -> Option<SpawnSpot> {
+    (0..depth).find_map(|dy| spawn_spot(top - IVec3::Y * dy, entity_type, chunk_manager, chunks, registry))
+}
+
+/// How many mobs of `entity_type` a pack gets, never more than the `room` left under the category's cap
+fn group_size(entity_type: &EntityType, room: usize, rng: &mut impl Rng) -> usize {
+    let Some(rule) = &entity_type.properties.spawn else {
+        return 0;
+    };
+    let size = rng.gen_range(rule.group_min..=rule.group_max.max(rule.group_min)).max(1);
+    (size as usize).min(room)
+}
+
+/// Spawn a pack of `entity_type` around `first`: the others land on nearby floors that pass `accept`
+/// The pack stops at `room` mobs so it can't push the category past its cap
+#[allow(clippy::too_many_arguments)]
+fn spawn_group(
+    id: EntityTypeId,
+    entity_type: &EntityType,
+    first: SpawnSpot,
+    accept: impl Fn(&SpawnSpot) -> bool,
+    room: usize,
+    chunk_manager: &ChunkManager,
+    chunks: &Query<&Chunk>,
+    block_registry: &BlockRegistry,
+    registry: &EntityRegistry,
+    rng: &mut impl Rng,
+    spawn: &mut EventWriter<SpawnMob>,
+) -> usize {
+    let size = group_size(entity_type, room, rng);
+    if size == 0 {
+        return 0;
+    }
+    let origin = first.feet.floor().as_ivec3();
+    let mut spots = vec![first];
+    // A few tries per mob, since some offsets land on walls or in the air
+    for _ in 0..size * 4 {
+        if spots.len() >= size {
+            break;
+        }
+        let offset = IVec3::new(rng.gen_range(-GROUP_SPREAD..=GROUP_SPREAD), 2, rng.gen_range(-GROUP_SPREAD..=GROUP_SPREAD));
+        if let Some(spot) = find_spot(origin + offset, 4, entity_type, chunk_manager, chunks, block_registry).filter(&accept) {
+            if spots.iter().all(|other| other.feet != spot.feet) {
+                spots.push(spot);
+            }
+        }
+    }
+
+    for spot in &spots {
+        let mut data = MobData::new(id, spot.feet, registry);
+        data.yaw = rng.gen_range(0.0..std::f32::consts::TAU);
+        spawn.send(SpawnMob(data));
+    }
+    spots.len()
+}
+
+/// Hostile mob spawning, once per world tick: try one random floor in the loaded chunks around
+/// a random player, and spawn a pack there if the overlay would mark it and it's dark enough right now
+/// (red spots any time, yellow ones once the sky has darkened) and no player is too close
+#[allow(clippy::too_many_arguments)]
+pub fn spawn_hostile_mobs(
+    registry: Res<EntityRegistry>,
+    block_registry: Res<BlockRegistry>,
+    chunk_manager: Res<ChunkManager>,
+    chunks: Query<&Chunk>,
+    sky_light: Res<SkyLightLevel>,
+    mut rng: ResMut<WorldRng>,
+    player_query: Query<&Transform, With<Player>>,
+    mobs: Query<&Mob>,
+    mut spawn: EventWriter<SpawnMob>,
+) {
+    let players: Vec<Vec3> = player_query.iter().map(|transform| transform.translation).collect();
+    let room = MobCategory::Hostile.cap().saturating_sub(count_mobs(&registry, &mobs, MobCategory::Hostile));
+    if players.is_empty() || room == 0 {
+        return;
+    }
+    let rng = &mut rng.0;
+    let Some((id, entity_type)) = pick_entity_type(&registry, MobCategory::Hostile, rng) else {
+        return;
+    };
+
+    let player_pos = players[rng.gen_range(0..players.len())];
+    let player_chunk = ChunkCoord::from_world_pos(player_pos);
+    let coord = ChunkCoord::new(
+        player_chunk.x + rng.gen_range(-SPAWN_CHUNK_RADIUS..=SPAWN_CHUNK_RADIUS),
+        player_chunk.y + rng.gen_range(-SPAWN_CHUNK_HEIGHT..=SPAWN_CHUNK_HEIGHT),
+        player_chunk.z + rng.gen_range(-SPAWN_CHUNK_RADIUS..=SPAWN_CHUNK_RADIUS),
+    );
+    if !chunk_manager.loaded_chunks.contains_key(&coord) {
+        return;
+    }
+    let size = CHUNK_SIZE as i32;
+    let top = IVec3::new(coord.x, coord.y, coord.z) * size
+        + IVec3::new(rng.gen_range(0..size), rng.gen_range(0..size), rng.gen_range(0..size));
+
+    let accept = |spot: &SpawnSpot| {
+        let distance = nearest_distance(&players, spot.feet);
+        (MIN_SPAWN_DISTANCE..DESPAWN_DISTANCE).contains(&distance)
+            && spot.light.spawns_at(spot.sky_light, spot.block_light, sky_light.level)
+    };
+    let Some(first) = find_spot(top, FLOOR_SEARCH_DEPTH, entity_type, &chunk_manager, &chunks, &block_registry).filter(accept) else {
+        return;
+    };
+    spawn_group(id, entity_type, first, accept, room, &chunk_manager, &chunks, &block_registry, &registry, rng, &mut spawn);
+}
+
+/// Despawn hostile mobs far from every player: at once past `DESPAWN_DISTANCE`,
+/// now and then past `RANDOM_DESPAWN_DISTANCE`; passive mobs stay
+pub fn despawn_distant_mobs(
+    mut commands: Commands,
+    registry: Res<EntityRegistry>,
+    mut rng: ResMut<WorldRng>,
+    player_query: Query<&Transform, With<Player>>,
+    mobs: Query<(Entity, &Mob, &PhysicsBody)>,
+) {
+    let players: Vec<Vec3> = player_query.iter().map(|transform| transform.translation).collect();
+    if players.is_empty() {
+        return;
+    }
+    for (entity, mob, body) in mobs.iter() {
+        if category(&registry, mob.entity_type) != Some(MobCategory::Hostile) {
+            continue;
+        }
+        let distance = nearest_distance(&players, body.position);
+        if distance > DESPAWN_DISTANCE
+            || (distance > RANDOM_DESPAWN_DISTANCE && rng.0.gen_range(0..RANDOM_DESPAWN_CHANCE) == 0)
+        {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+/// Distance from `pos` to the closest of `players`
+fn nearest_distance(players: &[Vec3], pos: Vec3) -> f32 {
+    players.iter().map(|player| player.distance(pos)).fold(f32::INFINITY, f32::min)
+}
+
+/// Passive animals come with newly generated chunks: now and then a chunk gets a herd
+/// on the surface blocks its entity type spawns on, out of the dark
+#[allow(clippy::too_many_arguments)]
+pub fn spawn_passive_mobs_in_new_chunks(
+    registry: Res<EntityRegistry>,
+    block_registry: Res<BlockRegistry>,
+    chunk_manager: Res<ChunkManager>,
+    chunks: Query<&Chunk>,
+    new_chunks: Query<Entity, Added<NeedsPostProcess>>,
+    mut rng: ResMut<WorldRng>,
+    mobs: Query<&Mob>,
+    mut spawn: EventWriter<SpawnMob>,
+) {
+    if new_chunks.is_empty() {
+        return;
+    }
+    let mut count = count_mobs(&registry, &mobs, MobCategory::Passive);
+    let rng = &mut rng.0;
+    let size = CHUNK_SIZE as i32;
+
+    for entity in new_chunks.iter() {
+        if count >= MobCategory::Passive.cap() || rng.gen_range(0..PASSIVE_CHUNK_CHANCE) != 0 {
+            continue;
+        }
+        let Ok(chunk) = chunks.get(entity) else {
+            continue;
+        };
+        let Some((id, entity_type)) = pick_entity_type(&registry, MobCategory::Passive, rng) else {
+            return;
+        };
+
+        let origin = IVec3::new(chunk.coord.x, chunk.coord.y, chunk.coord.z) * size;
+        let top = origin + IVec3::new(rng.gen_range(0..size), size - 1, rng.gen_range(0..size));
+        let accept = |spot: &SpawnSpot| spot.light != SpawnLight::Dangerous;
+        let Some(first) = find_spot(top, size, entity_type, &chunk_manager, &chunks, &block_registry).filter(accept) else {
+            continue;
+        };
+        let room = MobCategory::Passive.cap() - count;
+        count += spawn_group(id, entity_type, first, accept, room, &chunk_manager, &chunks, &block_registry, &registry, rng, &mut spawn);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn entity_types_are_picked_by_weight_within_their_category() {
+        let mut registry = EntityRegistry::new();
+        let common = registry.register_entity(
+            EntityType::builder("test:common", "Common").natural_spawn(MobCategory::Hostile, 3, 1, 1).build(),
+        ).unwrap();
+        let rare = registry.register_entity(
+            EntityType::builder("test:rare", "Rare").natural_spawn(MobCategory::Hostile, 1, 1, 1).build(),
+        ).unwrap();
+        registry.register_entity(
+            EntityType::builder("test:animal", "Animal").natural_spawn(MobCategory::Passive, 10, 1, 1).build(),
+        ).unwrap();
+        registry.register_entity(EntityType::builder("test:egg_only", "Egg Only").build()).unwrap();
+
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut picks = [0; 2];
+        for _ in 0..4000 {
+            let (id, _) = pick_entity_type(&registry, MobCategory::Hostile, &mut rng).unwrap();
+            assert!(id == common || id == rare);
+            picks[usize::from(id == rare)] += 1;
+        }
+        assert!((2800..3200).contains(&picks[0]), "3:1 weights, got {:?}", picks);
+
+        let empty = EntityRegistry::new();
+        assert!(pick_entity_type(&empty, MobCategory::Passive, &mut rng).is_none());
+    }
+
+    #[test]
+    fn packs_stop_at_the_room_left_under_the_cap() {
+        let pack = EntityType::builder("test:pack", "Pack").natural_spawn(MobCategory::Hostile, 1, 4, 4).build();
+        let mut rng = StdRng::seed_from_u64(7);
+        assert_eq!(group_size(&pack, 10, &mut rng), 4);
+        assert_eq!(group_size(&pack, 2, &mut rng), 2);
+        assert_eq!(group_size(&pack, 0, &mut rng), 0);
+    }
+}
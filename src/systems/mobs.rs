@@ -0,0 +1,391 @@
+use bevy::prelude::*;
+use bevy::input::mouse::MouseButton;
+use rand::Rng;
+use std::collections::HashMap;
+use crate::assets::AssetManager;
+use crate::blocks::{BlockRegistry, BlockState};
+use crate::components::{GameMode, Mob, MobData, MobPart, Player};
+use crate::entities::{EntityRegistry, EntityTypeId, NavigationStatus, PathCell, PathGrid, PathOptions, Senses};
+use crate::items::{attack_damage, new_stack, resolve_item, stack_tool, ItemRegistry};
+use crate::physics::{ChunkColliders, PhysicsBody, TERMINAL_VELOCITY};
+use crate::resources::PlayerInventory;
+use crate::world::{Chunk, ChunkCoord, ChunkManager, SavedChunks, WorldRng, world_to_chunk_local};
+use super::block_interaction::raycast_block;
+use super::damage::{DamageSource, PlayerDamage};
+use super::falling_blocks::VOID_Y;
+use super::item_drops::DropItem;
+use super::WorldBlocks;
+
+/// How far away the player can hit a mob
+const ATTACK_REACH: f32 = 4.0;
+
+/// World ticks a mob can't be hurt again after a hit
+const HURT_TICKS: u32 = 10;
+
+/// Speed a hit knocks a mob back and up (blocks/s)
+const KNOCKBACK_SPEED: f32 = 5.0;
+
+/// Upward speed of a mob swimming in a liquid, and the fastest it sinks
+const SWIM_SPEED: f32 = 2.5;
+const SINK_SPEED: f32 = 2.0;
+
+/// Fastest a mob turns, in radians per world tick
+const TURN_SPEED: f32 = 0.35;
+
+/// Path search nodes all mobs together may expand per world tick
+const PATH_NODES_PER_TICK: usize = 2000;
+
+/// How close a mob gets to a closed door on its path before opening it
+const DOOR_REACH: f32 = 1.5;
+
+/// Leg swing per block walked (radians of walk cycle) and widest swing angle
+const STRIDE: f32 = 4.0;
+const SWING_ANGLE: f32 = 0.6;
+
+/// Request to spawn a mob (spawn eggs, mobs restored with their chunk)
+#[derive(Event, Debug, Clone, Copy)]
+pub struct SpawnMob(pub MobData);
+
+/// A mob wants the closed door at `pos` opened
+#[derive(Event, Debug, Clone, Copy)]
+pub struct OpenDoor {
+    pub pos: IVec3,
+}
+
+/// Block state at a world position, None if its chunk isn't loaded
+pub(super) fn block_at(chunk_manager: &ChunkManager, chunks: &Query<&Chunk>, pos: IVec3) -> Option<BlockState> {
+    let (coord, local) = world_to_chunk_local(pos);
+    let chunk = chunks.get(*chunk_manager.loaded_chunks.get(&coord)?).ok()?;
+    Some(chunk.get_state(local.x as usize, local.y as usize, local.z as usize))
+}
+
+/// Yaw that faces the model (which looks down -Z) along `direction`
+fn yaw_towards(direction: Vec3) -> f32 {
+    f32::atan2(-direction.x, -direction.z)
+}
+
+/// Spawn every mob requested this frame as a physics body; `attach_mob_models` adds its model
+pub fn spawn_mobs(
+    mut commands: Commands,
+    mut events: EventReader<SpawnMob>,
+    registry: Res<EntityRegistry>,
+) {
+    for SpawnMob(data) in events.read() {
+        let Some(entity_type) = registry.get_entity(data.entity_type) else {
+            warn!("Tried to spawn unknown entity type {}", data.entity_type.0);
+            continue;
+        };
+        let properties = &entity_type.properties;
+        let body = PhysicsBody::new(data.position, properties.half_width, properties.height)
+            .with_step_height(properties.physics.step_height);
+
+        commands.spawn((
+            Transform::from_translation(data.position).with_rotation(Quat::from_rotation_y(data.yaw)),
+            Mob::new(*data),
+            body,
+        ));
+    }
+}
+
+/// Meshes and materials of mob model parts by (type, part index), built once and shared between mobs
+pub(crate) type MobPartAssets = HashMap<(EntityTypeId, usize), (Handle<Mesh>, Handle<StandardMaterial>)>;
+
+/// Give newly spawned mobs their type's model boxes as children
+/// Meshes and materials are built once per model part and shared between mobs
+pub fn attach_mob_models(
+    mut commands: Commands,
+    mobs: Query<(Entity, &Mob), Added<Mob>>,
+    registry: Res<EntityRegistry>,
+    asset_manager: Res<AssetManager>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut part_assets: Local<MobPartAssets>,
+) {
+    for (entity, mob) in mobs.iter() {
+        spawn_mob_model(&mut commands.entity(entity), mob.entity_type, &registry, &asset_manager, &mut meshes, &mut materials, &mut part_assets);
+    }
+}
+
+/// Add the model boxes of a mob of type `entity_type` as children of `mob`
+/// (nothing when the type isn't registered)
+pub(crate) fn spawn_mob_model(
+    mob: &mut EntityCommands,
+    entity_type: EntityTypeId,
+    registry: &EntityRegistry,
+    asset_manager: &AssetManager,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<StandardMaterial>,
+    part_assets: &mut MobPartAssets,
+) {
+    let Some(properties) = registry.get_entity(entity_type).map(|entity_type| &entity_type.properties) else {
+        return;
+    };
+    mob.insert(Visibility::default()).with_children(|parent| {
+        for (index, part) in properties.model.parts.iter().enumerate() {
+            let (mesh, material) = part_assets.entry((entity_type, index)).or_insert_with(|| (
+                meshes.add(part.mesh()),
+                materials.add(StandardMaterial {
+                    base_color: part.tint,
+                    base_color_texture: asset_manager.get_mod_texture_atlas("core"),
+                    alpha_mode: AlphaMode::Mask(0.5),
+                    perceptual_roughness: 1.0,
+                    ..default()
+                }),
+            )).clone();
+            parent.spawn((
+                Mesh3d(mesh),
+                MeshMaterial3d(material),
+                Transform::from_translation(part.pivot()),
+                MobPart { pivot: part.pivot(), swing: part.swing },
+            ));
+        }
+    });
+}
+
+/// Mob AI, path following and physics, once per world tick
+/// Path searches share a node budget per tick, so a crowd of mobs picking new paths
+/// at once spreads the work over several ticks
+/// Mobs whose chunk isn't loaded hold still; mobs that fall into the void are removed
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub fn update_mobs(
+    mut commands: Commands,
+    time: Res<Time>,
+    registry: Res<EntityRegistry>,
+    chunk_manager: Res<ChunkManager>,
+    chunks: Query<&'static Chunk>,
+    block_registry: Res<BlockRegistry>,
+    mut rng: ResMut<WorldRng>,
+    player_query: Query<(Entity, &Transform, &GameMode), (With<Player>, Without<Mob>)>,
+    mut mobs: Query<(Entity, &mut Mob, &mut PhysicsBody), Without<Player>>,
+    mut doors: EventWriter<OpenDoor>,
+    mut damage: EventWriter<PlayerDamage>,
+) {
+    let dt = time.delta_secs();
+    let mut path_budget = PATH_NODES_PER_TICK;
+    let colliders = ChunkColliders { chunk_manager: &chunk_manager, chunks: &chunks, registry: &block_registry };
+    // Spectators are invisible to mobs
+    let player_eyes: Vec<(Entity, Vec3)> = player_query.iter()
+        .filter(|(_, _, game_mode)| game_mode.can_interact())
+        .map(|(player, transform, _)| (player, Player::eye_position(transform)))
+        .collect();
+
+    for (entity, mut mob, mut body) in mobs.iter_mut() {
+        if body.position.y < VOID_Y {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+        if !chunk_manager.loaded_chunks.contains_key(&ChunkCoord::from_world_pos(body.position)) {
+            continue;
+        }
+        let Some(entity_type) = registry.get_entity(mob.entity_type) else {
+            continue;
+        };
+        let properties = &entity_type.properties;
+        mob.hurt_ticks = mob.hurt_ticks.saturating_sub(1);
+
+        // Liquid around the middle of the body
+        let middle = body.position + Vec3::Y * (body.height / 2.0);
+        let in_liquid = block_at(&chunk_manager, &chunks, middle.floor().as_ivec3())
+            .and_then(|state| block_registry.get_block(state.id))
+            .is_some_and(|block| block.properties.is_liquid);
+
+        // Each mob senses the player closest to it, and that's the player its attacks hit
+        let nearest = player_eyes.iter().copied()
+            .min_by(|(_, a), (_, b)| a.distance_squared(body.position).total_cmp(&b.distance_squared(body.position)));
+        let senses = Senses { position: body.position, in_liquid, blocked: mob.blocked, player: nearest.map(|(_, eye)| eye) };
+        let steering = mob.brain.think(&properties.behaviors, &senses, &mut rng.0);
+        if let (Some(amount), Some((player, _))) = (steering.attack, nearest) {
+            damage.send(PlayerDamage { player, source: DamageSource::Mob, amount });
+        }
+
+        let options = PathOptions::for_entity(properties);
+        let waypoint = match mob.navigation.follow(steering.goal, body.position, &options, &colliders, &mut path_budget) {
+            NavigationStatus::Walking(waypoint) => Some(waypoint),
+            NavigationStatus::Arrived => {
+                mob.brain.arrive();
+                None
+            }
+            NavigationStatus::Idle => None,
+        };
+        let direction = waypoint.map_or(Vec3::ZERO, |waypoint| (waypoint - body.position).with_y(0.0).normalize_or_zero());
+        // Ledges on the path too high to step up are jumped
+        let climb = waypoint.is_some_and(|waypoint| waypoint.y > body.position.y + body.step_height);
+
+        // Open closed doors on the way
+        if let Some(node) = mob.navigation.next_node().filter(|_| properties.opens_doors) {
+            if options.node_center(node).distance(body.position) < DOOR_REACH {
+                for y in 0..options.height {
+                    let pos = node + IVec3::Y * y;
+                    if colliders.cell(pos) == PathCell::ClosedDoor {
+                        doors.send(OpenDoor { pos });
+                    }
+                }
+            }
+        }
+
+        // Walking sets the horizontal velocity; knockback slides to a stop on the ground
+        if waypoint.is_some() {
+            let velocity = direction * properties.physics.walk_speed * steering.speed;
+            body.velocity.x = velocity.x;
+            body.velocity.z = velocity.z;
+        } else if body.on_ground {
+            body.velocity.x *= 0.5;
+            body.velocity.z *= 0.5;
+        }
+        if steering.jump || climb {
+            if in_liquid {
+                body.velocity.y = SWIM_SPEED;
+            } else if body.on_ground {
+                body.velocity.y = properties.physics.jump_velocity;
+            }
+        }
+        body.velocity.y -= properties.physics.gravity * dt;
+        body.velocity.y = body.velocity.y.max(if in_liquid { -SINK_SPEED } else { -TERMINAL_VELOCITY });
+
+        let delta = body.velocity * dt;
+        let result = body.move_by(&colliders, delta, false);
+        mob.blocked = result.blocked.x || result.blocked.z;
+        // Legs come back together when the mob stops
+        let walked = result.offset.xz().length();
+        mob.walk_phase = if walked > 1e-3 { mob.walk_phase + walked * STRIDE } else { 0.0 };
+
+        // Turn toward whatever the mob is looking at, or where it's walking
+        let facing = match steering.look_at {
+            Some(target) => (target - body.position).with_y(0.0),
+            None => direction,
+        };
+        if facing.length_squared() > 1e-4 {
+            let turn = (yaw_towards(facing) - mob.yaw + std::f32::consts::PI).rem_euclid(std::f32::consts::TAU) - std::f32::consts::PI;
+            mob.yaw += turn.clamp(-TURN_SPEED, TURN_SPEED);
+        }
+    }
+}
+
+/// Open the doors mobs asked for, if they're still closed
+pub fn open_doors_for_mobs(mut events: EventReader<OpenDoor>, mut world_blocks: WorldBlocks) {
+    for &OpenDoor { pos } in events.read() {
+        let closed_door = world_blocks.get_state(pos)
+            .and_then(|state| world_blocks.registry().get_block(state.id))
+            .is_some_and(|block| block.properties.is_door && block.properties.is_solid);
+        if closed_door {
+            world_blocks.edit(|world| world.use_block(pos));
+        }
+    }
+}
+
+/// Hit the mob under the crosshair on left click, if no block is in the way
+/// A hit mob is knocked back and hurt; at zero health it dies and drops its loot
+/// The click is consumed so it doesn't also hit the block behind the mob
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub fn attack_mobs(
+    mut commands: Commands,
+    mut mouse_button: ResMut<ButtonInput<MouseButton>>,
+    player_query: Query<(&Transform, &Player, &GameMode)>,
+    mut mobs: Query<(Entity, &mut Mob, &mut PhysicsBody), Without<Player>>,
+    chunk_manager: Res<ChunkManager>,
+    chunks: Query<&Chunk>,
+    registry: Res<EntityRegistry>,
+    item_registry: Res<ItemRegistry>,
+    block_registry: Res<BlockRegistry>,
+    mut inventory: ResMut<PlayerInventory>,
+    mut drops: EventWriter<DropItem>,
+) {
+    if !mouse_button.just_pressed(MouseButton::Left) {
+        return;
+    }
+    let Ok((player_transform, player, game_mode)) = player_query.get_single() else {
+        return;
+    };
+    if !game_mode.can_interact() {
+        return;
+    }
+
+    let origin = Player::eye_position(player_transform);
+    let direction = player.look_direction();
+    let block_distance = raycast_block(origin, direction, ATTACK_REACH, &chunk_manager, &chunks)
+        .and_then(|hit| crate::physics::CollisionBox::block(hit.block_pos).ray_distance(origin, direction))
+        .unwrap_or(f32::MAX);
+
+    let target = mobs.iter()
+        .filter_map(|(entity, _, body)| Some((entity, body.collision_box().ray_distance(origin, direction)?)))
+        .filter(|&(_, distance)| distance <= ATTACK_REACH && distance < block_distance)
+        .min_by(|a, b| a.1.total_cmp(&b.1));
+    let Some((entity, _)) = target else {
+        return;
+    };
+    mouse_button.clear_just_pressed(MouseButton::Left);
+
+    let Ok((_, mut mob, mut body)) = mobs.get_mut(entity) else {
+        return;
+    };
+    if mob.hurt_ticks > 0 {
+        return;
+    }
+    let Some(entity_type) = registry.get_entity(mob.entity_type) else {
+        return;
+    };
+    let properties = &entity_type.properties;
+
+    let tool = stack_tool(inventory.hotbar[inventory.selected_slot], &item_registry);
+    if tool.is_some() && inventory.wear_selected() {
+        info!("Tool broke!");
+    }
+    mob.health = mob.health.saturating_sub(attack_damage(tool));
+    mob.hurt_ticks = HURT_TICKS;
+    mob.brain.hurt(player_transform.translation, &properties.behaviors);
+    let push = direction.with_y(0.0).normalize_or_zero() * KNOCKBACK_SPEED;
+    body.velocity = Vec3::new(push.x, KNOCKBACK_SPEED * 0.8, push.z);
+
+    if mob.health == 0 {
+        let center = body.position + Vec3::Y * (body.height / 2.0);
+        let mut rng = rand::thread_rng();
+        for drop in &properties.drops {
+            let count = rng.gen_range(drop.min..=drop.max.max(drop.min));
+            match resolve_item(&drop.item, &item_registry, &block_registry) {
+                Ok(item) if count > 0 => {
+                    drops.send(DropItem { pos: center, stack: new_stack(item, count, &item_registry) });
+                }
+                Ok(_) => {}
+                Err(error) => warn!("{} drop: {}", properties.id, error),
+            }
+        }
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+/// Turn mobs to their yaw and swing their legs with the walk cycle
+#[allow(clippy::type_complexity)]
+pub fn animate_mobs(
+    mut mobs: Query<(&Mob, &mut Transform, &Children)>,
+    mut parts: Query<(&MobPart, &mut Transform), Without<Mob>>,
+) {
+    for (mob, mut transform, children) in mobs.iter_mut() {
+        transform.rotation = Quat::from_rotation_y(mob.yaw);
+        for &child in children.iter() {
+            let Ok((part, mut part_transform)) = parts.get_mut(child) else {
+                continue;
+            };
+            if let Some(phase) = part.swing {
+                part_transform.translation = part.pivot;
+                part_transform.rotation = Quat::from_rotation_x(mob.walk_phase.sin() * SWING_ANGLE * phase);
+            }
+        }
+    }
+}
+
+/// Save and despawn mobs standing in chunks that aren't loaded (usually because they just
+/// unloaded); they come back when the chunk loads again
+pub fn save_mobs_in_unloaded_chunks(
+    mut commands: Commands,
+    chunk_manager: Res<ChunkManager>,
+    mut saved: ResMut<SavedChunks>,
+    mobs: Query<(Entity, &Mob, &PhysicsBody)>,
+) {
+    for (entity, mob, body) in mobs.iter() {
+        let coord = ChunkCoord::from_world_pos(body.position);
+        if !chunk_manager.loaded_chunks.contains_key(&coord) {
+            saved.save_mob(coord, mob.data(body.position));
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
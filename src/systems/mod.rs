@@ -4,17 +4,33 @@ mod ui;
 mod voxel;
 mod world;
 mod chunk_borders;
+mod selection_outline;
 mod block_highlight;
 mod sky;
 mod debug_overlay;
 mod hotbar;
 mod ui_visibility;
 mod block_interaction;
+mod block_edits;
+mod relight;
 mod torch_light;
 mod lighting_overlay;
+mod world_tick;
+mod block_updates;
+mod falling_blocks;
+mod item_drops;
+mod damage;
+mod hunger;
+mod death;
+mod crafting;
+mod furnace;
+mod mobs;
+mod mob_spawning;
+mod chat;
+mod replicated_models;
 
-pub use camera::{camera_movement_controls, camera_look_controls, setup_cursor_grab, handle_window_focus};
-pub use input::{toggle_pause_menu, toggle_debug_overlay, toggle_chunk_borders, toggle_ui_visibility, take_screenshot, toggle_creative_mode};
+pub use camera::{camera_movement_controls, player_physics, apply_step_effects, camera_look_controls, update_camera_rig, setup_cursor_grab, handle_window_focus};
+pub use input::{toggle_pause_menu, toggle_debug_overlay, toggle_chunk_borders, toggle_ui_visibility, take_screenshot, cycle_camera_mode, undo_redo_keys};
 pub use ui::{
     handle_pause_menu_buttons, update_pause_menu_visibility,
     update_click_text_timer,
@@ -22,16 +38,32 @@ pub use ui::{
 pub use voxel::voxel_click_detection;
 pub use world::update_voxel_material;
 pub use chunk_borders::update_chunk_borders;
+pub use selection_outline::update_selection_outline;
 pub use block_highlight::update_block_highlight;
 pub use sky::{
     update_sky_position, update_time_of_day, update_sky_light_level,
-    update_sun_transform, handle_time_controls, update_night_skybox_alpha,
+    update_sun_transform, update_night_skybox_alpha,
     update_stale_chunk_lighting,
     TimeOfDay, SkyLightLevel, ChunkSkyLight,
 };
 pub use debug_overlay::update_debug_overlay;
-pub use hotbar::{update_hotbar_selection, hotbar_mouse_wheel_selection, hotbar_number_key_selection, update_hotbar_icons};
-pub use ui_visibility::{update_hotbar_visibility, update_crosshair_visibility, update_debug_visibility, update_debug_visibility_on_ui_toggle, update_survival_bars_visibility, update_health_display, update_hunger_display};
+pub use hotbar::{update_hotbar_selection, hotbar_mouse_wheel_selection, hotbar_number_key_selection, update_hotbar_icons, update_hotbar_counts, update_hotbar_durability};
+pub use ui_visibility::{update_hotbar_visibility, update_crosshair_visibility, update_debug_visibility, update_debug_visibility_on_ui_toggle, update_survival_bars_visibility, update_health_display, update_hunger_display, update_air_display, update_hurt_flash};
 pub use block_interaction::{block_interaction, remesh_modified_chunks, NeedsRemesh};
+pub use block_edits::{apply_block_edits, BlockAction, BlockEditRequest, BLOCK_REACH};
+pub use relight::{relight_chunks, ChunkLightChanged, NeedsRelight};
 pub use torch_light::{update_torch_light, follow_player_with_torch_light};
 pub use lighting_overlay::{toggle_lighting_overlay, update_lighting_overlay, detect_chunk_changes, LightingOverlayState};
+pub use world_tick::{advance_world_tick, process_scheduled_ticks, random_tick_chunks, update_block_contacts};
+pub use block_updates::{WorldBlocks, BlockChanged, NeedsPostProcess, post_process_generated_chunks};
+pub use falling_blocks::{spawn_falling_blocks, attach_falling_block_meshes, update_falling_blocks, FallingBlock};
+pub use item_drops::{spawn_item_drops, attach_item_drop_models, update_item_drops, ItemDrop, DropItem};
+pub use replicated_models::attach_replicated_models;
+pub use damage::{update_environment_damage, apply_player_damage, fall_damage, DamageSource, PlayerDamage, INVULNERABILITY_TICKS};
+pub use hunger::{update_hunger, eat_held_food};
+pub use crafting::{open_crafting_screen, close_crafting_screen, handle_crafting_clicks, update_crafting_screen};
+pub use furnace::{tick_furnaces, open_furnace_screen, close_furnace_screen, handle_furnace_clicks, update_furnace_screen};
+pub use mobs::{spawn_mobs, attach_mob_models, update_mobs, open_doors_for_mobs, attack_mobs, animate_mobs, save_mobs_in_unloaded_chunks, OpenDoor, SpawnMob};
+pub use chat::{chat_closed, chat_console_input, update_chat_suggestions, handle_local_chat, show_command_feedback, update_chat_display, SendChat};
+pub use mob_spawning::{spawn_hostile_mobs, despawn_distant_mobs, spawn_passive_mobs_in_new_chunks};
+pub use death::{handle_player_death, respawn_dead_players, update_bed_spawn, update_death_screen_visibility, handle_respawn_button};
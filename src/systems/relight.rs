@@ -0,0 +1,580 @@
+use bevy::prelude::*;
+use bevy::ecs::system::ParamSet;
+use std::collections::{HashMap, HashSet, VecDeque};
+use crate::blocks::{BlockId, BlockRegistry};
+use crate::resources::ChunkManager;
+use crate::world::{Chunk, ChunkCoord, NibbleArray, PalettedContainer, CHUNK_SIZE, MAX_LIGHT_LEVEL, NEIGHBOR_OFFSETS};
+use super::NeedsRemesh;
+
+/// Marker component for chunks whose sky and block light need recalculating
+/// Block edits and newly loaded chunks get it along with `NeedsRemesh`; the mesher waits
+/// for the light to settle before it rebuilds a chunk
+#[derive(Component)]
+pub struct NeedsRelight;
+
+/// A chunk's sky or block light changed when it was relit (sent so servers can forward it)
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChunkLightChanged(pub ChunkCoord);
+
+/// Chunks relit per frame, so big edits and chunk loads spread their work across frames
+const MAX_CHUNKS_PER_FRAME: usize = 32;
+
+/// Cached boundary light data for a chunk (6 faces worth of light values)
+#[derive(Clone)]
+pub(super) struct CachedLightData {
+    /// Light values at each face boundary
+    /// Key: neighbor direction as (dx, dy, dz), Value: 2D array of light levels
+    /// For X boundaries: [CHUNK_SIZE][CHUNK_SIZE] indexed by [y][z]
+    /// For Y boundaries: [CHUNK_SIZE][CHUNK_SIZE] indexed by [x][z]
+    /// For Z boundaries: [CHUNK_SIZE][CHUNK_SIZE] indexed by [x][y]
+    /// (clones of the chunk's own storage, so uniform chunks cost next to nothing to cache)
+    light_levels: NibbleArray,
+    block_light: NibbleArray,
+    blocks: PalettedContainer,
+}
+
+impl CachedLightData {
+    pub(super) fn from_chunk(chunk: &Chunk) -> Self {
+        Self {
+            light_levels: chunk.light_levels.clone(),
+            block_light: chunk.block_light.clone(),
+            blocks: chunk.blocks.clone(),
+        }
+    }
+
+    pub(super) fn get_block_light(&self, x: usize, y: usize, z: usize) -> u8 {
+        if x >= CHUNK_SIZE || y >= CHUNK_SIZE || z >= CHUNK_SIZE {
+            return 0;
+        }
+        self.block_light.get(x + z * CHUNK_SIZE + y * CHUNK_SIZE * CHUNK_SIZE)
+    }
+
+    pub(super) fn get_light(&self, x: usize, y: usize, z: usize) -> u8 {
+        if x >= CHUNK_SIZE || y >= CHUNK_SIZE || z >= CHUNK_SIZE {
+            return 15; // Full light outside
+        }
+        let idx = x + z * CHUNK_SIZE + y * CHUNK_SIZE * CHUNK_SIZE;
+        self.light_levels.get(idx)
+    }
+
+    /// Whether every block of the chunk is the same opaque block
+    pub(super) fn is_opaque(&self, block_registry: &BlockRegistry) -> bool {
+        self.blocks.uniform().is_some_and(|state| block_registry.is_opaque(state.id))
+    }
+
+    pub(super) fn get_block(&self, x: usize, y: usize, z: usize) -> BlockId {
+        if x >= CHUNK_SIZE || y >= CHUNK_SIZE || z >= CHUNK_SIZE {
+            return BlockId::AIR;
+        }
+        let idx = x + z * CHUNK_SIZE + y * CHUNK_SIZE * CHUNK_SIZE;
+        self.blocks.get(idx).id
+    }
+}
+
+
+/// System to recalculate light for chunks marked `NeedsRelight`
+/// Part of the simulation, so servers keep light current without meshing anything:
+/// 1. Cache all chunk data (light and blocks) so we can read from ALL chunks
+/// 2. Recalculate lighting using cached neighbor data, over a few passes so light
+///    can cross between chunks relit together
+/// 3. Pass changed border light on to neighbors outside the batch
+#[allow(clippy::type_complexity)]
+pub fn relight_chunks(
+    mut commands: Commands,
+    mut chunk_sets: ParamSet<(
+        Query<&mut Chunk, With<NeedsRelight>>,
+        Query<&Chunk>,  // All chunks for reading neighbor data
+    )>,
+    chunk_manager: Res<ChunkManager>,
+    block_registry: Res<BlockRegistry>,
+    mut light_changed: EventWriter<ChunkLightChanged>,
+) {
+    // Phase 1: Collect the chunks to relight and cache their data and their neighbors'
+    let mut entities_to_process: Vec<(Entity, ChunkCoord)> = {
+        let chunks_query = chunk_sets.p0();
+        chunk_manager.loaded_chunks.iter()
+            .filter(|(_, &entity)| chunks_query.contains(entity))
+            .map(|(coord, &entity)| (entity, *coord))
+            .collect()
+    };
+
+    if entities_to_process.is_empty() {
+        return;
+    }
+
+    // Sort by Y first (top to bottom), then by Z, then by X
+    // This ensures light propagates correctly from sky down
+    entities_to_process.sort_by(|a, b| {
+        b.1.y.cmp(&a.1.y) // Reverse Y: top chunks first (they have skylight)
+            .then(a.1.z.cmp(&b.1.z))
+            .then(a.1.x.cmp(&b.1.x))
+    });
+
+    // Limit to MAX_CHUNKS_PER_FRAME to spread work across frames
+    entities_to_process.truncate(MAX_CHUNKS_PER_FRAME);
+
+    let mut chunk_cache: HashMap<ChunkCoord, CachedLightData> = HashMap::new();
+    {
+        let all_chunks = chunk_sets.p1();
+
+        // Cache chunks being processed
+        for &(entity, coord) in &entities_to_process {
+            if let Ok(chunk) = all_chunks.get(entity) {
+                chunk_cache.insert(coord, CachedLightData::from_chunk(chunk));
+            }
+        }
+
+        // Cache all neighbors (that aren't already cached)
+        // Also cache chunks above for skylight column checking
+        for &(_, coord) in &entities_to_process {
+            let mut neighbor_coords: Vec<ChunkCoord> = face_neighbors(coord).iter().map(|(_, neighbor)| *neighbor).collect();
+            for dy in 2..=8 {
+                neighbor_coords.push(ChunkCoord::new(coord.x, coord.y + dy, coord.z));
+            }
+
+            for neighbor_coord in neighbor_coords {
+                if chunk_cache.contains_key(&neighbor_coord) {
+                    continue;
+                }
+                if let Some(&neighbor_entity) = chunk_manager.loaded_chunks.get(&neighbor_coord) {
+                    if let Ok(neighbor_chunk) = all_chunks.get(neighbor_entity) {
+                        chunk_cache.insert(neighbor_coord, CachedLightData::from_chunk(neighbor_chunk));
+                    }
+                }
+            }
+        }
+    }
+
+    // Light before relighting, to spot chunks whose light changed and light that
+    // now reaches into (or left) a neighbor
+    let old_light: HashMap<ChunkCoord, CachedLightData> = entities_to_process.iter()
+        .filter_map(|(_, coord)| chunk_cache.get(coord).map(|cached| (*coord, cached.clone())))
+        .collect();
+
+    // Phase 2: Recalculate lighting for all NeedsRelight chunks
+    // First pass: reset skylight columns (direct sunlight from above)
+    // Subsequent passes: only propagate from neighbors (don't reset)
+    const LIGHT_PROPAGATION_PASSES: usize = 4;
+
+    for pass in 0..LIGHT_PROPAGATION_PASSES {
+        // Process all chunks using current cache
+        {
+            let mut chunks_query = chunk_sets.p0();
+            for &(entity, coord) in &entities_to_process {
+                let Ok(mut chunk) = chunks_query.get_mut(entity) else {
+                    continue;
+                };
+
+                // A chunk of one opaque block has nowhere for light to reach: it's dark, apart
+                // from its own glow when the block gives off light (a chunk of water still
+                // lets light through, so it's lit like any other)
+                if let Some(state) = chunk.uniform_state().filter(|state| block_registry.is_opaque(state.id)) {
+                    if pass == 0 {
+                        let emission = block_registry.get_block(state.id).map_or(0, |block| block.properties.light_emission);
+                        chunk.light_levels.fill(0);
+                        chunk.block_light.fill(emission);
+                    }
+                    continue;
+                }
+
+                if pass == 0 {
+                    // First pass: full recalculation (reset skylight columns, then flood fill)
+                    calculate_skylight_with_cache(&mut chunk, coord, &chunk_cache, &block_registry);
+                    reset_block_light(&mut chunk, &block_registry);
+                } else {
+                    // Subsequent passes: only flood fill from neighbors (don't reset columns)
+                    propagate_light_from_cache(&mut chunk, coord, &chunk_cache, &block_registry);
+                }
+                propagate_block_light_from_cache(&mut chunk, coord, &chunk_cache, &block_registry);
+            }
+        }
+
+        // After processing all chunks, update the cache with new light values
+        // This allows the next pass to see updated light from neighboring NeedsRelight chunks
+        if pass < LIGHT_PROPAGATION_PASSES - 1 {
+            let all_chunks = chunk_sets.p1();
+            for &(entity, coord) in &entities_to_process {
+                if let Ok(chunk) = all_chunks.get(entity) {
+                    chunk_cache.insert(coord, CachedLightData::from_chunk(chunk));
+                }
+            }
+        }
+    }
+
+    // Light that ended up the same everywhere (open sky, solid rock) goes back to a single value,
+    // and blocks whose palette edits left states in that nothing uses are compacted
+    {
+        let mut chunks_query = chunk_sets.p0();
+        for &(entity, _) in &entities_to_process {
+            if let Ok(mut chunk) = chunks_query.get_mut(entity) {
+                chunk.compact_after_relight();
+            }
+        }
+    }
+
+    // Phase 3: Block light crosses chunk borders: when a chunk's border light changed, neighbors
+    // outside this batch need relighting too (they settle once the border stops changing)
+    let all_chunks = chunk_sets.p1();
+    let batch: HashSet<ChunkCoord> = entities_to_process.iter().map(|(_, coord)| *coord).collect();
+    for &(entity, coord) in &entities_to_process {
+        commands.entity(entity).remove::<NeedsRelight>();
+        let (Ok(chunk), Some(old)) = (all_chunks.get(entity), old_light.get(&coord)) else {
+            continue;
+        };
+        if old.light_levels != chunk.light_levels || old.block_light != chunk.block_light {
+            light_changed.send(ChunkLightChanged(coord));
+        }
+        for (offset, neighbor_coord) in face_neighbors(coord) {
+            if batch.contains(&neighbor_coord) || !border_light_changed(&old.block_light, &chunk.block_light, offset) {
+                continue;
+            }
+            if let Some(&neighbor) = chunk_manager.loaded_chunks.get(&neighbor_coord) {
+                commands.entity(neighbor).try_insert((NeedsRelight, NeedsRemesh));
+            }
+        }
+    }
+}
+
+/// Calculate skylight for a chunk using cached neighbor data
+/// This allows reading from neighbors that might also be marked NeedsRelight
+fn calculate_skylight_with_cache(
+    chunk: &mut Chunk,
+    coord: ChunkCoord,
+    cache: &HashMap<ChunkCoord, CachedLightData>,
+    block_registry: &BlockRegistry,
+) {
+    // First pass: propagate direct skylight from top down
+    // To determine if a column has sky access, trace UP through chunks above
+    // checking for opaque blocks (not light values, which may be stale)
+    for z in 0..CHUNK_SIZE {
+        for x in 0..CHUNK_SIZE {
+            // Check if this column has direct sky access by tracing upward
+            let in_shadow = is_column_shadowed(x, z, coord, cache, block_registry);
+
+            // Propagate from top to bottom
+            let mut column_shadow = in_shadow;
+            for y in (0..CHUNK_SIZE).rev() {
+                let block = chunk.get_block(x, y, z);
+
+                if !block_registry.is_opaque(block) {
+                    if !column_shadow {
+                        // Direct skylight - full brightness
+                        chunk.set_light(x, y, z, MAX_LIGHT_LEVEL);
+                    } else {
+                        // In shadow - start at 0, will be filled by flood fill
+                        chunk.set_light(x, y, z, 0);
+                    }
+                } else {
+                    // Opaque block - blocks light, and everything below is in shadow
+                    chunk.set_light(x, y, z, 0);
+                    column_shadow = true;
+                }
+            }
+        }
+    }
+
+    // Second pass: flood-fill light propagation
+    propagate_light_from_cache(chunk, coord, cache, block_registry);
+}
+
+/// Check if a column at (x, z) in the given chunk is shadowed by blocks above
+/// Traces upward through cached chunks to find any opaque blocks
+fn is_column_shadowed(
+    x: usize,
+    z: usize,
+    chunk_coord: ChunkCoord,
+    cache: &HashMap<ChunkCoord, CachedLightData>,
+    block_registry: &BlockRegistry,
+) -> bool {
+    // Start from the chunk above and trace upward
+    let mut check_coord = ChunkCoord::new(chunk_coord.x, chunk_coord.y + 1, chunk_coord.z);
+
+    // Check up to 8 chunks above (128 blocks) - beyond that assume open sky
+    for _ in 0..8 {
+        if let Some(above_cache) = cache.get(&check_coord) {
+            // Check the entire column in this chunk for any opaque blocks
+            for y in 0..CHUNK_SIZE {
+                if block_registry.is_opaque(above_cache.get_block(x, y, z)) {
+                    // Found an opaque block above - we're in shadow
+                    return true;
+                }
+            }
+            // Light passes this whole column of the chunk, check the next one up
+            check_coord = ChunkCoord::new(check_coord.x, check_coord.y + 1, check_coord.z);
+        } else {
+            // No chunk above in cache - assume open sky
+            return false;
+        }
+    }
+
+    // Reached max height without finding an opaque block - open sky
+    false
+}
+
+/// Clear a chunk's block light, then seed it from the light-emitting blocks inside it
+/// An emitting block holds its own level; the light spreads from there through non-opaque blocks
+fn reset_block_light(chunk: &mut Chunk, block_registry: &BlockRegistry) {
+    chunk.block_light.fill(0);
+    for y in 0..CHUNK_SIZE {
+        for z in 0..CHUNK_SIZE {
+            for x in 0..CHUNK_SIZE {
+                let block = chunk.get_block(x, y, z);
+                if block.is_air() {
+                    continue;
+                }
+                let emission = block_registry.get_block(block).map_or(0, |block| block.properties.light_emission);
+                if emission > 0 {
+                    chunk.set_block_light(x, y, z, emission);
+                }
+            }
+        }
+    }
+}
+
+/// Spread block light through the non-opaque blocks of a chunk, one level dimmer per block
+/// Seeds are the chunk's own lit cells plus light coming in across its borders (from the cache).
+/// A breadth-first flood, since block light usually comes from a few sources
+fn propagate_block_light_from_cache(
+    chunk: &mut Chunk,
+    coord: ChunkCoord,
+    cache: &HashMap<ChunkCoord, CachedLightData>,
+    block_registry: &BlockRegistry,
+) {
+    let size = CHUNK_SIZE as i32;
+    let mut queue: VecDeque<IVec3> = VecDeque::new();
+
+    for y in 0..CHUNK_SIZE {
+        for z in 0..CHUNK_SIZE {
+            for x in 0..CHUNK_SIZE {
+                let pos = IVec3::new(x as i32, y as i32, z as i32);
+                if chunk.get_block_light(x, y, z) > 1 {
+                    queue.push_back(pos);
+                }
+
+                // Light entering from a neighbor chunk
+                let on_border = x == 0 || y == 0 || z == 0 || x == CHUNK_SIZE - 1 || y == CHUNK_SIZE - 1 || z == CHUNK_SIZE - 1;
+                if !on_border || block_registry.is_opaque(chunk.get_block(x, y, z)) {
+                    continue;
+                }
+                let mut incoming = 0;
+                for (offset, neighbor_coord) in face_neighbors(coord) {
+                    let outside = pos + offset;
+                    if (0..size).contains(&outside.x) && (0..size).contains(&outside.y) && (0..size).contains(&outside.z) {
+                        continue;
+                    }
+                    let local = outside.rem_euclid(IVec3::splat(size));
+                    let light = cache.get(&neighbor_coord)
+                        .map_or(0, |c| c.get_block_light(local.x as usize, local.y as usize, local.z as usize));
+                    incoming = incoming.max(light.saturating_sub(1));
+                }
+                if incoming > chunk.get_block_light(x, y, z) {
+                    chunk.set_block_light(x, y, z, incoming);
+                    queue.push_back(pos);
+                }
+            }
+        }
+    }
+
+    while let Some(pos) = queue.pop_front() {
+        let spread = chunk.get_block_light(pos.x as usize, pos.y as usize, pos.z as usize).saturating_sub(1);
+        if spread == 0 {
+            continue;
+        }
+        for (offset, _) in face_neighbors(coord) {
+            let next = pos + offset;
+            if !(0..size).contains(&next.x) || !(0..size).contains(&next.y) || !(0..size).contains(&next.z) {
+                continue;
+            }
+            let (x, y, z) = (next.x as usize, next.y as usize, next.z as usize);
+            if !block_registry.is_opaque(chunk.get_block(x, y, z)) && chunk.get_block_light(x, y, z) < spread {
+                chunk.set_block_light(x, y, z, spread);
+                queue.push_back(next);
+            }
+        }
+    }
+}
+
+/// The 6 face offsets of a chunk with the coordinates of the chunks across them
+fn face_neighbors(coord: ChunkCoord) -> [(IVec3, ChunkCoord); 6] {
+    NEIGHBOR_OFFSETS.map(|offset| (offset, ChunkCoord::new(coord.x + offset.x, coord.y + offset.y, coord.z + offset.z)))
+}
+
+/// Whether any block light value on the chunk face towards `offset` differs
+fn border_light_changed(old: &NibbleArray, new: &NibbleArray, offset: IVec3) -> bool {
+    let edge = |value: i32| if value > 0 { CHUNK_SIZE - 1 } else { 0 };
+    (0..CHUNK_SIZE).any(|a| {
+        (0..CHUNK_SIZE).any(|b| {
+            let (x, y, z) = match (offset.x, offset.y, offset.z) {
+                (dx, 0, 0) => (edge(dx), a, b),
+                (0, dy, 0) => (a, edge(dy), b),
+                (0, 0, dz) => (a, b, edge(dz)),
+                _ => return false,
+            };
+            let index = x + z * CHUNK_SIZE + y * CHUNK_SIZE * CHUNK_SIZE;
+            old.get(index) != new.get(index)
+        })
+    })
+}
+
+/// Propagate light from neighbors without resetting skylight columns
+/// Used for subsequent passes after initial skylight calculation
+fn propagate_light_from_cache(
+    chunk: &mut Chunk,
+    coord: ChunkCoord,
+    cache: &HashMap<ChunkCoord, CachedLightData>,
+    block_registry: &BlockRegistry,
+) {
+    let get_cached_light = |neighbor_coord: ChunkCoord, x: usize, y: usize, z: usize| -> u8 {
+        cache.get(&neighbor_coord)
+            .map(|c| c.get_light(x, y, z))
+            // If neighbor not in cache, return 0 (dark)
+            // This is rare since we cache all neighbors during remesh
+            .unwrap_or(0)
+    };
+
+    let neg_x = ChunkCoord::new(coord.x - 1, coord.y, coord.z);
+    let pos_x = ChunkCoord::new(coord.x + 1, coord.y, coord.z);
+    let neg_y = ChunkCoord::new(coord.x, coord.y - 1, coord.z);
+    let pos_y = ChunkCoord::new(coord.x, coord.y + 1, coord.z);
+    let neg_z = ChunkCoord::new(coord.x, coord.y, coord.z - 1);
+    let pos_z = ChunkCoord::new(coord.x, coord.y, coord.z + 1);
+
+    for _iteration in 0..(CHUNK_SIZE * 2) {
+        let mut any_change = false;
+
+        // Forward pass
+        for y in 0..CHUNK_SIZE {
+            for z in 0..CHUNK_SIZE {
+                for x in 0..CHUNK_SIZE {
+                    if block_registry.is_opaque(chunk.get_block(x, y, z)) {
+                        continue;
+                    }
+
+                    let current_light = chunk.get_light(x, y, z);
+                    let mut max_neighbor: u8 = 0;
+
+                    // Check neighbors within this chunk
+                    if x > 0 { max_neighbor = max_neighbor.max(chunk.get_light(x - 1, y, z)); }
+                    if x < CHUNK_SIZE - 1 { max_neighbor = max_neighbor.max(chunk.get_light(x + 1, y, z)); }
+                    if y > 0 { max_neighbor = max_neighbor.max(chunk.get_light(x, y - 1, z)); }
+                    if y < CHUNK_SIZE - 1 { max_neighbor = max_neighbor.max(chunk.get_light(x, y + 1, z)); }
+                    if z > 0 { max_neighbor = max_neighbor.max(chunk.get_light(x, y, z - 1)); }
+                    if z < CHUNK_SIZE - 1 { max_neighbor = max_neighbor.max(chunk.get_light(x, y, z + 1)); }
+
+                    // Check cross-chunk neighbors using cached data
+                    if x == 0 { max_neighbor = max_neighbor.max(get_cached_light(neg_x, CHUNK_SIZE - 1, y, z)); }
+                    if x == CHUNK_SIZE - 1 { max_neighbor = max_neighbor.max(get_cached_light(pos_x, 0, y, z)); }
+                    if y == 0 { max_neighbor = max_neighbor.max(get_cached_light(neg_y, x, CHUNK_SIZE - 1, z)); }
+                    if y == CHUNK_SIZE - 1 { max_neighbor = max_neighbor.max(get_cached_light(pos_y, x, 0, z)); }
+                    if z == 0 { max_neighbor = max_neighbor.max(get_cached_light(neg_z, x, y, CHUNK_SIZE - 1)); }
+                    if z == CHUNK_SIZE - 1 { max_neighbor = max_neighbor.max(get_cached_light(pos_z, x, y, 0)); }
+
+                    let propagated = max_neighbor.saturating_sub(1);
+                    if propagated > current_light {
+                        chunk.set_light(x, y, z, propagated);
+                        any_change = true;
+                    }
+                }
+            }
+        }
+
+        // Backward pass for faster convergence
+        for y in (0..CHUNK_SIZE).rev() {
+            for z in (0..CHUNK_SIZE).rev() {
+                for x in (0..CHUNK_SIZE).rev() {
+                    if block_registry.is_opaque(chunk.get_block(x, y, z)) {
+                        continue;
+                    }
+
+                    let current_light = chunk.get_light(x, y, z);
+                    let mut max_neighbor: u8 = 0;
+
+                    if x > 0 { max_neighbor = max_neighbor.max(chunk.get_light(x - 1, y, z)); }
+                    if x < CHUNK_SIZE - 1 { max_neighbor = max_neighbor.max(chunk.get_light(x + 1, y, z)); }
+                    if y > 0 { max_neighbor = max_neighbor.max(chunk.get_light(x, y - 1, z)); }
+                    if y < CHUNK_SIZE - 1 { max_neighbor = max_neighbor.max(chunk.get_light(x, y + 1, z)); }
+                    if z > 0 { max_neighbor = max_neighbor.max(chunk.get_light(x, y, z - 1)); }
+                    if z < CHUNK_SIZE - 1 { max_neighbor = max_neighbor.max(chunk.get_light(x, y, z + 1)); }
+
+                    if x == 0 { max_neighbor = max_neighbor.max(get_cached_light(neg_x, CHUNK_SIZE - 1, y, z)); }
+                    if x == CHUNK_SIZE - 1 { max_neighbor = max_neighbor.max(get_cached_light(pos_x, 0, y, z)); }
+                    if y == 0 { max_neighbor = max_neighbor.max(get_cached_light(neg_y, x, CHUNK_SIZE - 1, z)); }
+                    if y == CHUNK_SIZE - 1 { max_neighbor = max_neighbor.max(get_cached_light(pos_y, x, 0, z)); }
+                    if z == 0 { max_neighbor = max_neighbor.max(get_cached_light(neg_z, x, y, CHUNK_SIZE - 1)); }
+                    if z == CHUNK_SIZE - 1 { max_neighbor = max_neighbor.max(get_cached_light(pos_z, x, y, 0)); }
+
+                    let propagated = max_neighbor.saturating_sub(1);
+                    if propagated > current_light {
+                        chunk.set_light(x, y, z, propagated);
+                        any_change = true;
+                    }
+                }
+            }
+        }
+
+        if !any_change {
+            break;
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::ecs::system::RunSystemOnce;
+    use crate::blocks::{BlockState, BlockType};
+
+    /// A registry with stone and water, returning (registry, stone, water)
+    fn registry() -> (BlockRegistry, BlockId, BlockId) {
+        let mut registry = BlockRegistry::new();
+        let stone = registry.register_block(BlockType::builder("core:stone", "Stone").build()).unwrap();
+        let water = registry.register_block(
+            BlockType::builder("core:water", "Water").solid(false).transparent(true).liquid(true).build()
+        ).unwrap();
+        (registry, stone, water)
+    }
+
+    fn uniform_chunk(id: BlockId) -> Chunk {
+        let mut chunk = Chunk::new(ChunkCoord::new(0, 0, 0));
+        chunk.blocks = PalettedContainer::filled(BlockState::new(id));
+        chunk.light_levels.fill(0);
+        chunk
+    }
+
+    /// Relight a lone chunk under open sky and return its sky light at the bottom
+    fn relight_alone(chunk: Chunk, registry: BlockRegistry) -> u8 {
+        let mut world = World::new();
+        world.insert_resource(registry);
+        world.init_resource::<Events<ChunkLightChanged>>();
+        let entity = world.spawn((chunk, NeedsRelight)).id();
+        let mut chunk_manager = ChunkManager::default();
+        chunk_manager.loaded_chunks.insert(ChunkCoord::new(0, 0, 0), entity);
+        world.insert_resource(chunk_manager);
+
+        world.run_system_once(relight_chunks).unwrap();
+        world.get::<Chunk>(entity).unwrap().get_light(8, 0, 8)
+    }
+
+    #[test]
+    fn uniform_water_chunks_let_skylight_through() {
+        let (registry, _, water) = registry();
+
+        let mut chunk = uniform_chunk(water);
+        chunk.calculate_skylight(&registry);
+        assert_eq!(chunk.get_light(8, 0, 8), MAX_LIGHT_LEVEL);
+
+        assert_eq!(relight_alone(uniform_chunk(water), registry), MAX_LIGHT_LEVEL);
+    }
+
+    #[test]
+    fn uniform_stone_chunks_stay_dark() {
+        let (registry, stone, _) = registry();
+
+        let mut chunk = uniform_chunk(stone);
+        chunk.calculate_skylight(&registry);
+        assert_eq!(chunk.get_light(8, 0, 8), 0);
+
+        assert_eq!(relight_alone(uniform_chunk(stone), registry), 0);
+    }
+}
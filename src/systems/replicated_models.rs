@@ -0,0 +1,76 @@
+use bevy::prelude::*;
+use crate::assets::AssetManager;
+use crate::blocks::BlockRegistry;
+use crate::components::PLAYER_MODEL_PARTS;
+use crate::entities::EntityRegistry;
+use crate::net::protocol::NetEntityKind;
+use crate::net::Replicated;
+use crate::rendering::terrain_material::TerrainMaterial;
+use crate::world::mesh_gen::create_single_block_mesh;
+use super::falling_blocks::block_entity_material;
+use super::item_drops::spawn_drop_model;
+use super::mobs::{spawn_mob_model, MobPartAssets};
+
+/// Give the entities a server tells us about the models their local counterparts get:
+/// other players the player's cuboid body, mobs their type's model, drops and falling
+/// blocks their block mesh
+/// Their `Transform` is moved between the server's snapshots by `ClientNetPlugin`
+#[allow(clippy::too_many_arguments)]
+pub fn attach_replicated_models(
+    mut commands: Commands,
+    replicated: Query<(Entity, &Replicated), Added<Replicated>>,
+    entity_registry: Res<EntityRegistry>,
+    block_registry: Res<BlockRegistry>,
+    asset_manager: Res<AssetManager>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<TerrainMaterial>>,
+    mut standard_materials: ResMut<Assets<StandardMaterial>>,
+    mut block_material: Local<Option<Handle<TerrainMaterial>>>,
+    mut mob_parts: Local<MobPartAssets>,
+    mut player_parts: Local<Vec<(Handle<Mesh>, Handle<StandardMaterial>)>>,
+) {
+    // Every player looks the same, so their parts are built once
+    if player_parts.is_empty() {
+        *player_parts = PLAYER_MODEL_PARTS.iter()
+            .map(|&(size, _, color)| (
+                meshes.add(Cuboid::from_size(size)),
+                standard_materials.add(StandardMaterial {
+                    base_color: color,
+                    perceptual_roughness: 1.0,
+                    ..default()
+                }),
+            ))
+            .collect();
+    }
+
+    for (entity, replicated) in replicated.iter() {
+        let mut parent = commands.entity(entity);
+        match &replicated.kind {
+            NetEntityKind::Player { .. } => {
+                parent.insert(Visibility::default()).with_children(|parent| {
+                    for ((mesh, material), (_, center, _)) in player_parts.iter().zip(PLAYER_MODEL_PARTS) {
+                        parent.spawn((
+                            Mesh3d(mesh.clone()),
+                            MeshMaterial3d(material.clone()),
+                            Transform::from_translation(center),
+                        ));
+                    }
+                });
+            }
+            NetEntityKind::Mob { entity_type } => {
+                spawn_mob_model(&mut parent, *entity_type, &entity_registry, &asset_manager, &mut meshes, &mut standard_materials, &mut mob_parts);
+            }
+            NetEntityKind::Item { stack } => {
+                spawn_drop_model(&mut parent, stack.item, &mut meshes, &mut materials, &mut standard_materials, &asset_manager, &block_registry, &mut block_material);
+            }
+            NetEntityKind::FallingBlock { state } => {
+                if let Some(mesh) = create_single_block_mesh(state.id, &block_registry) {
+                    parent.insert((
+                        Mesh3d(meshes.add(mesh)),
+                        MeshMaterial3d(block_entity_material(&mut block_material, &asset_manager, &mut materials)),
+                    ));
+                }
+            }
+        }
+    }
+}
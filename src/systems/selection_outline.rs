@@ -0,0 +1,79 @@
+use bevy::prelude::*;
+use crate::components::{Player, SelectionOutline};
+use crate::world::Selection;
+
+/// Draw a yellow wireframe around the local player's selection (or the one corner set so far),
+/// rebuilt only when the selection changes
+pub fn update_selection_outline(
+    mut commands: Commands,
+    player_query: Query<Ref<Selection>, With<Player>>,
+    existing_outlines: Query<Entity, With<SelectionOutline>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let Ok(selection) = player_query.get_single() else {
+        return;
+    };
+    if !selection.is_changed() {
+        return;
+    }
+
+    for entity in existing_outlines.iter() {
+        commands.entity(entity).despawn();
+    }
+
+    let (min, max) = match (selection.bounds(), selection.first.or(selection.second)) {
+        (Some(bounds), _) => bounds,
+        (None, Some(corner)) => (corner, corner),
+        (None, None) => return,
+    };
+
+    let material = materials.add(StandardMaterial {
+        base_color: Color::srgba(1.0, 0.85, 0.0, 0.9),
+        unlit: true,
+        alpha_mode: AlphaMode::Blend,
+        ..default()
+    });
+
+    commands.spawn((
+        Mesh3d(meshes.add(create_box_mesh((max - min + IVec3::ONE).as_vec3()))),
+        MeshMaterial3d(material),
+        Transform::from_translation(min.as_vec3()),
+        SelectionOutline,
+    ));
+}
+
+/// The 12 edges of a box from the origin to `size`, pushed out a little so they aren't
+/// hidden inside the faces of the selected blocks
+fn create_box_mesh(size: Vec3) -> Mesh {
+    use bevy::render::mesh::{Indices, PrimitiveTopology};
+    use bevy::render::render_asset::RenderAssetUsages;
+
+    const MARGIN: f32 = 0.01;
+    let (low, high) = (Vec3::splat(-MARGIN), size + Vec3::splat(MARGIN));
+
+    // Corners numbered by their bits: x = 1, y = 2, z = 4
+    let positions: Vec<[f32; 3]> = (0..8)
+        .map(|corner| {
+            let pick = |bit: usize, axis: usize| if corner & bit != 0 { high[axis] } else { low[axis] };
+            [pick(1, 0), pick(2, 1), pick(4, 2)]
+        })
+        .collect();
+
+    // Each edge joins two corners one bit apart
+    let mut indices: Vec<u32> = Vec::new();
+    for corner in 0..8u32 {
+        for bit in [1, 2, 4] {
+            if corner & bit == 0 {
+                indices.extend_from_slice(&[corner, corner | bit]);
+            }
+        }
+    }
+
+    let normals = vec![[0.0, 1.0, 0.0]; positions.len()];
+
+    Mesh::new(PrimitiveTopology::LineList, RenderAssetUsages::default())
+        .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, normals)
+        .with_inserted_indices(Indices::U32(indices))
+}
@@ -36,6 +36,20 @@ impl TimeOfDay {
     /// Minecraft's day is 20 minutes (1200 seconds)
     pub const DAY_LENGTH_SECONDS: f32 = 1200.0;
 
+    /// World ticks in a day, as counted by `/time` (0 is sunrise, 6000 noon)
+    pub const TICKS_PER_DAY: i64 = 24000;
+
+    /// The time of day in ticks since sunrise (0 to 23999)
+    pub fn ticks(&self) -> i64 {
+        ((self.time + 0.75) % 1.0 * Self::TICKS_PER_DAY as f32).round() as i64 % Self::TICKS_PER_DAY
+    }
+
+    /// Set the time of day from ticks since sunrise, any whole number of days before or after
+    pub fn set_ticks(&mut self, ticks: i64) {
+        let day_fraction = ticks.rem_euclid(Self::TICKS_PER_DAY) as f32 / Self::TICKS_PER_DAY as f32;
+        self.time = (day_fraction + 0.25) % 1.0;
+    }
+
     /// Get the current hour (0-24)
     pub fn hour(&self) -> f32 {
         self.time * 24.0
@@ -478,30 +492,3 @@ pub fn update_night_skybox_alpha(
         }
     }
 }
-
-/// Handle time control input (for testing)
-pub fn handle_time_controls(
-    keyboard: Res<ButtonInput<KeyCode>>,
-    mut time_of_day: ResMut<TimeOfDay>,
-) {
-    // T key: toggle time pause
-    if keyboard.just_pressed(KeyCode::KeyT) {
-        time_of_day.paused = !time_of_day.paused;
-    }
-
-    // [ and ] keys: slow down / speed up time
-    if keyboard.just_pressed(KeyCode::BracketLeft) {
-        time_of_day.speed = (time_of_day.speed / 2.0).max(0.125);
-    }
-    if keyboard.just_pressed(KeyCode::BracketRight) {
-        time_of_day.speed = (time_of_day.speed * 2.0).min(64.0);
-    }
-
-    // , and . keys: jump time backward/forward by 1 hour
-    if keyboard.just_pressed(KeyCode::Comma) {
-        time_of_day.time = (time_of_day.time - 1.0 / 24.0 + 1.0) % 1.0;
-    }
-    if keyboard.just_pressed(KeyCode::Period) {
-        time_of_day.time = (time_of_day.time + 1.0 / 24.0) % 1.0;
-    }
-}
@@ -1,5 +1,6 @@
 use bevy::prelude::*;
-use crate::components::CameraController;
+use crate::components::Player;
+use crate::items::ItemRegistry;
 use crate::resources::PlayerInventory;
 
 /// Marker component for the torch's point light
@@ -10,15 +11,16 @@ pub struct TorchLight;
 pub fn update_torch_light(
     mut commands: Commands,
     inventory: Res<PlayerInventory>,
-    camera_query: Query<&Transform, With<CameraController>>,
+    item_registry: Res<ItemRegistry>,
+    player_query: Query<(&Transform, &Player)>,
     torch_light_query: Query<Entity, With<TorchLight>>,
 ) {
-    let torch_selected = inventory.is_torch_selected();
+    let torch_selected = inventory.is_torch_selected(&item_registry);
     let torch_light_exists = !torch_light_query.is_empty();
 
     if torch_selected && !torch_light_exists {
         // Spawn torch light when torch is selected
-        if let Ok(camera_transform) = camera_query.get_single() {
+        if let Ok((player_transform, _)) = player_query.get_single() {
             commands.spawn((
                 PointLight {
                     color: Color::srgb(1.0, 0.85, 0.5), // Warm orange/yellow torch color
@@ -27,7 +29,7 @@ pub fn update_torch_light(
                     shadows_enabled: true,
                     ..default()
                 },
-                Transform::from_translation(camera_transform.translation),
+                Transform::from_translation(Player::eye_position(player_transform)),
                 TorchLight,
             ));
         }
@@ -40,24 +42,23 @@ pub fn update_torch_light(
 }
 
 /// Update torch light position to follow the player
+/// The light only exists while a torch is selected
 pub fn follow_player_with_torch_light(
-    inventory: Res<PlayerInventory>,
-    camera_query: Query<&Transform, With<CameraController>>,
-    mut torch_light_query: Query<&mut Transform, (With<TorchLight>, Without<CameraController>)>,
+    player_query: Query<(&Transform, &Player), Without<TorchLight>>,
+    mut torch_light_query: Query<&mut Transform, With<TorchLight>>,
 ) {
-    // Only update if torch is selected
-    if !inventory.is_torch_selected() {
+    if torch_light_query.is_empty() {
         return;
     }
 
-    let Ok(camera_transform) = camera_query.get_single() else {
+    let Ok((player_transform, player)) = player_query.get_single() else {
         return;
     };
 
     for mut light_transform in torch_light_query.iter_mut() {
-        // Position the light slightly in front and below the camera (like holding a torch)
-        let forward = camera_transform.forward();
+        // Position the light slightly in front of and below the eyes (like holding a torch)
+        let forward = player.look_direction();
         let offset = forward * 0.5 + Vec3::new(0.0, -0.3, 0.0);
-        light_transform.translation = camera_transform.translation + offset;
+        light_transform.translation = Player::eye_position(player_transform) + offset;
     }
 }
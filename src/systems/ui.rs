@@ -1,7 +1,7 @@
 use bevy::prelude::*;
 use bevy::diagnostic::{DiagnosticsStore, FrameTimeDiagnosticsPlugin};
 use bevy::window::{PrimaryWindow, CursorGrabMode};
-use crate::components::{ClickText, FpsCounter, PauseMenu, ResumeButton, ExitButton, CameraController};
+use crate::components::{ClickText, FpsCounter, PauseMenu, ResumeButton, ExitButton, Player};
 use crate::resources::GameState;
 
 pub fn update_click_text_timer(
@@ -22,7 +22,7 @@ pub fn update_click_text_timer(
 pub fn update_fps_counter(
     diagnostics: Res<DiagnosticsStore>,
     mut query: Query<(&mut Text, &FpsCounter)>,
-    camera_query: Query<(&Transform, &CameraController), With<CameraController>>,
+    player_query: Query<(&Transform, &Player)>,
 ) {
     for (mut text, fps_counter) in query.iter_mut() {
         if fps_counter.visible {
@@ -40,12 +40,12 @@ pub fn update_fps_counter(
             }
 
             // Add position and compass
-            if let Ok((transform, controller)) = camera_query.get_single() {
+            if let Ok((transform, player)) = player_query.get_single() {
                 let pos = transform.translation;
                 debug_text.push_str(&format!("X: {:.1}\nY: {:.1}\nZ: {:.1}\n", pos.x, pos.y, pos.z));
 
                 // Calculate cardinal direction from yaw
-                let direction = get_cardinal_direction(controller.yaw);
+                let direction = get_cardinal_direction(player.yaw);
                 debug_text.push_str(&format!("Facing: {}", direction));
             }
 
@@ -86,7 +86,7 @@ pub fn update_pause_menu_visibility(
     mut menu_query: Query<&mut Visibility, With<PauseMenu>>,
 ) {
     for mut visibility in menu_query.iter_mut() {
-        *visibility = if game_state.paused {
+        *visibility = if game_state.paused && !game_state.dead {
             Visibility::Visible
         } else {
             Visibility::Hidden
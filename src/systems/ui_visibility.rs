@@ -1,6 +1,7 @@
 use bevy::prelude::*;
-use crate::components::{Hotbar, DebugOverlay, Crosshair, SurvivalBars, HeartIcon, HungerIcon};
+use crate::components::{Hotbar, DebugOverlay, Crosshair, SurvivalBars, HeartIcon, HungerIcon, AirIcon, HurtOverlay, GameMode, Player};
 use crate::resources::{GameState, PlayerStats};
+use crate::systems::INVULNERABILITY_TICKS;
 
 // Icon atlas constants (icons.png is 256x256, icons are 9x9 pixels)
 const ICON_PIXELS: f32 = 9.0;
@@ -89,18 +90,22 @@ pub fn update_debug_visibility_on_ui_toggle(
 }
 
 /// Update survival bars visibility based on game mode
-/// Visible in survival mode, hidden in creative mode
+/// Visible in modes that can take damage, hidden in creative and spectator
 pub fn update_survival_bars_visibility(
     game_state: Res<GameState>,
+    player_query: Query<Ref<GameMode>, With<Player>>,
     mut bars_query: Query<&mut Visibility, With<SurvivalBars>>,
 ) {
-    if !game_state.is_changed() {
+    let Ok(game_mode) = player_query.get_single() else {
+        return;
+    };
+    if !game_state.is_changed() && !game_mode.is_changed() {
         return;
     }
 
     for mut visibility in bars_query.iter_mut() {
-        // Show in survival mode when UI is visible
-        *visibility = if game_state.ui_visible && !game_state.creative_mode {
+        // Show when the player can be hurt and the UI is visible
+        *visibility = if game_state.ui_visible && !game_mode.abilities().invulnerable {
             Visibility::Visible
         } else {
             Visibility::Hidden
@@ -170,3 +175,39 @@ pub fn update_hunger_display(
         }
     }
 }
+
+/// Update air bubbles from the air meter
+/// Bubbles only show while some air has been used; each one is a tenth of the meter
+pub fn update_air_display(
+    stats: Res<PlayerStats>,
+    mut air_query: Query<(&AirIcon, &mut Visibility)>,
+) {
+    if !stats.is_changed() {
+        return;
+    }
+
+    let full = stats.air >= stats.max_air;
+    let bubbles = (stats.air as usize * 10) / stats.max_air.max(1) as usize;
+    for (bubble, mut visibility) in air_query.iter_mut() {
+        *visibility = if !full && bubble.index < bubbles {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+}
+
+/// Fade the red damage flash out over the invulnerability frames
+pub fn update_hurt_flash(
+    stats: Res<PlayerStats>,
+    mut overlay_query: Query<&mut BackgroundColor, With<HurtOverlay>>,
+) {
+    if !stats.is_changed() {
+        return;
+    }
+
+    let strength = stats.invulnerable_ticks as f32 / INVULNERABILITY_TICKS as f32;
+    for mut background in overlay_query.iter_mut() {
+        background.0 = Color::srgba(0.8, 0.0, 0.0, 0.35 * strength);
+    }
+}
@@ -0,0 +1,114 @@
+use bevy::prelude::*;
+use rand::Rng;
+use std::collections::HashSet;
+use std::sync::Arc;
+use crate::blocks::BlockBehavior;
+use crate::world::{BlockWorld, WorldTick, CHUNK_SIZE, RANDOM_TICKS_PER_SECTION};
+use crate::components::GameMode;
+use crate::physics::PhysicsBody;
+use crate::systems::{ItemDrop, WorldBlocks};
+
+/// Maximum scheduled ticks processed per world tick (guards against runaway feedback loops)
+const MAX_SCHEDULED_TICKS_PER_TICK: usize = 65536;
+
+/// Advance the world tick counter (runs first in FixedUpdate)
+pub fn advance_world_tick(mut world_tick: ResMut<WorldTick>) {
+    world_tick.tick += 1;
+}
+
+/// Run every scheduled block tick that has come due
+pub fn process_scheduled_ticks(mut world_blocks: WorldBlocks) {
+    world_blocks.edit(|world| {
+        // Only ticks that were due when this tick started - behaviors may schedule new ones
+        let mut due = Vec::new();
+        while due.len() < MAX_SCHEDULED_TICKS_PER_TICK {
+            let Some(pos) = world.pop_due_tick() else {
+                break;
+            };
+            due.push(pos);
+        }
+
+        for pos in due {
+            world.run_scheduled_tick(pos);
+        }
+    });
+}
+
+/// Pick RANDOM_TICKS_PER_SECTION random blocks in every loaded chunk and random-tick them
+pub fn random_tick_chunks(mut world_blocks: WorldBlocks) {
+    let chunk_origins: Vec<IVec3> = world_blocks.chunk_manager().loaded_chunks.keys()
+        .map(|coord| IVec3::new(coord.x, coord.y, coord.z) * CHUNK_SIZE as i32)
+        .collect();
+
+    world_blocks.edit(|world| {
+        for chunk_origin in chunk_origins {
+            for _ in 0..RANDOM_TICKS_PER_SECTION {
+                let offset = IVec3::new(
+                    world.rng().gen_range(0..CHUNK_SIZE as i32),
+                    world.rng().gen_range(0..CHUNK_SIZE as i32),
+                    world.rng().gen_range(0..CHUNK_SIZE as i32),
+                );
+                let pos = chunk_origin + offset;
+
+                // Only blocks registered with random_ticks(true) are picked
+                let Some(block_id) = world.get_block(pos) else {
+                    continue;
+                };
+                let wants_random_ticks = !block_id.is_air() && world.registry().get_block(block_id)
+                    .is_some_and(|block| block.properties.random_ticks);
+                if !wants_random_ticks {
+                    continue;
+                }
+
+                if let Some(behavior) = block_behavior_at(world, pos) {
+                    behavior.random_tick(world, pos);
+                }
+            }
+        }
+    });
+}
+
+/// Tell blocks when entities move into or out of their cell (pressure plates)
+/// Occupied cells are tracked between ticks so each block sees one enter and one leave.
+/// Noclip bodies (spectators) pass through without touching anything
+pub fn update_block_contacts(
+    mut world_blocks: WorldBlocks,
+    bodies: Query<(&PhysicsBody, Option<&GameMode>)>,
+    item_drops: Query<&Transform, With<ItemDrop>>,
+    mut occupied: Local<HashSet<IVec3>>,
+) {
+    let feet = bodies.iter()
+        .filter(|(_, game_mode)| !game_mode.is_some_and(|mode| mode.abilities().noclip))
+        .map(|(body, _)| body.position);
+    let drops = item_drops.iter().map(|transform| transform.translation);
+    let now_occupied: HashSet<IVec3> = feet.chain(drops)
+        .map(|pos| pos.floor().as_ivec3())
+        .collect();
+
+    // Sorted so the callbacks run in the same order every time
+    let mut entered: Vec<IVec3> = now_occupied.difference(&occupied).copied().collect();
+    let mut left: Vec<IVec3> = occupied.difference(&now_occupied).copied().collect();
+    entered.sort_by_key(|pos| (pos.x, pos.y, pos.z));
+    left.sort_by_key(|pos| (pos.x, pos.y, pos.z));
+
+    if !entered.is_empty() || !left.is_empty() {
+        world_blocks.edit(|world| {
+            for pos in left {
+                world.entity_left(pos);
+            }
+            for pos in entered {
+                world.entity_entered(pos);
+            }
+        });
+    }
+    *occupied = now_occupied;
+}
+
+/// Look up the behavior of the block currently at `pos`
+fn block_behavior_at(world: &BlockWorld, pos: IVec3) -> Option<Arc<dyn BlockBehavior>> {
+    let block_id = world.get_block(pos)?;
+    if block_id.is_air() {
+        return None;
+    }
+    world.registry().get_block(block_id)?.properties.behavior.clone()
+}
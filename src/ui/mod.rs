@@ -1,3 +1,3 @@
 mod setup;
 
-pub use setup::{setup_pause_menu, setup_hotbar, setup_survival_bars};
+pub use setup::{setup_pause_menu, setup_death_screen, setup_crafting_screen, setup_furnace_screen, setup_hotbar, setup_survival_bars, setup_chat};
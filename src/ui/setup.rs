@@ -1,5 +1,6 @@
 use bevy::prelude::*;
-use crate::components::{PauseMenu, ResumeButton, ExitButton, Hotbar, HotbarSlot, HotbarSlotIcon, HotbarSlotText, SurvivalBars, HeartBackground, HeartIcon, HungerBackground, HungerIcon};
+use crate::components::{PauseMenu, ResumeButton, ExitButton, DeathScreen, RespawnButton, CraftingScreenRoot, CraftingSlot, CraftingOutput, CraftingSlotLabel, FurnaceScreenRoot, FurnaceSlotButton, FurnaceSlotLabel, FurnaceBurnFill, FurnaceProgressFill, Hotbar, HotbarSlot, HotbarSlotIcon, HotbarSlotText, HotbarSlotCount, HotbarSlotDurability, HotbarSlotDurabilityFill, SurvivalBars, HeartBackground, HeartIcon, HungerBackground, HungerIcon, AirIcon, HurtOverlay, ChatMessages, ChatSuggestions, ChatInput};
+use crate::crafting::FurnaceSlot;
 use crate::assets::{AssetManager, IconsTextureHandle};
 
 pub fn setup_pause_menu(mut commands: Commands) {
@@ -94,6 +95,324 @@ pub fn setup_pause_menu(mut commands: Commands) {
         });
 }
 
+pub fn setup_death_screen(mut commands: Commands) {
+    // Red-tinted full screen overlay shown while the player is dead (initially hidden)
+    commands
+        .spawn((
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                position_type: PositionType::Absolute,
+                flex_direction: FlexDirection::Column,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                row_gap: Val::Px(40.0),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.5, 0.0, 0.0, 0.6)),
+            Visibility::Hidden,
+            DeathScreen,
+        ))
+        .with_children(|parent| {
+            // Title
+            parent.spawn((
+                Text::new("You died!"),
+                TextFont {
+                    font_size: 60.0,
+                    ..default()
+                },
+                TextColor(Color::srgb(1.0, 1.0, 1.0)),
+            ));
+
+            // Respawn button
+            parent
+                .spawn((
+                    Button,
+                    Node {
+                        width: Val::Px(250.0),
+                        height: Val::Px(65.0),
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    BackgroundColor(Color::srgb(0.15, 0.15, 0.15)),
+                    RespawnButton,
+                ))
+                .with_children(|parent| {
+                    parent.spawn((
+                        Text::new("Respawn"),
+                        TextFont {
+                            font_size: 32.0,
+                            ..default()
+                        },
+                        TextColor(Color::srgb(0.9, 0.9, 0.9)),
+                    ));
+                });
+        });
+}
+
+/// Crafting table screen: a 3x3 grid of slots, an arrow and the result slot
+/// Slots show item names as text labels, filled in by `update_crafting_screen`
+pub fn setup_crafting_screen(mut commands: Commands) {
+    const SLOT_SIZE: f32 = 72.0;
+    const SLOT_GAP: f32 = 4.0;
+
+    let slot_node = Node {
+        width: Val::Px(SLOT_SIZE),
+        height: Val::Px(SLOT_SIZE),
+        border: UiRect::all(Val::Px(2.0)),
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        ..default()
+    };
+    let label = |index: Option<usize>| (
+        Text::new(""),
+        TextFont {
+            font_size: 13.0,
+            ..default()
+        },
+        TextColor(Color::srgb(1.0, 1.0, 1.0)),
+        TextLayout::new_with_justify(JustifyText::Center),
+        CraftingSlotLabel { index },
+    );
+
+    commands
+        .spawn((
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                position_type: PositionType::Absolute,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.5)),
+            Visibility::Hidden,
+            CraftingScreenRoot,
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn((
+                    Node {
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        row_gap: Val::Px(16.0),
+                        padding: UiRect::all(Val::Px(24.0)),
+                        ..default()
+                    },
+                    BackgroundColor(Color::srgba(0.1, 0.1, 0.1, 0.9)),
+                ))
+                .with_children(|parent| {
+                    parent.spawn((
+                        Text::new("Crafting"),
+                        TextFont {
+                            font_size: 32.0,
+                            ..default()
+                        },
+                        TextColor(Color::srgb(1.0, 1.0, 1.0)),
+                    ));
+
+                    parent
+                        .spawn(Node {
+                            align_items: AlignItems::Center,
+                            column_gap: Val::Px(24.0),
+                            ..default()
+                        })
+                        .with_children(|parent| {
+                            // 3x3 grid
+                            parent
+                                .spawn(Node {
+                                    display: Display::Grid,
+                                    grid_template_columns: RepeatedGridTrack::px(3, SLOT_SIZE),
+                                    row_gap: Val::Px(SLOT_GAP),
+                                    column_gap: Val::Px(SLOT_GAP),
+                                    ..default()
+                                })
+                                .with_children(|parent| {
+                                    for index in 0..9 {
+                                        parent
+                                            .spawn((
+                                                Button,
+                                                slot_node.clone(),
+                                                BorderColor(Color::srgb(0.3, 0.3, 0.3)),
+                                                BackgroundColor(Color::srgb(0.15, 0.15, 0.15)),
+                                                CraftingSlot { index },
+                                            ))
+                                            .with_children(|parent| {
+                                                parent.spawn(label(Some(index)));
+                                            });
+                                    }
+                                });
+
+                            parent.spawn((
+                                Text::new("->"),
+                                TextFont {
+                                    font_size: 40.0,
+                                    ..default()
+                                },
+                                TextColor(Color::srgb(0.8, 0.8, 0.8)),
+                            ));
+
+                            parent
+                                .spawn((
+                                    Button,
+                                    slot_node.clone(),
+                                    BorderColor(Color::srgb(0.6, 0.6, 0.6)),
+                                    BackgroundColor(Color::srgb(0.15, 0.15, 0.15)),
+                                    CraftingOutput,
+                                ))
+                                .with_children(|parent| {
+                                    parent.spawn(label(None));
+                                });
+                        });
+
+                    parent.spawn((
+                        Text::new("Left click: place one of the held item | Right click: take back | E / Esc: close"),
+                        TextFont {
+                            font_size: 14.0,
+                            ..default()
+                        },
+                        TextColor(Color::srgb(0.7, 0.7, 0.7)),
+                    ));
+                });
+        });
+}
+
+/// Furnace screen: input over a flame gauge over fuel, a progress bar, then the output slot
+/// Labels and gauges are filled in from the furnace's block entity by `update_furnace_screen`
+pub fn setup_furnace_screen(mut commands: Commands) {
+    const SLOT_SIZE: f32 = 72.0;
+    const GAUGE_HEIGHT: f32 = 8.0;
+
+    let slot = |slot: FurnaceSlot, border: Color| (
+        Button,
+        Node {
+            width: Val::Px(SLOT_SIZE),
+            height: Val::Px(SLOT_SIZE),
+            border: UiRect::all(Val::Px(2.0)),
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        BorderColor(border),
+        BackgroundColor(Color::srgb(0.15, 0.15, 0.15)),
+        FurnaceSlotButton { slot },
+    );
+    let label = |slot: FurnaceSlot| (
+        Text::new(""),
+        TextFont {
+            font_size: 13.0,
+            ..default()
+        },
+        TextColor(Color::srgb(1.0, 1.0, 1.0)),
+        TextLayout::new_with_justify(JustifyText::Center),
+        FurnaceSlotLabel { slot },
+    );
+    let gauge = Node {
+        width: Val::Px(SLOT_SIZE),
+        height: Val::Px(GAUGE_HEIGHT),
+        ..default()
+    };
+    let fill = Node {
+        width: Val::Percent(0.0),
+        height: Val::Percent(100.0),
+        ..default()
+    };
+
+    commands
+        .spawn((
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                position_type: PositionType::Absolute,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.5)),
+            Visibility::Hidden,
+            FurnaceScreenRoot,
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn((
+                    Node {
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        row_gap: Val::Px(16.0),
+                        padding: UiRect::all(Val::Px(24.0)),
+                        ..default()
+                    },
+                    BackgroundColor(Color::srgba(0.1, 0.1, 0.1, 0.9)),
+                ))
+                .with_children(|parent| {
+                    parent.spawn((
+                        Text::new("Furnace"),
+                        TextFont {
+                            font_size: 32.0,
+                            ..default()
+                        },
+                        TextColor(Color::srgb(1.0, 1.0, 1.0)),
+                    ));
+
+                    parent
+                        .spawn(Node {
+                            align_items: AlignItems::Center,
+                            column_gap: Val::Px(24.0),
+                            ..default()
+                        })
+                        .with_children(|parent| {
+                            // Input, flame gauge, fuel
+                            parent
+                                .spawn(Node {
+                                    flex_direction: FlexDirection::Column,
+                                    align_items: AlignItems::Center,
+                                    row_gap: Val::Px(8.0),
+                                    ..default()
+                                })
+                                .with_children(|parent| {
+                                    parent
+                                        .spawn(slot(FurnaceSlot::Input, Color::srgb(0.3, 0.3, 0.3)))
+                                        .with_children(|parent| {
+                                            parent.spawn(label(FurnaceSlot::Input));
+                                        });
+                                    parent
+                                        .spawn((gauge.clone(), BackgroundColor(Color::srgb(0.05, 0.05, 0.05))))
+                                        .with_children(|parent| {
+                                            parent.spawn((fill.clone(), BackgroundColor(Color::srgb(1.0, 0.55, 0.1)), FurnaceBurnFill));
+                                        });
+                                    parent
+                                        .spawn(slot(FurnaceSlot::Fuel, Color::srgb(0.3, 0.3, 0.3)))
+                                        .with_children(|parent| {
+                                            parent.spawn(label(FurnaceSlot::Fuel));
+                                        });
+                                });
+
+                            parent
+                                .spawn((gauge.clone(), BackgroundColor(Color::srgb(0.05, 0.05, 0.05))))
+                                .with_children(|parent| {
+                                    parent.spawn((fill.clone(), BackgroundColor(Color::srgb(0.9, 0.9, 0.9)), FurnaceProgressFill));
+                                });
+
+                            parent
+                                .spawn(slot(FurnaceSlot::Output, Color::srgb(0.6, 0.6, 0.6)))
+                                .with_children(|parent| {
+                                    parent.spawn(label(FurnaceSlot::Output));
+                                });
+                        });
+
+                    parent.spawn((
+                        Text::new("Left click: place one of the held item | Right click: take back | Click result: take | E / Esc: close"),
+                        TextFont {
+                            font_size: 14.0,
+                            ..default()
+                        },
+                        TextColor(Color::srgb(0.7, 0.7, 0.7)),
+                    ));
+                });
+        });
+}
+
 pub fn setup_hotbar(mut commands: Commands) {
     // Minecraft-style hotbar sizing
     // At 1080p, Minecraft's hotbar is roughly 364 pixels wide (9 slots * ~40px each)
@@ -171,6 +490,48 @@ pub fn setup_hotbar(mut commands: Commands) {
                             Visibility::Hidden,
                             HotbarSlotText { slot_index: i },
                         ));
+
+                        // Stack size in the bottom-right corner
+                        slot_parent.spawn((
+                            Text::new(""),
+                            TextFont {
+                                font_size: 14.0,
+                                ..default()
+                            },
+                            TextColor(Color::srgb(1.0, 1.0, 1.0)),
+                            Node {
+                                position_type: PositionType::Absolute,
+                                right: Val::Px(3.0),
+                                bottom: Val::Px(1.0),
+                                ..default()
+                            },
+                            HotbarSlotCount { slot_index: i },
+                        ));
+
+                        // Durability bar under the icon (hidden until a tool has been used)
+                        slot_parent.spawn((
+                            Node {
+                                width: Val::Px(ICON_SIZE - 4.0),
+                                height: Val::Px(4.0),
+                                position_type: PositionType::Absolute,
+                                bottom: Val::Px(4.0),
+                                ..default()
+                            },
+                            BackgroundColor(Color::srgb(0.0, 0.0, 0.0)),
+                            Visibility::Hidden,
+                            HotbarSlotDurability { slot_index: i },
+                        ))
+                        .with_children(|bar| {
+                            bar.spawn((
+                                Node {
+                                    width: Val::Percent(100.0),
+                                    height: Val::Percent(100.0),
+                                    ..default()
+                                },
+                                BackgroundColor(Color::srgb(0.0, 1.0, 0.0)),
+                                HotbarSlotDurabilityFill { slot_index: i },
+                            ));
+                        });
                     });
                 }
             });
@@ -217,11 +578,27 @@ pub fn setup_survival_bars(
     const HUNGER_HALF_U: f32 = 61.0;   // Half haunch (16 + 45)
     const HUNGER_V: f32 = 27.0;
 
+    // Air bubbles row: v=18
+    const AIR_FULL_U: f32 = 16.0;
+    const AIR_V: f32 = 18.0;
+
     // Helper to create rect from pixel coordinates (Bevy's ImageNode.rect uses pixel coords)
     let make_rect = |u: f32, v: f32| -> Rect {
         Rect::new(u, v, u + ICON_PIXELS, v + ICON_PIXELS)
     };
 
+    // Red damage flash behind the bars (alpha driven by update_hurt_flash)
+    commands.spawn((
+        Node {
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            position_type: PositionType::Absolute,
+            ..default()
+        },
+        BackgroundColor(Color::NONE),
+        HurtOverlay,
+    ));
+
     // Container for both health and hunger bars
     commands
         .spawn((
@@ -352,4 +729,94 @@ pub fn setup_survival_bars(
                 });
             });
         });
+
+    // Air bubbles above the hunger bar, only shown while underwater
+    commands
+        .spawn((
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Auto,
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(BAR_BOTTOM + ICON_DISPLAY_SIZE + 2.0),
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            Visibility::Hidden,
+            SurvivalBars,
+        ))
+        .with_children(|parent| {
+            parent.spawn(Node {
+                width: Val::Px(590.0),
+                display: Display::Flex,
+                flex_direction: FlexDirection::RowReverse, // Right-aligned above the hunger bar
+                column_gap: Val::Px(ICON_GAP),
+                ..default()
+            })
+            .with_children(|air_parent| {
+                for i in 0..10 {
+                    air_parent.spawn((
+                        ImageNode {
+                            image: texture.clone(),
+                            rect: Some(make_rect(AIR_FULL_U, AIR_V)),
+                            ..default()
+                        },
+                        Node {
+                            width: Val::Px(ICON_DISPLAY_SIZE),
+                            height: Val::Px(ICON_DISPLAY_SIZE),
+                            ..default()
+                        },
+                        Visibility::Hidden,
+                        AirIcon { index: i },
+                    ));
+                }
+            });
+        });
+}
+
+/// Chat lines, command suggestions and the console's input line, bottom left above the hotbar
+pub fn setup_chat(mut commands: Commands) {
+    let text = |font_size: f32, color: Color| (
+        Text::new(""),
+        TextFont {
+            font_size,
+            ..default()
+        },
+        TextColor(color),
+    );
+
+    commands
+        .spawn(Node {
+            position_type: PositionType::Absolute,
+            left: Val::Px(8.0),
+            bottom: Val::Px(96.0),
+            width: Val::Percent(45.0),
+            flex_direction: FlexDirection::Column,
+            row_gap: Val::Px(2.0),
+            ..default()
+        })
+        .with_children(|parent| {
+            let line = Node {
+                padding: UiRect::all(Val::Px(4.0)),
+                ..default()
+            };
+            parent.spawn((
+                text(16.0, Color::srgb(1.0, 1.0, 1.0)),
+                line.clone(),
+                BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.4)),
+                Visibility::Hidden,
+                ChatMessages,
+            ));
+            parent.spawn((
+                text(14.0, Color::srgb(1.0, 1.0, 0.5)),
+                Visibility::Hidden,
+                ChatSuggestions,
+            ));
+            parent.spawn((
+                text(16.0, Color::srgb(1.0, 1.0, 1.0)),
+                line,
+                BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.6)),
+                Visibility::Hidden,
+                ChatInput,
+            ));
+        });
 }
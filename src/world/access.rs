@@ -0,0 +1,779 @@
+use bevy::prelude::*;
+use rand::rngs::StdRng;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::Arc;
+
+use super::block_entity::BlockEntity;
+use super::history::{BlockDelta, BlockEntityDelta, EditTransaction};
+use super::chunk::{Chunk, ChunkCoord, CHUNK_SIZE};
+use super::terrain::ChunkManager;
+use super::tick::ScheduledTicks;
+use crate::blocks::{BlockBehavior, BlockId, BlockRegistry, BlockState};
+use crate::resources::ItemStack;
+
+/// The six face-adjacent offsets, in the order neighbor updates are delivered
+pub const NEIGHBOR_OFFSETS: [IVec3; 6] = [
+    IVec3::NEG_X, IVec3::X, IVec3::NEG_Y, IVec3::Y, IVec3::NEG_Z, IVec3::Z,
+];
+
+/// World ticks between a gravity block losing its support and starting to fall
+pub const FALL_DELAY_TICKS: u64 = 2;
+
+/// Maximum neighbor updates delivered by one top-level edit
+/// Stops runaway update chains (e.g., two blocks toggling each other forever)
+const MAX_NEIGHBOR_UPDATES: usize = 65536;
+
+/// Farthest a block position may be from the origin on any axis for commands to reach it
+/// or `BlockWorld::set_block` to write it (Minecraft's world border), so sizes and offsets
+/// between positions always fit an i32
+pub const WORLD_LIMIT: i32 = 30_000_000;
+
+/// Split a world block position into its chunk coordinate and local position
+pub fn world_to_chunk_local(pos: IVec3) -> (ChunkCoord, UVec3) {
+    let size = CHUNK_SIZE as i32;
+    let coord = ChunkCoord::new(pos.x.div_euclid(size), pos.y.div_euclid(size), pos.z.div_euclid(size));
+    let local = UVec3::new(
+        pos.x.rem_euclid(size) as u32,
+        pos.y.rem_euclid(size) as u32,
+        pos.z.rem_euclid(size) as u32,
+    );
+    (coord, local)
+}
+
+/// Side effects of `BlockWorld::set_block`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SetBlockFlags(u8);
+
+impl SetBlockFlags {
+    /// Raw write - no callbacks, no neighbor updates (chunks are still remeshed)
+    pub const NONE: SetBlockFlags = SetBlockFlags(0);
+    /// Fire `on_broken` on the old block and `on_placed` on the new one
+    pub const CALLBACKS: SetBlockFlags = SetBlockFlags(1);
+    /// Fire `on_neighbor_changed` on the 6 adjacent blocks
+    pub const NOTIFY_NEIGHBORS: SetBlockFlags = SetBlockFlags(2);
+    /// Everything - what player edits and most behaviors should use
+    pub const DEFAULT: SetBlockFlags = SetBlockFlags(1 | 2);
+
+    pub fn contains(self, other: SetBlockFlags) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl std::ops::BitOr for SetBlockFlags {
+    type Output = SetBlockFlags;
+
+    fn bitor(self, rhs: SetBlockFlags) -> SetBlockFlags {
+        SetBlockFlags(self.0 | rhs.0)
+    }
+}
+
+/// An entity a block edit wants spawned
+/// Collected by `BlockWorld` and sent as a Bevy event by `WorldBlocks::edit`
+#[derive(Event, Debug, Clone, Copy, PartialEq)]
+pub enum WorldSpawn {
+    /// A stack of items (e.g., a broken furnace's contents) dropped as a pickup at `pos`
+    ItemDrop { pos: Vec3, stack: ItemStack },
+    /// A gravity block that lost its support and starts falling from `pos`
+    FallingBlock { pos: IVec3, state: BlockState },
+    /// A block dropped as a pickup item at `pos`
+    BlockDrop { pos: Vec3, block: BlockId },
+    /// A bed at `pos` was used, so the player should respawn there
+    SpawnPointSet { pos: IVec3 },
+    /// A crafting table at `pos` was used, so its crafting screen should open
+    OpenCrafting { pos: IVec3 },
+    /// A furnace at `pos` was used, so its furnace screen should open
+    OpenFurnace { pos: IVec3 },
+}
+
+/// Raw block storage that world simulation reads and writes through
+/// Implemented for the live ECS chunks and for plain in-memory grids (tests)
+pub trait BlockStorage {
+    /// Get the block state at a world position, or None if that chunk isn't loaded
+    fn get_state(&self, pos: IVec3) -> Option<BlockState>;
+
+    /// Write a block state without any side effects
+    /// Returns false if the position isn't loaded
+    fn set_state_raw(&mut self, pos: IVec3, state: BlockState) -> bool;
+
+    /// Get the light level at a world position (0 if not loaded)
+    fn get_light(&self, pos: IVec3) -> u8;
+
+    /// Get the block entity at a world position, if there is one and it's loaded
+    fn block_entity(&self, pos: IVec3) -> Option<&BlockEntity>;
+
+    fn block_entity_mut(&mut self, pos: IVec3) -> Option<&mut BlockEntity>;
+
+    /// Attach or (with None) remove the block entity at a world position, returning the old one
+    fn set_block_entity(&mut self, pos: IVec3, entity: Option<BlockEntity>) -> Option<BlockEntity>;
+
+    /// Get the block at a world position, or None if that chunk isn't loaded
+    fn get_block(&self, pos: IVec3) -> Option<BlockId> {
+        self.get_state(pos).map(|state| state.id)
+    }
+}
+
+/// Block storage backed by the loaded chunk entities
+pub struct ChunkStorage<'a, 'w, 's> {
+    pub chunk_manager: &'a ChunkManager,
+    pub chunks: &'a mut Query<'w, 's, &'static mut Chunk>,
+}
+
+impl BlockStorage for ChunkStorage<'_, '_, '_> {
+    fn get_state(&self, pos: IVec3) -> Option<BlockState> {
+        let (coord, local) = world_to_chunk_local(pos);
+        let entity = self.chunk_manager.loaded_chunks.get(&coord)?;
+        let chunk = self.chunks.get(*entity).ok()?;
+        Some(chunk.get_state(local.x as usize, local.y as usize, local.z as usize))
+    }
+
+    fn set_state_raw(&mut self, pos: IVec3, state: BlockState) -> bool {
+        let (coord, local) = world_to_chunk_local(pos);
+        let Some(&entity) = self.chunk_manager.loaded_chunks.get(&coord) else {
+            return false;
+        };
+        let Ok(mut chunk) = self.chunks.get_mut(entity) else {
+            return false;
+        };
+        chunk.set_state(local.x as usize, local.y as usize, local.z as usize, state);
+        true
+    }
+
+    fn get_light(&self, pos: IVec3) -> u8 {
+        let (coord, local) = world_to_chunk_local(pos);
+        self.chunk_manager.loaded_chunks.get(&coord)
+            .and_then(|entity| self.chunks.get(*entity).ok())
+            .map(|chunk| chunk.get_light(local.x as usize, local.y as usize, local.z as usize))
+            .unwrap_or(0)
+    }
+
+    fn block_entity(&self, pos: IVec3) -> Option<&BlockEntity> {
+        let (coord, local) = world_to_chunk_local(pos);
+        let entity = self.chunk_manager.loaded_chunks.get(&coord)?;
+        self.chunks.get(*entity).ok()?.block_entity(local.x as usize, local.y as usize, local.z as usize)
+    }
+
+    fn block_entity_mut(&mut self, pos: IVec3) -> Option<&mut BlockEntity> {
+        let (coord, local) = world_to_chunk_local(pos);
+        let entity = self.chunk_manager.loaded_chunks.get(&coord)?;
+        self.chunks.get_mut(*entity).ok()?.into_inner()
+            .block_entity_mut(local.x as usize, local.y as usize, local.z as usize)
+    }
+
+    fn set_block_entity(&mut self, pos: IVec3, entity: Option<BlockEntity>) -> Option<BlockEntity> {
+        let (coord, local) = world_to_chunk_local(pos);
+        let chunk_entity = self.chunk_manager.loaded_chunks.get(&coord)?;
+        self.chunks.get_mut(*chunk_entity).ok()?
+            .set_block_entity(local.x as usize, local.y as usize, local.z as usize, entity)
+    }
+}
+
+/// Block storage backed by a plain map, for building small worlds by hand in tests
+/// Positions missing from the map count as unloaded
+#[cfg(test)]
+#[derive(Default)]
+pub struct MemoryStorage {
+    pub blocks: HashMap<IVec3, BlockState>,
+    pub block_entities: HashMap<IVec3, BlockEntity>,
+}
+
+#[cfg(test)]
+impl MemoryStorage {
+    /// Storage with every position in the inclusive box `min..=max` loaded and set to `state`
+    pub fn filled(min: IVec3, max: IVec3, state: BlockState) -> Self {
+        let mut blocks = HashMap::new();
+        for x in min.x..=max.x {
+            for y in min.y..=max.y {
+                for z in min.z..=max.z {
+                    blocks.insert(IVec3::new(x, y, z), state);
+                }
+            }
+        }
+        Self { blocks, block_entities: HashMap::new() }
+    }
+}
+
+#[cfg(test)]
+impl BlockStorage for MemoryStorage {
+    fn get_state(&self, pos: IVec3) -> Option<BlockState> {
+        self.blocks.get(&pos).copied()
+    }
+
+    fn set_state_raw(&mut self, pos: IVec3, state: BlockState) -> bool {
+        match self.blocks.get_mut(&pos) {
+            Some(slot) => {
+                *slot = state;
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn get_light(&self, _pos: IVec3) -> u8 {
+        super::chunk::MAX_LIGHT_LEVEL
+    }
+
+    fn block_entity(&self, pos: IVec3) -> Option<&BlockEntity> {
+        self.block_entities.get(&pos)
+    }
+
+    fn block_entity_mut(&mut self, pos: IVec3) -> Option<&mut BlockEntity> {
+        self.block_entities.get_mut(&pos)
+    }
+
+    fn set_block_entity(&mut self, pos: IVec3, entity: Option<BlockEntity>) -> Option<BlockEntity> {
+        if !self.blocks.contains_key(&pos) {
+            return None;
+        }
+        match entity {
+            Some(entity) => self.block_entities.insert(pos, entity),
+            None => self.block_entities.remove(&pos),
+        }
+    }
+}
+
+/// A small hand-built world for testing block behaviors: the vanilla blocks over a
+/// `MemoryStorage`, with their own tick scheduler and a fixed-seed RNG
+#[cfg(test)]
+pub struct TestWorld {
+    pub storage: MemoryStorage,
+    pub registry: BlockRegistry,
+    pub scheduled_ticks: ScheduledTicks,
+    pub rng: StdRng,
+    pub tick: u64,
+}
+
+#[cfg(test)]
+impl TestWorld {
+    /// Air loaded in the inclusive box `min..=max`, with a stone floor across it at y=0
+    pub fn new(min: IVec3, max: IVec3) -> Self {
+        use crate::mods::{GameMod, VanillaMod};
+        use rand::SeedableRng;
+
+        let mut registry = BlockRegistry::default();
+        VanillaMod.register_blocks(&mut registry);
+        let stone = registry.get_id("core:stone").unwrap();
+
+        let mut storage = MemoryStorage::filled(min, max, BlockState::AIR);
+        storage.blocks.extend(MemoryStorage::filled(IVec3::new(min.x, 0, min.z), IVec3::new(max.x, 0, max.z), BlockState::new(stone)).blocks);
+
+        Self { storage, registry, scheduled_ticks: ScheduledTicks::default(), rng: StdRng::seed_from_u64(0), tick: 0 }
+    }
+
+    pub fn edit<R>(&mut self, f: impl FnOnce(&mut BlockWorld) -> R) -> R {
+        let mut world = BlockWorld::new(&mut self.storage, &self.registry, &mut self.scheduled_ticks, &mut self.rng, self.tick);
+        f(&mut world)
+    }
+
+    pub fn id(&self, name: &str) -> BlockId {
+        self.registry.get_id(name).unwrap()
+    }
+
+    pub fn place(&mut self, pos: IVec3, name: &str) {
+        let id = self.id(name);
+        self.edit(|world| world.set_block(pos, id, SetBlockFlags::DEFAULT));
+    }
+
+    /// Place a block the way a player would against a face with outward normal `face_normal`
+    pub fn place_facing(&mut self, pos: IVec3, name: &str, face_normal: IVec3) {
+        let id = self.id(name);
+        self.edit(|world| {
+            let state = world.placement_state(id, face_normal);
+            world.set_block(pos, state, SetBlockFlags::DEFAULT)
+        });
+    }
+
+    pub fn use_block(&mut self, pos: IVec3) -> bool {
+        self.edit(|world| world.use_block(pos))
+    }
+
+    /// Advance one world tick, running the scheduled ticks that were due like
+    /// `process_scheduled_ticks`, and return what they spawned
+    pub fn run_tick(&mut self) -> Vec<WorldSpawn> {
+        self.tick += 1;
+        self.edit(|world| {
+            let mut due = Vec::new();
+            while let Some(pos) = world.pop_due_tick() {
+                due.push(pos);
+            }
+            for pos in due {
+                world.run_scheduled_tick(pos);
+            }
+            world.take_spawns()
+        })
+    }
+
+    pub fn run_ticks(&mut self, ticks: u64) {
+        for _ in 0..ticks {
+            self.run_tick();
+        }
+    }
+
+    pub fn state(&self, pos: IVec3) -> BlockState {
+        self.storage.blocks[&pos]
+    }
+
+    pub fn block(&self, pos: IVec3) -> BlockId {
+        self.state(pos).id
+    }
+}
+
+/// The world mutation API
+/// Every block change that should be visible to block behaviors goes through `set_block`,
+/// which fires placement/break callbacks and neighbor updates. Also hands behaviors the
+/// registry, the tick scheduler and the simulation RNG, and records every change
+/// so callers can remesh afterwards (and players can undo their edits)
+pub struct BlockWorld<'a> {
+    storage: &'a mut dyn BlockStorage,
+    registry: &'a BlockRegistry,
+    scheduled_ticks: &'a mut ScheduledTicks,
+    rng: &'a mut StdRng,
+    current_tick: u64,
+    changed: HashSet<IVec3>,
+    /// Every successful write, in order
+    deltas: Vec<BlockDelta>,
+    /// The block entity each written position had before its first write
+    entities_before: HashMap<IVec3, Option<BlockEntity>>,
+    /// Pending neighbor updates as (block to update, block that changed)
+    pending_updates: VecDeque<(IVec3, IVec3)>,
+    /// Whether the update queue is being drained (nested edits only enqueue)
+    flushing_updates: bool,
+    spawns: Vec<WorldSpawn>,
+}
+
+impl<'a> BlockWorld<'a> {
+    pub fn new(
+        storage: &'a mut dyn BlockStorage,
+        registry: &'a BlockRegistry,
+        scheduled_ticks: &'a mut ScheduledTicks,
+        rng: &'a mut StdRng,
+        current_tick: u64,
+    ) -> Self {
+        Self {
+            storage,
+            registry,
+            scheduled_ticks,
+            rng,
+            current_tick,
+            changed: HashSet::new(),
+            deltas: Vec::new(),
+            entities_before: HashMap::new(),
+            pending_updates: VecDeque::new(),
+            flushing_updates: false,
+            spawns: Vec::new(),
+        }
+    }
+
+    pub fn registry(&self) -> &'a BlockRegistry {
+        self.registry
+    }
+
+    pub fn rng(&mut self) -> &mut StdRng {
+        self.rng
+    }
+
+    /// Get the block at a world position (None if not loaded)
+    pub fn get_block(&self, pos: IVec3) -> Option<BlockId> {
+        self.storage.get_block(pos)
+    }
+
+    /// Get the block state at a world position (None if not loaded)
+    pub fn get_state(&self, pos: IVec3) -> Option<BlockState> {
+        self.storage.get_state(pos)
+    }
+
+    pub fn get_light(&self, pos: IVec3) -> u8 {
+        self.storage.get_light(pos)
+    }
+
+    pub fn block_entity(&self, pos: IVec3) -> Option<&BlockEntity> {
+        self.storage.block_entity(pos)
+    }
+
+    pub fn block_entity_mut(&mut self, pos: IVec3) -> Option<&mut BlockEntity> {
+        self.storage.block_entity_mut(pos)
+    }
+
+    /// Attach a block entity to the block at `pos`, replacing any existing one
+    pub fn set_block_entity(&mut self, pos: IVec3, entity: BlockEntity) {
+        self.storage.set_block_entity(pos, Some(entity));
+    }
+
+    /// Detach and return the block entity at `pos`
+    pub fn take_block_entity(&mut self, pos: IVec3) -> Option<BlockEntity> {
+        self.storage.set_block_entity(pos, None)
+    }
+
+    /// Whether the block at `pos` lets light and grass through (air or a transparent block)
+    pub fn is_transparent(&self, pos: IVec3) -> bool {
+        match self.get_block(pos) {
+            Some(id) if !id.is_air() => self.registry.get_block(id)
+                .is_none_or(|block| block.properties.is_transparent),
+            _ => true,
+        }
+    }
+
+    /// Whether the block at `pos` is solid (unloaded positions count as not solid)
+    pub fn is_solid(&self, pos: IVec3) -> bool {
+        match self.get_block(pos) {
+            Some(id) if !id.is_air() => self.registry.get_block(id)
+                .is_some_and(|block| block.properties.is_solid),
+            _ => false,
+        }
+    }
+
+    /// Replace the block at `pos` with `state`
+    /// Returns false if the position isn't loaded, lies past `WORLD_LIMIT` or already holds this exact state
+    pub fn set_block(&mut self, pos: IVec3, state: impl Into<BlockState>, flags: SetBlockFlags) -> bool {
+        let state = state.into();
+        if pos.cmplt(IVec3::splat(-WORLD_LIMIT)).any() || pos.cmpgt(IVec3::splat(WORLD_LIMIT)).any() {
+            return false;
+        }
+        let Some(old_state) = self.storage.get_state(pos) else {
+            return false;
+        };
+        if old_state == state || !self.storage.set_state_raw(pos, state) {
+            return false;
+        }
+        self.changed.insert(pos);
+        self.deltas.push(BlockDelta { pos, old: old_state, new: state });
+        if !self.entities_before.contains_key(&pos) {
+            self.entities_before.insert(pos, self.storage.block_entity(pos).cloned());
+        }
+
+        // Data-only changes (power level, orientation) don't count as break/place
+        if flags.contains(SetBlockFlags::CALLBACKS) && old_state.id != state.id {
+            if let Some(behavior) = self.behavior(old_state.id) {
+                behavior.on_broken(self, pos, old_state);
+            }
+            // The old block's callback may already have replaced us
+            if self.get_state(pos) == Some(state) {
+                if let Some(behavior) = self.behavior(state.id) {
+                    behavior.on_placed(self, pos, old_state);
+                }
+                self.check_gravity(pos, state.id);
+            }
+        }
+
+        if flags.contains(SetBlockFlags::NOTIFY_NEIGHBORS) {
+            self.notify_neighbors(pos);
+        }
+
+        true
+    }
+
+    /// Fire `on_placed` for a block that was written without callbacks (e.g., by worldgen)
+    /// Gravity isn't checked: terrain stays as generated until a neighbor changes
+    pub fn fire_placed(&mut self, pos: IVec3) {
+        let Some(state) = self.get_state(pos) else {
+            return;
+        };
+        if let Some(behavior) = self.behavior(state.id) {
+            behavior.on_placed(self, pos, BlockState::AIR);
+        }
+    }
+
+    /// Deliver `on_neighbor_changed` to the 6 blocks adjacent to `pos`
+    /// Updates are queued breadth-first, so chains caused by callbacks run in a stable order
+    pub fn notify_neighbors(&mut self, pos: IVec3) {
+        for offset in NEIGHBOR_OFFSETS {
+            self.pending_updates.push_back((pos + offset, pos));
+        }
+        self.flush_updates();
+    }
+
+    fn flush_updates(&mut self) {
+        // Nested edits from inside a callback only enqueue - the outermost call drains
+        if self.flushing_updates {
+            return;
+        }
+        self.flushing_updates = true;
+
+        let mut delivered = 0;
+        while let Some((target, source)) = self.pending_updates.pop_front() {
+            if delivered >= MAX_NEIGHBOR_UPDATES {
+                warn!("Neighbor update limit reached at {:?}, dropping {} updates", target, self.pending_updates.len() + 1);
+                self.pending_updates.clear();
+                break;
+            }
+            delivered += 1;
+
+            let Some(state) = self.get_state(target) else {
+                continue;
+            };
+            if let Some(behavior) = self.behavior(state.id) {
+                behavior.on_neighbor_changed(self, target, source);
+            }
+            self.check_gravity(target, state.id);
+        }
+
+        self.flushing_updates = false;
+    }
+
+    /// Schedule a tick for the block at `pos` after `delay` world ticks
+    pub fn schedule_tick(&mut self, pos: IVec3, delay: u64) {
+        self.scheduled_ticks.schedule(pos, self.current_tick, delay);
+    }
+
+    /// Pop the next scheduled tick that is due at the current world tick
+    pub fn pop_due_tick(&mut self) -> Option<IVec3> {
+        self.scheduled_ticks.pop_due(self.current_tick)
+    }
+
+    /// Run a scheduled tick that came due for the block at `pos`
+    /// Unsupported gravity blocks start falling; everything else gets `scheduled_tick`
+    pub fn run_scheduled_tick(&mut self, pos: IVec3) {
+        // Block may have changed or unloaded since the tick was scheduled
+        let Some(state) = self.get_state(pos) else {
+            return;
+        };
+        if state.is_air() {
+            return;
+        }
+
+        if self.has_gravity(state.id) && self.can_fall_into(pos - IVec3::Y) {
+            self.set_block(pos, BlockState::AIR, SetBlockFlags::DEFAULT);
+            self.spawn(WorldSpawn::FallingBlock { pos, state });
+            return;
+        }
+
+        if let Some(behavior) = self.behavior(state.id) {
+            behavior.scheduled_tick(self, pos);
+        }
+    }
+
+    /// Whether a falling block can move into `pos` (loaded and empty)
+    pub fn can_fall_into(&self, pos: IVec3) -> bool {
+        self.get_block(pos).is_some_and(|id| id.is_air())
+    }
+
+    /// Request an entity spawn once this edit finishes
+    pub fn spawn(&mut self, spawn: WorldSpawn) {
+        self.spawns.push(spawn);
+    }
+
+    /// Spawn requests made since this world view was created
+    pub fn take_spawns(&mut self) -> Vec<WorldSpawn> {
+        std::mem::take(&mut self.spawns)
+    }
+
+    /// Use (right-click) the block at `pos`
+    /// Returns true if the block's behavior handled it
+    pub fn use_block(&mut self, pos: IVec3) -> bool {
+        let Some(state) = self.get_state(pos) else {
+            return false;
+        };
+        self.behavior(state.id).is_some_and(|behavior| behavior.on_use(self, pos, state))
+    }
+
+    /// An entity moved into the cell at `pos`
+    pub fn entity_entered(&mut self, pos: IVec3) {
+        if let Some(behavior) = self.get_block(pos).and_then(|id| self.behavior(id)) {
+            behavior.on_entity_enter(self, pos);
+        }
+    }
+
+    /// The last entity left the cell at `pos`
+    pub fn entity_left(&mut self, pos: IVec3) {
+        if let Some(behavior) = self.get_block(pos).and_then(|id| self.behavior(id)) {
+            behavior.on_entity_leave(self, pos);
+        }
+    }
+
+    /// Signal strength the block at `pos` sends into its neighbor at `pos + direction`
+    pub fn power_output(&self, pos: IVec3, direction: IVec3) -> u8 {
+        let Some(state) = self.get_state(pos) else {
+            return 0;
+        };
+        self.behavior(state.id).map_or(0, |behavior| behavior.power_output(state, direction))
+    }
+
+    /// Strongest signal any of the 6 neighbors sends into `pos`
+    pub fn received_power(&self, pos: IVec3) -> u8 {
+        NEIGHBOR_OFFSETS.iter()
+            .map(|&offset| self.power_output(pos + offset, -offset))
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// The state `block` should be placed with when placed against a face with normal `face_normal`
+    pub fn placement_state(&self, block: BlockId, face_normal: IVec3) -> BlockState {
+        let state = BlockState::new(block);
+        self.behavior(block).map_or(state, |behavior| behavior.placement_state(state, face_normal))
+    }
+
+    /// Positions changed since this world view was created
+    pub fn take_changed(&mut self) -> HashSet<IVec3> {
+        std::mem::take(&mut self.changed)
+    }
+
+    /// Everything changed since this world view was created (callbacks' changes included),
+    /// merged into one transaction with the block entities the changed blocks had before and have now
+    /// A block entity the edit removed isn't kept: breaking its block already dropped the contents,
+    /// so undoing brings the block back empty rather than duplicating them
+    pub fn take_deltas(&mut self) -> EditTransaction {
+        let mut transaction = EditTransaction::from_changes(std::mem::take(&mut self.deltas));
+        let mut entities_before = std::mem::take(&mut self.entities_before);
+        for delta in &transaction.deltas {
+            let new = self.storage.block_entity(delta.pos).cloned();
+            let old = entities_before.remove(&delta.pos).flatten().filter(|_| new.is_some());
+            if old.is_some() || new.is_some() {
+                transaction.block_entities.push(BlockEntityDelta { pos: delta.pos, old, new });
+            }
+        }
+        transaction
+    }
+
+    fn has_gravity(&self, id: BlockId) -> bool {
+        !id.is_air() && self.registry.get_block(id).is_some_and(|block| block.properties.affected_by_gravity)
+    }
+
+    /// Schedule a fall check for a gravity block whose support may have changed
+    fn check_gravity(&mut self, pos: IVec3, id: BlockId) {
+        if self.has_gravity(id) && self.can_fall_into(pos - IVec3::Y) {
+            self.schedule_tick(pos, FALL_DELAY_TICKS);
+        }
+    }
+
+    fn behavior(&self, id: BlockId) -> Option<Arc<dyn BlockBehavior>> {
+        if id.is_air() {
+            return None;
+        }
+        self.registry.get_block(id)?.properties.behavior.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use crate::blocks::BlockType;
+
+    #[derive(Debug, Default)]
+    struct Counts {
+        placed: AtomicUsize,
+        broken: AtomicUsize,
+        neighbor_changed: AtomicUsize,
+    }
+
+    /// Counts the callbacks its blocks get; an echoing one answers every neighbor update
+    /// by notifying its own neighbors, so two of them side by side never settle
+    #[derive(Debug)]
+    struct CountingBehavior {
+        counts: Arc<Counts>,
+        echo: bool,
+    }
+
+    impl BlockBehavior for CountingBehavior {
+        fn on_placed(&self, _world: &mut BlockWorld, _pos: IVec3, _old_state: BlockState) {
+            self.counts.placed.fetch_add(1, Ordering::Relaxed);
+        }
+
+        fn on_broken(&self, _world: &mut BlockWorld, _pos: IVec3, _old_state: BlockState) {
+            self.counts.broken.fetch_add(1, Ordering::Relaxed);
+        }
+
+        fn on_neighbor_changed(&self, world: &mut BlockWorld, pos: IVec3, _neighbor_pos: IVec3) {
+            self.counts.neighbor_changed.fetch_add(1, Ordering::Relaxed);
+            if self.echo {
+                world.notify_neighbors(pos);
+            }
+        }
+    }
+
+    /// A test world that also has `test:counter` and `test:echo` blocks, with their shared counts
+    fn world_with_counters() -> (TestWorld, Arc<Counts>) {
+        let mut world = TestWorld::new(IVec3::new(-4, 0, -4), IVec3::new(4, 4, 4));
+        let counts = Arc::new(Counts::default());
+        for (id, echo) in [("test:counter", false), ("test:echo", true)] {
+            let behavior = CountingBehavior { counts: counts.clone(), echo };
+            world.registry.register_block(BlockType::builder(id, id).behavior(behavior).build()).unwrap();
+        }
+        (world, counts)
+    }
+
+    #[test]
+    fn callbacks_fire_only_when_the_block_id_changes() {
+        let (mut world, counts) = world_with_counters();
+        let pos = IVec3::new(0, 1, 0);
+        let counter = world.id("test:counter");
+
+        world.place(pos, "test:counter");
+        assert_eq!(counts.placed.load(Ordering::Relaxed), 1);
+
+        // A data-only change is neither a break nor a place, and rewriting the same state is no change
+        assert!(world.edit(|world| world.set_block(pos, BlockState::with_data(counter, 3), SetBlockFlags::DEFAULT)));
+        assert!(!world.edit(|world| world.set_block(pos, BlockState::with_data(counter, 3), SetBlockFlags::DEFAULT)));
+        assert_eq!(counts.placed.load(Ordering::Relaxed), 1);
+        assert_eq!(counts.broken.load(Ordering::Relaxed), 0);
+
+        // Without CALLBACKS a different block replaces it silently
+        world.edit(|world| world.set_block(pos, BlockState::AIR, SetBlockFlags::NOTIFY_NEIGHBORS));
+        assert_eq!(counts.broken.load(Ordering::Relaxed), 0);
+        world.place(pos, "test:counter");
+        world.place(pos, "core:stone");
+        assert_eq!(counts.placed.load(Ordering::Relaxed), 2);
+        assert_eq!(counts.broken.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn neighbor_updates_stop_at_the_limit() {
+        let (mut world, counts) = world_with_counters();
+        let (a, b) = (IVec3::new(0, 1, 0), IVec3::new(1, 1, 0));
+        let echo = world.id("test:echo");
+        let pending = world.edit(|world| {
+            world.set_block(a, echo, SetBlockFlags::NONE);
+            world.set_block(b, echo, SetBlockFlags::NONE);
+            world.notify_neighbors(a);
+            world.pending_updates.len()
+        });
+        assert_eq!(pending, 0, "the dropped updates don't linger");
+        // Each batch of six updates reaches the other echo once, within its first four
+        assert_eq!(counts.neighbor_changed.load(Ordering::Relaxed), MAX_NEIGHBOR_UPDATES.div_ceil(6));
+
+        // The next edit gets a fresh budget
+        world.edit(|world| world.notify_neighbors(a));
+        assert_eq!(counts.neighbor_changed.load(Ordering::Relaxed), 2 * MAX_NEIGHBOR_UPDATES.div_ceil(6));
+    }
+
+    #[test]
+    fn deltas_merge_repeated_writes_and_keep_block_entities() {
+        let mut world = TestWorld::new(IVec3::new(-4, 0, -4), IVec3::new(4, 4, 4));
+        let (dirt, stone, furnace) = (world.id("core:dirt"), world.id("core:stone"), world.id("core:furnace"));
+        let (twice, undone, oven) = (IVec3::new(0, 1, 0), IVec3::new(1, 1, 0), IVec3::new(2, 1, 0));
+        let transaction = world.edit(|world| {
+            world.set_block(twice, dirt, SetBlockFlags::DEFAULT);
+            world.set_block(twice, stone, SetBlockFlags::DEFAULT);
+            world.set_block(undone, stone, SetBlockFlags::DEFAULT);
+            world.set_block(undone, BlockState::AIR, SetBlockFlags::DEFAULT);
+            world.set_block(oven, furnace, SetBlockFlags::DEFAULT);
+            world.take_deltas()
+        });
+
+        assert_eq!(transaction.deltas, [
+            BlockDelta { pos: twice, old: BlockState::AIR, new: BlockState::new(stone) },
+            BlockDelta { pos: oven, old: BlockState::AIR, new: BlockState::new(furnace) },
+        ]);
+        assert_eq!(transaction.block_entities.len(), 1);
+        assert_eq!(transaction.block_entities[0].pos, oven);
+        assert_eq!(transaction.block_entities[0].old, None);
+        assert!(matches!(transaction.block_entities[0].new, Some(BlockEntity::Furnace(_))));
+    }
+
+    #[test]
+    fn positions_past_the_world_limit_are_never_written() {
+        let edge = IVec3::new(WORLD_LIMIT, 1, 0);
+        let beyond = IVec3::new(-WORLD_LIMIT - 1, 1, 0);
+        let mut storage = MemoryStorage::filled(edge, edge, BlockState::AIR);
+        storage.blocks.insert(beyond, BlockState::AIR);
+        let registry = BlockRegistry::default();
+        let mut scheduled_ticks = ScheduledTicks::default();
+        let mut rng = <StdRng as rand::SeedableRng>::seed_from_u64(0);
+        let mut world = BlockWorld::new(&mut storage, &registry, &mut scheduled_ticks, &mut rng, 0);
+
+        let state = BlockState::new(BlockId(1));
+        assert!(world.set_block(edge, state, SetBlockFlags::DEFAULT));
+        assert!(!world.set_block(beyond, state, SetBlockFlags::DEFAULT));
+        assert_eq!(world.get_state(beyond), Some(BlockState::AIR));
+        assert!(!world.set_block(IVec3::new(0, 1, 0), state, SetBlockFlags::DEFAULT), "unloaded");
+    }
+}
@@ -0,0 +1,643 @@
+use bevy::prelude::*;
+use flate2::read::{GzDecoder, ZlibDecoder};
+use flate2::write::{GzEncoder, ZlibEncoder};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use crate::blocks::{BlockId, BlockRegistry};
+use super::chunk::{Chunk, ChunkCoord, CHUNK_SIZE};
+use super::nbt::{self, Tag};
+
+/// Block that Minecraft blocks without a mapping are imported as
+pub const PLACEHOLDER_BLOCK: &str = "core:unknown";
+
+/// Region files are split into 4 KiB sectors; the first two hold the header
+const SECTOR_SIZE: usize = 4096;
+
+/// A region file holds 32x32 columns
+const REGION_COLUMNS: i32 = 32;
+
+/// Blocks in a 16x16x16 section, the same size and order (x fastest, then z, then y) as a `Chunk`
+const SECTION_VOLUME: usize = CHUNK_SIZE * CHUNK_SIZE * CHUNK_SIZE;
+
+/// First data version (20w17a, 1.16) whose packed block states don't run on from one long into the next
+const NON_SPANNING_DATA_VERSION: i64 = 2529;
+
+/// Most bytes a column (or level.dat) may inflate to; real ones are well under a megabyte,
+/// so anything past this is corrupt or crafted to exhaust memory
+const MAX_COLUMN_BYTES: u64 = 32 << 20;
+
+/// Columns kept decoded, so the cubic chunks stacked in one column read its region file once
+const CACHED_COLUMNS: usize = 256;
+
+/// Minecraft blocks that are air, for finding the surface before any mapping applies
+const AIR_NAMES: [&str; 3] = ["minecraft:air", "minecraft:cave_air", "minecraft:void_air"];
+
+/// The built-in mapping (Minecraft names without the `minecraft:` namespace)
+const DEFAULT_MAPPING: &[(&str, &str)] = &[
+    ("air", "core:air"),
+    ("cave_air", "core:air"),
+    ("void_air", "core:air"),
+    ("stone", "core:stone"),
+    ("granite", "core:stone"),
+    ("diorite", "core:stone"),
+    ("andesite", "core:stone"),
+    ("deepslate", "core:stone"),
+    ("tuff", "core:stone"),
+    ("calcite", "core:stone"),
+    ("cobblestone", "core:stone"),
+    ("mossy_cobblestone", "core:stone"),
+    ("cobbled_deepslate", "core:stone"),
+    ("stone_bricks", "core:stone"),
+    ("sandstone", "core:stone"),
+    ("dirt", "core:dirt"),
+    ("coarse_dirt", "core:dirt"),
+    ("rooted_dirt", "core:dirt"),
+    ("podzol", "core:dirt"),
+    ("mycelium", "core:dirt"),
+    ("farmland", "core:dirt"),
+    ("dirt_path", "core:dirt"),
+    ("grass_path", "core:dirt"),
+    ("clay", "core:dirt"),
+    ("mud", "core:dirt"),
+    ("grass_block", "core:grass"),
+    ("bedrock", "core:bedrock"),
+    ("sand", "core:sand"),
+    ("red_sand", "core:sand"),
+    ("gravel", "core:gravel"),
+    ("water", "core:water"),
+    ("bubble_column", "core:water"),
+    ("seagrass", "core:water"),
+    ("tall_seagrass", "core:water"),
+    ("kelp", "core:water"),
+    ("kelp_plant", "core:water"),
+    // Small plants have nothing to stand for them and would cover the land in placeholders
+    ("grass", "core:air"),
+    ("short_grass", "core:air"),
+    ("tall_grass", "core:air"),
+    ("fern", "core:air"),
+    ("large_fern", "core:air"),
+    ("dead_bush", "core:air"),
+    ("dandelion", "core:air"),
+    ("poppy", "core:air"),
+    ("coal_ore", "core:coal_ore"),
+    ("deepslate_coal_ore", "core:coal_ore"),
+    ("iron_ore", "core:iron_ore"),
+    ("deepslate_iron_ore", "core:iron_ore"),
+    ("diamond_ore", "core:diamond_ore"),
+    ("deepslate_diamond_ore", "core:diamond_ore"),
+    ("crafting_table", "core:crafting_table"),
+    ("furnace", "core:furnace"),
+    ("torch", "core:torch"),
+    ("wall_torch", "core:torch"),
+    ("redstone_wire", "core:wire"),
+    ("redstone_lamp", "core:lamp"),
+    ("lever", "core:lever"),
+    ("stone_button", "core:button"),
+    ("oak_button", "core:button"),
+    ("stone_pressure_plate", "core:pressure_plate"),
+    ("oak_pressure_plate", "core:pressure_plate"),
+    ("piston", "core:piston"),
+    ("sticky_piston", "core:piston"),
+    ("piston_head", "core:piston_head"),
+];
+
+/// Wood types whose logs and planks map to the engine's (oak) log and planks
+const WOODS: [&str; 9] = ["oak", "spruce", "birch", "jungle", "acacia", "dark_oak", "mangrove", "cherry", "pale_oak"];
+
+/// Add the `minecraft:` namespace to a name that has none
+fn namespaced(name: &str) -> String {
+    match name.contains(':') {
+        true => name.to_string(),
+        false => format!("minecraft:{}", name),
+    }
+}
+
+/// Which registered block (by string ID) each Minecraft block becomes
+/// Block state properties are ignored: every `minecraft:oak_log` is a log whichever way it lies
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlockMapping {
+    blocks: HashMap<String, String>,
+}
+
+impl Default for BlockMapping {
+    /// Common terrain, ores, wood and the blocks the core mod has a counterpart for
+    fn default() -> Self {
+        let mut mapping = Self::empty();
+        for (minecraft, block) in DEFAULT_MAPPING {
+            mapping.insert(minecraft, block);
+        }
+        for wood in WOODS {
+            for log in [format!("{}_log", wood), format!("stripped_{}_log", wood), format!("{}_wood", wood), format!("stripped_{}_wood", wood)] {
+                mapping.insert(&log, "core:log");
+            }
+            mapping.insert(&format!("{}_planks", wood), "core:planks");
+        }
+        mapping
+    }
+}
+
+impl BlockMapping {
+    /// A mapping that turns every block into the placeholder
+    pub fn empty() -> Self {
+        Self { blocks: HashMap::new() }
+    }
+
+    /// Import `minecraft` (namespace optional) as the block with string ID `block`
+    pub fn insert(&mut self, minecraft: &str, block: &str) {
+        self.blocks.insert(namespaced(minecraft), block.to_string());
+    }
+
+    /// String ID the Minecraft block `minecraft` is imported as, if it has a mapping
+    pub fn get(&self, minecraft: &str) -> Option<&str> {
+        self.blocks.get(&namespaced(minecraft)).map(String::as_str)
+    }
+
+    /// The default mapping with the entries of a RON map file on top, e.g.
+    /// `{"minecraft:oak_leaves": "mymod:leaves", "snow": "core:air"}`
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let source = std::fs::read_to_string(path).map_err(|error| format!("Could not read {}: {}", path.display(), error))?;
+        let entries: HashMap<String, String> = ron::from_str(&source)
+            .map_err(|error| format!("Invalid block mapping {}: {}", path.display(), error))?;
+        let mut mapping = Self::default();
+        for (minecraft, block) in entries {
+            mapping.insert(&minecraft, &block);
+        }
+        Ok(mapping)
+    }
+}
+
+/// A Minecraft world (1.13 or later) to load chunks from instead of generating them
+/// Insert it before adding `SimulationPlugin`. Columns are read from `<world>/region/*.mca`
+/// as chunks load, so only the part of the map being explored is ever in memory;
+/// outside the map the world is empty. Nothing is written back to the Minecraft world
+#[derive(Resource, Debug, Clone)]
+pub struct AnvilImport {
+    /// The Minecraft world directory, holding `level.dat` and `region/`
+    pub world_path: PathBuf,
+    pub mapping: BlockMapping,
+}
+
+impl AnvilImport {
+    /// Import the world at `world_path` with the default block mapping
+    pub fn new(world_path: impl Into<PathBuf>) -> Self {
+        Self { world_path: world_path.into(), mapping: BlockMapping::default() }
+    }
+
+    pub fn with_mapping(mut self, mapping: BlockMapping) -> Self {
+        self.mapping = mapping;
+        self
+    }
+
+    fn region_directory(&self) -> PathBuf {
+        self.world_path.join("region")
+    }
+
+    /// The world's spawn from `level.dat`, or else the block above the highest one at the origin
+    pub fn spawn_block(&self) -> IVec3 {
+        match read_level_spawn(&self.world_path.join("level.dat")) {
+            Ok(spawn) => return spawn,
+            Err(error) => warn!("No spawn point from level.dat ({}), spawning at the origin", error),
+        }
+        let surface = read_raw_column(&self.region_directory(), 0, 0)
+            .unwrap_or_else(|error| {
+                warn!("Could not read the column at the origin: {}", error);
+                Vec::new()
+            })
+            .into_iter()
+            .flat_map(|(section_y, section)| (0..CHUNK_SIZE).filter_map(move |y| {
+                let name = &section.palette[section.index(y * CHUNK_SIZE * CHUNK_SIZE)];
+                (!AIR_NAMES.contains(&name.as_str())).then_some(section_y * CHUNK_SIZE as i32 + y as i32)
+            }))
+            .max();
+        IVec3::new(0, surface.map_or(0, |y| y + 1), 0)
+    }
+}
+
+/// Spawn position kept in `level.dat` (gzipped NBT)
+fn read_level_spawn(path: &Path) -> Result<IVec3, String> {
+    let compressed = std::fs::read(path).map_err(|error| format!("Could not read {}: {}", path.display(), error))?;
+    let level = nbt::read(&Compression::Gzip.decompress(&compressed)?)?;
+    let coordinate = |name: &str| level.get("Data").and_then(|data| data.get(name)).and_then(Tag::as_i64)
+        .map(|value| value as i32)
+        .ok_or_else(|| format!("level.dat has no {}", name));
+    Ok(IVec3::new(coordinate("SpawnX")?, coordinate("SpawnY")?, coordinate("SpawnZ")?))
+}
+
+/// How a column is compressed in a region file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    Gzip,
+    Zlib,
+    None,
+}
+
+impl Compression {
+    fn from_id(id: u8) -> Result<Self, String> {
+        match id {
+            1 => Ok(Compression::Gzip),
+            2 => Ok(Compression::Zlib),
+            3 => Ok(Compression::None),
+            4 => Err("LZ4-compressed columns aren't supported".to_string()),
+            id if id & 0x80 != 0 => Err("Columns stored outside the region file (.mcc) aren't supported".to_string()),
+            id => Err(format!("Unknown column compression {}", id)),
+        }
+    }
+
+    fn id(self) -> u8 {
+        match self {
+            Compression::Gzip => 1,
+            Compression::Zlib => 2,
+            Compression::None => 3,
+        }
+    }
+
+    /// Inflate `data`, refusing to go past `MAX_COLUMN_BYTES`
+    pub fn decompress(self, data: &[u8]) -> Result<Vec<u8>, String> {
+        let mut bytes = Vec::new();
+        // One byte more than allowed, to tell a column of exactly the limit from a longer one
+        let limit = MAX_COLUMN_BYTES + 1;
+        let result = match self {
+            Compression::Gzip => GzDecoder::new(data).take(limit).read_to_end(&mut bytes),
+            Compression::Zlib => ZlibDecoder::new(data).take(limit).read_to_end(&mut bytes),
+            Compression::None => return Ok(data.to_vec()),
+        };
+        result.map_err(|error| format!("Could not decompress: {}", error))?;
+        if bytes.len() as u64 > MAX_COLUMN_BYTES {
+            return Err(format!("Decompresses to more than {} bytes", MAX_COLUMN_BYTES));
+        }
+        Ok(bytes)
+    }
+
+    pub fn compress(self, data: &[u8]) -> Vec<u8> {
+        let written = match self {
+            Compression::Gzip => {
+                let mut encoder = GzEncoder::new(Vec::new(), flate2::Compression::default());
+                encoder.write_all(data).and_then(|_| encoder.finish())
+            }
+            Compression::Zlib => {
+                let mut encoder = ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+                encoder.write_all(data).and_then(|_| encoder.finish())
+            }
+            Compression::None => Ok(data.to_vec()),
+        };
+        written.expect("compressing into memory can't fail")
+    }
+}
+
+/// A Minecraft region file (`r.<x>.<z>.mca`): a header locating each of its 32x32 columns,
+/// then the columns as compressed NBT, each starting on a 4 KiB sector
+pub struct RegionFile {
+    file: File,
+    /// (first sector, sector count) of each column, x fastest; (0, 0) for columns never saved
+    locations: Vec<(u32, u8)>,
+}
+
+impl RegionFile {
+    /// Name of the region file holding the column at chunk coordinates `x`, `z`
+    pub fn file_name(x: i32, z: i32) -> String {
+        format!("r.{}.{}.mca", x.div_euclid(REGION_COLUMNS), z.div_euclid(REGION_COLUMNS))
+    }
+
+    pub fn open(path: &Path) -> Result<Self, String> {
+        let mut file = File::open(path).map_err(|error| format!("Could not open {}: {}", path.display(), error))?;
+        let mut header = vec![0; SECTOR_SIZE];
+        file.read_exact(&mut header).map_err(|error| format!("Invalid region file {}: {}", path.display(), error))?;
+        let locations = header.chunks_exact(4)
+            .map(|entry| (u32::from_be_bytes([0, entry[0], entry[1], entry[2]]), entry[3]))
+            .collect();
+        Ok(Self { file, locations })
+    }
+
+    /// The NBT of the column at chunk coordinates `x`, `z` (either absolute or within the region),
+    /// or `None` if Minecraft never saved it
+    pub fn read_column(&mut self, x: i32, z: i32) -> Result<Option<Tag>, String> {
+        let index = (x.rem_euclid(REGION_COLUMNS) + z.rem_euclid(REGION_COLUMNS) * REGION_COLUMNS) as usize;
+        let (sector, sectors) = self.locations[index];
+        if sector == 0 && sectors == 0 {
+            return Ok(None);
+        }
+        let error = |error: std::io::Error| format!("Could not read column {}, {}: {}", x, z, error);
+        self.file.seek(SeekFrom::Start(sector as u64 * SECTOR_SIZE as u64)).map_err(error)?;
+        let mut prefix = [0; 5];
+        self.file.read_exact(&mut prefix).map_err(error)?;
+        let length = u32::from_be_bytes([prefix[0], prefix[1], prefix[2], prefix[3]]) as usize;
+        if length == 0 || length > sectors as usize * SECTOR_SIZE {
+            return Err(format!("Column {}, {} has invalid length {}", x, z, length));
+        }
+        let compression = Compression::from_id(prefix[4])?;
+        let mut data = vec![0; length - 1];
+        self.file.read_exact(&mut data).map_err(error)?;
+        nbt::read(&compression.decompress(&data)?).map(Some)
+    }
+
+    /// Write a region file holding `columns`, given with their chunk coordinates within the region
+    /// (0-31); the other columns are left unsaved
+    pub fn write(path: &Path, columns: &[((i32, i32), Tag)], compression: Compression) -> Result<(), String> {
+        let mut header = vec![0; SECTOR_SIZE * 2];
+        let mut body = Vec::new();
+        for ((x, z), column) in columns {
+            let data = compression.compress(&nbt::write(column));
+            let sector = 2 + body.len() / SECTOR_SIZE;
+            body.extend_from_slice(&(data.len() as u32 + 1).to_be_bytes());
+            body.push(compression.id());
+            body.extend_from_slice(&data);
+            body.resize(body.len().div_ceil(SECTOR_SIZE) * SECTOR_SIZE, 0);
+            let sectors = 2 + body.len() / SECTOR_SIZE - sector;
+            if sectors > u8::MAX as usize {
+                return Err(format!("Column {}, {} is too large", x, z));
+            }
+            let index = (x.rem_euclid(REGION_COLUMNS) + z.rem_euclid(REGION_COLUMNS) * REGION_COLUMNS) as usize * 4;
+            header[index..index + 3].copy_from_slice(&(sector as u32).to_be_bytes()[1..]);
+            header[index + 3] = sectors as u8;
+        }
+        header.extend_from_slice(&body);
+        std::fs::write(path, header).map_err(|error| format!("Could not write {}: {}", path.display(), error))
+    }
+}
+
+/// A section's blocks as indices into its palette (no indices when the palette has one entry)
+struct Section<T> {
+    palette: Vec<T>,
+    indices: Vec<u16>,
+}
+
+impl<T> Section<T> {
+    fn index(&self, block: usize) -> usize {
+        self.indices.get(block).map_or(0, |&index| index as usize)
+    }
+}
+
+/// Palette indices packed into longs, lowest bits first, each at least 4 bits wide
+/// Since 1.16 an index never spans two longs (the leftover high bits are padding);
+/// before that they run on from one long into the next
+fn unpack_indices(data: &[i64], palette_len: usize, spanning: bool) -> Result<Vec<u16>, String> {
+    let bits = ((usize::BITS - (palette_len - 1).leading_zeros()) as usize).max(4);
+    let per_long = 64 / bits;
+    let needed = match spanning {
+        true => (SECTION_VOLUME * bits).div_ceil(64),
+        false => SECTION_VOLUME.div_ceil(per_long),
+    };
+    if data.len() < needed {
+        return Err(format!("Section has {} longs of block states, expected {}", data.len(), needed));
+    }
+    let mask = (1u64 << bits) - 1;
+    (0..SECTION_VOLUME)
+        .map(|block| {
+            let index = match spanning {
+                true => {
+                    let (long, shift) = (block * bits / 64, block * bits % 64);
+                    let mut value = data[long] as u64 >> shift;
+                    if shift + bits > 64 {
+                        value |= (data[long + 1] as u64) << (64 - shift);
+                    }
+                    value & mask
+                }
+                false => (data[block / per_long] as u64 >> (block % per_long * bits)) & mask,
+            };
+            match (index as usize) < palette_len {
+                true => Ok(index as u16),
+                false => Err(format!("Palette index {} out of range", index)),
+            }
+        })
+        .collect()
+}
+
+/// Pack palette indices `bits` wide into longs the way Minecraft does, one bit at a time,
+/// spanning longs or not as `unpack_indices` reads them back; for writing test worlds
+#[doc(hidden)]
+pub fn pack_indices(indices: &[u16], bits: usize, spanning: bool) -> Vec<i64> {
+    let per_long = 64 / bits;
+    let mut data = vec![0u64; match spanning {
+        true => (indices.len() * bits).div_ceil(64),
+        false => indices.len().div_ceil(per_long),
+    }];
+    for (block, &index) in indices.iter().enumerate() {
+        for bit in 0..bits {
+            let position = match spanning {
+                true => block * bits + bit,
+                false => block / per_long * 64 + block % per_long * bits + bit,
+            };
+            data[position / 64] |= ((index as u64 >> bit) & 1) << (position % 64);
+        }
+    }
+    data.into_iter().map(|long| long as i64).collect()
+}
+
+/// The sections of a column's NBT by Y, their palettes by Minecraft block name
+/// Reads both the 1.18+ layout (`sections` with `block_states`) and the 1.13-1.17 one
+/// (`Level.Sections` with `Palette` and `BlockStates`); sections holding only light are skipped
+fn column_sections(column: &Tag) -> Result<Vec<(i32, Section<String>)>, String> {
+    let data_version = column.get("DataVersion").and_then(Tag::as_i64).unwrap_or(0);
+    let (sections, modern) = match (column.get("sections"), column.get("Level").and_then(|level| level.get("Sections"))) {
+        (Some(sections), _) => (sections, true),
+        (None, Some(sections)) => (sections, false),
+        (None, None) => return Ok(Vec::new()),
+    };
+
+    let mut result = Vec::new();
+    for section in sections.as_list().unwrap_or_default() {
+        let y = section.get("Y").and_then(Tag::as_i64).ok_or("Section without a Y")? as i32;
+        let (palette, data) = match modern {
+            true => {
+                let states = section.get("block_states");
+                (states.and_then(|states| states.get("palette")), states.and_then(|states| states.get("data")))
+            }
+            false => (section.get("Palette"), section.get("BlockStates")),
+        };
+        let Some(palette) = palette.and_then(Tag::as_list).filter(|palette| !palette.is_empty()) else {
+            if section.get("Blocks").is_some() {
+                return Err("Chunks from before Minecraft 1.13 aren't supported".to_string());
+            }
+            continue;
+        };
+        let palette = palette.iter()
+            .map(|entry| entry.get("Name").and_then(Tag::as_str).map(str::to_string).ok_or("Palette entry without a name"))
+            .collect::<Result<Vec<_>, _>>()?;
+        let indices = match palette.len() {
+            1 => Vec::new(),
+            len => {
+                let data = data.and_then(Tag::as_long_array).ok_or("Section without block states")?;
+                unpack_indices(data, len, data_version < NON_SPANNING_DATA_VERSION)?
+            }
+        };
+        result.push((y, Section { palette, indices }));
+    }
+    Ok(result)
+}
+
+/// The sections of the column at chunk coordinates `x`, `z`; none if it was never saved
+fn read_raw_column(region_directory: &Path, x: i32, z: i32) -> Result<Vec<(i32, Section<String>)>, String> {
+    let path = region_directory.join(RegionFile::file_name(x, z));
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    match RegionFile::open(&path)?.read_column(x, z)? {
+        Some(column) => column_sections(&column),
+        None => Ok(Vec::new()),
+    }
+}
+
+/// An imported column: its sections by Y, blocks already mapped to this session's IDs
+type Column = HashMap<i32, Section<BlockId>>;
+
+#[derive(Default)]
+struct ColumnCache {
+    columns: HashMap<(i32, i32), Arc<Column>>,
+    /// Oldest first, to evict
+    order: VecDeque<(i32, i32)>,
+}
+
+/// An `AnvilImport` with its mapping resolved against the block registry, shared with the
+/// chunk loading tasks, which cut the columns they read into cubic chunks
+pub struct AnvilWorld {
+    region_directory: PathBuf,
+    blocks: HashMap<String, BlockId>,
+    placeholder: BlockId,
+    columns: Mutex<ColumnCache>,
+    /// Unmapped Minecraft blocks already warned about
+    unmapped: Mutex<HashSet<String>>,
+}
+
+impl AnvilWorld {
+    pub fn new(import: &AnvilImport, registry: &BlockRegistry) -> Self {
+        let placeholder = registry.get_id(PLACEHOLDER_BLOCK).unwrap_or_else(|| {
+            warn!("Placeholder block '{}' isn't registered, importing unknown blocks as air", PLACEHOLDER_BLOCK);
+            BlockId::AIR
+        });
+        let blocks = import.mapping.blocks.iter()
+            .filter_map(|(minecraft, block)| match registry.get_id(block) {
+                Some(id) => Some((minecraft.clone(), id)),
+                None => {
+                    warn!("Block '{}' (mapped from {}) isn't registered, importing a placeholder", block, minecraft);
+                    None
+                }
+            })
+            .collect();
+        Self {
+            region_directory: import.region_directory(),
+            blocks,
+            placeholder,
+            columns: Mutex::new(ColumnCache::default()),
+            unmapped: Mutex::new(HashSet::new()),
+        }
+    }
+
+    /// The block a Minecraft block is imported as
+    fn block(&self, minecraft: &str) -> BlockId {
+        self.blocks.get(minecraft).copied().unwrap_or_else(|| {
+            if self.unmapped.lock().unwrap().insert(minecraft.to_string()) {
+                warn!("Minecraft block '{}' has no mapping, importing a placeholder", minecraft);
+            }
+            self.placeholder
+        })
+    }
+
+    /// The column at chunk coordinates `x`, `z`, read and mapped the first time it's asked for
+    /// A column that can't be read is logged and imported as empty
+    fn column(&self, x: i32, z: i32) -> Arc<Column> {
+        if let Some(column) = self.columns.lock().unwrap().columns.get(&(x, z)) {
+            return column.clone();
+        }
+        let sections = read_raw_column(&self.region_directory, x, z).unwrap_or_else(|error| {
+            warn!("Could not import column {}, {}: {}", x, z, error);
+            Vec::new()
+        });
+        let column: Arc<Column> = Arc::new(sections.into_iter()
+            .map(|(y, section)| {
+                let palette = section.palette.iter().map(|name| self.block(name)).collect();
+                (y, Section { palette, indices: section.indices })
+            })
+            .collect());
+
+        let mut cache = self.columns.lock().unwrap();
+        if cache.columns.insert((x, z), column.clone()).is_none() {
+            cache.order.push_back((x, z));
+        }
+        while cache.order.len() > CACHED_COLUMNS {
+            if let Some(oldest) = cache.order.pop_front() {
+                cache.columns.remove(&oldest);
+            }
+        }
+        column
+    }
+
+    /// The cubic chunk at `coord`: section `coord.y` of its column, lit from above
+    /// Empty where the map has no such section
+    pub fn chunk(&self, coord: ChunkCoord, registry: &BlockRegistry) -> Chunk {
+        let mut chunk = Chunk::new(coord);
+        if let Some(section) = self.column(coord.x, coord.z).get(&coord.y) {
+            for block in 0..SECTION_VOLUME {
+                let id = section.palette[section.index(block)];
+                if !id.is_air() {
+                    chunk.set_block(block % CHUNK_SIZE, block / (CHUNK_SIZE * CHUNK_SIZE), block / CHUNK_SIZE % CHUNK_SIZE, id);
+                }
+            }
+        }
+        chunk.compact();
+        chunk.calculate_skylight(registry);
+        chunk
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn block_states_unpack_with_and_without_spanning_longs() {
+        // 17 entries need 5 bits: 12 per long with 4 bits of padding, or 64 / 5 spanning
+        let indices: Vec<u16> = (0..SECTION_VOLUME).map(|block| (block * 7 % 17) as u16).collect();
+        for spanning in [false, true] {
+            let data = pack_indices(&indices, 5, spanning);
+            assert_eq!(unpack_indices(&data, 17, spanning).unwrap(), indices, "spanning {}", spanning);
+            assert!(unpack_indices(&data[1..], 17, spanning).is_err());
+        }
+        // Small palettes still take 4 bits
+        let two: Vec<u16> = (0..SECTION_VOLUME).map(|block| (block % 2) as u16).collect();
+        assert_eq!(unpack_indices(&pack_indices(&two, 4, false), 2, false).unwrap(), two);
+        // Indices past the palette are caught
+        let mut corrupt = indices.clone();
+        corrupt[100] = 20;
+        assert!(unpack_indices(&pack_indices(&corrupt, 5, false), 17, false).is_err());
+    }
+
+    #[test]
+    fn region_files_find_columns_through_any_compression() {
+        let directory = std::env::temp_dir().join(format!("voxelcraft-region-test-{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        let column = |x: i32| Tag::compound([("xPos", Tag::Int(x)), ("Filler", Tag::ByteArray(vec![1; 5000]))]);
+        for compression in [Compression::Gzip, Compression::Zlib, Compression::None] {
+            let path = directory.join(RegionFile::file_name(-1, 0));
+            assert_eq!(path.file_name().unwrap(), "r.-1.0.mca");
+            RegionFile::write(&path, &[((31, 0), column(-1)), ((0, 5), column(-32))], compression).unwrap();
+            let mut region = RegionFile::open(&path).unwrap();
+            assert_eq!(region.read_column(-1, 0).unwrap(), Some(column(-1)));
+            assert_eq!(region.read_column(-32, 5).unwrap(), Some(column(-32)));
+            assert_eq!(region.read_column(-2, 0).unwrap(), None);
+        }
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn decompression_stops_at_the_column_limit() {
+        let bomb = vec![0; MAX_COLUMN_BYTES as usize + 1];
+        for compression in [Compression::Gzip, Compression::Zlib] {
+            let packed = compression.compress(&bomb);
+            assert_eq!(compression.decompress(&packed).unwrap_err(), format!("Decompresses to more than {} bytes", MAX_COLUMN_BYTES));
+            assert_eq!(compression.decompress(&compression.compress(&bomb[1..])).unwrap().len(), bomb.len() - 1);
+        }
+    }
+
+    #[test]
+    fn mapping_ignores_the_namespace_and_falls_back_to_nothing() {
+        let mut mapping = BlockMapping::default();
+        assert_eq!(mapping.get("minecraft:grass_block"), Some("core:grass"));
+        assert_eq!(mapping.get("spruce_log"), Some("core:log"));
+        assert_eq!(mapping.get("minecraft:oak_leaves"), None);
+        mapping.insert("minecraft:oak_leaves", "test:leaves");
+        assert_eq!(mapping.get("oak_leaves"), Some("test:leaves"));
+    }
+}
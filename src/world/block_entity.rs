@@ -0,0 +1,9 @@
+use crate::crafting::Furnace;
+
+/// Per-block state too big for a block's 4-bit data value
+/// Stored in the block's chunk, so it unloads with it. Created and removed by the owning
+/// block's behavior (`on_placed` / `on_broken`)
+#[derive(Debug, Clone, PartialEq)]
+pub enum BlockEntity {
+    Furnace(Furnace),
+}
@@ -1,10 +1,16 @@
 use bevy::prelude::*;
-use crate::blocks::BlockId;
+use std::collections::HashMap;
+use crate::blocks::{BlockId, BlockRegistry, BlockState};
+use super::block_entity::BlockEntity;
+use super::storage::{NibbleArray, PalettedContainer};
 
 // Chunk configuration - 16x16x16 cubic chunks
 pub const CHUNK_SIZE: usize = 16;
 pub const VIEW_DISTANCE: i32 = 10; // Render distance in chunks (horizontal) - 160 blocks
 pub const VIEW_DISTANCE_VERTICAL: i32 = 5; // Render distance in chunks (vertical)
+/// Chunks this many chunks past the view distance are unloaded (the gap stops chunks
+/// on the edge from loading and unloading over and over)
+pub const UNLOAD_MARGIN: i32 = 2;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Component)]
 pub struct ChunkCoord {
@@ -170,19 +176,24 @@ impl<'a> NeighborLightData<'a> {
     }
 }
 
-#[derive(Component)]
+#[derive(Component, Clone)]
 pub struct Chunk {
     pub coord: ChunkCoord,
-    pub blocks: Vec<BlockId>, // Block IDs for each position (16x16x16 = 4096 blocks)
-    pub light_levels: Vec<u8>, // Light level (0-15) for each position
+    pub blocks: PalettedContainer, // Block state (ID and data value) for each position (16x16x16 = 4096 blocks)
+    pub light_levels: NibbleArray, // Light level (0-15) for each position
+    pub block_light: NibbleArray, // Light from light-emitting blocks (0-15), separate from sky light
+    /// Block entities by block index (furnace contents and other state too big for the data value)
+    pub block_entities: HashMap<usize, BlockEntity>,
 }
 
 impl Chunk {
     pub fn new(coord: ChunkCoord) -> Self {
         Self {
             coord,
-            blocks: vec![BlockId::AIR; CHUNK_SIZE * CHUNK_SIZE * CHUNK_SIZE],
-            light_levels: vec![MAX_LIGHT_LEVEL; CHUNK_SIZE * CHUNK_SIZE * CHUNK_SIZE],
+            blocks: PalettedContainer::filled(BlockState::AIR),
+            light_levels: NibbleArray::filled(MAX_LIGHT_LEVEL),
+            block_light: NibbleArray::filled(0),
+            block_entities: HashMap::new(),
         }
     }
 
@@ -197,12 +208,24 @@ impl Chunk {
         if x >= CHUNK_SIZE || y >= CHUNK_SIZE || z >= CHUNK_SIZE {
             return BlockId::AIR;
         }
-        self.blocks[Self::index(x, y, z)]
+        self.blocks.get(Self::index(x, y, z)).id
     }
 
+    /// Set the block at a position, resetting its data value to 0
     pub fn set_block(&mut self, x: usize, y: usize, z: usize, block_id: BlockId) {
+        self.set_state(x, y, z, BlockState::new(block_id));
+    }
+
+    pub fn get_state(&self, x: usize, y: usize, z: usize) -> BlockState {
+        if x >= CHUNK_SIZE || y >= CHUNK_SIZE || z >= CHUNK_SIZE {
+            return BlockState::AIR;
+        }
+        self.blocks.get(Self::index(x, y, z))
+    }
+
+    pub fn set_state(&mut self, x: usize, y: usize, z: usize, state: BlockState) {
         if x < CHUNK_SIZE && y < CHUNK_SIZE && z < CHUNK_SIZE {
-            self.blocks[Self::index(x, y, z)] = block_id;
+            self.blocks.set(Self::index(x, y, z), BlockState { id: state.id, data: state.data.min(BlockState::MAX_DATA) });
         }
     }
 
@@ -210,25 +233,104 @@ impl Chunk {
         if x >= CHUNK_SIZE || y >= CHUNK_SIZE || z >= CHUNK_SIZE {
             return MAX_LIGHT_LEVEL; // Assume full light outside chunk
         }
-        self.light_levels[Self::index(x, y, z)]
+        self.light_levels.get(Self::index(x, y, z))
     }
 
     pub fn set_light(&mut self, x: usize, y: usize, z: usize, level: u8) {
         if x < CHUNK_SIZE && y < CHUNK_SIZE && z < CHUNK_SIZE {
-            self.light_levels[Self::index(x, y, z)] = level.min(MAX_LIGHT_LEVEL);
+            self.light_levels.set(Self::index(x, y, z), level.min(MAX_LIGHT_LEVEL));
+        }
+    }
+
+    pub fn get_block_light(&self, x: usize, y: usize, z: usize) -> u8 {
+        if x >= CHUNK_SIZE || y >= CHUNK_SIZE || z >= CHUNK_SIZE {
+            return 0;
+        }
+        self.block_light.get(Self::index(x, y, z))
+    }
+
+    pub fn set_block_light(&mut self, x: usize, y: usize, z: usize, level: u8) {
+        if x < CHUNK_SIZE && y < CHUNK_SIZE && z < CHUNK_SIZE {
+            self.block_light.set(Self::index(x, y, z), level.min(MAX_LIGHT_LEVEL));
         }
     }
 
+    /// Sky light and block light at a position, as the mesher combines them
+    pub fn get_light_pair(&self, x: usize, y: usize, z: usize) -> (u8, u8) {
+        (self.get_light(x, y, z), self.get_block_light(x, y, z))
+    }
+
+    /// The state of every block, when they're all the same (all air, solid stone underground)
+    /// Such chunks skip meshing (all air) or lighting (all solid)
+    pub fn uniform_state(&self) -> Option<BlockState> {
+        self.blocks.uniform()
+    }
+
+    /// Move blocks and light to the smallest storage that holds them, once the chunk is
+    /// generated or after edits that may have made it uniform again
+    pub fn compact(&mut self) {
+        self.blocks.compact();
+        self.light_levels.compact();
+        self.block_light.compact();
+    }
+
+    /// Light back to a single level where it's the same everywhere, and blocks compacted only
+    /// if edits left states in the palette that no block uses (see `PalettedContainer::compact_unused`)
+    pub fn compact_after_relight(&mut self) {
+        self.blocks.compact_unused();
+        self.light_levels.compact();
+        self.block_light.compact();
+    }
+
+    pub fn block_entity(&self, x: usize, y: usize, z: usize) -> Option<&BlockEntity> {
+        self.block_entities.get(&Self::index(x, y, z))
+    }
+
+    pub fn block_entity_mut(&mut self, x: usize, y: usize, z: usize) -> Option<&mut BlockEntity> {
+        self.block_entities.get_mut(&Self::index(x, y, z))
+    }
+
+    /// Attach or (with None) remove the block entity at a position, returning the old one
+    pub fn set_block_entity(&mut self, x: usize, y: usize, z: usize, entity: Option<BlockEntity>) -> Option<BlockEntity> {
+        if x >= CHUNK_SIZE || y >= CHUNK_SIZE || z >= CHUNK_SIZE {
+            return None;
+        }
+        let index = Self::index(x, y, z);
+        match entity {
+            Some(entity) => self.block_entities.insert(index, entity),
+            None => self.block_entities.remove(&index),
+        }
+    }
+
+    /// Local positions of every block entity in this chunk
+    pub fn block_entity_positions(&self) -> impl Iterator<Item = UVec3> + '_ {
+        self.block_entities.keys().map(|&index| {
+            UVec3::new(
+                (index % CHUNK_SIZE) as u32,
+                (index / (CHUNK_SIZE * CHUNK_SIZE)) as u32,
+                ((index / CHUNK_SIZE) % CHUNK_SIZE) as u32,
+            )
+        })
+    }
+
     /// Calculate skylight for this chunk using flood-fill propagation
     /// Light propagates from sky downward, then spreads in all directions
     /// This version doesn't use neighbor chunks (for initial generation)
-    pub fn calculate_skylight(&mut self) {
-        self.calculate_skylight_with_neighbors(&NeighborLightData::none());
+    pub fn calculate_skylight(&mut self, registry: &BlockRegistry) {
+        self.calculate_skylight_with_neighbors(&NeighborLightData::none(), registry);
     }
 
     /// Calculate skylight with neighbor chunk data for cross-chunk propagation
     /// This allows light to flow from one chunk into another through tunnels/caves
-    pub fn calculate_skylight_with_neighbors(&mut self, neighbors: &NeighborLightData) {
+    /// Light passes through every block the registry doesn't count as opaque (air, glass, water)
+    pub fn calculate_skylight_with_neighbors(&mut self, neighbors: &NeighborLightData, registry: &BlockRegistry) {
+        // Opaque chunks are dark throughout, and see-through ones with nothing above are lit throughout
+        match self.uniform_state() {
+            Some(state) if registry.is_opaque(state.id) => return self.light_levels.fill(0),
+            Some(_) if neighbors.pos_y.is_none() => return self.light_levels.fill(MAX_LIGHT_LEVEL),
+            _ => {}
+        }
+
         // First pass: propagate direct skylight from top down
         // Only blocks with unobstructed sky access get full light
         for z in 0..CHUNK_SIZE {
@@ -245,7 +347,7 @@ impl Chunk {
                         in_shadow = true;
                     }
                     // Also check if bottom block of above chunk is solid
-                    if !above_chunk.get_block(x, 0, z).is_air() {
+                    if registry.is_opaque(above_chunk.get_block(x, 0, z)) {
                         in_shadow = true;
                     }
                 }
@@ -254,7 +356,7 @@ impl Chunk {
                 for y in (0..CHUNK_SIZE).rev() {
                     let block = self.get_block(x, y, z);
 
-                    if block.is_air() {
+                    if !registry.is_opaque(block) {
                         if !in_shadow {
                             // Direct skylight - full brightness
                             self.set_light(x, y, z, MAX_LIGHT_LEVEL);
@@ -263,7 +365,7 @@ impl Chunk {
                             self.set_light(x, y, z, 0);
                         }
                     } else {
-                        // Solid block - blocks light, and is in shadow itself
+                        // Opaque block - blocks light, and is in shadow itself
                         self.set_light(x, y, z, 0);
                         in_shadow = true;
                     }
@@ -281,7 +383,7 @@ impl Chunk {
             for y in 0..CHUNK_SIZE {
                 for z in 0..CHUNK_SIZE {
                     for x in 0..CHUNK_SIZE {
-                        if !self.get_block(x, y, z).is_air() {
+                        if registry.is_opaque(self.get_block(x, y, z)) {
                             continue;
                         }
                         if self.propagate_light_from_neighbors_ext(x, y, z, neighbors) {
@@ -295,7 +397,7 @@ impl Chunk {
             for y in (0..CHUNK_SIZE).rev() {
                 for z in (0..CHUNK_SIZE).rev() {
                     for x in (0..CHUNK_SIZE).rev() {
-                        if !self.get_block(x, y, z).is_air() {
+                        if registry.is_opaque(self.get_block(x, y, z)) {
                             continue;
                         }
                         if self.propagate_light_from_neighbors_ext(x, y, z, neighbors) {
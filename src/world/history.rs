@@ -0,0 +1,211 @@
+use bevy::prelude::*;
+use std::collections::{HashMap, VecDeque};
+use crate::blocks::BlockState;
+use super::block_entity::BlockEntity;
+
+/// Memory a player's undo history may use before its oldest edits are forgotten
+pub const EDIT_HISTORY_BUDGET: usize = 4 << 20;
+
+/// One block changed by an edit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockDelta {
+    pub pos: IVec3,
+    pub old: BlockState,
+    pub new: BlockState,
+}
+
+/// The block entity (furnace contents) of a changed block before and after, kept apart
+/// from `BlockDelta` since few blocks have one
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlockEntityDelta {
+    pub pos: IVec3,
+    pub old: Option<BlockEntity>,
+    pub new: Option<BlockEntity>,
+}
+
+/// Every block one action changed (a click, a `/fill`, a paste), undone and redone together
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EditTransaction {
+    /// In the order the blocks first changed
+    pub deltas: Vec<BlockDelta>,
+    /// Block entities of the changed blocks, restored along with them
+    pub block_entities: Vec<BlockEntityDelta>,
+}
+
+impl EditTransaction {
+    /// Merge the raw changes of an edit: a block changed more than once keeps its first old
+    /// and last new state, and blocks that ended up as they started are left out
+    pub fn from_changes(changes: impl IntoIterator<Item = BlockDelta>) -> Self {
+        let mut index: HashMap<IVec3, usize> = HashMap::new();
+        let mut deltas: Vec<BlockDelta> = Vec::new();
+        for change in changes {
+            match index.get(&change.pos) {
+                Some(&i) => deltas[i].new = change.new,
+                None => {
+                    index.insert(change.pos, deltas.len());
+                    deltas.push(change);
+                }
+            }
+        }
+        deltas.retain(|delta| delta.old != delta.new);
+        Self { deltas, block_entities: Vec::new() }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.deltas.is_empty()
+    }
+
+    /// The same changes the other way round, to undo this one
+    pub fn inverse(&self) -> Self {
+        let deltas = self.deltas.iter().rev()
+            .map(|delta| BlockDelta { pos: delta.pos, old: delta.new, new: delta.old })
+            .collect();
+        let block_entities = self.block_entities.iter()
+            .map(|delta| BlockEntityDelta { pos: delta.pos, old: delta.new.clone(), new: delta.old.clone() })
+            .collect();
+        Self { deltas, block_entities }
+    }
+
+    /// Roughly how much memory the transaction holds
+    fn size(&self) -> usize {
+        std::mem::size_of::<Self>()
+            + self.deltas.len() * std::mem::size_of::<BlockDelta>()
+            + self.block_entities.len() * std::mem::size_of::<BlockEntityDelta>()
+    }
+}
+
+/// A player's block edits, for Ctrl+Z and Ctrl+Y (and `/undo` and `/redo`)
+/// Deltas are kept by world position, so they stay valid while their chunks unload and load again.
+/// Once the undo and redo stacks together pass the budget, the oldest edits are forgotten
+#[derive(Component, Debug, Clone)]
+pub struct EditHistory {
+    undo: VecDeque<EditTransaction>,
+    redo: Vec<EditTransaction>,
+    budget: usize,
+    size: usize,
+}
+
+impl Default for EditHistory {
+    fn default() -> Self {
+        Self::with_budget(EDIT_HISTORY_BUDGET)
+    }
+}
+
+impl EditHistory {
+    /// A history holding at most about `budget` bytes (the newest edit is always kept)
+    pub fn with_budget(budget: usize) -> Self {
+        Self { undo: VecDeque::new(), redo: Vec::new(), budget, size: 0 }
+    }
+
+    /// Remember a new edit; anything that could be redone is forgotten
+    pub fn record(&mut self, transaction: EditTransaction) {
+        if transaction.is_empty() {
+            return;
+        }
+        for redo in self.redo.drain(..) {
+            self.size -= redo.size();
+        }
+        self.push_undo(transaction);
+    }
+
+    /// The edit to undo next, taken off the history
+    /// Hand it to `undone` once it's been undone, or back to `push_undo` if it couldn't be
+    pub fn take_undo(&mut self) -> Option<EditTransaction> {
+        let transaction = self.undo.pop_back()?;
+        self.size -= transaction.size();
+        Some(transaction)
+    }
+
+    /// The edit to redo next, taken off the history
+    /// Hand it to `push_undo` once it's been redone, or back to `undone` if it couldn't be
+    pub fn take_redo(&mut self) -> Option<EditTransaction> {
+        let transaction = self.redo.pop()?;
+        self.size -= transaction.size();
+        Some(transaction)
+    }
+
+    /// An edit that was undone, so it can be redone
+    pub fn undone(&mut self, transaction: EditTransaction) {
+        self.size += transaction.size();
+        self.redo.push(transaction);
+        self.trim();
+    }
+
+    /// An edit that can be undone, newest last
+    pub fn push_undo(&mut self, transaction: EditTransaction) {
+        self.size += transaction.size();
+        self.undo.push_back(transaction);
+        self.trim();
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// Bytes held, as counted against the budget
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Forget the oldest edits that could be undone until the history fits its budget
+    fn trim(&mut self) {
+        while self.size > self.budget && self.undo.len() > 1 {
+            if let Some(oldest) = self.undo.pop_front() {
+                self.size -= oldest.size();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blocks::BlockId;
+
+    fn delta(x: i32, old: u16, new: u16) -> BlockDelta {
+        BlockDelta { pos: IVec3::new(x, 0, 0), old: BlockState::new(BlockId(old)), new: BlockState::new(BlockId(new)) }
+    }
+
+    fn transaction(len: i32) -> EditTransaction {
+        EditTransaction::from_changes((0..len).map(|x| delta(x, 0, 1)))
+    }
+
+    #[test]
+    fn changes_merge_per_position() {
+        // A block changed twice keeps where it started and where it ended; one changed back drops out
+        let merged = EditTransaction::from_changes([delta(0, 0, 1), delta(1, 0, 2), delta(0, 1, 3), delta(1, 2, 0)]);
+        assert_eq!(merged.deltas, [delta(0, 0, 3)]);
+        assert_eq!(merged.inverse().deltas, [delta(0, 3, 0)]);
+    }
+
+    #[test]
+    fn history_keeps_to_its_budget() {
+        let one = transaction(1);
+        let mut history = EditHistory::with_budget(3 * one.size());
+        for _ in 0..5 {
+            history.record(one.clone());
+        }
+        assert!(history.size() <= 3 * one.size());
+
+        // Undone edits wait to be redone until something new is recorded
+        let undone = history.take_undo().unwrap();
+        history.undone(undone);
+        assert!(history.can_redo());
+        history.record(transaction(2));
+        assert!(!history.can_redo());
+        let mut undos = 0;
+        while history.take_undo().is_some() {
+            undos += 1;
+        }
+        assert_eq!(undos, 2);
+        assert_eq!(history.size(), 0);
+
+        // An edit bigger than the whole budget is still kept on its own
+        history.record(transaction(100));
+        assert!(history.can_undo());
+    }
+}
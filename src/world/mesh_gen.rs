@@ -2,8 +2,8 @@ use bevy::prelude::*;
 use bevy::render::mesh::{Indices, PrimitiveTopology};
 use bevy::render::render_asset::RenderAssetUsages;
 
-use super::chunk::{Chunk, CHUNK_SIZE, MAX_LIGHT_LEVEL};
-use crate::blocks::{BlockRegistry, BlockFace};
+use super::chunk::{Chunk, ChunkCoord, CHUNK_SIZE, MAX_LIGHT_LEVEL};
+use crate::blocks::{BlockRegistry, BlockFace, BlockId};
 
 /// Neighbor chunks for face culling (6 directions: -X, +X, -Y, +Y, -Z, +Z)
 #[allow(dead_code)]
@@ -93,6 +93,14 @@ pub fn create_chunk_mesh(chunk: &Chunk, block_registry: &BlockRegistry) -> Optio
     create_chunk_mesh_with_neighbors(chunk, block_registry, &NeighborChunks::none())
 }
 
+/// Create a standalone mesh for a single fully-lit block spanning (0,0,0)-(1,1,1)
+/// Uses the chunk mesh attributes, so it renders with TerrainMaterial (falling blocks, drops)
+pub fn create_single_block_mesh(block_id: BlockId, block_registry: &BlockRegistry) -> Option<Mesh> {
+    let mut chunk = Chunk::new(ChunkCoord::new(0, 0, 0));
+    chunk.set_block(0, 0, 0, block_id);
+    create_chunk_mesh(&chunk, block_registry)
+}
+
 /// Face brightness values for baked lighting (Minecraft-style)
 /// These match MC's hardcoded directional shading values
 const BRIGHTNESS_UP: f32 = 1.0;        // Top faces get full light
@@ -194,12 +202,29 @@ fn get_light_at(
 /// This prevents the world from being pitch black but keeps caves very dark
 const MIN_BRIGHTNESS: f32 = 0.05;
 
-/// Convert light level (0-15) to brightness multiplier
+/// Block light at a position (0-15), from the neighbor chunk across a boundary
+/// Unlike sky light, missing neighbors count as dark - nothing there is glowing
+fn get_block_light_at(chunk: &Chunk, neighbors: &NeighborChunks, x: i32, y: i32, z: i32) -> u8 {
+    let size = CHUNK_SIZE as i32;
+    let (source, x, y, z) = match (x, y, z) {
+        _ if (0..size).contains(&x) && (0..size).contains(&y) && (0..size).contains(&z) => (Some(chunk), x, y, z),
+        _ if x < 0 => (neighbors.neg_x, x + size, y, z),
+        _ if x >= size => (neighbors.pos_x, x - size, y, z),
+        _ if y < 0 => (neighbors.neg_y, x, y + size, z),
+        _ if y >= size => (neighbors.pos_y, x, y - size, z),
+        _ if z < 0 => (neighbors.neg_z, x, y, z + size),
+        _ => (neighbors.pos_z, x, y, z - size),
+    };
+    source.map_or(0, |chunk| chunk.get_block_light(x as usize, y as usize, z as usize))
+}
+
+/// Convert sky light and block light levels (0-15) to a brightness multiplier
 /// Uses a curve that provides good contrast at high levels but smooth falloff at low levels
-fn light_to_brightness(light: u8, sky_light_level: u8) -> f32 {
-    // Clamp stored light by current sky light level
-    // Blocks can't be brighter than the current global sky light
-    let effective_light = light.min(sky_light_level);
+fn light_to_brightness(sky_light: u8, block_light: u8, sky_light_level: u8) -> f32 {
+    // Clamp stored sky light by current sky light level
+    // Blocks can't be brighter than the current global sky light - unless a light-emitting
+    // block (torch, lit furnace) nearby lights them regardless of the time of day
+    let effective_light = sky_light.min(sky_light_level).max(block_light);
 
     // Use a quadratic-ish curve that's smoother at low light levels
     // This avoids the harsh jump between light 1 and 0
@@ -239,6 +264,11 @@ fn get_block_light(chunk: &Chunk, neighbors: &NeighborChunks, x: usize, y: usize
 /// Create chunk mesh with a specific sky light level (for day/night cycle)
 /// Uses UV2 attribute for overlay textures (grass sides) instead of separate quads
 pub fn create_chunk_mesh_with_sky_light(chunk: &Chunk, block_registry: &BlockRegistry, neighbors: &NeighborChunks, sky_light_level: u8) -> Option<Mesh> {
+    // Nothing to draw in a chunk of air
+    if chunk.uniform_state().is_some_and(|state| state.is_air()) {
+        return None;
+    }
+
     let mut positions = Vec::new();
     let mut normals = Vec::new();
     let mut uvs = Vec::new();
@@ -274,7 +304,8 @@ pub fn create_chunk_mesh_with_sky_light(chunk: &Chunk, block_registry: &BlockReg
                 let zi = z as i32;
                 let get_face_brightness = |dx: i32, dy: i32, dz: i32, face_shading: f32| -> f32 {
                     let face_light = get_light_at(chunk, neighbors, xi + dx, yi + dy, zi + dz);
-                    let brightness = light_to_brightness(face_light, sky_light_level);
+                    let face_block_light = get_block_light_at(chunk, neighbors, xi + dx, yi + dy, zi + dz);
+                    let brightness = light_to_brightness(face_light, face_block_light, sky_light_level);
                     face_shading * brightness
                 };
 
@@ -597,6 +628,7 @@ pub fn create_chunk_mesh_with_sky_light(chunk: &Chunk, block_registry: &BlockReg
 
 /// Create chunk mesh using closures for neighbor lookups
 /// This allows using cached data for face culling instead of live chunk references
+/// `get_neighbor_light` returns (sky light, block light)
 pub fn create_chunk_mesh_with_cached_neighbors<F, G>(
     chunk: &Chunk,
     block_registry: &BlockRegistry,
@@ -606,10 +638,14 @@ pub fn create_chunk_mesh_with_cached_neighbors<F, G>(
 ) -> Option<Mesh>
 where
     F: Fn(i32, i32, i32, usize, usize, usize) -> crate::blocks::BlockId,
-    G: Fn(i32, i32, i32, usize, usize, usize) -> u8,
+    G: Fn(i32, i32, i32, usize, usize, usize) -> (u8, u8),
 {
     use crate::blocks::BlockFace;
 
+    if chunk.uniform_state().is_some_and(|state| state.is_air()) {
+        return None;
+    }
+
     let mut positions = Vec::new();
     let mut normals = Vec::new();
     let mut uvs = Vec::new();
@@ -658,13 +694,13 @@ where
         }
     };
 
-    // Helper to get light at a position
-    let get_light = |x: i32, y: i32, z: i32| -> u8 {
+    // Helper to get (sky, block) light at a position
+    let get_light = |x: i32, y: i32, z: i32| -> (u8, u8) {
         // Within chunk
         if x >= 0 && x < CHUNK_SIZE as i32 &&
            y >= 0 && y < CHUNK_SIZE as i32 &&
            z >= 0 && z < CHUNK_SIZE as i32 {
-            return chunk.get_light(x as usize, y as usize, z as usize);
+            return chunk.get_light_pair(x as usize, y as usize, z as usize);
         }
 
         // Above chunk
@@ -707,7 +743,7 @@ where
             return get_neighbor_light(0, 0, 1, cx, cy, cz);
         }
 
-        0
+        (0, 0)
     };
 
     for y in 0..CHUNK_SIZE {
@@ -731,8 +767,8 @@ where
                 let yi = y as i32;
                 let zi = z as i32;
                 let get_face_brightness = |dx: i32, dy: i32, dz: i32, face_shading: f32| -> f32 {
-                    let face_light = get_light(xi + dx, yi + dy, zi + dz);
-                    let brightness = light_to_brightness(face_light, sky_light_level);
+                    let (face_light, face_block_light) = get_light(xi + dx, yi + dy, zi + dz);
+                    let brightness = light_to_brightness(face_light, face_block_light, sky_light_level);
                     face_shading * brightness
                 };
 
@@ -1,6 +1,31 @@
 mod chunk;
+mod storage;
 mod terrain;
+mod tick;
+mod access;
+mod spawn;
+mod block_entity;
+mod saved;
+mod persist;
+mod history;
+mod schematic;
+mod selection;
+mod anvil;
 pub mod mesh_gen;
+pub mod nbt;
 
-pub use chunk::{Chunk, ChunkCoord, CHUNK_SIZE, MAX_LIGHT_LEVEL};
-pub use terrain::{ChunkManager, TerrainChunk, setup_terrain, spawn_chunks_around_player, process_chunk_tasks, get_spawn_height};
+pub use chunk::{Chunk, ChunkCoord, CHUNK_SIZE, MAX_LIGHT_LEVEL, UNLOAD_MARGIN};
+pub use storage::{PalettedContainer, NibbleArray};
+pub use saved::{SavedChunk, SavedChunks, ChunkModified, unload_distant_chunks};
+pub use persist::{WorldDirectory, ChunkFile, SaveRegistries, load_world, write_saved_chunks, save_world_on_exit};
+pub use terrain::{ChunkManager, ChunkMeshing, TerrainChunk, WorldSettings, setup_terrain, spawn_chunks_around_player, process_chunk_tasks, attach_chunk_meshes};
+pub use spawn::SpawnPoint;
+pub use history::{BlockDelta, BlockEntityDelta, EditTransaction, EditHistory, EDIT_HISTORY_BUDGET};
+pub use schematic::{Schematic, SchematicFile, PaletteEntry, SchematicDirectory, SCHEMATIC_EXTENSION};
+pub use selection::{Selection, Clipboard, SELECTION_WAND};
+pub use anvil::{pack_indices, AnvilImport, AnvilWorld, BlockMapping, Compression, RegionFile, PLACEHOLDER_BLOCK};
+pub use block_entity::BlockEntity;
+pub use tick::{WorldTick, ScheduledTicks, WorldRng, TICKS_PER_SECOND, RANDOM_TICKS_PER_SECTION};
+pub use access::{ChunkStorage, BlockWorld, SetBlockFlags, WorldSpawn, FALL_DELAY_TICKS, NEIGHBOR_OFFSETS, WORLD_LIMIT, world_to_chunk_local};
+#[cfg(test)]
+pub use access::{MemoryStorage, TestWorld};
@@ -0,0 +1,278 @@
+use std::collections::BTreeMap;
+
+/// Deepest nesting of lists and compounds read, so a corrupt file can't overflow the stack
+const MAX_DEPTH: usize = 512;
+
+/// One value of Minecraft's Named Binary Tag format, the big-endian tag tree chunk columns
+/// and `level.dat` are stored in (region files and `level.dat` compress it around this)
+#[derive(Debug, Clone, PartialEq)]
+pub enum Tag {
+    Byte(i8),
+    Short(i16),
+    Int(i32),
+    Long(i64),
+    Float(f32),
+    Double(f64),
+    ByteArray(Vec<i8>),
+    String(String),
+    List(Vec<Tag>),
+    Compound(BTreeMap<String, Tag>),
+    IntArray(Vec<i32>),
+    LongArray(Vec<i64>),
+}
+
+impl Tag {
+    /// A compound of `entries`
+    pub fn compound<'a>(entries: impl IntoIterator<Item = (&'a str, Tag)>) -> Self {
+        Tag::Compound(entries.into_iter().map(|(name, tag)| (name.to_string(), tag)).collect())
+    }
+
+    /// The entry called `name`, if this is a compound that has one
+    pub fn get(&self, name: &str) -> Option<&Tag> {
+        match self {
+            Tag::Compound(entries) => entries.get(name),
+            _ => None,
+        }
+    }
+
+    /// Any whole number, widened
+    pub fn as_i64(&self) -> Option<i64> {
+        match *self {
+            Tag::Byte(value) => Some(value as i64),
+            Tag::Short(value) => Some(value as i64),
+            Tag::Int(value) => Some(value as i64),
+            Tag::Long(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Tag::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn as_list(&self) -> Option<&[Tag]> {
+        match self {
+            Tag::List(values) => Some(values),
+            _ => None,
+        }
+    }
+
+    pub fn as_long_array(&self) -> Option<&[i64]> {
+        match self {
+            Tag::LongArray(values) => Some(values),
+            _ => None,
+        }
+    }
+
+    fn id(&self) -> u8 {
+        match self {
+            Tag::Byte(_) => 1,
+            Tag::Short(_) => 2,
+            Tag::Int(_) => 3,
+            Tag::Long(_) => 4,
+            Tag::Float(_) => 5,
+            Tag::Double(_) => 6,
+            Tag::ByteArray(_) => 7,
+            Tag::String(_) => 8,
+            Tag::List(_) => 9,
+            Tag::Compound(_) => 10,
+            Tag::IntArray(_) => 11,
+            Tag::LongArray(_) => 12,
+        }
+    }
+}
+
+/// Read an NBT document: a named root compound (the name is dropped)
+pub fn read(bytes: &[u8]) -> Result<Tag, String> {
+    let mut reader = Reader { bytes };
+    match reader.u8()? {
+        10 => {
+            reader.string()?;
+            reader.payload(10, 0)
+        }
+        id => Err(format!("NBT root must be a compound, found tag type {}", id)),
+    }
+}
+
+/// Write `root` (a compound) as an NBT document with an empty root name
+pub fn write(root: &Tag) -> Vec<u8> {
+    let mut bytes = vec![root.id()];
+    write_string(&mut bytes, "");
+    write_payload(&mut bytes, root);
+    bytes
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, count: usize) -> Result<&'a [u8], String> {
+        if self.bytes.len() < count {
+            return Err("NBT data ends early".to_string());
+        }
+        let (taken, rest) = self.bytes.split_at(count);
+        self.bytes = rest;
+        Ok(taken)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], String> {
+        Ok(self.take(N)?.try_into().expect("took N bytes"))
+    }
+
+    fn u8(&mut self) -> Result<u8, String> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn i32(&mut self) -> Result<i32, String> {
+        Ok(i32::from_be_bytes(self.array()?))
+    }
+
+    /// A length prefix, which can't be negative
+    fn length(&mut self) -> Result<usize, String> {
+        let length = self.i32()?;
+        usize::try_from(length).map_err(|_| format!("Negative NBT length {}", length))
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        let length = u16::from_be_bytes(self.array()?) as usize;
+        // Java's modified UTF-8 only differs from UTF-8 for NUL and characters outside the BMP
+        Ok(String::from_utf8_lossy(self.take(length)?).into_owned())
+    }
+
+    /// `count` fixed-size values, checked against what's left before allocating
+    fn values<T, const N: usize>(&mut self, count: usize, convert: fn([u8; N]) -> T) -> Result<Vec<T>, String> {
+        let bytes = self.take(count.checked_mul(N).ok_or("NBT array too long")?)?;
+        Ok(bytes.chunks_exact(N).map(|chunk| convert(chunk.try_into().expect("chunks of N bytes"))).collect())
+    }
+
+    fn payload(&mut self, id: u8, depth: usize) -> Result<Tag, String> {
+        if depth > MAX_DEPTH {
+            return Err("NBT nested too deeply".to_string());
+        }
+        Ok(match id {
+            1 => Tag::Byte(self.u8()? as i8),
+            2 => Tag::Short(i16::from_be_bytes(self.array()?)),
+            3 => Tag::Int(self.i32()?),
+            4 => Tag::Long(i64::from_be_bytes(self.array()?)),
+            5 => Tag::Float(f32::from_be_bytes(self.array()?)),
+            6 => Tag::Double(f64::from_be_bytes(self.array()?)),
+            7 => {
+                let length = self.length()?;
+                Tag::ByteArray(self.values(length, |[byte]: [u8; 1]| byte as i8)?)
+            }
+            8 => Tag::String(self.string()?),
+            9 => {
+                let element = self.u8()?;
+                let length = self.length()?;
+                if element == 0 && length > 0 {
+                    return Err("NBT list of end tags".to_string());
+                }
+                // Each element takes at least a byte, so a length past the data is caught early
+                if length > self.bytes.len() {
+                    return Err("NBT data ends early".to_string());
+                }
+                let values = (0..length).map(|_| self.payload(element, depth + 1)).collect::<Result<_, _>>()?;
+                Tag::List(values)
+            }
+            10 => {
+                let mut entries = BTreeMap::new();
+                loop {
+                    let id = self.u8()?;
+                    if id == 0 {
+                        break;
+                    }
+                    let name = self.string()?;
+                    entries.insert(name, self.payload(id, depth + 1)?);
+                }
+                Tag::Compound(entries)
+            }
+            11 => {
+                let length = self.length()?;
+                Tag::IntArray(self.values(length, i32::from_be_bytes)?)
+            }
+            12 => {
+                let length = self.length()?;
+                Tag::LongArray(self.values(length, i64::from_be_bytes)?)
+            }
+            id => return Err(format!("Unknown NBT tag type {}", id)),
+        })
+    }
+}
+
+fn write_string(bytes: &mut Vec<u8>, value: &str) {
+    bytes.extend_from_slice(&(value.len() as u16).to_be_bytes());
+    bytes.extend_from_slice(value.as_bytes());
+}
+
+fn write_payload(bytes: &mut Vec<u8>, tag: &Tag) {
+    match tag {
+        Tag::Byte(value) => bytes.push(*value as u8),
+        Tag::Short(value) => bytes.extend_from_slice(&value.to_be_bytes()),
+        Tag::Int(value) => bytes.extend_from_slice(&value.to_be_bytes()),
+        Tag::Long(value) => bytes.extend_from_slice(&value.to_be_bytes()),
+        Tag::Float(value) => bytes.extend_from_slice(&value.to_be_bytes()),
+        Tag::Double(value) => bytes.extend_from_slice(&value.to_be_bytes()),
+        Tag::ByteArray(values) => {
+            bytes.extend_from_slice(&(values.len() as i32).to_be_bytes());
+            bytes.extend(values.iter().map(|&value| value as u8));
+        }
+        Tag::String(value) => write_string(bytes, value),
+        Tag::List(values) => {
+            bytes.push(values.first().map_or(0, Tag::id));
+            bytes.extend_from_slice(&(values.len() as i32).to_be_bytes());
+            for value in values {
+                write_payload(bytes, value);
+            }
+        }
+        Tag::Compound(entries) => {
+            for (name, value) in entries {
+                bytes.push(value.id());
+                write_string(bytes, name);
+                write_payload(bytes, value);
+            }
+            bytes.push(0);
+        }
+        Tag::IntArray(values) => {
+            bytes.extend_from_slice(&(values.len() as i32).to_be_bytes());
+            for value in values {
+                bytes.extend_from_slice(&value.to_be_bytes());
+            }
+        }
+        Tag::LongArray(values) => {
+            bytes.extend_from_slice(&(values.len() as i32).to_be_bytes());
+            for value in values {
+                bytes.extend_from_slice(&value.to_be_bytes());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn documents_read_back_what_was_written() {
+        let root = Tag::compound([
+            ("DataVersion", Tag::Int(3465)),
+            ("Name", Tag::String("minecraft:stone".to_string())),
+            ("Bytes", Tag::ByteArray(vec![-1, 0, 1])),
+            ("Longs", Tag::LongArray(vec![i64::MIN, 42])),
+            ("Sections", Tag::List(vec![Tag::compound([("Y", Tag::Byte(-4))]), Tag::compound([])])),
+            ("Empty", Tag::List(Vec::new())),
+            ("Nested", Tag::compound([("Pi", Tag::Double(3.25)), ("Ints", Tag::IntArray(vec![7]))])),
+        ]);
+        let bytes = write(&root);
+        assert_eq!(read(&bytes).unwrap(), root);
+        assert_eq!(root.get("Sections").and_then(Tag::as_list).unwrap()[0].get("Y").and_then(Tag::as_i64), Some(-4));
+
+        // Cut short anywhere, the document is an error rather than a panic
+        for length in 0..bytes.len() {
+            assert!(read(&bytes[..length]).is_err(), "read {} bytes", length);
+        }
+    }
+}
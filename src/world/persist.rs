@@ -0,0 +1,320 @@
+use bevy::app::AppExit;
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use crate::blocks::{BlockRegistry, BlockState};
+use crate::components::{Mob, MobData};
+use crate::crafting::Furnace;
+use crate::entities::EntityRegistry;
+use crate::items::ItemRegistry;
+use crate::physics::PhysicsBody;
+use crate::resources::{HotbarItem, ItemStack};
+use super::block_entity::BlockEntity;
+use super::chunk::{Chunk, ChunkCoord};
+use super::saved::{ChunkModified, SavedChunk, SavedChunks};
+use super::schematic::PaletteEntry;
+use super::spawn::SpawnPoint;
+use super::storage::PalettedContainer;
+
+/// Directory in the world directory holding one file per saved chunk
+const CHUNKS_DIR: &str = "chunks";
+
+/// File in the world directory holding the spawn point
+const SPAWN_FILE: &str = "spawn.ron";
+
+/// Directory the world is saved to between runs (the server's `world_path`)
+/// Edited chunks and the mobs in unloaded chunks are written there as they unload and when
+/// the app exits, and read back at startup. Without it they live in memory only
+#[derive(Resource, Debug, Clone)]
+pub struct WorldDirectory(pub PathBuf);
+
+impl WorldDirectory {
+    fn chunks(&self) -> PathBuf {
+        self.0.join(CHUNKS_DIR)
+    }
+
+    fn chunk_path(&self, coord: ChunkCoord) -> PathBuf {
+        self.chunks().join(format!("{}.{}.{}.ron", coord.x, coord.y, coord.z))
+    }
+}
+
+/// A saved chunk (`chunks/<x>.<y>.<z>.ron`), naming blocks, items and mobs by string ID
+/// so it loads whatever numeric IDs they get next time
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChunkFile {
+    pub coord: [i32; 3],
+    /// Left out when only the chunk's mobs are kept
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub blocks: Option<ChunkBlocksFile>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mobs: Vec<MobFile>,
+}
+
+/// A chunk's blocks as its `PalettedContainer` stores them (see `PalettedContainer::to_palette`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChunkBlocksFile {
+    pub palette: Vec<PaletteEntry>,
+    pub bits: u8,
+    pub words: Vec<u64>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub furnaces: Vec<FurnaceFile>,
+}
+
+/// A furnace block entity by its block index in the chunk
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FurnaceFile {
+    pub index: usize,
+    pub input: Option<StackFile>,
+    pub fuel: Option<StackFile>,
+    pub output: Option<StackFile>,
+    pub burn_ticks: u32,
+    pub burn_ticks_total: u32,
+    pub cook_ticks: u32,
+    pub cook_ticks_total: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ItemFile {
+    Block(String),
+    Item(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StackFile {
+    pub item: ItemFile,
+    pub count: u8,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub durability: Option<u16>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MobFile {
+    pub entity: String,
+    pub position: [f32; 3],
+    pub yaw: f32,
+    pub health: u8,
+}
+
+/// The registries that turn numeric IDs into the string IDs files keep, and back
+#[derive(SystemParam)]
+pub struct SaveRegistries<'w> {
+    blocks: Res<'w, BlockRegistry>,
+    items: Res<'w, ItemRegistry>,
+    entities: Res<'w, EntityRegistry>,
+}
+
+impl SaveRegistries<'_> {
+    pub fn to_file(&self, coord: ChunkCoord, saved: &SavedChunk) -> ChunkFile {
+        let blocks = saved.chunk.as_ref().map(|chunk| {
+            let (palette, bits, words) = chunk.blocks.to_palette();
+            let palette = palette.into_iter()
+                .map(|state| PaletteEntry {
+                    block: self.blocks.get_string_id(state.id).unwrap_or("core:air").to_string(),
+                    data: state.data,
+                })
+                .collect();
+            let mut furnaces: Vec<FurnaceFile> = chunk.block_entities.iter()
+                .map(|(&index, BlockEntity::Furnace(furnace))| FurnaceFile {
+                    index,
+                    input: self.stack_to_file(furnace.input),
+                    fuel: self.stack_to_file(furnace.fuel),
+                    output: self.stack_to_file(furnace.output),
+                    burn_ticks: furnace.burn_ticks,
+                    burn_ticks_total: furnace.burn_ticks_total,
+                    cook_ticks: furnace.cook_ticks,
+                    cook_ticks_total: furnace.cook_ticks_total,
+                })
+                .collect();
+            furnaces.sort_by_key(|furnace| furnace.index);
+            ChunkBlocksFile { palette, bits, words, furnaces }
+        });
+        let mobs = saved.mobs.iter()
+            .filter_map(|mob| Some(MobFile {
+                entity: self.entities.get_entity(mob.entity_type)?.properties.id.clone(),
+                position: mob.position.to_array(),
+                yaw: mob.yaw,
+                health: mob.health,
+            }))
+            .collect();
+        ChunkFile { coord: [coord.x, coord.y, coord.z], blocks, mobs }
+    }
+
+    /// Read a chunk file back with this session's IDs
+    /// Blocks that aren't registered any more become air, and such items and mobs are dropped
+    pub fn from_file(&self, file: ChunkFile) -> Result<(ChunkCoord, SavedChunk), String> {
+        let coord = ChunkCoord::new(file.coord[0], file.coord[1], file.coord[2]);
+        let chunk = match file.blocks {
+            Some(blocks) => {
+                let palette = blocks.palette.into_iter()
+                    .map(|entry| match self.blocks.get_id(&entry.block) {
+                        Some(id) => BlockState::with_data(id, entry.data),
+                        None => {
+                            warn!("Block '{}' not found, replacing with air", entry.block);
+                            BlockState::AIR
+                        }
+                    })
+                    .collect();
+                let mut chunk = Chunk::new(coord);
+                chunk.blocks = PalettedContainer::from_palette(palette, blocks.bits, &blocks.words)
+                    .ok_or_else(|| format!("Invalid blocks in chunk {:?}", file.coord))?;
+                for furnace in blocks.furnaces {
+                    let block_entity = BlockEntity::Furnace(Furnace {
+                        input: furnace.input.and_then(|stack| self.stack_from_file(stack)),
+                        fuel: furnace.fuel.and_then(|stack| self.stack_from_file(stack)),
+                        output: furnace.output.and_then(|stack| self.stack_from_file(stack)),
+                        burn_ticks: furnace.burn_ticks,
+                        burn_ticks_total: furnace.burn_ticks_total,
+                        cook_ticks: furnace.cook_ticks,
+                        cook_ticks_total: furnace.cook_ticks_total,
+                    });
+                    chunk.block_entities.insert(furnace.index, block_entity);
+                }
+                Some(chunk)
+            }
+            None => None,
+        };
+        let mobs = file.mobs.into_iter()
+            .filter_map(|mob| {
+                let Some(entity_type) = self.entities.get_id(&mob.entity) else {
+                    warn!("Mob '{}' not found, leaving it out", mob.entity);
+                    return None;
+                };
+                Some(MobData { entity_type, position: Vec3::from_array(mob.position), yaw: mob.yaw, health: mob.health })
+            })
+            .collect();
+        Ok((coord, SavedChunk { chunk, mobs }))
+    }
+
+    fn stack_to_file(&self, stack: Option<ItemStack>) -> Option<StackFile> {
+        let stack = stack?;
+        let item = match stack.item {
+            HotbarItem::Block(id) => ItemFile::Block(self.blocks.get_string_id(id)?.to_string()),
+            HotbarItem::Item(id) => ItemFile::Item(self.items.get_string_id(id)?.to_string()),
+        };
+        Some(StackFile { item, count: stack.count, durability: stack.durability })
+    }
+
+    fn stack_from_file(&self, stack: StackFile) -> Option<ItemStack> {
+        let item = match &stack.item {
+            ItemFile::Block(id) => self.blocks.get_id(id).map(HotbarItem::Block),
+            ItemFile::Item(id) => self.items.get_id(id).map(HotbarItem::Item),
+        };
+        let Some(item) = item else {
+            warn!("Item {:?} not found, leaving it out", stack.item);
+            return None;
+        };
+        Some(ItemStack { item, count: stack.count, durability: stack.durability })
+    }
+}
+
+fn write_ron<T: Serialize>(path: &Path, value: &T) -> Result<(), String> {
+    if let Some(directory) = path.parent() {
+        fs::create_dir_all(directory).map_err(|error| format!("Can't create {}: {}", directory.display(), error))?;
+    }
+    let source = ron::to_string(value).map_err(|error| error.to_string())?;
+    fs::write(path, source).map_err(|error| format!("Can't write {}: {}", path.display(), error))
+}
+
+fn read_ron<T: for<'de> Deserialize<'de>>(path: &Path) -> Result<T, String> {
+    let source = fs::read_to_string(path).map_err(|error| format!("Can't read {}: {}", path.display(), error))?;
+    ron::from_str(&source).map_err(|error| format!("Invalid {}: {}", path.display(), error))
+}
+
+/// Read the saved chunks and spawn point back from the world directory
+/// Runs once the mods have registered everything the files name
+pub fn load_world(
+    directory: Option<Res<WorldDirectory>>,
+    registries: SaveRegistries,
+    mut saved: ResMut<SavedChunks>,
+    mut spawn_point: ResMut<SpawnPoint>,
+) {
+    let Some(directory) = directory else {
+        return;
+    };
+    let spawn_path = directory.0.join(SPAWN_FILE);
+    if spawn_path.exists() {
+        match read_ron::<[i32; 3]>(&spawn_path) {
+            Ok(block) => spawn_point.block = IVec3::from_array(block),
+            Err(error) => error!("{}", error),
+        }
+    } else if let Err(error) = write_ron(&spawn_path, &spawn_point.block.to_array()) {
+        error!("{}", error);
+    }
+
+    let entries = fs::read_dir(directory.chunks()).into_iter().flatten().flatten();
+    for path in entries.map(|entry| entry.path()).filter(|path| path.extension().is_some_and(|extension| extension == "ron")) {
+        match read_ron(&path).and_then(|file| registries.from_file(file)) {
+            Ok((coord, chunk)) => saved.restore(coord, chunk),
+            Err(error) => error!("{}", error),
+        }
+    }
+    info!("Loaded {} saved chunks from {}", saved.len(), directory.0.display());
+}
+
+/// Write the chunks that unloaded (or changed while unloaded) this frame
+/// A chunk that loads again keeps its file until the world is saved on exit
+pub fn write_saved_chunks(directory: Option<Res<WorldDirectory>>, registries: SaveRegistries, mut saved: ResMut<SavedChunks>) {
+    let Some(directory) = directory else {
+        return;
+    };
+    for coord in saved.take_changed() {
+        let Some(chunk) = saved.get(coord) else {
+            continue;
+        };
+        if let Err(error) = write_ron(&directory.chunk_path(coord), &registries.to_file(coord, chunk)) {
+            error!("{}", error);
+        }
+    }
+}
+
+/// Write the whole world when the app exits: saved chunks, loaded chunks worth keeping and the
+/// mobs in them, and the spawn point. Files of chunks that aren't worth keeping any more are removed
+pub fn save_world_on_exit(
+    mut exit: EventReader<AppExit>,
+    directory: Option<Res<WorldDirectory>>,
+    registries: SaveRegistries,
+    saved: Res<SavedChunks>,
+    spawn_point: Res<SpawnPoint>,
+    chunks: Query<(&Chunk, Has<ChunkModified>)>,
+    mobs: Query<(&Mob, &PhysicsBody)>,
+) {
+    if exit.read().count() == 0 {
+        return;
+    }
+    let Some(directory) = directory else {
+        return;
+    };
+
+    let mut world: HashMap<ChunkCoord, SavedChunk> = saved.iter()
+        .map(|(coord, chunk)| (coord, chunk.clone()))
+        .collect();
+    for (chunk, modified) in chunks.iter() {
+        if modified || !chunk.block_entities.is_empty() {
+            world.entry(chunk.coord).or_default().chunk = Some(chunk.clone());
+        }
+    }
+    for (mob, body) in mobs.iter() {
+        world.entry(ChunkCoord::from_world_pos(body.position)).or_default().mobs.push(mob.data(body.position));
+    }
+
+    let mut written = 0;
+    for (&coord, chunk) in &world {
+        match write_ron(&directory.chunk_path(coord), &registries.to_file(coord, chunk)) {
+            Ok(()) => written += 1,
+            Err(error) => error!("{}", error),
+        }
+    }
+    let kept: HashSet<PathBuf> = world.keys().map(|&coord| directory.chunk_path(coord)).collect();
+    for entry in fs::read_dir(directory.chunks()).into_iter().flatten().flatten() {
+        if !kept.contains(&entry.path()) {
+            let _ = fs::remove_file(entry.path());
+        }
+    }
+    if let Err(error) = write_ron(&directory.0.join(SPAWN_FILE), &spawn_point.block.to_array()) {
+        error!("{}", error);
+    }
+    info!("Saved {} chunks to {}", written, directory.0.display());
+}
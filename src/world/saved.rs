@@ -0,0 +1,149 @@
+use bevy::prelude::*;
+use std::collections::{HashMap, HashSet};
+
+use super::chunk::{Chunk, ChunkCoord, UNLOAD_MARGIN};
+use super::terrain::{ChunkManager, WorldSettings};
+use crate::components::{MobData, Player};
+
+/// Marker for chunks edited since they were generated, which are kept when they unload
+#[derive(Component)]
+pub struct ChunkModified;
+
+/// What's kept of an unloaded chunk
+#[derive(Clone, Default)]
+pub struct SavedChunk {
+    /// The blocks, if they differ from what the generator would make
+    pub chunk: Option<Chunk>,
+    /// Mobs that were in the chunk
+    pub mobs: Vec<MobData>,
+}
+
+/// Unloaded chunks waiting to be loaded again, held in memory
+/// Untouched chunks without mobs aren't kept at all - they're generated again.
+/// With a `WorldDirectory` they're written to disk too (see `persist`)
+#[derive(Resource, Default)]
+pub struct SavedChunks {
+    chunks: HashMap<ChunkCoord, SavedChunk>,
+    /// Chunks saved or changed since they were last written to disk
+    changed: HashSet<ChunkCoord>,
+}
+
+impl SavedChunks {
+    pub fn save_chunk(&mut self, mut chunk: Chunk) {
+        // Edits can leave a chunk needing fewer bits per block than its storage grew to
+        chunk.compact();
+        let coord = chunk.coord;
+        self.chunks.entry(coord).or_default().chunk = Some(chunk);
+        self.changed.insert(coord);
+    }
+
+    pub fn save_mob(&mut self, coord: ChunkCoord, mob: MobData) {
+        self.chunks.entry(coord).or_default().mobs.push(mob);
+        self.changed.insert(coord);
+    }
+
+    /// Put back a chunk read from disk, which doesn't need writing again
+    pub fn restore(&mut self, coord: ChunkCoord, saved: SavedChunk) {
+        self.chunks.insert(coord, saved);
+    }
+
+    /// Take the saved blocks of a chunk that's about to load; its mobs stay until `take_mobs`
+    pub fn take_chunk(&mut self, coord: ChunkCoord) -> Option<Chunk> {
+        self.chunks.get_mut(&coord)?.chunk.take()
+    }
+
+    /// Take the mobs of a chunk that just loaded, forgetting the chunk
+    pub fn take_mobs(&mut self, coord: ChunkCoord) -> Vec<MobData> {
+        self.chunks.remove(&coord).map(|saved| saved.mobs).unwrap_or_default()
+    }
+
+    /// The saved blocks of an unloaded chunk, to change them before it loads again
+    pub fn chunk_mut(&mut self, coord: ChunkCoord) -> Option<&mut Chunk> {
+        let chunk = self.chunks.get_mut(&coord)?.chunk.as_mut()?;
+        self.changed.insert(coord);
+        Some(chunk)
+    }
+
+    pub fn get(&self, coord: ChunkCoord) -> Option<&SavedChunk> {
+        self.chunks.get(&coord)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (ChunkCoord, &SavedChunk)> {
+        self.chunks.iter().map(|(&coord, saved)| (coord, saved))
+    }
+
+    /// Chunks saved or changed since the last call, to write to disk
+    pub fn take_changed(&mut self) -> Vec<ChunkCoord> {
+        self.changed.drain().collect()
+    }
+
+    pub fn contains(&self, coord: ChunkCoord) -> bool {
+        self.chunks.contains_key(&coord)
+    }
+
+    pub fn len(&self) -> usize {
+        self.chunks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.chunks.is_empty()
+    }
+}
+
+/// Despawn chunks that are well past the view distance of every player, keeping the ones worth saving
+/// (mobs in them are saved by `save_mobs_in_unloaded_chunks`)
+/// Runs after remeshing so no queued command still points at a despawned chunk
+pub fn unload_distant_chunks(
+    mut commands: Commands,
+    mut chunk_manager: ResMut<ChunkManager>,
+    mut saved: ResMut<SavedChunks>,
+    settings: Res<WorldSettings>,
+    player_query: Query<&Transform, With<Player>>,
+    chunks: Query<(&Chunk, Has<ChunkModified>)>,
+) {
+    // With nobody around, keep what's loaded rather than emptying the world
+    if player_query.is_empty() {
+        return;
+    }
+    let player_chunks: Vec<ChunkCoord> = player_query.iter()
+        .map(|transform| ChunkCoord::from_world_pos(transform.translation))
+        .collect();
+    let distant: Vec<(ChunkCoord, Entity)> = chunk_manager.loaded_chunks.iter()
+        .filter(|(coord, _)| {
+            player_chunks.iter().all(|&player_chunk| !settings.in_view(player_chunk, **coord, UNLOAD_MARGIN))
+        })
+        .map(|(&coord, &entity)| (coord, entity))
+        .collect();
+
+    for (coord, entity) in distant {
+        chunk_manager.loaded_chunks.remove(&coord);
+        if let Ok((chunk, modified)) = chunks.get(entity) {
+            if modified || !chunk.block_entities.is_empty() {
+                saved.save_chunk(chunk.clone());
+            }
+        }
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::EntityTypeId;
+
+    #[test]
+    fn saved_chunks_hand_back_blocks_then_mobs() {
+        let coord = ChunkCoord::new(1, 2, 3);
+        let mob = MobData { entity_type: EntityTypeId(0), position: Vec3::new(20.5, 40.0, 50.5), yaw: 1.0, health: 7 };
+        let mut saved = SavedChunks::default();
+        saved.save_chunk(Chunk::new(coord));
+        saved.save_mob(coord, mob);
+
+        assert!(saved.take_chunk(coord).is_some_and(|chunk| chunk.coord == coord));
+        // The mobs wait for the chunk to finish loading
+        assert!(saved.contains(coord));
+        assert_eq!(saved.take_chunk(coord).map(|chunk| chunk.coord), None);
+        assert_eq!(saved.take_mobs(coord), vec![mob]);
+        assert_eq!(saved.len(), 0);
+    }
+}
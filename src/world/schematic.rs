@@ -0,0 +1,307 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use crate::blocks::{BlockRegistry, BlockState};
+use super::access::WORLD_LIMIT;
+use super::block_entity::BlockEntity;
+
+/// Extension of schematic files
+pub const SCHEMATIC_EXTENSION: &str = "schem.ron";
+
+/// Most blocks a schematic holds, well past what one paste places, so a corrupt file can't
+/// ask for an enormous allocation
+pub const MAX_SCHEMATIC_VOLUME: i64 = 1 << 20;
+
+/// Where `/schematic save` and `/schematic load` keep their files
+#[derive(Resource, Debug, Clone)]
+pub struct SchematicDirectory(pub PathBuf);
+
+impl Default for SchematicDirectory {
+    fn default() -> Self {
+        Self(PathBuf::from("schematics"))
+    }
+}
+
+impl SchematicDirectory {
+    /// The file of the schematic called `name`
+    pub fn path(&self, name: &str) -> PathBuf {
+        self.0.join(format!("{}.{}", name, SCHEMATIC_EXTENSION))
+    }
+
+    /// Names of the saved schematics, sorted
+    pub fn names(&self) -> Vec<String> {
+        let suffix = format!(".{}", SCHEMATIC_EXTENSION);
+        let mut names: Vec<String> = std::fs::read_dir(&self.0).into_iter().flatten().flatten()
+            .filter_map(|entry| entry.file_name().to_str()?.strip_suffix(&suffix).map(str::to_string))
+            .collect();
+        names.sort();
+        names
+    }
+}
+
+/// A box of blocks lifted out of the world (a clipboard), to be placed somewhere else
+/// Positions inside it are local, from its lowest corner; `offset` is where that corner goes
+/// from the position it's pasted at (where the player stood when copying)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Schematic {
+    size: IVec3,
+    pub offset: IVec3,
+    /// x fastest, then z, then y
+    blocks: Vec<BlockState>,
+    block_entities: Vec<(IVec3, BlockEntity)>,
+}
+
+impl Schematic {
+    /// An all-air schematic of `size` blocks
+    /// Panics past `MAX_SCHEMATIC_VOLUME` blocks; sizes from outside are checked by `from_file`
+    pub fn new(size: IVec3, offset: IVec3) -> Self {
+        let size = size.max(IVec3::ONE);
+        let volume = checked_volume(size).filter(|&volume| volume <= MAX_SCHEMATIC_VOLUME)
+            .unwrap_or_else(|| panic!("Schematic of {} blocks is too big", size));
+        Self { size, offset, blocks: vec![BlockState::AIR; volume as usize], block_entities: Vec::new() }
+    }
+
+    pub fn size(&self) -> IVec3 {
+        self.size
+    }
+
+    pub fn volume(&self) -> usize {
+        self.blocks.len()
+    }
+
+    fn index(&self, local: IVec3) -> usize {
+        ((local.y * self.size.z + local.z) * self.size.x + local.x) as usize
+    }
+
+    fn local(&self, index: usize) -> IVec3 {
+        let index = index as i32;
+        IVec3::new(index % self.size.x, index / (self.size.x * self.size.z), (index / self.size.x) % self.size.z)
+    }
+
+    pub fn get(&self, local: IVec3) -> BlockState {
+        self.blocks[self.index(local)]
+    }
+
+    pub fn set(&mut self, local: IVec3, state: BlockState) {
+        let index = self.index(local);
+        self.blocks[index] = state;
+    }
+
+    /// Every block with its local position, in the order they're stored
+    pub fn blocks(&self) -> impl Iterator<Item = (IVec3, BlockState)> + '_ {
+        self.blocks.iter().enumerate().map(|(index, &state)| (self.local(index), state))
+    }
+
+    pub fn block_entities(&self) -> &[(IVec3, BlockEntity)] {
+        &self.block_entities
+    }
+
+    pub fn add_block_entity(&mut self, local: IVec3, block_entity: BlockEntity) {
+        self.block_entities.push((local, block_entity));
+    }
+
+    /// Turned `quarter_turns` times clockwise seen from above, around the position it's pasted at
+    pub fn rotated(&self, quarter_turns: i32, registry: &BlockRegistry) -> Self {
+        let turns = quarter_turns.rem_euclid(4);
+        self.transformed(&|direction: IVec3| {
+            (0..turns).fold(direction, |direction, _| IVec3::new(-direction.z, direction.y, direction.x))
+        }, registry)
+    }
+
+    /// Mirrored along `axis` (0 = x, 1 = y, 2 = z), through the position it's pasted at
+    pub fn flipped(&self, axis: usize, registry: &BlockRegistry) -> Self {
+        self.transformed(&|mut direction: IVec3| {
+            direction[axis] = -direction[axis];
+            direction
+        }, registry)
+    }
+
+    /// Moved by a transform made of quarter turns and mirrors, which blocks with a direction follow
+    fn transformed(&self, transform: &dyn Fn(IVec3) -> IVec3, registry: &BlockRegistry) -> Self {
+        let (a, b) = (transform(self.offset), transform(self.offset + self.size - IVec3::ONE));
+        let offset = a.min(b);
+        let mut result = Self::new((a - b).abs() + IVec3::ONE, offset);
+        let moved = |local: IVec3| transform(self.offset + local) - offset;
+        for (local, state) in self.blocks() {
+            let state = match registry.get_block(state.id).and_then(|block| block.properties.behavior.as_ref()) {
+                Some(behavior) if !state.is_air() => behavior.transform_state(state, transform),
+                _ => state,
+            };
+            let index = result.index(moved(local));
+            result.blocks[index] = state;
+        }
+        result.block_entities = self.block_entities.iter()
+            .map(|(local, block_entity)| (moved(*local), block_entity.clone()))
+            .collect();
+        result
+    }
+
+    /// The schematic as a file, its blocks named by string ID so it loads whatever numeric
+    /// IDs blocks get next time. Block entities (furnace contents) aren't kept
+    pub fn to_file(&self, registry: &BlockRegistry) -> SchematicFile {
+        let mut palette = Vec::new();
+        let mut indices: HashMap<BlockState, u16> = HashMap::new();
+        let blocks = self.blocks.iter()
+            .map(|&state| *indices.entry(state).or_insert_with(|| {
+                palette.push(PaletteEntry {
+                    block: registry.get_string_id(state.id).unwrap_or("core:air").to_string(),
+                    data: state.data,
+                });
+                (palette.len() - 1) as u16
+            }))
+            .collect();
+        SchematicFile { size: self.size.to_array(), offset: self.offset.to_array(), palette, blocks }
+    }
+
+    /// Read a schematic file back with this session's block IDs
+    /// Blocks that aren't registered any more (their mod was removed) become air;
+    /// their string IDs are returned alongside
+    pub fn from_file(file: SchematicFile, registry: &BlockRegistry) -> Result<(Self, Vec<String>), String> {
+        let size = IVec3::from_array(file.size);
+        let offset = IVec3::from_array(file.offset);
+        if size.cmplt(IVec3::ONE).any() {
+            return Err(format!("Invalid schematic size {:?}", file.size));
+        }
+        let volume = checked_volume(size).filter(|&volume| volume <= MAX_SCHEMATIC_VOLUME)
+            .ok_or_else(|| format!("Schematic size {:?} is over the limit of {} blocks", file.size, MAX_SCHEMATIC_VOLUME))?;
+        if offset.cmplt(IVec3::splat(-WORLD_LIMIT)).any() || offset.cmpgt(IVec3::splat(WORLD_LIMIT)).any() {
+            return Err(format!("Invalid schematic offset {:?}", file.offset));
+        }
+        if file.blocks.len() as i64 != volume {
+            return Err(format!("Schematic has {} blocks, expected {}", file.blocks.len(), volume));
+        }
+        let mut schematic = Self::new(size, offset);
+
+        let mut missing = Vec::new();
+        let palette: Vec<BlockState> = file.palette.into_iter()
+            .map(|entry| match registry.get_id(&entry.block) {
+                Some(id) => BlockState::with_data(id, entry.data),
+                None => {
+                    warn!("Block '{}' not found, replacing with air", entry.block);
+                    missing.push(entry.block);
+                    BlockState::AIR
+                }
+            })
+            .collect();
+        for (slot, index) in schematic.blocks.iter_mut().zip(file.blocks) {
+            *slot = *palette.get(index as usize)
+                .ok_or_else(|| format!("Palette index {} out of range", index))?;
+        }
+        Ok((schematic, missing))
+    }
+
+    pub fn save(&self, path: &Path, registry: &BlockRegistry) -> Result<(), String> {
+        let source = ron::ser::to_string_pretty(&self.to_file(registry), ron::ser::PrettyConfig::default())
+            .map_err(|error| format!("Could not write schematic: {}", error))?;
+        if let Some(directory) = path.parent() {
+            std::fs::create_dir_all(directory).map_err(|error| format!("Could not create {}: {}", directory.display(), error))?;
+        }
+        std::fs::write(path, source).map_err(|error| format!("Could not write {}: {}", path.display(), error))
+    }
+
+    pub fn load(path: &Path, registry: &BlockRegistry) -> Result<(Self, Vec<String>), String> {
+        let source = std::fs::read_to_string(path).map_err(|error| format!("Could not read {}: {}", path.display(), error))?;
+        let file = ron::from_str(&source).map_err(|error| format!("Invalid schematic file: {}", error))?;
+        Self::from_file(file, registry)
+    }
+}
+
+/// Blocks in a box of `size`, worked out in i64 so no size wraps around
+fn checked_volume(size: IVec3) -> Option<i64> {
+    let size = size.as_i64vec3();
+    size.x.checked_mul(size.y)?.checked_mul(size.z)
+}
+
+/// A schematic file (`<name>.schem.ron`): its size, paste offset, the block states it uses
+/// and, for each block (x fastest, then z, then y), an index into that palette
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SchematicFile {
+    pub size: [i32; 3],
+    pub offset: [i32; 3],
+    pub palette: Vec<PaletteEntry>,
+    pub blocks: Vec<u16>,
+}
+
+/// A block state by its string ID
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PaletteEntry {
+    pub block: String,
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub data: u8,
+}
+
+fn is_zero(data: &u8) -> bool {
+    *data == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blocks::{BlockId, BlockType};
+
+    fn registry() -> BlockRegistry {
+        let mut registry = BlockRegistry::new();
+        for id in ["test:a", "test:b"] {
+            registry.register_block(BlockType::builder(id, id).build()).unwrap();
+        }
+        registry
+    }
+
+    /// A 3x1x2 schematic pasted one block west of where it was copied from, with
+    /// a different block in each cell
+    fn sample() -> Schematic {
+        let mut schematic = Schematic::new(IVec3::new(3, 1, 2), IVec3::new(-1, 0, 0));
+        for (i, local) in [IVec3::new(0, 0, 0), IVec3::new(2, 0, 0), IVec3::new(2, 0, 1)].into_iter().enumerate() {
+            schematic.set(local, BlockState::with_data(BlockId(1 + i as u16 % 2), i as u8));
+        }
+        schematic
+    }
+
+    #[test]
+    fn rotating_turns_blocks_around_the_paste_position() {
+        let registry = registry();
+        let schematic = sample();
+        let turned = schematic.rotated(1, &registry);
+        assert_eq!(turned.size(), IVec3::new(2, 1, 3));
+        // Relative positions (x, z) go to (-z, x): the block one west of the paste position ends up one north
+        assert_eq!(turned.offset, IVec3::new(-1, 0, -1));
+        assert_eq!(turned.get(IVec3::new(1, 0, 0)), schematic.get(IVec3::ZERO));
+        assert_eq!(turned.rotated(3, &registry), schematic);
+        assert_eq!(schematic.flipped(0, &registry).flipped(0, &registry), schematic);
+        assert_eq!(schematic.flipped(0, &registry).get(IVec3::new(2, 0, 0)), schematic.get(IVec3::ZERO));
+    }
+
+    #[test]
+    fn files_name_blocks_by_string_id() {
+        let schematic = sample();
+        let file = schematic.to_file(&registry());
+        assert_eq!(file.palette[0], PaletteEntry { block: "test:a".to_string(), data: 0 });
+
+        // Loaded again after "test:a" moved to another ID and "test:b" went away
+        let mut remapped = BlockRegistry::new();
+        remapped.register_block(BlockType::builder("test:c", "C").build()).unwrap();
+        let a = remapped.register_block(BlockType::builder("test:a", "A").build()).unwrap();
+        let source = ron::ser::to_string(&file).unwrap();
+        let (loaded, missing) = Schematic::from_file(ron::from_str(&source).unwrap(), &remapped).unwrap();
+        assert_eq!(missing, ["test:b"]);
+        assert_eq!(loaded.get(IVec3::ZERO), BlockState::new(a));
+        assert_eq!(loaded.get(IVec3::new(2, 0, 0)), BlockState::AIR);
+        assert_eq!(loaded.get(IVec3::new(2, 0, 1)), BlockState::with_data(a, 2));
+    }
+
+    #[test]
+    fn files_with_impossible_sizes_are_refused_before_allocating() {
+        let registry = registry();
+        let file = |size: [i32; 3], offset: [i32; 3]| SchematicFile { size, offset, palette: Vec::new(), blocks: vec![0; 6] };
+        for (size, offset) in [
+            ([i32::MAX, i32::MAX, 2], [0; 3]),
+            ([65536, 65536, 1], [0; 3]),
+            ([0, 1, 1], [0; 3]),
+            ([3, 1, 2], [i32::MIN, 0, 0]),
+            ([3, 1, 3], [0; 3]),
+        ] {
+            assert!(Schematic::from_file(file(size, offset), &registry).is_err(), "{:?} at {:?}", size, offset);
+        }
+    }
+}
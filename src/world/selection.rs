@@ -0,0 +1,34 @@
+use bevy::math::I64Vec3;
+use bevy::prelude::*;
+use super::schematic::Schematic;
+
+/// Item creative players mark selections with: left-click a block for the first corner,
+/// right-click for the second
+pub const SELECTION_WAND: &str = "core:wooden_axe";
+
+/// The box of blocks a player has marked for `/copy` and `/cut`, both corners included
+#[derive(Component, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Selection {
+    pub first: Option<IVec3>,
+    pub second: Option<IVec3>,
+}
+
+impl Selection {
+    /// The lowest and highest corner, once both are set
+    pub fn bounds(&self) -> Option<(IVec3, IVec3)> {
+        let (first, second) = (self.first?, self.second?);
+        Some((first.min(second), first.max(second)))
+    }
+
+    /// Blocks in the selection (0 until both corners are set)
+    pub fn volume(&self) -> i64 {
+        self.bounds().map_or(0, |(min, max)| {
+            let size = max.as_i64vec3() - min.as_i64vec3() + I64Vec3::ONE;
+            size.x.saturating_mul(size.y).saturating_mul(size.z)
+        })
+    }
+}
+
+/// What a player last copied or cut (or loaded from a schematic file), ready for `/paste`
+#[derive(Component, Debug, Clone, Default)]
+pub struct Clipboard(pub Option<Schematic>);
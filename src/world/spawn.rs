@@ -0,0 +1,88 @@
+use bevy::prelude::*;
+use noise::Simplex;
+use super::terrain::get_terrain_height;
+
+/// How far (in blocks) from the origin the spawn search looks for safe ground
+const SPAWN_SEARCH_RADIUS: i32 = 128;
+
+/// Largest height difference to the neighboring columns for ground to count as flat
+const MAX_SPAWN_SLOPE: i32 = 1;
+
+/// World spawn point: where players appear on joining and respawn without a bed
+/// Found from the world seed when a world is created; with a `WorldDirectory` it's saved
+/// there and read back on later starts
+#[derive(Resource, Debug, Clone, Copy, PartialEq)]
+pub struct SpawnPoint {
+    /// Block the player's feet stand in (the air block above the surface)
+    pub block: IVec3,
+}
+
+impl SpawnPoint {
+    /// Search the world generated from `seed` for a safe spawn
+    pub fn find(seed: u32) -> Self {
+        Self { block: find_spawn_block(seed) }
+    }
+
+    /// Feet position centered on the spawn block
+    pub fn feet_position(&self) -> Vec3 {
+        self.block.as_vec3() + Vec3::new(0.5, 0.0, 0.5)
+    }
+}
+
+/// Find the air block above the first safe surface, spiralling outwards from the origin
+/// Safe means solid ground above the bedrock floor with no steep drop next to it.
+/// Falls back to the origin column if nothing qualifies within the search radius
+pub fn find_spawn_block(seed: u32) -> IVec3 {
+    let simplex = Simplex::new(seed);
+    let height = |x: i32, z: i32| get_terrain_height(&simplex, x, z);
+
+    let is_safe = |x: i32, z: i32| {
+        let surface = height(x, z);
+        surface >= 1 && [(1, 0), (-1, 0), (0, 1), (0, -1)]
+            .iter()
+            .all(|&(dx, dz)| (height(x + dx, z + dz) - surface).abs() <= MAX_SPAWN_SLOPE)
+    };
+
+    for radius in 0..=SPAWN_SEARCH_RADIUS {
+        for (x, z) in ring(radius) {
+            if is_safe(x, z) {
+                return IVec3::new(x, height(x, z) + 1, z);
+            }
+        }
+    }
+
+    IVec3::new(0, height(0, 0).max(1) + 1, 0)
+}
+
+/// Columns on the square ring at Chebyshev distance `radius` from the origin
+fn ring(radius: i32) -> impl Iterator<Item = (i32, i32)> {
+    (-radius..=radius).flat_map(move |x| {
+        (-radius..=radius)
+            .filter(move |&z| x.abs() == radius || z.abs() == radius)
+            .map(move |z| (x, z))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spawn_stands_on_flat_ground_above_bedrock() {
+        for seed in [0, 42, 1234, 987654] {
+            let simplex = Simplex::new(seed);
+            let spawn = find_spawn_block(seed);
+            let surface = get_terrain_height(&simplex, spawn.x, spawn.z);
+            assert_eq!(spawn.y, surface + 1, "seed {seed}");
+            assert!(surface >= 1, "seed {seed}");
+        }
+    }
+
+    #[test]
+    fn ring_covers_the_border_once() {
+        assert_eq!(ring(0).collect::<Vec<_>>(), vec![(0, 0)]);
+        let cells: Vec<_> = ring(2).collect();
+        assert_eq!(cells.len(), 16);
+        assert!(cells.iter().all(|&(x, z)| x.abs() == 2 || z.abs() == 2));
+    }
+}
@@ -0,0 +1,393 @@
+use std::collections::HashMap;
+use crate::blocks::BlockState;
+use super::chunk::CHUNK_SIZE;
+
+/// Blocks in a chunk
+const VOLUME: usize = CHUNK_SIZE * CHUNK_SIZE * CHUNK_SIZE;
+
+/// Bits per index of the packed forms: 4 bits for up to 16 states, 8 for up to 256
+const SMALL_BITS: u8 = 4;
+const LARGE_BITS: u8 = 8;
+
+/// Bits per index of the palette `to_palette` builds for direct storage (a chunk can't hold
+/// more states than it has blocks)
+const DIRECT_PALETTE_BITS: u8 = 16;
+
+#[derive(Debug, Clone)]
+enum Storage {
+    /// Every block is the same state (all air, solid stone): nothing stored per block
+    Single(BlockState),
+    /// A palette of the states in use and one index per block, packed into words
+    /// (indices never straddle two words)
+    Packed { palette: Vec<BlockState>, bits: u8, words: Box<[u64]> },
+    /// More states than an 8-bit palette holds, so each block is stored as it is
+    Direct(Box<[BlockState]>),
+}
+
+/// A chunk's block states, stored with as few bits per block as the states in it need
+/// Starts out as a single value and grows to 4-bit, 8-bit and direct storage as edits add
+/// states; it never shrinks by itself, so call `compact` once a chunk is built or edited in bulk
+#[derive(Debug, Clone)]
+pub struct PalettedContainer {
+    storage: Storage,
+}
+
+impl PalettedContainer {
+    /// Every block `state`
+    pub fn filled(state: BlockState) -> Self {
+        Self { storage: Storage::Single(state) }
+    }
+
+    /// The states of every block, in chunk index order, in the smallest storage that holds them
+    pub fn from_states(states: impl IntoIterator<Item = BlockState>) -> Self {
+        let states: Vec<BlockState> = states.into_iter().collect();
+        assert_eq!(states.len(), VOLUME, "a chunk holds {} blocks", VOLUME);
+        let mut palette = Vec::new();
+        let mut lookup: HashMap<BlockState, usize> = HashMap::new();
+        let mut indices = Vec::with_capacity(VOLUME);
+        for &state in &states {
+            indices.push(*lookup.entry(state).or_insert_with(|| {
+                palette.push(state);
+                palette.len() - 1
+            }));
+            if palette.len() > 1 << LARGE_BITS {
+                return Self { storage: Storage::Direct(states.into_boxed_slice()) };
+            }
+        }
+        let bits = match palette.len() {
+            1 => return Self::filled(palette[0]),
+            len if len <= 1 << SMALL_BITS => SMALL_BITS,
+            _ => LARGE_BITS,
+        };
+        Self { storage: Storage::Packed { words: pack(indices.into_iter(), bits), palette, bits } }
+    }
+
+    pub fn get(&self, index: usize) -> BlockState {
+        match &self.storage {
+            Storage::Single(state) => *state,
+            Storage::Packed { palette, bits, words } => palette[unpack(words, *bits, index)],
+            Storage::Direct(states) => states[index],
+        }
+    }
+
+    pub fn set(&mut self, index: usize, state: BlockState) {
+        match &mut self.storage {
+            Storage::Single(current) if *current == state => {}
+            Storage::Single(current) => {
+                // One new state: a 4-bit palette with everything else still the old state
+                let palette = vec![*current, state];
+                let mut words = vec![0; VOLUME.div_ceil(per_word(SMALL_BITS))].into_boxed_slice();
+                write(&mut words, SMALL_BITS, index, 1);
+                self.storage = Storage::Packed { palette, bits: SMALL_BITS, words };
+            }
+            Storage::Packed { palette, bits, words } => {
+                if let Some(position) = palette.iter().position(|&entry| entry == state) {
+                    write(words, *bits, index, position);
+                } else if palette.len() < 1 << *bits {
+                    palette.push(state);
+                    write(words, *bits, index, palette.len() - 1);
+                } else {
+                    // The palette is full: move up a size and try again
+                    self.grow();
+                    self.set(index, state);
+                }
+            }
+            Storage::Direct(states) => states[index] = state,
+        }
+    }
+
+    /// Set every block to `state`
+    pub fn fill(&mut self, state: BlockState) {
+        self.storage = Storage::Single(state);
+    }
+
+    /// The state of every block, if they're all the same as far as the storage knows
+    /// (a compacted container always knows)
+    pub fn uniform(&self) -> Option<BlockState> {
+        match self.storage {
+            Storage::Single(state) => Some(state),
+            _ => None,
+        }
+    }
+
+    /// Whether any state in the palette passes `test`, without looking at the blocks
+    /// The palette may still hold states no block uses (until `compact`), so a match means
+    /// one might be there; no match means none is. Direct storage checks every block
+    pub fn any_state(&self, mut test: impl FnMut(BlockState) -> bool) -> bool {
+        match &self.storage {
+            Storage::Single(state) => test(*state),
+            Storage::Packed { palette, .. } => palette.iter().any(|&state| test(state)),
+            Storage::Direct(states) => states.iter().any(|&state| test(state)),
+        }
+    }
+
+    /// Bits stored per block: 0 for a single value, 4 or 8 for a palette, 32 when direct
+    pub fn bits_per_block(&self) -> usize {
+        match &self.storage {
+            Storage::Single(_) => 0,
+            Storage::Packed { bits, .. } => *bits as usize,
+            Storage::Direct(_) => std::mem::size_of::<BlockState>() * 8,
+        }
+    }
+
+    /// The storage as a palette, bits per index and packed index words, the form chunks are
+    /// sent in: a single state is a one-entry palette with no words, and direct storage gets
+    /// a palette of the states in it with 16-bit indices
+    pub fn to_palette(&self) -> (Vec<BlockState>, u8, Vec<u64>) {
+        match &self.storage {
+            Storage::Single(state) => (vec![*state], 0, Vec::new()),
+            Storage::Packed { palette, bits, words } => (palette.clone(), *bits, words.to_vec()),
+            Storage::Direct(states) => {
+                let mut palette = Vec::new();
+                let mut lookup: HashMap<BlockState, usize> = HashMap::new();
+                let indices = states.iter().map(|&state| *lookup.entry(state).or_insert_with(|| {
+                    palette.push(state);
+                    palette.len() - 1
+                }));
+                let words = pack(indices, DIRECT_PALETTE_BITS).into_vec();
+                (palette, DIRECT_PALETTE_BITS, words)
+            }
+        }
+    }
+
+    /// A container from what `to_palette` gives, or None if the bits, the number of words or
+    /// an index don't fit the palette
+    pub fn from_palette(palette: Vec<BlockState>, bits: u8, words: &[u64]) -> Option<Self> {
+        if palette.is_empty() || !matches!(bits, 0 | SMALL_BITS | LARGE_BITS | DIRECT_PALETTE_BITS) {
+            return None;
+        }
+        if bits == 0 {
+            return (palette.len() == 1).then(|| Self::filled(palette[0]));
+        }
+        if palette.len() > 1 << bits || words.len() != VOLUME.div_ceil(per_word(bits)) {
+            return None;
+        }
+        if (0..VOLUME).any(|index| unpack(words, bits, index) >= palette.len()) {
+            return None;
+        }
+        if bits == DIRECT_PALETTE_BITS {
+            return Some(Self::from_states((0..VOLUME).map(|index| palette[unpack(words, bits, index)])));
+        }
+        Some(Self { storage: Storage::Packed { palette, bits, words: words.into() } })
+    }
+
+    /// Drop palette entries no block uses any more, moving to smaller storage where it fits
+    pub fn compact(&mut self) {
+        if !matches!(self.storage, Storage::Single(_)) {
+            *self = Self::from_states((0..VOLUME).map(|index| self.get(index)));
+        }
+    }
+
+    /// Compact only when the palette holds states no block uses any more (after edits replaced
+    /// them), which takes one pass over the indices to find out instead of rebuilding the storage
+    /// Direct storage is left for `compact`
+    pub fn compact_unused(&mut self) {
+        if let Storage::Packed { palette, bits, words } = &self.storage {
+            let mut used = vec![false; palette.len()];
+            for index in 0..VOLUME {
+                used[unpack(words, *bits, index)] = true;
+            }
+            if used.contains(&false) {
+                self.compact();
+            }
+        }
+    }
+
+    /// Move to the next larger storage, for a state the palette has no room for
+    fn grow(&mut self) {
+        let states = (0..VOLUME).map(|index| self.get(index));
+        self.storage = match &self.storage {
+            Storage::Packed { palette, bits: SMALL_BITS, words } => {
+                let indices = (0..VOLUME).map(|index| unpack(words, SMALL_BITS, index));
+                Storage::Packed { palette: palette.clone(), bits: LARGE_BITS, words: pack(indices, LARGE_BITS) }
+            }
+            _ => Storage::Direct(states.collect()),
+        };
+    }
+}
+
+impl PartialEq for PalettedContainer {
+    /// Containers holding the same states are equal whatever their storage
+    fn eq(&self, other: &Self) -> bool {
+        match (self.uniform(), other.uniform()) {
+            (Some(state), Some(other_state)) => state == other_state,
+            _ => (0..VOLUME).all(|index| self.get(index) == other.get(index)),
+        }
+    }
+}
+
+fn per_word(bits: u8) -> usize {
+    64 / bits as usize
+}
+
+fn pack(indices: impl Iterator<Item = usize>, bits: u8) -> Box<[u64]> {
+    let mut words = vec![0; VOLUME.div_ceil(per_word(bits))].into_boxed_slice();
+    for (index, value) in indices.enumerate() {
+        write(&mut words, bits, index, value);
+    }
+    words
+}
+
+fn unpack(words: &[u64], bits: u8, index: usize) -> usize {
+    let per_word = per_word(bits);
+    ((words[index / per_word] >> (index % per_word * bits as usize)) & ((1 << bits) - 1)) as usize
+}
+
+fn write(words: &mut [u64], bits: u8, index: usize, value: usize) {
+    let per_word = per_word(bits);
+    let shift = index % per_word * bits as usize;
+    let mask = ((1u64 << bits) - 1) << shift;
+    let word = &mut words[index / per_word];
+    *word = (*word & !mask) | ((value as u64) << shift & mask);
+}
+
+/// A light level (0-15) for each block of a chunk, two to a byte (the even index in the
+/// low nibble), or a single level while every block has the same one
+#[derive(Debug, Clone)]
+pub struct NibbleArray {
+    uniform: Option<u8>,
+    bytes: Box<[u8]>,
+}
+
+impl NibbleArray {
+    pub fn filled(level: u8) -> Self {
+        Self { uniform: Some(level), bytes: Box::default() }
+    }
+
+    /// Levels packed two to a byte, as `to_bytes` gives them
+    pub fn from_bytes(bytes: &[u8]) -> Self {
+        assert_eq!(bytes.len(), VOLUME / 2, "a chunk's light takes {} bytes", VOLUME / 2);
+        let mut array = Self { uniform: None, bytes: bytes.into() };
+        array.compact();
+        array
+    }
+
+    /// Levels packed two to a byte
+    pub fn to_bytes(&self) -> Vec<u8> {
+        match self.uniform {
+            Some(level) => vec![level | level << 4; VOLUME / 2],
+            None => self.bytes.to_vec(),
+        }
+    }
+
+    pub fn get(&self, index: usize) -> u8 {
+        match self.uniform {
+            Some(level) => level,
+            None => (self.bytes[index / 2] >> (index % 2 * 4)) & 0xf,
+        }
+    }
+
+    pub fn set(&mut self, index: usize, level: u8) {
+        if let Some(uniform) = self.uniform {
+            if uniform == level {
+                return;
+            }
+            self.bytes = vec![uniform | uniform << 4; VOLUME / 2].into_boxed_slice();
+            self.uniform = None;
+        }
+        let shift = index % 2 * 4;
+        let byte = &mut self.bytes[index / 2];
+        *byte = (*byte & !(0xf << shift)) | (level & 0xf) << shift;
+    }
+
+    pub fn fill(&mut self, level: u8) {
+        *self = Self::filled(level);
+    }
+
+    /// The level of every block, if it's known to be the same everywhere
+    pub fn uniform(&self) -> Option<u8> {
+        self.uniform
+    }
+
+    /// Go back to a single level if every block has the same one
+    pub fn compact(&mut self) {
+        if self.uniform.is_none() && self.bytes.iter().all(|&byte| byte == self.bytes[0]) && self.bytes[0] >> 4 == self.bytes[0] & 0xf {
+            *self = Self::filled(self.bytes[0] & 0xf);
+        }
+    }
+}
+
+impl PartialEq for NibbleArray {
+    fn eq(&self, other: &Self) -> bool {
+        match (self.uniform, other.uniform) {
+            (Some(level), Some(other_level)) => level == other_level,
+            (None, None) => self.bytes == other.bytes,
+            _ => self.to_bytes() == other.to_bytes(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blocks::BlockId;
+
+    fn state(n: usize) -> BlockState {
+        BlockState::with_data(BlockId(1 + (n / 16) as u16), (n % 16) as u8)
+    }
+
+    #[test]
+    fn containers_grow_through_each_storage_and_compact_back() {
+        let mut blocks = PalettedContainer::filled(BlockState::AIR);
+        assert_eq!(blocks.bits_per_block(), 0);
+        blocks.set(7, BlockState::AIR);
+        assert_eq!(blocks.uniform(), Some(BlockState::AIR), "setting the same state keeps a single value");
+
+        // Air every other block, and between them 300 different states
+        let expected: Vec<BlockState> = (0..VOLUME).map(|index| if index % 2 == 0 { BlockState::AIR } else { state(index / 2 % 300) }).collect();
+        let mut widths = Vec::new();
+        for (index, &state) in expected.iter().enumerate() {
+            blocks.set(index, state);
+            if widths.last() != Some(&blocks.bits_per_block()) {
+                widths.push(blocks.bits_per_block());
+            }
+        }
+        assert_eq!(widths, [0, 4, 8, 32]);
+        assert!((0..VOLUME).all(|index| blocks.get(index) == expected[index]));
+        assert_eq!(PalettedContainer::from_states(expected.iter().copied()), blocks);
+        let (palette, bits, words) = blocks.to_palette();
+        assert_eq!((palette.len(), bits), (301, DIRECT_PALETTE_BITS));
+        assert_eq!(PalettedContainer::from_palette(palette, bits, &words), Some(blocks.clone()));
+
+        // Cleared back down to a few states, compacting picks the smallest storage again
+        for index in 0..VOLUME {
+            blocks.set(index, if index < 100 { state(index % 3) } else { BlockState::AIR });
+        }
+        blocks.compact();
+        assert_eq!(blocks.bits_per_block(), 4);
+        assert_eq!(blocks.get(4), state(1));
+        // A palette that's all in use is kept as it is, one with a state gone is compacted
+        blocks.compact_unused();
+        assert_eq!(blocks.to_palette().0.len(), 4);
+        blocks.set(0, state(7));
+        blocks.set(0, state(0));
+        assert_eq!(blocks.to_palette().0.len(), 5);
+        blocks.compact_unused();
+        assert_eq!(blocks.to_palette().0.len(), 4);
+        assert!(blocks.any_state(|candidate| candidate == state(2)));
+        assert!(!blocks.any_state(|candidate| candidate == state(7)));
+        blocks.fill(state(5));
+        assert_eq!(blocks.uniform(), Some(state(5)));
+        blocks.set(0, state(6));
+        blocks.set(0, state(5));
+        blocks.compact();
+        assert_eq!(blocks.uniform(), Some(state(5)));
+    }
+
+    #[test]
+    fn light_packs_two_levels_a_byte() {
+        let mut light = NibbleArray::filled(15);
+        assert_eq!(light.to_bytes(), vec![0xff; VOLUME / 2]);
+        light.set(0, 3);
+        light.set(1, 12);
+        assert_eq!((light.get(0), light.get(1), light.get(2)), (3, 12, 15));
+        assert_eq!(light.to_bytes()[0], 3 | 12 << 4);
+        assert_eq!(NibbleArray::from_bytes(&light.to_bytes()), light);
+
+        light.set(0, 15);
+        light.set(1, 15);
+        assert_eq!(light, NibbleArray::filled(15), "arrays with the same levels are equal");
+        light.compact();
+        assert_eq!(light.uniform(), Some(15));
+    }
+}
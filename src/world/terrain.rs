@@ -9,7 +9,12 @@ use super::chunk::{Chunk, ChunkCoord, CHUNK_SIZE, VIEW_DISTANCE, VIEW_DISTANCE_V
 use super::mesh_gen::create_chunk_mesh;
 use crate::assets::AssetManager;
 use crate::blocks::{BlockRegistry, BlockId};
-use crate::components::CameraController;
+use crate::components::Player;
+use super::spawn::SpawnPoint;
+use super::saved::SavedChunks;
+use super::tick::WorldRng;
+use super::anvil::{AnvilImport, AnvilWorld};
+use crate::systems::SpawnMob;
 use crate::rendering::terrain_material::TerrainMaterial;
 
 #[derive(Resource)]
@@ -29,94 +34,167 @@ impl Default for ChunkManager {
     }
 }
 
+/// World generation and chunk loading settings, inserted before startup to override the defaults
+/// (the dedicated server reads them from server.toml)
+#[derive(Resource, Debug, Clone, Copy, PartialEq)]
+pub struct WorldSettings {
+    pub seed: u32,
+    /// Radius of the sphere of chunks loaded around each player
+    pub view_distance: i32,
+    /// How many chunks above and below each player are loaded
+    pub view_distance_vertical: i32,
+}
+
+impl Default for WorldSettings {
+    fn default() -> Self {
+        Self {
+            seed: 42,
+            view_distance: VIEW_DISTANCE,
+            view_distance_vertical: VIEW_DISTANCE_VERTICAL,
+        }
+    }
+}
+
+impl WorldSettings {
+    /// Whether the chunk at `coord` is in view of a player standing in chunk `center`,
+    /// with the view widened by `margin` chunks
+    pub fn in_view(&self, center: ChunkCoord, coord: ChunkCoord, margin: i32) -> bool {
+        let distance = self.view_distance + margin;
+        coord.distance_squared(&center) <= distance * distance
+            && (coord.y - center.y).abs() <= self.view_distance_vertical + margin
+    }
+}
+
+/// Present when chunks are drawn: chunk tasks then also build a first mesh off the main thread
+/// The headless server leaves it out and never meshes
+#[derive(Resource, Default)]
+pub struct ChunkMeshing;
+
+/// A chunk being generated (or restored from `SavedChunks`, or imported) off the main thread
 #[derive(Component)]
-pub struct ChunkTask(Task<(ChunkCoord, Chunk, Option<Mesh>)>);
+pub struct ChunkTask {
+    task: Task<(ChunkCoord, Chunk, Option<Mesh>)>,
+    /// Blocks came from `SavedChunks`, so they've already had their placement callbacks
+    restored: bool,
+}
+
+/// Mesh built with a chunk, waiting for `attach_chunk_meshes` to give it a material
+#[derive(Component)]
+pub struct PendingChunkMesh {
+    mesh: Option<Mesh>,
+}
 
 /// Marker component for terrain chunk meshes
 #[derive(Component)]
 pub struct TerrainChunk;
 
-pub fn setup_terrain(mut commands: Commands) {
-    commands.init_resource::<ChunkManager>();
+pub fn setup_terrain(mut commands: Commands, settings: Res<WorldSettings>, import: Option<Res<AnvilImport>>) {
+    let chunk_manager = ChunkManager { world_seed: settings.seed, ..default() };
+    let spawn_point = match import {
+        Some(import) => SpawnPoint { block: import.spawn_block() },
+        None => SpawnPoint::find(chunk_manager.world_seed),
+    };
+    commands.insert_resource(spawn_point);
+    commands.insert_resource(chunk_manager);
+    commands.insert_resource(WorldRng::from_seed(settings.seed));
 }
 
+/// Start loading the chunks within view distance of every player
+/// With an `AnvilImport`, chunks come from the Minecraft world instead of the generator
+#[allow(clippy::too_many_arguments)]
 pub fn spawn_chunks_around_player(
     mut commands: Commands,
     mut chunk_manager: ResMut<ChunkManager>,
+    mut saved: ResMut<SavedChunks>,
     block_registry: Res<BlockRegistry>,
-    camera_query: Query<&Transform, With<CameraController>>,
+    settings: Res<WorldSettings>,
+    meshing: Option<Res<ChunkMeshing>>,
+    import: Option<Res<AnvilImport>>,
+    mut anvil: Local<Option<Arc<AnvilWorld>>>,
+    mut shared_registry: Local<Option<Arc<BlockRegistry>>>,
+    player_query: Query<&Transform, With<Player>>,
 ) {
-    let Ok(camera_transform) = camera_query.get_single() else {
-        return;
-    };
-
-    let player_chunk = ChunkCoord::from_world_pos(camera_transform.translation);
     let task_pool = AsyncComputeTaskPool::get();
-
-    // Get all chunks in view distance (3D sphere)
-    for dx in -VIEW_DISTANCE..=VIEW_DISTANCE {
-        for dy in -VIEW_DISTANCE_VERTICAL..=VIEW_DISTANCE_VERTICAL {
-            for dz in -VIEW_DISTANCE..=VIEW_DISTANCE {
-                let chunk_coord = ChunkCoord::new(
-                    player_chunk.x + dx,
-                    player_chunk.y + dy,
-                    player_chunk.z + dz
-                );
-
-                // Check if within spherical view distance
-                if chunk_coord.distance_squared(&player_chunk) > VIEW_DISTANCE * VIEW_DISTANCE {
-                    continue;
-                }
-
-                // Skip if already loaded or loading
-                if chunk_manager.loaded_chunks.contains_key(&chunk_coord)
-                    || chunk_manager.loading_chunks.contains(&chunk_coord)
-                {
-                    continue;
+    // The mapping is resolved once the mods have registered their blocks
+    if let (None, Some(import)) = (anvil.as_ref(), import) {
+        *anvil = Some(Arc::new(AnvilWorld::new(&import, &block_registry)));
+    }
+    let view_distance = settings.view_distance;
+    let view_distance_vertical = settings.view_distance_vertical;
+    // Share the registry with the async tasks by wrapping in Arc (lighting needs it, and
+    // meshing too), cloned again only when mods change it
+    if block_registry.is_changed() {
+        *shared_registry = None;
+    }
+    let registry_arc = shared_registry.get_or_insert_with(|| Arc::new(block_registry.clone())).clone();
+    let meshing = meshing.is_some();
+
+    for player_transform in player_query.iter() {
+        let player_chunk = ChunkCoord::from_world_pos(player_transform.translation);
+
+        // Get all chunks in view distance (3D sphere)
+        for dx in -view_distance..=view_distance {
+            for dy in -view_distance_vertical..=view_distance_vertical {
+                for dz in -view_distance..=view_distance {
+                    let chunk_coord = ChunkCoord::new(
+                        player_chunk.x + dx,
+                        player_chunk.y + dy,
+                        player_chunk.z + dz
+                    );
+
+                    // Check if within spherical view distance
+                    if !settings.in_view(player_chunk, chunk_coord, 0) {
+                        continue;
+                    }
+
+                    // Skip if already loaded or loading
+                    if chunk_manager.loaded_chunks.contains_key(&chunk_coord)
+                        || chunk_manager.loading_chunks.contains(&chunk_coord)
+                    {
+                        continue;
+                    }
+
+                    // Mark as loading
+                    chunk_manager.loading_chunks.insert(chunk_coord);
+
+                    // Spawn async task to generate chunk
+                    let seed = chunk_manager.world_seed;
+
+                    // Get block IDs we need for terrain generation
+                    let blocks = TerrainBlocks::from_registry(&block_registry);
+                    let registry_arc = registry_arc.clone();
+                    let anvil = anvil.clone();
+
+                    // Chunks that were edited before they unloaded come back as they were left
+                    let saved_chunk = saved.take_chunk(chunk_coord);
+                    let restored = saved_chunk.is_some();
+                    let task = task_pool.spawn(async move {
+                        let chunk = match (saved_chunk, anvil) {
+                            (Some(chunk), _) => chunk,
+                            (None, Some(anvil)) => anvil.chunk(chunk_coord, &registry_arc),
+                            (None, None) => generate_chunk(chunk_coord, seed, blocks, &registry_arc),
+                        };
+                        let mesh = if meshing { create_chunk_mesh(&chunk, &registry_arc) } else { None };
+                        (chunk_coord, chunk, mesh)
+                    });
+
+                    commands.spawn(ChunkTask { task, restored });
                 }
-
-                // Mark as loading
-                chunk_manager.loading_chunks.insert(chunk_coord);
-
-                // Spawn async task to generate chunk
-                let seed = chunk_manager.world_seed;
-
-                // Get block IDs we need for terrain generation
-                let grass_id = block_registry.get_id("core:grass").unwrap_or(BlockId::AIR);
-                let dirt_id = block_registry.get_id("core:dirt").unwrap_or(BlockId::AIR);
-                let stone_id = block_registry.get_id("core:stone").unwrap_or(BlockId::AIR);
-                let bedrock_id = block_registry.get_id("core:bedrock").unwrap_or(BlockId::AIR);
-
-                // Share the registry with the async task by wrapping in Arc
-                // This eliminates duplicate registrations
-                let registry_arc = Arc::new(block_registry.clone());
-
-                let task = task_pool.spawn(async move {
-                    generate_chunk(chunk_coord, seed, grass_id, dirt_id, stone_id, bedrock_id, &registry_arc)
-                });
-
-                commands.spawn(ChunkTask(task));
             }
         }
     }
 }
 
+/// Turn finished chunk tasks into chunk entities and register them as loaded
 pub fn process_chunk_tasks(
     mut commands: Commands,
     mut chunk_manager: ResMut<ChunkManager>,
+    mut saved: ResMut<SavedChunks>,
+    mut spawn_mobs: EventWriter<SpawnMob>,
     mut chunk_tasks: Query<(Entity, &mut ChunkTask)>,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<TerrainMaterial>>,
-    asset_manager: Res<AssetManager>,
-    mut texture_handle: Local<Option<Handle<Image>>>,
 ) {
-    // Get texture atlas from AssetManager for the core mod
-    if texture_handle.is_none() {
-        *texture_handle = asset_manager.get_mod_texture_atlas("core");
-    }
-
     for (entity, mut task) in chunk_tasks.iter_mut() {
-        if let Some((coord, chunk, mesh_opt)) = future::block_on(future::poll_once(&mut task.0)) {
+        if let Some((coord, chunk, mesh_opt)) = future::block_on(future::poll_once(&mut task.task)) {
             // Remove from loading set
             chunk_manager.loading_chunks.remove(&coord);
 
@@ -126,22 +204,9 @@ pub fn process_chunk_tasks(
                 chunk,
             ));
 
-            // Only add mesh components if we have a mesh
+            // The mesh (if the task built one) is uploaded by `attach_chunk_meshes`
             if let Some(mesh) = mesh_opt {
-                let mesh_handle = meshes.add(mesh);
-                // Use custom TerrainMaterial with overlay support
-                let material_handle = if let Some(ref tex) = *texture_handle {
-                    materials.add(TerrainMaterial::new(tex.clone()))
-                } else {
-                    // Fallback - shouldn't happen
-                    materials.add(TerrainMaterial::new(Handle::default()))
-                };
-
-                chunk_entity_builder.insert((
-                    Mesh3d(mesh_handle),
-                    MeshMaterial3d(material_handle),
-                    TerrainChunk,
-                ));
+                chunk_entity_builder.insert(PendingChunkMesh { mesh: Some(mesh) });
             }
 
             let chunk_entity = chunk_entity_builder.id();
@@ -149,10 +214,21 @@ pub fn process_chunk_tasks(
             // Register in chunk manager
             chunk_manager.loaded_chunks.insert(coord, chunk_entity);
 
-            // Mark this chunk for remeshing to incorporate neighbor data
-            commands.entity(chunk_entity).insert(crate::systems::NeedsRemesh);
+            // Mark this chunk for relighting and remeshing to incorporate neighbor data,
+            // and fresh chunks for post-processing so their blocks get their placement callbacks
+            commands.entity(chunk_entity).insert((crate::systems::NeedsRelight, crate::systems::NeedsRemesh));
+            if task.restored {
+                commands.entity(chunk_entity).insert(super::saved::ChunkModified);
+            } else {
+                commands.entity(chunk_entity).insert(crate::systems::NeedsPostProcess);
+            }
 
-            // Mark neighboring chunks for remeshing too, since they now have a new neighbor
+            // Mobs saved with the chunk come back now that there's ground under them
+            for mob in saved.take_mobs(coord) {
+                spawn_mobs.send(SpawnMob(mob));
+            }
+
+            // Mark neighboring chunks for relighting and remeshing too, since they now have a new neighbor
             // This ensures faces at chunk boundaries are rendered correctly
             let neighbor_coords = [
                 ChunkCoord::new(coord.x - 1, coord.y, coord.z),
@@ -165,7 +241,7 @@ pub fn process_chunk_tasks(
 
             for neighbor_coord in neighbor_coords.iter() {
                 if let Some(&neighbor_entity) = chunk_manager.loaded_chunks.get(neighbor_coord) {
-                    commands.entity(neighbor_entity).insert(crate::systems::NeedsRemesh);
+                    commands.entity(neighbor_entity).insert((crate::systems::NeedsRelight, crate::systems::NeedsRemesh));
                 }
             }
 
@@ -175,16 +251,41 @@ pub fn process_chunk_tasks(
     }
 }
 
-/// Calculate a safe spawn height for the given world position
-/// Returns the camera Y coordinate (player eye level) for spawning above terrain
-pub fn get_spawn_height(world_x: i32, world_z: i32, seed: u32) -> f32 {
-    let simplex = Simplex::new(seed);
-    let terrain_height = get_terrain_height(&simplex, world_x, world_z);
-    // terrain_height is the Y level of the top solid block (e.g., grass at Y=36)
-    // Player feet at terrain_height + 1.5 (standing on top of surface block with clearance)
-    // Camera at eye level = feet + 1.6
-    // So camera Y = terrain_height + 1.5 + 1.6 = terrain_height + 3.1
-    (terrain_height as f32) + 3.1
+/// Upload the meshes chunk tasks built and give the chunks their terrain material
+pub fn attach_chunk_meshes(
+    mut commands: Commands,
+    mut pending: Query<(Entity, &mut PendingChunkMesh)>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<TerrainMaterial>>,
+    asset_manager: Res<AssetManager>,
+    mut texture_handle: Local<Option<Handle<Image>>>,
+) {
+    // Get texture atlas from AssetManager for the core mod
+    if texture_handle.is_none() {
+        *texture_handle = asset_manager.get_mod_texture_atlas("core");
+    }
+
+    for (entity, mut pending_mesh) in pending.iter_mut() {
+        let Some(mesh) = pending_mesh.mesh.take() else {
+            continue;
+        };
+        let mesh_handle = meshes.add(mesh);
+        // Use custom TerrainMaterial with overlay support
+        let material_handle = if let Some(ref tex) = *texture_handle {
+            materials.add(TerrainMaterial::new(tex.clone()))
+        } else {
+            // Fallback - shouldn't happen
+            materials.add(TerrainMaterial::new(Handle::default()))
+        };
+
+        commands.entity(entity)
+            .remove::<PendingChunkMesh>()
+            .insert((
+                Mesh3d(mesh_handle),
+                MeshMaterial3d(material_handle),
+                TerrainChunk,
+            ));
+    }
 }
 
 /// Generate terrain height using multi-octave Simplex noise (fractal Brownian motion)
@@ -221,20 +322,61 @@ pub fn get_terrain_height(simplex: &Simplex, world_x: i32, world_z: i32) -> i32
     height as i32
 }
 
+/// Block IDs the terrain generator places, looked up once per chunk
+#[derive(Clone, Copy)]
+struct TerrainBlocks {
+    grass: BlockId,
+    dirt: BlockId,
+    stone: BlockId,
+    bedrock: BlockId,
+    coal_ore: BlockId,
+    iron_ore: BlockId,
+    diamond_ore: BlockId,
+}
+
+impl TerrainBlocks {
+    fn from_registry(registry: &BlockRegistry) -> Self {
+        let id = |name: &str| registry.get_id(name).unwrap_or(BlockId::AIR);
+        Self {
+            grass: id("core:grass"),
+            dirt: id("core:dirt"),
+            stone: id("core:stone"),
+            bedrock: id("core:bedrock"),
+            coal_ore: id("core:coal_ore"),
+            iron_ore: id("core:iron_ore"),
+            diamond_ore: id("core:diamond_ore"),
+        }
+    }
+}
+
+/// Ore veins: each ore has its own 3D noise field and shows up where the field peaks,
+/// rarer ores only below a maximum height
+/// (noise seed offset, noise scale in blocks, threshold, highest world Y)
+const ORE_VEINS: [(u32, f64, f64, i32); 3] = [
+    (1, 5.0, 0.72, i32::MAX), // Coal
+    (2, 4.0, 0.78, 48),       // Iron
+    (3, 3.0, 0.86, 16),       // Diamond
+];
+
 fn generate_chunk(
     coord: ChunkCoord,
     seed: u32,
-    grass_id: BlockId,
-    dirt_id: BlockId,
-    stone_id: BlockId,
-    bedrock_id: BlockId,
-    block_registry: &Arc<BlockRegistry>,
-) -> (ChunkCoord, Chunk, Option<Mesh>) {
+    blocks: TerrainBlocks,
+    registry: &BlockRegistry,
+) -> Chunk {
     let mut chunk = Chunk::new(coord);
 
     // Use Simplex noise for terrain generation
     // Simplex is better than Perlin: smoother gradients, no directional artifacts, faster
     let simplex = Simplex::new(seed);
+    let ore_noise = ORE_VEINS.map(|(offset, _, _, _)| Simplex::new(seed.wrapping_add(offset)));
+    let ore_ids = [blocks.coal_ore, blocks.iron_ore, blocks.diamond_ore];
+    let ore_at = |world_x: i32, world_y: i32, world_z: i32| {
+        ORE_VEINS.iter().zip(&ore_noise).zip(ore_ids).find_map(|((&(_, scale, threshold, max_y), noise), id)| {
+            let point = [world_x as f64 / scale, world_y as f64 / scale, world_z as f64 / scale];
+            (world_y <= max_y && !id.is_air() && noise.get(point) > threshold).then_some(id)
+        })
+    };
 
     // Calculate world position of this chunk
     let chunk_world_x = coord.x * CHUNK_SIZE as i32;
@@ -262,19 +404,19 @@ fn generate_chunk(
                     BlockId::AIR
                 } else if world_y == 0 {
                     // Bedrock at y=0
-                    bedrock_id
+                    blocks.bedrock
                 } else if world_y > terrain_height {
                     // Air above terrain
                     BlockId::AIR
                 } else if depth_from_surface == 0 {
                     // Surface block - grass
-                    grass_id
+                    blocks.grass
                 } else if depth_from_surface <= 3 {
                     // Top 3 blocks below surface - dirt
-                    dirt_id
+                    blocks.dirt
                 } else {
-                    // Everything else underground - stone
-                    stone_id
+                    // Everything else underground - stone, with ore veins
+                    ore_at(world_x, world_y, world_z).unwrap_or(blocks.stone)
                 };
 
                 chunk.set_block(x, y, z, block_id);
@@ -282,11 +424,9 @@ fn generate_chunk(
         }
     }
 
-    // Calculate skylight propagation for this chunk
-    chunk.calculate_skylight();
-
-    // Generate mesh for this chunk
-    let mesh = create_chunk_mesh(&chunk, block_registry);
+    // Calculate skylight propagation for this chunk (all-air and solid chunks skip it)
+    chunk.compact();
+    chunk.calculate_skylight(registry);
 
-    (coord, chunk, mesh)
+    chunk
 }
@@ -0,0 +1,126 @@
+use bevy::prelude::*;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashSet};
+
+/// Fixed simulation rate for world ticks (Minecraft server rate)
+pub const TICKS_PER_SECOND: f64 = 20.0;
+
+/// Number of random blocks picked per loaded chunk section each tick
+/// Matches Minecraft's default `randomTickSpeed` gamerule
+pub const RANDOM_TICKS_PER_SECTION: usize = 3;
+
+/// Monotonic world tick counter, advanced once per fixed tick
+#[derive(Resource, Default)]
+pub struct WorldTick {
+    pub tick: u64,
+}
+
+/// A block tick that has been scheduled to run at a future world tick
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ScheduledTick {
+    /// World tick this entry becomes due
+    pub due_tick: u64,
+    /// Insertion order - keeps ticks scheduled for the same tick in FIFO order
+    pub sequence: u64,
+    /// World position of the block to tick
+    pub pos: (i32, i32, i32),
+}
+
+/// Priority queue of pending scheduled block ticks (earliest first)
+#[derive(Resource, Default)]
+pub struct ScheduledTicks {
+    queue: BinaryHeap<Reverse<ScheduledTick>>,
+    /// Positions already queued, so the same block is never scheduled twice
+    pending: HashSet<IVec3>,
+    next_sequence: u64,
+}
+
+impl ScheduledTicks {
+    /// Schedule a tick for the block at `pos`, `delay` ticks after `now`
+    /// Returns false if the block already has a pending tick
+    pub fn schedule(&mut self, pos: IVec3, now: u64, delay: u64) -> bool {
+        if !self.pending.insert(pos) {
+            return false;
+        }
+
+        let entry = ScheduledTick {
+            due_tick: now + delay.max(1),
+            sequence: self.next_sequence,
+            pos: (pos.x, pos.y, pos.z),
+        };
+        self.next_sequence += 1;
+        self.queue.push(Reverse(entry));
+        true
+    }
+
+    /// Pop the next tick that is due at or before `now`
+    pub fn pop_due(&mut self, now: u64) -> Option<IVec3> {
+        let Reverse(next) = self.queue.peek()?;
+        if next.due_tick > now {
+            return None;
+        }
+
+        let Reverse(entry) = self.queue.pop()?;
+        let pos = IVec3::new(entry.pos.0, entry.pos.1, entry.pos.2);
+        self.pending.remove(&pos);
+        Some(pos)
+    }
+
+    pub fn len(&self) -> usize {
+        self.queue.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+}
+
+/// Deterministic random source for world simulation (random ticks, block behaviors)
+/// Inserted by `setup_terrain`, seeded from the world seed so each world ticks differently
+/// but the same world ticks the same every time
+#[derive(Resource)]
+pub struct WorldRng(pub StdRng);
+
+impl WorldRng {
+    pub fn from_seed(seed: u32) -> Self {
+        Self(StdRng::seed_from_u64(seed as u64))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::RngCore;
+
+    #[test]
+    fn test_scheduled_ticks_run_in_order() {
+        let mut ticks = ScheduledTicks::default();
+        ticks.schedule(IVec3::new(0, 0, 0), 0, 5);
+        ticks.schedule(IVec3::new(1, 0, 0), 0, 2);
+        ticks.schedule(IVec3::new(2, 0, 0), 0, 2);
+
+        assert_eq!(ticks.pop_due(1), None);
+        assert_eq!(ticks.pop_due(2), Some(IVec3::new(1, 0, 0)));
+        assert_eq!(ticks.pop_due(2), Some(IVec3::new(2, 0, 0)));
+        assert_eq!(ticks.pop_due(4), None);
+        assert_eq!(ticks.pop_due(5), Some(IVec3::new(0, 0, 0)));
+        assert!(ticks.is_empty());
+    }
+
+    #[test]
+    fn test_world_rng_follows_the_world_seed() {
+        let first = |seed| WorldRng::from_seed(seed).0.next_u64();
+        assert_eq!(first(7), first(7));
+        assert_ne!(first(7), first(8));
+    }
+
+    #[test]
+    fn test_duplicate_schedule_is_ignored() {
+        let mut ticks = ScheduledTicks::default();
+        assert!(ticks.schedule(IVec3::ZERO, 0, 1));
+        assert!(!ticks.schedule(IVec3::ZERO, 0, 3));
+        assert_eq!(ticks.len(), 1);
+    }
+}
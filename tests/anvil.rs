@@ -0,0 +1,139 @@
+use bevy::prelude::*;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use voxelcraft::blocks::{BlockId, BlockRegistry};
+use voxelcraft::components::Player;
+use voxelcraft::world::nbt::{self, Tag};
+use voxelcraft::world::{pack_indices, AnvilImport, Chunk, ChunkCoord, ChunkManager, Compression, RegionFile, SpawnPoint, WorldSettings};
+use voxelcraft::SimulationPlugin;
+
+/// 4096 palette indices `bits` wide as Minecraft stores them: whole indices per long from
+/// 1.16 on, running on across longs before
+fn pack(indices: &[u16], bits: usize, spanning: bool) -> Tag {
+    Tag::LongArray(pack_indices(indices, bits, spanning))
+}
+
+fn palette(names: &[&str]) -> Tag {
+    Tag::List(names.iter().map(|name| Tag::compound([("Name", Tag::String(name.to_string()))])).collect())
+}
+
+/// Index of a block in a section: x fastest, then z, then y
+fn index(x: usize, y: usize, z: usize) -> usize {
+    x + z * 16 + y * 256
+}
+
+/// A Minecraft world with two columns side by side:
+/// - (0, 0) saved by 1.20: a stone section below 0 and a floor of bedrock, stone and grass,
+///   with an oak log and a block the mapping doesn't know standing on it
+/// - (1, 0) saved by 1.15: a stone floor 5 blocks deep, its palette wide enough for 5-bit
+///   indices that run on across longs
+fn write_world(directory: &Path) {
+    std::fs::create_dir_all(directory.join("region")).unwrap();
+
+    let mut floor = vec![0u16; 4096];
+    for z in 0..16 {
+        for x in 0..16 {
+            for (y, block) in [1, 2, 2, 2, 3].into_iter().enumerate() {
+                floor[index(x, y, z)] = block;
+            }
+        }
+    }
+    floor[index(3, 5, 3)] = 4;
+    floor[index(5, 5, 5)] = 5;
+    let mut log = palette(&["minecraft:air", "minecraft:bedrock", "minecraft:stone", "minecraft:grass_block", "minecraft:strange_block", "minecraft:oak_log"]);
+    if let Tag::List(entries) = &mut log {
+        entries[5] = Tag::compound([
+            ("Name", Tag::String("minecraft:oak_log".to_string())),
+            ("Properties", Tag::compound([("axis", Tag::String("x".to_string()))])),
+        ]);
+    }
+    let modern = Tag::compound([
+        ("DataVersion", Tag::Int(3465)),
+        ("sections", Tag::List(vec![
+            Tag::compound([("Y", Tag::Byte(-1)), ("block_states", Tag::compound([("palette", palette(&["minecraft:stone"]))]))]),
+            Tag::compound([("Y", Tag::Byte(0)), ("block_states", Tag::compound([("palette", log), ("data", pack(&floor, 4, false))]))]),
+        ])),
+    ]);
+
+    let mut names = vec!["minecraft:air"];
+    names.extend(["minecraft:stone"; 16]);
+    let stone_floor: Vec<u16> = (0..4096).map(|block| if block / 256 < 5 { 1 + (block % 16) as u16 } else { 0 }).collect();
+    let legacy = Tag::compound([
+        ("DataVersion", Tag::Int(2230)),
+        ("Level", Tag::compound([("Sections", Tag::List(vec![
+            // Only light, as Minecraft keeps above the top of the world
+            Tag::compound([("Y", Tag::Byte(-1)), ("SkyLight", Tag::ByteArray(vec![0; 2048]))]),
+            Tag::compound([("Y", Tag::Byte(0)), ("Palette", palette(&names)), ("BlockStates", pack(&stone_floor, 5, true))]),
+        ]))])),
+    ]);
+
+    RegionFile::write(&directory.join("region/r.0.0.mca"), &[((0, 0), modern), ((1, 0), legacy)], Compression::Zlib).unwrap();
+
+    let level = Tag::compound([("Data", Tag::compound([("SpawnX", Tag::Int(8)), ("SpawnY", Tag::Int(5)), ("SpawnZ", Tag::Int(8))]))]);
+    std::fs::write(directory.join("level.dat"), Compression::Gzip.compress(&nbt::write(&level))).unwrap();
+}
+
+fn chunk(app: &App, coord: ChunkCoord) -> Option<Chunk> {
+    let entity = *app.world().resource::<ChunkManager>().loaded_chunks.get(&coord)?;
+    app.world().get::<Chunk>(entity).cloned()
+}
+
+fn id(app: &App, name: &str) -> BlockId {
+    app.world().resource::<BlockRegistry>().get_id(name).unwrap()
+}
+
+#[test]
+fn minecraft_columns_load_as_cubic_chunks() {
+    let directory: PathBuf = std::env::temp_dir().join(format!("voxelcraft-anvil-test-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&directory);
+    write_world(&directory);
+
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .insert_resource(AnvilImport::new(&directory))
+        .insert_resource(WorldSettings { view_distance: 2, view_distance_vertical: 1, ..default() })
+        .add_plugins(SimulationPlugin);
+    app.update();
+    let spawn = *app.world().resource::<SpawnPoint>();
+    assert_eq!(spawn.block, IVec3::new(8, 5, 8), "spawn comes from level.dat");
+    app.world_mut().spawn((Transform::from_translation(spawn.feet_position()), Player::default()));
+
+    let wanted = [(0, 0, 0), (0, -1, 0), (0, 1, 0), (1, 0, 0), (2, 0, 0)].map(|(x, y, z)| ChunkCoord::new(x, y, z));
+    let deadline = Instant::now() + Duration::from_secs(60);
+    while wanted.iter().any(|&coord| chunk(&app, coord).is_none()) {
+        assert!(Instant::now() < deadline, "the imported chunks never loaded");
+        app.update();
+        std::thread::sleep(Duration::from_millis(5));
+    }
+
+    let (stone, grass) = (id(&app, "core:stone"), id(&app, "core:grass"));
+    let floor = chunk(&app, ChunkCoord::new(0, 0, 0)).unwrap();
+    assert_eq!(floor.get_block(0, 0, 0), id(&app, "core:bedrock"));
+    assert_eq!(floor.get_block(9, 2, 4), stone);
+    assert_eq!(floor.get_block(15, 4, 15), grass);
+    assert_eq!(floor.get_block(5, 5, 5), id(&app, "core:log"), "properties are ignored");
+    assert_eq!(floor.get_block(3, 5, 3), id(&app, "core:unknown"), "unmapped blocks become placeholders");
+    assert!(floor.get_block(8, 5, 8).is_air());
+    assert_eq!(floor.get_light(8, 15, 8), 15, "imported chunks are lit");
+
+    let below = chunk(&app, ChunkCoord::new(0, -1, 0)).unwrap();
+    assert!((0..16).all(|y| below.get_block(7, y, 7) == stone), "single-entry palettes fill the section");
+    assert_eq!(below.blocks.bits_per_block(), 0, "uniform sections are stored as one state");
+    assert_eq!(below.light_levels.uniform(), Some(0));
+    let above = chunk(&app, ChunkCoord::new(0, 1, 0)).unwrap();
+    assert!(above.get_block(8, 0, 8).is_air());
+    assert_eq!(above.uniform_state().map(|state| state.is_air()), Some(true));
+    assert_eq!(floor.blocks.bits_per_block(), 4, "six states fit a 4-bit palette");
+
+    let legacy = chunk(&app, ChunkCoord::new(1, 0, 0)).unwrap();
+    assert!((0..16).all(|x| legacy.get_block(x, 4, 3) == stone && legacy.get_block(x, 5, 3).is_air()));
+
+    // Past the edge of the map there's nothing, rather than generated terrain
+    let outside = chunk(&app, ChunkCoord::new(2, 0, 0)).unwrap();
+    assert!((0..16).all(|y| outside.get_block(8, y, 8).is_air()));
+
+    // Without level.dat, players spawn on top of the column at the origin
+    std::fs::remove_file(directory.join("level.dat")).unwrap();
+    assert_eq!(AnvilImport::new(&directory).spawn_block(), IVec3::new(0, 5, 0));
+    std::fs::remove_dir_all(&directory).unwrap();
+}
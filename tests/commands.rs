@@ -0,0 +1,322 @@
+use bevy::prelude::*;
+use std::time::{Duration, Instant};
+use voxelcraft::blocks::{BlockRegistry, BlockState};
+use voxelcraft::commands::{CommandFeedback, RunCommand};
+use voxelcraft::crafting::Furnace;
+use voxelcraft::components::{GameMode, Player, PLAYER_HALF_WIDTH, PLAYER_HEIGHT, PLAYER_STEP_HEIGHT};
+use voxelcraft::items::{new_stack, ItemRegistry};
+use voxelcraft::net::RemotePlayer;
+use voxelcraft::physics::PhysicsBody;
+use voxelcraft::resources::{HotbarItem, PlayerInventory};
+use voxelcraft::systems::{BlockAction, BlockEditRequest, ItemDrop, TimeOfDay};
+use voxelcraft::world::{world_to_chunk_local, BlockEntity, Chunk, ChunkManager, Clipboard, EditHistory, SavedChunks, SchematicDirectory, Selection, SpawnPoint, WorldSettings};
+use voxelcraft::SimulationPlugin;
+
+/// A headless world with a survival player standing at the spawn point, once the
+/// chunks around them have loaded
+fn world_with_player() -> (App, Entity) {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .insert_resource(WorldSettings { view_distance: 2, view_distance_vertical: 1, ..default() })
+        .add_plugins(SimulationPlugin);
+    app.update();
+    let spawn = app.world().resource::<SpawnPoint>().feet_position();
+    let player = app.world_mut().spawn((
+        Transform::from_translation(spawn),
+        Player::default(),
+        GameMode::Survival,
+        EditHistory::default(),
+        Selection::default(),
+        Clipboard::default(),
+        PhysicsBody::new(spawn, PLAYER_HALF_WIDTH, PLAYER_HEIGHT).with_step_height(PLAYER_STEP_HEIGHT),
+    )).id();
+
+    let area = spawn.floor().as_ivec3();
+    let deadline = Instant::now() + Duration::from_secs(60);
+    while (-8..=8).any(|x| (-8..=8).any(|z| block(&mut app, area + IVec3::new(x, 8, z)).is_none())) {
+        assert!(Instant::now() < deadline, "the chunks around the spawn point never loaded");
+        app.update();
+        std::thread::sleep(Duration::from_millis(5));
+    }
+    (app, player)
+}
+
+/// Update until `done`, failing after a minute
+fn wait_until(app: &mut App, what: &str, mut done: impl FnMut(&mut App) -> bool) {
+    let deadline = Instant::now() + Duration::from_secs(60);
+    while !done(app) {
+        assert!(Instant::now() < deadline, "{} never happened", what);
+        app.update();
+        std::thread::sleep(Duration::from_millis(5));
+    }
+}
+
+fn run(app: &mut App, player: Entity, command: &str) -> CommandFeedback {
+    app.world_mut().send_event(RunCommand { player, command: command.to_string() });
+    app.update();
+    // Events stay around for a couple of updates, so the newest is this command's
+    let events = app.world().resource::<Events<CommandFeedback>>();
+    events.iter_current_update_events().last().cloned().expect("the command said nothing back")
+}
+
+fn run_ok(app: &mut App, player: Entity, command: &str) -> String {
+    let feedback = run(app, player, command);
+    assert!(!feedback.error, "/{} failed: {}", command, feedback.message);
+    feedback.message
+}
+
+fn block(app: &mut App, pos: IVec3) -> Option<BlockState> {
+    let (coord, local) = world_to_chunk_local(pos);
+    let entity = *app.world().resource::<ChunkManager>().loaded_chunks.get(&coord)?;
+    let chunk = app.world().get::<Chunk>(entity)?;
+    Some(chunk.get_state(local.x as usize, local.y as usize, local.z as usize))
+}
+
+fn block_id(app: &App, id: &str) -> BlockState {
+    BlockState::new(app.world().resource::<BlockRegistry>().get_id(id).unwrap())
+}
+
+#[test]
+fn region_commands_fill_and_copy_blocks() {
+    let (mut app, player) = world_with_player();
+    let stone = block_id(&app, "core:stone");
+    let dirt = block_id(&app, "core:dirt");
+    let base = app.world().resource::<SpawnPoint>().block + IVec3::new(-3, 2, -3);
+    let at = |offset: IVec3| base + offset;
+    let coords = |pos: IVec3| format!("{} {} {}", pos.x, pos.y, pos.z);
+    let to = at(IVec3::new(4, 4, 4));
+
+    // Clear the space above the spawn point, whatever the terrain put there
+    run(&mut app, player, &format!("fill {} {} air", coords(at(IVec3::new(-1, 0, -1))), coords(at(IVec3::new(8, 5, 8)))));
+
+    // A hollow box has walls of the block and air inside
+    assert_eq!(run_ok(&mut app, player, &format!("fill {} {} stone hollow", coords(base), coords(to))), "Successfully filled 98 block(s)");
+    assert_eq!(block(&mut app, at(IVec3::new(0, 2, 2))), Some(stone));
+    assert_eq!(block(&mut app, at(IVec3::new(2, 2, 2))), Some(BlockState::AIR));
+
+    // Replacing with a filter only touches blocks of that type, the outline leaves the inside alone
+    run_ok(&mut app, player, &format!("setblock {} dirt", coords(at(IVec3::new(2, 2, 2)))));
+    assert_eq!(run_ok(&mut app, player, &format!("fill {} {} dirt replace stone", coords(base), coords(to))), "Successfully filled 98 block(s)");
+    assert_eq!(block(&mut app, at(IVec3::new(4, 0, 0))), Some(dirt));
+    assert_eq!(run_ok(&mut app, player, &format!("fill {} {} stone outline", coords(base), coords(to))), "Successfully filled 98 block(s)");
+    assert_eq!(block(&mut app, at(IVec3::new(2, 2, 2))), Some(dirt));
+    assert_eq!(block(&mut app, at(IVec3::new(2, 2, 1))), Some(BlockState::AIR));
+
+    // A masked clone of the box's middle layer copies the solid blocks only
+    let destination = at(IVec3::new(0, 5, 0));
+    run_ok(&mut app, player, &format!("fill {} {} dirt", coords(destination), coords(destination + IVec3::new(4, 0, 4))));
+    assert_eq!(
+        run_ok(&mut app, player, &format!("clone {} {} {} masked", coords(at(IVec3::new(0, 2, 0))), coords(at(IVec3::new(4, 2, 4))), coords(destination))),
+        "Successfully cloned 16 block(s)",
+    );
+    assert_eq!(block(&mut app, destination), Some(stone));
+    assert_eq!(block(&mut app, destination + IVec3::new(1, 0, 1)), Some(dirt));
+
+    // Keep only sets air, and nothing is edited outside the loaded world or past the size limit
+    assert_eq!(run(&mut app, player, &format!("setblock {} dirt keep", coords(base))).message, "Could not set the block");
+    assert_eq!(run(&mut app, player, "setblock 100000 64 0 dirt").message, "That position is not loaded");
+    let too_big = run(&mut app, player, &format!("fill {} {} stone", coords(base), coords(base + IVec3::splat(40))));
+    assert!(too_big.error);
+    assert_eq!(too_big.message, "Too many blocks in the specified area (maximum 32768, specified 68921)");
+    assert_eq!(run(&mut app, player, "fill -2147483648 0 0 2147483647 0 0 stone").message, "That position is outside the world");
+    assert_eq!(run(&mut app, player, "fill -30000000 0 0 30000000 0 0 stone").message, "Too many blocks in the specified area (maximum 32768, specified 60000001)");
+    assert_eq!(run(&mut app, player, &format!("clone {} {} 29999999 0 0", coords(base), coords(to))).message, "That position is outside the world");
+}
+
+#[test]
+fn player_commands_act_on_whoever_runs_them() {
+    let (mut app, player) = world_with_player();
+
+    assert_eq!(run_ok(&mut app, player, "gamemode creative"), "Set own game mode to Creative Mode");
+    assert_eq!(app.world().get::<GameMode>(player), Some(&GameMode::Creative));
+
+    // Relative coordinates are from the player's feet
+    let start = app.world().get::<PhysicsBody>(player).unwrap().position;
+    run_ok(&mut app, player, "tp ~ ~10 ~");
+    let body = app.world().get::<PhysicsBody>(player).unwrap();
+    assert!((body.position - (start + Vec3::Y * 10.0)).length() < 0.5, "teleported to {:?} from {:?}", body.position, start);
+    // Nowhere past the edge of the world, where chunk coordinates would overflow
+    assert_eq!(run(&mut app, player, "tp 1e30 64 0").message, "That position is outside the world");
+    assert_eq!(run(&mut app, player, "tp ~ ~ ~30000001").message, "That position is outside the world");
+    let body = app.world().get::<PhysicsBody>(player).unwrap();
+    assert!(body.position.xz().distance(start.xz()) < 0.5, "moved to {:?} from {:?}", body.position, start);
+
+    // What doesn't fit in the hotbar drops next to the player
+    *app.world_mut().resource_mut::<PlayerInventory>() = PlayerInventory::default();
+    assert_eq!(run_ok(&mut app, player, "give core:iron_pickaxe 2"), "Gave 2 Iron Pickaxe");
+    assert_eq!(run_ok(&mut app, player, "give dirt 600"), "Gave 600 Dirt");
+    let pickaxe = HotbarItem::Item(app.world().resource::<ItemRegistry>().get_id("core:iron_pickaxe").unwrap());
+    let hotbar = app.world().resource::<PlayerInventory>().hotbar;
+    assert_eq!(hotbar.iter().flatten().filter(|stack| stack.item == pickaxe).count(), 2);
+    assert_eq!(hotbar.iter().flatten().map(|stack| stack.count as u32).sum::<u32>(), 2 + 7 * 64);
+    app.update();
+    let dropped: u32 = app.world_mut().query::<&ItemDrop>().iter(app.world()).map(|drop| drop.stack.count as u32).sum();
+    assert_eq!(dropped, 600 - 7 * 64);
+    assert!(run(&mut app, player, "give core:nothing").message.starts_with("Unknown item 'core:nothing'"));
+
+    run_ok(&mut app, player, "time pause");
+    assert_eq!(run_ok(&mut app, player, "time set noon"), "Set the time to 6000");
+    assert!((app.world().resource::<TimeOfDay>().hour() - 12.0).abs() < 0.01);
+    run_ok(&mut app, player, "time add 20000");
+    assert_eq!(run_ok(&mut app, player, "time query daytime"), "The time is 2000");
+
+    let seed = app.world().resource::<ChunkManager>().world_seed;
+    assert_eq!(run_ok(&mut app, player, "seed"), format!("Seed: [{}]", seed));
+
+    // A server's clients can only change the world once they're opped
+    app.world_mut().entity_mut(player).insert(RemotePlayer { name: "guest".to_string(), operator: false });
+    let refused = run(&mut app, player, "gamemode survival");
+    assert!(refused.error && refused.message.contains("permission"), "{:?}", refused);
+    assert_eq!(app.world().get::<GameMode>(player), Some(&GameMode::Creative));
+    let help = run(&mut app, player, "help").message;
+    assert!(help.lines().all(|line| ["/help", "/undo", "/redo"].iter().any(|command| line.starts_with(command))), "help lists only what they can run: {}", help);
+    app.world_mut().get_mut::<RemotePlayer>(player).unwrap().operator = true;
+    run_ok(&mut app, player, "gamemode survival");
+}
+
+#[test]
+fn edits_undo_and_redo_even_after_their_chunk_unloads() {
+    let (mut app, player) = world_with_player();
+    let stone = block_id(&app, "core:stone");
+    let base = app.world().resource::<SpawnPoint>().block + IVec3::new(2, 2, 2);
+    let coords = |pos: IVec3| format!("{} {} {}", pos.x, pos.y, pos.z);
+    let corner = base + IVec3::new(2, 2, 2);
+
+    assert_eq!(run(&mut app, player, "undo").message, "Undo only works in creative mode");
+    run_ok(&mut app, player, "gamemode creative");
+    assert_eq!(run(&mut app, player, "undo").message, "Nothing to undo");
+
+    // A fill is one transaction, undone back to whatever was there before
+    run_ok(&mut app, player, &format!("fill {} {} air", coords(base), coords(corner)));
+    run_ok(&mut app, player, &format!("fill {} {} stone", coords(base), coords(corner)));
+    assert_eq!(run_ok(&mut app, player, "undo"), "Undid 27 block change(s)");
+    assert_eq!(block(&mut app, corner), Some(BlockState::AIR));
+    assert_eq!(run_ok(&mut app, player, "redo"), "Redid 27 block change(s)");
+    assert_eq!(block(&mut app, corner), Some(stone));
+
+    // So is a click, and a new edit forgets what could be redone
+    app.world_mut().send_event(BlockEditRequest { player, action: BlockAction::Break { pos: base } });
+    app.update();
+    assert_eq!(block(&mut app, base), Some(BlockState::AIR));
+    assert_eq!(run_ok(&mut app, player, "undo"), "Undid 1 block change(s)");
+    assert_eq!(block(&mut app, base), Some(stone));
+    run_ok(&mut app, player, &format!("setblock {} air", coords(corner)));
+    assert_eq!(run(&mut app, player, "redo").message, "Nothing to redo");
+    run_ok(&mut app, player, "undo");
+
+    // Undoing the fill while its chunk is unloaded changes the saved blocks it loads again with
+    let (coord, _) = world_to_chunk_local(corner);
+    run_ok(&mut app, player, "tp ~2000 ~ ~");
+    wait_until(&mut app, "unloading the filled chunk", |app| app.world().resource::<SavedChunks>().contains(coord));
+    assert_eq!(run_ok(&mut app, player, "undo"), "Undid 27 block change(s)");
+    run_ok(&mut app, player, "tp ~-2000 ~ ~");
+    wait_until(&mut app, "loading the filled chunk again", |app| block(app, corner).is_some());
+    assert_eq!(block(&mut app, corner), Some(BlockState::AIR));
+    assert_eq!(run_ok(&mut app, player, "redo"), "Redid 27 block change(s)");
+    assert_eq!(block(&mut app, base), Some(stone));
+
+    assert_eq!(run_ok(&mut app, player, "redo"), "Redid 1 block change(s)");
+    assert_eq!(block(&mut app, corner), Some(BlockState::AIR));
+    assert!(!app.world().get::<EditHistory>(player).unwrap().can_redo());
+
+    // An edit whose blocks someone else changed since can't be undone over them, and is
+    // dropped so the edits before it still can be
+    let planks = block_id(&app, "core:planks");
+    run_ok(&mut app, player, &format!("setblock {} dirt", coords(base)));
+    let other = app.world_mut().spawn((Transform::from_translation(base.as_vec3()), GameMode::Creative)).id();
+    run_ok(&mut app, other, &format!("setblock {} planks", coords(base)));
+    assert_eq!(run(&mut app, player, "undo").message, "1 of those blocks changed since, so the edit can't be undone");
+    assert_eq!(block(&mut app, base), Some(planks));
+    assert_eq!(run_ok(&mut app, player, "undo"), "Undid 1 block change(s)");
+    assert_eq!(block(&mut app, corner), Some(stone));
+
+    // Breaking a furnace drops what was in it, so undoing brings it back empty
+    let furnace_at = base + IVec3::X;
+    run_ok(&mut app, player, &format!("setblock {} furnace", coords(furnace_at)));
+    let ore = HotbarItem::Block(app.world().resource::<BlockRegistry>().get_id("core:iron_ore").unwrap());
+    let input = new_stack(ore, 5, app.world().resource::<ItemRegistry>());
+    let (coord, local) = world_to_chunk_local(furnace_at);
+    let chunk = app.world().resource::<ChunkManager>().loaded_chunks[&coord];
+    let furnace = |app: &App| app.world().get::<Chunk>(chunk).unwrap()
+        .block_entity(local.x as usize, local.y as usize, local.z as usize)
+        .map(|BlockEntity::Furnace(furnace)| furnace.clone());
+    app.world_mut().get_mut::<Chunk>(chunk).unwrap()
+        .set_block_entity(local.x as usize, local.y as usize, local.z as usize, Some(BlockEntity::Furnace(Furnace { input: Some(input), ..default() })));
+    let dropped_ore = |app: &mut App| app.world_mut().query::<&ItemDrop>().iter(app.world())
+        .filter(|drop| drop.stack.item == ore)
+        .map(|drop| drop.stack.count)
+        .sum::<u8>();
+    run_ok(&mut app, player, &format!("setblock {} air", coords(furnace_at)));
+    app.update();
+    assert_eq!(furnace(&app), None);
+    assert_eq!(dropped_ore(&mut app), 5);
+    assert_eq!(run_ok(&mut app, player, "undo"), "Undid 1 block change(s)");
+    assert_eq!(furnace(&app).map(|furnace| furnace.input), Some(None));
+
+    // A server's creative clients undo their own edits without being opped
+    app.world_mut().entity_mut(player).insert(RemotePlayer { name: "guest".to_string(), operator: false });
+    assert_eq!(run_ok(&mut app, player, "redo"), "Redid 1 block change(s)");
+    assert_eq!(run_ok(&mut app, player, "undo"), "Undid 1 block change(s)");
+    app.update();
+    assert_eq!(dropped_ore(&mut app), 5);
+}
+
+#[test]
+fn selections_copy_rotate_paste_and_save_as_schematics() {
+    let (mut app, player) = world_with_player();
+    let directory = std::env::temp_dir().join(format!("voxelcraft-schematics-{}", std::process::id()));
+    app.insert_resource(SchematicDirectory(directory.clone()));
+    let stone = block_id(&app, "core:stone");
+    let dirt = block_id(&app, "core:dirt");
+    let feet = app.world().get::<PhysicsBody>(player).unwrap().position.floor().as_ivec3();
+    let at = |x: i32, y: i32, z: i32| feet + IVec3::new(x, y, z);
+    let coords = |pos: IVec3| format!("{} {} {}", pos.x, pos.y, pos.z);
+
+    run_ok(&mut app, player, "gamemode creative");
+    run(&mut app, player, &format!("fill {} {} air", coords(at(-4, 1, -4)), coords(at(4, 3, 4))));
+    run_ok(&mut app, player, &format!("setblock {} stone", coords(at(2, 1, 0))));
+    run_ok(&mut app, player, &format!("setblock {} dirt", coords(at(3, 1, 0))));
+
+    // The wand marks corners instead of breaking and placing
+    let axe = HotbarItem::Item(app.world().resource::<ItemRegistry>().get_id("core:wooden_axe").unwrap());
+    let wand = new_stack(axe, 1, app.world().resource::<ItemRegistry>());
+    app.world_mut().resource_mut::<PlayerInventory>().hotbar[0] = Some(wand);
+    app.world_mut().resource_mut::<PlayerInventory>().selected_slot = 0;
+    app.world_mut().send_event(BlockEditRequest { player, action: BlockAction::Break { pos: at(2, 1, 0) } });
+    app.update();
+    app.world_mut().send_event(BlockEditRequest { player, action: BlockAction::UseOn { pos: at(3, 2, 1), face: IVec3::Y } });
+    app.update();
+    let said = app.world().resource::<Events<CommandFeedback>>().iter_current_update_events().last().unwrap().message.clone();
+    assert_eq!(said, format!("Second corner set to {} (8 blocks)", coords(at(3, 2, 1)).replace(' ', ", ")));
+    assert_eq!(block(&mut app, at(2, 1, 0)), Some(stone));
+    assert_eq!(block(&mut app, at(3, 2, 0)), Some(BlockState::AIR));
+
+    // A quarter turn clockwise takes east of the player to south of them
+    assert_eq!(run_ok(&mut app, player, "copy"), "Copied 8 block(s)");
+    run_ok(&mut app, player, "rotate 90");
+    assert_eq!(run_ok(&mut app, player, "paste"), "Pasted 2 block(s)");
+    assert_eq!(block(&mut app, at(0, 1, 2)), Some(stone));
+    assert_eq!(block(&mut app, at(0, 1, 3)), Some(dirt));
+    run_ok(&mut app, player, "undo");
+    assert_eq!(block(&mut app, at(0, 1, 2)), Some(BlockState::AIR));
+
+    // Schematic files hand the clipboard back as it was saved
+    assert_eq!(run_ok(&mut app, player, "schematic save corner_piece"), "Saved the clipboard as 'corner_piece'");
+    run_ok(&mut app, player, "flip x");
+    assert_eq!(run_ok(&mut app, player, "schematic load corner_piece"), "Loaded 'corner_piece' into the clipboard (8 block(s))");
+    assert_eq!(run_ok(&mut app, player, "schematic list"), "Schematics: corner_piece");
+    run_ok(&mut app, player, "paste");
+    assert_eq!(block(&mut app, at(0, 1, 3)), Some(dirt));
+    assert_eq!(run(&mut app, player, "schematic load nothing").message, "No schematic named 'nothing'");
+    assert!(run(&mut app, player, "schematic save ../outside").message.starts_with("Invalid schematic name"));
+
+    // Cutting empties the selection and takes it along
+    assert_eq!(run_ok(&mut app, player, "cut"), "Cut 8 block(s)");
+    assert_eq!(block(&mut app, at(2, 1, 0)), Some(BlockState::AIR));
+    run_ok(&mut app, player, "paste");
+    assert_eq!(block(&mut app, at(2, 1, 0)), Some(stone));
+    run_ok(&mut app, player, "deselect");
+    assert_eq!(run(&mut app, player, "copy").message, "Set both corners of your selection first");
+
+    std::fs::remove_dir_all(directory).ok();
+}
@@ -0,0 +1,206 @@
+use bevy::app::AppExit;
+use bevy::prelude::*;
+use std::path::Path;
+use std::time::{Duration, Instant};
+use voxelcraft::blocks::{BlockRegistry, BlockState};
+use voxelcraft::commands::{ArgumentType, CommandDispatcher, CommandFeedback, CommandNode, RunCommand};
+use voxelcraft::components::{GameMode, Mob, MobData, Player, PLAYER_HALF_WIDTH, PLAYER_HEIGHT, PLAYER_STEP_HEIGHT};
+use voxelcraft::crafting::Furnace;
+use voxelcraft::entities::EntityRegistry;
+use voxelcraft::mods::{GameMod, ModRegistry};
+use voxelcraft::physics::PhysicsBody;
+use voxelcraft::resources::{HotbarItem, ItemStack};
+use voxelcraft::systems::SpawnMob;
+use voxelcraft::world::{world_to_chunk_local, BlockEntity, Chunk, ChunkCoord, ChunkManager, ChunkModified, SpawnPoint, WorldDirectory, WorldSettings};
+use voxelcraft::SimulationPlugin;
+
+/// The simulation with no window, GPU or renderer, loading a small area around players
+fn headless_app() -> App {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .insert_resource(WorldSettings { view_distance: 2, view_distance_vertical: 1, ..default() })
+        .add_plugins(SimulationPlugin);
+    // Startup: mods register their content and the spawn point is found
+    app.update();
+    app
+}
+
+/// `headless_app` keeping its world in `directory`, the way the dedicated server does
+fn headless_app_saving_to(directory: &Path) -> App {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .insert_resource(WorldSettings { view_distance: 2, view_distance_vertical: 1, ..default() })
+        .insert_resource(WorldDirectory(directory.to_path_buf()))
+        .add_plugins(SimulationPlugin);
+    app.update();
+    app
+}
+
+/// Run the app until the chunk holding `pos` has loaded, returning the chunk's entity
+fn wait_for_chunk(app: &mut App, pos: IVec3) -> Entity {
+    let coord = ChunkCoord::from_world_pos(pos.as_vec3());
+    let deadline = Instant::now() + Duration::from_secs(60);
+    loop {
+        app.update();
+        if let Some(&entity) = app.world().resource::<ChunkManager>().loaded_chunks.get(&coord) {
+            return entity;
+        }
+        assert!(Instant::now() < deadline, "chunk {:?} never loaded", coord);
+        std::thread::sleep(Duration::from_millis(5));
+    }
+}
+
+#[test]
+fn a_player_lands_on_generated_terrain_without_a_window() {
+    let mut app = headless_app();
+    let spawn = *app.world().resource::<SpawnPoint>();
+    let start = spawn.feet_position() + Vec3::Y * 3.0;
+    app.world_mut().spawn((
+        Transform::from_translation(start),
+        Player::default(),
+        GameMode::default(),
+        PhysicsBody::new(start, PLAYER_HALF_WIDTH, PLAYER_HEIGHT).with_step_height(PLAYER_STEP_HEIGHT),
+    ));
+
+    let deadline = Instant::now() + Duration::from_secs(60);
+    let body = loop {
+        app.update();
+        let body = app.world_mut().query_filtered::<&PhysicsBody, With<Player>>().single(app.world()).clone();
+        if body.on_ground {
+            break body;
+        }
+        assert!(Instant::now() < deadline, "player never landed, still at {:?}", body.position);
+        std::thread::sleep(Duration::from_millis(5));
+    };
+
+    // The player fell onto the spawn block's floor, in a chunk the server generated around them
+    assert!((body.position.y - spawn.feet_position().y).abs() < 0.01, "landed at {:?}", body.position);
+    let chunk_manager = app.world().resource::<ChunkManager>();
+    assert!(chunk_manager.loaded_chunks.contains_key(&ChunkCoord::from_world_pos(body.position)));
+}
+
+/// A mod that only adds a command
+struct EchoMod;
+
+impl GameMod for EchoMod {
+    fn id(&self) -> &str {
+        "echo"
+    }
+
+    fn name(&self) -> &str {
+        "Echo"
+    }
+
+    fn version(&self) -> &str {
+        "1.0.0"
+    }
+
+    fn register_blocks(&self, _registry: &mut BlockRegistry) {}
+
+    fn register_commands(&self, dispatcher: &mut CommandDispatcher) {
+        let echo = CommandNode::literal("echo")
+            .describe("Say something back")
+            .then(CommandNode::argument("times", ArgumentType::integer_between(1, 3))
+                .then(CommandNode::argument("text", ArgumentType::GreedyString).executes(|context| {
+                    Ok(vec![context.string("text"); context.integer("times") as usize].join("\n"))
+                })));
+        dispatcher.register(echo).unwrap();
+    }
+}
+
+#[test]
+fn mods_add_commands_players_can_run() {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .insert_resource(WorldSettings { view_distance: 2, view_distance_vertical: 1, ..default() })
+        .add_plugins(SimulationPlugin);
+    app.world_mut().resource_mut::<ModRegistry>().register_mod(Box::new(EchoMod));
+    app.update();
+    let player = app.world_mut().spawn((Transform::default(), Player::default())).id();
+
+    let mut run = |command: &str| {
+        app.world_mut().send_event(RunCommand { player, command: command.to_string() });
+        app.update();
+        // Events stay around for a couple of updates, so the newest is this command's
+        let events = app.world().resource::<Events<CommandFeedback>>();
+        events.iter_current_update_events().last().cloned().expect("the command said nothing back")
+    };
+
+    let feedback = run("echo 2 hello there");
+    assert_eq!((feedback.player, feedback.message.as_str(), feedback.error), (player, "hello there\nhello there", false));
+
+    let feedback = run("echo 4 hello");
+    assert!(feedback.error);
+    assert!(feedback.message.starts_with("The number must not be more than 3"), "{}", feedback.message);
+
+    // The built-in help lists mod commands next to the game's own
+    let feedback = run("help");
+    assert!(feedback.message.lines().any(|line| line == "/echo - Say something back"), "{}", feedback.message);
+}
+
+#[test]
+fn edits_mobs_and_the_spawn_point_survive_a_restart() {
+    let directory = std::env::temp_dir().join(format!("voxelcraft-restart-test-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&directory);
+
+    let mut app = headless_app_saving_to(&directory);
+    let spawn = *app.world().resource::<SpawnPoint>();
+    app.world_mut().spawn((Transform::from_translation(spawn.feet_position()), Player::default()));
+    let planks_pos = spawn.block + IVec3::new(0, 2, 0);
+    let furnace_pos = spawn.block + IVec3::new(1, 2, 0);
+    wait_for_chunk(&mut app, planks_pos);
+    wait_for_chunk(&mut app, furnace_pos);
+    // Let the fresh chunks finish post-processing before editing them
+    for _ in 0..5 {
+        app.update();
+    }
+
+    let registry = app.world().resource::<BlockRegistry>();
+    let planks = BlockState::new(registry.get_id("core:planks").unwrap());
+    let furnace_block = BlockState::new(registry.get_id("core:furnace").unwrap());
+    let pig = app.world().resource::<EntityRegistry>().get_id("core:pig").unwrap();
+    // Finished smelting, so the furnace has nothing left to tick before the save
+    let furnace = Furnace { output: Some(ItemStack::new(HotbarItem::Block(planks.id), 3)), ..default() };
+    for (pos, state) in [(planks_pos, planks), (furnace_pos, furnace_block)] {
+        let (_, local) = world_to_chunk_local(pos);
+        let entity = app.world().resource::<ChunkManager>().loaded_chunks[&ChunkCoord::from_world_pos(pos.as_vec3())];
+        let mut chunk_entity = app.world_mut().entity_mut(entity);
+        let mut chunk = chunk_entity.get_mut::<Chunk>().unwrap();
+        chunk.set_state(local.x as usize, local.y as usize, local.z as usize, state);
+        if state == furnace_block {
+            let index = local.x as usize + local.z as usize * 16 + local.y as usize * 256;
+            chunk.block_entities.insert(index, BlockEntity::Furnace(furnace.clone()));
+        }
+        chunk_entity.insert(ChunkModified);
+    }
+    let mob = MobData { entity_type: pig, position: spawn.feet_position() + Vec3::new(2.0, 0.0, 2.0), yaw: 1.0, health: 3 };
+    app.world_mut().send_event(SpawnMob(mob));
+    app.update();
+    let moved_spawn = SpawnPoint { block: spawn.block + IVec3::new(4, 0, 4) };
+    app.world_mut().insert_resource(moved_spawn);
+
+    app.world_mut().send_event(AppExit::Success);
+    app.update();
+    drop(app);
+
+    // A new run reads the world back instead of starting over
+    let mut app = headless_app_saving_to(&directory);
+    assert_eq!(*app.world().resource::<SpawnPoint>(), moved_spawn);
+    app.world_mut().spawn((Transform::from_translation(spawn.feet_position()), Player::default()));
+    let block_at = |app: &mut App, pos: IVec3| {
+        let entity = wait_for_chunk(app, pos);
+        let (_, local) = world_to_chunk_local(pos);
+        let chunk = app.world().get::<Chunk>(entity).unwrap();
+        let index = local.x as usize + local.z as usize * 16 + local.y as usize * 256;
+        (chunk.get_state(local.x as usize, local.y as usize, local.z as usize), chunk.block_entities.get(&index).cloned())
+    };
+    assert_eq!(block_at(&mut app, planks_pos), (planks, None));
+    assert_eq!(block_at(&mut app, furnace_pos), (furnace_block, Some(BlockEntity::Furnace(furnace))));
+    app.update();
+    let hurt_pigs = app.world_mut().query::<&Mob>().iter(app.world())
+        .filter(|mob| mob.entity_type == pig && mob.health == 3)
+        .count();
+    assert_eq!(hurt_pigs, 1, "the saved pig came back once");
+
+    std::fs::remove_dir_all(&directory).unwrap();
+}
@@ -0,0 +1,354 @@
+use bevy::prelude::*;
+use std::net::{Ipv4Addr, SocketAddr};
+use std::time::{Duration, Instant};
+use voxelcraft::blocks::{BlockId, BlockRegistry, BlockState};
+use voxelcraft::items::break_seconds;
+use voxelcraft::components::{Player, PLAYER_HALF_WIDTH, PLAYER_HEIGHT, PLAYER_STEP_HEIGHT};
+use voxelcraft::mods::ModPlugin;
+use voxelcraft::net::protocol::{ClientPacket, ServerPacket};
+use voxelcraft::net::{
+    ClientNetPlugin, Connection, NetClient, NetServer, Prediction, RemotePlayer, Replicated, ReplicatedEntities,
+    ServerNetPlugin,
+};
+use voxelcraft::physics::PhysicsBody;
+use voxelcraft::resources::{ChatLine, ChatLog, PlayerInventory, PlayerStats};
+use voxelcraft::systems::{BlockAction, BlockChanged, BlockEditRequest, SendChat};
+use voxelcraft::world::{world_to_chunk_local, Chunk, ChunkManager, Selection, SpawnPoint, WorldSettings};
+use voxelcraft::SimulationPlugin;
+
+/// A headless server on a free loopback port, with a small view distance
+fn server_app() -> (App, SocketAddr) {
+    let server = NetServer::bind(0).expect("bind server");
+    let addr = SocketAddr::from((Ipv4Addr::LOCALHOST, server.local_addr().unwrap().port()));
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .insert_resource(WorldSettings { view_distance: 2, view_distance_vertical: 1, ..default() })
+        .add_plugins(SimulationPlugin)
+        .insert_resource(server)
+        .add_plugins(ServerNetPlugin);
+    app.update();
+    (app, addr)
+}
+
+/// A client with no world of its own, logging in as `name` with `latency` added each way,
+/// and its local player
+fn client_app(addr: SocketAddr, name: &str, latency: Duration) -> (App, Entity) {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .add_plugins(ModPlugin)
+        .add_plugins(ClientNetPlugin)
+        .insert_resource(NetClient::connect(addr, name).expect("connect").with_latency(latency));
+    let body = PhysicsBody::new(Vec3::ZERO, PLAYER_HALF_WIDTH, PLAYER_HEIGHT).with_step_height(PLAYER_STEP_HEIGHT);
+    let player = app.world_mut().spawn((Transform::default(), Player::default(), Selection::default(), body)).id();
+    (app, player)
+}
+
+/// Update every app until `done` holds, failing after a generous deadline
+fn pump(apps: &mut [&mut App], what: &str, mut done: impl FnMut(&mut [&mut App]) -> bool) {
+    let deadline = Instant::now() + Duration::from_secs(60);
+    while !done(apps) {
+        assert!(Instant::now() < deadline, "timed out waiting for {}", what);
+        for app in apps.iter_mut() {
+            app.update();
+        }
+        std::thread::sleep(Duration::from_millis(2));
+    }
+}
+
+fn chunk_at(app: &mut App, pos: IVec3) -> Option<Chunk> {
+    let (coord, _) = world_to_chunk_local(pos);
+    let entity = *app.world().resource::<ChunkManager>().loaded_chunks.get(&coord)?;
+    app.world().get::<Chunk>(entity).cloned()
+}
+
+fn block_at(app: &mut App, pos: IVec3) -> Option<u16> {
+    let (_, local) = world_to_chunk_local(pos);
+    chunk_at(app, pos).map(|chunk| chunk.get_state(local.x as usize, local.y as usize, local.z as usize).id.0)
+}
+
+fn player_id(app: &App) -> Option<u64> {
+    app.world().resource::<NetClient>().player_id()
+}
+
+/// Where the client predicts its player's feet are
+fn predicted_position(app: &App, player: Entity) -> Vec3 {
+    app.world().get::<PhysicsBody>(player).unwrap().position
+}
+
+/// Where the server has the player called `name`'s feet
+fn server_position(app: &mut App, name: &str) -> Option<Vec3> {
+    let mut players = app.world_mut().query::<(&RemotePlayer, &PhysicsBody)>();
+    players.iter(app.world()).find(|(player, _)| player.name == name).map(|(_, body)| body.position)
+}
+
+/// The server's `PlayerStats` for the player called `name`
+fn server_stats(app: &mut App, name: &str) -> Option<PlayerStats> {
+    let mut players = app.world_mut().query::<(&RemotePlayer, &PlayerStats)>();
+    players.iter(app.world()).find(|(player, _)| player.name == name).map(|(_, stats)| stats.clone())
+}
+
+/// Change the server's body and stats of the player called `name`
+fn with_server_player(app: &mut App, name: &str, change: impl FnOnce(&mut PhysicsBody, &mut PlayerStats)) {
+    let mut players = app.world_mut().query::<(&RemotePlayer, &mut PhysicsBody, &mut PlayerStats)>();
+    let (_, mut body, mut stats) = players.iter_mut(app.world_mut()).find(|(player, _, _)| player.name == name).unwrap();
+    change(&mut body, &mut stats);
+}
+
+fn set_block(app: &mut App, pos: IVec3, state: BlockState) {
+    let (coord, local) = world_to_chunk_local(pos);
+    let entity = app.world().resource::<ChunkManager>().loaded_chunks[&coord];
+    let mut chunk = app.world_mut().get_mut::<Chunk>(entity).unwrap();
+    chunk.set_state(local.x as usize, local.y as usize, local.z as usize, state);
+}
+
+/// Flatten the terrain around the spawn point into a dirt floor with room to walk above it,
+/// so players can be walked about without climbing, and wait for the clients to see it
+/// `apps` is the server then its clients
+fn level_spawn(apps: &mut [&mut App]) {
+    let server = apps[0].world();
+    let floor = server.resource::<SpawnPoint>().feet_position().floor().as_ivec3() - IVec3::Y;
+    let dirt = BlockState::new(server.resource::<BlockRegistry>().get_id("core:dirt").unwrap());
+    let area: Vec<(IVec3, BlockState)> = (-4..=4)
+        .flat_map(|x| (-4..=4).flat_map(move |z| (0..=3).map(move |y| floor + IVec3::new(x, y, z))))
+        .map(|pos| (pos, if pos.y == floor.y { dirt } else { BlockState::AIR }))
+        .collect();
+    pump(apps, "the chunks around the spawn point", |apps| {
+        apps.iter_mut().all(|app| area.iter().all(|&(pos, _)| block_at(app, pos).is_some()))
+    });
+    let server = &mut apps[0];
+    for &(pos, state) in &area {
+        set_block(server, pos, state);
+        server.world_mut().send_event(BlockChanged(pos));
+    }
+    pump(apps, "the level spawn area", |apps| {
+        apps.iter_mut().all(|app| area.iter().all(|&(pos, state)| block_at(app, pos) == Some(state.id.0)))
+    });
+}
+
+fn set_move_input(app: &mut App, player: Entity, move_input: Vec3) {
+    app.world_mut().get_mut::<Player>(player).unwrap().move_input = move_input;
+}
+
+fn replicated_position(app: &mut App, id: u64) -> Option<Vec3> {
+    let entity = *app.world().resource::<ReplicatedEntities>().entities.get(&id)?;
+    let world = app.world();
+    world.get::<Replicated>(entity)?;
+    world.get::<Transform>(entity).map(|transform| transform.translation)
+}
+
+#[test]
+fn players_share_a_world_over_loopback() {
+    let (mut server, addr) = server_app();
+    let spawn = server.world().resource::<SpawnPoint>().feet_position();
+    let floor = spawn.floor().as_ivec3() - IVec3::Y;
+    let (mut alice, alice_player) = client_app(addr, "alice", Duration::ZERO);
+    let (mut bob, _) = client_app(addr, "bob", Duration::ZERO);
+
+    // Both log in at the spawn point and are sent the chunk under it, as the server has it
+    pump(&mut [&mut server, &mut alice, &mut bob], "logins and chunks", |apps| {
+        let [_, alice, bob] = apps else { unreachable!() };
+        player_id(alice).is_some() && player_id(bob).is_some()
+            && block_at(alice, floor).is_some() && block_at(bob, floor).is_some()
+    });
+    let served = chunk_at(&mut server, floor).unwrap();
+    let received = chunk_at(&mut alice, floor).unwrap();
+    assert_eq!(received.blocks, served.blocks);
+    assert_eq!(received.light_levels, served.light_levels);
+    assert_ne!(block_at(&mut bob, floor), Some(0));
+    level_spawn(&mut [&mut server, &mut alice, &mut bob]);
+
+    // Alice walks; the server moves her by the same commands and bob sees where she ends up
+    let alice_id = player_id(&alice).unwrap();
+    set_move_input(&mut alice, alice_player, Vec3::X * 4.0);
+    pump(&mut [&mut server, &mut alice, &mut bob], "alice's walk", |apps| {
+        predicted_position(apps[1], alice_player).x > spawn.x + 0.9
+    });
+    set_move_input(&mut alice, alice_player, Vec3::ZERO);
+    pump(&mut [&mut server, &mut alice, &mut bob], "alice's position to settle", |apps| {
+        let [server, alice, bob] = apps else { unreachable!() };
+        let predicted = predicted_position(alice, alice_player);
+        server_position(server, "alice").is_some_and(|position| position.distance(predicted) < 0.001)
+            && replicated_position(bob, alice_id).is_some_and(|position| position.distance(predicted) < 0.01)
+    });
+
+    // Alice mines the block she stands on; the server ignores a break before she's had time
+    // to, applies the one after and bob sees it go
+    let floor = predicted_position(&alice, alice_player).floor().as_ivec3() - IVec3::Y;
+    let floor_id = BlockId(block_at(&mut server, floor).unwrap());
+    let floor_type = server.world().resource::<BlockRegistry>().get_block(floor_id).unwrap();
+    let mined = Instant::now() + Duration::from_secs_f32(break_seconds(floor_type, None).unwrap());
+    alice.world_mut().send_event(BlockEditRequest { player: alice_player, action: BlockAction::StartBreak { pos: floor } });
+    alice.world_mut().send_event(BlockEditRequest { player: alice_player, action: BlockAction::Break { pos: floor } });
+    pump(&mut [&mut server, &mut alice, &mut bob], "the time it takes to mine", |_| Instant::now() > mined);
+    assert_ne!(block_at(&mut server, floor), Some(0), "broke before mining long enough");
+    alice.world_mut().send_event(BlockEditRequest { player: alice_player, action: BlockAction::Break { pos: floor } });
+    pump(&mut [&mut server, &mut alice, &mut bob], "the block change", |apps| {
+        let [server, _, bob] = apps else { unreachable!() };
+        block_at(server, floor) == Some(0) && block_at(bob, floor) == Some(0)
+    });
+
+    // She drops into the hole and picks up what it dropped, which syncs to her hotbar
+    pump(&mut [&mut server, &mut alice, &mut bob], "the picked up drop", |apps| {
+        apps[1].world().resource::<PlayerInventory>().hotbar.iter().any(Option::is_some)
+    });
+    assert!(predicted_position(&alice, alice_player).y < floor.y as f32 + 0.5);
+
+    // Chat reaches everyone
+    bob.world_mut().send_event(SendChat("hello alice".to_string()));
+    let line = ChatLine { sender: Some("bob".to_string()), message: "hello alice".to_string() };
+    pump(&mut [&mut server, &mut alice, &mut bob], "chat", |apps| {
+        apps[1].world().resource::<ChatLog>().lines.contains(&line)
+    });
+
+    // Her wand selection on the server shows up on her client, for the outline
+    let corners = Selection { first: Some(floor), second: Some(floor + IVec3::new(3, 1, -2)) };
+    let mut remote = server.world_mut().query::<(&RemotePlayer, &mut Selection)>();
+    let (_, mut selection) = remote.iter_mut(server.world_mut()).find(|(remote, _)| remote.name == "alice").unwrap();
+    *selection = corners;
+    pump(&mut [&mut server, &mut alice, &mut bob], "alice's selection", |apps| {
+        apps[1].world().get::<Selection>(alice_player) == Some(&corners)
+    });
+
+    // When alice leaves, bob is told and her player goes away
+    drop(alice);
+    pump(&mut [&mut server, &mut bob], "alice leaving", |apps| {
+        let bob = &mut apps[1];
+        replicated_position(bob, alice_id).is_none()
+            && bob.world().resource::<ChatLog>().lines.iter().any(|line| line.message == "alice left the game")
+    });
+    assert_eq!(server.world().resource::<NetServer>().player_names().collect::<Vec<_>>(), vec!["bob"]);
+}
+
+#[test]
+fn predicted_movement_converges_with_the_server_under_latency() {
+    let (mut server, addr) = server_app();
+    let (mut alice, alice_player) = client_app(addr, "alice", Duration::from_millis(100));
+    pump(&mut [&mut server, &mut alice], "login", |apps| player_id(apps[1]).is_some());
+    level_spawn(&mut [&mut server, &mut alice]);
+    pump(&mut [&mut server, &mut alice], "landing", |apps| {
+        let [server, alice] = apps else { unreachable!() };
+        let predicted = predicted_position(alice, alice_player);
+        player_id(alice).is_some()
+            && alice.world().get::<PhysicsBody>(alice_player).unwrap().on_ground
+            && server_position(server, "alice").is_some_and(|position| position.distance(predicted) < 0.001)
+    });
+    let start = predicted_position(&alice, alice_player);
+    let heading = Vec3::X;
+
+    // Walking moves the local player at once, well before the server hears about it,
+    // and replaying the unacknowledged commands agrees with every state the server sends back
+    set_move_input(&mut alice, alice_player, heading * 4.0);
+    pump(&mut [&mut server, &mut alice], "the first predicted step", |apps| {
+        (predicted_position(apps[1], alice_player) - start).dot(heading) > 0.0
+    });
+    assert_eq!(server_position(&mut server, "alice"), Some(start));
+    let mut worst = 0.0f32;
+    pump(&mut [&mut server, &mut alice], "the walk", |apps| {
+        worst = worst.max(apps[1].world().resource::<Prediction>().last_correction);
+        (predicted_position(apps[1], alice_player) - start).dot(heading) > 1.0
+    });
+    set_move_input(&mut alice, alice_player, Vec3::ZERO);
+    pump(&mut [&mut server, &mut alice], "the walk to settle", |apps| {
+        let [server, alice] = apps else { unreachable!() };
+        worst = worst.max(alice.world().resource::<Prediction>().last_correction);
+        let predicted = predicted_position(alice, alice_player);
+        server_position(server, "alice").is_some_and(|position| position.distance(predicted) < 0.001)
+    });
+    assert!(worst < 0.01, "predictions were corrected by up to {}", worst);
+
+    // A wall goes up on the server right where the client has already walked: the server
+    // stops the player at it, and the client is pulled back when that state arrives
+    let feet = predicted_position(&alice, alice_player).floor().as_ivec3();
+    let mut wall_x = None;
+    set_move_input(&mut alice, alice_player, heading * 4.0);
+    pump(&mut [&mut server, &mut alice], "the client to run ahead of the server", |apps| {
+        let client_edge = predicted_position(apps[1], alice_player).x + PLAYER_HALF_WIDTH;
+        let server_edge = server_position(apps[0], "alice").unwrap().x + PLAYER_HALF_WIDTH;
+        let x = (client_edge - 0.1).floor();
+        if x >= server_edge {
+            wall_x = Some(x as i32);
+        }
+        wall_x.is_some()
+    });
+    let stone = BlockState::new(server.world().resource::<BlockRegistry>().get_id("core:stone").unwrap());
+    let wall = IVec3::new(wall_x.unwrap(), feet.y, feet.z);
+    for pos in [wall, wall + IVec3::Y] {
+        set_block(&mut server, pos, stone);
+        server.world_mut().send_event(BlockChanged(pos));
+    }
+    let mut largest = 0.0f32;
+    pump(&mut [&mut server, &mut alice], "the correction", |apps| {
+        largest = largest.max(apps[1].world().resource::<Prediction>().last_correction);
+        largest > 0.05
+    });
+    set_move_input(&mut alice, alice_player, Vec3::ZERO);
+    pump(&mut [&mut server, &mut alice], "the correction to settle", |apps| {
+        let [server, alice] = apps else { unreachable!() };
+        let predicted = predicted_position(alice, alice_player);
+        server_position(server, "alice").is_some_and(|position| position.distance(predicted) < 0.001)
+    });
+    let stopped = predicted_position(&alice, alice_player);
+    let wall_center = wall.as_vec3() + Vec3::new(0.5, 0.0, 0.5);
+    let gap = (wall_center - stopped).dot(heading) - 0.5 - PLAYER_HALF_WIDTH;
+    assert!(gap > -0.001, "walked {} into the wall", -gap);
+}
+
+#[test]
+fn players_are_hurt_and_respawned_on_their_own() {
+    let (mut server, addr) = server_app();
+    let spawn = server.world().resource::<SpawnPoint>().feet_position();
+    let (mut alice, _) = client_app(addr, "alice", Duration::ZERO);
+    let (mut bob, _) = client_app(addr, "bob", Duration::ZERO);
+    pump(&mut [&mut server, &mut alice, &mut bob], "logins", |apps| {
+        player_id(apps[1]).is_some() && player_id(apps[2]).is_some()
+    });
+    level_spawn(&mut [&mut server, &mut alice, &mut bob]);
+
+    // Alice falls ten blocks on the server: she's hurt and told so, bob isn't
+    with_server_player(&mut server, "alice", |body, _| body.teleport(spawn + Vec3::Y * 10.0));
+    pump(&mut [&mut server, &mut alice, &mut bob], "alice's fall damage", |apps| {
+        let [server, alice, _] = apps else { unreachable!() };
+        server_stats(server, "alice").is_some_and(|stats| stats.health < 20)
+            && alice.world().resource::<PlayerStats>().health < 20
+    });
+    assert_eq!(server_stats(&mut server, "bob").unwrap().health, 20);
+    assert_eq!(bob.world().resource::<PlayerStats>().health, 20);
+    assert_eq!(server.world().resource::<PlayerStats>().health, 20, "the server has no local player to hurt");
+
+    // Out of health away from the spawn point, she comes back there healed
+    let away = spawn + Vec3::X * 3.0;
+    with_server_player(&mut server, "alice", |body, _| body.teleport(away));
+    pump(&mut [&mut server, &mut alice, &mut bob], "alice to stand away from spawn", |apps| {
+        server_position(apps[0], "alice").is_some_and(|position| position.distance(away) < 0.001)
+    });
+    with_server_player(&mut server, "alice", |_, stats| stats.health = 0);
+    pump(&mut [&mut server, &mut alice, &mut bob], "alice's respawn", |apps| {
+        let [server, alice, _] = apps else { unreachable!() };
+        server_position(server, "alice").is_some_and(|position| position.distance(spawn) < 0.001)
+            && server_stats(server, "alice").is_some_and(|stats| (stats.health, stats.hunger) == (20, 20))
+            && alice.world().resource::<PlayerStats>().health == 20
+    });
+}
+
+#[test]
+fn logins_with_another_protocol_version_or_a_taken_name_are_refused() {
+    let (mut server, addr) = server_app();
+    let (mut alice, _) = client_app(addr, "alice", Duration::ZERO);
+    pump(&mut [&mut server, &mut alice], "login", |apps| player_id(apps[1]).is_some());
+
+    let mut outdated = Connection::connect(addr).unwrap();
+    outdated.send(&ClientPacket::Login { protocol_version: 0, name: "carol".to_string() });
+    outdated.flush().unwrap();
+    let (mut duplicate, _) = client_app(addr, "alice", Duration::ZERO);
+
+    let mut refusal = None;
+    pump(&mut [&mut server, &mut duplicate], "refusals", |apps| {
+        if let Ok(Some(ServerPacket::Disconnect { reason })) = outdated.receive::<ServerPacket>() {
+            refusal = Some(reason);
+        }
+        refusal.is_some() && !apps[1].world().resource::<NetClient>().is_connected()
+    });
+    assert!(refusal.unwrap().contains("Outdated client"));
+    assert_eq!(duplicate.world().resource::<NetClient>().disconnect_reason(), Some("alice is already playing"));
+    assert_eq!(server.world().resource::<NetServer>().player_names().collect::<Vec<_>>(), vec!["alice"]);
+}